@@ -0,0 +1,84 @@
+/** Range of the source code: lines from 1, columns counting characters from 0 */
+export interface Location {
+  file: string
+  line: number
+  column: number | null
+  end_line: number | null
+  end_column: number | null
+}
+
+/** Replacement of a range of the source code */
+export interface TextEdit {
+  location: Location
+  replacement: string
+}
+
+/** Fix suggested by a rule */
+export interface Fix {
+  description: string
+  edits: TextEdit[]
+  applicability: 'machine-applicable' | 'advisory'
+}
+
+/** Rule that reported a finding */
+export interface RuleInfo {
+  id: string
+  title: string
+  references: string[]
+  tags: string[]
+  cwe: number[]
+  category: string | null
+}
+
+/** Finding of the analysis, as in the JSON report */
+export interface Finding {
+  description: string
+  severity: 'high' | 'medium' | 'low' | 'informational'
+  confidence: 'high' | 'medium' | 'low'
+  location: Location
+  code_snippet: string | null
+  recommendations: string[]
+  fixes: Fix[]
+  metadata: Record<string, unknown>
+  /** Rules that reported the finding, several when duplicates were merged */
+  rules: RuleInfo[]
+  fingerprint: string | null
+}
+
+/** Rule selection, applied over the `solana-analyzer.toml` of the project like the CLI flags */
+export interface AnalyzeOptions {
+  /** Project the sources belong to, its configuration and crates apply (the current directory by default) */
+  project?: string
+  /** Configuration file layered over the one of the project (`--config`) */
+  config?: string
+  /** Rule pack or profile (`--profile`) */
+  profile?: string
+  /** Rules to run (`--rules`) */
+  rules?: string[]
+  /** Rules to skip (`--ignore-rules`) */
+  ignoreRules?: string[]
+  /** Minimum confidence of the findings (`--min-confidence`) */
+  minConfidence?: 'high' | 'medium' | 'low'
+  /** Analyze the test code too (`--include-tests`) */
+  includeTests?: boolean
+}
+
+/** Rust file held in memory */
+export interface Source {
+  /** Path of the file, relative to the project */
+  path: string
+  /** Code of the file */
+  source: string
+}
+
+/** Analyze a project directory, a Rust file or a glob of Rust files */
+export function analyzePath(path: string, options?: AnalyzeOptions): Promise<Finding[]>
+
+/** Analyze Rust files held in memory as files of the project at `options.project` */
+export function analyzeSources(sources: Source[], options?: AnalyzeOptions): Promise<Finding[]>
+
+/** Analyze Rust code held in memory as the file `filename` (`lib.rs` by default) of the project at `options.project` */
+export function analyzeSource(source: string, filename?: string, options?: AnalyzeOptions): Promise<Finding[]>
+
+/** Version of the analyzer */
+export const version: string
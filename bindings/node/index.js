@@ -0,0 +1,18 @@
+'use strict'
+
+// Addon built by `npm run build` (src/lib.rs)
+const native = require('./solana-analyzer.node')
+
+/**
+ * Analyze Rust code held in memory as the file `filename` of the project at `options.project`.
+ */
+function analyzeSource(source, filename = 'lib.rs', options = undefined) {
+  return native.analyzeSources([{ path: filename, source }], options)
+}
+
+module.exports = {
+  analyzePath: native.analyzePath,
+  analyzeSources: native.analyzeSources,
+  analyzeSource,
+  version: native.version(),
+}
@@ -0,0 +1,142 @@
+//! Node.js bindings of the analyzer: the `solana-analyzer` npm package
+//!
+//! `analyzePath()` analyzes a project directory, a Rust file or a glob the way the CLI does,
+//! and `analyzeSources()` analyzes code held in memory as files of a project, so Anchor test
+//! scripts and dashboards run the analyzer without spawning the CLI. Both return a promise:
+//! the analysis runs on the libuv thread pool, and resolves to the findings of the JSON report.
+
+use std::path::PathBuf;
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use rust_solana_analyzer::analyzer::packs;
+use rust_solana_analyzer::ast::parser::SourceFile;
+use rust_solana_analyzer::{AnalysisOptions, Confidence, ProjectOptions};
+
+/// Rule selection, applied over the `solana-analyzer.toml` of the project like the CLI flags
+#[napi(object)]
+#[derive(Default)]
+pub struct AnalyzeOptions {
+    /// Project the sources belong to, its configuration and crates apply (`analyzeSources()`
+    /// only, the current directory by default)
+    pub project: Option<String>,
+    /// Configuration file layered over the one of the project (`--config`)
+    pub config: Option<String>,
+    /// Rule pack or profile (`--profile`)
+    pub profile: Option<String>,
+    /// Rules to run (`--rules`)
+    pub rules: Option<Vec<String>>,
+    /// Rules to skip (`--ignore-rules`)
+    pub ignore_rules: Option<Vec<String>>,
+    /// `high`, `medium` or `low` (`--min-confidence`)
+    pub min_confidence: Option<String>,
+    /// Analyze the test code too (`--include-tests`)
+    pub include_tests: Option<bool>,
+}
+
+impl AnalyzeOptions {
+    fn project_options(self) -> Result<ProjectOptions<'static>> {
+        if let Some(profile) = &self.profile {
+            packs::resolve_profile(profile).map_err(|e| Error::from_reason(format!("Invalid profile: {e}")))?;
+        }
+        let min_confidence = self
+            .min_confidence
+            .as_deref()
+            .map(str::parse::<Confidence>)
+            .transpose()
+            .map_err(|e| Error::from_reason(format!("Invalid minConfidence: {e}")))?;
+
+        let options = ProjectOptions { config_file: self.config.map(PathBuf::from), ..Default::default() };
+        Ok(options.with_overrides(move |options: &mut AnalysisOptions| {
+            // Embedding scripts do not write the cache of the CLI in the analyzed project
+            options.cache_dir = None;
+            if let Some(profile) = self.profile {
+                options.profile = Some(profile);
+            }
+            if let Some(rules) = self.rules {
+                options.include_rules = rules;
+            }
+            if let Some(ignore_rules) = self.ignore_rules {
+                options.ignore_rules = ignore_rules;
+            }
+            if let Some(min_confidence) = min_confidence {
+                options.min_confidence = Some(min_confidence);
+            }
+            options.include_tests |= self.include_tests.unwrap_or(false);
+        }))
+    }
+}
+
+/// Rust file held in memory
+#[napi(object)]
+pub struct Source {
+    /// Path of the file, relative to the project
+    pub path: String,
+    /// Code of the file
+    pub source: String,
+}
+
+enum Input {
+    Path(PathBuf),
+    Sources(Vec<Source>),
+}
+
+/// Analysis run on the libuv thread pool
+pub struct Analysis {
+    input: Option<Input>,
+    options: Option<AnalyzeOptions>,
+}
+
+impl Task for Analysis {
+    type Output = Vec<serde_json::Value>;
+    type JsValue = Vec<serde_json::Value>;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let mut options = self.options.take().unwrap_or_default();
+        let project = PathBuf::from(options.project.take().unwrap_or_else(|| ".".to_string()));
+        let options = options.project_options()?;
+        let result = match self.input.take() {
+            Some(Input::Path(path)) => rust_solana_analyzer::analyze_project(&path, options),
+            Some(Input::Sources(sources)) => {
+                // Parsed on this thread, the AST cannot leave it
+                let files = sources
+                    .into_iter()
+                    .map(|file| SourceFile::from_source(project.join(file.path), file.source))
+                    .collect();
+                rust_solana_analyzer::analyze_sources(&project, files, options)
+            }
+            None => return Err(Error::from_reason("The analysis already ran")),
+        }
+        .map_err(|e| Error::from_reason(e.to_string()))?;
+        result
+            .result
+            .findings
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<serde_json::Result<_>>()
+            .map_err(|e| Error::from_reason(e.to_string()))
+    }
+
+    fn resolve(&mut self, _env: Env, findings: Self::Output) -> Result<Self::JsValue> {
+        Ok(findings)
+    }
+}
+
+/// Analyze a project directory, a Rust file or a glob of Rust files
+#[napi(ts_return_type = "Promise<Finding[]>")]
+pub fn analyze_path(path: String, options: Option<AnalyzeOptions>) -> AsyncTask<Analysis> {
+    AsyncTask::new(Analysis { input: Some(Input::Path(PathBuf::from(path))), options })
+}
+
+/// Analyze Rust files held in memory as files of the project at `options.project`
+#[napi(ts_return_type = "Promise<Finding[]>")]
+pub fn analyze_sources(sources: Vec<Source>, options: Option<AnalyzeOptions>) -> AsyncTask<Analysis> {
+    AsyncTask::new(Analysis { input: Some(Input::Sources(sources)), options })
+}
+
+/// Version of the analyzer
+#[napi]
+pub fn version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
@@ -0,0 +1,71 @@
+'use strict'
+
+// Tests of the Node.js bindings, run on the built addon (`npm run build && npm test`)
+
+const assert = require('node:assert/strict')
+const fs = require('node:fs')
+const os = require('node:os')
+const path = require('node:path')
+const { test } = require('node:test')
+
+const analyzer = require('..')
+
+const SOURCE = `use anchor_lang::prelude::*;
+
+pub fn share(amount: u64, shares: u64) -> Result<u64> {
+    Ok(amount / shares)
+}
+`
+
+// Empty project, the sources analyzed in memory are its only files
+const project = fs.mkdtempSync(path.join(os.tmpdir(), 'solana-analyzer-'))
+test.after(() => fs.rmSync(project, { recursive: true, force: true }))
+
+test('analyzeSource resolves to the findings of the JSON report', async () => {
+  const findings = await analyzer.analyzeSource(SOURCE, 'src/lib.rs', { project, rules: ['solana-division-by-zero'] })
+
+  assert.equal(findings.length, 1)
+  const [finding] = findings
+  assert.equal(finding.rules[0].id, 'solana-division-by-zero')
+  assert.deepEqual(finding.rules[0].cwe, [369])
+  assert.equal(finding.severity, 'medium')
+  assert.equal(finding.confidence, 'high')
+  assert.match(finding.location.file, /src\/lib\.rs$/)
+  assert.equal(finding.location.line, 3)
+  assert.equal(finding.fixes[0].applicability, 'machine-applicable')
+  assert.match(finding.fixes[0].edits[0].replacement, /checked_div/)
+})
+
+test('analyzeSources analyzes several files concurrently', async () => {
+  const files = ['a', 'b'].map((name) => ({ path: `src/${name}.rs`, source: SOURCE }))
+  const [findings, none] = await Promise.all([
+    analyzer.analyzeSources(files, { project, rules: ['solana-division-by-zero'] }),
+    analyzer.analyzeSources(files, { project, rules: ['solana-division-by-zero'], ignoreRules: ['solana-division-by-zero'] }),
+  ])
+
+  assert.deepEqual(findings.map((finding) => path.basename(finding.location.file)).sort(), ['a.rs', 'b.rs'])
+  assert.deepEqual(none, [])
+})
+
+test('analyzePath analyzes a project without writing a cache in it', async () => {
+  const root = fs.mkdtempSync(path.join(os.tmpdir(), 'solana-analyzer-'))
+  try {
+    fs.mkdirSync(path.join(root, 'src'))
+    fs.writeFileSync(path.join(root, 'src', 'lib.rs'), SOURCE)
+
+    const findings = await analyzer.analyzePath(root, { rules: ['solana-division-by-zero'] })
+    assert.deepEqual(findings.map((finding) => [path.basename(finding.location.file), finding.location.line]), [['lib.rs', 3]])
+    assert.equal(fs.existsSync(path.join(root, '.analyzer-cache')), false)
+  } finally {
+    fs.rmSync(root, { recursive: true, force: true })
+  }
+})
+
+test('invalid options and paths reject the promise', async () => {
+  await assert.rejects(analyzer.analyzeSource(SOURCE, 'lib.rs', { project, minConfidence: 'certain' }), /Invalid minConfidence/)
+  await assert.rejects(analyzer.analyzePath('/nonexistent/solana-program'))
+})
+
+test('version', () => {
+  assert.match(analyzer.version, /^\d+\.\d+\.\d+/)
+})
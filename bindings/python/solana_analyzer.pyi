@@ -0,0 +1,79 @@
+"""Static analyzer for Solana/Anchor contracts in Rust."""
+
+from os import PathLike
+from typing import Literal, Optional, Sequence, Union
+
+__version__: str
+
+StrPath = Union[str, PathLike[str]]
+
+class AnalyzerError(Exception):
+    """The analysis could not run (missing path, invalid configuration or option)."""
+
+class TextEdit:
+    """Replacement of a range of the source code (lines from 1, columns counting characters from 0)."""
+
+    file: str
+    line: int
+    column: Optional[int]
+    end_line: Optional[int]
+    end_column: Optional[int]
+    replacement: str
+
+class Fix:
+    """Fix suggested by a rule."""
+
+    description: str
+    applicability: Literal["machine-applicable", "advisory"]
+    edits: list[TextEdit]
+
+class Finding:
+    """Finding of the analysis."""
+
+    rule_id: str
+    rule_ids: list[str]
+    title: str
+    severity: Literal["high", "medium", "low", "informational"]
+    confidence: Literal["high", "medium", "low"]
+    category: Optional[str]
+    cwe: list[int]
+    description: str
+    file: str
+    line: int
+    column: Optional[int]
+    end_line: Optional[int]
+    end_column: Optional[int]
+    code_snippet: Optional[str]
+    recommendations: list[str]
+    references: list[str]
+    fixes: list[Fix]
+    fingerprint: Optional[str]
+
+    def to_json(self) -> str:
+        """Returns the finding as JSON, with the fields of the findings of the JSON report."""
+
+def analyze_path(
+    path: StrPath,
+    *,
+    config: Optional[StrPath] = None,
+    profile: Optional[str] = None,
+    rules: Optional[Sequence[str]] = None,
+    ignore_rules: Optional[Sequence[str]] = None,
+    min_confidence: Optional[Literal["high", "medium", "low"]] = None,
+    include_tests: bool = False,
+) -> list[Finding]:
+    """Analyze a project directory, a Rust file or a glob of Rust files, and return the findings."""
+
+def analyze_source(
+    source: str,
+    filename: str = "lib.rs",
+    *,
+    project: StrPath = ".",
+    config: Optional[StrPath] = None,
+    profile: Optional[str] = None,
+    rules: Optional[Sequence[str]] = None,
+    ignore_rules: Optional[Sequence[str]] = None,
+    min_confidence: Optional[Literal["high", "medium", "low"]] = None,
+    include_tests: bool = False,
+) -> list[Finding]:
+    """Analyze Rust code held in memory as the file `filename` of the project at `project`, and return the findings."""
@@ -0,0 +1,271 @@
+//! Python bindings of the analyzer: the `solana_analyzer` module
+//!
+//! `analyze_path()` analyzes a project directory, a Rust file or a glob the way the CLI does,
+//! and `analyze_source()` analyzes code held in memory as a file of a project. Both return
+//! [`Finding`] objects built from the findings of the library, and release the GIL while the
+//! analysis runs.
+
+use std::path::PathBuf;
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+
+use rust_solana_analyzer::analyzer::packs;
+use rust_solana_analyzer::ast::parser::SourceFile;
+use rust_solana_analyzer::{AnalysisOptions, Confidence, ProjectOptions, analyze_project, analyze_sources};
+
+create_exception!(solana_analyzer, AnalyzerError, PyException, "The analysis could not run");
+
+/// Replacement of a range of the source code (lines from 1, columns counting characters from 0)
+#[pyclass(module = "solana_analyzer", frozen, get_all, skip_from_py_object)]
+#[derive(Clone)]
+struct TextEdit {
+    file: String,
+    line: usize,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    replacement: String,
+}
+
+#[pymethods]
+impl TextEdit {
+    fn __repr__(&self) -> String {
+        format!("TextEdit({}:{}, {:?})", self.file, self.line, self.replacement)
+    }
+}
+
+/// Fix suggested by a rule, `machine-applicable` or `advisory`
+#[pyclass(module = "solana_analyzer", frozen, get_all, skip_from_py_object)]
+#[derive(Clone)]
+struct Fix {
+    description: String,
+    applicability: &'static str,
+    edits: Vec<TextEdit>,
+}
+
+#[pymethods]
+impl Fix {
+    fn __repr__(&self) -> String {
+        format!("Fix({:?}, {})", self.description, self.applicability)
+    }
+}
+
+/// Finding of the analysis
+#[pyclass(module = "solana_analyzer", frozen)]
+struct Finding {
+    /// Rule that reported the finding
+    #[pyo3(get)]
+    rule_id: String,
+    /// Rules that reported the finding, several when duplicates were merged
+    #[pyo3(get)]
+    rule_ids: Vec<String>,
+    #[pyo3(get)]
+    title: String,
+    /// `high`, `medium`, `low` or `informational`
+    #[pyo3(get)]
+    severity: &'static str,
+    /// `high`, `medium` or `low`
+    #[pyo3(get)]
+    confidence: String,
+    #[pyo3(get)]
+    category: Option<&'static str>,
+    #[pyo3(get)]
+    cwe: Vec<u32>,
+    #[pyo3(get)]
+    description: String,
+    #[pyo3(get)]
+    file: String,
+    #[pyo3(get)]
+    line: usize,
+    #[pyo3(get)]
+    column: Option<usize>,
+    #[pyo3(get)]
+    end_line: Option<usize>,
+    #[pyo3(get)]
+    end_column: Option<usize>,
+    #[pyo3(get)]
+    code_snippet: Option<String>,
+    #[pyo3(get)]
+    recommendations: Vec<String>,
+    #[pyo3(get)]
+    references: Vec<String>,
+    #[pyo3(get)]
+    fixes: Vec<Fix>,
+    #[pyo3(get)]
+    fingerprint: Option<String>,
+    /// Finding of the library, serialized as in the JSON report (see `to_json()`)
+    json: String,
+}
+
+#[pymethods]
+impl Finding {
+    /// Returns the finding as JSON, with the fields of the findings of the JSON report
+    fn to_json(&self) -> &str {
+        &self.json
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Finding({}, {}, {}:{})", self.rule_id, self.severity, self.file, self.line)
+    }
+}
+
+impl From<&rust_solana_analyzer::Finding> for Finding {
+    fn from(finding: &rust_solana_analyzer::Finding) -> Self {
+        let rule = finding.rule().cloned().unwrap_or_default();
+        let location = &finding.location;
+        Self {
+            rule_id: rule.id.clone(),
+            rule_ids: finding.rule_ids().into_iter().map(str::to_string).collect(),
+            title: rule.title,
+            severity: finding.severity.as_str(),
+            confidence: finding.confidence.to_string().to_lowercase(),
+            category: rule.category.map(|category| category.as_str()),
+            cwe: rule.cwe,
+            description: finding.description.clone(),
+            file: location.file.clone(),
+            line: location.line,
+            column: location.column,
+            end_line: location.end_line,
+            end_column: location.end_column,
+            code_snippet: finding.code_snippet.clone(),
+            recommendations: finding.recommendations.clone(),
+            references: rule.references,
+            fixes: finding
+                .fixes
+                .iter()
+                .map(|fix| Fix {
+                    description: fix.description.clone(),
+                    applicability: fix.applicability.as_str(),
+                    edits: fix
+                        .edits
+                        .iter()
+                        .map(|edit| TextEdit {
+                            file: edit.location.file.clone(),
+                            line: edit.location.line,
+                            column: edit.location.column,
+                            end_line: edit.location.end_line,
+                            end_column: edit.location.end_column,
+                            replacement: edit.replacement.clone(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            fingerprint: finding.fingerprint.clone(),
+            json: serde_json::to_string(finding).unwrap_or_default(),
+        }
+    }
+}
+
+/// Rule selection shared by `analyze_path()` and `analyze_source()`, applied over the
+/// `solana-analyzer.toml` of the project like the CLI flags
+struct Selection {
+    config: Option<PathBuf>,
+    profile: Option<String>,
+    rules: Option<Vec<String>>,
+    ignore_rules: Option<Vec<String>>,
+    min_confidence: Option<Confidence>,
+    include_tests: bool,
+}
+
+impl Selection {
+    fn new(
+        config: Option<PathBuf>,
+        profile: Option<String>,
+        rules: Option<Vec<String>>,
+        ignore_rules: Option<Vec<String>>,
+        min_confidence: Option<&str>,
+        include_tests: bool,
+    ) -> PyResult<Self> {
+        if let Some(profile) = &profile {
+            packs::resolve_profile(profile).map_err(|e| AnalyzerError::new_err(format!("Invalid profile: {e}")))?;
+        }
+        let min_confidence = min_confidence
+            .map(str::parse::<Confidence>)
+            .transpose()
+            .map_err(|e| AnalyzerError::new_err(format!("Invalid min_confidence: {e}")))?;
+        Ok(Self { config, profile, rules, ignore_rules, min_confidence, include_tests })
+    }
+
+    fn options(self) -> ProjectOptions<'static> {
+        ProjectOptions { config_file: self.config, ..Default::default() }.with_overrides(move |options: &mut AnalysisOptions| {
+            // Embedding pipelines do not write the cache of the CLI in the analyzed project
+            options.cache_dir = None;
+            if let Some(profile) = self.profile {
+                options.profile = Some(profile);
+            }
+            if let Some(rules) = self.rules {
+                options.include_rules = rules;
+            }
+            if let Some(ignore_rules) = self.ignore_rules {
+                options.ignore_rules = ignore_rules;
+            }
+            if let Some(min_confidence) = self.min_confidence {
+                options.min_confidence = Some(min_confidence);
+            }
+            options.include_tests |= self.include_tests;
+        })
+    }
+}
+
+/// Analyze a project directory, a Rust file or a glob of Rust files, and return the findings
+#[pyfunction]
+#[pyo3(signature = (path, *, config=None, profile=None, rules=None, ignore_rules=None, min_confidence=None, include_tests=false))]
+#[allow(clippy::too_many_arguments)]
+fn analyze_path(
+    py: Python<'_>,
+    path: PathBuf,
+    config: Option<PathBuf>,
+    profile: Option<String>,
+    rules: Option<Vec<String>>,
+    ignore_rules: Option<Vec<String>>,
+    min_confidence: Option<&str>,
+    include_tests: bool,
+) -> PyResult<Vec<Finding>> {
+    let selection = Selection::new(config, profile, rules, ignore_rules, min_confidence, include_tests)?;
+    let project = py
+        .detach(|| analyze_project(&path, selection.options()))
+        .map_err(|e| AnalyzerError::new_err(e.to_string()))?;
+    Ok(project.result.findings.iter().map(Finding::from).collect())
+}
+
+/// Analyze Rust code held in memory as the file `filename` of the project at `project` (its
+/// configuration and crates apply), and return the findings
+#[pyfunction]
+#[pyo3(signature = (source, filename="lib.rs", *, project=PathBuf::from("."), config=None, profile=None, rules=None, ignore_rules=None, min_confidence=None, include_tests=false))]
+#[allow(clippy::too_many_arguments)]
+fn analyze_source(
+    py: Python<'_>,
+    source: String,
+    filename: &str,
+    project: PathBuf,
+    config: Option<PathBuf>,
+    profile: Option<String>,
+    rules: Option<Vec<String>>,
+    ignore_rules: Option<Vec<String>>,
+    min_confidence: Option<&str>,
+    include_tests: bool,
+) -> PyResult<Vec<Finding>> {
+    let selection = Selection::new(config, profile, rules, ignore_rules, min_confidence, include_tests)?;
+    // Parsed once the GIL is released, the AST cannot leave its thread
+    let analysis = py
+        .detach(|| {
+            let file = SourceFile::from_source(project.join(filename), source);
+            analyze_sources(&project, vec![file], selection.options())
+        })
+        .map_err(|e| AnalyzerError::new_err(e.to_string()))?;
+    Ok(analysis.result.findings.iter().map(Finding::from).collect())
+}
+
+#[pymodule]
+fn solana_analyzer(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add("AnalyzerError", m.py().get_type::<AnalyzerError>())?;
+    m.add_class::<Finding>()?;
+    m.add_class::<Fix>()?;
+    m.add_class::<TextEdit>()?;
+    m.add_function(wrap_pyfunction!(analyze_path, m)?)?;
+    m.add_function(wrap_pyfunction!(analyze_source, m)?)?;
+    Ok(())
+}
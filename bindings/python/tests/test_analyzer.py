@@ -0,0 +1,64 @@
+"""Tests of the Python bindings, run on the installed module (`maturin develop`)."""
+
+import json
+import tempfile
+import unittest
+from pathlib import Path
+
+import solana_analyzer
+
+SOURCE = """use anchor_lang::prelude::*;
+
+pub fn share(amount: u64, shares: u64) -> Result<u64> {
+    Ok(amount / shares)
+}
+"""
+
+
+class AnalyzeSourceTest(unittest.TestCase):
+    def test_findings_are_typed(self):
+        findings = solana_analyzer.analyze_source(SOURCE, "src/lib.rs", rules=["solana-division-by-zero"])
+
+        self.assertEqual(len(findings), 1)
+        finding = findings[0]
+        self.assertIsInstance(finding, solana_analyzer.Finding)
+        self.assertEqual(finding.rule_id, "solana-division-by-zero")
+        self.assertEqual(finding.severity, "medium")
+        self.assertEqual(finding.confidence, "high")
+        self.assertEqual(finding.category, "arithmetic")
+        self.assertEqual(finding.cwe, [369])
+        self.assertTrue(finding.file.endswith("src/lib.rs"))
+        self.assertEqual(finding.line, 3)
+        self.assertEqual(finding.fixes[0].applicability, "machine-applicable")
+        self.assertEqual(finding.fixes[0].edits[0].line, 4)
+        self.assertIn("checked_div", finding.fixes[0].edits[0].replacement)
+
+    def test_to_json_matches_the_json_report(self):
+        finding = solana_analyzer.analyze_source(SOURCE, rules=["solana-division-by-zero"])[0]
+        data = json.loads(finding.to_json())
+        self.assertEqual(data["location"]["line"], finding.line)
+        self.assertEqual(data["rules"][0]["id"], finding.rule_id)
+
+    def test_rule_selection(self):
+        self.assertEqual(solana_analyzer.analyze_source(SOURCE, ignore_rules=["solana-division-by-zero"], rules=["solana-division-by-zero"]), [])
+        with self.assertRaises(solana_analyzer.AnalyzerError):
+            solana_analyzer.analyze_source(SOURCE, min_confidence="certain")
+
+
+class AnalyzePathTest(unittest.TestCase):
+    def test_project(self):
+        with tempfile.TemporaryDirectory() as root:
+            (Path(root) / "src").mkdir()
+            (Path(root) / "src" / "lib.rs").write_text(SOURCE)
+            findings = solana_analyzer.analyze_path(Path(root), rules=["solana-division-by-zero"])
+            self.assertEqual([(Path(finding.file).name, finding.line) for finding in findings], [("lib.rs", 3)])
+            # Nothing is cached in the analyzed project
+            self.assertFalse((Path(root) / ".analyzer-cache").exists())
+
+    def test_missing_path(self):
+        with self.assertRaises(solana_analyzer.AnalyzerError):
+            solana_analyzer.analyze_path("/nonexistent/solana-program")
+
+
+if __name__ == "__main__":
+    unittest.main()
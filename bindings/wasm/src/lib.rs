@@ -0,0 +1,20 @@
+//! WebAssembly build of the analyzer, for the browser playground (`www/`)
+//!
+//! Users paste code in the page and get its findings as they type, with no server: the module
+//! runs the library built without its `native` feature, which reads no file, spawns no process
+//! and analyzes on the calling thread.
+
+use wasm_bindgen::prelude::*;
+
+/// Analyze Rust code and return its findings as JSON, with the fields of the findings of the JSON
+/// report (see `playground::PlaygroundOptions` for `options_json`, `""` for the defaults)
+#[wasm_bindgen(js_name = analyzeSource)]
+pub fn analyze_source(code: &str, options_json: &str) -> Result<String, JsError> {
+    rust_solana_analyzer::playground::analyze_source(code, options_json).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Version of the analyzer
+#[wasm_bindgen]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
@@ -0,0 +1,92 @@
+// Module built by `wasm-pack build --target web` (../pkg)
+import init, { analyzeSource, version } from '../pkg/solana_analyzer_wasm.js'
+
+const EXAMPLE = `use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.balance -= amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: AccountInfo<'info>,
+}
+
+#[account]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub balance: u64,
+}
+`
+
+const code = document.getElementById('code')
+const output = document.getElementById('findings')
+const status = document.getElementById('status')
+
+function escape(text) {
+  return text.replace(/[&<>"]/g, (c) => ({ '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;' })[c])
+}
+
+function options() {
+  const value = (id) => document.getElementById(id).value || undefined
+  return JSON.stringify({
+    profile: value('profile'),
+    min_confidence: value('min-confidence'),
+    include_tests: document.getElementById('include-tests').checked,
+  })
+}
+
+function render(findings) {
+  if (findings.length === 0) {
+    return '<p class="meta">No findings.</p>'
+  }
+  return findings
+    .map((finding) => {
+      const rule = finding.rules[0]
+      const snippet = finding.code_snippet ? `<pre>${escape(finding.code_snippet)}</pre>` : ''
+      const recommendations = finding.recommendations.map((text) => `<li>${escape(text)}</li>`).join('')
+      return `<div class="finding">
+  <p><span class="badge ${finding.severity}">${finding.severity}</span><b>${escape(rule.title)}</b>
+  <span class="location">${escape(rule.id)} · line ${finding.location.line}</span></p>
+  <p>${escape(finding.description)}</p>${snippet}
+  <ul>${recommendations}</ul>
+</div>`
+    })
+    .join('')
+}
+
+function analyze() {
+  const started = performance.now()
+  try {
+    const findings = JSON.parse(analyzeSource(code.value, options()))
+    output.innerHTML = render(findings)
+    status.textContent = `${findings.length} findings in ${Math.round(performance.now() - started)}ms`
+  } catch (error) {
+    output.innerHTML = `<p class="error">${escape(String(error.message ?? error))}</p>`
+    status.textContent = ''
+  }
+}
+
+let pending
+function schedule() {
+  clearTimeout(pending)
+  pending = setTimeout(analyze, 250)
+}
+
+await init()
+document.title += ` ${version()}`
+code.value = EXAMPLE
+code.addEventListener('input', schedule)
+document.querySelectorAll('.options select, .options input').forEach((input) => input.addEventListener('change', analyze))
+analyze()
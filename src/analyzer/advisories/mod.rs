@@ -0,0 +1,284 @@
+use log::{debug, warn};
+use semver::{Op, Version, VersionReq};
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::dsl::query::{Metadata, MetadataValue};
+use crate::analyzer::workspace::{PROGRAM_KEY, Workspace};
+use crate::analyzer::{Confidence, Finding, Location, RuleInfo, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Advisory table embedded in the analyzer
+const EMBEDDED_ADVISORIES: &str = include_str!("advisories.toml");
+
+/// ID under which vulnerable dependencies are reported (can be ignored like a rule ID)
+pub const ADVISORY_ID: &str = "vulnerable-dependency";
+
+/// Metadata key of the findings holding the ID of the advisory
+pub const ADVISORY_KEY: &str = "advisory";
+
+/// Error returned when an advisory table cannot be loaded
+#[derive(Debug)]
+pub enum AdvisoryError {
+    /// The advisory table cannot be read
+    Io { path: PathBuf, error: std::io::Error },
+    /// The advisory table is not valid TOML or does not follow the schema
+    Parse { source: String, error: String },
+    /// The affected versions of an advisory are not a valid version requirement
+    InvalidVersions { id: String, error: String },
+}
+
+impl fmt::Display for AdvisoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdvisoryError::Io { path, error } => write!(f, "cannot read advisories {}: {error}", path.display()),
+            AdvisoryError::Parse { source, error } => write!(f, "invalid advisories {source}: {error}"),
+            AdvisoryError::InvalidVersions { id, error } => write!(f, "invalid affected versions of {id}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for AdvisoryError {}
+
+/// Known problem in some versions of a package
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Advisory {
+    /// ID of the advisory (RustSec ID when there is one)
+    pub id: String,
+    /// Name of the affected package
+    pub package: String,
+    /// Affected versions, as a Cargo version requirement (`<0.29.0`)
+    pub affected: String,
+    /// Versions without the problem, shown in the recommendation
+    pub patched: Option<String>,
+    /// Severity of the findings (high for vulnerabilities, informational for unmaintained releases)
+    pub severity: Severity,
+    /// Short title
+    pub title: String,
+    /// What the problem is
+    pub description: String,
+    /// Link to the advisory
+    pub url: Option<String>,
+}
+
+/// Layout of an advisory table
+#[derive(Deserialize)]
+struct AdvisoryTable {
+    #[serde(default)]
+    advisory: Vec<Advisory>,
+}
+
+/// Advisories checked against the dependencies of the analyzed workspace
+///
+/// The embedded table ([`AdvisoryDb::embedded`]) can be extended with tables of the same format,
+/// whose entries replace the ones with the same ID.
+#[derive(Debug, Clone, Default)]
+pub struct AdvisoryDb {
+    /// Advisories with their parsed affected versions
+    advisories: Vec<(Advisory, VersionReq)>,
+}
+
+impl AdvisoryDb {
+    /// Returns the advisory table embedded in the analyzer
+    pub fn embedded() -> Self {
+        Self::from_toml(EMBEDDED_ADVISORIES, "(embedded)").expect("the embedded advisory table is valid")
+    }
+
+    /// Parse an advisory table, `source` naming it in the errors
+    pub fn from_toml(content: &str, source: &str) -> Result<Self, AdvisoryError> {
+        let table: AdvisoryTable = toml::from_str(content).map_err(|error| AdvisoryError::Parse {
+            source: source.to_string(),
+            error: error.message().to_string(),
+        })?;
+        let advisories = table
+            .advisory
+            .into_iter()
+            .map(|advisory| match VersionReq::parse(&advisory.affected) {
+                Ok(affected) => Ok((advisory, affected)),
+                Err(error) => Err(AdvisoryError::InvalidVersions {
+                    id: advisory.id,
+                    error: error.to_string(),
+                }),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { advisories })
+    }
+
+    /// Read an advisory table from a file
+    pub fn from_file(path: &Path) -> Result<Self, AdvisoryError> {
+        let content = std::fs::read_to_string(path).map_err(|error| AdvisoryError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        Self::from_toml(&content, &path.to_string_lossy())
+    }
+
+    /// Add the advisories of another table, replacing the ones with the same ID
+    pub fn extend(&mut self, other: AdvisoryDb) {
+        for (advisory, affected) in other.advisories {
+            self.advisories.retain(|(existing, _)| existing.id != advisory.id);
+            self.advisories.push((advisory, affected));
+        }
+    }
+
+    /// Returns the number of advisories
+    pub fn len(&self) -> usize {
+        self.advisories.len()
+    }
+
+    /// Check if the table has no advisory
+    pub fn is_empty(&self) -> bool {
+        self.advisories.is_empty()
+    }
+
+    /// Returns the advisories affecting a version of a package
+    pub fn matching<'a>(&'a self, package: &'a str, version: &'a Version) -> impl Iterator<Item = &'a Advisory> {
+        self.advisories
+            .iter()
+            .filter(move |(advisory, affected)| advisory.package == package && affected.matches(version))
+            .map(|(advisory, _)| advisory)
+    }
+
+    /// Report the dependencies of a workspace affected by an advisory
+    ///
+    /// The locked versions of `Cargo.lock` are checked when there is one, including the
+    /// transitive dependencies. Otherwise the lowest version allowed by the requirements of each
+    /// crate's `Cargo.toml` is. Findings point to the line of the version.
+    pub fn check(&self, workspace: &Workspace) -> Vec<Finding> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        let lock_path = workspace.root.join("Cargo.lock");
+        let mut findings = Vec::new();
+        if let Ok(lock) = std::fs::read_to_string(&lock_path) {
+            for (package, version, line) in locked_packages(&lock) {
+                let Ok(version) = Version::parse(&version) else {
+                    continue;
+                };
+                for advisory in self.matching(&package, &version) {
+                    findings.push(advisory_finding(advisory, &version, &lock_path, &lock, line, None));
+                }
+            }
+            debug!("{} locked dependencies affected by an advisory", findings.len());
+            return findings;
+        }
+
+        for manifest_crate in &workspace.crates {
+            let manifest_path = manifest_crate.dir.join("Cargo.toml");
+            let manifest = match std::fs::read_to_string(&manifest_path) {
+                Ok(manifest) => manifest,
+                Err(e) => {
+                    warn!("Cannot check the dependencies of {}: {e}", manifest_path.display());
+                    continue;
+                }
+            };
+            for (package, requirement) in &manifest_crate.dependencies {
+                let Some(version) = lowest_version(requirement) else {
+                    continue;
+                };
+                for advisory in self.matching(package, &version) {
+                    let line = dependency_line(&manifest, package);
+                    findings.push(advisory_finding(advisory, &version, &manifest_path, &manifest, line, Some(&manifest_crate.name)));
+                }
+            }
+        }
+        findings
+    }
+}
+
+/// Helper function to build the finding of a dependency affected by an advisory
+fn advisory_finding(advisory: &Advisory, version: &Version, file: &Path, content: &str, line: usize, program: Option<&str>) -> Finding {
+    let mut metadata = Metadata::new();
+    metadata.insert(ADVISORY_KEY.to_string(), MetadataValue::Text(advisory.id.clone()));
+    if let Some(program) = program {
+        metadata.insert(PROGRAM_KEY.to_string(), MetadataValue::Text(program.to_string()));
+    }
+    let upgrade = match &advisory.patched {
+        Some(patched) => format!("Upgrade {} to a version matching {patched}", advisory.package),
+        None => format!("Replace {}, no version fixes the problem", advisory.package),
+    };
+    Finding {
+        description: format!("{} {version}: {} ({}). {}", advisory.package, advisory.title, advisory.id, advisory.description),
+        severity: advisory.severity.clone(),
+        confidence: Confidence::High,
+        location: Location::new_precise(file.to_string_lossy().to_string(), line, None, None, None),
+        code_snippet: content.lines().nth(line - 1).map(|code| code.trim().to_string()),
+        recommendations: vec![upgrade],
+        fixes: Vec::new(),
+        metadata,
+        rules: vec![RuleInfo {
+            id: ADVISORY_ID.to_string(),
+            title: "Vulnerable Dependency".to_string(),
+            references: advisory.url.iter().cloned().collect(),
+            tags: vec!["dependencies".to_string()],
+            cwe: vec![1395],
+            ..Default::default()
+        }],
+        fingerprint: None,
+    }
+}
+
+/// Helper function to list the packages of a lock file with their version and its line
+pub(crate) fn locked_packages(lock: &str) -> Vec<(String, String, usize)> {
+    let mut packages = Vec::new();
+    let mut name = None;
+    for (index, line) in lock.lines().enumerate() {
+        let line = line.trim();
+        if line == "[[package]]" {
+            name = None;
+        } else if let Some(value) = string_value(line, "name") {
+            name = Some(value);
+        } else if let Some(version) = string_value(line, "version")
+            && let Some(name) = name.take()
+        {
+            packages.push((name, version, index + 1));
+        }
+    }
+    packages
+}
+
+/// Helper function to read a `key = "value"` line
+fn string_value(line: &str, key: &str) -> Option<String> {
+    let value = line.strip_prefix(key)?.trim_start().strip_prefix('=')?.trim();
+    Some(value.strip_prefix('"')?.strip_suffix('"')?.to_string())
+}
+
+/// Helper function to get the lowest version allowed by a requirement (`0.28` gives 0.28.0)
+///
+/// Requirements without a lower bound (`*`, `<2`) give `None`.
+pub(crate) fn lowest_version(requirement: &str) -> Option<Version> {
+    let requirement = VersionReq::parse(requirement).ok()?;
+    requirement
+        .comparators
+        .iter()
+        .filter(|comparator| matches!(comparator.op, Op::Exact | Op::Caret | Op::Tilde | Op::GreaterEq | Op::Wildcard))
+        .map(|comparator| Version::new(comparator.major, comparator.minor.unwrap_or(0), comparator.patch.unwrap_or(0)))
+        .max()
+}
+
+/// Helper function to find the line declaring a dependency in a manifest (the first line if it
+/// is not found, e.g. for a renamed dependency)
+fn dependency_line(manifest: &str, package: &str) -> usize {
+    let mut in_dependencies = false;
+    for (index, line) in manifest.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.trim_end_matches(']');
+            let is_dependencies = |table: &str| table == "dependencies" || table.ends_with(".dependencies");
+            // `[dependencies.anchor-lang]` declares the dependency on its own
+            if header.strip_suffix(package).and_then(|table| table.strip_suffix('.')).is_some_and(is_dependencies) {
+                return index + 1;
+            }
+            in_dependencies = is_dependencies(header);
+        } else if in_dependencies
+            && let Some(key) = line.split(['=', '.']).next()
+            && key.trim().trim_matches('"') == package
+        {
+            return index + 1;
+        }
+    }
+    1
+}
@@ -0,0 +1,122 @@
+use crate::analyzer::Severity;
+use crate::analyzer::advisories::{ADVISORY_ID, AdvisoryDb, AdvisoryError, dependency_line, lowest_version};
+use crate::analyzer::workspace::{PROGRAM_KEY, Workspace};
+use semver::Version;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"[package]
+name = "vault"
+
+[dev-dependencies]
+solana-program-test = "1.17"
+
+[dependencies]
+anchor-lang = { version = "0.28.0", features = ["init-if-needed"] }
+solana-program = "~1.18"
+
+[dependencies.spl-token]
+version = "3.5"
+"#;
+
+    const LOCK: &str = r#"version = 3
+
+[[package]]
+name = "borsh"
+version = "0.10.3"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "ed25519-dalek"
+version = "1.0.1"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "vault"
+version = "0.1.0"
+"#;
+
+    /// Helper function to write a crate with its manifest (and lock file) in a temporary directory
+    fn write_crate(name: &str, lock: Option<&str>) -> Workspace {
+        let root = std::env::temp_dir().join(format!("solana-analyzer-advisories-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("Cargo.toml"), MANIFEST).unwrap();
+        if let Some(lock) = lock {
+            std::fs::write(root.join("Cargo.lock"), lock).unwrap();
+        }
+        Workspace::load(&root).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_advisory_tables() {
+        let embedded = AdvisoryDb::embedded();
+        assert!(!embedded.is_empty());
+        let version = Version::parse("0.28.0").unwrap();
+        let advisories: Vec<_> = embedded.matching("anchor-lang", &version).collect();
+        assert_eq!(advisories.len(), 1);
+        assert_eq!(advisories[0].severity, Severity::Informational);
+        assert_eq!(embedded.matching("anchor-lang", &Version::parse("0.30.1").unwrap()).count(), 0);
+
+        // Entries with the same ID replace the embedded ones
+        let extra = AdvisoryDb::from_toml(
+            r#"
+[[advisory]]
+id = "RUSTSEC-2023-0033"
+package = "borsh"
+affected = "<1.0.0"
+severity = "high"
+title = "Updated"
+description = "Updated range"
+"#,
+            "extra.toml",
+        )
+        .unwrap();
+        let mut advisories = embedded.clone();
+        advisories.extend(extra);
+        assert_eq!(advisories.len(), embedded.len());
+        let borsh = Version::parse("0.10.3").unwrap();
+        assert_eq!(advisories.matching("borsh", &borsh).map(|advisory| advisory.title.as_str()).collect::<Vec<_>>(), ["Updated"]);
+
+        let invalid = "[[advisory]]\nid = \"X\"\npackage = \"x\"\naffected = \"not a version\"\nseverity = \"high\"\ntitle = \"\"\ndescription = \"\"\n";
+        assert!(matches!(AdvisoryDb::from_toml(invalid, "x.toml"), Err(AdvisoryError::InvalidVersions { .. })));
+        assert!(matches!(AdvisoryDb::from_toml("[[advisory]]\nid = 1\n", "x.toml"), Err(AdvisoryError::Parse { .. })));
+    }
+
+    #[test]
+    fn test_dependencies_are_checked_against_the_advisories() {
+        // Locked versions, with the line of the version
+        let findings = AdvisoryDb::embedded().check(&write_crate("locked", Some(LOCK)));
+        assert_eq!(findings.len(), 1, "{findings:#?}");
+        assert!(findings[0].location.file.ends_with("Cargo.lock"));
+        assert_eq!(findings[0].location.line, 10);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].rules[0].id, ADVISORY_ID);
+        assert!(findings[0].description.starts_with("ed25519-dalek 1.0.1:"));
+
+        // Requirements of the manifest without a lock file
+        let findings = AdvisoryDb::embedded().check(&write_crate("manifest", None));
+        let reported: Vec<(usize, &str)> = findings
+            .iter()
+            .map(|finding| (finding.location.line, finding.description.split(':').next().unwrap()))
+            .collect();
+        assert_eq!(reported, [(8, "anchor-lang 0.28.0"), (11, "spl-token 3.5.0")]);
+        assert!(findings.iter().all(|finding| finding.metadata.contains_key(PROGRAM_KEY)));
+
+        assert!(AdvisoryDb::default().check(&write_crate("empty", None)).is_empty());
+    }
+
+    #[test]
+    fn test_declared_versions() {
+        assert_eq!(lowest_version("0.28"), Some(Version::new(0, 28, 0)));
+        assert_eq!(lowest_version("~1.18.2"), Some(Version::new(1, 18, 2)));
+        assert_eq!(lowest_version(">=1.16, <1.19"), Some(Version::new(1, 16, 0)));
+        assert_eq!(lowest_version("*"), None);
+        assert_eq!(lowest_version("<2"), None);
+
+        assert_eq!(dependency_line(MANIFEST, "solana-program"), 9);
+        assert_eq!(dependency_line(MANIFEST, "solana-program-test"), 1);
+        assert_eq!(dependency_line(MANIFEST, "spl-token"), 11);
+    }
+}
@@ -0,0 +1,162 @@
+use log::debug;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use crate::analyzer::dsl::query::MetadataValue;
+use crate::analyzer::idl::{IDL_DIR, Idl};
+use crate::analyzer::output::Format;
+use crate::analyzer::vfs::OsFileSystem;
+use crate::analyzer::workspace::{CrateManifest, PROGRAM_KEY};
+use crate::analyzer::{AnalysisResult, AnalysisStats, Finding};
+
+#[cfg(test)]
+mod test;
+
+/// Manifest of an Anchor workspace
+pub const ANCHOR_MANIFEST: &str = "Anchor.toml";
+
+/// Directory of the TypeScript types built by `anchor build`, relative to the workspace root
+pub const TYPES_DIR: &str = "target/types";
+
+/// Default directory of the reports of `--anchor`, relative to the workspace root
+pub const REPORTS_DIR: &str = "target/solana-analyzer";
+
+/// Directory of the Anchor workspace of a path: the nearest directory with an `Anchor.toml`,
+/// the path itself or one above it
+pub fn find_root(path: &Path) -> Option<PathBuf> {
+    let dir = if path.is_file() { path.parent().unwrap_or(Path::new("")) } else { path };
+    let directory = |ancestor: &Path| if ancestor.as_os_str().is_empty() { PathBuf::from(".") } else { ancestor.to_path_buf() };
+    dir.ancestors()
+        .map(directory)
+        .find(|ancestor| ancestor.join(ANCHOR_MANIFEST).is_file())
+        // The directories above a relative path are only found through its absolute path
+        .or_else(|| dir.canonicalize().ok()?.ancestors().find(|ancestor| ancestor.join(ANCHOR_MANIFEST).is_file()).map(Path::to_path_buf))
+}
+
+/// Program of an Anchor workspace, with the artifacts `anchor build` wrote for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorProgram {
+    /// Name of the crate (the `program` metadata of its findings)
+    pub name: String,
+    /// Directory of the crate
+    pub dir: PathBuf,
+    /// Program ID from `Anchor.toml` or `declare_id!`, else from the IDL or the TypeScript types
+    pub program_id: Option<String>,
+    /// IDL of the program (`target/idl/<lib name>.json`), if built
+    pub idl: Option<PathBuf>,
+    /// TypeScript types of the program (`target/types/<lib name>.ts`), if built
+    pub types: Option<PathBuf>,
+    /// Whether a source file of the crate changed after the IDL or the types were built
+    pub stale: bool,
+}
+
+impl AnchorProgram {
+    /// Program of a crate of the Anchor workspace at `root`
+    pub fn of(root: &Path, manifest: &CrateManifest) -> Self {
+        let idl = Some(root.join(IDL_DIR).join(format!("{}.json", manifest.lib_name))).filter(|path| path.is_file());
+        let types = Some(root.join(TYPES_DIR).join(format!("{}.ts", manifest.lib_name))).filter(|path| path.is_file());
+
+        let program_id = manifest
+            .program_id
+            .clone()
+            .or_else(|| Idl::load(&OsFileSystem, root, manifest).ok().flatten().and_then(|idl| idl.address))
+            .or_else(|| types.as_deref().and_then(|types| std::fs::read_to_string(types).ok()).and_then(|types| types_address(&types)));
+
+        let sources_modified = last_modified(&manifest.dir);
+        let stale = [&idl, &types]
+            .into_iter()
+            .flatten()
+            .any(|artifact| modified(artifact).zip(sources_modified).is_some_and(|(built, changed)| built < changed));
+        debug!(
+            "Anchor program {} ({}): IDL {}, types {}{}",
+            manifest.name,
+            program_id.as_deref().unwrap_or("no program ID"),
+            if idl.is_some() { "built" } else { "missing" },
+            if types.is_some() { "built" } else { "missing" },
+            if stale { ", stale" } else { "" }
+        );
+        Self { name: manifest.name.clone(), dir: manifest.dir.clone(), program_id, idl, types, stale }
+    }
+
+    /// Name of the reports of the program: its program ID, or its crate name without one
+    pub fn report_name(&self) -> &str {
+        self.program_id.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Path of the report of the program in a format, in the directory `dir`
+    pub fn report_path(&self, dir: &Path, format: Format) -> PathBuf {
+        dir.join(format!("{}.{}", self.report_name(), format.extension()))
+    }
+
+    /// Findings of the program in an analysis of the workspace, with the statistics of its files
+    pub fn result(&self, result: &AnalysisResult) -> AnalysisResult {
+        let in_program = |finding: &Finding| match finding.metadata.get(PROGRAM_KEY) {
+            Some(MetadataValue::Text(program)) => *program == self.name,
+            _ => false,
+        };
+        let in_dir = |file: &str| Path::new(file).starts_with(&self.dir);
+        let findings: Vec<Finding> = result.findings.iter().filter(|finding| in_program(finding)).cloned().collect();
+
+        let source = &result.stats;
+        let mut stats = AnalysisStats {
+            rules_executed: source.rules_executed,
+            total_time_ms: source.total_time_ms,
+            lines_of_code: source.lines_of_code.iter().filter(|(file, _)| in_dir(file)).map(|(file, lines)| (file.clone(), *lines)).collect(),
+            suppressed_findings: source.suppressed_findings.iter().filter(|suppressed| in_program(&suppressed.finding)).cloned().collect(),
+            rule_types: source.rule_types.clone(),
+            inventory: source.inventory.iter().filter(|inventory| inventory.program.as_deref() == Some(&self.name)).cloned().collect(),
+            pdas: source.pdas.iter().filter(|pdas| pdas.program.as_deref() == Some(&self.name)).cloned().collect(),
+            cpis: source.cpis.iter().filter(|cpis| cpis.program.as_deref() == Some(&self.name)).cloned().collect(),
+            ..Default::default()
+        };
+        stats.files_analyzed = stats.lines_of_code.len();
+        stats.findings_suppressed = stats.suppressed_findings.len();
+        for finding in &findings {
+            *stats.findings_by_severity.entry(finding.severity.clone()).or_insert(0) += 1;
+            *stats.findings_by_file.entry(finding.location.file.clone()).or_insert(0) += 1;
+            if let Some(rule) = finding.rule() {
+                *stats.findings_by_rule.entry(rule.id.clone()).or_insert(0) += 1;
+            }
+        }
+        if !findings.is_empty() {
+            stats.findings_by_program.insert(self.name.clone(), findings.len());
+        }
+        AnalysisResult { findings, stats }
+    }
+}
+
+/// Programs of the Anchor workspace at `root`, from its crates that are Solana programs
+pub fn programs(root: &Path, manifests: &[CrateManifest]) -> Vec<AnchorProgram> {
+    manifests
+        .iter()
+        .filter(|manifest| manifest.is_program())
+        .map(|manifest| AnchorProgram::of(root, manifest))
+        .collect()
+}
+
+/// Helper function to read the program ID of the TypeScript types of a program (the first
+/// `address` field, quoted or not)
+fn types_address(types: &str) -> Option<String> {
+    types.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("\"address\":").or_else(|| line.trim().strip_prefix("address:"))?;
+        let address = value.trim().trim_end_matches(',').trim_matches(['"', '\'']);
+        (!address.is_empty()).then(|| address.to_string())
+    })
+}
+
+/// Helper function to get the last modification of a file
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Helper function to get the last modification of the Rust files of a crate
+fn last_modified(dir: &Path) -> Option<SystemTime> {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != "target")
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "rs"))
+        .filter_map(|entry| modified(entry.path()))
+        .max()
+}
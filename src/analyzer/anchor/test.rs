@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::anchor::{AnchorProgram, types_address};
+use crate::analyzer::dsl::query::MetadataValue;
+use crate::analyzer::output::Format;
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::workspace::PROGRAM_KEY;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Finding, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(program_id: Option<&str>) -> AnchorProgram {
+        AnchorProgram {
+            name: "vault".to_string(),
+            dir: PathBuf::from("./programs/vault"),
+            program_id: program_id.map(str::to_string),
+            idl: None,
+            types: None,
+            stale: false,
+        }
+    }
+
+    fn finding(file: &str, program: &str, severity: Severity) -> Finding {
+        FindingBuilder::new("Division Without Zero Check in 'f'")
+            .severity(severity)
+            .at(file, 3)
+            .rule_id("solana-division-by-zero")
+            .metadata(PROGRAM_KEY, MetadataValue::Text(program.to_string()))
+            .build()
+    }
+
+    #[test]
+    fn test_report_names() {
+        let program = program(Some("Vau1t11111111111111111111111111111111111111"));
+        assert_eq!(
+            program.report_path(Path::new("target/solana-analyzer"), Format::Json),
+            Path::new("target/solana-analyzer/Vau1t11111111111111111111111111111111111111.json")
+        );
+        // Without a program ID, the crate names the reports
+        assert_eq!(self::program(None).report_path(Path::new("out"), Format::Markdown), Path::new("out/vault.md"));
+    }
+
+    #[test]
+    fn test_findings_of_a_program() {
+        let findings = vec![
+            finding("./programs/vault/src/lib.rs", "vault", Severity::High),
+            finding("./programs/staking/src/lib.rs", "staking", Severity::Medium),
+            finding("./programs/vault/src/state.rs", "vault", Severity::Low),
+        ];
+        let mut result = AnalysisResult { findings, stats: AnalysisStats::default() };
+        result.stats.lines_of_code.insert("./programs/vault/src/lib.rs".to_string(), 40);
+        result.stats.lines_of_code.insert("./programs/staking/src/lib.rs".to_string(), 25);
+
+        let vault = program(None).result(&result);
+        let files: Vec<&str> = vault.findings.iter().map(|finding| finding.location.file.as_str()).collect();
+        assert_eq!(files, ["./programs/vault/src/lib.rs", "./programs/vault/src/state.rs"]);
+        assert_eq!(vault.stats.files_analyzed, 1);
+        assert_eq!(vault.stats.total_lines_of_code(), 40);
+        assert_eq!(vault.stats.findings_by_severity.get(&Severity::High), Some(&1));
+        assert_eq!(vault.stats.findings_by_program.get("vault"), Some(&2));
+        assert_eq!(vault.stats.findings_by_rule.get("solana-division-by-zero"), Some(&2));
+    }
+
+    #[test]
+    fn test_address_of_the_types() {
+        let types = "export type Vault = {\n  \"address\": \"Vau1t11111111111111111111111111111111111111\",\n  \"metadata\": {}\n};\n";
+        assert_eq!(types_address(types).as_deref(), Some("Vau1t11111111111111111111111111111111111111"));
+        assert_eq!(types_address("export type Vault = {\n  version: \"0.1.0\",\n};\n"), None);
+    }
+}
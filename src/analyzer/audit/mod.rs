@@ -0,0 +1,504 @@
+use std::fmt::Write;
+
+use crate::analyzer::html::{escape, highlight};
+use crate::analyzer::results::{self, SeverityCounts};
+use crate::analyzer::suppressions::SuppressedFinding;
+use crate::analyzer::{AnalysisResult, Confidence, Finding, Severity, items};
+
+#[cfg(test)]
+mod test;
+
+/// Prefix of the finding IDs of the audit report (`SLA-001`)
+pub const FINDING_ID_PREFIX: &str = "SLA";
+
+/// Confidences of the severity matrix, the most certain first
+const CONFIDENCES: [Confidence; 3] = [Confidence::High, Confidence::Medium, Confidence::Low];
+
+/// Page break of the Markdown report, kept by the Markdown to PDF converters that go through HTML
+const PAGE_BREAK: &str = "<div style=\"page-break-after: always;\"></div>\n\n";
+
+/// Print styles of the HTML report: A4 pages, a cover page and one page per section
+const STYLE: &str = r#"
+@page { size: A4; margin: 2cm; }
+body { font-family: Georgia, "Times New Roman", serif; color: #1f2328; line-height: 1.45; max-width: 800px; margin: 0 auto; }
+.cover { page-break-after: always; text-align: center; padding-top: 30vh; }
+.cover h1 { font-size: 2.2rem; margin-bottom: 2rem; }
+.cover dl { display: inline-grid; grid-template-columns: auto auto; gap: 0.3rem 1rem; text-align: left; }
+.cover dt { font-weight: bold; }
+.cover dd { margin: 0; }
+section { page-break-before: always; }
+h1, h2, h3 { font-family: -apple-system, "Segoe UI", Roboto, sans-serif; }
+h3 { page-break-after: avoid; border-bottom: 1px solid #d1d9e0; padding-bottom: 0.2rem; }
+.finding { page-break-inside: avoid; margin-bottom: 1.5rem; }
+table { border-collapse: collapse; margin: 0.8rem 0; font-size: 0.9rem; }
+th, td { border: 1px solid #d1d9e0; padding: 0.3rem 0.6rem; text-align: left; }
+td.num-cell { text-align: right; }
+pre { background: #f6f8fa; padding: 0.6rem; white-space: pre-wrap; font-size: 0.8rem; }
+code { font-family: ui-monospace, monospace; }
+.kw { color: #cf222e; } .str { color: #0a3069; } .com { color: #59636e; font-style: italic; } .num { color: #0550ae; } .mac { color: #8250df; } .ty { color: #953800; }
+"#;
+
+/// Fields of the cover page, set in the `[audit]` section of the configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditInfo {
+    /// Client the audit is delivered to
+    pub client: Option<String>,
+    /// Audited commit of the repository
+    pub commit: Option<String>,
+    /// Date of the report (today if not set)
+    pub date: Option<String>,
+}
+
+/// Long-form audit report (`--format audit` and `audit-html`), meant to be converted to a PDF
+/// deliverable
+///
+/// The report has a cover page, the methodology, the scope (the analyzed files and their lines
+/// of code), a severity matrix, the findings numbered `SLA-001`, `SLA-002`... the most severe
+/// first, and an appendix of the findings removed by suppression comments and filters.
+#[derive(Debug)]
+pub struct AuditReport<'a> {
+    result: &'a AnalysisResult,
+    project: &'a str,
+    info: AuditInfo,
+}
+
+impl<'a> AuditReport<'a> {
+    /// Creates the audit report of an analysis of `project`
+    pub fn new(result: &'a AnalysisResult, project: &'a str) -> Self {
+        Self { result, project, info: AuditInfo::default() }
+    }
+
+    /// Fill the cover page with the client, the commit and the date
+    pub fn with_info(mut self, info: AuditInfo) -> Self {
+        self.info = info;
+        self
+    }
+
+    /// Returns the findings with their IDs (`SLA-001`...), the most severe first
+    pub fn numbered_findings(&self) -> Vec<(String, &'a Finding)> {
+        let mut findings: Vec<&Finding> = self.result.findings.iter().collect();
+        findings.sort_by(|a, b| results::compare(a, b));
+        findings
+            .into_iter()
+            .enumerate()
+            .map(|(index, finding)| (format!("{FINDING_ID_PREFIX}-{:03}", index + 1), finding))
+            .collect()
+    }
+
+    /// Returns the report as Markdown
+    pub fn to_markdown(&self) -> String {
+        let findings = self.numbered_findings();
+        let mut report = String::new();
+
+        // Cover page
+        report.push_str("# Security Audit Report\n\n");
+        for (field, value) in self.cover_fields() {
+            let _ = writeln!(report, "**{field}:** {value}  ");
+        }
+        report.push('\n');
+        report.push_str(PAGE_BREAK);
+
+        report.push_str("## Table of Contents\n\n");
+        for section in SECTIONS {
+            let _ = writeln!(report, "- [{section}](#{})", anchor(section));
+        }
+        report.push('\n');
+        report.push_str(PAGE_BREAK);
+
+        let _ = writeln!(report, "## {}\n\n{}\n", SECTIONS[0], self.methodology());
+        report.push_str("| Severity | Meaning |\n| --- | --- |\n");
+        for severity in Severity::ALL {
+            let _ = writeln!(report, "| {severity:?} | {} |", severity_meaning(&severity));
+        }
+        report.push_str("\n| Confidence | Meaning |\n| --- | --- |\n");
+        for confidence in CONFIDENCES {
+            let _ = writeln!(report, "| {confidence} | {} |", confidence_meaning(confidence));
+        }
+        report.push('\n');
+        report.push_str(PAGE_BREAK);
+
+        let _ = writeln!(report, "## {}\n\n{}\n", SECTIONS[1], self.scope_summary());
+        if !self.result.stats.lines_of_code.is_empty() {
+            report.push_str("| File | Lines of Code |\n| --- | --- |\n");
+            for (file, lines) in &self.result.stats.lines_of_code {
+                let _ = writeln!(report, "| {} | {lines} |", self.display_path(file));
+            }
+            let _ = writeln!(report, "| **Total** | **{}** |\n", self.result.stats.total_lines_of_code());
+        }
+        report.push_str(PAGE_BREAK);
+
+        let _ = writeln!(report, "## {}\n\n{}\n", SECTIONS[2], self.summary_sentence());
+        report.push_str("| Severity \\ Confidence | High | Medium | Low | Total |\n| --- | --- | --- | --- | --- |\n");
+        for (severity, row, total) in self.severity_matrix() {
+            let cells: String = row.iter().map(|count| format!(" {count} |")).collect();
+            let _ = writeln!(report, "| {severity:?} |{cells} {total} |");
+        }
+        if !findings.is_empty() {
+            report.push_str("\n| ID | Title | Severity | Confidence | Location |\n| --- | --- | --- | --- | --- |\n");
+            for (id, finding) in &findings {
+                let _ = writeln!(
+                    report,
+                    "| [{id}](#{}) | {} | {:?} | {} | {} |",
+                    id.to_lowercase(),
+                    title(finding).replace('|', "\\|"),
+                    finding.severity,
+                    finding.confidence,
+                    self.location(finding)
+                );
+            }
+        }
+        report.push('\n');
+        report.push_str(PAGE_BREAK);
+
+        let _ = writeln!(report, "## {}\n", SECTIONS[3]);
+        if findings.is_empty() {
+            report.push_str("No vulnerabilities found.\n\n");
+        }
+        for (id, finding) in &findings {
+            report.push_str(&self.markdown_finding(id, finding));
+        }
+        report.push_str(PAGE_BREAK);
+
+        let _ = writeln!(report, "## {}\n", SECTIONS[4]);
+        let suppressed = &self.result.stats.suppressed_findings;
+        if suppressed.is_empty() {
+            report.push_str("No finding was suppressed.\n");
+        } else {
+            report.push_str(
+                "Findings removed by `analyzer:ignore` comments in the code, with the reasons given by the developers, \
+                 and by the severity and confidence filters of the analysis.\n\n\
+                 | Rule | Severity | Location | Suppressed By | Reason |\n| --- | --- | --- | --- | --- |\n",
+            );
+            for suppressed in suppressed {
+                let _ = writeln!(
+                    report,
+                    "| {} | {:?} | {} | {} | {} |",
+                    suppressed.rule_id,
+                    suppressed.finding.severity,
+                    self.location(&suppressed.finding),
+                    suppressed.suppressed_by.as_str(),
+                    reason(suppressed).replace('|', "\\|")
+                );
+            }
+        }
+        report
+    }
+
+    /// Returns the report as an HTML document with print styles
+    pub fn to_html(&self) -> String {
+        let findings = self.numbered_findings();
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        let _ = writeln!(html, "<title>Security Audit Report - {}</title>", escape(self.project));
+        let _ = writeln!(html, "<style>{STYLE}</style>\n</head>\n<body>");
+
+        // Cover page
+        html.push_str("<div class=\"cover\">\n<h1>Security Audit Report</h1>\n<dl>\n");
+        for (field, value) in self.cover_fields() {
+            let _ = writeln!(html, "<dt>{field}</dt><dd>{}</dd>", escape(&value));
+        }
+        html.push_str("</dl>\n</div>\n");
+
+        html.push_str("<nav>\n<h2>Table of Contents</h2>\n<ul>\n");
+        for section in SECTIONS {
+            let _ = writeln!(html, "<li><a href=\"#{}\">{section}</a></li>", anchor(section));
+        }
+        html.push_str("</ul>\n</nav>\n");
+
+        let _ = writeln!(html, "<section id=\"{}\">\n<h2>{}</h2>\n<p>{}</p>", anchor(SECTIONS[0]), SECTIONS[0], escape(&self.methodology()));
+        html.push_str("<table>\n<tr><th>Severity</th><th>Meaning</th></tr>\n");
+        for severity in Severity::ALL {
+            let _ = writeln!(html, "<tr><td>{severity:?}</td><td>{}</td></tr>", severity_meaning(&severity));
+        }
+        html.push_str("</table>\n<table>\n<tr><th>Confidence</th><th>Meaning</th></tr>\n");
+        for confidence in CONFIDENCES {
+            let _ = writeln!(html, "<tr><td>{confidence}</td><td>{}</td></tr>", confidence_meaning(confidence));
+        }
+        html.push_str("</table>\n</section>\n");
+
+        let _ = writeln!(html, "<section id=\"{}\">\n<h2>{}</h2>\n<p>{}</p>", anchor(SECTIONS[1]), SECTIONS[1], escape(&self.scope_summary()));
+        if !self.result.stats.lines_of_code.is_empty() {
+            html.push_str("<table>\n<tr><th>File</th><th>Lines of Code</th></tr>\n");
+            for (file, lines) in &self.result.stats.lines_of_code {
+                let _ = writeln!(html, "<tr><td><code>{}</code></td><td class=\"num-cell\">{lines}</td></tr>", escape(self.display_path(file)));
+            }
+            let _ = writeln!(
+                html,
+                "<tr><th>Total</th><th class=\"num-cell\">{}</th></tr>\n</table>",
+                self.result.stats.total_lines_of_code()
+            );
+        }
+        html.push_str("</section>\n");
+
+        let _ = writeln!(html, "<section id=\"{}\">\n<h2>{}</h2>\n<p>{}</p>", anchor(SECTIONS[2]), SECTIONS[2], escape(&self.summary_sentence()));
+        html.push_str("<table>\n<tr><th>Severity \\ Confidence</th><th>High</th><th>Medium</th><th>Low</th><th>Total</th></tr>\n");
+        for (severity, row, total) in self.severity_matrix() {
+            let cells: String = row.iter().map(|count| format!("<td class=\"num-cell\">{count}</td>")).collect();
+            let _ = writeln!(html, "<tr><th>{severity:?}</th>{cells}<td class=\"num-cell\">{total}</td></tr>");
+        }
+        html.push_str("</table>\n");
+        if !findings.is_empty() {
+            html.push_str("<table>\n<tr><th>ID</th><th>Title</th><th>Severity</th><th>Confidence</th><th>Location</th></tr>\n");
+            for (id, finding) in &findings {
+                let _ = writeln!(
+                    html,
+                    "<tr><td><a href=\"#{}\">{id}</a></td><td>{}</td><td>{:?}</td><td>{}</td><td><code>{}</code></td></tr>",
+                    id.to_lowercase(),
+                    escape(&title(finding)),
+                    finding.severity,
+                    finding.confidence,
+                    escape(&self.location(finding))
+                );
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str("</section>\n");
+
+        let _ = writeln!(html, "<section id=\"{}\">\n<h2>{}</h2>", anchor(SECTIONS[3]), SECTIONS[3]);
+        if findings.is_empty() {
+            html.push_str("<p>No vulnerabilities found.</p>\n");
+        }
+        for (id, finding) in &findings {
+            html.push_str(&self.html_finding(id, finding));
+        }
+        html.push_str("</section>\n");
+
+        let _ = writeln!(html, "<section id=\"{}\">\n<h2>{}</h2>", anchor(SECTIONS[4]), SECTIONS[4]);
+        let suppressed = &self.result.stats.suppressed_findings;
+        if suppressed.is_empty() {
+            html.push_str("<p>No finding was suppressed.</p>\n");
+        } else {
+            html.push_str(
+                "<p>Findings removed by <code>analyzer:ignore</code> comments in the code, with the reasons given by the developers, \
+                 and by the severity and confidence filters of the analysis.</p>\n\
+                 <table>\n<tr><th>Rule</th><th>Severity</th><th>Location</th><th>Suppressed by</th><th>Reason</th></tr>\n",
+            );
+            for suppressed in suppressed {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td>{:?}</td><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+                    escape(&suppressed.rule_id),
+                    suppressed.finding.severity,
+                    escape(&self.location(&suppressed.finding)),
+                    suppressed.suppressed_by.as_str(),
+                    escape(&reason(suppressed))
+                );
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str("</section>\n</body>\n</html>\n");
+        html
+    }
+
+    /// Helper function to list the fields of the cover page, the unset ones left out
+    fn cover_fields(&self) -> Vec<(&'static str, String)> {
+        let date = self.info.date.clone().unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string());
+        let mut fields = Vec::new();
+        if let Some(client) = &self.info.client {
+            fields.push(("Client", client.clone()));
+        }
+        fields.push(("Project", self.project.to_string()));
+        if let Some(commit) = &self.info.commit {
+            fields.push(("Commit", commit.clone()));
+        }
+        fields.push(("Date", date));
+        fields.push(("Tool", format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))));
+        fields
+    }
+
+    /// Helper function to describe how the findings were found
+    fn methodology(&self) -> String {
+        format!(
+            "The code was analyzed with {} {}, a static analyzer for Solana programs: {} rules matched the \
+             syntax trees of the source files against known vulnerability patterns (missing signer and owner \
+             checks, unchecked arithmetic, account validation...). Each finding has a severity, the impact of \
+             the issue, and a confidence, the likelihood that it is a true positive. Static analysis does not \
+             replace a manual review: the findings should be confirmed, and issues in the business logic can \
+             be missed.",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            self.result.stats.rules_executed
+        )
+    }
+
+    /// Helper function to describe the audited code
+    fn scope_summary(&self) -> String {
+        let stats = &self.result.stats;
+        let commit = self.info.commit.as_ref().map(|commit| format!(" at commit {commit}")).unwrap_or_default();
+        let mut scope = format!(
+            "The review covers the Rust sources of {}{commit}: {} file{} analyzed",
+            self.project,
+            stats.files_analyzed,
+            if stats.files_analyzed == 1 { "" } else { "s" }
+        );
+        if stats.total_lines_of_code() > 0 {
+            let _ = write!(scope, ", {} lines of code", stats.total_lines_of_code());
+        }
+        scope.push('.');
+        if stats.test_files_skipped > 0 {
+            let _ = write!(scope, " Test code was left out ({} files).", stats.test_files_skipped);
+        }
+        if stats.files_unparsed > 0 {
+            let _ = write!(scope, " {} files could not be parsed and were not analyzed.", stats.files_unparsed);
+        }
+        scope
+    }
+
+    /// Helper function to count the findings of each severity in a sentence
+    fn summary_sentence(&self) -> String {
+        let counts = SeverityCounts::of(&self.result.findings);
+        if counts.total() == 0 {
+            return "No vulnerabilities found.".to_string();
+        }
+        let severities: Vec<String> = Severity::ALL
+            .iter()
+            .filter(|severity| counts.get(severity) > 0)
+            .map(|severity| format!("{} {}", counts.get(severity), severity.as_str()))
+            .collect();
+        format!("{} findings: {}. The matrix counts them by severity and confidence.", counts.total(), severities.join(", "))
+    }
+
+    /// Helper function to count the findings by severity (rows) and confidence (columns)
+    fn severity_matrix(&self) -> Vec<(Severity, [usize; 3], usize)> {
+        Severity::ALL
+            .into_iter()
+            .map(|severity| {
+                let findings: Vec<&Finding> = self.result.findings.iter().filter(|finding| finding.severity == severity).collect();
+                let row = CONFIDENCES.map(|confidence| findings.iter().filter(|finding| finding.confidence == confidence).count());
+                (severity, row, findings.len())
+            })
+            .collect()
+    }
+
+    /// Helper function to render a finding in Markdown
+    fn markdown_finding(&self, id: &str, finding: &Finding) -> String {
+        let mut section = format!("### {id}: {}\n\n", title(finding));
+        section.push_str("| Severity | Confidence | Rule | Location |\n| --- | --- | --- | --- |\n");
+        let _ = writeln!(
+            section,
+            "| {:?} | {} | {} | {} |\n",
+            finding.severity,
+            finding.confidence,
+            finding.rule_ids().join(", "),
+            self.location(finding)
+        );
+        let _ = writeln!(section, "**Description:** {}\n", finding.description);
+        if let Some(snippet) = &finding.code_snippet {
+            let _ = writeln!(section, "```rust\n{}\n```\n", snippet.trim_end());
+        }
+        if !finding.recommendations.is_empty() {
+            section.push_str("**Recommendations:**\n\n");
+            for recommendation in &finding.recommendations {
+                let _ = writeln!(section, "- {recommendation}");
+            }
+            section.push('\n');
+        }
+        let references: Vec<&String> = finding.rules.iter().flat_map(|rule| &rule.references).collect();
+        if !references.is_empty() {
+            section.push_str("**References:**\n\n");
+            for reference in references {
+                let _ = writeln!(section, "- <{reference}>");
+            }
+            section.push('\n');
+        }
+        section
+    }
+
+    /// Helper function to render a finding in HTML
+    fn html_finding(&self, id: &str, finding: &Finding) -> String {
+        let mut html = format!("<div class=\"finding\" id=\"{}\">\n", id.to_lowercase());
+        let _ = writeln!(html, "<h3>{id}: {}</h3>", escape(&title(finding)));
+        let _ = writeln!(
+            html,
+            "<table>\n<tr><th>Severity</th><th>Confidence</th><th>Rule</th><th>Location</th></tr>\n\
+             <tr><td>{:?}</td><td>{}</td><td>{}</td><td><code>{}</code></td></tr>\n</table>",
+            finding.severity,
+            finding.confidence,
+            escape(&finding.rule_ids().join(", ")),
+            escape(&self.location(finding))
+        );
+        let _ = writeln!(html, "<p><b>Description:</b> {}</p>", escape(&finding.description));
+        if let Some(snippet) = &finding.code_snippet {
+            let _ = writeln!(html, "<pre><code>{}</code></pre>", highlight(snippet.trim_end()));
+        }
+        if !finding.recommendations.is_empty() {
+            html.push_str("<p><b>Recommendations:</b></p>\n<ul>\n");
+            for recommendation in &finding.recommendations {
+                let _ = writeln!(html, "<li>{}</li>", escape(recommendation));
+            }
+            html.push_str("</ul>\n");
+        }
+        let references: Vec<&String> = finding.rules.iter().flat_map(|rule| &rule.references).collect();
+        if !references.is_empty() {
+            html.push_str("<p><b>References:</b></p>\n<ul>\n");
+            for reference in references {
+                let _ = writeln!(html, "<li><a href=\"{0}\">{0}</a></li>", escape(reference));
+            }
+            html.push_str("</ul>\n");
+        }
+        html.push_str("</div>\n");
+        html
+    }
+
+    /// Helper function to locate a finding, relative to the project, with its enclosing item
+    fn location(&self, finding: &Finding) -> String {
+        let location = format!("{}:{}", self.display_path(&finding.location.file), finding.location.line);
+        match items::qualified_name(finding) {
+            Some(item) => format!("{location} ({item})"),
+            None => location,
+        }
+    }
+
+    /// Helper function to show a path relative to the project
+    fn display_path<'b>(&self, file: &'b str) -> &'b str {
+        file.strip_prefix(self.project).unwrap_or(file).trim_start_matches('/')
+    }
+}
+
+/// Headings of the sections of the report after the table of contents
+const SECTIONS: [&str; 5] = ["1. Methodology", "2. Scope", "3. Summary", "4. Findings", "Appendix A. Suppressed Findings"];
+
+/// Anchor of a section heading, the way GitHub generates it (`1. Methodology` is `1-methodology`)
+fn anchor(heading: &str) -> String {
+    heading
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Title of a finding: the title of its rule, or its description
+fn title(finding: &Finding) -> String {
+    match finding.rule() {
+        Some(rule) if !rule.title.is_empty() => rule.title.clone(),
+        _ => finding.description.clone(),
+    }
+}
+
+/// Reason of a suppression, as given in the comment
+fn reason(suppressed: &SuppressedFinding) -> String {
+    suppressed.reason.clone().unwrap_or_else(|| "No reason given".to_string())
+}
+
+/// Meaning of a severity in the methodology
+fn severity_meaning(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::High => "Loss or theft of funds, or control of the program, by any user",
+        Severity::Medium => "Loss of funds or denial of service under specific conditions",
+        Severity::Low => "Non-recommended practice that can become an issue as the code changes",
+        Severity::Informational => "Code quality and best practices, without direct risk",
+    }
+}
+
+/// Meaning of a confidence in the methodology
+fn confidence_meaning(confidence: Confidence) -> &'static str {
+    match confidence {
+        Confidence::High => "The pattern is an issue whenever it matches",
+        Confidence::Medium => "Likely issue, but the pattern can have legitimate uses",
+        Confidence::Low => "Heuristic match that needs manual review",
+    }
+}
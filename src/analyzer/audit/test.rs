@@ -0,0 +1,104 @@
+use crate::analyzer::audit::{AuditInfo, AuditReport};
+use crate::analyzer::suppressions::{SuppressedBy, SuppressedFinding};
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Confidence, Finding, RuleInfo, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(severity: Severity, confidence: Confidence, line: usize, title: &str) -> Finding {
+        FindingBuilder::new(format!("{title} in 'withdraw'"))
+            .severity(severity)
+            .confidence(confidence)
+            .span("/project/src/lib.rs", line, 4, 19)
+            .snippet("amount / shares")
+            .recommendations(&["Check the divisor"])
+            .rule(RuleInfo {
+                id: title.to_lowercase().replace(' ', "-"),
+                title: title.to_string(),
+                references: vec!["https://example.com/rule".to_string()],
+                ..Default::default()
+            })
+            .build()
+    }
+
+    fn result() -> AnalysisResult {
+        let suppressed = SuppressedFinding {
+            rule_id: "solana-unsafe-code".to_string(),
+            suppressed_by: SuppressedBy::Comment,
+            reason: Some("audited | vendored".to_string()),
+            finding: finding(Severity::Medium, Confidence::High, 30, "Unsafe Code"),
+        };
+        AnalysisResult {
+            findings: vec![
+                finding(Severity::Low, Confidence::Medium, 12, "Division By Zero"),
+                finding(Severity::High, Confidence::High, 20, "Missing Signer Check"),
+            ],
+            stats: AnalysisStats {
+                files_analyzed: 1,
+                rules_executed: 40,
+                lines_of_code: [("/project/src/lib.rs".to_string(), 120)].into(),
+                suppressed_findings: vec![suppressed],
+                ..Default::default()
+            },
+        }
+    }
+
+    fn info() -> AuditInfo {
+        AuditInfo {
+            client: Some("Acme Labs".to_string()),
+            commit: Some("4f2c9e1".to_string()),
+            date: Some("2026-10-17".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_audit_markdown() {
+        let result = result();
+        let report = AuditReport::new(&result, "/project").with_info(info()).to_markdown();
+
+        // Cover page
+        assert!(report.starts_with("# Security Audit Report\n\n**Client:** Acme Labs  \n**Project:** /project  \n**Commit:** 4f2c9e1  \n**Date:** 2026-10-17  \n"));
+        assert!(report.contains("- [1. Methodology](#1-methodology)\n"));
+        assert!(report.contains("- [Appendix A. Suppressed Findings](#appendix-a-suppressed-findings)\n"));
+        assert!(report.contains("40 rules"));
+
+        // Scope
+        assert!(report.contains("The review covers the Rust sources of /project at commit 4f2c9e1: 1 file analyzed, 120 lines of code."));
+        assert!(report.contains("| src/lib.rs | 120 |\n"));
+
+        // Severity matrix and findings, the most severe first
+        assert!(report.contains("| High | 1 | 0 | 0 | 1 |\n| Medium | 0 | 0 | 0 | 0 |\n| Low | 0 | 1 | 0 | 1 |\n"));
+        assert!(report.contains("| [SLA-001](#sla-001) | Missing Signer Check | High | High | src/lib.rs:20 |\n"));
+        assert!(report.contains("### SLA-002: Division By Zero\n"));
+        assert!(report.find("### SLA-001: Missing Signer Check").unwrap() < report.find("### SLA-002").unwrap());
+        assert!(report.contains("**Recommendations:**\n\n- Check the divisor\n"));
+
+        // Suppressed findings, with their reasons
+        assert!(report.contains("| solana-unsafe-code | Medium | src/lib.rs:30 | comment | audited \\| vendored |\n"));
+    }
+
+    #[test]
+    fn test_audit_html() {
+        let result = result();
+        let html = AuditReport::new(&result, "/project").with_info(info()).to_html();
+
+        assert!(html.contains("@page { size: A4; margin: 2cm; }"));
+        assert!(html.contains("<dt>Client</dt><dd>Acme Labs</dd>"));
+        assert!(html.contains("<div class=\"finding\" id=\"sla-001\">\n<h3>SLA-001: Missing Signer Check</h3>"));
+        assert!(html.contains("<td>comment</td><td>audited | vendored</td>"));
+    }
+
+    #[test]
+    fn test_audit_without_findings() {
+        let result = AnalysisResult { findings: Vec::new(), stats: AnalysisStats::default() };
+        let report = AuditReport::new(&result, "/project").to_markdown();
+
+        // Unset fields are left out of the cover page, the date defaults to today
+        assert!(!report.contains("**Client:**"));
+        assert!(report.contains("**Date:** "));
+        assert!(report.contains("No vulnerabilities found."));
+        assert!(report.contains("No finding was suppressed."));
+    }
+}
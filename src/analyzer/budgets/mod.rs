@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::analyzer::{AnalysisResult, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Number of findings of a severity tolerated by the `check` command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Budget {
+    /// At most this many findings
+    Limited(usize),
+    /// Any number of findings
+    Unlimited,
+}
+
+impl FromStr for Budget {
+    type Err = String;
+
+    /// Parse a budget: a number of findings, or `unlimited`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "unlimited" => Ok(Budget::Unlimited),
+            count => count
+                .parse()
+                .map(Budget::Limited)
+                .map_err(|_| format!("invalid budget '{value}' (expected a number of findings or unlimited)")),
+        }
+    }
+}
+
+impl fmt::Display for Budget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Budget::Limited(count) => write!(f, "{count}"),
+            Budget::Unlimited => write!(f, "unlimited"),
+        }
+    }
+}
+
+/// Finding budgets by severity (`[budgets]` of the configuration, `check --budget`)
+///
+/// Unlike `fail_on`, each budget counts the findings of its severity only. The severities
+/// without a budget are unlimited.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Budgets {
+    budgets: HashMap<Severity, Budget>,
+}
+
+impl Budgets {
+    /// Check if no budget is set
+    pub fn is_empty(&self) -> bool {
+        self.budgets.is_empty()
+    }
+
+    /// Set the budget of a severity, replacing the current one
+    pub fn set(&mut self, severity: Severity, budget: Budget) {
+        self.budgets.insert(severity, budget);
+    }
+
+    /// Returns the budget of a severity
+    pub fn get(&self, severity: &Severity) -> Budget {
+        self.budgets.get(severity).copied().unwrap_or(Budget::Unlimited)
+    }
+
+    /// Parse a `<severity>=<budget>` override
+    pub fn parse_override(value: &str) -> Result<(Severity, Budget), String> {
+        let Some((severity, budget)) = value.split_once('=') else {
+            return Err(format!("invalid budget '{value}' (expected <severity>=<budget>)"));
+        };
+        Ok((severity.parse()?, budget.parse()?))
+    }
+
+    /// Count the findings of each budgeted severity, the most severe first
+    pub fn check(&self, result: &AnalysisResult) -> Vec<BudgetUsage> {
+        Severity::ALL
+            .into_iter()
+            .filter_map(|severity| {
+                let budget = *self.budgets.get(&severity)?;
+                let count = result.findings.iter().filter(|finding| finding.severity == severity).count();
+                Some(BudgetUsage { severity, count, budget })
+            })
+            .collect()
+    }
+}
+
+/// Findings of a severity against its budget
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetUsage {
+    /// Severity of the budget
+    pub severity: Severity,
+    /// Number of findings of this severity
+    pub count: usize,
+    /// Budget of the severity
+    pub budget: Budget,
+}
+
+impl BudgetUsage {
+    /// Check if there are more findings than the budget allows
+    pub fn exceeded(&self) -> bool {
+        matches!(self.budget, Budget::Limited(budget) if self.count > budget)
+    }
+}
+
+impl fmt::Display for BudgetUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} findings (budget: {})", self.severity.as_str(), self.count, self.budget)
+    }
+}
@@ -0,0 +1,63 @@
+use crate::analyzer::budgets::{Budget, BudgetUsage, Budgets};
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Confidence, Finding, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(severity: Severity, line: usize) -> Finding {
+        FindingBuilder::new(format!("finding at line {line}"))
+            .severity(severity)
+            .confidence(Confidence::Medium)
+            .span("lib.rs", line, 5, 20)
+            .rule_id("error-handling")
+            .build()
+    }
+
+    #[test]
+    fn test_parse_budgets() {
+        assert_eq!("5".parse::<Budget>(), Ok(Budget::Limited(5)));
+        assert_eq!("unlimited".parse::<Budget>(), Ok(Budget::Unlimited));
+        assert!("-1".parse::<Budget>().is_err());
+        assert!("many".parse::<Budget>().is_err());
+
+        assert_eq!(Budgets::parse_override("high=0"), Ok((Severity::High, Budget::Limited(0))));
+        assert_eq!(Budgets::parse_override("low=unlimited"), Ok((Severity::Low, Budget::Unlimited)));
+        assert!(Budgets::parse_override("high").is_err());
+        assert!(Budgets::parse_override("critical=0").is_err());
+    }
+
+    #[test]
+    fn test_budgets_count_their_severity_only() {
+        let result = AnalysisResult {
+            findings: vec![
+                finding(Severity::High, 1),
+                finding(Severity::Medium, 2),
+                finding(Severity::Medium, 3),
+                finding(Severity::Low, 4),
+            ],
+            stats: AnalysisStats::default(),
+        };
+        let mut budgets = Budgets::default();
+        assert!(budgets.is_empty());
+        assert!(budgets.check(&result).is_empty());
+
+        budgets.set(Severity::Low, Budget::Unlimited);
+        budgets.set(Severity::Medium, Budget::Limited(2));
+        budgets.set(Severity::High, Budget::Limited(0));
+        assert_eq!(budgets.get(&Severity::Informational), Budget::Unlimited);
+
+        let usage = budgets.check(&result);
+        assert_eq!(
+            usage,
+            vec![
+                BudgetUsage { severity: Severity::High, count: 1, budget: Budget::Limited(0) },
+                BudgetUsage { severity: Severity::Medium, count: 2, budget: Budget::Limited(2) },
+                BudgetUsage { severity: Severity::Low, count: 1, budget: Budget::Unlimited },
+            ]
+        );
+        let exceeded: Vec<String> = usage.iter().filter(|usage| usage.exceeded()).map(ToString::to_string).collect();
+        assert_eq!(exceeded, vec!["high: 1 findings (budget: 0)"]);
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::analyzer::Finding;
+
+#[cfg(test)]
+mod test;
+
+/// Limits on the number of findings reported, so chatty rules do not bury the others
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FindingCaps {
+    /// Findings reported per rule (the first ones, in report order)
+    pub per_rule: Option<usize>,
+    /// Findings reported per file (the most severe ones, the first ones on ties)
+    pub per_file: Option<usize>,
+}
+
+impl FindingCaps {
+    /// Check if no cap is set
+    pub fn is_empty(&self) -> bool {
+        self.per_rule.is_none() && self.per_file.is_none()
+    }
+
+    /// Remove the findings over the caps, keeping the others in their order
+    ///
+    /// The rule caps apply first, then the file caps. Returns the number of findings removed, by
+    /// ID of the rule that reported them (the first rule of merged findings).
+    pub fn apply(&self, findings: &mut Vec<Finding>) -> BTreeMap<String, usize> {
+        let mut omitted: BTreeMap<String, usize> = BTreeMap::new();
+        let mut record = |finding: &Finding| {
+            let rule_id = finding.rule().map(|rule| rule.id.clone()).unwrap_or_default();
+            *omitted.entry(rule_id).or_insert(0) += 1;
+        };
+
+        if let Some(per_rule) = self.per_rule {
+            let mut counts: HashMap<String, usize> = HashMap::new();
+            findings.retain(|finding| {
+                let count = counts.entry(finding.rule().map(|rule| rule.id.clone()).unwrap_or_default()).or_insert(0);
+                *count += 1;
+                if *count > per_rule {
+                    record(finding);
+                }
+                *count <= per_rule
+            });
+        }
+
+        if let Some(per_file) = self.per_file {
+            let mut by_file: HashMap<&str, Vec<usize>> = HashMap::new();
+            for (index, finding) in findings.iter().enumerate() {
+                by_file.entry(finding.location.file.as_str()).or_default().push(index);
+            }
+            let mut kept = vec![true; findings.len()];
+            for mut indices in by_file.into_values() {
+                // Stable sort: the first findings win among findings of the same severity
+                indices.sort_by_key(|&index| std::cmp::Reverse(findings[index].severity.rank()));
+                for &index in indices.iter().skip(per_file) {
+                    kept[index] = false;
+                }
+            }
+            let mut kept = kept.into_iter();
+            findings.retain(|finding| {
+                let kept = kept.next().unwrap_or(true);
+                if !kept {
+                    record(finding);
+                }
+                kept
+            });
+        }
+
+        omitted
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+
+use crate::analyzer::caps::FindingCaps;
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{Confidence, Finding, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(rule_id: &str, severity: Severity, file: &str, line: usize) -> Finding {
+        FindingBuilder::new(format!("{rule_id} at line {line}"))
+            .severity(severity)
+            .confidence(Confidence::Medium)
+            .span(file, line, 5, 20)
+            .rule_id(rule_id)
+            .build()
+    }
+
+    fn lines(findings: &[Finding]) -> Vec<(&str, usize)> {
+        findings
+            .iter()
+            .map(|finding| (finding.location.file.as_str(), finding.location.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_no_caps_keep_every_finding() {
+        let mut findings = vec![
+            finding("error-handling", Severity::Low, "lib.rs", 1),
+            finding("error-handling", Severity::Low, "lib.rs", 2),
+        ];
+        assert!(FindingCaps::default().is_empty());
+        assert!(FindingCaps::default().apply(&mut findings).is_empty());
+        assert_eq!(findings.len(), 2);
+    }
+
+    #[test]
+    fn test_rule_caps_keep_the_first_findings_of_each_rule() {
+        let mut findings = vec![
+            finding("error-handling", Severity::Low, "lib.rs", 1),
+            finding("signer-check", Severity::High, "lib.rs", 2),
+            finding("error-handling", Severity::Low, "lib.rs", 3),
+            finding("error-handling", Severity::Low, "state.rs", 4),
+            finding("signer-check", Severity::High, "state.rs", 5),
+        ];
+        let caps = FindingCaps {
+            per_rule: Some(2),
+            per_file: None,
+        };
+        let omitted = caps.apply(&mut findings);
+
+        assert_eq!(
+            lines(&findings),
+            vec![("lib.rs", 1), ("lib.rs", 2), ("lib.rs", 3), ("state.rs", 5)]
+        );
+        assert_eq!(omitted, BTreeMap::from([("error-handling".to_string(), 1)]));
+    }
+
+    #[test]
+    fn test_file_caps_keep_the_most_severe_findings() {
+        let mut findings = vec![
+            finding("error-handling", Severity::Low, "lib.rs", 1),
+            finding("error-handling", Severity::Low, "lib.rs", 2),
+            finding("signer-check", Severity::High, "lib.rs", 3),
+            finding("overflow", Severity::Medium, "lib.rs", 4),
+            finding("error-handling", Severity::Low, "state.rs", 1),
+        ];
+        let caps = FindingCaps {
+            per_rule: None,
+            per_file: Some(2),
+        };
+        let omitted = caps.apply(&mut findings);
+
+        // The order of the findings kept does not change
+        assert_eq!(lines(&findings), vec![("lib.rs", 3), ("lib.rs", 4), ("state.rs", 1)]);
+        assert_eq!(omitted, BTreeMap::from([("error-handling".to_string(), 2)]));
+    }
+
+    #[test]
+    fn test_rule_caps_apply_before_file_caps() {
+        let mut findings = vec![
+            finding("error-handling", Severity::Low, "lib.rs", 1),
+            finding("error-handling", Severity::Low, "lib.rs", 2),
+            finding("error-handling", Severity::Low, "lib.rs", 3),
+            finding("signer-check", Severity::High, "lib.rs", 4),
+            finding("signer-check", Severity::High, "lib.rs", 5),
+        ];
+        let caps = FindingCaps {
+            per_rule: Some(1),
+            per_file: Some(2),
+        };
+        let omitted = caps.apply(&mut findings);
+
+        assert_eq!(lines(&findings), vec![("lib.rs", 1), ("lib.rs", 4)]);
+        assert_eq!(
+            omitted,
+            BTreeMap::from([("error-handling".to_string(), 2), ("signer-check".to_string(), 1)])
+        );
+    }
+}
@@ -0,0 +1,535 @@
+use log::trace;
+use proc_macro2::LineColumn;
+use std::collections::BTreeSet;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{Block, Expr, Stmt};
+
+use crate::analyzer::dsl::query::{AstNode, NodeData};
+use crate::analyzer::dsl::values::compact;
+
+#[cfg(test)]
+mod test;
+
+/// Macros aborting the function (`panic!("..")`, `unreachable!()`)
+const PANIC_MACROS: &[&str] = &["panic", "unreachable", "todo", "unimplemented"];
+
+/// Identifier of a node of a [`Cfg`] (its index in [`Cfg::nodes`])
+pub type NodeId = usize;
+
+/// Kind of a node of a [`Cfg`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CfgNodeKind {
+    /// Start of the function
+    Entry,
+    /// End of the function, reached by returns, errors propagated with `?`, failed checks and panics
+    Exit,
+    /// Statement or expression evaluated without branching, except to the exit when it
+    /// propagates an error with `?` or is a failing check (`require!`, `assert!`)
+    Statement,
+    /// Condition of an `if`, scrutinee of a `match`, guard of a match arm or `let .. else`
+    Branch,
+    /// Head of a `loop`, `while` or `for` loop, target of its back edges and `continue`s
+    Loop,
+    /// `return`, `break` or `continue`, or a panic
+    Jump,
+}
+
+/// Node of a [`Cfg`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CfgNode {
+    /// Kind of the node
+    pub kind: CfgNodeKind,
+    /// Code of the node, without the spaces around paths and calls (empty for the entry and exit)
+    pub code: String,
+    /// Start and end of the code of the node (`None` for the entry and exit)
+    pub range: Option<(LineColumn, LineColumn)>,
+    /// Nodes the control can flow to after this one
+    pub successors: Vec<NodeId>,
+    /// Nodes the control can come from
+    pub predecessors: Vec<NodeId>,
+}
+
+impl CfgNode {
+    /// Line of the node (0 for the entry and exit)
+    pub fn line(&self) -> usize {
+        self.range.map_or(0, |(start, _)| start.line)
+    }
+}
+
+/// Control-flow graph of a function body
+///
+/// Nodes are statements and the conditions they branch on: `if`, `match` (with guards),
+/// `loop`/`while`/`for` (with labeled `break` and `continue`), `let .. else`, early returns,
+/// errors propagated with `?`, `require!`/`assert!`-like checks and panics are followed.
+/// Expressions nested in other expressions (`foo(if a { 1 } else { 2 })`) are a single node,
+/// closures and nested items are not entered.
+///
+/// The dominators answer guard-before-use questions (every path to a division goes through a
+/// check of the divisor), the post-dominators what always runs after a statement, and
+/// [`Cfg::has_path`] ordering questions (a transfer can run before the state is updated).
+/// Statements no path from the entry reaches are [`Cfg::unreachable`].
+///
+/// ```ignore
+/// let cfg = Cfg::of_node(node)?;
+/// let (check, division) = (cfg.node_at(&check_expr)?, cfg.node_at(&division_expr)?);
+/// cfg.dominates(check, division)
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cfg {
+    nodes: Vec<CfgNode>,
+    /// Immediate dominator of each node (`None` for the entry and unreachable nodes)
+    dominators: Vec<Option<NodeId>>,
+    /// Immediate post-dominator of each node (`None` for the exit and nodes never reaching it)
+    post_dominators: Vec<Option<NodeId>>,
+}
+
+impl Cfg {
+    /// Entry node of every graph
+    pub const ENTRY: NodeId = 0;
+    /// Exit node of every graph
+    pub const EXIT: NodeId = 1;
+
+    /// Build the graph of a function body
+    pub fn of_block(block: &Block) -> Self {
+        let mut builder = Builder::default();
+        builder.add_node(CfgNodeKind::Entry, String::new(), None, &[]);
+        builder.add_node(CfgNodeKind::Exit, String::new(), None, &[]);
+        let ends = builder.block(block, vec![Self::ENTRY]);
+        builder.connect(&ends, Self::EXIT);
+
+        let nodes = builder.nodes;
+        let dominators = immediate_dominators(&nodes, Self::ENTRY, |node| &node.predecessors, |node| &node.successors);
+        let post_dominators = immediate_dominators(&nodes, Self::EXIT, |node| &node.successors, |node| &node.predecessors);
+        trace!("Control-flow graph built with {} nodes", nodes.len());
+        Self {
+            nodes,
+            dominators,
+            post_dominators,
+        }
+    }
+
+    /// Build the graph of a function node (`None` for other nodes)
+    pub fn of_node(node: &AstNode<'_>) -> Option<Self> {
+        match &node.data {
+            NodeData::Function(func) => Some(Self::of_block(&func.block)),
+            NodeData::ImplFunction(func) => Some(Self::of_block(&func.block)),
+            _ => None,
+        }
+    }
+
+    /// Returns the nodes of the graph, entry and exit first
+    pub fn nodes(&self) -> &[CfgNode] {
+        &self.nodes
+    }
+
+    /// Returns a node of the graph
+    pub fn node(&self, id: NodeId) -> &CfgNode {
+        &self.nodes[id]
+    }
+
+    /// Returns the innermost node whose code contains the start of a piece of code of the function
+    pub fn node_at(&self, code: &impl Spanned) -> Option<NodeId> {
+        let position = code.span().start();
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(id, node)| node.range.filter(|(start, end)| *start <= position && position < *end).map(|range| (id, range)))
+            .min_by_key(|(_, (start, end))| (end.line - start.line, std::cmp::Reverse(*start)))
+            .map(|(id, _)| id)
+    }
+
+    /// Check if a path from the entry reaches a node
+    pub fn is_reachable(&self, id: NodeId) -> bool {
+        id == Self::ENTRY || self.dominators[id].is_some()
+    }
+
+    /// Returns the nodes no path from the entry reaches (code after a `return` or a `loop`
+    /// without `break`), the exit excepted
+    pub fn unreachable(&self) -> Vec<NodeId> {
+        (Self::EXIT + 1..self.nodes.len()).filter(|&id| !self.is_reachable(id)).collect()
+    }
+
+    /// Check if every path from the entry to `node` goes through `dominator` (a node dominates itself)
+    pub fn dominates(&self, dominator: NodeId, node: NodeId) -> bool {
+        self.is_reachable(node) && chain(&self.dominators, node).any(|id| id == dominator)
+    }
+
+    /// Check if every path from `node` to the exit goes through `post_dominator` (a node
+    /// post-dominates itself)
+    pub fn post_dominates(&self, post_dominator: NodeId, node: NodeId) -> bool {
+        (node == Self::EXIT || self.post_dominators[node].is_some()) && chain(&self.post_dominators, node).any(|id| id == post_dominator)
+    }
+
+    /// Returns the nodes every path from the entry to `node` goes through, from the closest
+    /// (`node` itself) to the entry
+    pub fn dominators(&self, node: NodeId) -> Vec<NodeId> {
+        if self.is_reachable(node) { chain(&self.dominators, node).collect() } else { Vec::new() }
+    }
+
+    /// Check if a path leads from `from` to `to` (through at least one edge, so a node reaches
+    /// itself only in a loop)
+    pub fn has_path(&self, from: NodeId, to: NodeId) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut stack = self.nodes[from].successors.clone();
+        while let Some(id) = stack.pop() {
+            if id == to {
+                return true;
+            }
+            if visited.insert(id) {
+                stack.extend(&self.nodes[id].successors);
+            }
+        }
+        false
+    }
+}
+
+/// Helper function to walk a dominator tree from a node up to its root
+fn chain(dominators: &[Option<NodeId>], node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+    std::iter::successors(Some(node), move |&id| dominators[id].filter(|&dominator| dominator != id))
+}
+
+/// Helper function to compute the immediate dominators of the nodes reachable from `root`
+/// (Cooper, Harvey and Kennedy's iterative algorithm), following `forward` edges
+///
+/// The root is its own dominator in the result, unreachable nodes have none.
+fn immediate_dominators<'a>(
+    nodes: &'a [CfgNode],
+    root: NodeId,
+    backward: impl Fn(&'a CfgNode) -> &'a Vec<NodeId>,
+    forward: impl Fn(&'a CfgNode) -> &'a Vec<NodeId>,
+) -> Vec<Option<NodeId>> {
+    // Postorder of the nodes reachable from the root
+    let mut order = Vec::new();
+    let mut visited = vec![false; nodes.len()];
+    let mut stack = vec![(root, 0)];
+    visited[root] = true;
+    while let Some((id, next)) = stack.pop() {
+        match forward(&nodes[id]).get(next) {
+            Some(&successor) => {
+                stack.push((id, next + 1));
+                if !visited[successor] {
+                    visited[successor] = true;
+                    stack.push((successor, 0));
+                }
+            }
+            None => order.push(id),
+        }
+    }
+    let mut position = vec![usize::MAX; nodes.len()];
+    for (index, &id) in order.iter().enumerate() {
+        position[id] = index;
+    }
+
+    let mut dominators = vec![None; nodes.len()];
+    dominators[root] = Some(root);
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &id in order.iter().rev().filter(|&&id| id != root) {
+            let mut dominator = None;
+            for &predecessor in backward(&nodes[id]).iter().filter(|&&predecessor| dominators[predecessor].is_some()) {
+                dominator = Some(match dominator {
+                    None => predecessor,
+                    Some(other) => intersect(&dominators, &position, predecessor, other),
+                });
+            }
+            if dominator.is_some() && dominators[id] != dominator {
+                dominators[id] = dominator;
+                changed = true;
+            }
+        }
+    }
+    dominators[root] = None;
+    dominators
+}
+
+/// Helper function to find the closest common dominator of two nodes
+fn intersect(dominators: &[Option<NodeId>], position: &[usize], mut a: NodeId, mut b: NodeId) -> NodeId {
+    while a != b {
+        while position[a] < position[b] {
+            a = dominators[a].unwrap_or(b);
+        }
+        while position[b] < position[a] {
+            b = dominators[b].unwrap_or(a);
+        }
+    }
+    a
+}
+
+/// Loop (or labeled block) being built
+struct LoopFrame {
+    label: Option<String>,
+    /// Target of `continue` (`None` for labeled blocks)
+    head: Option<NodeId>,
+    /// `break`s leaving the loop
+    breaks: Vec<NodeId>,
+}
+
+/// Helper to build the nodes of a graph
+///
+/// Lowering functions take the nodes the control comes from and return the nodes it leaves
+/// from (none when the code always returns, breaks or panics).
+#[derive(Default)]
+struct Builder {
+    nodes: Vec<CfgNode>,
+    loops: Vec<LoopFrame>,
+}
+
+impl Builder {
+    fn add_node(&mut self, kind: CfgNodeKind, code: String, range: Option<(LineColumn, LineColumn)>, from: &[NodeId]) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(CfgNode {
+            kind,
+            code,
+            range,
+            successors: Vec::new(),
+            predecessors: Vec::new(),
+        });
+        self.connect(from, id);
+        id
+    }
+
+    fn connect(&mut self, from: &[NodeId], to: NodeId) {
+        for &id in from {
+            if !self.nodes[id].successors.contains(&to) {
+                self.nodes[id].successors.push(to);
+                self.nodes[to].predecessors.push(id);
+            }
+        }
+    }
+
+    /// Add the node of a piece of code
+    fn add(&mut self, kind: CfgNodeKind, code: &impl Spanned, text: String, from: &[NodeId]) -> NodeId {
+        let span = code.span();
+        self.add_node(kind, text, Some((span.start(), span.end())), from)
+    }
+
+    fn block(&mut self, block: &Block, mut from: Vec<NodeId>) -> Vec<NodeId> {
+        for stmt in &block.stmts {
+            from = self.stmt(stmt, from);
+        }
+        from
+    }
+
+    fn stmt(&mut self, stmt: &Stmt, from: Vec<NodeId>) -> Vec<NodeId> {
+        match stmt {
+            Stmt::Local(local) => {
+                let from = match &local.init {
+                    Some(init) if is_structured(&init.expr) => self.expr(&init.expr, from),
+                    _ => from,
+                };
+                let diverge = local.init.as_ref().and_then(|init| init.diverge.as_ref());
+                let kind = if diverge.is_some() { CfgNodeKind::Branch } else { CfgNodeKind::Statement };
+                let node = self.add(kind, local, compact(local), &from);
+                if local.init.as_ref().is_some_and(|init| !is_structured(&init.expr) && contains_try(&init.expr)) {
+                    self.connect(&[node], Cfg::EXIT);
+                }
+                let mut ends = vec![node];
+                if let Some((_, else_expr)) = diverge {
+                    ends.extend(self.expr(else_expr, vec![node]));
+                }
+                ends
+            }
+            Stmt::Expr(expr, _) => self.expr(expr, from),
+            Stmt::Macro(stmt_macro) => self.mac(&stmt_macro.mac, from),
+            Stmt::Item(_) => from,
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr, from: Vec<NodeId>) -> Vec<NodeId> {
+        match expr {
+            Expr::If(expr_if) => {
+                let condition = self.add(CfgNodeKind::Branch, &expr_if.cond, compact(&expr_if.cond), &from);
+                if contains_try(&expr_if.cond) {
+                    self.connect(&[condition], Cfg::EXIT);
+                }
+                let mut ends = self.block(&expr_if.then_branch, vec![condition]);
+                match &expr_if.else_branch {
+                    Some((_, else_branch)) => ends.extend(self.expr(else_branch, vec![condition])),
+                    None => ends.push(condition),
+                }
+                ends
+            }
+            Expr::Match(expr_match) => {
+                let scrutinee = self.add(CfgNodeKind::Branch, &expr_match.expr, compact(&expr_match.expr), &from);
+                if contains_try(&expr_match.expr) {
+                    self.connect(&[scrutinee], Cfg::EXIT);
+                }
+                let mut ends = Vec::new();
+                // Arms are tried in order, so a failed guard goes on with the next arms
+                let mut failed_guards = Vec::new();
+                for arm in &expr_match.arms {
+                    let mut arm_from = vec![scrutinee];
+                    arm_from.extend(&failed_guards);
+                    if let Some((_, guard)) = &arm.guard {
+                        let guard_node = self.add(CfgNodeKind::Branch, guard, compact(guard), &arm_from);
+                        failed_guards.push(guard_node);
+                        arm_from = vec![guard_node];
+                    }
+                    ends.extend(self.expr(&arm.body, arm_from));
+                }
+                ends
+            }
+            Expr::Block(expr_block) => match &expr_block.label {
+                Some(label) => {
+                    self.loops.push(LoopFrame {
+                        label: Some(label.name.ident.to_string()),
+                        head: None,
+                        breaks: Vec::new(),
+                    });
+                    let mut ends = self.block(&expr_block.block, from);
+                    ends.extend(self.loops.pop().map(|frame| frame.breaks).unwrap_or_default());
+                    ends
+                }
+                None => self.block(&expr_block.block, from),
+            },
+            Expr::Unsafe(expr_unsafe) => self.block(&expr_unsafe.block, from),
+            Expr::Loop(expr_loop) => {
+                let head = self.add(CfgNodeKind::Loop, &expr_loop.loop_token, "loop".to_string(), &from);
+                let frame = self.lower_loop(expr_loop.label.as_ref(), head, &expr_loop.body);
+                frame.breaks
+            }
+            Expr::While(expr_while) => {
+                let head = self.add(CfgNodeKind::Loop, &expr_while.cond, compact(&expr_while.cond), &from);
+                let mut frame = self.lower_loop(expr_while.label.as_ref(), head, &expr_while.body);
+                frame.breaks.push(head);
+                frame.breaks
+            }
+            Expr::ForLoop(expr_for) => {
+                let from = if is_structured(&expr_for.expr) { self.expr(&expr_for.expr, from) } else { from };
+                let text = format!("{} in {}", compact(&expr_for.pat), compact(&expr_for.expr));
+                let head = self.add(CfgNodeKind::Loop, &expr_for.pat, text, &from);
+                let mut frame = self.lower_loop(expr_for.label.as_ref(), head, &expr_for.body);
+                frame.breaks.push(head);
+                frame.breaks
+            }
+            Expr::Return(expr_return) => {
+                let from = match &expr_return.expr {
+                    Some(value) if is_structured(value) => self.expr(value, from),
+                    _ => from,
+                };
+                let node = self.add(CfgNodeKind::Jump, expr_return, compact(expr_return), &from);
+                self.connect(&[node], Cfg::EXIT);
+                Vec::new()
+            }
+            Expr::Break(expr_break) => {
+                let from = match &expr_break.expr {
+                    Some(value) if is_structured(value) => self.expr(value, from),
+                    _ => from,
+                };
+                let node = self.add(CfgNodeKind::Jump, expr_break, compact(expr_break), &from);
+                let label = expr_break.label.as_ref().map(|label| label.ident.to_string());
+                if let Some(frame) = self.frame(label.as_deref()) {
+                    frame.breaks.push(node);
+                }
+                Vec::new()
+            }
+            Expr::Continue(expr_continue) => {
+                let node = self.add(CfgNodeKind::Jump, expr_continue, compact(expr_continue), &from);
+                let label = expr_continue.label.as_ref().map(|label| label.ident.to_string());
+                if let Some(head) = self.frame(label.as_deref()).and_then(|frame| frame.head) {
+                    self.connect(&[node], head);
+                }
+                Vec::new()
+            }
+            Expr::Macro(expr_macro) => self.mac(&expr_macro.mac, from),
+            Expr::Paren(paren) if is_structured(&paren.expr) => self.expr(&paren.expr, from),
+            Expr::Assign(assign) if is_structured(&assign.right) => {
+                let from = self.expr(&assign.right, from);
+                let node = self.add(CfgNodeKind::Statement, &assign.left, format!("{} = ..", compact(&assign.left)), &from);
+                vec![node]
+            }
+            _ => {
+                let node = self.add(CfgNodeKind::Statement, expr, compact(expr), &from);
+                if contains_try(expr) {
+                    self.connect(&[node], Cfg::EXIT);
+                }
+                vec![node]
+            }
+        }
+    }
+
+    /// Lower a macro call: panics end the function, checks may end it
+    fn mac(&mut self, mac: &syn::Macro, from: Vec<NodeId>) -> Vec<NodeId> {
+        let name = mac.path.segments.last().map(|segment| segment.ident.to_string()).unwrap_or_default();
+        let text = format!("{name}!({})", compact(&mac.tokens));
+        if PANIC_MACROS.contains(&name.as_str()) {
+            let node = self.add(CfgNodeKind::Jump, mac, text, &from);
+            self.connect(&[node], Cfg::EXIT);
+            return Vec::new();
+        }
+        let node = self.add(CfgNodeKind::Statement, mac, text, &from);
+        if name.starts_with("require") || name.starts_with("assert") {
+            self.connect(&[node], Cfg::EXIT);
+        }
+        vec![node]
+    }
+
+    /// Lower the body of a loop whose head is built, returning its frame with the `break`s
+    fn lower_loop(&mut self, label: Option<&syn::Label>, head: NodeId, body: &Block) -> LoopFrame {
+        self.loops.push(LoopFrame {
+            label: label.map(|label| label.name.ident.to_string()),
+            head: Some(head),
+            breaks: Vec::new(),
+        });
+        let ends = self.block(body, vec![head]);
+        self.connect(&ends, head);
+        self.loops.pop().expect("the frame of the loop was pushed")
+    }
+
+    /// Returns the loop (or labeled block) a `break` or `continue` targets: the one with its
+    /// label, or the innermost loop
+    fn frame(&mut self, label: Option<&str>) -> Option<&mut LoopFrame> {
+        self.loops.iter_mut().rev().find(|frame| match label {
+            Some(label) => frame.label.as_deref() == Some(label),
+            None => frame.head.is_some(),
+        })
+    }
+}
+
+/// Helper function to check if an expression has its own control flow, lowered into several nodes
+fn is_structured(expr: &Expr) -> bool {
+    match expr {
+        Expr::If(_)
+        | Expr::Match(_)
+        | Expr::Block(_)
+        | Expr::Unsafe(_)
+        | Expr::Loop(_)
+        | Expr::While(_)
+        | Expr::ForLoop(_)
+        | Expr::Return(_)
+        | Expr::Break(_)
+        | Expr::Continue(_) => true,
+        Expr::Macro(expr_macro) => expr_macro.mac.path.segments.last().is_some_and(|segment| {
+            let name = segment.ident.to_string();
+            PANIC_MACROS.contains(&name.as_str()) || name.starts_with("require") || name.starts_with("assert")
+        }),
+        Expr::Paren(paren) => is_structured(&paren.expr),
+        _ => false,
+    }
+}
+
+/// Helper function to check if an expression propagates errors with `?` (closures and async
+/// blocks excepted, their `?` returns from them)
+fn contains_try(expr: &Expr) -> bool {
+    let mut finder = TryFinder(false);
+    finder.visit_expr(expr);
+    finder.0
+}
+
+/// Helper visitor to find the `?` operators of an expression
+struct TryFinder(bool);
+
+impl<'ast> Visit<'ast> for TryFinder {
+    fn visit_expr_try(&mut self, expr_try: &'ast syn::ExprTry) {
+        self.0 = true;
+        visit::visit_expr_try(self, expr_try);
+    }
+
+    fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+
+    fn visit_expr_async(&mut self, _: &'ast syn::ExprAsync) {}
+
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+}
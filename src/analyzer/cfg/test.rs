@@ -0,0 +1,157 @@
+use crate::analyzer::cfg::{Cfg, CfgNodeKind, NodeId};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper function to build the graph of a function and find its nodes by (a part of) their code
+    fn graph(source: &str) -> (Cfg, impl Fn(&str) -> NodeId) {
+        let func: syn::ItemFn = syn::parse_str(source).unwrap();
+        let cfg = Cfg::of_block(&func.block);
+        let codes: Vec<String> = cfg.nodes().iter().map(|node| node.code.clone()).collect();
+        let find = move |code: &str| {
+            codes
+                .iter()
+                .position(|node| node == code)
+                .or_else(|| codes.iter().position(|node| node.contains(code)))
+                .unwrap_or_else(|| panic!("no node '{code}' in {codes:#?}"))
+        };
+        (cfg, find)
+    }
+
+    #[test]
+    fn test_guards_dominate_uses() {
+        let (cfg, node) = graph(
+            r#"
+fn withdraw(ctx: Context<Withdraw>, amount: u64, shares: u64) -> Result<()> {
+    let vault = load(&ctx)?;
+    if vault.paused {
+        return err!(VaultError::Paused);
+    }
+    if amount > 100 {
+        log(amount);
+    }
+    require!(shares > 0, VaultError::Zero);
+    let out = amount / shares;
+    let fee = match vault.mode {
+        Mode::Free => 0,
+        Mode::Flat if vault.fee > 0 => vault.fee,
+        _ => return Err(VaultError::Mode.into()),
+    };
+    transfer(out - fee)?;
+    vault.total -= out;
+    Ok(())
+}
+"#,
+        );
+        let division = node("let out");
+        assert!(cfg.dominates(node("require!(shares > 0, VaultError::Zero)"), division));
+        assert!(cfg.dominates(node("vault.paused"), division));
+        assert!(!cfg.dominates(node("log(amount)"), division));
+        assert!(cfg.dominates(node("amount > 100"), division));
+        assert_eq!(cfg.dominators(division).last(), Some(&Cfg::ENTRY));
+
+        // `?`, returns and failed checks leave for the exit
+        let load = node("let vault");
+        assert!(cfg.node(load).successors.contains(&Cfg::EXIT));
+        assert!(!cfg.post_dominates(division, load));
+        assert!(cfg.post_dominates(node("Ok(())"), node("vault.total -= out")));
+        assert!(cfg.post_dominates(Cfg::EXIT, load));
+
+        // A guard only applies to its arm, a failed guard goes on with the next arm
+        let guard = node("vault.fee > 0");
+        assert_eq!(cfg.node(guard).kind, CfgNodeKind::Branch);
+        assert!(cfg.dominates(guard, node("vault.fee")));
+        assert!(!cfg.dominates(guard, node("0")));
+        assert!(cfg.has_path(guard, node("return Err(VaultError::Mode.into())")));
+
+        // Checks-effects ordering: the transfer runs before the state is updated
+        assert!(cfg.has_path(node("transfer(out - fee)?"), node("vault.total -= out")));
+        assert!(!cfg.has_path(node("vault.total -= out"), node("transfer(out - fee)?")));
+        assert!(cfg.unreachable().is_empty());
+    }
+
+    #[test]
+    fn test_loops_and_unreachable_code() {
+        let (cfg, node) = graph(
+            r#"
+fn process(items: &[Item]) -> u64 {
+    let mut total = 0;
+    'outer: for item in items {
+        while item.pending() {
+            if item.skip {
+                continue 'outer;
+            }
+            if item.last {
+                break 'outer;
+            }
+            total += item.amount;
+        }
+        total += 1;
+    }
+    loop {
+        total -= 1;
+        if total == 0 {
+            break;
+        }
+    }
+    let found = 'search: {
+        if total > 10 {
+            break 'search true;
+        }
+        false
+    };
+    loop {
+        poll();
+    }
+    finish(found);
+    panic!("done");
+}
+"#,
+        );
+        let head = node("item in items");
+        assert_eq!(cfg.node(head).kind, CfgNodeKind::Loop);
+        assert!(cfg.node(node("continue 'outer")).successors.contains(&head));
+        assert!(cfg.node(node("break 'outer")).successors.contains(&node("loop")));
+        assert!(cfg.has_path(node("total += item.amount"), node("item.pending()")));
+        assert!(cfg.has_path(node("total += 1"), head));
+        assert!(!cfg.dominates(node("total += item.amount"), node("total += 1")));
+
+        // The loop without `break` never ends: what follows is unreachable
+        let unreachable: Vec<&str> = cfg.unreachable().into_iter().map(|id| cfg.node(id).code.as_str()).collect();
+        assert_eq!(unreachable, ["finish(found)", "panic!(\"done\")"]);
+        assert!(cfg.is_reachable(node("false")));
+        assert!(!cfg.is_reachable(Cfg::EXIT));
+        assert!(cfg.dominators(node("finish(found)")).is_empty());
+    }
+
+    #[test]
+    fn test_let_else_and_nested_code() {
+        let source = r#"
+fn parse(data: &[u8]) -> Result<u64> {
+    let Some(first) = data.first() else {
+        return Err(ErrorCode::Empty.into());
+    };
+    let value = if *first == 0 { 0 } else { decode(data)? };
+    Ok(value)
+}
+"#;
+        let (cfg, node) = graph(source);
+        let let_else = node("let Some(first)");
+        assert_eq!(cfg.node(let_else).kind, CfgNodeKind::Branch);
+        assert!(cfg.dominates(let_else, node("Ok(value)")));
+        assert!(cfg.node(node("decode(data)?")).successors.contains(&Cfg::EXIT));
+
+        // Code of the function resolves to the innermost node containing it
+        let func: syn::ItemFn = syn::parse_str(source).unwrap();
+        let syn::Stmt::Local(local) = &func.block.stmts[1] else {
+            panic!("the second statement is a let binding");
+        };
+        let syn::Expr::If(expr_if) = &*local.init.as_ref().unwrap().expr else {
+            panic!("the value is an if");
+        };
+        assert_eq!(cfg.node_at(&expr_if.cond), Some(node("* first == 0")));
+        assert_eq!(cfg.node_at(local), Some(node("let value")));
+        assert_eq!(cfg.node(node("Ok(value)")).line(), 7);
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::Finding;
+use crate::analyzer::fingerprint::fingerprint_of;
+use crate::analyzer::json::SCHEMA_VERSION;
+
+#[cfg(test)]
+mod test;
+
+/// Error returned when the report of a previous run cannot be loaded
+#[derive(Debug)]
+pub enum CompareError {
+    /// The report cannot be read
+    Io { path: PathBuf, error: std::io::Error },
+    /// The report is not a JSON report of the analyzer
+    Parse { path: PathBuf, error: serde_json::Error },
+    /// The report was written with another version of the JSON schema
+    UnsupportedSchema { path: PathBuf, version: u32 },
+}
+
+impl fmt::Display for CompareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompareError::Io { path, error } => write!(f, "cannot read report {}: {error}", path.display()),
+            CompareError::Parse { path, error } => write!(f, "invalid JSON report {}: {error}", path.display()),
+            CompareError::UnsupportedSchema { path, version } => write!(
+                f,
+                "report {} has schema version {version}, only version {SCHEMA_VERSION} can be compared",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CompareError {}
+
+/// Fields of a JSON report (`--format json`) needed to compare it
+#[derive(Deserialize)]
+struct PreviousReport {
+    schema_version: u32,
+    findings: Vec<Finding>,
+}
+
+/// Load the findings of a previous run from its JSON report
+pub fn load_findings(path: &Path) -> Result<Vec<Finding>, CompareError> {
+    let json = fs::read_to_string(path).map_err(|error| CompareError::Io { path: path.to_path_buf(), error })?;
+    let report: PreviousReport =
+        serde_json::from_str(&json).map_err(|error| CompareError::Parse { path: path.to_path_buf(), error })?;
+    if report.schema_version != SCHEMA_VERSION {
+        return Err(CompareError::UnsupportedSchema { path: path.to_path_buf(), version: report.schema_version });
+    }
+    Ok(report.findings)
+}
+
+/// Number of new, fixed and persisting findings of a [`Comparison`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ComparisonSummary {
+    pub new: usize,
+    pub fixed: usize,
+    pub persisting: usize,
+}
+
+/// Findings of two runs matched by fingerprint (`--compare`)
+///
+/// Fingerprints do not change when unrelated edits shift the code, so a finding moved by a few
+/// lines persists; a finding whose code or enclosing item changed is fixed and reported again as
+/// new. Findings with the same fingerprint are matched one to one.
+#[derive(Debug, Clone, Default)]
+pub struct Comparison {
+    /// Findings of the current run that the previous run did not report
+    pub new: Vec<Finding>,
+    /// Findings of the previous run that the current run no longer reports
+    pub fixed: Vec<Finding>,
+    /// Findings of the current run that the previous run also reported
+    pub persisting: Vec<Finding>,
+}
+
+impl Comparison {
+    /// Compare the findings of a previous run with the findings of the current run
+    pub fn of(previous: &[Finding], current: &[Finding]) -> Self {
+        let mut comparison = Self::default();
+
+        let mut unmatched = fingerprint_counts(previous);
+        for finding in current {
+            match unmatched.get_mut(&fingerprint_of(finding, None)) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    comparison.persisting.push(finding.clone());
+                }
+                _ => comparison.new.push(finding.clone()),
+            }
+        }
+
+        let mut unmatched = fingerprint_counts(current);
+        for finding in previous {
+            match unmatched.get_mut(&fingerprint_of(finding, None)) {
+                Some(count) if *count > 0 => *count -= 1,
+                _ => comparison.fixed.push(finding.clone()),
+            }
+        }
+        comparison
+    }
+
+    /// Returns the number of new, fixed and persisting findings
+    pub fn summary(&self) -> ComparisonSummary {
+        ComparisonSummary { new: self.new.len(), fixed: self.fixed.len(), persisting: self.persisting.len() }
+    }
+}
+
+/// Helper function to count the findings of each fingerprint
+fn fingerprint_counts(findings: &[Finding]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for finding in findings {
+        *counts.entry(fingerprint_of(finding, None)).or_insert(0) += 1;
+    }
+    counts
+}
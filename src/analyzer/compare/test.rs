@@ -0,0 +1,56 @@
+use crate::analyzer::compare::{CompareError, Comparison, ComparisonSummary, load_findings};
+use crate::analyzer::json::JsonReport;
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Finding};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(fingerprint: &str, line: usize) -> Finding {
+        FindingBuilder::new(format!("Division by zero at line {line}"))
+            .span("src/lib.rs", line, 5, 20)
+            .rule_id("division-by-zero")
+            .fingerprint(fingerprint)
+            .build()
+    }
+
+    fn lines(findings: &[Finding]) -> Vec<usize> {
+        findings.iter().map(|finding| finding.location.line).collect()
+    }
+
+    #[test]
+    fn test_findings_are_matched_by_fingerprint() {
+        let previous = [finding("a", 10), finding("b", 20), finding("c", 30), finding("c", 31)];
+        // "a" moved, "b" was fixed, one of the "c" was fixed, "d" is new
+        let current = [finding("a", 14), finding("c", 34), finding("d", 40)];
+
+        let comparison = Comparison::of(&previous, &current);
+        assert_eq!(lines(&comparison.new), vec![40]);
+        assert_eq!(lines(&comparison.fixed), vec![20, 31]);
+        assert_eq!(lines(&comparison.persisting), vec![14, 34]);
+        assert_eq!(comparison.summary(), ComparisonSummary { new: 1, fixed: 2, persisting: 2 });
+    }
+
+    #[test]
+    fn test_previous_findings_are_loaded_from_json_reports() {
+        let dir = std::env::temp_dir().join(format!("solana-analyzer-compare-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let result = AnalysisResult { findings: vec![finding("a", 10)], stats: AnalysisStats::default() };
+        let report = dir.join("report.json");
+        std::fs::write(&report, JsonReport::new(&result, "project").to_json()).unwrap();
+        let findings = load_findings(&report).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].fingerprint.as_deref(), Some("a"));
+
+        let future = dir.join("future.json");
+        std::fs::write(&future, r#"{ "schema_version": 99, "findings": [] }"#).unwrap();
+        assert!(matches!(load_findings(&future), Err(CompareError::UnsupportedSchema { version: 99, .. })));
+        assert!(matches!(load_findings(&dir.join("missing.json")), Err(CompareError::Io { .. })));
+        std::fs::write(&future, "# Report").unwrap();
+        assert!(matches!(load_findings(&future), Err(CompareError::Parse { .. })));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,509 @@
+use log::{debug, info};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::audit::AuditInfo;
+use crate::analyzer::ignore::glob_to_regex;
+use crate::analyzer::budgets::Budgets;
+use crate::analyzer::output::parse_formats;
+use crate::analyzer::packs::resolve_profile;
+use crate::analyzer::remote;
+use crate::analyzer::report_templates::{Escape, ReportTemplate};
+use crate::analyzer::{AnalysisOptions, Confidence, MAX_CONTEXT_LINES, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Name of the configuration file looked up in the project directory and its parents
+pub const CONFIG_FILE_NAME: &str = "solana-analyzer.toml";
+
+/// Report formats supported by `output.format` (see [`Format`](crate::analyzer::output::Format))
+pub const OUTPUT_FORMATS: &[&str] = &[
+    "markdown", "json", "html", "csv", "junit", "github", "gitlab", "rdjson", "sarif", "audit", "audit-html", "template",
+    "ndjson",
+];
+
+/// Error returned when a configuration file cannot be loaded
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The configuration file cannot be read
+    Io { path: String, error: std::io::Error },
+    /// The configuration is not valid TOML or does not follow the schema
+    Parse { path: String, error: String },
+    /// A field of the configuration has an invalid value
+    InvalidField { field: String, reason: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, error } => write!(f, "cannot read configuration {path}: {error}"),
+            ConfigError::Parse { path, error } => write!(f, "invalid configuration {path}: {error}"),
+            ConfigError::InvalidField { field, reason } => write!(f, "invalid configuration field '{field}': {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Analyzer configuration (`solana-analyzer.toml`)
+///
+/// ```toml
+/// [rules]
+/// profile = "anchor"                       # --profile
+/// include = ["missing-signer-check"]       # --rules
+/// exclude = ["anchor-instructions"]        # --ignore-rules
+/// tags = ["security"]                      # --tags
+/// ignore_severities = ["informational"]    # --ignore
+///
+/// [rules.severity]                         # --severity
+/// duplicate-mutable-accounts = "high"
+///
+/// [rules.params.missing-signer-check]      # --rule-param
+/// authority_names = ["admin", "owner"]
+///
+/// [paths]
+/// exclude = ["tests", "**/generated/*.rs"] # not analyzed
+/// templates = "rules"                      # --templates (or a remote rule pack URL)
+/// plugins = "plugins"                      # --plugins
+///
+/// [output]
+/// path = "report.md"                       # --output
+/// format = "markdown"                    # --format (markdown, json, html, csv, junit, github, gitlab, rdjson, sarif,
+///                                          # audit, audit-html, template, ndjson),
+///                                          # several ones separated by commas: "markdown,json"
+/// max_per_rule = 20                        # --max-per-rule
+/// max_per_file = 50                        # --max-per-file
+/// context = 3                              # --context
+/// template = "reports/internal.md.hbs"     # --report-template (or markdown, html)
+/// template_escape = "html"                 # --template-escape (none, html)
+///
+/// [thresholds]
+/// min_confidence = "medium"                # --min-confidence
+/// fail_on = "high"                         # --fail-on
+/// max_findings = 0                         # --max-findings
+///
+/// [budgets]                                # findings tolerated by `check`, by severity
+/// high = 0                                 # --budget high=0
+/// medium = 5
+/// low = "unlimited"                        # the default
+///
+/// [audit]                                  # cover page of --format audit and audit-html
+/// client = "Acme Labs"
+/// commit = "4f2c9e1"
+/// date = "2026-10-17"                      # today if not set
+/// ```
+///
+/// Every field is optional. Layers are merged field by field (see [`Config::merge`]), and
+/// relative paths are resolved from the directory of the file that sets them.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Rule selection, severities and parameters
+    #[serde(default)]
+    pub rules: RulesConfig,
+    /// Analyzed files and custom rule locations
+    #[serde(default)]
+    pub paths: PathsConfig,
+    /// Report settings
+    #[serde(default)]
+    pub output: OutputConfig,
+    /// Reporting and failure thresholds
+    #[serde(default)]
+    pub thresholds: ThresholdsConfig,
+    /// Findings tolerated by `check`, by severity (a number or `unlimited`)
+    #[serde(default)]
+    pub budgets: BTreeMap<String, toml::Value>,
+    /// Cover page of the audit reports
+    #[serde(default)]
+    pub audit: AuditConfig,
+}
+
+/// `[rules]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RulesConfig {
+    /// Profile or packs to run
+    pub profile: Option<String>,
+    /// Rule IDs to run
+    pub include: Option<Vec<String>>,
+    /// Rule IDs to ignore
+    pub exclude: Option<Vec<String>>,
+    /// Tags of the rules to run
+    pub tags: Option<Vec<String>>,
+    /// Severities to ignore
+    pub ignore_severities: Option<Vec<String>>,
+    /// Severity overrides by rule ID
+    #[serde(default)]
+    pub severity: BTreeMap<String, String>,
+    /// Parameter overrides by rule ID
+    #[serde(default)]
+    pub params: BTreeMap<String, BTreeMap<String, toml::Value>>,
+}
+
+/// `[paths]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PathsConfig {
+    /// Files and directories not analyzed (globs relative to the analyzed directory)
+    pub exclude: Option<Vec<String>>,
+    /// Custom rule templates directory, or remote rule pack URL
+    pub templates: Option<PathBuf>,
+    /// WebAssembly rule plugins directory
+    pub plugins: Option<PathBuf>,
+}
+
+/// `[output]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutputConfig {
+    /// Report file
+    pub path: Option<PathBuf>,
+    /// Report formats, separated by commas
+    pub format: Option<String>,
+    /// Findings reported per rule, the others are counted in the report
+    pub max_per_rule: Option<usize>,
+    /// Findings reported per file, the others are counted in the report
+    pub max_per_file: Option<usize>,
+    /// Lines of source shown before and after each finding
+    pub context: Option<usize>,
+    /// Template of `--format template`, a file or a builtin template name
+    pub template: Option<PathBuf>,
+    /// Escaping of the values of the template (`none` or `html`)
+    pub template_escape: Option<String>,
+}
+
+/// `[audit]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuditConfig {
+    /// Client the audit is delivered to
+    pub client: Option<String>,
+    /// Audited commit
+    pub commit: Option<String>,
+    /// Date of the report
+    pub date: Option<String>,
+}
+
+/// `[thresholds]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThresholdsConfig {
+    /// Minimum confidence of the reported findings
+    pub min_confidence: Option<String>,
+    /// Severity from which findings make the analysis fail
+    pub fail_on: Option<String>,
+    /// Number of findings (from the `fail_on` severity) tolerated before the analysis fails
+    pub max_findings: Option<usize>,
+}
+
+impl Config {
+    /// Parse a configuration, resolving its relative paths from `base_dir`
+    pub fn from_toml(content: &str, path: &str, base_dir: &Path) -> Result<Self, ConfigError> {
+        let mut config: Config = toml::from_str(content).map_err(|error| ConfigError::Parse {
+            path: path.to_string(),
+            error: error.to_string(),
+        })?;
+
+        for dir in [&mut config.paths.templates, &mut config.paths.plugins, &mut config.output.path]
+            .into_iter()
+            .flatten()
+        {
+            // Remote rule packs are URLs, not paths
+            if dir.is_relative() && !remote::is_remote(&dir.to_string_lossy()) {
+                *dir = base_dir.join(&*dir);
+            }
+        }
+        // Builtin templates are named, not resolved
+        if let Some(template) = &mut config.output.template
+            && template.is_relative()
+            && ReportTemplate::builtin(&template.to_string_lossy()).is_none()
+        {
+            *template = base_dir.join(&*template);
+        }
+        Ok(config)
+    }
+
+    /// Load a configuration file
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let path_str = path.display().to_string();
+        let content = std::fs::read_to_string(path).map_err(|error| ConfigError::Io {
+            path: path_str.clone(),
+            error,
+        })?;
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        Self::from_toml(&content, &path_str, base_dir)
+    }
+
+    /// Find the project configuration: the first `solana-analyzer.toml` in the project
+    /// directory or one of its parents
+    pub fn find_project_file(project_dir: &Path) -> Option<PathBuf> {
+        let project_dir = project_dir.canonicalize().ok()?;
+        project_dir
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Load the layered configuration: the project file, then the explicit `--config` file
+    pub fn load(project_dir: &Path, explicit: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut config = Config::default();
+        for path in Self::find_project_file(project_dir).into_iter().chain(explicit.map(Path::to_path_buf)) {
+            info!("Loading configuration from {}", path.display());
+            config.merge(Self::from_file(&path)?);
+        }
+        Ok(config)
+    }
+
+    /// Merge a configuration layer on top of this one
+    ///
+    /// Fields set by the layer replace the current values, severity and parameter
+    /// overrides are merged by rule (and by parameter).
+    pub fn merge(&mut self, layer: Config) {
+        fn replace<T>(current: &mut Option<T>, layer: Option<T>) {
+            if layer.is_some() {
+                *current = layer;
+            }
+        }
+
+        let rules = layer.rules;
+        replace(&mut self.rules.profile, rules.profile);
+        replace(&mut self.rules.include, rules.include);
+        replace(&mut self.rules.exclude, rules.exclude);
+        replace(&mut self.rules.tags, rules.tags);
+        replace(&mut self.rules.ignore_severities, rules.ignore_severities);
+        self.rules.severity.extend(rules.severity);
+        for (rule_id, params) in rules.params {
+            self.rules.params.entry(rule_id).or_default().extend(params);
+        }
+
+        replace(&mut self.paths.exclude, layer.paths.exclude);
+        replace(&mut self.paths.templates, layer.paths.templates);
+        replace(&mut self.paths.plugins, layer.paths.plugins);
+        replace(&mut self.output.path, layer.output.path);
+        replace(&mut self.output.format, layer.output.format);
+        replace(&mut self.output.max_per_rule, layer.output.max_per_rule);
+        replace(&mut self.output.max_per_file, layer.output.max_per_file);
+        replace(&mut self.output.context, layer.output.context);
+        replace(&mut self.output.template, layer.output.template);
+        replace(&mut self.output.template_escape, layer.output.template_escape);
+        replace(&mut self.thresholds.min_confidence, layer.thresholds.min_confidence);
+        replace(&mut self.thresholds.fail_on, layer.thresholds.fail_on);
+        replace(&mut self.thresholds.max_findings, layer.thresholds.max_findings);
+        self.budgets.extend(layer.budgets);
+        replace(&mut self.audit.client, layer.audit.client);
+        replace(&mut self.audit.commit, layer.audit.commit);
+        replace(&mut self.audit.date, layer.audit.date);
+    }
+
+    /// Apply the analysis settings to the options (the CLI flags are applied afterwards)
+    pub fn apply(&self, options: &mut AnalysisOptions) -> Result<(), ConfigError> {
+        let rules = &self.rules;
+        if let Some(profile) = &rules.profile {
+            resolve_profile(profile).map_err(|reason| invalid("rules.profile", reason))?;
+            options.profile = Some(profile.clone());
+        }
+        if let Some(include) = &rules.include {
+            options.include_rules = include.clone();
+        }
+        if let Some(exclude) = &rules.exclude {
+            options.ignore_rules = exclude.clone();
+        }
+        if let Some(tags) = &rules.tags {
+            options.include_tags = tags.clone();
+        }
+        if let Some(severities) = &rules.ignore_severities {
+            options.ignore_severities = severities
+                .iter()
+                .map(|severity| severity.parse().map_err(|reason| invalid("rules.ignore_severities", reason)))
+                .collect::<Result<_, _>>()?;
+        }
+        for (rule_id, severity) in &rules.severity {
+            let severity = severity
+                .parse()
+                .map_err(|reason| invalid(&format!("rules.severity.{rule_id}"), reason))?;
+            options.severity_overrides.insert(rule_id.clone(), severity);
+        }
+        for (rule_id, params) in &rules.params {
+            let overrides = options.rule_params.entry(rule_id.clone()).or_default();
+            for (param, value) in params {
+                let field = format!("rules.params.{rule_id}.{param}");
+                overrides.insert(param.clone(), raw_param_value(value).map_err(|reason| invalid(&field, reason))?);
+            }
+        }
+
+        if let Some(templates) = &self.paths.templates {
+            options.custom_templates_path = Some(templates.to_string_lossy().to_string());
+        }
+        if let Some(plugins) = &self.paths.plugins {
+            options.plugins_path = Some(plugins.to_string_lossy().to_string());
+        }
+        options.min_confidence = self.min_confidence()?.or(options.min_confidence);
+        options.finding_caps.per_rule = self.output.max_per_rule.or(options.finding_caps.per_rule);
+        options.finding_caps.per_file = self.output.max_per_file.or(options.finding_caps.per_file);
+        if let Some(context) = self.output.context {
+            if context > MAX_CONTEXT_LINES {
+                return Err(invalid("output.context", format!("at most {MAX_CONTEXT_LINES} lines")));
+            }
+            options.context_lines = context;
+        }
+
+        debug!("Configuration applied: {options:?}");
+        Ok(())
+    }
+
+    /// Minimum confidence of the reported findings
+    pub fn min_confidence(&self) -> Result<Option<Confidence>, ConfigError> {
+        self.thresholds
+            .min_confidence
+            .as_deref()
+            .map(|confidence| confidence.parse().map_err(|reason| invalid("thresholds.min_confidence", reason)))
+            .transpose()
+    }
+
+    /// Severity from which findings make the analysis fail
+    pub fn fail_on(&self) -> Result<Option<Severity>, ConfigError> {
+        self.thresholds
+            .fail_on
+            .as_deref()
+            .map(|severity| severity.parse().map_err(|reason| invalid("thresholds.fail_on", reason)))
+            .transpose()
+    }
+
+    /// Findings tolerated by `check`, by severity
+    pub fn budgets(&self) -> Result<Budgets, ConfigError> {
+        let mut budgets = Budgets::default();
+        for (severity, budget) in &self.budgets {
+            let field = format!("budgets.{severity}");
+            let severity = severity.parse().map_err(|reason| invalid(&field, reason))?;
+            let budget = match budget {
+                toml::Value::Integer(count) => count.to_string(),
+                toml::Value::String(budget) => budget.clone(),
+                budget => return Err(invalid(&field, format!("unsupported value {budget}"))),
+            };
+            budgets.set(severity, budget.parse().map_err(|reason| invalid(&field, reason))?);
+        }
+        Ok(budgets)
+    }
+
+    /// Report formats, checked against the supported formats
+    pub fn output_format(&self) -> Result<Option<&str>, ConfigError> {
+        match self.output.format.as_deref() {
+            Some(format) => match parse_formats(format) {
+                Ok(_) => Ok(Some(format)),
+                Err(e) => Err(invalid("output.format", e.to_string())),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Template of `--format template`, loaded from its file
+    pub fn report_template(&self) -> Result<Option<ReportTemplate>, ConfigError> {
+        self.output
+            .template
+            .as_deref()
+            .map(|template| ReportTemplate::load(template).map_err(|e| invalid("output.template", e.to_string())))
+            .transpose()
+    }
+
+    /// Escaping of the values of the report template
+    pub fn template_escape(&self) -> Result<Option<Escape>, ConfigError> {
+        self.output
+            .template_escape
+            .as_deref()
+            .map(|escape| escape.parse().map_err(|e: String| invalid("output.template_escape", e)))
+            .transpose()
+    }
+
+    /// Fields of the cover page of the audit reports
+    pub fn audit_info(&self) -> AuditInfo {
+        AuditInfo {
+            client: self.audit.client.clone(),
+            commit: self.audit.commit.clone(),
+            date: self.audit.date.clone(),
+        }
+    }
+
+    /// Compile the excluded paths
+    pub fn path_filter(&self) -> Result<PathFilter, ConfigError> {
+        PathFilter::new(self.paths.exclude.as_deref().unwrap_or_default())
+    }
+}
+
+/// Helper function to build an `InvalidField` error
+fn invalid(field: &str, reason: impl Into<String>) -> ConfigError {
+    ConfigError::InvalidField {
+        field: field.to_string(),
+        reason: reason.into(),
+    }
+}
+
+/// Helper function to convert a TOML parameter value into the raw value parsed by the rule
+///
+/// Arrays become comma-separated lists, like on the command line.
+fn raw_param_value(value: &toml::Value) -> Result<String, String> {
+    match value {
+        toml::Value::String(value) => Ok(value.clone()),
+        toml::Value::Integer(value) => Ok(value.to_string()),
+        toml::Value::Float(value) => Ok(value.to_string()),
+        toml::Value::Boolean(value) => Ok(value.to_string()),
+        toml::Value::Array(values) => Ok(values
+            .iter()
+            .map(raw_param_value)
+            .collect::<Result<Vec<_>, _>>()?
+            .join(",")),
+        _ => Err(format!("unsupported value {value}")),
+    }
+}
+
+/// Excluded files and directories
+///
+/// Patterns are globs on the path relative to the analyzed directory (`*` and `?` do not
+/// match `/`, `**` matches any number of directories). A pattern matching a directory
+/// excludes everything under it.
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    patterns: Vec<(String, Regex)>,
+}
+
+impl PathFilter {
+    /// Compile the exclusion patterns
+    pub fn new(patterns: &[String]) -> Result<Self, ConfigError> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(&anchored_glob(pattern))
+                    .map(|regex| (pattern.clone(), regex))
+                    .map_err(|e| invalid("paths.exclude", format!("invalid pattern '{pattern}': {e}")))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Check if a path (relative to the analyzed directory) is excluded
+    pub fn is_excluded(&self, relative_path: &Path) -> bool {
+        self.matching(relative_path).is_some()
+    }
+
+    /// Returns the first pattern excluding a path (relative to the analyzed directory)
+    pub fn matching(&self, relative_path: &Path) -> Option<&str> {
+        let path = relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if path.is_empty() {
+            return None;
+        }
+        self.patterns.iter().find(|(_, regex)| regex.is_match(&path)).map(|(pattern, _)| pattern.as_str())
+    }
+}
+
+/// Helper function to convert a glob into an anchored regex also matching the paths under it
+fn anchored_glob(glob: &str) -> String {
+    let glob = glob.trim_start_matches("./").trim_end_matches('/');
+    format!("^{}(?:/.*)?$", glob_to_regex(glob))
+}
@@ -0,0 +1,159 @@
+use crate::analyzer::budgets::Budget;
+use crate::analyzer::config::{Config, ConfigError, PathFilter};
+use crate::analyzer::report_templates::Escape;
+use crate::analyzer::{AnalysisOptions, Confidence, Severity};
+use std::path::Path;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[rules]
+profile = "anchor"
+exclude = ["anchor-instructions"]
+tags = ["security"]
+ignore_severities = ["informational"]
+
+[rules.severity]
+duplicate-mutable-accounts = "high"
+
+[rules.params.missing-signer-check]
+authority_names = ["admin", "owner"]
+
+[paths]
+exclude = ["tests", "**/generated/*.rs"]
+templates = "rules"
+
+[output]
+max_per_rule = 20
+context = 3
+
+[thresholds]
+min_confidence = "medium"
+fail_on = "high"
+max_findings = 2
+
+[budgets]
+high = 0
+medium = 5
+
+[audit]
+client = "Acme Labs"
+commit = "4f2c9e1"
+"#;
+
+    fn parse(content: &str) -> Result<Config, ConfigError> {
+        Config::from_toml(content, "solana-analyzer.toml", Path::new("/project"))
+    }
+
+    #[test]
+    fn test_config_applies_to_options() {
+        let config = parse(CONFIG).unwrap();
+        let mut options = AnalysisOptions::default();
+        config.apply(&mut options).unwrap();
+
+        assert_eq!(options.profile.as_deref(), Some("anchor"));
+        assert_eq!(options.ignore_rules, ["anchor-instructions"]);
+        assert_eq!(options.include_tags, ["security"]);
+        assert_eq!(options.ignore_severities, [Severity::Informational]);
+        assert_eq!(options.severity_overrides["duplicate-mutable-accounts"], Severity::High);
+        assert_eq!(options.rule_params["missing-signer-check"]["authority_names"], "admin,owner");
+        // Relative paths are resolved from the directory of the configuration
+        assert_eq!(options.custom_templates_path.as_deref(), Some("/project/rules"));
+        assert_eq!(options.min_confidence, Some(Confidence::Medium));
+        assert_eq!(config.fail_on().unwrap(), Some(Severity::High));
+        assert_eq!(config.thresholds.max_findings, Some(2));
+        assert_eq!(options.finding_caps.per_rule, Some(20));
+        assert_eq!(options.finding_caps.per_file, None);
+        assert_eq!(options.context_lines, 3);
+        let budgets = config.budgets().unwrap();
+        assert_eq!(budgets.get(&Severity::High), Budget::Limited(0));
+        assert_eq!(budgets.get(&Severity::Medium), Budget::Limited(5));
+        assert_eq!(budgets.get(&Severity::Low), Budget::Unlimited);
+
+        let audit = config.audit_info();
+        assert_eq!(audit.client.as_deref(), Some("Acme Labs"));
+        assert_eq!(audit.commit.as_deref(), Some("4f2c9e1"));
+        assert_eq!(audit.date, None);
+    }
+
+    #[test]
+    fn test_config_layers() {
+        let mut config = parse(CONFIG).unwrap();
+        let layer = parse(
+            r#"
+[rules]
+tags = ["arithmetic"]
+
+[rules.severity]
+owner-check = "low"
+
+[rules.params.missing-signer-check]
+other = 1
+
+[budgets]
+medium = "unlimited"
+"#,
+        )
+        .unwrap();
+        config.merge(layer);
+
+        // Fields set by the layer win, the others are kept
+        assert_eq!(config.rules.tags.as_deref(), Some(&["arithmetic".to_string()][..]));
+        assert_eq!(config.rules.profile.as_deref(), Some("anchor"));
+        assert_eq!(config.rules.severity.len(), 2);
+        assert_eq!(config.rules.params["missing-signer-check"].len(), 2);
+        let budgets = config.budgets().unwrap();
+        assert_eq!(budgets.get(&Severity::High), Budget::Limited(0));
+        assert_eq!(budgets.get(&Severity::Medium), Budget::Unlimited);
+    }
+
+    #[test]
+    fn test_config_errors() {
+        assert!(matches!(parse("[rules]\nunknown = 1"), Err(ConfigError::Parse { .. })));
+
+        let bad_severity = CONFIG.replace("= \"high\"\n\n[rules.params", "= \"critical\"\n\n[rules.params");
+        let result = parse(&bad_severity).unwrap().apply(&mut AnalysisOptions::default());
+        assert!(matches!(result, Err(ConfigError::InvalidField { field, .. }) if field == "rules.severity.duplicate-mutable-accounts"));
+
+        let bad_profile = CONFIG.replace("profile = \"anchor\"", "profile = \"Anchor\"");
+        assert!(parse(&bad_profile).unwrap().apply(&mut AnalysisOptions::default()).is_err());
+
+        let bad_format = parse("[output]\nformat = \"pdf\"").unwrap();
+        assert!(bad_format.output_format().is_err());
+        assert_eq!(parse("[output]\nformat = \"md,json\"").unwrap().output_format().unwrap(), Some("md,json"));
+
+        let bad_budget = parse("[budgets]\nmedium = \"some\"").unwrap().budgets();
+        assert!(matches!(bad_budget, Err(ConfigError::InvalidField { field, .. }) if field == "budgets.medium"));
+        let bad_budget_severity = parse("[budgets]\ncritical = 0").unwrap().budgets();
+        assert!(matches!(bad_budget_severity, Err(ConfigError::InvalidField { field, .. }) if field == "budgets.critical"));
+
+        let too_much_context = parse("[output]\ncontext = 50").unwrap().apply(&mut AnalysisOptions::default());
+        assert!(matches!(too_much_context, Err(ConfigError::InvalidField { field, .. }) if field == "output.context"));
+
+        // Builtin templates are named, files are resolved from the configuration
+        let builtin = parse("[output]\ntemplate = \"html\"").unwrap();
+        assert_eq!(builtin.report_template().unwrap().unwrap().name(), "report.html.hbs");
+        let missing = parse("[output]\ntemplate = \"reports/internal.md.hbs\"").unwrap();
+        assert_eq!(missing.output.template.as_deref(), Some(Path::new("/project/reports/internal.md.hbs")));
+        assert!(matches!(missing.report_template(), Err(ConfigError::InvalidField { field, .. }) if field == "output.template"));
+        let escape = parse("[output]\ntemplate_escape = \"html\"").unwrap();
+        assert_eq!(escape.template_escape().unwrap(), Some(Escape::Html));
+        let bad_escape = parse("[output]\ntemplate_escape = \"xml\"").unwrap().template_escape();
+        assert!(matches!(bad_escape, Err(ConfigError::InvalidField { field, .. }) if field == "output.template_escape"));
+    }
+
+    #[test]
+    fn test_path_filter() {
+        let filter = parse(CONFIG).unwrap().path_filter().unwrap();
+        assert!(filter.is_excluded(Path::new("tests")));
+        assert!(filter.is_excluded(Path::new("tests/integration.rs")));
+        assert!(filter.is_excluded(Path::new("generated/idl.rs")));
+        assert!(filter.is_excluded(Path::new("programs/vault/generated/idl.rs")));
+        assert!(!filter.is_excluded(Path::new("programs/vault/src/lib.rs")));
+        assert!(!filter.is_excluded(Path::new("src/tests_helpers.rs")));
+
+        assert!(!PathFilter::default().is_excluded(Path::new("tests")));
+    }
+}
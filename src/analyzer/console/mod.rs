@@ -0,0 +1,367 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::io::IsTerminal;
+use std::str::FromStr;
+
+use crate::analyzer::compare::Comparison;
+use crate::analyzer::dedup::MERGED_DESCRIPTIONS_KEY;
+use crate::analyzer::dsl::query::MetadataValue;
+use crate::analyzer::expansion::EXPANDED_LOCATION_KEY;
+use crate::analyzer::items;
+use crate::analyzer::results::{self, SeverityCounts, Sources};
+use crate::analyzer::workspace::PROGRAM_KEY;
+use crate::analyzer::{AnalysisResult, Finding, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Lines of a multi-line finding shown before the others are elided
+pub const MAX_LINES: usize = 6;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const GUTTER: &str = "\x1b[1;34m";
+
+/// When the console output is colored (`--color`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// When the output is a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether to color an output, `terminal` if it is a terminal
+    pub fn enabled(&self, terminal: bool) -> bool {
+        match self {
+            ColorChoice::Auto => terminal && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+
+    /// Whether to color the standard output
+    pub fn for_stdout(&self) -> bool {
+        self.enabled(std::io::stdout().is_terminal())
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!("unknown color choice '{value}' (expected auto, always or never)")),
+        }
+    }
+}
+
+impl fmt::Display for ColorChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorChoice::Auto => write!(f, "auto"),
+            ColorChoice::Always => write!(f, "always"),
+            ColorChoice::Never => write!(f, "never"),
+        }
+    }
+}
+
+/// How the findings are printed to the console (`--message-format`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageFormat {
+    /// Diagnostics with the code of the findings and the summary (see [`ConsoleReport::to_text`])
+    #[default]
+    Human,
+    /// One `path:line:col: severity[rule-id]: message` line per finding (see [`ConsoleReport::to_short`])
+    Short,
+}
+
+impl FromStr for MessageFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "human" => Ok(MessageFormat::Human),
+            "short" => Ok(MessageFormat::Short),
+            _ => Err(format!("unknown message format '{value}' (expected human or short)")),
+        }
+    }
+}
+
+impl fmt::Display for MessageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageFormat::Human => write!(f, "human"),
+            MessageFormat::Short => write!(f, "short"),
+        }
+    }
+}
+
+/// Findings of an analysis as compiler-style diagnostics, printed to the console
+///
+/// ```text
+/// high[missing-signer-check]: Missing Signer Check in 'withdraw'
+///   --> programs/vault/src/lib.rs:12:5
+///    |
+/// 12 |     pub authority: AccountInfo<'info>,
+///    |     ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ confidence: high
+///    = also reported: ...
+/// ```
+///
+/// The findings are grouped by crate when the workspace has several of them, and followed by
+/// the number of findings of each severity.
+#[derive(Debug)]
+pub struct ConsoleReport<'a> {
+    result: &'a AnalysisResult,
+    color: bool,
+    comparison: Option<&'a Comparison>,
+    sources: Option<&'a Sources>,
+}
+
+impl<'a> ConsoleReport<'a> {
+    /// Creates the diagnostics of an analysis, without colors
+    pub fn new(result: &'a AnalysisResult) -> Self {
+        Self { result, color: false, comparison: None, sources: None }
+    }
+
+    /// Color the severities, the underlines and the gutter with ANSI escape codes
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Follow the summary with the number of new, fixed and persisting findings since a previous run
+    pub fn with_comparison(mut self, comparison: &'a Comparison) -> Self {
+        self.comparison = Some(comparison);
+        self
+    }
+
+    /// Show the code of the files held in memory instead of reading them from the disk
+    pub fn with_sources(mut self, sources: &'a Sources) -> Self {
+        self.sources = Some(sources);
+        self
+    }
+
+    /// Returns the diagnostics of every finding and the summary
+    pub fn to_text(&self) -> String {
+        let findings = &self.result.findings;
+        if findings.is_empty() {
+            return format!("No vulnerabilities found\n{}", self.trend());
+        }
+
+        let sources: BTreeMap<&str, Option<Vec<String>>> = results::group_by_file(findings)
+            .into_keys()
+            .map(|file| (file, results::source_lines(file, self.sources)))
+            .collect();
+        let source = |finding: &Finding| sources.get(finding.location.file.as_str()).and_then(Option::as_deref);
+
+        let mut text = String::new();
+        let by_program = &self.result.stats.findings_by_program;
+        if by_program.len() > 1 {
+            let in_program = |finding: &Finding, program: Option<&str>| match finding.metadata.get(PROGRAM_KEY) {
+                Some(MetadataValue::Text(name)) => Some(name.as_str()) == program,
+                _ => program.is_none(),
+            };
+            for program in by_program.keys().map(|program| Some(program.as_str())).chain([None]) {
+                let findings: Vec<&Finding> = findings.iter().filter(|finding| in_program(finding, program)).collect();
+                if !findings.is_empty() {
+                    let heading = format!("===== {} =====", program.unwrap_or("Outside the workspace crates"));
+                    let _ = writeln!(text, "{}\n", self.paint(BOLD, &heading));
+                    for finding in findings {
+                        text.push_str(&self.diagnostic(finding, source(finding)));
+                    }
+                }
+            }
+        } else {
+            for finding in findings {
+                text.push_str(&self.diagnostic(finding, source(finding)));
+            }
+        }
+
+        text.push_str(&self.summary());
+        text.push_str(&self.trend());
+        text
+    }
+
+    /// Returns one line per finding, like the short messages of rustc, without the summary
+    ///
+    /// ```text
+    /// programs/vault/src/lib.rs:12:5: high[missing-signer-check]: Missing Signer Check in 'withdraw'
+    /// ```
+    pub fn to_short(&self) -> String {
+        self.result.findings.iter().map(|finding| self.short_message(finding) + "\n").collect()
+    }
+
+    /// Returns the line of a finding: its location (the column is 1-indexed, 1 when unknown), its
+    /// severity, its rule and its description on one line
+    pub fn short_message(&self, finding: &Finding) -> String {
+        let location = &finding.location;
+        let label = match finding.rule() {
+            Some(rule) => format!("{}[{}]", finding.severity.as_str(), rule.id),
+            None => finding.severity.as_str().to_string(),
+        };
+        let description = finding.description.split_whitespace().collect::<Vec<_>>().join(" ");
+        format!(
+            "{}:{}:{}: {}: {description}",
+            location.file,
+            location.line,
+            location.column.map_or(1, |column| column + 1),
+            self.severity(&finding.severity, &label)
+        )
+    }
+
+    /// Returns the diagnostic of a finding, with its lines of `source` (the lines of its file)
+    pub fn diagnostic(&self, finding: &Finding, source: Option<&[String]>) -> String {
+        let location = &finding.location;
+        let rule_id = finding.rule().map_or("", |rule| rule.id.as_str());
+        let mut text = String::new();
+
+        // Header and location
+        let label = if rule_id.is_empty() {
+            self.severity(&finding.severity, finding.severity.as_str())
+        } else {
+            self.severity(&finding.severity, &format!("{}[{rule_id}]", finding.severity.as_str()))
+        };
+        let _ = writeln!(text, "{label}{}", self.paint(BOLD, &format!(": {}", finding.description)));
+
+        let end_line = location.end_line.unwrap_or(location.line).max(location.line);
+        let width = end_line.to_string().len();
+        let pad = " ".repeat(width);
+        let column = location.column.map(|column| format!(":{}", column + 1)).unwrap_or_default();
+        let _ = writeln!(text, "{pad}{} {}:{}{column}", self.paint(GUTTER, "-->"), location.file, location.line);
+
+        // Source lines, underlined
+        let note = format!("confidence: {}", finding.confidence.to_string().to_lowercase());
+        let lines = source.filter(|lines| location.line > 0 && end_line <= lines.len());
+        let bar = self.paint(GUTTER, "|");
+        match lines {
+            Some(lines) => {
+                let _ = writeln!(text, "{pad} {bar}");
+                if end_line == location.line {
+                    let line = &lines[location.line - 1];
+                    let _ = writeln!(text, "{} {bar} {line}", self.paint(GUTTER, &format!("{:>width$}", location.line)));
+                    let _ = writeln!(text, "{pad} {bar} {}", self.underline(finding, line, &note));
+                } else {
+                    // Long spans keep their first lines and their last one
+                    let elided = end_line - location.line + 1 > MAX_LINES;
+                    for number in location.line..=end_line {
+                        if elided && number > location.line + MAX_LINES - 2 && number < end_line {
+                            if number == location.line + MAX_LINES - 1 {
+                                let _ = writeln!(text, "{pad} {bar} {} ...", self.severity(&finding.severity, "|"));
+                            }
+                            continue;
+                        }
+                        let _ = writeln!(
+                            text,
+                            "{} {bar} {} {}",
+                            self.paint(GUTTER, &format!("{number:>width$}")),
+                            self.severity(&finding.severity, "|"),
+                            lines[number - 1]
+                        );
+                    }
+                    let _ = writeln!(text, "{pad} {bar} {}", self.severity(&finding.severity, &format!("|_ {note}")));
+                }
+            }
+            None => {
+                // The snippet when the file cannot be read
+                if let Some(snippet) = &finding.code_snippet {
+                    let _ = writeln!(text, "{pad} {bar}");
+                    for line in snippet.lines() {
+                        let _ = writeln!(text, "{pad} {bar} {line}");
+                    }
+                }
+                let _ = writeln!(text, "{pad} {} {note}", self.paint(GUTTER, "="));
+            }
+        }
+
+        // Notes
+        let equals = self.paint(GUTTER, "=");
+        if let Some(item) = items::qualified_name(finding) {
+            let _ = writeln!(text, "{pad} {equals} in: {item}");
+        }
+        if finding.rules.len() > 1 {
+            let _ = writeln!(text, "{pad} {equals} rules: {}", finding.rule_ids().join(", "));
+        }
+        if let Some(MetadataValue::List(descriptions)) = finding.metadata.get(MERGED_DESCRIPTIONS_KEY) {
+            for description in descriptions {
+                let _ = writeln!(text, "{pad} {equals} also reported: {description}");
+            }
+        }
+        if let Some(MetadataValue::Text(expanded)) = finding.metadata.get(EXPANDED_LOCATION_KEY) {
+            let _ = writeln!(text, "{pad} {equals} expanded code: {expanded}");
+        }
+        text.push('\n');
+        text
+    }
+
+    /// Returns the number of findings of each severity, e.g. "3 findings: 1 high, 2 medium"
+    pub fn summary(&self) -> String {
+        let counts = SeverityCounts::of(&self.result.findings);
+        let severities: Vec<String> = Severity::ALL
+            .iter()
+            .filter(|severity| counts.get(severity) > 0)
+            .map(|severity| self.severity(severity, &format!("{} {}", counts.get(severity), severity.as_str())))
+            .collect();
+        let total = counts.total();
+        format!(
+            "{}: {}\n",
+            self.paint(BOLD, &format!("{total} finding{}", if total == 1 { "" } else { "s" })),
+            severities.join(", ")
+        )
+    }
+
+    /// Returns the number of new, fixed and persisting findings since the previous run (empty
+    /// without comparison)
+    pub fn trend(&self) -> String {
+        let Some(comparison) = self.comparison else {
+            return String::new();
+        };
+        let summary = comparison.summary();
+        format!(
+            "Since the previous run: {}, {}, {} persisting\n",
+            self.severity(&Severity::High, &format!("{} new", summary.new)),
+            self.severity(&Severity::Informational, &format!("{} fixed", summary.fixed)),
+            summary.persisting
+        )
+    }
+
+    /// Carets under the span of a single-line finding, followed by `note`
+    fn underline(&self, finding: &Finding, line: &str, note: &str) -> String {
+        let location = &finding.location;
+        let length = line.chars().count();
+        let (start, end) = match (location.column, location.end_column) {
+            (Some(start), Some(end)) if start < end && end <= length => (start, end),
+            (Some(start), _) if start < length => (start, length),
+            // The whole line, without its indentation
+            _ => (line.chars().take_while(|c| c.is_whitespace()).count(), length),
+        };
+        // Tabs are kept so the carets line up with the code
+        let indent: String = line.chars().take(start).map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+        let carets = "^".repeat((end - start).max(1));
+        format!("{indent}{}", self.severity(&finding.severity, &format!("{carets} {note}")))
+    }
+
+    /// `text` in the color of a severity
+    fn severity(&self, severity: &Severity, text: &str) -> String {
+        let code = match severity {
+            Severity::High => "\x1b[1;31m",
+            Severity::Medium => "\x1b[1;33m",
+            Severity::Low => "\x1b[1;36m",
+            Severity::Informational => "\x1b[1;32m",
+        };
+        self.paint(code, text)
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.color {
+            format!("{code}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::analyzer::compare::Comparison;
+use crate::analyzer::console::{ColorChoice, ConsoleReport, MessageFormat};
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Finding, Location, RuleInfo, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(findings: Vec<Finding>) -> AnalysisResult {
+        AnalysisResult { findings, stats: AnalysisStats::default() }
+    }
+
+    fn lines(source: &str) -> Vec<String> {
+        source.lines().map(str::to_string).collect()
+    }
+
+    fn finding(severity: Severity, location: Location) -> Finding {
+        FindingBuilder::new("Division Without Zero Check in 'f'")
+            .severity(severity)
+            .location(location)
+            .snippet("amount / shares")
+            .rule(RuleInfo {
+                id: "solana-division-by-zero".to_string(),
+                title: "Division Without Zero Check".to_string(),
+                ..Default::default()
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let source = lines("pub fn f(amount: u64, shares: u64) -> u64 {\n    amount / shares\n}\n");
+        let report_result = result(Vec::new());
+        let report = ConsoleReport::new(&report_result);
+
+        // Single-line findings are underlined
+        let single = finding(Severity::Medium, Location::new_precise("lib.rs".to_string(), 2, Some(4), Some(2), Some(19)));
+        assert_eq!(
+            report.diagnostic(&single, Some(&source)),
+            "medium[solana-division-by-zero]: Division Without Zero Check in 'f'\n \
+             --> lib.rs:2:5\n  \
+             |\n\
+             2 |     amount / shares\n  \
+             |     ^^^^^^^^^^^^^^^ confidence: high\n\n"
+        );
+
+        // Multi-line findings are marked in the margin
+        let multi = finding(Severity::High, Location::new_precise("lib.rs".to_string(), 1, Some(0), Some(3), Some(1)));
+        assert_eq!(
+            report.diagnostic(&multi, Some(&source)),
+            "high[solana-division-by-zero]: Division Without Zero Check in 'f'\n \
+             --> lib.rs:1:1\n  \
+             |\n\
+             1 | | pub fn f(amount: u64, shares: u64) -> u64 {\n\
+             2 | |     amount / shares\n\
+             3 | | }\n  \
+             | |_ confidence: high\n\n"
+        );
+
+        // The snippet when the file cannot be read
+        assert_eq!(
+            report.diagnostic(&single, None),
+            "medium[solana-division-by-zero]: Division Without Zero Check in 'f'\n \
+             --> lib.rs:2:5\n  |\n  | amount / shares\n  = confidence: high\n\n"
+        );
+    }
+
+    #[test]
+    fn test_long_spans_are_elided() {
+        let source = lines(&(1..=20).map(|number| format!("line {number}")).collect::<Vec<_>>().join("\n"));
+        let report_result = result(Vec::new());
+        let diagnostic = ConsoleReport::new(&report_result)
+            .diagnostic(&finding(Severity::Low, Location::new_precise("lib.rs".to_string(), 2, Some(0), Some(12), Some(7))), Some(&source));
+
+        assert!(diagnostic.contains(" 6 | | line 6\n   | | ...\n12 | | line 12\n"));
+        assert!(!diagnostic.contains("line 7"));
+    }
+
+    #[test]
+    fn test_summary_and_colors() {
+        let analysis = result(vec![
+            finding(Severity::High, Location::new_precise("missing.rs".to_string(), 1, None, None, None)),
+            finding(Severity::Low, Location::new_precise("missing.rs".to_string(), 2, None, None, None)),
+            finding(Severity::Low, Location::new_precise("missing.rs".to_string(), 3, None, None, None)),
+        ]);
+
+        let text = ConsoleReport::new(&analysis).to_text();
+        assert!(text.ends_with("\n3 findings: 1 high, 2 low\n"));
+        assert!(!text.contains('\x1b'));
+
+        let colored = ConsoleReport::new(&analysis).with_color(true).to_text();
+        assert!(colored.contains("\x1b[1;31mhigh[solana-division-by-zero]\x1b[0m"));
+
+        assert_eq!(ConsoleReport::new(&result(Vec::new())).to_text(), "No vulnerabilities found\n");
+
+        // The trend since a previous run that reported the high finding only
+        let comparison = Comparison::of(&analysis.findings[..1], &analysis.findings);
+        let text = ConsoleReport::new(&analysis).with_comparison(&comparison).to_text();
+        assert!(text.ends_with("\n3 findings: 1 high, 2 low\nSince the previous run: 2 new, 0 fixed, 1 persisting\n"));
+    }
+
+    #[test]
+    fn test_short_messages() {
+        let mut multiline = finding(Severity::Low, Location::new_precise("src/lib.rs".to_string(), 2, None, None, None));
+        multiline.description = "Missing Error Handling in 'f'.\n  Public functions should return Result".to_string();
+        multiline.rules.clear();
+        let analysis = result(vec![
+            finding(Severity::High, Location::new_precise("src/lib.rs".to_string(), 12, Some(4), None, None)),
+            multiline,
+        ]);
+
+        assert_eq!(
+            ConsoleReport::new(&analysis).to_short(),
+            "src/lib.rs:12:5: high[solana-division-by-zero]: Division Without Zero Check in 'f'\n\
+             src/lib.rs:2:1: low: Missing Error Handling in 'f'. Public functions should return Result\n"
+        );
+        assert_eq!(ConsoleReport::new(&result(Vec::new())).to_short(), "");
+
+        assert_eq!("short".parse::<MessageFormat>(), Ok(MessageFormat::Short));
+        assert!("json".parse::<MessageFormat>().is_err());
+    }
+
+    #[test]
+    fn test_color_choice() {
+        assert_eq!("Always".parse::<ColorChoice>(), Ok(ColorChoice::Always));
+        assert!("sometimes".parse::<ColorChoice>().is_err());
+        assert!(ColorChoice::Always.enabled(false));
+        assert!(!ColorChoice::Never.enabled(true));
+        assert!(!ColorChoice::Auto.enabled(false));
+    }
+}
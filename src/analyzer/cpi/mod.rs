@@ -0,0 +1,606 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::fs;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{Block, Expr, File, Item, Signature};
+
+use crate::analyzer::dsl::ProgramContext;
+use crate::analyzer::dsl::call_graph::FunctionSummary;
+use crate::analyzer::dsl::program::DeclarationKind;
+use crate::analyzer::dsl::values::compact;
+use crate::analyzer::json::Tool;
+use crate::analyzer::native;
+
+#[cfg(test)]
+mod test;
+
+/// Version of the CPI graph schema, incremented on any change that is not an addition
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Functions invoking an instruction (`invoke(&ix, &accounts)`)
+const INVOKE_FUNCTIONS: &[&str] = &["invoke", "invoke_signed", "invoke_unchecked", "invoke_signed_unchecked"];
+
+/// Conversions looked through to get the account of a program (`token_program.to_account_info()`)
+const CONVERSIONS: &[&str] = &[".to_account_info()", ".clone()", ".as_ref()"];
+
+/// How a cross-program invocation is made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CpiKind {
+    /// `invoke(..)` or `invoke_signed(..)` of an instruction, or `.invoke()` of a Pinocchio
+    /// instruction struct
+    Invoke,
+    /// `CpiContext::new(..)` or `CpiContext::new_with_signer(..)`
+    CpiContext,
+}
+
+/// How the program invoked is constrained
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetCheck {
+    /// The program is fixed by its type or crate (`Program<'info, Token>`, `spl_token::instruction`,
+    /// `spl_token::ID`)
+    Typed,
+    /// The program is an account whose address is checked (`address = ...` constraint, or a
+    /// check of its key in the function or its helpers)
+    AddressChecked,
+    /// The program is an account the caller chooses, without any check of its address
+    Arbitrary,
+    /// The program could not be resolved (instruction built by a helper, or passed in)
+    Unknown,
+}
+
+impl TargetCheck {
+    /// Returns the name of the check in the reports
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TargetCheck::Typed => "typed",
+            TargetCheck::AddressChecked => "address_checked",
+            TargetCheck::Arbitrary => "arbitrary",
+            TargetCheck::Unknown => "unknown",
+        }
+    }
+}
+
+/// Cross-program invocation of a program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CpiSite {
+    pub kind: CpiKind,
+    /// Whether the invocation signs for PDAs (`invoke_signed`, `CpiContext::new_with_signer`)
+    pub signed: bool,
+    /// Program invoked: its type or crate when it is typed (`Token`, `spl_token`,
+    /// `system_program`), else the account or expression giving its ID
+    pub target: String,
+    /// Account whose key is the program ID, if the program comes from an account
+    pub program_account: Option<String>,
+    pub check: TargetCheck,
+    /// Function making the invocation (`Deposit::transfer` for methods)
+    pub function: String,
+    pub file: String,
+    pub line: usize,
+    /// Handlers making the invocation, directly or through the helpers they call
+    pub instructions: Vec<String>,
+}
+
+/// Edge of the CPI graph: an instruction invoking a program
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct CpiEdge {
+    pub instruction: String,
+    pub target: String,
+    pub check: TargetCheck,
+}
+
+/// Cross-program invocations of a program, with the programs invoked by each instruction
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ProgramCpis {
+    /// Crate of the workspace, `None` when the files are analyzed as one program
+    pub program: Option<String>,
+    /// Invocations, in the order of the files
+    pub sites: Vec<CpiSite>,
+    /// Programs invoked by each instruction, sorted by instruction
+    pub edges: Vec<CpiEdge>,
+}
+
+impl ProgramCpis {
+    /// Collects the invocations of the given files (path and AST) of a program, keeping the ones
+    /// whose file and line are accepted by `include` (e.g. the ones outside of the test code)
+    pub fn build<'a>(
+        program: Option<String>,
+        files: impl IntoIterator<Item = (&'a str, &'a File)>,
+        context: &ProgramContext,
+        include: impl Fn(&str, usize) -> bool,
+    ) -> Self {
+        let call_graph = context.call_graph();
+        // Functions reachable from each handler, to credit the invocations of helpers
+        let reachable: Vec<(&str, BTreeSet<String>)> = call_graph
+            .instructions()
+            .filter(|function| include(&function.file, function.line))
+            .map(|function| (function.name.as_str(), call_graph.reachable_from([function.name.as_str()])))
+            .collect();
+
+        let mut sites = Vec::new();
+        for (file_path, ast) in files {
+            let mut collector = FunctionCollector { functions: Vec::new() };
+            collector.collect_items(&ast.items);
+            for (sig, block) in collector.functions {
+                let line = sig.ident.span().start().line;
+                let Some(function) = call_graph
+                    .function(&sig.ident.to_string())
+                    .iter()
+                    .find(|function| function.file == file_path && function.line == line)
+                else {
+                    continue;
+                };
+                let resolver = TargetResolver { context, file: file_path, sig, block, function };
+                for mut site in resolver.sites() {
+                    if include(file_path, site.line) {
+                        site.instructions = reachable
+                            .iter()
+                            .filter(|(_, functions)| functions.contains(&function.name))
+                            .map(|(instruction, _)| instruction.to_string())
+                            .collect::<BTreeSet<String>>()
+                            .into_iter()
+                            .collect();
+                        sites.push(site);
+                    }
+                }
+            }
+        }
+
+        let edges: BTreeSet<CpiEdge> = sites
+            .iter()
+            .flat_map(|site| {
+                site.instructions.iter().map(|instruction| CpiEdge {
+                    instruction: instruction.clone(),
+                    target: site.target.clone(),
+                    check: site.check,
+                })
+            })
+            .collect();
+        Self { program, sites, edges: edges.into_iter().collect() }
+    }
+
+    /// Check if the program makes no cross-program invocation
+    pub fn is_empty(&self) -> bool {
+        self.sites.is_empty()
+    }
+}
+
+/// CPI graph of the programs of a project (`--cpi-graph`), their external attack surface
+///
+/// ```json
+/// {
+///   "schema_version": 1,
+///   "tool": { "name": "rust-solana-analyzer", "version": "0.1.0" },
+///   "project": "programs/vault",
+///   "programs": [
+///     {
+///       "program": "vault",
+///       "sites": [{
+///         "kind": "cpi_context", "signed": true, "target": "Token", "program_account": "token_program",
+///         "check": "typed", "function": "withdraw", "file": "...", "line": 30, "instructions": ["withdraw"]
+///       }],
+///       "edges": [{ "instruction": "withdraw", "target": "Token", "check": "typed" }]
+///     }
+///   ]
+/// }
+/// ```
+///
+/// The DOT graph links each instruction to the programs it invokes, the edges labeled with the
+/// check of the target: arbitrary targets are red, address-checked ones dashed and unknown ones
+/// dotted.
+#[derive(Debug, Serialize)]
+pub struct CpiGraphReport<'a> {
+    /// Version of the schema ([`SCHEMA_VERSION`])
+    pub schema_version: u32,
+    /// Analyzer that produced the report
+    pub tool: Tool,
+    /// Analyzed path
+    pub project: &'a str,
+    /// Invocations of each program
+    pub programs: &'a [ProgramCpis],
+}
+
+impl<'a> CpiGraphReport<'a> {
+    /// Creates the CPI graph of the programs of `project`
+    pub fn new(programs: &'a [ProgramCpis], project: &'a str) -> Self {
+        Self { schema_version: SCHEMA_VERSION, tool: Tool::default(), project, programs }
+    }
+
+    /// Returns the graph as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("CPI graph serializes to JSON");
+        json.push('\n');
+        json
+    }
+
+    /// Returns the graph in the DOT language of Graphviz, one cluster per program
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph cpi {\n    rankdir=LR;\n    node [fontname=\"Helvetica\"];\n");
+        for (index, program) in self.programs.iter().filter(|program| !program.is_empty()).enumerate() {
+            let _ = writeln!(dot, "    subgraph cluster_{index} {{");
+            let _ = writeln!(dot, "        label={};", quote(program.program.as_deref().unwrap_or("program")));
+            let instructions: BTreeSet<&str> = program.edges.iter().map(|edge| edge.instruction.as_str()).collect();
+            for instruction in instructions {
+                let _ = writeln!(
+                    dot,
+                    "        {} [label={}, shape=box];",
+                    quote(&format!("{index}:instruction:{instruction}")),
+                    quote(instruction)
+                );
+            }
+            let targets: BTreeSet<&str> = program.edges.iter().map(|edge| edge.target.as_str()).collect();
+            for target in targets {
+                let _ = writeln!(
+                    dot,
+                    "        {} [label={}, shape=ellipse];",
+                    quote(&format!("{index}:program:{target}")),
+                    quote(target)
+                );
+            }
+            for edge in &program.edges {
+                let style = match edge.check {
+                    TargetCheck::Typed => "",
+                    TargetCheck::AddressChecked => ", style=dashed",
+                    TargetCheck::Arbitrary => ", color=red, fontcolor=red",
+                    TargetCheck::Unknown => ", style=dotted",
+                };
+                let _ = writeln!(
+                    dot,
+                    "        {} -> {} [label={}{style}];",
+                    quote(&format!("{index}:instruction:{}", edge.instruction)),
+                    quote(&format!("{index}:program:{}", edge.target)),
+                    quote(edge.check.as_str())
+                );
+            }
+            dot.push_str("    }\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Save the graph to a file, as DOT if its extension is `dot` or `gv` and as JSON otherwise
+    pub fn save(&self, output_path: &str) -> Result<(), std::io::Error> {
+        let dot = std::path::Path::new(output_path)
+            .extension()
+            .is_some_and(|extension| extension == "dot" || extension == "gv");
+        fs::write(output_path, if dot { self.to_dot() } else { self.to_json() })
+    }
+}
+
+/// Helper to collect the functions and methods of a file, in nested modules too
+struct FunctionCollector<'a> {
+    functions: Vec<(&'a Signature, &'a Block)>,
+}
+
+impl<'a> FunctionCollector<'a> {
+    fn collect_items(&mut self, items: &'a [Item]) {
+        for item in items {
+            match item {
+                Item::Fn(func) => self.functions.push((&func.sig, &func.block)),
+                Item::Mod(module) => {
+                    if let Some((_, items)) = &module.content {
+                        self.collect_items(items);
+                    }
+                }
+                Item::Impl(impl_block) => {
+                    for impl_item in &impl_block.items {
+                        if let syn::ImplItem::Fn(func) = impl_item {
+                            self.functions.push((&func.sig, &func.block));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Helper to find the invocations of a function and resolve the programs they invoke
+struct TargetResolver<'a> {
+    context: &'a ProgramContext,
+    file: &'a str,
+    sig: &'a Signature,
+    block: &'a Block,
+    function: &'a FunctionSummary,
+}
+
+impl TargetResolver<'_> {
+    /// Returns the invocations of the function, without their instructions
+    fn sites(&self) -> Vec<CpiSite> {
+        let mut finder = InvocationFinder::default();
+        finder.visit_block(self.block);
+        let function = match &self.function.owner {
+            Some(owner) => format!("{owner}::{}", self.function.name),
+            None => self.function.name.clone(),
+        };
+        finder
+            .invocations
+            .iter()
+            .map(|invocation| {
+                let (target, program_account, check) = match invocation.kind {
+                    CpiKind::Invoke => self.instruction_target(&invocation.expr, &finder.bindings, 0),
+                    CpiKind::CpiContext => self.account_target(&invocation.expr),
+                };
+                CpiSite {
+                    kind: invocation.kind,
+                    signed: invocation.signed,
+                    target,
+                    program_account,
+                    check,
+                    function: function.clone(),
+                    file: self.file.to_string(),
+                    line: invocation.line,
+                    instructions: Vec::new(),
+                }
+            })
+            .collect()
+    }
+
+    /// Resolve the program of an instruction: built with a program ID, by the instruction
+    /// builders of a program crate, or a Pinocchio instruction struct
+    fn instruction_target(
+        &self,
+        expr: &Expr,
+        bindings: &BTreeMap<String, Expr>,
+        depth: usize,
+    ) -> (String, Option<String>, TargetCheck) {
+        match expr {
+            Expr::Reference(reference) => self.instruction_target(&reference.expr, bindings, depth),
+            Expr::Paren(paren) => self.instruction_target(&paren.expr, bindings, depth),
+            Expr::MethodCall(method_call) if method_call.method == "clone" => {
+                self.instruction_target(&method_call.receiver, bindings, depth)
+            }
+            // Instruction bound to a variable of the function (`let ix = ...; invoke(&ix, ..)`)
+            Expr::Path(path) if depth == 0 => match path.path.get_ident().and_then(|ident| bindings.get(&ident.to_string())) {
+                Some(bound) => self.instruction_target(bound, bindings, depth + 1),
+                None => (compact(expr), None, TargetCheck::Unknown),
+            },
+            Expr::Struct(expr_struct) => {
+                let program_id = expr_struct.fields.iter().find(|field| {
+                    matches!(&field.member, syn::Member::Named(name) if name == "program_id")
+                });
+                match program_id {
+                    Some(field) => self.program_id_target(&field.expr),
+                    // Instruction struct of a program crate (`pinocchio_token::instructions::Transfer`)
+                    None => (crate_of(&expr_struct.path), None, TargetCheck::Typed),
+                }
+            }
+            Expr::Call(call) => {
+                let Expr::Path(path) = &*call.func else {
+                    return (compact(expr), None, TargetCheck::Unknown);
+                };
+                let name = path.path.segments.last().map(|segment| segment.ident.to_string()).unwrap_or_default();
+                if native::INSTRUCTION_CONSTRUCTORS.contains(&name.as_str()) {
+                    return match call.args.first() {
+                        Some(program_id) => self.program_id_target(program_id),
+                        None => (compact(expr), None, TargetCheck::Unknown),
+                    };
+                }
+                match builder_crate(&path.path) {
+                    Some(target) => (target, None, TargetCheck::Typed),
+                    None => (compact(&call.func), None, TargetCheck::Unknown),
+                }
+            }
+            _ => (compact(expr), None, TargetCheck::Unknown),
+        }
+    }
+
+    /// Resolve a program ID: the key of an account, or a constant of a program crate
+    fn program_id_target(&self, expr: &Expr) -> (String, Option<String>, TargetCheck) {
+        if let Some(account) = native::key_owner(expr) {
+            let check = if self.checks_address(&account) { TargetCheck::AddressChecked } else { TargetCheck::Arbitrary };
+            return (account.clone(), Some(account), check);
+        }
+        let program_id = compact(expr);
+        let program_id = program_id.trim_start_matches(['&', '*']);
+        let is_path = !program_id.is_empty() && program_id.chars().all(|c| c.is_alphanumeric() || c == '_' || c == ':' || c == '(' || c == ')');
+        let program = program_id.strip_suffix("::ID").or_else(|| program_id.strip_suffix("::id()"));
+        match program {
+            Some(program) if is_path => (program.to_string(), None, TargetCheck::Typed),
+            _ if is_path && self.is_constant(program_id) => (program_id.to_string(), None, TargetCheck::Typed),
+            _ => (program_id.to_string(), None, TargetCheck::Unknown),
+        }
+    }
+
+    /// Resolve the program account of a `CpiContext` from the accounts struct of the function
+    fn account_target(&self, expr: &Expr) -> (String, Option<String>, TargetCheck) {
+        let mut program = compact(expr);
+        loop {
+            let stripped = program.trim_start_matches('&');
+            let stripped = CONVERSIONS.iter().find_map(|conversion| stripped.strip_suffix(conversion)).unwrap_or(stripped);
+            if stripped == program {
+                break;
+            }
+            program = stripped.to_string();
+        }
+        let account = program.rsplit('.').next().unwrap_or(&program).to_string();
+        let accounts_struct = if program.starts_with("self.") {
+            self.function.owner.clone()
+        } else if program.contains("accounts.") {
+            self.function.accounts.clone().or_else(|| context_struct(self.sig))
+        } else {
+            None
+        };
+        let member = accounts_struct
+            .and_then(|accounts_struct| self.context.resolve(self.file, &accounts_struct))
+            .filter(|declaration| declaration.kind == DeclarationKind::Accounts)
+            .and_then(|declaration| declaration.member(&account));
+        let Some(member) = member else {
+            let check = if self.checks_address(&account) { TargetCheck::AddressChecked } else { TargetCheck::Unknown };
+            return (account.clone(), Some(account), check);
+        };
+
+        let ty = member.ty.as_deref().and_then(|ty| syn::parse_str::<syn::Type>(ty).ok());
+        let typed = ty.as_ref().and_then(program_type);
+        let address = member.attribute("account").and_then(|arguments| {
+            crate::analyzer::inventory::split_arguments(arguments)
+                .into_iter()
+                .find_map(|constraint| constraint.strip_prefix("address=").map(str::to_string))
+        });
+        match (typed, address) {
+            (Some(program), _) => (program, Some(account), TargetCheck::Typed),
+            (None, Some(address)) => {
+                let program = address.strip_suffix("::ID").or_else(|| address.strip_suffix("::id()")).unwrap_or(&address);
+                (program.to_string(), Some(account), TargetCheck::AddressChecked)
+            }
+            (None, None) if self.checks_address(&account) => (account.clone(), Some(account), TargetCheck::AddressChecked),
+            (None, None) => (account.clone(), Some(account), TargetCheck::Arbitrary),
+        }
+    }
+
+    /// Check if the function or its helpers check the address of an account
+    fn checks_address(&self, account: &str) -> bool {
+        native::methods_called_on(self.block, account)
+            .iter()
+            .any(|method| native::PROGRAM_CHECKS.contains(&method.as_str()))
+            || native::checks_mention(self.context, self.file, self.sig, &format!("{account}.key"))
+    }
+
+    /// Check if a path is a constant of the program (`TOKEN_PROGRAM_ID`)
+    fn is_constant(&self, path: &str) -> bool {
+        self.context
+            .resolve(self.file, path)
+            .is_some_and(|declaration| declaration.kind == DeclarationKind::Constant)
+    }
+}
+
+/// Invocation found in a function, before its target is resolved
+struct Invocation {
+    kind: CpiKind,
+    signed: bool,
+    /// Instruction invoked, or program account of a `CpiContext`
+    expr: Expr,
+    line: usize,
+}
+
+/// Helper visitor to find the invocations of a function and the variables it binds
+#[derive(Default)]
+struct InvocationFinder {
+    invocations: Vec<Invocation>,
+    bindings: BTreeMap<String, Expr>,
+}
+
+impl<'ast> Visit<'ast> for InvocationFinder {
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        let pat = match &local.pat {
+            syn::Pat::Type(pat_type) => &*pat_type.pat,
+            pat => pat,
+        };
+        if let (syn::Pat::Ident(pat_ident), Some(init)) = (pat, &local.init) {
+            self.bindings.insert(pat_ident.ident.to_string(), (*init.expr).clone());
+        }
+        visit::visit_local(self, local);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let Expr::Path(path) = &*call.func
+            && let Some(segment) = path.path.segments.last()
+        {
+            let name = segment.ident.to_string();
+            let segments = &path.path.segments;
+            let owner = (segments.len() > 1).then(|| segments[segments.len() - 2].ident.to_string());
+            let kind = if INVOKE_FUNCTIONS.contains(&name.as_str()) {
+                Some((CpiKind::Invoke, name.starts_with("invoke_signed")))
+            } else if owner.as_deref() == Some("CpiContext") && name.starts_with("new") {
+                Some((CpiKind::CpiContext, name.contains("signer")))
+            } else {
+                None
+            };
+            if let Some((kind, signed)) = kind
+                && let Some(expr) = call.args.first()
+            {
+                self.invocations.push(Invocation { kind, signed, expr: expr.clone(), line: call.span().start().line });
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, method_call: &'ast syn::ExprMethodCall) {
+        // Pinocchio instruction structs invoke themselves (`Transfer { .. }.invoke()?`)
+        let method = method_call.method.to_string();
+        if (method == "invoke" || method == "invoke_signed") && matches!(&*method_call.receiver, Expr::Struct(_) | Expr::Path(_)) {
+            self.invocations.push(Invocation {
+                kind: CpiKind::Invoke,
+                signed: method == "invoke_signed",
+                expr: (*method_call.receiver).clone(),
+                line: method_call.method.span().start().line,
+            });
+        }
+        visit::visit_expr_method_call(self, method_call);
+    }
+}
+
+/// Helper function to get the program of a `Program<'info, T>` or `Interface<'info, T>` account
+/// (boxed or not)
+fn program_type(ty: &syn::Type) -> Option<String> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    let inner = arguments.args.iter().find_map(|argument| match argument {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })?;
+    match (segment.ident.to_string().as_str(), inner) {
+        ("Box", _) => program_type(inner),
+        ("Program" | "Interface", syn::Type::Path(program)) => {
+            program.path.segments.last().map(|segment| segment.ident.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Helper function to get the accounts struct of a helper taking the `Context` of a handler by
+/// reference (`fn notify(ctx: &Context<Relay>)`)
+fn context_struct(sig: &Signature) -> Option<String> {
+    sig.inputs.iter().find_map(|input| {
+        let syn::FnArg::Typed(pat_type) = input else {
+            return None;
+        };
+        let mut ty = &*pat_type.ty;
+        while let syn::Type::Reference(reference) = ty {
+            ty = &reference.elem;
+        }
+        let syn::Type::Path(type_path) = ty else {
+            return None;
+        };
+        let segment = type_path.path.segments.last().filter(|segment| segment.ident == "Context")?;
+        let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+            return None;
+        };
+        arguments.args.iter().find_map(|argument| match argument {
+            syn::GenericArgument::Type(syn::Type::Path(accounts)) => {
+                accounts.path.segments.last().map(|segment| segment.ident.to_string())
+            }
+            _ => None,
+        })
+    })
+}
+
+/// Helper function to get the program crate of an instruction builder
+/// (`spl_token::instruction::transfer` is `spl_token`, `system_instruction::transfer` is
+/// `system_program`)
+fn builder_crate(path: &syn::Path) -> Option<String> {
+    let segments: Vec<String> = path.segments.iter().map(|segment| segment.ident.to_string()).collect();
+    segments.iter().enumerate().find_map(|(index, segment)| match segment.as_str() {
+        "instruction" | "instructions" if index > 0 => Some(segments[index - 1].clone()),
+        _ => segment.strip_suffix("_instruction").map(|program| format!("{program}_program")),
+    })
+}
+
+/// Helper function to get the program crate of an instruction struct, or its name if the path
+/// has no `instructions` module
+fn crate_of(path: &syn::Path) -> String {
+    builder_crate(path).unwrap_or_else(|| compact(path))
+}
+
+/// Helper function to quote a DOT identifier
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,129 @@
+use crate::analyzer::cpi::{CpiGraphReport, CpiKind, ProgramCpis, TargetCheck};
+use crate::analyzer::dsl::ProgramContext;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANCHOR: &str = r#"
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, Transfer};
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        let accounts = Transfer { from: ctx.accounts.vault.to_account_info(), to: ctx.accounts.user.to_account_info(), authority: ctx.accounts.vault.to_account_info() };
+        let cpi = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), accounts, &[]);
+        token::transfer(cpi, amount)
+    }
+
+    pub fn relay(ctx: Context<Relay>) -> Result<()> {
+        notify(&ctx)
+    }
+}
+
+fn notify(ctx: &Context<Relay>) -> Result<()> {
+    CpiContext::new(ctx.accounts.hook.to_account_info(), ctx.accounts.clone());
+    CpiContext::new(ctx.accounts.registry.to_account_info(), ctx.accounts.clone());
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Relay<'info> {
+    pub hook: UncheckedAccount<'info>,
+    #[account(address = registry::ID)]
+    pub registry: UncheckedAccount<'info>,
+}
+"#;
+
+    const NATIVE: &str = r#"
+use solana_program::{account_info::AccountInfo, entrypoint, program::{invoke, invoke_signed}, pubkey::Pubkey};
+
+entrypoint!(process_instruction);
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [payer, vault, program, checked, system] = accounts else { return Err(ProgramError::NotEnoughAccountKeys) };
+    invoke(&system_instruction::transfer(payer.key, vault.key, 1), &[payer.clone(), vault.clone()])?;
+    let ix = Instruction { program_id: *program.key, accounts: vec![], data: data.to_vec() };
+    invoke(&ix, &[vault.clone()])?;
+    if checked.key != &spl_token::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    invoke_signed(&Instruction::new_with_bytes(*checked.key, data, vec![]), &[vault.clone()], &[])?;
+    invoke(&build(), &[])?;
+    Ok(())
+}
+"#;
+
+    fn cpis(source: &str) -> ProgramCpis {
+        let ast = syn::parse_file(source).unwrap();
+        let context = ProgramContext::build([("src/lib.rs", &ast)]);
+        ProgramCpis::build(Some("vault".to_string()), [("src/lib.rs", &ast)], &context, |_, _| true)
+    }
+
+    #[test]
+    fn test_cpi_context_targets() {
+        let cpis = cpis(ANCHOR);
+        let targets: Vec<(&str, TargetCheck)> = cpis.sites.iter().map(|site| (site.target.as_str(), site.check)).collect();
+        assert_eq!(
+            targets,
+            vec![("Token", TargetCheck::Typed), ("hook", TargetCheck::Arbitrary), ("registry", TargetCheck::AddressChecked)]
+        );
+
+        let withdraw = &cpis.sites[0];
+        assert_eq!((withdraw.kind, withdraw.signed), (CpiKind::CpiContext, true));
+        assert_eq!(withdraw.program_account.as_deref(), Some("token_program"));
+        assert_eq!(withdraw.instructions, vec!["withdraw"]);
+
+        // The helper is reached from the handler through the call graph
+        let hook = &cpis.sites[1];
+        assert_eq!(hook.function, "notify");
+        assert_eq!(hook.instructions, vec!["relay"]);
+    }
+
+    #[test]
+    fn test_invoke_targets() {
+        let cpis = cpis(NATIVE);
+        let targets: Vec<(&str, TargetCheck)> = cpis.sites.iter().map(|site| (site.target.as_str(), site.check)).collect();
+        assert_eq!(
+            targets,
+            vec![
+                ("system_program", TargetCheck::Typed),
+                ("program", TargetCheck::Arbitrary),
+                ("checked", TargetCheck::AddressChecked),
+                ("build", TargetCheck::Unknown),
+            ]
+        );
+        assert!(cpis.sites[2].signed);
+        assert_eq!(cpis.sites[1].program_account.as_deref(), Some("program"));
+    }
+
+    #[test]
+    fn test_edges_and_dot() {
+        let programs = vec![cpis(ANCHOR)];
+        let edges: Vec<(&str, &str)> =
+            programs[0].edges.iter().map(|edge| (edge.instruction.as_str(), edge.target.as_str())).collect();
+        assert_eq!(edges, vec![("relay", "hook"), ("relay", "registry"), ("withdraw", "Token")]);
+
+        let report = CpiGraphReport::new(&programs, "/project");
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["programs"][0]["sites"][0]["kind"], "cpi_context");
+        assert_eq!(json["programs"][0]["edges"][0]["check"], "arbitrary");
+
+        let dot = report.to_dot();
+        assert!(dot.starts_with("digraph cpi {\n"));
+        assert!(dot.contains("    subgraph cluster_0 {\n        label=\"vault\";\n"));
+        assert!(dot.contains("        \"0:instruction:withdraw\" [label=\"withdraw\", shape=box];\n"));
+        assert!(dot.contains("        \"0:instruction:relay\" -> \"0:program:hook\" [label=\"arbitrary\", color=red, fontcolor=red];\n"));
+        assert!(dot.contains("        \"0:instruction:relay\" -> \"0:program:registry\" [label=\"address_checked\", style=dashed];\n"));
+        assert!(dot.ends_with("    }\n}\n"));
+    }
+}
@@ -0,0 +1,82 @@
+use std::fs;
+
+use crate::analyzer::{AnalysisResult, Finding};
+
+#[cfg(test)]
+mod test;
+
+/// Columns of the CSV report
+pub const COLUMNS: &[&str] = &[
+    "rule_id", "severity", "confidence", "file", "line", "column", "title", "description", "fingerprint",
+];
+
+/// CSV report of an analysis (`--format csv`), one row per finding for spreadsheet triage
+///
+/// Rows follow the order of the findings, with the [`COLUMNS`] header and fields quoted as in
+/// RFC 4180. Files are relative to the analyzed path, and merged findings give the ID of the
+/// rule that produced their description.
+#[derive(Debug)]
+pub struct CsvReport<'a> {
+    result: &'a AnalysisResult,
+    project: &'a str,
+}
+
+impl<'a> CsvReport<'a> {
+    /// Creates the report of an analysis of `project`
+    pub fn new(result: &'a AnalysisResult, project: &'a str) -> Self {
+        Self { result, project }
+    }
+
+    /// Returns the report as CSV
+    pub fn to_csv(&self) -> String {
+        let mut csv = row(COLUMNS.iter().copied());
+        for finding in &self.result.findings {
+            csv.push_str(&self.finding_row(finding));
+        }
+        csv
+    }
+
+    /// Save the CSV report to a file
+    pub fn save(&self, output_path: &str) -> Result<(), std::io::Error> {
+        fs::write(output_path, self.to_csv())
+    }
+
+    fn finding_row(&self, finding: &Finding) -> String {
+        let rule = finding.rule();
+        let confidence = finding.confidence.to_string().to_lowercase();
+        let line = finding.location.line.to_string();
+        // The parser's columns start at 0, the other reports' at 1
+        let column = finding.location.column.map(|column| (column + 1).to_string()).unwrap_or_default();
+        row([
+            rule.map_or("", |rule| rule.id.as_str()),
+            finding.severity.as_str(),
+            &confidence,
+            relative_path(&finding.location.file, self.project),
+            &line,
+            &column,
+            rule.map_or("", |rule| rule.title.as_str()),
+            &finding.description,
+            finding.fingerprint.as_deref().unwrap_or_default(),
+        ])
+    }
+}
+
+/// Path of a file relative to the analyzed path (unchanged if it is outside)
+pub(crate) fn relative_path<'a>(file: &'a str, project: &str) -> &'a str {
+    file.strip_prefix(project).map_or(file, |path| path.trim_start_matches('/'))
+}
+
+/// Helper function to format a CSV row, quoting the fields that need it
+pub(crate) fn row<'a>(fields: impl IntoIterator<Item = &'a str>) -> String {
+    let fields: Vec<String> = fields.into_iter().map(field).collect();
+    format!("{}\r\n", fields.join(","))
+}
+
+/// Quote a CSV field if it contains a separator, a quote or a line break
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,59 @@
+use crate::analyzer::csv::CsvReport;
+use crate::analyzer::sarif::SarifReport;
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Confidence, Finding, RuleInfo, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(rule_id: &str, severity: Severity, line: usize, description: &str) -> Finding {
+        FindingBuilder::new(description)
+            .severity(severity)
+            .confidence(Confidence::Medium)
+            .span("/project/src/lib.rs", line, 5, 20)
+            .rule(RuleInfo {
+                id: rule_id.to_string(),
+                title: format!("Title of {rule_id}"),
+                ..Default::default()
+            })
+            .fingerprint(format!("{rule_id}-{line}"))
+            .build()
+    }
+
+    #[test]
+    fn test_one_row_per_finding() {
+        let result = AnalysisResult {
+            findings: vec![
+                finding("division-by-zero", Severity::High, 3, "Division by zero"),
+                finding("error-handling", Severity::Low, 8, "Unwrap on \"amount\", then panic\non error"),
+            ],
+            stats: AnalysisStats::default(),
+        };
+
+        let csv = CsvReport::new(&result, "/project").to_csv();
+
+        assert_eq!(
+            csv,
+            "rule_id,severity,confidence,file,line,column,title,description,fingerprint\r\n\
+             division-by-zero,high,medium,src/lib.rs,3,6,Title of division-by-zero,Division by zero,division-by-zero-3\r\n\
+             error-handling,low,medium,src/lib.rs,8,6,Title of error-handling,\
+             \"Unwrap on \"\"amount\"\", then panic\non error\",error-handling-8\r\n"
+        );
+    }
+
+    #[test]
+    fn test_columns_match_the_other_reports() {
+        let result = AnalysisResult {
+            findings: vec![finding("division-by-zero", Severity::High, 3, "Division by zero")],
+            stats: AnalysisStats::default(),
+        };
+
+        let csv = CsvReport::new(&result, "/project").to_csv();
+        let column = csv.lines().nth(1).and_then(|row| row.split(',').nth(5)).unwrap();
+        let log = SarifReport::new(&result, std::path::Path::new("/project")).log();
+        let region = &log.runs[0].results[0].locations[0].physical_location.region;
+
+        assert_eq!(column, region.start_column.unwrap().to_string());
+    }
+}
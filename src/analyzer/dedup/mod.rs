@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use crate::analyzer::dsl::query::MetadataValue;
+use crate::analyzer::{Finding, Location};
+
+#[cfg(test)]
+mod test;
+
+/// Metadata key listing the descriptions of the findings merged into another one
+pub const MERGED_DESCRIPTIONS_KEY: &str = "merged_descriptions";
+
+/// Key identifying the findings reported on the same code
+///
+/// Findings with a precise span are identical when their spans are; findings located by line
+/// only (or by the fallback location) also need the same description, since different issues
+/// often fall on the same line.
+#[derive(PartialEq, Eq, Hash)]
+struct FindingKey {
+    file: String,
+    line: usize,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    description: Option<String>,
+}
+
+impl FindingKey {
+    fn new(finding: &Finding) -> Self {
+        let Location { file, line, column, end_line, end_column } = finding.location.clone();
+        Self {
+            file,
+            line,
+            column,
+            end_line,
+            end_column,
+            description: column.is_none().then(|| finding.description.clone()),
+        }
+    }
+}
+
+/// Merge the findings reported on the same code by several rules (or several times by a rule)
+///
+/// Findings are kept in the order they were first reported. A merged finding keeps the description
+/// of its most severe finding (the first one on ties) with its rule first, the highest severity
+/// and confidence, and the rules, recommendations and fixes of every finding. The descriptions of
+/// the other findings are listed in the `merged_descriptions` metadata.
+pub fn merge_findings(findings: Vec<Finding>) -> Vec<Finding> {
+    let mut merged: Vec<Finding> = Vec::with_capacity(findings.len());
+    let mut index_by_key = HashMap::new();
+
+    for finding in findings {
+        match index_by_key.get(&FindingKey::new(&finding)) {
+            Some(&index) => merge_into(&mut merged[index], finding),
+            None => {
+                index_by_key.insert(FindingKey::new(&finding), merged.len());
+                merged.push(finding);
+            }
+        }
+    }
+
+    merged
+}
+
+/// Helper function to merge a finding into the one reported on the same code
+fn merge_into(target: &mut Finding, mut finding: Finding) {
+    if !target.severity.is_at_least(&finding.severity) {
+        std::mem::swap(target, &mut finding);
+    }
+
+    target.confidence = target.confidence.max(finding.confidence);
+    if target.code_snippet.is_none() {
+        target.code_snippet = finding.code_snippet;
+    }
+
+    append_unique(&mut target.rules, finding.rules);
+    append_unique(&mut target.recommendations, finding.recommendations);
+    append_unique(&mut target.fixes, finding.fixes);
+
+    let mut descriptions = vec![finding.description];
+    if let Some(MetadataValue::List(merged)) = finding.metadata.remove(MERGED_DESCRIPTIONS_KEY) {
+        descriptions.extend(merged);
+    }
+    descriptions.retain(|description| *description != target.description);
+    if !descriptions.is_empty() {
+        let merged = target
+            .metadata
+            .entry(MERGED_DESCRIPTIONS_KEY.to_string())
+            .or_insert_with(|| MetadataValue::List(Vec::new()));
+        if let MetadataValue::List(merged) = merged {
+            append_unique(merged, descriptions);
+        }
+    }
+    for (key, value) in finding.metadata {
+        target.metadata.entry(key).or_insert(value);
+    }
+}
+
+/// Helper function to append the values missing from a list
+fn append_unique<T: PartialEq>(values: &mut Vec<T>, others: Vec<T>) {
+    for other in others {
+        if !values.contains(&other) {
+            values.push(other);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::analyzer::dedup::{MERGED_DESCRIPTIONS_KEY, merge_findings};
+use crate::analyzer::dsl::query::MetadataValue;
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{Confidence, Finding, Location, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(rule_id: &str, severity: Severity, location: Location, description: &str) -> Finding {
+        FindingBuilder::new(description)
+            .severity(severity)
+            .confidence(Confidence::Medium)
+            .location(location)
+            .recommendations(&[&format!("Fix {rule_id}")])
+            .rule_id(rule_id)
+            .build()
+    }
+
+    fn span(line: usize) -> Location {
+        Location::new_precise("lib.rs".to_string(), line, Some(5), Some(line), Some(20))
+    }
+
+    #[test]
+    fn test_merge_findings_on_the_same_span() {
+        let findings = vec![
+            finding("owner-check", Severity::Medium, span(3), "Missing owner check"),
+            finding("unchecked-account", Severity::High, span(3), "Unchecked account"),
+            finding("owner-check", Severity::Medium, span(8), "Missing owner check"),
+            finding("account-type", Severity::Low, span(3), "Untyped account"),
+            finding("owner-check", Severity::Medium, span(3), "Missing owner check"),
+        ];
+        let merged = merge_findings(findings);
+
+        assert_eq!(merged.len(), 2);
+        // The most severe finding is kept, with its rule first
+        assert_eq!(merged[0].severity, Severity::High);
+        assert_eq!(merged[0].description, "Unchecked account");
+        assert_eq!(merged[0].rule_ids(), ["unchecked-account", "owner-check", "account-type"]);
+        assert_eq!(merged[0].recommendations.len(), 3);
+        assert_eq!(
+            merged[0].metadata.get(MERGED_DESCRIPTIONS_KEY),
+            Some(&MetadataValue::List(vec!["Missing owner check".to_string(), "Untyped account".to_string()]))
+        );
+        assert_eq!(merged[1].location.line, 8);
+        assert_eq!(merged[1].rule_ids(), ["owner-check"]);
+    }
+
+    #[test]
+    fn test_line_only_findings_need_the_same_description() {
+        let line = |line| Location::new_precise("lib.rs".to_string(), line, None, None, None);
+        let findings = vec![
+            finding("a", Severity::Low, line(1), "First issue"),
+            finding("b", Severity::Low, line(1), "Second issue"),
+            finding("c", Severity::Low, line(1), "First issue"),
+        ];
+        let merged = merge_findings(findings);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].rule_ids(), ["a", "c"]);
+        assert!(!merged[0].metadata.contains_key(MERGED_DESCRIPTIONS_KEY));
+    }
+}
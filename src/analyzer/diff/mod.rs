@@ -0,0 +1,185 @@
+#[cfg(feature = "native")]
+use log::debug;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "native")]
+use std::process::Command;
+
+use crate::analyzer::Location;
+
+#[cfg(test)]
+mod test;
+
+/// Error returned when the changed lines cannot be computed
+#[derive(Debug)]
+pub enum DiffError {
+    /// Git cannot be run
+    Io(std::io::Error),
+    /// A git command failed (not a repository, unknown reference...)
+    Git { command: String, stderr: String },
+}
+
+impl fmt::Display for DiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffError::Io(error) => write!(f, "cannot run git: {error}"),
+            DiffError::Git { command, stderr } => write!(f, "`{command}` failed: {}", stderr.trim()),
+        }
+    }
+}
+
+impl std::error::Error for DiffError {}
+
+/// Lines changed since a git reference, by file
+///
+/// Files are identified by their canonical path. New (untracked) files are changed as a whole.
+#[derive(Debug, Clone, Default)]
+pub struct ChangedLines {
+    /// Changed line ranges (1-indexed, inclusive) of the modified files
+    ranges: HashMap<PathBuf, Vec<(usize, usize)>>,
+    /// Files changed as a whole
+    whole_files: HashSet<PathBuf>,
+}
+
+impl ChangedLines {
+    /// Compute the lines changed in the working tree since `reference`, for the repository
+    /// containing `path`
+    #[cfg(feature = "native")]
+    pub fn from_git(path: &Path, reference: &str) -> Result<Self, DiffError> {
+        let dir = if path.is_dir() { path } else { path.parent().unwrap_or(Path::new(".")) };
+        let root = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?.trim());
+
+        // Fixed prefixes and unquoted paths whatever the user's configuration (`diff.noprefix`,
+        // `diff.mnemonicPrefix`, `core.quotePath`), as `from_diff` expects
+        let diff = git(
+            &root,
+            &[
+                "-c",
+                "core.quotePath=false",
+                "diff",
+                "--unified=0",
+                "--no-color",
+                "--no-ext-diff",
+                "--no-renames",
+                "--src-prefix=a/",
+                "--dst-prefix=b/",
+                reference,
+                "--",
+            ],
+        )?;
+        let mut changed = Self::from_diff(&diff, &root);
+
+        for file in git(&root, &["-c", "core.quotePath=false", "ls-files", "--others", "--exclude-standard"])?.lines() {
+            changed.whole_files.insert(canonical(&root.join(file)));
+        }
+
+        debug!(
+            "{} files changed since {reference} ({} new)",
+            changed.ranges.len() + changed.whole_files.len(),
+            changed.whole_files.len()
+        );
+        Ok(changed)
+    }
+
+    /// Parse a unified diff (`git diff --unified=0`), with paths relative to `root`
+    pub fn from_diff(diff: &str, root: &Path) -> Self {
+        let mut changed = Self::default();
+        let mut current: Option<PathBuf> = None;
+
+        for line in diff.lines() {
+            if let Some(path) = line.strip_prefix("+++ ") {
+                // `/dev/null` for deleted files
+                current = path.strip_prefix("b/").map(|path| canonical(&root.join(path)));
+            } else if let (Some(hunk), Some(file)) = (line.strip_prefix("@@ "), &current)
+                && let Some(range) = parse_hunk(hunk)
+            {
+                changed.ranges.entry(file.clone()).or_default().push(range);
+            }
+        }
+        changed
+    }
+
+    /// Check if a file has changes
+    pub fn is_changed_file(&self, file: &Path) -> bool {
+        let file = canonical(file);
+        self.whole_files.contains(&file) || self.ranges.contains_key(&file)
+    }
+
+    /// Check if the span of a finding intersects the changed lines
+    pub fn intersects(&self, location: &Location) -> bool {
+        let file = canonical(Path::new(&location.file));
+        if self.whole_files.contains(&file) {
+            return true;
+        }
+        let start = location.line;
+        let end = location.end_line.unwrap_or(start).max(start);
+        self.ranges
+            .get(&file)
+            .is_some_and(|ranges| ranges.iter().any(|(first, last)| *first <= end && start <= *last))
+    }
+}
+
+/// Rust file staged for the next commit, with its content in the git index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StagedFile {
+    /// Path of the file, relative to the directory it was listed from
+    pub path: PathBuf,
+    /// Staged content of the file, which may differ from the working tree
+    pub source: String,
+}
+
+/// List the Rust files staged under `dir` (added, copied, modified or renamed, not deleted),
+/// with their staged content
+///
+/// The content comes from the index, so a pre-commit hook analyzes what is committed even if
+/// the files changed since they were staged.
+#[cfg(feature = "native")]
+pub fn staged_files(dir: &Path) -> Result<Vec<StagedFile>, DiffError> {
+    // Outside a repository, `git diff` compares paths instead of failing
+    git(dir, &["rev-parse", "--show-toplevel"])?;
+    let names = git(dir, &["diff", "--cached", "--name-only", "-z", "--relative", "--diff-filter=ACMR", "--", "*.rs"])?;
+    let files = names
+        .split('\0')
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            let source = git(dir, &["show", &format!(":./{name}")])?;
+            Ok(StagedFile { path: PathBuf::from(name), source })
+        })
+        .collect::<Result<Vec<_>, DiffError>>()?;
+    debug!("{} Rust files staged in {}", files.len(), dir.display());
+    Ok(files)
+}
+
+/// Helper function to parse the new-file range of a hunk header (`-a,b +c,d @@`)
+///
+/// A hunk without new lines (deletion) marks the lines around the deletion as changed.
+fn parse_hunk(hunk: &str) -> Option<(usize, usize)> {
+    let new_range = hunk.split_whitespace().find_map(|part| part.strip_prefix('+'))?;
+    let (start, count) = match new_range.split_once(',') {
+        Some((start, count)) => (start.parse::<usize>().ok()?, count.parse::<usize>().ok()?),
+        None => (new_range.parse::<usize>().ok()?, 1),
+    };
+    if count == 0 {
+        return Some((start.max(1), start + 1));
+    }
+    Some((start, start + count - 1))
+}
+
+/// Helper function to run a git command in a directory and return its output
+#[cfg(feature = "native")]
+pub(crate) fn git(dir: &Path, args: &[&str]) -> Result<String, DiffError> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output().map_err(DiffError::Io)?;
+    if !output.status.success() {
+        return Err(DiffError::Git {
+            command: format!("git {}", args.join(" ")),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Helper function to identify a file by its canonical path (as is if it does not exist)
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
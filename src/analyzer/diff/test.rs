@@ -0,0 +1,62 @@
+use crate::analyzer::Location;
+use crate::analyzer::diff::{ChangedLines, parse_hunk};
+use std::path::Path;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = r#"diff --git a/programs/vault/src/lib.rs b/programs/vault/src/lib.rs
+index 1111111..2222222 100644
+--- a/programs/vault/src/lib.rs
++++ b/programs/vault/src/lib.rs
+@@ -10,0 +11,3 @@ pub mod vault {
++    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
++        Ok(())
++    }
+@@ -40 +43 @@ pub struct Deposit<'info> {
+-    pub vault: Account<'info, Vault>,
++    pub vault: AccountInfo<'info>,
+@@ -60,2 +62,0 @@ fn helper() {
+-    let a = 1;
+-    let b = 2;
+diff --git a/programs/vault/src/old.rs b/programs/vault/src/old.rs
+deleted file mode 100644
+--- a/programs/vault/src/old.rs
++++ /dev/null
+@@ -1,2 +0,0 @@
+-fn old() {}
+-
+"#;
+
+    fn location(line: usize, end_line: Option<usize>) -> Location {
+        Location::new_precise("/repo/programs/vault/src/lib.rs".to_string(), line, None, end_line, None)
+    }
+
+    #[test]
+    fn test_parse_hunks() {
+        assert_eq!(parse_hunk("-10,0 +11,3 @@ pub mod vault {"), Some((11, 13)));
+        assert_eq!(parse_hunk("-40 +43 @@"), Some((43, 43)));
+        // Deletions mark the lines around them
+        assert_eq!(parse_hunk("-60,2 +62,0 @@"), Some((62, 63)));
+        assert_eq!(parse_hunk("invalid"), None);
+    }
+
+    #[test]
+    fn test_findings_intersecting_the_diff() {
+        let changed = ChangedLines::from_diff(DIFF, Path::new("/repo"));
+        assert!(changed.is_changed_file(Path::new("/repo/programs/vault/src/lib.rs")));
+        assert!(!changed.is_changed_file(Path::new("/repo/programs/vault/src/old.rs")));
+
+        assert!(changed.intersects(&location(11, None)));
+        assert!(changed.intersects(&location(43, None)));
+        // Findings spanning a changed line
+        assert!(changed.intersects(&location(5, Some(20))));
+        assert!(changed.intersects(&location(60, Some(62))));
+        assert!(!changed.intersects(&location(20, Some(30))));
+        assert!(!changed.intersects(&location(1, None)));
+
+        let other_file = Location::new_precise("/repo/src/other.rs".to_string(), 11, None, None, None);
+        assert!(!changed.intersects(&other_file));
+    }
+}
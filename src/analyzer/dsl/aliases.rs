@@ -0,0 +1,339 @@
+use log::trace;
+use proc_macro2::LineColumn;
+use std::collections::BTreeMap;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{Block, Expr};
+
+use crate::analyzer::dsl::query::{AstNode, NodeData};
+use crate::analyzer::dsl::values::pattern_names;
+
+/// Methods returning the account (or its data) they are called on
+/// (`ctx.accounts.vault.to_account_info()`, `ctx.accounts.state.load_mut()?`)
+const CONVERSIONS: &[&str] = &[
+    "to_account_info",
+    "as_ref",
+    "as_mut",
+    "clone",
+    "deref",
+    "deref_mut",
+    "borrow",
+    "borrow_mut",
+    "try_borrow_mut",
+    "load",
+    "load_mut",
+    "load_init",
+    "try_borrow_mut_data",
+    "try_borrow_mut_lamports",
+];
+
+/// Methods writing the account they are called on
+const WRITING_METHODS: &[&str] = &[
+    "set_inner",
+    "exit",
+    "close",
+    "realloc",
+    "resize",
+    "assign",
+    "add_lamports",
+    "sub_lamports",
+    "set_lamports",
+    "serialize",
+    "copy_from_slice",
+    "fill",
+    "push",
+    "insert",
+    "remove",
+    "clear",
+    "retain",
+    "extend",
+    "extend_from_slice",
+    "truncate",
+];
+
+/// Local variables of a function referring to accounts of its accounts struct
+///
+/// Handlers often work on aliases rather than on `ctx.accounts` directly:
+/// `let vault = &mut ctx.accounts.vault;`, `let info = ctx.accounts.vault.to_account_info();`,
+/// `let Withdraw { vault, .. } = ctx.accounts;` or `let mut state = ctx.accounts.state.load_mut()?;`.
+/// Expressions using them resolve to the field of the accounts they refer to
+/// (`vault.amount` to `ctx.accounts.vault.amount`), so writes through an alias are writes to
+/// the account. Bindings follow the scopes of the function: a shadowed alias no longer refers
+/// to the account, and an alias of an alias resolves to the account.
+///
+/// The accounts are reached through the `Context` parameters of the function (`ctx.accounts`),
+/// or through the roots given to [`AccountAliases::of_block`] (`self` in the methods of an
+/// accounts struct).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountAliases {
+    /// Paths the accounts are reached through (`ctx.accounts`)
+    roots: Vec<String>,
+    /// Local variables in the order of their declaration
+    bindings: Vec<Binding>,
+    /// Fields of the accounts written by the function, with the lines of the writes
+    writes: BTreeMap<String, Vec<usize>>,
+}
+
+/// Local variable and the account path it refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Binding {
+    name: String,
+    /// Account path (`None` for variables shadowing an alias with something else)
+    target: Option<String>,
+    /// Position from which the variable is in scope
+    start: LineColumn,
+    /// End of the block declaring the variable
+    end: LineColumn,
+}
+
+impl AccountAliases {
+    /// Track the aliases of the accounts in a function
+    pub fn of_function(sig: &syn::Signature, block: &Block) -> Self {
+        let roots = sig
+            .inputs
+            .iter()
+            .filter_map(|input| match input {
+                syn::FnArg::Typed(pat_type) if is_context(&pat_type.ty) => match &*pat_type.pat {
+                    syn::Pat::Ident(pat_ident) => Some(format!("{}.accounts", pat_ident.ident)),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        Self::of_block(roots.iter().map(String::as_str), block)
+    }
+
+    /// Track the aliases of the accounts in a function node (`None` for other nodes)
+    pub fn of_node(node: &AstNode<'_>) -> Option<Self> {
+        match &node.data {
+            NodeData::Function(func) => Some(Self::of_function(&func.sig, &func.block)),
+            NodeData::ImplFunction(func) => Some(Self::of_function(&func.sig, &func.block)),
+            _ => None,
+        }
+    }
+
+    /// Track the aliases of the accounts reached through the given paths (`self`, `ctx.accounts`)
+    /// in a function body
+    pub fn of_block<'r>(roots: impl IntoIterator<Item = &'r str>, block: &Block) -> Self {
+        let mut walker = AliasWalker {
+            aliases: Self {
+                roots: roots.into_iter().map(str::to_string).collect(),
+                ..Self::default()
+            },
+            scope_end: block.span().end(),
+        };
+        walker.visit_block(block);
+        trace!("{} account aliases tracked", walker.aliases.bindings.iter().filter(|binding| binding.target.is_some()).count());
+        walker.aliases
+    }
+
+    /// Returns the path of the account data an expression refers to, through aliases
+    /// (`ctx.accounts.vault.amount` for `vault.amount` after `let vault = &mut ctx.accounts.vault;`),
+    /// or `None` if it does not refer to an account
+    pub fn resolve(&self, expr: &Expr) -> Option<String> {
+        let (base, fields) = access_path(expr)?;
+        let resolved = match self.binding(&base, expr.span().start()) {
+            Some(binding) => binding.target.clone()?,
+            None => base,
+        };
+        let path = std::iter::once(resolved).chain(fields).collect::<Vec<_>>().join(".");
+        self.roots
+            .iter()
+            .any(|root| path == *root || path.strip_prefix(root.as_str()).is_some_and(|rest| rest.starts_with('.')))
+            .then_some(path)
+    }
+
+    /// Returns the field of the accounts struct an expression refers to, through aliases
+    /// (`vault` for `vault.amount` after `let vault = &mut ctx.accounts.vault;`)
+    pub fn account_of(&self, expr: &Expr) -> Option<String> {
+        let path = self.resolve(expr)?;
+        let root = self.roots.iter().find(|root| path.starts_with(root.as_str()))?;
+        path[root.len()..].split('.').find(|segment| !segment.is_empty()).map(str::to_string)
+    }
+
+    /// Returns the account path a local variable refers to at a position, if it is an alias
+    pub fn alias_target(&self, name: &str, at: LineColumn) -> Option<&str> {
+        self.binding(name, at)?.target.as_deref()
+    }
+
+    /// Returns the fields of the accounts struct the function writes, directly or through
+    /// aliases, with the lines of the writes: assignments (`vault.amount += amount`), lamport
+    /// updates (`**info.lamports.borrow_mut() -= amount`) and mutating methods
+    /// (`vault.set_inner(..)`, `info.realloc(..)`)
+    pub fn writes(&self) -> &BTreeMap<String, Vec<usize>> {
+        &self.writes
+    }
+
+    /// Helper function to find the binding of a variable in scope at a position
+    fn binding(&self, name: &str, at: LineColumn) -> Option<&Binding> {
+        self.bindings
+            .iter()
+            .filter(|binding| binding.name == name && binding.start <= at && at < binding.end)
+            .max_by_key(|binding| binding.start)
+    }
+}
+
+/// Helper visitor to bind the aliases and record the writes of a function body, in order
+struct AliasWalker {
+    aliases: AccountAliases,
+    /// End of the innermost block, where its variables go out of scope
+    scope_end: LineColumn,
+}
+
+impl AliasWalker {
+    /// Bind the variables of a pattern to the account path of a value (`None` if it is no alias)
+    fn bind(&mut self, pat: &syn::Pat, target: Option<String>, value: Option<&Expr>, start: LineColumn) {
+        match pat {
+            syn::Pat::Ident(pat_ident) if pat_ident.subpat.is_none() => self.push(pat_ident.ident.to_string(), target, start),
+            syn::Pat::Reference(reference) => self.bind(&reference.pat, target, value, start),
+            syn::Pat::Type(pat_type) => self.bind(&pat_type.pat, target, value, start),
+            syn::Pat::Paren(paren) => self.bind(&paren.pat, target, value, start),
+            // `let Withdraw { vault, authority: owner, .. } = ctx.accounts;`
+            syn::Pat::Struct(pat_struct) => {
+                for field in &pat_struct.fields {
+                    let member = match &field.member {
+                        syn::Member::Named(ident) => ident.to_string(),
+                        syn::Member::Unnamed(index) => index.index.to_string(),
+                    };
+                    self.bind(&field.pat, target.as_ref().map(|target| format!("{target}.{member}")), None, start);
+                }
+            }
+            // `let (vault, pool) = (&ctx.accounts.vault, &ctx.accounts.pool);`
+            syn::Pat::Tuple(pat_tuple) => match value.map(unwrap) {
+                Some(Expr::Tuple(tuple)) if tuple.elems.len() == pat_tuple.elems.len() => {
+                    for (pat, elem) in pat_tuple.elems.iter().zip(&tuple.elems) {
+                        let target = self.aliases.resolve(elem);
+                        self.bind(pat, target, Some(elem), start);
+                    }
+                }
+                _ => self.shadow(pat, start),
+            },
+            _ => self.shadow(pat, start),
+        }
+    }
+
+    /// Bind the variables of a pattern to values that are no aliases
+    fn shadow(&mut self, pat: &syn::Pat, start: LineColumn) {
+        for name in pattern_names(pat) {
+            self.push(name, None, start);
+        }
+    }
+
+    fn push(&mut self, name: String, target: Option<String>, start: LineColumn) {
+        self.aliases.bindings.push(Binding {
+            name,
+            target,
+            start,
+            end: self.scope_end,
+        });
+    }
+
+    fn write(&mut self, target: &Expr, line: usize) {
+        if let Some(account) = self.aliases.account_of(target) {
+            self.aliases.writes.entry(account).or_default().push(line);
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for AliasWalker {
+    fn visit_block(&mut self, block: &'ast Block) {
+        let outer = std::mem::replace(&mut self.scope_end, block.span().end());
+        visit::visit_block(self, block);
+        self.scope_end = outer;
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        visit::visit_local(self, local);
+        // The variables are in scope after the statement (the value still sees the shadowed ones)
+        let start = local.span().end();
+        let value = local.init.as_ref().map(|init| &*init.expr);
+        let target = value.and_then(|value| self.aliases.resolve(value));
+        self.bind(&local.pat, target, value, start);
+    }
+
+    fn visit_expr_assign(&mut self, assign: &'ast syn::ExprAssign) {
+        visit::visit_expr_assign(self, assign);
+        // `vault = &mut ctx.accounts.other;` rebinds an alias, other assignments write
+        if let Expr::Path(path) = &*assign.left
+            && let Some(ident) = path.path.get_ident()
+        {
+            let target = self.aliases.resolve(&assign.right);
+            self.push(ident.to_string(), target, assign.span().end());
+            return;
+        }
+        self.write(&assign.left, assign.left.span().start().line);
+    }
+
+    fn visit_expr_binary(&mut self, binary: &'ast syn::ExprBinary) {
+        visit::visit_expr_binary(self, binary);
+        let compound = matches!(
+            binary.op,
+            syn::BinOp::AddAssign(_)
+                | syn::BinOp::SubAssign(_)
+                | syn::BinOp::MulAssign(_)
+                | syn::BinOp::DivAssign(_)
+                | syn::BinOp::RemAssign(_)
+                | syn::BinOp::BitAndAssign(_)
+                | syn::BinOp::BitOrAssign(_)
+                | syn::BinOp::BitXorAssign(_)
+                | syn::BinOp::ShlAssign(_)
+                | syn::BinOp::ShrAssign(_)
+        );
+        if compound {
+            self.write(&binary.left, binary.left.span().start().line);
+        }
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        visit::visit_expr_method_call(self, call);
+        if WRITING_METHODS.contains(&call.method.to_string().as_str()) {
+            self.write(&call.receiver, call.method.span().start().line);
+        }
+    }
+
+    // Closures and nested functions have their own variables
+    fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+}
+
+/// Helper function to split an expression into a variable and the fields accessed on it,
+/// looking through references, dereferences, `?` and conversions
+/// (`vault` and `[lamports]` for `**vault.to_account_info().lamports.borrow_mut()`)
+fn access_path(expr: &Expr) -> Option<(String, Vec<String>)> {
+    match unwrap(expr) {
+        Expr::Path(path) if path.qself.is_none() => Some((path.path.get_ident()?.to_string(), Vec::new())),
+        Expr::Field(field) => {
+            let (base, mut fields) = access_path(&field.base)?;
+            fields.push(match &field.member {
+                syn::Member::Named(ident) => ident.to_string(),
+                syn::Member::Unnamed(index) => index.index.to_string(),
+            });
+            Some((base, fields))
+        }
+        _ => None,
+    }
+}
+
+/// Helper function to look through the wrappers of an expression that keep referring to the
+/// same data
+fn unwrap(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Reference(reference) => unwrap(&reference.expr),
+        Expr::Paren(paren) => unwrap(&paren.expr),
+        Expr::Group(group) => unwrap(&group.expr),
+        Expr::Try(expr_try) => unwrap(&expr_try.expr),
+        Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Deref(_)) => unwrap(&unary.expr),
+        Expr::MethodCall(call) if call.args.is_empty() && CONVERSIONS.contains(&call.method.to_string().as_str()) => unwrap(&call.receiver),
+        _ => expr,
+    }
+}
+
+/// Helper function to check if a parameter type is an Anchor `Context` (possibly borrowed)
+fn is_context(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(type_path) => type_path.path.segments.last().is_some_and(|segment| segment.ident == "Context"),
+        syn::Type::Reference(reference) => is_context(&reference.elem),
+        _ => false,
+    }
+}
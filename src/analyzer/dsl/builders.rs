@@ -1,9 +1,163 @@
-use log::{debug, info};
+use log::{debug, warn};
+use semver::VersionReq;
+use std::fmt;
 use std::sync::Arc;
 use syn::File;
 
-use crate::analyzer::{Finding, Severity};
-use crate::analyzer::engine::{Rule, RuleType, RustRule};
+use crate::analyzer::{Category, Confidence, Finding, Fix, Severity};
+use crate::analyzer::dsl::context::NodeContext;
+use crate::analyzer::dsl::message::MessageTemplate;
+use crate::analyzer::dsl::params::{ParamValue, RuleParams};
+use crate::analyzer::dsl::program::ProgramContext;
+use crate::analyzer::dsl::project::{ProjectFile, ProjectQuery};
+use crate::analyzer::dsl::query::{AstNode, AstQuery};
+use crate::analyzer::engine::{Rule, RuleEngine, RuleExample, RuleType, RustRule};
+use crate::analyzer::packs::is_valid_pack_name;
+
+/// Function that analyzes an AST with `SpanExtractor` support, the rule parameters and the
+/// declarations of the program and returns findings
+type QueryFn =
+    Box<dyn Fn(&File, &str, &crate::analyzer::span_utils::SpanExtractor, &RuleParams, &ProgramContext) -> Vec<Finding> + Send + Sync>;
+
+/// Function that builds an `AstQuery` over the analyzed file
+type DslQueryFn = Box<
+    dyn for<'a> Fn(&'a File, &'a str, &'a crate::analyzer::span_utils::SpanExtractor, &RuleParams, &ProgramContext) -> AstQuery<'a>
+        + Send
+        + Sync,
+>;
+
+/// Function that builds a `ProjectQuery` over all the files of the program
+type ProjectQueryFn = Box<dyn for<'a> Fn(&'a [ProjectFile<'a>], &RuleParams) -> ProjectQuery<'a> + Send + Sync>;
+
+/// Predicate deciding whether a matched node is reported
+type NodeFilterFn = Box<dyn Fn(&AstNode<'_>, &NodeContext<'_>) -> bool + Send + Sync>;
+
+/// Function suggesting a fix for a matched node
+type FixFn =
+    Box<dyn Fn(&AstNode<'_>, &NodeContext<'_>, &crate::analyzer::span_utils::SpanExtractor) -> Option<Fix> + Send + Sync>;
+
+/// Post-processing step applied to the findings of a rule
+type TransformFn = Box<dyn Fn(Vec<Finding>) -> Vec<Finding> + Send + Sync>;
+
+/// Error returned when a rule cannot be built or registered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleBuildError {
+    /// The rule has no ID
+    MissingId,
+    /// The rule has no title
+    MissingTitle { id: String },
+    /// The rule has no description
+    MissingDescription { id: String },
+    /// The rule has no query
+    MissingQuery { id: String },
+    /// The rule ID does not follow the naming convention
+    InvalidId { id: String },
+    /// A pack name of the rule does not follow the naming convention
+    InvalidPack { id: String, pack: String },
+    /// A rule with the same ID is already registered
+    DuplicateId { id: String },
+    /// The configuration sets a parameter the rule does not declare
+    UnknownParam { id: String, param: String },
+    /// The configuration sets a parameter to a value of the wrong type
+    InvalidParam { id: String, param: String, value: String, expected: &'static str },
+    /// The anchor-lang versions of the rule are not a valid version requirement
+    InvalidAnchorVersions { id: String, versions: String, error: String },
+}
+
+impl fmt::Display for RuleBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleBuildError::MissingId => write!(f, "rule has no ID"),
+            RuleBuildError::MissingTitle { id } => write!(f, "rule '{id}' has no title"),
+            RuleBuildError::MissingDescription { id } => write!(f, "rule '{id}' has no description"),
+            RuleBuildError::MissingQuery { id } => write!(f, "rule '{id}' has no query"),
+            RuleBuildError::InvalidId { id } => write!(
+                f,
+                "rule ID '{id}' is invalid (expected lowercase words separated by hyphens, e.g. 'solana-missing-signer')"
+            ),
+            RuleBuildError::InvalidPack { id, pack } => write!(
+                f,
+                "pack name '{pack}' of rule '{id}' is invalid (expected lowercase words separated by hyphens, e.g. 'anchor-core')"
+            ),
+            RuleBuildError::DuplicateId { id } => write!(f, "a rule with ID '{id}' is already registered"),
+            RuleBuildError::UnknownParam { id, param } => write!(f, "rule '{id}' has no parameter '{param}'"),
+            RuleBuildError::InvalidParam { id, param, value, expected } => write!(
+                f,
+                "invalid value '{value}' for parameter '{param}' of rule '{id}' (expected {expected})"
+            ),
+            RuleBuildError::InvalidAnchorVersions { id, versions, error } => {
+                write!(f, "anchor-lang versions '{versions}' of rule '{id}' are invalid: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuleBuildError {}
+
+/// Check if a rule ID follows the naming convention (lowercase alphanumeric words separated by single hyphens)
+pub fn is_valid_rule_id(id: &str) -> bool {
+    id.starts_with(|c: char| c.is_ascii_lowercase())
+        && id.split('-').all(|word| {
+            !word.is_empty() && word.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        })
+}
+
+/// Logic of the rule being built
+enum RuleQuery {
+    /// Function returning findings directly
+    Findings(QueryFn),
+    /// DSL query, converted to findings with the rule metadata when the rule is built
+    Dsl(DslQueryFn),
+    /// DSL query over all the files of the program, converted like `Dsl`
+    Project(ProjectQueryFn),
+}
+
+/// Conversion of the nodes matched by a DSL query into findings, with the rule's
+/// metadata, node filters, message template and fixers
+struct DslFindings {
+    severity: Severity,
+    title: String,
+    description: String,
+    recommendations: Vec<String>,
+    message: Option<MessageTemplate>,
+    filters: Vec<NodeFilterFn>,
+    fixers: Vec<FixFn>,
+}
+
+impl DslFindings {
+    /// Convert the results of a query over the given file into findings
+    fn findings(
+        &self,
+        mut query_result: AstQuery<'_>,
+        ast: &File,
+        file_path: &str,
+        span_extractor: &crate::analyzer::span_utils::SpanExtractor,
+        program: &ProgramContext,
+    ) -> Vec<Finding> {
+        let context = NodeContext::new(file_path, ast).with_program(program);
+        if !self.filters.is_empty() {
+            query_result = query_result.filter(|node| self.filters.iter().all(|filter| filter(node, &context)));
+        }
+
+        let mut findings = query_result.to_findings_with_formatter(
+            self.severity.clone(),
+            &self.recommendations,
+            file_path,
+            span_extractor,
+            &|node| match &self.message {
+                Some(template) => template.render(node, &self.title, &self.description),
+                None => node.describe(&self.title, &self.description),
+            },
+        );
+
+        // Findings are created in the same order as the nodes
+        for (finding, node) in findings.iter_mut().zip(query_result.results()) {
+            finding.fixes.extend(self.fixers.iter().filter_map(|fixer| fixer(node, &context, span_extractor)));
+        }
+
+        findings
+    }
+}
 
 /// Rule builder to facilitate the creation of static analysis rules
 ///
@@ -19,20 +173,50 @@ pub struct RuleBuilder {
     description: String,
     /// Rule severity
     severity: Severity,
+    /// Confidence of the rule's findings
+    confidence: Confidence,
     /// Rule type
     rule_type: RuleType,
     /// Query builder with `SpanExtractor` support
-    query_builder: Option<Box<dyn Fn(&File, &str, &crate::analyzer::span_utils::SpanExtractor) -> Vec<Finding> + Send + Sync>>,
+    query_builder: Option<RuleQuery>,
+    /// Configuration parameters declared by the rule
+    params: RuleParams,
+    /// Template for the descriptions of DSL findings
+    message: Option<MessageTemplate>,
+    /// Filters that every matched node must pass to be reported
+    filters: Vec<NodeFilterFn>,
+    /// Functions suggesting fixes for the matched nodes
+    fixers: Vec<FixFn>,
+    /// Post-processing steps applied in order to the findings
+    transforms: Vec<TransformFn>,
     /// References to documentation or additional resources
     references: Vec<String>,
     /// Recommendations for fixing the issue
     recommendations: Vec<String>,
+    /// Why the issue matters
+    rationale: Option<String>,
+    /// Vulnerable and safe code examples
+    examples: Vec<RuleExample>,
     /// Tags to classify the rule
     tags: Vec<String>,
+    /// Packs the rule belongs to (see `crate::analyzer::packs`)
+    packs: Vec<String>,
+    /// CWE IDs of the weakness detected by the rule
+    cwe: Vec<u32>,
+    /// Solana vulnerability class of the rule
+    category: Option<Category>,
+    /// Versions of anchor-lang the rule applies to, as a version requirement (e.g. `>=0.28`)
+    anchor_versions: Option<String>,
     /// Indicates if the rule is enabled by default
     enabled: bool,
 }
 
+impl Default for RuleBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RuleBuilder {
     /// Creates a new rule builder with default values
     pub fn new() -> Self {
@@ -41,11 +225,23 @@ impl RuleBuilder {
             title: String::new(),
             description: String::new(),
             severity: Severity::Medium,
+            confidence: Confidence::High,
             rule_type: RuleType::Solana,
             query_builder: None,
+            params: RuleParams::new(),
+            message: None,
+            filters: Vec::new(),
+            fixers: Vec::new(),
+            transforms: Vec::new(),
             references: Vec::new(),
             recommendations: Vec::new(),
+            rationale: None,
+            examples: Vec::new(),
             tags: Vec::new(),
+            packs: Vec::new(),
+            cwe: Vec::new(),
+            category: None,
+            anchor_versions: None,
             enabled: true,
         }
     }
@@ -74,6 +270,15 @@ impl RuleBuilder {
         self
     }
 
+    /// Sets the confidence of the rule's findings
+    ///
+    /// Findings never report a higher confidence than the rule's, but filters
+    /// can lower it further for heuristic matches.
+    pub fn confidence(mut self, confidence: Confidence) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
     /// Sets the rule type
     pub fn rule_type(mut self, rule_type: RuleType) -> Self {
         self.rule_type = rule_type;
@@ -85,7 +290,9 @@ impl RuleBuilder {
     where
         F: Fn(&syn::File, &str, &crate::analyzer::span_utils::SpanExtractor) -> Vec<crate::analyzer::Finding> + Send + Sync + 'static,
     {
-        self.query_builder = Some(Box::new(rule_fn));
+        self.query_builder = Some(RuleQuery::Findings(Box::new(move |file, file_path, span_extractor, _params, _program| {
+            rule_fn(file, file_path, span_extractor)
+        })));
         self
     }
 
@@ -96,9 +303,9 @@ impl RuleBuilder {
     {
         // Store the rule function that expects SpanExtractor
         // The SpanExtractor will be provided when the rule is executed
-        self.query_builder = Some(Box::new(move |file, file_path, span_extractor| {
+        self.query_builder = Some(RuleQuery::Findings(Box::new(move |file, file_path, span_extractor, _params, _program| {
             rule_fn(file, file_path, span_extractor)
-        }));
+        })));
         self
     }
 
@@ -107,7 +314,9 @@ impl RuleBuilder {
     where
         F: Fn(&File, &str, &crate::analyzer::span_utils::SpanExtractor) -> Vec<Finding> + Send + Sync + 'static,
     {
-        self.query_builder = Some(Box::new(query_builder));
+        self.query_builder = Some(RuleQuery::Findings(Box::new(move |file, file_path, span_extractor, _params, _program| {
+            query_builder(file, file_path, span_extractor)
+        })));
         self
     }
 
@@ -115,57 +324,140 @@ impl RuleBuilder {
     /// This is the new, preferred way to define rules using the DSL
     pub fn dsl_query<F>(mut self, dsl_builder: F) -> Self
     where
-        F: for<'a> Fn(&'a File, &'a str, &'a crate::analyzer::span_utils::SpanExtractor) -> crate::analyzer::dsl::query::AstQuery<'a> + Send + Sync + 'static,
+        F: for<'a> Fn(&'a File, &'a str, &'a crate::analyzer::span_utils::SpanExtractor) -> AstQuery<'a> + Send + Sync + 'static,
     {
-        // Capture rule metadata for use in the wrapped builder
-        let rule_severity = self.severity.clone();
-        let rule_title = self.title.clone();
-        let rule_description = self.description.clone();
-        let rule_recommendations = self.recommendations.clone();
-        
-        // Wrap the DSL builder to convert AstQuery to Vec<Finding>
-        let wrapped_builder = move |ast: &File, file_path: &str, span_extractor: &crate::analyzer::span_utils::SpanExtractor| -> Vec<Finding> {
-            let query_result = dsl_builder(ast, file_path, span_extractor);
-            
-            // Convert AstQuery to findings using the rule's actual metadata
-            query_result.to_findings_with_span_extractor(
-                rule_severity.clone(),
-                &rule_title,
-                &rule_description,
-                &rule_recommendations,
-                file_path,
-                span_extractor
-            )
-        };
-        
-        self.query_builder = Some(Box::new(wrapped_builder));
+        // The conversion to findings is deferred to build() so it sees the final rule metadata
+        self.query_builder = Some(RuleQuery::Dsl(Box::new(move |file, file_path, span_extractor, _params, _program| {
+            dsl_builder(file, file_path, span_extractor)
+        })));
+        self
+    }
+
+    /// Sets a DSL-based query builder that reads the rule's configuration parameters
+    pub fn dsl_query_with_params<F>(mut self, dsl_builder: F) -> Self
+    where
+        F: for<'a> Fn(&'a File, &'a str, &'a crate::analyzer::span_utils::SpanExtractor, &RuleParams) -> AstQuery<'a> + Send + Sync + 'static,
+    {
+        self.query_builder = Some(RuleQuery::Dsl(Box::new(move |file, file_path, span_extractor, params, _program| {
+            dsl_builder(file, file_path, span_extractor, params)
+        })));
+        self
+    }
+
+    /// Sets a DSL-based query builder that reads the declarations of the other files of the
+    /// program (e.g. the account types of `state.rs` from a handler in `instructions/`)
+    pub fn dsl_query_in_program<F>(mut self, dsl_builder: F) -> Self
+    where
+        F: for<'a> Fn(&'a File, &'a str, &'a crate::analyzer::span_utils::SpanExtractor, &ProgramContext) -> AstQuery<'a>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.query_builder = Some(RuleQuery::Dsl(Box::new(move |file, file_path, span_extractor, _params, program| {
+            dsl_builder(file, file_path, span_extractor, program)
+        })));
+        self
+    }
+
+    /// Sets a DSL-based query builder that reads the rule's configuration parameters and the
+    /// declarations of the other files of the program
+    pub fn dsl_query_in_program_with_params<F>(mut self, dsl_builder: F) -> Self
+    where
+        F: for<'a> Fn(&'a File, &'a str, &'a crate::analyzer::span_utils::SpanExtractor, &RuleParams, &ProgramContext) -> AstQuery<'a>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.query_builder = Some(RuleQuery::Dsl(Box::new(dsl_builder)));
+        self
+    }
+
+    /// Sets a DSL query over all the files of the program, for rules matching
+    /// relationships across files (e.g. an account struct defined in `state.rs`
+    /// and used by a handler in `instructions/withdraw.rs`)
+    ///
+    /// The rule runs once per analysis instead of once per file.
+    pub fn project_query<F>(mut self, project_builder: F) -> Self
+    where
+        F: for<'a> Fn(&'a [ProjectFile<'a>]) -> ProjectQuery<'a> + Send + Sync + 'static,
+    {
+        self.query_builder = Some(RuleQuery::Project(Box::new(move |files, _params| project_builder(files))));
+        self
+    }
+
+    /// Sets a DSL query over all the files of the program that reads the rule's configuration parameters
+    pub fn project_query_with_params<F>(mut self, project_builder: F) -> Self
+    where
+        F: for<'a> Fn(&'a [ProjectFile<'a>], &RuleParams) -> ProjectQuery<'a> + Send + Sync + 'static,
+    {
+        self.query_builder = Some(RuleQuery::Project(Box::new(project_builder)));
         self
     }
 
-    /// Sets the message formatter (now integrated into the query)
-    pub fn message<F>(self, _formatter: F) -> Self
+    /// Declares a configuration parameter with its default value
+    ///
+    /// Users can override it per rule (e.g. `--rule-param <rule-id>.<name>=<value>`);
+    /// the resolved value is passed to `dsl_query_with_params`.
+    pub fn param(mut self, name: &str, default: impl Into<ParamValue>, description: &str) -> Self {
+        self.params.declare(name, default.into(), description);
+        self
+    }
+
+    /// Sets the template used for the descriptions of DSL findings
+    ///
+    /// Placeholders such as `{struct}`, `{function}`, `{name}` and any metadata key
+    /// attached by filters (e.g. `{field}`, `{value}`) are replaced per finding.
+    pub fn message(mut self, template: &str) -> Self {
+        self.message = Some(MessageTemplate::new(template));
+        self
+    }
+
+    /// Adds a filter deciding whether each node matched by the DSL query is reported
+    ///
+    /// The filter receives the matched node and its context (file path, enclosing
+    /// modules, attributes) and returns `false` to suppress the finding, e.g. for
+    /// code in `tests/` or inside `#[cfg(test)]` modules. Only applies to `dsl_query` rules.
+    pub fn filter<F>(mut self, filter: F) -> Self
     where
-        F: Fn(&str) -> String + Send + Sync + 'static,
+        F: Fn(&AstNode<'_>, &NodeContext<'_>) -> bool + Send + Sync + 'static,
     {
-        //@todo => implement message formatter
+        self.filters.push(Box::new(filter));
         self
     }
 
-    /// Sets an additional filter for the nodes found (now integrated into the query)
-    pub fn filter<F>(self, _filter: F) -> Self
+    /// Adds a function suggesting a machine-applicable fix for each node matched by the DSL query
+    ///
+    /// The function receives the node and its context like filters, builds the edits with the
+    /// `SpanExtractor` helpers (`replace_edit`, `insert_before_edit`...) and returns `None` when
+    /// no safe fix applies. Only applies to `dsl_query` rules.
+    pub fn suggest_fix<F>(mut self, fixer: F) -> Self
     where
-        F: Fn(&str) -> bool + Send + Sync + 'static,
+        F: Fn(&AstNode<'_>, &NodeContext<'_>, &crate::analyzer::span_utils::SpanExtractor) -> Option<Fix> + Send + Sync + 'static,
     {
-        //@todo => implement filter
+        self.fixers.push(Box::new(fixer));
         self
     }
 
-    /// Sets a transformer to modify findings before returning them (now integrated into the query)
-    pub fn transform<F>(self, _transformer: F) -> Self
+    /// Adds a transformer applied to each finding after the query runs
+    ///
+    /// The transformer can adjust the severity or rewrite the description (e.g. based on
+    /// the metadata recorded by filters), or drop the finding by returning `None`.
+    pub fn transform<F>(mut self, transformer: F) -> Self
     where
-        F: Fn(Finding) -> Finding + Send + Sync + 'static,
+        F: Fn(Finding) -> Option<Finding> + Send + Sync + 'static,
     {
-        //@todo => implement transformer
+        self.transforms.push(Box::new(move |findings| {
+            findings.into_iter().filter_map(&transformer).collect()
+        }));
+        self
+    }
+
+    /// Adds a transformer applied to all findings of a file at once (e.g. to merge related findings)
+    pub fn transform_all<F>(mut self, transformer: F) -> Self
+    where
+        F: Fn(Vec<Finding>) -> Vec<Finding> + Send + Sync + 'static,
+    {
+        self.transforms.push(Box::new(transformer));
         self
     }
 
@@ -197,6 +489,24 @@ impl RuleBuilder {
         self
     }
 
+    /// Sets why the issue matters: what an attacker can do, or what goes wrong
+    pub fn rationale(mut self, rationale: &str) -> Self {
+        self.rationale = Some(rationale.to_string());
+        self
+    }
+
+    /// Adds an example of code reported by the rule (its indentation is removed)
+    pub fn vulnerable_example(mut self, code: &str) -> Self {
+        self.examples.push(RuleExample::vulnerable(code));
+        self
+    }
+
+    /// Adds an example of code fixed as the recommendations suggest (its indentation is removed)
+    pub fn safe_example(mut self, code: &str) -> Self {
+        self.examples.push(RuleExample::safe(code));
+        self
+    }
+
     /// Adds a tag to classify the rule
     pub fn tag(mut self, tag: &str) -> Self {
         self.tags.push(tag.to_string());
@@ -211,21 +521,153 @@ impl RuleBuilder {
         self
     }
 
+    /// Adds the rule to a pack, selectable with `--profile`
+    pub fn pack(mut self, pack: &str) -> Self {
+        if !self.packs.iter().any(|p| p == pack) {
+            self.packs.push(pack.to_string());
+        }
+        self
+    }
+
+    /// Adds the rule to multiple packs
+    pub fn packs(mut self, packs: Vec<&str>) -> Self {
+        for pack in packs {
+            self = self.pack(pack);
+        }
+        self
+    }
+
+    /// Adds the CWE ID of the weakness detected by the rule (e.g. 862, Missing Authorization)
+    pub fn cwe(mut self, cwe: u32) -> Self {
+        if !self.cwe.contains(&cwe) {
+            self.cwe.push(cwe);
+        }
+        self
+    }
+
+    /// Sets the Solana vulnerability class of the rule
+    pub fn category(mut self, category: Category) -> Self {
+        self.category = Some(category);
+        self
+    }
+
+    /// Restricts the rule to the crates built with the given versions of anchor-lang, as a Cargo
+    /// version requirement (e.g. `">=0.28"` for `InterfaceAccount`)
+    ///
+    /// The rule does not run on the crates whose anchor-lang version is outside the range, and
+    /// still runs when the version is unknown.
+    pub fn anchor_versions(mut self, versions: &str) -> Self {
+        self.anchor_versions = Some(versions.to_string());
+        self
+    }
+
     /// Sets whether the rule is enabled by default
     pub fn enabled(mut self, enabled: bool) -> Self {
         self.enabled = enabled;
         self
     }
 
+    /// Take the parts of the rule used to convert DSL query results into findings
+    fn dsl_findings(&mut self) -> Arc<DslFindings> {
+        Arc::new(DslFindings {
+            severity: self.severity.clone(),
+            title: self.title.clone(),
+            description: self.description.clone(),
+            recommendations: self.recommendations.clone(),
+            message: self.message.take(),
+            filters: std::mem::take(&mut self.filters),
+            fixers: std::mem::take(&mut self.fixers),
+        })
+    }
+
+    /// Verify that the rule has all the necessary components
+    fn validate(&self) -> Result<(), RuleBuildError> {
+        let id = self.id.clone();
+        if id.is_empty() {
+            return Err(RuleBuildError::MissingId);
+        }
+        if !is_valid_rule_id(&id) {
+            return Err(RuleBuildError::InvalidId { id });
+        }
+        if self.title.trim().is_empty() {
+            return Err(RuleBuildError::MissingTitle { id });
+        }
+        if self.description.trim().is_empty() {
+            return Err(RuleBuildError::MissingDescription { id });
+        }
+        if let Some(pack) = self.packs.iter().find(|pack| !is_valid_pack_name(pack)) {
+            return Err(RuleBuildError::InvalidPack { id, pack: pack.clone() });
+        }
+        if self.query_builder.is_none() {
+            return Err(RuleBuildError::MissingQuery { id });
+        }
+        self.parse_anchor_versions()?;
+        Ok(())
+    }
+
+    /// Parse the anchor-lang versions of the rule
+    fn parse_anchor_versions(&self) -> Result<Option<VersionReq>, RuleBuildError> {
+        self.anchor_versions
+            .as_deref()
+            .map(|versions| {
+                VersionReq::parse(versions).map_err(|error| RuleBuildError::InvalidAnchorVersions {
+                    id: self.id.clone(),
+                    versions: versions.to_string(),
+                    error: error.to_string(),
+                })
+            })
+            .transpose()
+    }
+
+    /// Builds the rule and adds it to the engine, failing if the ID is already registered
+    pub fn register(self, engine: &mut RuleEngine) -> Result<(), RuleBuildError> {
+        if engine.registry().contains(&self.id) {
+            return Err(RuleBuildError::DuplicateId { id: self.id });
+        }
+        engine.add_rule(self.build()?)
+    }
+
     /// Builds the rule
-    pub fn build(self) -> Arc<dyn Rule> {
+    pub fn build(mut self) -> Result<Arc<dyn Rule>, RuleBuildError> {
         debug!("Building rule: {}", self.id);
 
-        // Verify that we have all the necessary components
-        let query_builder = self.query_builder.expect("Query builder is required");
+        self.validate()?;
+        let Some(query) = self.query_builder.take() else {
+            return Err(RuleBuildError::MissingQuery { id: self.id });
+        };
+        let mut project_builder = None;
+        let query_builder: QueryFn = match query {
+            RuleQuery::Findings(query_builder) => {
+                if !self.filters.is_empty() || !self.fixers.is_empty() {
+                    warn!("Rule {} defines node filters or fixes but no DSL query, they are ignored", self.id);
+                }
+                query_builder
+            }
+            RuleQuery::Dsl(dsl_builder) => {
+                let dsl_findings = self.dsl_findings();
+                Box::new(move |ast, file_path, span_extractor, params, program| {
+                    let query_result = dsl_builder(ast, file_path, span_extractor, params, program);
+                    dsl_findings.findings(query_result, ast, file_path, span_extractor, program)
+                })
+            }
+            RuleQuery::Project(builder) => {
+                // Project rules do not run per file
+                project_builder = Some((builder, self.dsl_findings()));
+                Box::new(|_ast, _file_path, _span_extractor, _params, _program| Vec::new())
+            }
+        };
+        let anchor_versions = self.parse_anchor_versions()?;
+        let transforms = self.transforms;
+        let params = self.params;
+        let confidence = self.confidence;
         let references = self.references;
         let recommendations = self.recommendations;
+        let rationale = self.rationale;
+        let examples = self.examples;
         let tags = self.tags;
+        let packs = self.packs;
+        let cwe = self.cwe;
+        let category = self.category;
         let enabled = self.enabled;
         let id = self.id.clone();
         let title = self.title.clone();
@@ -235,39 +677,84 @@ impl RuleBuilder {
 
         // Log information about the rule
         if !references.is_empty() {
-            info!("References for rule {id}: {references:?}");
+            debug!("References for rule {id}: {references:?}");
         }
         if !tags.is_empty() {
-            info!("Tags for rule {id}: {tags:?}");
+            debug!("Tags for rule {id}: {tags:?}");
         }
 
         if !enabled {
-            info!("Rule {} is disabled by default", self.id);
+            debug!("Rule {} is disabled by default", self.id);
         }
 
+        // Post-processing shared by the per-file and project checks
+        let id_clone = id.clone();
+        let post_process = Arc::new(move |mut findings: Vec<Finding>| {
+            for finding in &mut findings {
+                finding.confidence = finding.confidence.min(confidence);
+            }
+
+            // Post-process the findings in the order the transformers were added
+            for transform in &transforms {
+                findings = transform(findings);
+            }
+
+            // Only return findings if the rule is enabled
+            if enabled {
+                findings
+            } else {
+                debug!("Rule {id_clone} is disabled, no findings returned");
+                Vec::new()
+            }
+        });
+
         // Create the rule
         let id_clone = id.clone();
-        Arc::new(RustRule::new(
+        let file_post_process = Arc::clone(&post_process);
+        let rule = RustRule::new(
             &id,
             &title,
             &description,
             severity,
             rule_type,
             recommendations,
-            move |ast, file_path, span_extractor| {
+            move |ast, file_path, span_extractor, params, program| {
                 debug!("Executing rule {id_clone} in {file_path}");
 
                 // Execute the query with SpanExtractor and get findings directly
-                let findings = query_builder(ast, file_path, span_extractor);
-
-                // Only return findings if the rule is enabled
-                if enabled {
-                    Ok(findings)
-                } else {
-                    debug!("Rule {id_clone} is disabled, no findings returned");
-                    Ok(Vec::new())
-                }
+                let findings = query_builder(ast, file_path, span_extractor, params, program);
+                Ok(file_post_process(findings))
             },
-        ))
+        )
+        .with_tags(tags)
+        .with_references(references)
+        .with_documentation(rationale, examples)
+        .with_taxonomy(cwe, category)
+        .with_packs(packs)
+        .with_params(params)
+        .with_anchor_versions(anchor_versions);
+
+        let Some((project_builder, dsl_findings)) = project_builder else {
+            return Ok(Arc::new(rule));
+        };
+        Ok(Arc::new(rule.with_project_check(move |files, params| {
+            debug!("Executing rule {id} on {} files", files.len());
+
+            // Node filters can resolve the declarations of every file
+            let program = if dsl_findings.filters.is_empty() {
+                ProgramContext::new()
+            } else {
+                ProgramContext::build(files.iter().map(|file| (file.path, file.ast)))
+            };
+            let findings = project_builder(files, params)
+                .into_file_queries()
+                .flat_map(|(file, query_result)| {
+                    let span_extractor =
+                        crate::analyzer::span_utils::SpanExtractor::new(file.source.to_string(), file.path.to_string());
+                    dsl_findings.findings(query_result, file.ast, file.path, &span_extractor, &program)
+                })
+                .collect();
+            Ok(post_process(findings))
+        })))
     }
 }
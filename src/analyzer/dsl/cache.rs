@@ -0,0 +1,117 @@
+use log::{debug, trace};
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use syn::File;
+
+/// Position of an item in a file: indices into the item lists of the file, nested
+/// modules and impl blocks (e.g. `[3, 0, 2]` is the third item of the impl block
+/// that is the first item of the module at index 3)
+pub type ItemPath = Vec<usize>;
+
+thread_local! {
+    /// Cache of the file currently analyzed by the rule engine on this thread
+    static FILE_CACHE: RefCell<Option<FileCache>> = const { RefCell::new(None) };
+}
+
+/// Results shared by all rules while they analyze the same file
+struct FileCache {
+    /// Address of the cached file
+    file: usize,
+    /// Items found by common traversals (`functions`, `structs`...), by traversal name
+    traversals: HashMap<&'static str, Rc<Vec<ItemPath>>>,
+    /// Arbitrary derived values (e.g. parsed Anchor account structs), by key
+    values: HashMap<String, Rc<dyn Any>>,
+    /// Number of lookups answered from the cache
+    hits: usize,
+}
+
+/// Guard enabling the query cache for a file while it is alive
+///
+/// The rule engine enters a scope before running the rules on a file so the
+/// common traversals are computed once and shared. Outside a scope, queries
+/// are computed from scratch.
+pub struct CacheScope<'a> {
+    _file: PhantomData<&'a File>,
+}
+
+impl<'a> CacheScope<'a> {
+    /// Enable the cache for the given file on the current thread
+    pub fn enter(ast: &'a File) -> Self {
+        FILE_CACHE.with(|cache| {
+            *cache.borrow_mut() = Some(FileCache {
+                file: file_key(ast),
+                traversals: HashMap::new(),
+                values: HashMap::new(),
+                hits: 0,
+            });
+        });
+        Self { _file: PhantomData }
+    }
+}
+
+impl Drop for CacheScope<'_> {
+    fn drop(&mut self) {
+        FILE_CACHE.with(|cache| {
+            if let Some(file_cache) = cache.borrow_mut().take() {
+                debug!("Query cache released after {} hits", file_cache.hits);
+            }
+        });
+    }
+}
+
+/// Helper function to identify a file by its address (stable while the scope borrows it)
+fn file_key(ast: &File) -> usize {
+    std::ptr::from_ref(ast) as usize
+}
+
+/// Returns the items found by a traversal of the file, computing them only once per scope
+pub fn traversal(ast: &File, name: &'static str, compute: impl FnOnce() -> Vec<ItemPath>) -> Rc<Vec<ItemPath>> {
+    let cached = FILE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let file_cache = cache.as_mut().filter(|file_cache| file_cache.file == file_key(ast))?;
+        let paths = file_cache.traversals.get(name).cloned()?;
+        file_cache.hits += 1;
+        Some(paths)
+    });
+    if let Some(paths) = cached {
+        trace!("Query cache hit: {name}");
+        return paths;
+    }
+
+    let paths = Rc::new(compute());
+    FILE_CACHE.with(|cache| {
+        if let Some(file_cache) = cache.borrow_mut().as_mut().filter(|file_cache| file_cache.file == file_key(ast)) {
+            file_cache.traversals.insert(name, Rc::clone(&paths));
+        }
+    });
+    paths
+}
+
+/// Returns a value derived from the file being analyzed, computing it only once per scope
+///
+/// Rules use it to share expensive models (e.g. Anchor account structs parsed with
+/// anchor-syn) under a key that identifies the item they derive from (name and position).
+pub fn memoize<T: 'static>(key: &str, compute: impl FnOnce() -> T) -> Rc<T> {
+    let cached = FILE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let file_cache = cache.as_mut()?;
+        let value = file_cache.values.get(key).cloned()?.downcast::<T>().ok()?;
+        file_cache.hits += 1;
+        Some(value)
+    });
+    if let Some(value) = cached {
+        trace!("Query cache hit: {key}");
+        return value;
+    }
+
+    let value = Rc::new(compute());
+    FILE_CACHE.with(|cache| {
+        if let Some(file_cache) = cache.borrow_mut().as_mut() {
+            file_cache.values.insert(key.to_string(), Rc::clone(&value) as Rc<dyn Any>);
+        }
+    });
+    value
+}
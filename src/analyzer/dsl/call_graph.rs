@@ -0,0 +1,634 @@
+use log::{debug, trace};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{Attribute, Expr, File, Item};
+
+use crate::analyzer::dsl::program::context_accounts;
+use crate::analyzer::dsl::query::{AstNode, NodeData};
+use crate::analyzer::dsl::values::compact;
+
+/// Maximum depth of the helper calls followed when collecting the checks of a function
+const MAX_CALL_DEPTH: usize = 8;
+
+/// Conversions looked through when an account is passed to a helper
+/// (`check_signer(&ctx.accounts.authority.to_account_info())`)
+const CONVERSIONS: &[&str] = &[".to_account_info()", ".as_ref()", ".clone()"];
+
+/// Intra-crate call graph built from the functions of an AST
+///
+/// Functions are keyed by name, so calls are resolved by the last path segment
+/// (`helpers::validate(..)`, `Self::validate(..)` and `ctx.validate()` all resolve
+/// to `validate`). Calls to functions that are not defined in the analyzed code
+/// are ignored.
+pub struct CallGraph<'a> {
+    /// Function nodes by name (several impls may define the same name)
+    functions: BTreeMap<String, Vec<AstNode<'a>>>,
+    /// Names of the functions called by each function
+    callees: BTreeMap<String, BTreeSet<String>>,
+    /// Names of the functions calling each function
+    callers: BTreeMap<String, BTreeSet<String>>,
+    /// Names of the Anchor instruction handlers
+    instructions: BTreeSet<String>,
+}
+
+impl<'a> CallGraph<'a> {
+    /// Build the call graph of a file
+    pub fn from_file(ast: &'a File) -> Self {
+        debug!("Building call graph");
+        let mut graph = Self {
+            functions: BTreeMap::new(),
+            callees: BTreeMap::new(),
+            callers: BTreeMap::new(),
+            instructions: BTreeSet::new(),
+        };
+
+        graph.collect_items(&ast.items, false);
+
+        // Resolve calls only against functions defined in the analyzed code
+        let names: BTreeSet<String> = graph.functions.keys().cloned().collect();
+        for (name, nodes) in &graph.functions {
+            for node in nodes {
+                for callee in called_names(node) {
+                    if names.contains(&callee) {
+                        trace!("Call edge: {name} -> {callee}");
+                        graph.callees.entry(name.clone()).or_default().insert(callee.clone());
+                        graph.callers.entry(callee).or_default().insert(name.clone());
+                    }
+                }
+            }
+        }
+
+        // Without a #[program] module fall back to public functions taking a Context
+        if graph.instructions.is_empty() {
+            for (name, nodes) in &graph.functions {
+                if nodes.iter().any(is_public_context_handler) {
+                    graph.instructions.insert(name.clone());
+                }
+            }
+        }
+
+        debug!(
+            "Call graph built with {} functions and {} instruction handlers",
+            graph.functions.len(),
+            graph.instructions.len()
+        );
+        graph
+    }
+
+    /// Helper function to register functions recursively (including nested modules and impl blocks)
+    fn collect_items(&mut self, items: &'a [Item], in_program: bool) {
+        for item in items {
+            match item {
+                Item::Fn(func) => {
+                    let name = func.sig.ident.to_string();
+                    if in_program && matches!(func.vis, syn::Visibility::Public(_)) {
+                        self.instructions.insert(name.clone());
+                    }
+                    self.functions.entry(name).or_default().push(AstNode::from_function(func));
+                }
+                Item::Mod(module) => {
+                    if let Some((_, items)) = &module.content {
+                        let is_program = module.attrs.iter().any(|attr| attr.path().is_ident("program"));
+                        self.collect_items(items, in_program || is_program);
+                    }
+                }
+                Item::Impl(impl_block) => {
+                    for impl_item in &impl_block.items {
+                        if let syn::ImplItem::Fn(func) = impl_item {
+                            self.functions
+                                .entry(func.sig.ident.to_string())
+                                .or_default()
+                                .push(AstNode::from_impl_function(func));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the function nodes with the given name
+    pub fn function(&self, name: &str) -> &[AstNode<'a>] {
+        self.functions.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the names of the functions directly called by `name`
+    pub fn callees(&self, name: &str) -> impl Iterator<Item = &str> {
+        self.callees.get(name).into_iter().flatten().map(String::as_str)
+    }
+
+    /// Returns the names of the functions directly calling `name`
+    pub fn callers(&self, name: &str) -> impl Iterator<Item = &str> {
+        self.callers.get(name).into_iter().flatten().map(String::as_str)
+    }
+
+    /// Returns the names of the Anchor instruction handlers
+    pub fn instructions(&self) -> impl Iterator<Item = &str> {
+        self.instructions.iter().map(String::as_str)
+    }
+
+    /// Returns the names of all functions reachable from the given roots (roots included)
+    pub fn reachable_from<'n>(&self, roots: impl IntoIterator<Item = &'n str>) -> BTreeSet<String> {
+        let mut visited = BTreeSet::new();
+        let mut queue: VecDeque<String> = roots.into_iter().map(str::to_string).collect();
+
+        while let Some(name) = queue.pop_front() {
+            if visited.insert(name.clone()) {
+                queue.extend(self.callees(&name).map(str::to_string));
+            }
+        }
+
+        visited
+    }
+
+    /// Check if `name` calls `target` directly or through any chain of helper functions
+    pub fn calls_transitively(&self, name: &str, target: &str) -> bool {
+        let reachable = self.reachable_from([name]);
+        reachable.iter().any(|function| {
+            self.function(function)
+                .iter()
+                .any(|node| called_names(node).iter().any(|callee| callee == target))
+        })
+    }
+}
+
+/// Returns the names of all functions and methods called in a function node
+pub fn called_names(node: &AstNode<'_>) -> BTreeSet<String> {
+    let mut collector = CalleeCollector {
+        names: BTreeSet::new(),
+    };
+    match node.data {
+        NodeData::Function(func) => collector.visit_block(&func.block),
+        NodeData::ImplFunction(func) => collector.visit_block(&func.block),
+        _ => {}
+    }
+    collector.names
+}
+
+/// Helper function to check if a function looks like an Anchor instruction handler
+fn is_public_context_handler(node: &AstNode<'_>) -> bool {
+    let (vis, sig) = match node.data {
+        NodeData::Function(func) => (&func.vis, &func.sig),
+        NodeData::ImplFunction(func) => (&func.vis, &func.sig),
+        _ => return false,
+    };
+
+    matches!(vis, syn::Visibility::Public(_))
+        && sig.inputs.iter().any(|input| {
+            if let syn::FnArg::Typed(pat_type) = input
+                && let syn::Type::Path(type_path) = &*pat_type.ty
+            {
+                type_path
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|segment| segment.ident == "Context")
+            } else {
+                false
+            }
+        })
+}
+
+/// Helper visitor to collect the names of called functions and methods
+struct CalleeCollector {
+    names: BTreeSet<String>,
+}
+
+impl<'ast> Visit<'ast> for CalleeCollector {
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(path) = &*call.func
+            && let Some(segment) = path.path.segments.last()
+        {
+            self.names.insert(segment.ident.to_string());
+        }
+
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, method_call: &'ast syn::ExprMethodCall) {
+        self.names.insert(method_call.method.to_string());
+
+        visit::visit_expr_method_call(self, method_call);
+    }
+}
+
+/// Call graph of all the files of a program
+///
+/// Unlike [`CallGraph`], which borrows the AST of a single file, it owns a summary of every
+/// free function and impl method of the program: its parameters, the calls it makes (including
+/// the `#[access_control]` ones of handlers) and the checks it performs. It is built with the
+/// [`ProgramContext`](crate::analyzer::dsl::ProgramContext), so rules can credit the validation
+/// done in helpers, in any file, to the handlers calling them (see [`ProgramCallGraph::checks_from`]).
+///
+/// Calls are resolved by name like in [`CallGraph`]; when several impls define the called
+/// name, the type qualifying the call (`Withdraw::validate(..)`, `self.validate()` or
+/// `ctx.accounts.validate()` in a handler of `Withdraw`) selects the impl.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ProgramCallGraph {
+    /// Function summaries by name (several impls may define the same name)
+    functions: BTreeMap<String, Vec<FunctionSummary>>,
+}
+
+/// Summary of a function of the program
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FunctionSummary {
+    /// Name of the function
+    pub name: String,
+    /// Type of the impl block of methods (`Withdraw` for `impl<'info> Withdraw<'info>`)
+    pub owner: Option<String>,
+    /// File of the function
+    pub file: String,
+    /// Line of the function name
+    pub line: usize,
+    /// Names of the parameters, `self` included (`_` for patterns)
+    pub params: Vec<String>,
+    /// Whether the function is an instruction handler (public function of the `#[program]`
+    /// module or taking a `Context`)
+    pub instruction: bool,
+    /// Accounts struct of handlers (the `T` of `Context<T>`)
+    pub accounts: Option<String>,
+    /// Calls made by the function, in order
+    pub calls: Vec<CallSite>,
+    /// Checks performed by the function, in order
+    pub checks: Vec<Check>,
+}
+
+/// Call of a function or method
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CallSite {
+    /// Name of the called function or method
+    pub callee: String,
+    /// Type the call is made on, if known (`Withdraw` for `Withdraw::validate(..)`)
+    pub owner: Option<String>,
+    /// Receiver of method calls, without spaces
+    pub receiver: Option<String>,
+    /// Arguments, without spaces and references
+    pub args: Vec<String>,
+    /// Line of the call
+    pub line: usize,
+}
+
+/// Check performed by a function: `require!`-like or `assert!`-like macro, or `if` condition
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Check {
+    /// Macro call or condition, without the spaces around paths and calls
+    /// (`require_keys_eq!(vault.authority, authority.key())`, `amount > vault.limit`)
+    pub condition: String,
+    /// File of the check
+    pub file: String,
+    /// Line of the check
+    pub line: usize,
+}
+
+impl ProgramCallGraph {
+    /// Creates an empty call graph
+    pub const fn new() -> Self {
+        Self {
+            functions: BTreeMap::new(),
+        }
+    }
+
+    /// Build the call graph of the given files (path and AST)
+    pub fn build<'a>(files: impl IntoIterator<Item = (&'a str, &'a File)>) -> Self {
+        let mut graph = Self::new();
+        for (file_path, ast) in files {
+            graph.collect_items(file_path, &ast.items, false);
+        }
+        debug!("Program call graph built with {} functions", graph.functions().count());
+        graph
+    }
+
+    /// Helper function to summarize the functions of a module, recursively
+    fn collect_items(&mut self, file_path: &str, items: &[Item], in_program: bool) {
+        for item in items {
+            match item {
+                Item::Fn(func) => {
+                    let accounts = context_accounts(&func.sig);
+                    let instruction = matches!(func.vis, syn::Visibility::Public(_)) && (in_program || accounts.is_some());
+                    let summary = summarize(file_path, None, &func.sig, &func.attrs, &func.block, instruction);
+                    self.functions.entry(summary.name.clone()).or_default().push(summary);
+                }
+                Item::Mod(module) => {
+                    if let Some((_, items)) = &module.content {
+                        let is_program = module.attrs.iter().any(|attr| attr.path().is_ident("program"));
+                        self.collect_items(file_path, items, in_program || is_program);
+                    }
+                }
+                Item::Impl(impl_block) => {
+                    let owner = match &*impl_block.self_ty {
+                        syn::Type::Path(type_path) => type_path.path.segments.last().map(|segment| segment.ident.to_string()),
+                        _ => None,
+                    };
+                    for impl_item in &impl_block.items {
+                        if let syn::ImplItem::Fn(func) = impl_item {
+                            let summary = summarize(file_path, owner.clone(), &func.sig, &func.attrs, &func.block, false);
+                            self.functions.entry(summary.name.clone()).or_default().push(summary);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns a copy of the call graph without the lines of its functions, calls and checks
+    pub fn without_positions(&self) -> Self {
+        let mut graph = self.clone();
+        for function in graph.functions.values_mut().flatten() {
+            function.line = 0;
+            function.calls.iter_mut().for_each(|call| call.line = 0);
+            function.checks.iter_mut().for_each(|check| check.line = 0);
+        }
+        graph
+    }
+
+    /// Returns the functions with the given name
+    pub fn function(&self, name: &str) -> &[FunctionSummary] {
+        self.functions.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns every function of the program
+    pub fn functions(&self) -> impl Iterator<Item = &FunctionSummary> {
+        self.functions.values().flatten()
+    }
+
+    /// Returns the instruction handlers
+    pub fn instructions(&self) -> impl Iterator<Item = &FunctionSummary> {
+        self.functions().filter(|function| function.instruction)
+    }
+
+    /// Returns the instruction handlers taking the given accounts struct
+    pub fn handlers_of<'g>(&'g self, accounts: &'g str) -> impl Iterator<Item = &'g FunctionSummary> {
+        self.instructions().filter(move |function| function.accounts.as_deref() == Some(accounts))
+    }
+
+    /// Returns the functions of the program a call may reach
+    pub fn targets(&self, call: &CallSite) -> Vec<&FunctionSummary> {
+        let candidates = self.function(&call.callee);
+        let owned: Vec<&FunctionSummary> = candidates
+            .iter()
+            .filter(|function| call.owner.is_some() && function.owner == call.owner)
+            .collect();
+        if owned.is_empty() { candidates.iter().collect() } else { owned }
+    }
+
+    /// Returns the names of the functions of the program called by the functions named `name`
+    pub fn callees(&self, name: &str) -> BTreeSet<&str> {
+        self.function(name)
+            .iter()
+            .flat_map(|function| &function.calls)
+            .filter(|call| !self.function(&call.callee).is_empty())
+            .map(|call| call.callee.as_str())
+            .collect()
+    }
+
+    /// Returns the names of the functions calling a function named `name`
+    pub fn callers(&self, name: &str) -> BTreeSet<&str> {
+        self.functions()
+            .filter(|function| function.calls.iter().any(|call| call.callee == name))
+            .map(|function| function.name.as_str())
+            .collect()
+    }
+
+    /// Returns the names of all functions reachable from the given roots (roots included)
+    pub fn reachable_from<'n>(&self, roots: impl IntoIterator<Item = &'n str>) -> BTreeSet<String> {
+        let mut visited = BTreeSet::new();
+        let mut queue: VecDeque<String> = roots.into_iter().map(str::to_string).collect();
+
+        while let Some(name) = queue.pop_front() {
+            if visited.insert(name.clone()) {
+                queue.extend(self.callees(&name).into_iter().map(str::to_string));
+            }
+        }
+
+        visited
+    }
+
+    /// Returns the checks performed by a function and by the helpers it calls
+    ///
+    /// The checks of helpers are written with the arguments of the call instead of the
+    /// parameters: `require!(account.is_signer)` in `fn check_signer(account: &AccountInfo)`
+    /// called as `check_signer(&ctx.accounts.authority)` gives
+    /// `require!(ctx.accounts.authority.is_signer)`, and `self.authority` in a method called as
+    /// `ctx.accounts.validate()` gives `ctx.accounts.authority`. At most [`MAX_CALL_DEPTH`]
+    /// levels of helpers are followed.
+    pub fn checks_from(&self, function: &FunctionSummary) -> Vec<Check> {
+        let mut checks = Vec::new();
+        let mut visited = BTreeSet::new();
+        self.collect_checks(function, &BTreeMap::new(), 0, &mut visited, &mut checks);
+        trace!("{} checks reachable from {}", checks.len(), function.name);
+        checks
+    }
+
+    /// Helper function to collect the checks of a function called with the given bindings of
+    /// its parameters, and of its callees
+    fn collect_checks<'g>(
+        &'g self,
+        function: &'g FunctionSummary,
+        bindings: &BTreeMap<String, String>,
+        depth: usize,
+        visited: &mut BTreeSet<(&'g str, usize, Vec<String>)>,
+        checks: &mut Vec<Check>,
+    ) {
+        // The same function called with the same arguments gives the same checks
+        let key = (function.file.as_str(), function.line, bindings.values().cloned().collect());
+        if depth > MAX_CALL_DEPTH || !visited.insert(key) {
+            return;
+        }
+
+        checks.extend(function.checks.iter().map(|check| Check {
+            condition: substitute(&check.condition, bindings),
+            ..check.clone()
+        }));
+        for call in &function.calls {
+            for target in self.targets(call) {
+                let mut values: Vec<String> = call.receiver.iter().chain(&call.args).map(|value| substitute(value, bindings)).collect();
+                // `Type::method(&accounts)` passes the receiver as the first argument
+                if call.receiver.is_some() && target.params.first().is_none_or(|param| param != "self") {
+                    values.remove(0);
+                }
+                let callee_bindings = target
+                    .params
+                    .iter()
+                    .zip(values)
+                    .filter(|(param, _)| *param != "_")
+                    .map(|(param, value)| (param.clone(), value))
+                    .collect();
+                self.collect_checks(target, &callee_bindings, depth + 1, visited, checks);
+            }
+        }
+    }
+}
+
+/// Helper function to summarize a function or method
+fn summarize(file_path: &str, owner: Option<String>, sig: &syn::Signature, attrs: &[Attribute], block: &syn::Block, instruction: bool) -> FunctionSummary {
+    let params = sig
+        .inputs
+        .iter()
+        .map(|input| match input {
+            syn::FnArg::Receiver(_) => "self".to_string(),
+            syn::FnArg::Typed(pat_type) => match &*pat_type.pat {
+                syn::Pat::Ident(pat_ident) => pat_ident.ident.to_string(),
+                _ => "_".to_string(),
+            },
+        })
+        .collect();
+    let accounts = context_accounts(sig).filter(|accounts| !accounts.is_empty());
+    let mut collector = SummaryCollector {
+        file: file_path,
+        owner: owner.as_deref(),
+        accounts: accounts.as_deref(),
+        calls: Vec::new(),
+        checks: Vec::new(),
+    };
+
+    // `#[access_control(Withdraw::validate(&ctx))]` runs before the handler
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("access_control")) {
+        if let Ok(exprs) = attr.parse_args_with(Punctuated::<Expr, syn::Token![,]>::parse_terminated) {
+            for expr in &exprs {
+                collector.visit_expr(expr);
+            }
+        }
+    }
+    collector.visit_block(block);
+    let SummaryCollector { calls, checks, .. } = collector;
+
+    FunctionSummary {
+        name: sig.ident.to_string(),
+        owner,
+        file: file_path.to_string(),
+        line: sig.ident.span().start().line,
+        params,
+        instruction,
+        accounts,
+        calls,
+        checks,
+    }
+}
+
+/// Helper visitor to collect the calls and checks of a function
+struct SummaryCollector<'a> {
+    file: &'a str,
+    /// Type of the impl block of methods
+    owner: Option<&'a str>,
+    /// Accounts struct of handlers
+    accounts: Option<&'a str>,
+    calls: Vec<CallSite>,
+    checks: Vec<Check>,
+}
+
+impl SummaryCollector<'_> {
+    fn check(&mut self, condition: String, line: usize) {
+        self.checks.push(Check {
+            condition,
+            file: self.file.to_string(),
+            line,
+        });
+    }
+}
+
+impl<'ast> Visit<'ast> for SummaryCollector<'_> {
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let Expr::Path(path) = &*call.func
+            && let Some(segment) = path.path.segments.last()
+        {
+            let segments = &path.path.segments;
+            let owner = (segments.len() > 1).then(|| segments[segments.len() - 2].ident.to_string()).and_then(|owner| {
+                if owner == "Self" { self.owner.map(str::to_string) } else { Some(owner) }
+            });
+            self.calls.push(CallSite {
+                callee: segment.ident.to_string(),
+                owner,
+                receiver: None,
+                args: call.args.iter().map(argument).collect(),
+                line: call.span().start().line,
+            });
+        }
+
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, method_call: &'ast syn::ExprMethodCall) {
+        let receiver = argument(&method_call.receiver);
+        let owner = if receiver == "self" {
+            self.owner
+        } else if receiver.ends_with(".accounts") {
+            self.accounts
+        } else {
+            None
+        };
+        self.calls.push(CallSite {
+            callee: method_call.method.to_string(),
+            owner: owner.map(str::to_string),
+            receiver: Some(receiver),
+            args: method_call.args.iter().map(argument).collect(),
+            line: method_call.method.span().start().line,
+        });
+
+        visit::visit_expr_method_call(self, method_call);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        let name = mac.path.segments.last().map(|segment| segment.ident.to_string()).unwrap_or_default();
+        if name.starts_with("require") || name.starts_with("assert") {
+            self.check(format!("{name}!({})", compact(&mac.tokens)), mac.span().start().line);
+        }
+        // Calls made in macro arguments (`require!(is_admin(&ctx.accounts.admin), ..)`)
+        if let Ok(exprs) = mac.parse_body_with(Punctuated::<Expr, syn::Token![,]>::parse_terminated) {
+            for expr in &exprs {
+                self.visit_expr(expr);
+            }
+        }
+    }
+
+    fn visit_expr_if(&mut self, expr_if: &'ast syn::ExprIf) {
+        self.check(compact(&expr_if.cond), expr_if.cond.span().start().line);
+
+        visit::visit_expr_if(self, expr_if);
+    }
+}
+
+/// Helper function to write an argument without spaces, references and conversions
+fn argument(expr: &Expr) -> String {
+    let mut argument = compact(expr);
+    loop {
+        let stripped = argument.trim_start_matches('&').trim_start().trim_start_matches("mut ").trim_start();
+        let stripped = CONVERSIONS.iter().find_map(|conversion| stripped.strip_suffix(conversion)).unwrap_or(stripped);
+        if stripped == argument {
+            return argument;
+        }
+        argument = stripped.to_string();
+    }
+}
+
+/// Helper function to replace the parameters of a function by the arguments of a call
+///
+/// Only whole identifiers that are not fields are replaced (`account.key` becomes
+/// `ctx.accounts.authority.key` when `account` is `ctx.accounts.authority`, `self.account` is
+/// left alone).
+fn substitute(text: &str, bindings: &BTreeMap<String, String>) -> String {
+    if bindings.is_empty() {
+        return text.to_string();
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut word = String::new();
+    for character in text.chars().map(Some).chain([None]) {
+        if let Some(character) = character
+            && (character.is_alphanumeric() || character == '_')
+        {
+            word.push(character);
+            continue;
+        }
+        if !word.is_empty() {
+            match bindings.get(&word) {
+                Some(value) if !result.ends_with('.') => result.push_str(value),
+                _ => result.push_str(&word),
+            }
+            word.clear();
+        }
+        result.extend(character);
+    }
+    result
+}
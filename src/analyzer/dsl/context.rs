@@ -0,0 +1,165 @@
+use proc_macro2::LineColumn;
+use std::path::{Component, Path};
+use syn::spanned::Spanned;
+use syn::{File, Item, ItemMod};
+
+use crate::analyzer::dsl::aliases::AccountAliases;
+use crate::analyzer::dsl::program::{Declaration, EMPTY_PROGRAM, ProgramContext};
+use crate::analyzer::dsl::query::AstNode;
+use crate::analyzer::dsl::taint::{TaintConfig, TaintFlow};
+use crate::analyzer::dsl::values::{Constants, ValueAnalysis};
+use crate::ast::modules::is_test_attribute;
+
+/// Context available to `RuleBuilder::filter` hooks when deciding whether to keep a node
+///
+/// Gives access to the analyzed file (path and AST) so filters can look at the
+/// surroundings of a matched node: its attributes, the modules it is nested in
+/// and whether it belongs to test code. The declarations of the other files of the
+/// program are available through [`NodeContext::resolve`].
+pub struct NodeContext<'a> {
+    /// Path of the analyzed file
+    pub file_path: &'a str,
+    /// AST of the analyzed file
+    pub ast: &'a File,
+    /// Declarations of all the files of the program (empty if the file is analyzed alone)
+    pub program: &'a ProgramContext,
+}
+
+impl<'a> NodeContext<'a> {
+    /// Creates a new context for the given file
+    pub fn new(file_path: &'a str, ast: &'a File) -> Self {
+        Self {
+            file_path,
+            ast,
+            program: &EMPTY_PROGRAM,
+        }
+    }
+
+    /// Sets the declarations of the program the file belongs to
+    pub fn with_program(mut self, program: &'a ProgramContext) -> Self {
+        self.program = program;
+        self
+    }
+
+    /// Resolve a type, constant or function used in the file, wherever it is declared in the program
+    pub fn resolve(&self, path: &str) -> Option<&'a Declaration> {
+        self.program.resolve(self.file_path, path)
+    }
+
+    /// Resolve a type used in the file to the struct or enum it refers to, looking through
+    /// aliases and wrappers (`Account<'info, Vault>` resolves to `Vault`)
+    pub fn resolve_type(&self, ty: &str) -> Option<&'a Declaration> {
+        self.program.resolve_type(self.file_path, ty)
+    }
+
+    /// Returns the integer constants of the file and of the rest of the program
+    pub fn constants(&self) -> Constants {
+        let mut constants = Constants::from_program(self.program);
+        constants.extend(&Constants::from_items(&self.ast.items));
+        constants
+    }
+
+    /// Analyze the constants and value ranges of a function node (`None` for other nodes), to
+    /// evaluate its expressions with `eval_const` and `range_of` or find the checks guarding
+    /// them with `guarded_by`
+    pub fn values(&self, node: &AstNode<'_>) -> Option<ValueAnalysis> {
+        ValueAnalysis::of_node(node, &self.constants())
+    }
+
+    /// Track the local variables of a function node referring to its accounts (`None` for
+    /// other nodes), to resolve `vault.amount` to `ctx.accounts.vault.amount` with `resolve` or
+    /// list the accounts written with `writes`
+    pub fn aliases(&self, node: &AstNode<'_>) -> Option<AccountAliases> {
+        AccountAliases::of_node(node)
+    }
+
+    /// Returns the flows of untrusted values to sinks in a function node (none for other nodes),
+    /// resolving the accounts struct of handlers in the program
+    pub fn taint_flows(&self, node: &AstNode<'_>, config: &TaintConfig) -> Vec<TaintFlow> {
+        config.flows(node, self.program, self.file_path)
+    }
+
+    /// Check if the file path looks like test code (`tests/` directories or `*_test.rs` / `test.rs` files)
+    pub fn is_test_file(&self) -> bool {
+        let path = Path::new(self.file_path);
+        let in_tests_dir = path.components().any(|component| {
+            matches!(component, Component::Normal(name) if name == "tests" || name == "test")
+        });
+        let test_file_name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem == "test" || stem == "tests" || stem.ends_with("_test") || stem.ends_with("_tests"));
+
+        in_tests_dir || test_file_name
+    }
+
+    /// Returns the inline modules enclosing the node, outermost first
+    pub fn enclosing_modules(&self, node: &AstNode<'_>) -> Vec<&'a ItemMod> {
+        let mut modules = Vec::new();
+        if let Some(spanned) = node.get_spanned_node() {
+            let span = spanned.span();
+            collect_enclosing_modules(&self.ast.items, span.start(), span.end(), &mut modules);
+        }
+        modules
+    }
+
+    /// Returns the path of the modules enclosing the node, from the crate root and without `crate`
+    /// (e.g. `["instructions", "tests", "helpers"]`)
+    ///
+    /// The module of the file comes from the `mod` declarations of the program, so a module
+    /// declared in its own file has the same path as an inline one. Files analyzed alone only
+    /// have their inline modules.
+    pub fn module_path(&self, node: &AstNode<'_>) -> Vec<String> {
+        let mut path: Vec<String> = self
+            .program
+            .module_of(self.file_path)
+            .map(|module| module.path.iter().skip(1).cloned().collect())
+            .unwrap_or_default();
+        path.extend(self.enclosing_modules(node).iter().map(|module| module.ident.to_string()));
+        path
+    }
+
+    /// Check if the node or any enclosing module has an attribute with the given name
+    pub fn has_attribute(&self, node: &AstNode<'_>, name: &str) -> bool {
+        node.attrs().iter().any(|attr| attr.path().is_ident(name))
+            || self
+                .enclosing_modules(node)
+                .iter()
+                .any(|module| module.attrs.iter().any(|attr| attr.path().is_ident(name)))
+    }
+
+    /// Check if the node belongs to test code (test file, `#[test]` function or `#[cfg(test)]` module,
+    /// inline or declared with `#[cfg(test)] mod tests;`)
+    ///
+    /// Nothing is test code when the program includes the tests (`--include-tests`), so the
+    /// filters skipping it report everything.
+    pub fn is_test_code(&self, node: &AstNode<'_>) -> bool {
+        if self.program.includes_tests() {
+            return false;
+        }
+        self.is_test_file()
+            || self.program.module_of(self.file_path).is_some_and(|module| module.test)
+            || node.attrs().iter().any(is_test_attribute)
+            || self
+                .enclosing_modules(node)
+                .iter()
+                .any(|module| module.attrs.iter().any(is_test_attribute))
+    }
+}
+
+/// Helper function to collect the modules whose span contains the given range
+fn collect_enclosing_modules<'a>(items: &'a [Item], start: LineColumn, end: LineColumn, modules: &mut Vec<&'a ItemMod>) {
+    for item in items {
+        if let Item::Mod(module) = item
+            && let Some((_, content)) = &module.content
+        {
+            let span = module.span();
+            if span.start() <= start && end <= span.end() {
+                modules.push(module);
+                collect_enclosing_modules(content, start, end, modules);
+                return;
+            }
+        }
+    }
+}
+
@@ -0,0 +1,73 @@
+/// Declares a rule in a single place: its `create_rule()` function and its registration
+///
+/// Every entry is a `RuleBuilder` setter followed by its argument, except:
+/// - `severity: High` - shorthand for `Severity::High`
+/// - `category: Arithmetic` - shorthand for `Category::Arithmetic`
+/// - `query: structs().derives_accounts()` - DSL chain applied to `AstQuery::new(ast)`
+///
+/// ```ignore
+/// rule! {
+///     id: "solana-unsafe-code",
+///     title: "Unsafe Code Usage",
+///     description: "Using unsafe code in Solana programs can lead to security vulnerabilities",
+///     severity: High,
+///     tag: "security",
+///     recommendations: vec!["Avoid using unsafe code in Solana programs"],
+///     query: functions().uses_unsafe(),
+/// }
+/// ```
+///
+/// The rule is registered automatically, so adding a rule only requires its module.
+macro_rules! rule {
+    // Rule fully parsed: emit the constructor and register it
+    (@parse [$($builder:tt)*]) => {
+        pub fn create_rule() -> ::std::result::Result<
+            ::std::sync::Arc<dyn $crate::analyzer::Rule>,
+            $crate::analyzer::dsl::RuleBuildError,
+        > {
+            $($builder)*.build()
+        }
+
+        ::inventory::submit! {
+            $crate::analyzer::rules::RuleRegistration::new(create_rule)
+        }
+    };
+
+    // DSL chain applied to the analyzed file
+    (@parse [$($builder:tt)*] query: $($method:ident ( $($args:tt)* )).+ $(, $($rest:tt)*)?) => {
+        $crate::analyzer::dsl::rule!(@parse [
+            $($builder)*.dsl_query(|ast, _file_path, _span_extractor| {
+                $crate::analyzer::dsl::AstQuery::new(ast) $(.$method($($args)*))+
+            })
+        ] $($($rest)*)?);
+    };
+
+    // Severity shorthand
+    (@parse [$($builder:tt)*] severity: $severity:ident $(, $($rest:tt)*)?) => {
+        $crate::analyzer::dsl::rule!(@parse [
+            $($builder)*.severity($crate::analyzer::Severity::$severity)
+        ] $($($rest)*)?);
+    };
+
+    // Category shorthand
+    (@parse [$($builder:tt)*] category: $category:ident $(, $($rest:tt)*)?) => {
+        $crate::analyzer::dsl::rule!(@parse [
+            $($builder)*.category($crate::analyzer::Category::$category)
+        ] $($($rest)*)?);
+    };
+
+    // Any other RuleBuilder setter
+    (@parse [$($builder:tt)*] $setter:ident : $value:expr $(, $($rest:tt)*)?) => {
+        $crate::analyzer::dsl::rule!(@parse [$($builder)*.$setter($value)] $($($rest)*)?);
+    };
+
+    (@parse [$($builder:tt)*] $($rest:tt)*) => {
+        compile_error!(concat!("invalid rule! entry: ", stringify!($($rest)*)));
+    };
+
+    ($($body:tt)*) => {
+        $crate::analyzer::dsl::rule!(@parse [$crate::analyzer::dsl::RuleBuilder::new()] $($body)*);
+    };
+}
+
+pub(crate) use rule;
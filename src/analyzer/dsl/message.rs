@@ -0,0 +1,69 @@
+use crate::analyzer::dsl::query::{AstNode, NodeType};
+
+/// Template for finding descriptions with `{placeholder}` interpolation
+///
+/// Supported placeholders:
+/// - `{title}` and `{description}`: the rule's title and description
+/// - `{name}`: the name of the matched node
+/// - `{struct}` / `{function}`: the name of the matched struct / function
+/// - any metadata key attached by filters (e.g. `{field}`, `{value}`)
+///
+/// Unknown placeholders are left untouched so mistakes are visible in reports.
+#[derive(Debug, Clone)]
+pub struct MessageTemplate {
+    template: String,
+}
+
+impl MessageTemplate {
+    /// Creates a new template
+    pub fn new(template: &str) -> Self {
+        Self {
+            template: template.to_string(),
+        }
+    }
+
+    /// Render the template for a matched node
+    pub fn render(&self, node: &AstNode<'_>, title: &str, description: &str) -> String {
+        let mut rendered = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+
+            match after.find('}') {
+                Some(end) => {
+                    let key = &after[..end];
+                    match Self::resolve(key, node, title, description) {
+                        Some(value) => rendered.push_str(&value),
+                        None => {
+                            rendered.push('{');
+                            rendered.push_str(key);
+                            rendered.push('}');
+                        }
+                    }
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    rendered.push_str(&rest[start..]);
+                    rest = "";
+                }
+            }
+        }
+        rendered.push_str(rest);
+
+        rendered
+    }
+
+    /// Helper function to resolve a placeholder value
+    fn resolve(key: &str, node: &AstNode<'_>, title: &str, description: &str) -> Option<String> {
+        match key {
+            "title" => Some(title.to_string()),
+            "description" => Some(description.to_string()),
+            "name" => Some(node.name()),
+            "struct" if node.node_type == NodeType::Struct => Some(node.name()),
+            "function" if node.node_type == NodeType::Function => Some(node.name()),
+            _ => node.metadata(key).map(ToString::to_string),
+        }
+    }
+}
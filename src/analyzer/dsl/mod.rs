@@ -1,5 +1,22 @@
+pub mod aliases;
 pub mod builders;
+pub mod cache;
+pub mod call_graph;
+pub mod context;
+mod macros;
+pub mod message;
+pub mod params;
+pub mod program;
+pub mod project;
 pub mod query;
+pub mod taint;
+pub mod values;
 
-pub use builders::RuleBuilder;
+pub use builders::{RuleBuildError, RuleBuilder};
+pub(crate) use macros::rule;
+pub use program::ProgramContext;
+pub use project::ProjectFile;
 pub use query::AstQuery;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::analyzer::dsl::builders::RuleBuildError;
+
+/// Value of a rule configuration parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamValue {
+    /// Boolean flag
+    Bool(bool),
+    /// Integer value (thresholds, limits...)
+    Int(i64),
+    /// Text value
+    Text(String),
+    /// List of text values (identifier allowlists...)
+    List(Vec<String>),
+}
+
+impl ParamValue {
+    /// Name of the value type, used in error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ParamValue::Bool(_) => "a boolean",
+            ParamValue::Int(_) => "an integer",
+            ParamValue::Text(_) => "a string",
+            ParamValue::List(_) => "a comma-separated list",
+        }
+    }
+
+    /// Parse a raw value (e.g. from the CLI) as the same type as this value
+    pub fn parse_like(&self, raw: &str) -> Option<ParamValue> {
+        let raw = raw.trim();
+        match self {
+            ParamValue::Bool(_) => raw.parse().ok().map(ParamValue::Bool),
+            ParamValue::Int(_) => raw.parse().ok().map(ParamValue::Int),
+            ParamValue::Text(_) => Some(ParamValue::Text(raw.to_string())),
+            ParamValue::List(_) => Some(ParamValue::List(
+                raw.split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamValue::Bool(value) => write!(f, "{value}"),
+            ParamValue::Int(value) => write!(f, "{value}"),
+            ParamValue::Text(value) => write!(f, "{value}"),
+            ParamValue::List(values) => write!(f, "{}", values.join(",")),
+        }
+    }
+}
+
+impl From<bool> for ParamValue {
+    fn from(value: bool) -> Self {
+        ParamValue::Bool(value)
+    }
+}
+
+impl From<i64> for ParamValue {
+    fn from(value: i64) -> Self {
+        ParamValue::Int(value)
+    }
+}
+
+impl From<&str> for ParamValue {
+    fn from(value: &str) -> Self {
+        ParamValue::Text(value.to_string())
+    }
+}
+
+impl From<Vec<String>> for ParamValue {
+    fn from(values: Vec<String>) -> Self {
+        ParamValue::List(values)
+    }
+}
+
+impl From<&[&str]> for ParamValue {
+    fn from(values: &[&str]) -> Self {
+        ParamValue::List(values.iter().map(|value| value.to_string()).collect())
+    }
+}
+
+/// Parameter declared by a rule
+#[derive(Debug, Clone)]
+pub struct RuleParam {
+    /// What the parameter controls
+    pub description: String,
+    /// Value used when the user does not configure the parameter
+    pub default: ParamValue,
+    /// Current value
+    pub value: ParamValue,
+}
+
+/// Configuration parameters of a rule, keyed by name
+///
+/// Rules declare their parameters with defaults through `RuleBuilder::param`; the
+/// engine applies the user's overrides when the rule is loaded and the resolved
+/// values are passed to the rule's query.
+#[derive(Debug, Clone, Default)]
+pub struct RuleParams {
+    params: BTreeMap<String, RuleParam>,
+}
+
+impl RuleParams {
+    /// Creates an empty set of parameters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare a parameter with its default value
+    pub fn declare(&mut self, name: &str, default: ParamValue, description: &str) {
+        self.params.insert(
+            name.to_string(),
+            RuleParam {
+                description: description.to_string(),
+                value: default.clone(),
+                default,
+            },
+        );
+    }
+
+    /// Override a parameter from its raw value, which must match the declared type
+    pub fn set(&mut self, rule_id: &str, name: &str, raw: &str) -> Result<(), RuleBuildError> {
+        let Some(param) = self.params.get_mut(name) else {
+            return Err(RuleBuildError::UnknownParam {
+                id: rule_id.to_string(),
+                param: name.to_string(),
+            });
+        };
+
+        match param.default.parse_like(raw) {
+            Some(value) => {
+                param.value = value;
+                Ok(())
+            }
+            None => Err(RuleBuildError::InvalidParam {
+                id: rule_id.to_string(),
+                param: name.to_string(),
+                value: raw.to_string(),
+                expected: param.default.type_name(),
+            }),
+        }
+    }
+
+    /// Returns the declared parameters
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &RuleParam)> {
+        self.params.iter().map(|(name, param)| (name.as_str(), param))
+    }
+
+    /// Check if no parameters are declared
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Get the current value of a parameter
+    pub fn get(&self, name: &str) -> Option<&ParamValue> {
+        self.params.get(name).map(|param| &param.value)
+    }
+
+    /// Get a boolean parameter
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.get(name)? {
+            ParamValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Get an integer parameter
+    pub fn get_int(&self, name: &str) -> Option<i64> {
+        match self.get(name)? {
+            ParamValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Get a text parameter
+    pub fn get_text(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            ParamValue::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get a list parameter
+    pub fn get_list(&self, name: &str) -> Option<&[String]> {
+        match self.get(name)? {
+            ParamValue::List(values) => Some(values),
+            _ => None,
+        }
+    }
+}
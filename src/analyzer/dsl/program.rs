@@ -0,0 +1,567 @@
+use log::debug;
+use quote::ToTokens;
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Attribute, File, Item, Token};
+
+use crate::analyzer::dsl::call_graph::ProgramCallGraph;
+use crate::analyzer::native;
+use crate::analyzer::workspace::CrateManifest;
+use crate::ast::modules::{FileModule, ModuleTree};
+
+/// Empty context, used when a rule runs without the rest of the program
+pub(crate) static EMPTY_PROGRAM: ProgramContext = ProgramContext::new();
+
+/// Kind of a declaration indexed by the [`ProgramContext`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeclarationKind {
+    /// Struct deriving `Accounts` (accounts of an instruction)
+    Accounts,
+    /// Account data struct (`#[account]`, or deriving `BorshDeserialize` in native programs)
+    State,
+    /// Instruction handler (public function of the `#[program]` module or taking a `Context`)
+    Instruction,
+    /// `const` item
+    Constant,
+    /// Error enum (`#[error_code]`, or deriving `Error`)
+    ErrorEnum,
+    /// Any other struct
+    Struct,
+    /// Any other enum
+    Enum,
+    /// `type` alias
+    TypeAlias,
+    /// Any other free function
+    Function,
+}
+
+/// Maximum number of type aliases followed when resolving a type (aliases can be cyclic in
+/// code that does not compile)
+const MAX_ALIAS_DEPTH: usize = 8;
+
+/// Field of a struct, parameter of a handler or variant of an error enum
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Member {
+    /// Name of the member
+    pub name: String,
+    /// Type of the member, without spaces (`None` for enum variants)
+    pub ty: Option<String>,
+    /// Line of the member name (of the type of tuple struct fields)
+    pub line: usize,
+    /// Attributes of the member without spaces, doc comments excepted (e.g. `account(mut,has_one=authority)`)
+    pub attributes: Vec<String>,
+}
+
+impl Member {
+    /// Returns the arguments of an attribute of the member, empty if it has none
+    /// (`mut,has_one=authority` for `#[account(mut, has_one = authority)]`)
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        attribute_arguments(&self.attributes, name)
+    }
+}
+
+/// Declaration indexed by the [`ProgramContext`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Declaration {
+    /// Kind of the declaration
+    pub kind: DeclarationKind,
+    /// Name of the declaration
+    pub name: String,
+    /// Path from the crate root (e.g. `crate::state::Vault`)
+    pub path: String,
+    /// File of the declaration
+    pub file: String,
+    /// Line of the declaration name
+    pub line: usize,
+    /// Fields of structs, parameters of functions, variants of enums
+    pub members: Vec<Member>,
+    /// Type of constants, accounts struct of handlers (the `T` of `Context<T>`), aliased type of
+    /// type aliases, return type of functions
+    pub ty: Option<String>,
+    /// Value of constants
+    pub value: Option<String>,
+    /// Directory of the crate of the declaration (parent of its `src` directory)
+    pub crate_dir: String,
+    /// Attributes of the declaration without spaces, doc comments excepted (e.g. `account(zero_copy)`)
+    pub attributes: Vec<String>,
+}
+
+impl Declaration {
+    /// Returns the member with the given name
+    pub fn member(&self, name: &str) -> Option<&Member> {
+        self.members.iter().find(|member| member.name == name)
+    }
+
+    /// Returns the arguments of an attribute of the declaration, empty if it has none
+    /// (`Clone,Debug` for `#[derive(Clone, Debug)]`)
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        attribute_arguments(&self.attributes, name)
+    }
+}
+
+/// Index of the declarations of all the files of a program
+///
+/// Built once before the rules run, so per-file rules can resolve the types declared in
+/// sibling files (e.g. the `Vault` account of `state.rs` used by `instructions/withdraw.rs`).
+/// Every struct, enum, constant, type alias and free function is indexed, with its members and
+/// attributes, and [`ProgramContext::resolve_type`] resolves field types such as
+/// `Account<'info, Vault>` to the declaration of the data they wrap. The call graph of the
+/// program ([`ProgramContext::call_graph`]) links handlers to the helpers they call, in any file.
+/// Module paths follow the `mod` declarations from the crate roots (see [`ModuleTree`]), so
+/// `#[path]` attributes and inline modules are taken into account. Files no crate root reaches
+/// get a module path derived from their path (`src/instructions/withdraw.rs` is
+/// `crate::instructions::withdraw`). The index owns its data, so it is shared by the threads
+/// analyzing the files. In a workspace, each program has its own context, carrying the facts
+/// of its manifest (see [`ProgramContext::manifest`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ProgramContext {
+    /// Declarations in the order of the files
+    declarations: Vec<Declaration>,
+    /// Modules of the files reached from a crate root, by file path
+    modules: BTreeMap<String, FileModule>,
+    /// Whether test code is analyzed like program code (`--include-tests`)
+    include_tests: bool,
+    /// Manifest of the crate, when the analyzed files are a crate of a workspace
+    manifest: Option<CrateManifest>,
+    /// Functions of the program, with their calls and checks
+    call_graph: ProgramCallGraph,
+    /// Processors declared as native entrypoints (see [`native::entrypoints`])
+    entrypoints: Vec<String>,
+    /// Whether a file declares an Anchor `#[program]` module
+    anchor_program: bool,
+    /// Framework imported by the files (see [`native::imported_framework`])
+    framework: Option<native::Framework>,
+}
+
+impl ProgramContext {
+    /// Creates an empty context
+    pub const fn new() -> Self {
+        Self {
+            declarations: Vec::new(),
+            modules: BTreeMap::new(),
+            include_tests: false,
+            manifest: None,
+            call_graph: ProgramCallGraph::new(),
+            entrypoints: Vec::new(),
+            anchor_program: false,
+            framework: None,
+        }
+    }
+
+    /// Sets the manifest of the crate the files belong to
+    pub fn with_manifest(mut self, manifest: Option<CrateManifest>) -> Self {
+        self.manifest = manifest;
+        self
+    }
+
+    /// Returns the manifest of the crate (name, program ID, features, dependency versions, release
+    /// profile), if the program was analyzed as part of a workspace
+    pub fn manifest(&self) -> Option<&CrateManifest> {
+        self.manifest.as_ref()
+    }
+
+    /// Returns the version of anchor-lang the program builds with, if it is known (see
+    /// [`CrateManifest::anchor_lang`]), so rules can adapt to the Anchor API of the program
+    pub fn anchor_version(&self) -> Option<&semver::Version> {
+        self.manifest.as_ref().and_then(|manifest| manifest.anchor_lang.as_ref())
+    }
+
+    /// Analyze test code like program code, instead of leaving it out of the findings
+    pub fn with_tests(mut self, include_tests: bool) -> Self {
+        self.include_tests = include_tests;
+        self
+    }
+
+    /// Check if test code is analyzed like program code
+    pub fn includes_tests(&self) -> bool {
+        self.include_tests
+    }
+
+    /// Index the declarations of the given files (path and AST)
+    pub fn build<'a>(files: impl IntoIterator<Item = (&'a str, &'a File)>) -> Self {
+        let files: Vec<(&str, &File)> = files.into_iter().collect();
+        let tree = ModuleTree::build(files.iter().map(|(file_path, ast)| (Path::new(*file_path), *ast)));
+        let mut context = Self::new();
+        for &(file_path, ast) in &files {
+            let (crate_dir, module) = match tree.module_of(Path::new(file_path)) {
+                Some(file_module) => {
+                    context.modules.insert(file_path.to_string(), file_module.clone());
+                    (crate_dir(file_module), file_module.path.clone())
+                }
+                None => module_path(file_path),
+            };
+            let mut indexer = Indexer {
+                file: file_path,
+                crate_dir: &crate_dir,
+                declarations: &mut context.declarations,
+            };
+            indexer.index_items(&ast.items, &module, false);
+            context.entrypoints.extend(native::entrypoints(ast));
+            context.anchor_program |= native::declares_anchor_program(ast);
+            context.framework = context.framework.or_else(|| native::imported_framework(ast));
+        }
+        context.call_graph = ProgramCallGraph::build(files);
+        debug!("Program context built with {} declarations", context.declarations.len());
+        context
+    }
+
+    /// Returns the processors declared as native entrypoints (`entrypoint!(process_instruction)`)
+    pub fn entrypoints(&self) -> &[String] {
+        &self.entrypoints
+    }
+
+    /// Check if the program is a native program: it declares an entrypoint and no Anchor
+    /// `#[program]` module
+    pub fn is_native(&self) -> bool {
+        !self.entrypoints.is_empty() && !self.anchor_program
+    }
+
+    /// Returns the framework of a native program: the one its `Cargo.toml` depends on or its
+    /// files import (Pinocchio, Steel), or else `Native` (`None` if it is not a native program)
+    pub fn framework(&self) -> Option<native::Framework> {
+        if !self.is_native() {
+            return None;
+        }
+        let from_manifest = self.manifest.as_ref().and_then(native::Framework::of_manifest);
+        Some(from_manifest.or(self.framework).unwrap_or(native::Framework::Native))
+    }
+
+    /// Returns the call graph of the program, to follow handlers into the helpers they call
+    pub fn call_graph(&self) -> &ProgramCallGraph {
+        &self.call_graph
+    }
+
+    /// Returns a copy of the context without the lines of its declarations, members, calls and
+    /// checks, so moving code around compares equal to the original
+    pub fn without_positions(&self) -> Self {
+        let mut context = self.clone();
+        for declaration in &mut context.declarations {
+            declaration.line = 0;
+            for member in &mut declaration.members {
+                member.line = 0;
+            }
+        }
+        context.call_graph = self.call_graph.without_positions();
+        context
+    }
+
+    /// Returns every declaration, in the order of the files
+    pub fn declarations(&self) -> &[Declaration] {
+        &self.declarations
+    }
+
+    /// Returns the declarations of the given kind
+    pub fn of_kind(&self, kind: DeclarationKind) -> impl Iterator<Item = &Declaration> {
+        self.declarations.iter().filter(move |declaration| declaration.kind == kind)
+    }
+
+    /// Check if no declaration was indexed
+    pub fn is_empty(&self) -> bool {
+        self.declarations.is_empty()
+    }
+
+    /// Returns the module of a file, if a crate root reaches it through `mod` declarations
+    pub fn module_of(&self, file_path: &str) -> Option<&FileModule> {
+        self.modules.get(file_path)
+    }
+
+    /// Resolve a path used in a file (`Vault`, `state::Vault` or `crate::state::Vault`)
+    ///
+    /// Paths starting with `crate::` must match the full path of the declaration, other
+    /// paths match its last segments. Declarations of the crate of `from_file` come first.
+    pub fn resolve(&self, from_file: &str, path: &str) -> Option<&Declaration> {
+        let path = path.replace(' ', "");
+        let segments: Vec<&str> = path
+            .split("::")
+            .skip_while(|segment| matches!(*segment, "self" | "super"))
+            .collect();
+        let matches = |declaration: &&Declaration| {
+            let declared: Vec<&str> = declaration.path.split("::").collect();
+            if segments.first() == Some(&"crate") {
+                declared == segments
+            } else {
+                !segments.is_empty() && declared.ends_with(&segments)
+            }
+        };
+
+        let crate_dir = self.module_of(from_file).map_or_else(|| module_path(from_file).0, crate_dir);
+        self.declarations
+            .iter()
+            .filter(matches)
+            .find(|declaration| declaration.crate_dir == crate_dir)
+            .or_else(|| self.declarations.iter().find(matches))
+    }
+
+    /// Resolve a type used in a file to the struct or enum it refers to
+    ///
+    /// Type aliases are followed, and wrappers declared outside the program are looked through:
+    /// `Account<'info, Vault>`, `Box<Account<'info, Vault>>` and `Option<VaultAlias>` all resolve
+    /// to `Vault`. Returns `None` for types declared nowhere in the program (`u64`, `Signer`).
+    pub fn resolve_type(&self, from_file: &str, ty: &str) -> Option<&Declaration> {
+        let ty = syn::parse_str::<syn::Type>(ty).ok()?;
+        self.resolve_syn_type(from_file, &ty, 0)
+    }
+
+    /// Helper function to resolve a parsed type, following at most [`MAX_ALIAS_DEPTH`] aliases
+    fn resolve_syn_type(&self, from_file: &str, ty: &syn::Type, depth: usize) -> Option<&Declaration> {
+        match ty {
+            syn::Type::Path(type_path) => {
+                let path: Vec<String> = type_path.path.segments.iter().map(|segment| segment.ident.to_string()).collect();
+                if let Some(declaration) = self.resolve(from_file, &path.join("::")) {
+                    match declaration.kind {
+                        DeclarationKind::TypeAlias if depth < MAX_ALIAS_DEPTH => {
+                            let aliased = syn::parse_str::<syn::Type>(declaration.ty.as_deref()?).ok()?;
+                            return self.resolve_syn_type(&declaration.file, &aliased, depth + 1);
+                        }
+                        DeclarationKind::Accounts
+                        | DeclarationKind::State
+                        | DeclarationKind::ErrorEnum
+                        | DeclarationKind::Struct
+                        | DeclarationKind::Enum => return Some(declaration),
+                        _ => {}
+                    }
+                }
+                // Wrapper declared outside the program: the first argument resolving is the data
+                let syn::PathArguments::AngleBracketed(arguments) = &type_path.path.segments.last()?.arguments else {
+                    return None;
+                };
+                arguments.args.iter().find_map(|argument| match argument {
+                    syn::GenericArgument::Type(ty) => self.resolve_syn_type(from_file, ty, depth),
+                    _ => None,
+                })
+            }
+            syn::Type::Reference(reference) => self.resolve_syn_type(from_file, &reference.elem, depth),
+            syn::Type::Array(array) => self.resolve_syn_type(from_file, &array.elem, depth),
+            syn::Type::Slice(slice) => self.resolve_syn_type(from_file, &slice.elem, depth),
+            syn::Type::Paren(paren) => self.resolve_syn_type(from_file, &paren.elem, depth),
+            syn::Type::Group(group) => self.resolve_syn_type(from_file, &group.elem, depth),
+            _ => None,
+        }
+    }
+}
+
+/// Helper function to get the crate directory and the module path of a file
+///
+/// The crate is the parent of the last `src` directory of the path (the directory of the
+/// file if there is none). `lib.rs`, `main.rs` and `mod.rs` do not add a segment.
+fn module_path(file_path: &str) -> (String, Vec<String>) {
+    let components: Vec<String> = Path::new(file_path)
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+    let Some((file_name, dirs)) = components.split_last() else {
+        return (String::new(), vec!["crate".to_string()]);
+    };
+
+    let (crate_dirs, module_dirs) = match dirs.iter().rposition(|dir| dir == "src") {
+        Some(src) => (&dirs[..src], &dirs[src + 1..]),
+        None => (dirs, &[][..]),
+    };
+    let mut module = vec!["crate".to_string()];
+    module.extend(module_dirs.iter().cloned());
+    let stem = file_name.strip_suffix(".rs").unwrap_or(file_name);
+    if !matches!(stem, "lib" | "main" | "mod") {
+        module.push(stem.to_string());
+    }
+    (crate_dirs.join("/"), module)
+}
+
+/// Helper function to get the crate directory of a module (parent of the `src` directory of its root)
+fn crate_dir(file_module: &FileModule) -> String {
+    module_path(&file_module.crate_root.to_string_lossy()).0
+}
+
+/// Helper to collect the declarations of a file
+struct Indexer<'a> {
+    file: &'a str,
+    crate_dir: &'a str,
+    declarations: &'a mut Vec<Declaration>,
+}
+
+impl Indexer<'_> {
+    /// Index the items of a module, recursively
+    fn index_items(&mut self, items: &[Item], module: &[String], in_program: bool) {
+        for item in items {
+            match item {
+                Item::Struct(item_struct) => {
+                    let kind = if derives(&item_struct.attrs, "Accounts") {
+                        DeclarationKind::Accounts
+                    } else if has_attribute(&item_struct.attrs, "account") || derives(&item_struct.attrs, "BorshDeserialize") {
+                        DeclarationKind::State
+                    } else {
+                        DeclarationKind::Struct
+                    };
+                    let members = item_struct
+                        .fields
+                        .iter()
+                        .enumerate()
+                        .map(|(index, field)| Member {
+                            name: field.ident.as_ref().map_or_else(|| index.to_string(), ToString::to_string),
+                            ty: Some(type_name(&field.ty)),
+                            line: field.ident.as_ref().map_or_else(|| field.ty.span(), syn::Ident::span).start().line,
+                            attributes: attributes(&field.attrs),
+                        })
+                        .collect();
+                    let declaration = self.declaration(kind, &item_struct.ident, &item_struct.attrs, module);
+                    self.declarations.push(Declaration { members, ..declaration });
+                }
+                Item::Enum(item_enum) => {
+                    let kind = if has_attribute(&item_enum.attrs, "error_code") || derives(&item_enum.attrs, "Error") {
+                        DeclarationKind::ErrorEnum
+                    } else {
+                        DeclarationKind::Enum
+                    };
+                    let members = item_enum
+                        .variants
+                        .iter()
+                        .map(|variant| Member {
+                            name: variant.ident.to_string(),
+                            ty: None,
+                            line: variant.ident.span().start().line,
+                            attributes: attributes(&variant.attrs),
+                        })
+                        .collect();
+                    let declaration = self.declaration(kind, &item_enum.ident, &item_enum.attrs, module);
+                    self.declarations.push(Declaration { members, ..declaration });
+                }
+                Item::Const(item_const) => {
+                    let value = Some(item_const.expr.to_token_stream().to_string());
+                    let ty = Some(type_name(&item_const.ty));
+                    let declaration = self.declaration(DeclarationKind::Constant, &item_const.ident, &item_const.attrs, module);
+                    self.declarations.push(Declaration { ty, value, ..declaration });
+                }
+                Item::Type(item_type) => {
+                    let ty = Some(type_name(&item_type.ty));
+                    let declaration = self.declaration(DeclarationKind::TypeAlias, &item_type.ident, &item_type.attrs, module);
+                    self.declarations.push(Declaration { ty, ..declaration });
+                }
+                Item::Fn(item_fn) => {
+                    let members = item_fn
+                        .sig
+                        .inputs
+                        .iter()
+                        .filter_map(|input| match input {
+                            syn::FnArg::Typed(pat_type) => Some(Member {
+                                name: pat_type.pat.to_token_stream().to_string().replace(' ', ""),
+                                ty: Some(type_name(&pat_type.ty)),
+                                line: pat_type.pat.span().start().line,
+                                attributes: attributes(&pat_type.attrs),
+                            }),
+                            syn::FnArg::Receiver(_) => None,
+                        })
+                        .collect();
+                    let accounts = context_accounts(&item_fn.sig);
+                    if matches!(item_fn.vis, syn::Visibility::Public(_)) && (in_program || accounts.is_some()) {
+                        let accounts = accounts.filter(|accounts| !accounts.is_empty());
+                        let declaration = self.declaration(DeclarationKind::Instruction, &item_fn.sig.ident, &item_fn.attrs, module);
+                        self.declarations.push(Declaration { members, ty: accounts, ..declaration });
+                    } else {
+                        let output = match &item_fn.sig.output {
+                            syn::ReturnType::Type(_, ty) => Some(type_name(ty)),
+                            syn::ReturnType::Default => None,
+                        };
+                        let declaration = self.declaration(DeclarationKind::Function, &item_fn.sig.ident, &item_fn.attrs, module);
+                        self.declarations.push(Declaration { members, ty: output, ..declaration });
+                    }
+                }
+                Item::Mod(item_mod) => {
+                    if let Some((_, items)) = &item_mod.content {
+                        let mut nested = module.to_vec();
+                        nested.push(item_mod.ident.to_string());
+                        self.index_items(items, &nested, in_program || has_attribute(&item_mod.attrs, "program"));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Declaration of an item of a module, without members, type nor value
+    fn declaration(&self, kind: DeclarationKind, ident: &syn::Ident, attrs: &[Attribute], module: &[String]) -> Declaration {
+        let name = ident.to_string();
+        Declaration {
+            kind,
+            path: format!("{}::{name}", module.join("::")),
+            name,
+            file: self.file.to_string(),
+            line: ident.span().start().line,
+            members: Vec::new(),
+            ty: None,
+            value: None,
+            crate_dir: self.crate_dir.to_string(),
+            attributes: attributes(attrs),
+        }
+    }
+}
+
+/// Helper function to get a type without spaces (e.g. `Account<'info,Vault>`)
+fn type_name(ty: &syn::Type) -> String {
+    ty.to_token_stream().to_string().replace(' ', "")
+}
+
+/// Helper function to get the attributes of an item without spaces, doc comments excepted
+fn attributes(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| !attr.path().is_ident("doc"))
+        .map(|attr| attr.meta.to_token_stream().to_string().replace(' ', ""))
+        .collect()
+}
+
+/// Helper function to get the arguments of an attribute (between its parentheses, or after `=`)
+fn attribute_arguments<'a>(attributes: &'a [String], name: &str) -> Option<&'a str> {
+    attributes.iter().find_map(|attribute| {
+        let rest = attribute.strip_prefix(name)?;
+        if rest.is_empty() {
+            Some(rest)
+        } else if let Some(value) = rest.strip_prefix('=') {
+            Some(value)
+        } else {
+            rest.strip_prefix('(')?.strip_suffix(')')
+        }
+    })
+}
+
+/// Helper function to get the accounts struct of a handler (the `T` of its `Context<T>` parameter,
+/// empty if it has none)
+pub(super) fn context_accounts(sig: &syn::Signature) -> Option<String> {
+    sig.inputs.iter().find_map(|input| {
+        let syn::FnArg::Typed(pat_type) = input else {
+            return None;
+        };
+        let syn::Type::Path(type_path) = &*pat_type.ty else {
+            return None;
+        };
+        let segment = type_path.path.segments.last().filter(|segment| segment.ident == "Context")?;
+        let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+            return Some(String::new());
+        };
+        arguments.args.iter().find_map(|argument| match argument {
+            syn::GenericArgument::Type(syn::Type::Path(accounts)) => {
+                accounts.path.segments.last().map(|segment| segment.ident.to_string())
+            }
+            _ => None,
+        })
+    })
+}
+
+/// Helper function to check if an item has an attribute (e.g. `#[account]` or `#[account(zero_copy)]`)
+fn has_attribute(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident(name))
+}
+
+/// Check if an item derives a trait, by the last segment of its path (`Accounts` matches
+/// `#[derive(Accounts)]` and `#[derive(anchor_lang::Accounts)]`)
+pub fn derives(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("derive")
+            && attr
+                .parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)
+                .is_ok_and(|paths| paths.iter().any(|path| path.segments.last().is_some_and(|segment| segment.ident == name)))
+    })
+}
@@ -0,0 +1,291 @@
+use log::debug;
+use std::collections::BTreeSet;
+use syn::File;
+use syn::visit::{self, Visit};
+
+use crate::analyzer::dsl::query::{AstNode, AstQuery, NodeData};
+use crate::analyzer::span_utils::SpanExtractor;
+use crate::analyzer::{Finding, Severity};
+
+/// File of the analyzed program
+#[derive(Debug, Clone, Copy)]
+pub struct ProjectFile<'a> {
+    /// Path of the file
+    pub path: &'a str,
+    /// AST of the file
+    pub ast: &'a File,
+    /// Source code of the file (used for precise locations)
+    pub source: &'a str,
+}
+
+impl<'a> ProjectFile<'a> {
+    /// Creates a new project file
+    pub fn new(path: &'a str, ast: &'a File, source: &'a str) -> Self {
+        Self { path, ast, source }
+    }
+}
+
+/// Results of a file-level query, with the file they come from
+struct FileQuery<'a> {
+    file: &'a ProjectFile<'a>,
+    query: AstQuery<'a>,
+}
+
+/// Query spanning all the files of a program
+///
+/// Supports the same combinators as `AstQuery`, applied to every file, plus
+/// relations between files (e.g. account structs defined in `state.rs` and used
+/// by the handlers in `instructions/*.rs`).
+pub struct ProjectQuery<'a> {
+    /// Results, grouped by file in the order of the project files
+    queries: Vec<FileQuery<'a>>,
+}
+
+impl<'a> ProjectQuery<'a> {
+    /// Create a new query from the files of a program
+    pub fn new(files: &'a [ProjectFile<'a>]) -> Self {
+        Self {
+            queries: files
+                .iter()
+                .map(|file| FileQuery {
+                    file,
+                    query: AstQuery::new(file.ast),
+                })
+                .collect(),
+        }
+    }
+
+    /// Apply a file-level query to the results of every file
+    pub fn per_file<F>(self, query: F) -> Self
+    where
+        F: Fn(AstQuery<'a>) -> AstQuery<'a>,
+    {
+        Self {
+            queries: self
+                .queries
+                .into_iter()
+                .map(|file_query| FileQuery {
+                    file: file_query.file,
+                    query: query(file_query.query),
+                })
+                .collect(),
+        }
+    }
+
+    /// Filter functions
+    pub fn functions(self) -> Self {
+        self.per_file(AstQuery::functions)
+    }
+
+    /// Filter structs
+    pub fn structs(self) -> Self {
+        self.per_file(AstQuery::structs)
+    }
+
+    /// Filter by name
+    pub fn with_name(self, name: &str) -> Self {
+        self.per_file(|query| query.with_name(name))
+    }
+
+    /// Filter for structs that derive the Accounts trait
+    pub fn derives_accounts(self) -> Self {
+        self.per_file(AstQuery::derives_accounts)
+    }
+
+    /// Filter for public functions only
+    pub fn public_functions(self) -> Self {
+        self.per_file(AstQuery::public_functions)
+    }
+
+    /// Search for calls to a specific function
+    pub fn calls_to(self, function_name: &str) -> Self {
+        self.per_file(|query| query.calls_to(function_name))
+    }
+
+    /// Apply a custom predicate
+    pub fn filter<F>(self, predicate: F) -> Self
+    where
+        F: Fn(&AstNode<'a>) -> bool,
+    {
+        self.per_file(|query| query.filter(&predicate))
+    }
+
+    /// Annotate every node with metadata for downstream filters and findings
+    pub fn annotate<F>(self, annotator: F) -> Self
+    where
+        F: Fn(&mut AstNode<'a>),
+    {
+        self.per_file(|query| query.annotate(&annotator))
+    }
+
+    /// Filter nodes that carry the given metadata entry
+    pub fn with_metadata(self, key: &str) -> Self {
+        self.per_file(|query| query.with_metadata(key))
+    }
+
+    /// Keep the results of the files whose path matches the predicate
+    pub fn in_files<F>(self, predicate: F) -> Self
+    where
+        F: Fn(&str) -> bool,
+    {
+        debug!("Filtering results by file path");
+        Self {
+            queries: self
+                .queries
+                .into_iter()
+                .map(|file_query| {
+                    if predicate(file_query.file.path) {
+                        file_query
+                    } else {
+                        FileQuery {
+                            file: file_query.file,
+                            query: file_query.query.with_results(Vec::new()),
+                        }
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Keep the nodes whose name is referenced by a node of the other query (in any file)
+    ///
+    /// The files of the referencing nodes are recorded in the `referenced_in` metadata.
+    pub fn referenced_by(self, other: &ProjectQuery<'a>) -> Self {
+        debug!("Filtering nodes referenced by another query");
+        let references: Vec<(&str, &AstNode<'a>, BTreeSet<String>)> = other
+            .nodes()
+            .map(|(file, node)| (file.path, node, referenced_names(node)))
+            .collect();
+
+        self.per_file(|mut query| {
+            query.results_mut().retain_mut(|node| {
+                let Some(name) = node.name.clone() else {
+                    return false;
+                };
+                let files: BTreeSet<String> = references
+                    .iter()
+                    .filter(|(_, other_node, names)| other_node.data != node.data && names.contains(&name))
+                    .map(|(path, _, _)| path.to_string())
+                    .collect();
+                if files.is_empty() {
+                    return false;
+                }
+                node.set_metadata("referenced_in", files.into_iter().collect::<Vec<_>>());
+                true
+            });
+            query
+        })
+    }
+
+    /// Keep the nodes that reference a node of the other query by name (in any file)
+    pub fn references(self, other: &ProjectQuery<'a>) -> Self {
+        debug!("Filtering nodes referencing another query");
+        let names: BTreeSet<String> = other.nodes().filter_map(|(_, node)| node.name.clone()).collect();
+
+        self.per_file(|query| {
+            query.filter(|node| {
+                // A node does not reference itself through its own name (e.g. recursion)
+                let mut used = referenced_names(node);
+                if let Some(name) = &node.name {
+                    used.remove(name);
+                }
+                !used.is_disjoint(&names)
+            })
+        })
+    }
+
+    /// Combine with another query over the same files (OR operator)
+    pub fn or(self, other: Self) -> Self {
+        debug!("Combining project queries with OR");
+        let mut other_queries = other.queries;
+        Self {
+            queries: self
+                .queries
+                .into_iter()
+                .map(|file_query| {
+                    match other_queries.iter().position(|other| other.file.path == file_query.file.path) {
+                        Some(index) => FileQuery {
+                            file: file_query.file,
+                            query: file_query.query.or(other_queries.remove(index).query),
+                        },
+                        None => file_query,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the nodes found by the query, with the file they belong to
+    pub fn nodes(&self) -> impl Iterator<Item = (&'a ProjectFile<'a>, &AstNode<'a>)> {
+        self.queries
+            .iter()
+            .flat_map(|file_query| file_query.query.results().iter().map(move |node| (file_query.file, node)))
+    }
+
+    /// Check if there are results
+    pub fn exists(&self) -> bool {
+        self.nodes().next().is_some()
+    }
+
+    /// Get the number of results
+    pub fn count(&self) -> usize {
+        self.nodes().count()
+    }
+
+    /// Returns the results grouped by file
+    pub fn file_queries(&self) -> impl Iterator<Item = (&'a ProjectFile<'a>, &AstQuery<'a>)> {
+        self.queries.iter().map(|file_query| (file_query.file, &file_query.query))
+    }
+
+    /// Consumes the query and returns the results grouped by file
+    pub fn into_file_queries(self) -> impl Iterator<Item = (&'a ProjectFile<'a>, AstQuery<'a>)> {
+        self.queries.into_iter().map(|file_query| (file_query.file, file_query.query))
+    }
+
+    /// Convert query results to findings with precise locations in their own files,
+    /// building each description with the given formatter
+    pub fn to_findings_with_formatter(
+        &self,
+        severity: Severity,
+        recommendations: &[String],
+        formatter: &dyn Fn(&AstNode<'a>) -> String,
+    ) -> Vec<Finding> {
+        self.file_queries()
+            .flat_map(|(file, query)| {
+                let span_extractor = SpanExtractor::new(file.source.to_string(), file.path.to_string());
+                query.to_findings_with_formatter(severity.clone(), recommendations, file.path, &span_extractor, formatter)
+            })
+            .collect()
+    }
+}
+
+/// Returns the names used in the paths of a node (types, functions, constants...)
+pub fn referenced_names(node: &AstNode<'_>) -> BTreeSet<String> {
+    let mut collector = NameCollector::default();
+    match node.data {
+        NodeData::File(file) => collector.visit_file(file),
+        NodeData::Function(func) => collector.visit_item_fn(func),
+        NodeData::ImplFunction(func) => collector.visit_impl_item_fn(func),
+        NodeData::Struct(struct_item) => collector.visit_item_struct(struct_item),
+        NodeData::Enum(enum_item) => collector.visit_item_enum(enum_item),
+        NodeData::Block(block) => collector.visit_block(block),
+        NodeData::Expression(expr) => collector.visit_expr(expr),
+        NodeData::Other => {}
+    }
+    collector.names
+}
+
+/// Helper visitor to collect the identifiers of every path segment
+#[derive(Default)]
+struct NameCollector {
+    names: BTreeSet<String>,
+}
+
+impl<'ast> Visit<'ast> for NameCollector {
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        for segment in &path.segments {
+            self.names.insert(segment.ident.to_string());
+        }
+        visit::visit_path(self, path);
+    }
+}
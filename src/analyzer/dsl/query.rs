@@ -1,9 +1,15 @@
 use log::{debug, trace};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 use syn::visit::{self, Visit};
 use syn::{Block, Expr, File, Item, ItemEnum, ItemFn, ItemStruct};
 
-use crate::analyzer::{Finding, Severity};
+use crate::analyzer::dsl::cache;
+use crate::analyzer::dsl::call_graph::CallGraph;
+use crate::analyzer::dsl::program::{ProgramContext, derives};
+use crate::analyzer::dsl::taint::TaintConfig;
+use crate::analyzer::{Confidence, Finding, Severity};
 
 /// Type of node in the AST
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -59,6 +65,69 @@ pub enum NodeData<'a> {
     Other,
 }
 
+/// Value of a metadata entry attached to a node by a filter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetadataValue {
+    /// Boolean flag
+    Bool(bool),
+    /// Integer value (sizes, counts...)
+    Int(i64),
+    /// Text value (field names, constraint expressions...)
+    Text(String),
+    /// List of text values
+    List(Vec<String>),
+}
+
+impl fmt::Display for MetadataValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetadataValue::Bool(value) => write!(f, "{value}"),
+            MetadataValue::Int(value) => write!(f, "{value}"),
+            MetadataValue::Text(value) => write!(f, "{value}"),
+            MetadataValue::List(values) => write!(f, "{}", values.join(", ")),
+        }
+    }
+}
+
+impl From<bool> for MetadataValue {
+    fn from(value: bool) -> Self {
+        MetadataValue::Bool(value)
+    }
+}
+
+impl From<i64> for MetadataValue {
+    fn from(value: i64) -> Self {
+        MetadataValue::Int(value)
+    }
+}
+
+impl From<usize> for MetadataValue {
+    fn from(value: usize) -> Self {
+        MetadataValue::Int(i64::try_from(value).unwrap_or(i64::MAX))
+    }
+}
+
+impl From<&str> for MetadataValue {
+    fn from(value: &str) -> Self {
+        MetadataValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for MetadataValue {
+    fn from(value: String) -> Self {
+        MetadataValue::Text(value)
+    }
+}
+
+impl From<Vec<String>> for MetadataValue {
+    fn from(values: Vec<String>) -> Self {
+        MetadataValue::List(values)
+    }
+}
+
+/// Metadata attached to a node, keyed by name
+pub type Metadata = BTreeMap<String, MetadataValue>;
+
 /// Node of the AST with metadata
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AstNode<'a> {
@@ -68,6 +137,10 @@ pub struct AstNode<'a> {
     pub data: NodeData<'a>,
     /// Node name (if applicable)
     pub name: Option<String>,
+    /// Metadata attached by filters (e.g. the field that triggered a match)
+    pub metadata: Metadata,
+    /// Confidence of the match (lowered by filters for heuristic matches)
+    pub confidence: Confidence,
 }
 
 impl<'a> AstNode<'a> {
@@ -77,6 +150,8 @@ impl<'a> AstNode<'a> {
             node_type: NodeType::File,
             data: NodeData::File(file),
             name: None,
+            metadata: Metadata::new(),
+            confidence: Confidence::High,
         }
     }
 
@@ -86,6 +161,8 @@ impl<'a> AstNode<'a> {
             node_type: NodeType::Function,
             data: NodeData::Function(func),
             name: Some(func.sig.ident.to_string()),
+            metadata: Metadata::new(),
+            confidence: Confidence::High,
         }
     }
 
@@ -95,6 +172,8 @@ impl<'a> AstNode<'a> {
             node_type: NodeType::Function,
             data: NodeData::ImplFunction(func),
             name: Some(func.sig.ident.to_string()),
+            metadata: Metadata::new(),
+            confidence: Confidence::High,
         }
     }
 
@@ -104,6 +183,8 @@ impl<'a> AstNode<'a> {
             node_type: NodeType::Struct,
             data: NodeData::Struct(struct_item),
             name: Some(struct_item.ident.to_string()),
+            metadata: Metadata::new(),
+            confidence: Confidence::High,
         }
     }
 
@@ -117,6 +198,79 @@ impl<'a> AstNode<'a> {
         self.name.clone().unwrap_or_else(|| "unnamed".to_string())
     }
 
+    /// Attach a metadata entry to the node
+    pub fn set_metadata(&mut self, key: &str, value: impl Into<MetadataValue>) {
+        self.metadata.insert(key.to_string(), value.into());
+    }
+
+    /// Attach a metadata entry to the node (builder style)
+    pub fn with_metadata(mut self, key: &str, value: impl Into<MetadataValue>) -> Self {
+        self.set_metadata(key, value);
+        self
+    }
+
+    /// Get a metadata entry of the node
+    pub fn metadata(&self, key: &str) -> Option<&MetadataValue> {
+        self.metadata.get(key)
+    }
+
+    /// Lower the confidence of the match (never raises it)
+    pub fn downgrade_confidence(&mut self, confidence: Confidence) {
+        self.confidence = self.confidence.min(confidence);
+    }
+
+    /// Default finding description for the node based on its name (and triggering field, if recorded)
+    pub fn describe(&self, title: &str, description: &str) -> String {
+        match (&self.name, self.metadata("field")) {
+            (Some(name), Some(field)) => format!(
+                "{title} in '{name}' (field '{field}'). {description}"
+            ),
+            (Some(name), None) => format!(
+                "{title} in '{name}'. {description}"
+            ),
+            (None, _) => format!("{title}: {description}"),
+        }
+    }
+
+    /// Get the attributes of the node (empty for nodes without attributes)
+    pub fn attrs(&self) -> &'a [syn::Attribute] {
+        match self.data {
+            NodeData::File(file) => &file.attrs,
+            NodeData::Function(func) => &func.attrs,
+            NodeData::ImplFunction(func) => &func.attrs,
+            NodeData::Struct(struct_item) => &struct_item.attrs,
+            NodeData::Enum(enum_item) => &enum_item.attrs,
+            _ => &[],
+        }
+    }
+
+    /// Check if the node is a public item (`pub` function, struct or enum)
+    pub fn is_public(&self) -> bool {
+        let vis = match self.data {
+            NodeData::Function(func) => &func.vis,
+            NodeData::ImplFunction(func) => &func.vis,
+            NodeData::Struct(struct_item) => &struct_item.vis,
+            NodeData::Enum(enum_item) => &enum_item.vis,
+            _ => return false,
+        };
+        matches!(vis, syn::Visibility::Public(_))
+    }
+
+    /// Get the return type of a function node without whitespace (e.g. `Result<()>`, `()` if none)
+    pub fn return_type(&self) -> Option<String> {
+        use quote::ToTokens;
+
+        let output = match self.data {
+            NodeData::Function(func) => &func.sig.output,
+            NodeData::ImplFunction(func) => &func.sig.output,
+            _ => return None,
+        };
+        Some(match output {
+            syn::ReturnType::Default => "()".to_string(),
+            syn::ReturnType::Type(_, ty) => ty.to_token_stream().to_string().replace(' ', ""),
+        })
+    }
+
     /// Get a code snippet of the node
     pub fn snippet(&self) -> String {
         match &self.data {
@@ -151,6 +305,10 @@ impl<'a> AstNode<'a> {
 pub struct AstQuery<'a> {
     /// Query results
     results: Vec<AstNode<'a>>,
+    /// File the query started from (used by whole-file analyses such as the call graph)
+    root: Option<&'a File>,
+    /// Name of the cached traversal that produced the results, if they come straight from one
+    traversal: Option<&'static str>,
 }
 
 impl<'a> AstQuery<'a> {
@@ -158,6 +316,8 @@ impl<'a> AstQuery<'a> {
     pub fn new(ast: &'a File) -> Self {
         Self {
             results: vec![AstNode::from_file(ast)],
+            root: Some(ast),
+            traversal: None,
         }
     }
 
@@ -165,18 +325,43 @@ impl<'a> AstQuery<'a> {
     pub fn from_nodes(nodes: Vec<AstNode<'a>>) -> Self {
         Self {
             results: nodes,
+            root: None,
+            traversal: None,
         }
     }
 
     /// Create a new query from a node
     pub fn from_node(node: &AstNode<'a>) -> Self {
+        let root = match node.data {
+            NodeData::File(file) => Some(file),
+            _ => None,
+        };
+
         Self {
             results: vec![node.clone()],
+            root,
+            traversal: None,
         }
     }
 
+    /// Create a new query with the given results, keeping the root of this query
+    /// This is the preferred way for custom filters to return their results
+    pub fn with_results(&self, results: Vec<AstNode<'a>>) -> Self {
+        Self {
+            results,
+            root: self.root,
+            traversal: None,
+        }
+    }
+
+    /// Returns the file the query started from (if known)
+    pub fn root(&self) -> Option<&'a File> {
+        self.root
+    }
+
     /// Returns a mutable reference to the results for internal use
     pub(crate) fn results_mut(&mut self) -> &mut Vec<AstNode<'a>> {
+        self.traversal = None;
         &mut self.results
     }
 
@@ -196,18 +381,21 @@ impl<'a> AstQuery<'a> {
         let mut new_results = Vec::new();
 
         for node in self.results {
-            match node.data {
-                NodeData::File(file) => {
-                    // Search for functions recursively in the file
-                    Self::extract_functions_recursive(&file.items, &mut new_results);
-                }
-                // Other cases
-                _ => {}
+            if let NodeData::File(file) = node.data {
+                // Search for functions recursively in the file (computed once per file and shared by all rules)
+                let paths = cache::traversal(file, "functions", || {
+                    let mut paths = Vec::new();
+                    Self::extract_functions_recursive(&file.items, &mut Vec::new(), &mut paths);
+                    paths
+                });
+                new_results.extend(paths.iter().filter_map(|path| Self::resolve_item_path(&file.items, path)));
             }
         }
 
         Self {
             results: new_results,
+            root: self.root,
+            traversal: None,
         }
     }
 
@@ -216,24 +404,24 @@ impl<'a> AstQuery<'a> {
         debug!("Searching for structs");
         let mut new_results = Vec::new();
 
+        // Results that come straight from the root file can be reused by cached follow-up filters
+        let traversal = match (self.root, self.results.as_slice()) {
+            (Some(root), [node]) if node.data == NodeData::File(root) => Some("structs"),
+            _ => None,
+        };
+
         for node in self.results {
-            match node.data {
-                NodeData::File(file) => {
-                    // Search for structs in the file
-                    for item in &file.items {
-                        if let Item::Struct(struct_item) = item {
-                            trace!("Found struct: {}", struct_item.ident);
-                            new_results.push(AstNode::from_struct(struct_item));
-                        }
-                    }
-                }
-                // Other cases
-                _ => {}
+            if let NodeData::File(file) = node.data {
+                // Search for structs in the file
+                let paths = Self::struct_paths(file);
+                new_results.extend(paths.iter().filter_map(|path| Self::resolve_item_path(&file.items, path)));
             }
         }
         
         Self {
             results: new_results,
+            root: self.root,
+            traversal,
         }
     }
 
@@ -243,45 +431,68 @@ impl<'a> AstQuery<'a> {
         let mut new_results = Vec::new();
 
         for node in self.results {
-            if let Some(node_name) = &node.name {
-                if node_name == name {
-                    trace!("Found node with name: {name}");
-                    new_results.push(node);
-                }
+            if let Some(node_name) = &node.name
+                && node_name == name
+            {
+                trace!("Found node with name: {name}");
+                new_results.push(node);
             }
         }
 
         Self {
             results: new_results,
+            root: self.root,
+            traversal: None,
         }
     }
 
     /// Filter for structs that derive the Accounts trait
     pub fn derives_accounts(self) -> Self {
         debug!("Filtering structs that derive Accounts");
-        let mut new_results = Vec::new();
-        
-        for node in self.results {
-            if let NodeData::Struct(struct_item) = &node.data {
-                // Check if the struct derives Accounts
-                for attr in &struct_item.attrs {
-                    if let syn::Meta::List(meta_list) = &attr.meta {
-                        if meta_list.path.is_ident("derive") {
-                            let tokens_str = meta_list.tokens.to_string();
-                            if tokens_str.contains("Accounts") {
-                                trace!("Found struct deriving Accounts: {}", struct_item.ident);
-                                new_results.push(node);
-                                break;
-                            }
-                        }
-                    }
-                }
-            }
+
+        // Straight after structs() the result only depends on the file, so it is shared through the cache
+        if self.traversal == Some("structs")
+            && let Some(root) = self.root
+        {
+            let paths = cache::traversal(root, "structs.derives_accounts", || {
+                Self::struct_paths(root)
+                    .iter()
+                    .filter(|path| {
+                        Self::resolve_item_path(&root.items, path).is_some_and(|node| Self::derives_accounts_trait(&node))
+                    })
+                    .cloned()
+                    .collect()
+            });
+
+            return Self {
+                results: paths.iter().filter_map(|path| Self::resolve_item_path(&root.items, path)).collect(),
+                root: self.root,
+                traversal: None,
+            };
         }
+
+        let new_results = self
+            .results
+            .into_iter()
+            .filter(Self::derives_accounts_trait)
+            .collect();
         
         Self {
             results: new_results,
+            root: self.root,
+            traversal: None,
+        }
+    }
+
+    /// Helper function to check if a struct node derives the Accounts trait
+    fn derives_accounts_trait(node: &AstNode<'a>) -> bool {
+        if let NodeData::Struct(struct_item) = &node.data
+            && derives(&struct_item.attrs, "Accounts")
+        {
+            trace!("Found struct deriving Accounts: {}", struct_item.ident);
+            return true;
         }
+        false
     }
 
     /// Filter for public functions only
@@ -312,6 +523,8 @@ impl<'a> AstQuery<'a> {
         
         Self {
             results: new_results,
+            root: self.root,
+            traversal: None,
         }
     }
 
@@ -342,9 +555,132 @@ impl<'a> AstQuery<'a> {
 
         Self {
             results: new_results,
+            root: self.root,
+            traversal: None,
         }
     }
 
+    /// Filter functions that call a specific function directly or through helper functions
+    /// defined in the analyzed code (e.g. a handler calling `validate_authority(&ctx)?`)
+    pub fn calls_to_transitively(self, function_name: &str) -> Self {
+        debug!("Searching for direct or transitive calls to: {function_name}");
+        let Some(graph) = self.call_graph() else {
+            return self.calls_to(function_name);
+        };
+
+        let new_results = self
+            .results
+            .into_iter()
+            .filter(|node| {
+                node.name
+                    .as_ref()
+                    .is_some_and(|name| graph.calls_transitively(name, function_name))
+            })
+            .collect();
+
+        Self {
+            results: new_results,
+            root: self.root,
+            traversal: None,
+        }
+    }
+
+    /// Replace each function with the functions it calls (within the analyzed code)
+    pub fn callees_of(self) -> Self {
+        debug!("Searching for callees");
+        let Some(graph) = self.call_graph() else {
+            return self.with_results(Vec::new());
+        };
+
+        let names: std::collections::BTreeSet<&str> = self
+            .results
+            .iter()
+            .filter_map(|node| node.name.as_deref())
+            .flat_map(|name| graph.callees(name))
+            .collect();
+
+        let new_results = names
+            .into_iter()
+            .flat_map(|name| graph.function(name).iter().cloned())
+            .collect();
+
+        self.with_results(new_results)
+    }
+
+    /// Replace each function with the functions that call it
+    pub fn callers_of(self) -> Self {
+        debug!("Searching for callers");
+        let Some(graph) = self.call_graph() else {
+            return self.with_results(Vec::new());
+        };
+
+        let names: std::collections::BTreeSet<&str> = self
+            .results
+            .iter()
+            .filter_map(|node| node.name.as_deref())
+            .flat_map(|name| graph.callers(name))
+            .collect();
+
+        let new_results = names
+            .into_iter()
+            .flat_map(|name| graph.function(name).iter().cloned())
+            .collect();
+
+        self.with_results(new_results)
+    }
+
+    /// Filter functions reachable from an Anchor instruction handler (handlers included)
+    pub fn reachable_from_instruction(self) -> Self {
+        debug!("Filtering functions reachable from instruction handlers");
+        let Some(graph) = self.call_graph() else {
+            return self.with_results(Vec::new());
+        };
+
+        let reachable = graph.reachable_from(graph.instructions());
+        let new_results = self
+            .results
+            .into_iter()
+            .filter(|node| node.name.as_ref().is_some_and(|name| reachable.contains(name)))
+            .collect();
+
+        Self {
+            results: new_results,
+            root: self.root,
+            traversal: None,
+        }
+    }
+
+    /// Filter functions where an untrusted value reaches a sink, annotating the first flow as
+    /// `taint_source` and `taint_sink`
+    ///
+    /// Only the file the query started from is known here: filters needing accounts structs
+    /// declared elsewhere use `NodeContext::taint_flows` instead.
+    pub fn with_taint_flows(self, config: &TaintConfig) -> Self {
+        debug!("Filtering functions with taint flows");
+        let program = ProgramContext::build(self.root.map(|root| ("", root)));
+        let new_results = self
+            .results
+            .into_iter()
+            .filter_map(|mut node| {
+                let flow = config.flows(&node, &program, "").into_iter().next()?;
+                node.set_metadata("taint_source", flow.source);
+                node.set_metadata("taint_sink", flow.sink);
+                Some(node)
+            })
+            .collect();
+
+        Self {
+            results: new_results,
+            root: self.root,
+            traversal: None,
+        }
+    }
+
+    /// Build the call graph of the file the query started from
+    pub fn call_graph(&self) -> Option<CallGraph<'a>> {
+        self.root.map(CallGraph::from_file)
+    }
+
     /// Helper function to check if a function call exists
     fn has_function_call<F>(function_name: &str, visit_fn: F) -> bool
     where
@@ -372,12 +708,44 @@ impl<'a> AstQuery<'a> {
 
         Self {
             results: new_results,
+            root: self.root,
+            traversal: None,
+        }
+    }
+
+    /// Annotate every node with metadata for downstream filters and findings
+    pub fn annotate<F>(mut self, annotator: F) -> Self
+    where
+        F: Fn(&mut AstNode<'a>),
+    {
+        debug!("Annotating {} nodes", self.results.len());
+        self.traversal = None;
+        for node in &mut self.results {
+            annotator(node);
+        }
+        self
+    }
+
+    /// Filter nodes that carry the given metadata entry
+    pub fn with_metadata(self, key: &str) -> Self {
+        debug!("Filtering nodes with metadata: {key}");
+        let new_results = self
+            .results
+            .into_iter()
+            .filter(|node| node.metadata.contains_key(key))
+            .collect();
+
+        Self {
+            results: new_results,
+            root: self.root,
+            traversal: None,
         }
     }
 
     /// Combine with another query (OR operator)
     pub fn or(mut self, other: Self) -> Self {
         debug!("Combining queries with OR");
+        self.traversal = None;
         self.results.extend(other.results);
         self
     }
@@ -388,24 +756,36 @@ impl<'a> AstQuery<'a> {
         let other_results = other.results;
 
         // @todo => Simple implementation
+        // Nodes are compared by the AST item they wrap, ignoring metadata
         let new_results = self
             .results
             .into_iter()
-            .filter(|node| other_results.contains(node))
+            .filter(|node| other_results.iter().any(|other| other.data == node.data))
             .collect();
 
         Self {
             results: new_results,
+            root: self.root,
+            traversal: None,
         }
     }
 
-    /// Negate the query (NOT operator)
-    pub fn not(self) -> Self {
-        debug!("Negating query - returning empty result (placeholder implementation)");
-        // @todo => Implement proper negation logic
+    /// Remove the results of another query (difference operator)
+    pub fn except(self, other: Self) -> Self {
+        debug!("Removing the results of another query");
+        let other_results = other.results;
+
+        // Nodes are compared by the AST item they wrap, ignoring metadata
+        let new_results = self
+            .results
+            .into_iter()
+            .filter(|node| other_results.iter().all(|other| other.data != node.data))
+            .collect();
 
         Self {
-            results: Vec::new(),
+            results: new_results,
+            root: self.root,
+            traversal: None,
         }
     }
 
@@ -425,11 +805,11 @@ impl<'a> AstQuery<'a> {
     }
 
     /// Convert the results to findings
-    pub fn to_findings(self, severity: Severity, message: &str, recommendations: &[String], file_path: &str) -> Vec<Finding> {
+    pub fn to_findings(&self, severity: Severity, message: &str, recommendations: &[String], file_path: &str) -> Vec<Finding> {
         debug!("Converting {} results to findings", self.results.len());
 
         self.results
-            .into_iter()
+            .iter()
             .map(|node| {
                 let description = match &node.name {
                     Some(name) => format!("{message} in '{name}'"),
@@ -439,9 +819,14 @@ impl<'a> AstQuery<'a> {
                 Finding {
                     description,
                     severity: severity.clone(),
+                    confidence: node.confidence,
                     location: Self::create_fallback_location(file_path),
                     code_snippet: Some(node.snippet()),
                     recommendations: recommendations.to_vec(),
+                    fixes: Vec::new(),
+                    metadata: node.metadata.clone(),
+                    rules: Vec::new(),
+                    fingerprint: None,
                 }
             })
             .collect()
@@ -450,18 +835,37 @@ impl<'a> AstQuery<'a> {
     /// Convert query results to findings with precise locations using `SpanExtractor`
     /// This is the preferred method for `dsl_query` rules
     pub fn to_findings_with_span_extractor(
-        self, 
+        &self,
         severity: Severity, 
         title: &str,
         description: &str,
         recommendations: &[String],
         file_path: &str,
         span_extractor: &crate::analyzer::span_utils::SpanExtractor
+    ) -> Vec<Finding> {
+        self.to_findings_with_formatter(
+            severity,
+            recommendations,
+            file_path,
+            span_extractor,
+            &|node| node.describe(title, description),
+        )
+    }
+
+    /// Convert query results to findings with precise locations, building each
+    /// description with the given formatter (used by `RuleBuilder::message`)
+    pub fn to_findings_with_formatter(
+        &self,
+        severity: Severity,
+        recommendations: &[String],
+        file_path: &str,
+        span_extractor: &crate::analyzer::span_utils::SpanExtractor,
+        formatter: &dyn Fn(&AstNode<'a>) -> String,
     ) -> Vec<Finding> {
         debug!("Converting {} results to findings with precise locations", self.results.len());
 
         self.results
-            .into_iter()
+            .iter()
             .map(|node| {
                 // Use SpanExtractor for precise location and snippet
                 let (location, code_snippet) = if let Some(spanned_node) = node.get_spanned_node() {
@@ -474,20 +878,17 @@ impl<'a> AstQuery<'a> {
                     (Self::create_fallback_location(file_path), node.snippet())
                 };
 
-                // Create descriptive message based on node name
-                let finding_description = match &node.name {
-                    Some(name) => format!(
-                        "{title} in '{name}'. {description}"
-                    ),
-                    None => format!("{title}: {description}"),
-                };
-
                 Finding {
-                    description: finding_description,
+                    description: formatter(node),
                     severity: severity.clone(),
+                    confidence: node.confidence,
                     location,
                     code_snippet: Some(code_snippet),
                     recommendations: recommendations.to_vec(),
+                    fixes: Vec::new(),
+                    metadata: node.metadata.clone(),
+                    rules: Vec::new(),
+                    fingerprint: None,
                 }
             })
             .collect()
@@ -504,29 +905,64 @@ impl<'a> AstQuery<'a> {
         }
     }
 
-    /// Helper function to recursively extract functions from items (including nested modules)
-    fn extract_functions_recursive<'b>(items: &'b [syn::Item], results: &mut Vec<AstNode<'b>>) {
-        for item in items {
+    /// Helper function to find the top-level structs of a file (computed once per file)
+    fn struct_paths(file: &File) -> std::rc::Rc<Vec<cache::ItemPath>> {
+        cache::traversal(file, "structs", || {
+            file.items
+                .iter()
+                .enumerate()
+                .filter_map(|(index, item)| match item {
+                    Item::Struct(struct_item) => {
+                        trace!("Found struct: {}", struct_item.ident);
+                        Some(vec![index])
+                    }
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+
+    /// Helper function to resolve the node of an item from its path
+    fn resolve_item_path<'b>(items: &'b [syn::Item], path: &[usize]) -> Option<AstNode<'b>> {
+        let (index, rest) = path.split_first()?;
+        match (items.get(*index)?, rest) {
+            (syn::Item::Fn(func), []) => Some(AstNode::from_function(func)),
+            (syn::Item::Struct(struct_item), []) => Some(AstNode::from_struct(struct_item)),
+            (syn::Item::Mod(module), rest) => Self::resolve_item_path(&module.content.as_ref()?.1, rest),
+            (syn::Item::Impl(impl_block), [impl_index]) => match impl_block.items.get(*impl_index)? {
+                syn::ImplItem::Fn(func) => Some(AstNode::from_impl_function(func)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Helper function to recursively find the paths of the functions in items (including nested modules)
+    fn extract_functions_recursive(items: &[syn::Item], prefix: &mut Vec<usize>, paths: &mut Vec<cache::ItemPath>) {
+        for (index, item) in items.iter().enumerate() {
+            prefix.push(index);
             match item {
                 syn::Item::Fn(func) => {
                     trace!("Found function: {}", func.sig.ident);
-                    results.push(AstNode::from_function(func));
+                    paths.push(prefix.clone());
                 }
                 syn::Item::Mod(module) => {
                     debug!("Searching in module: {}", module.ident);
                     // Check if module has inline content (not external file)
                     if let Some((_, items)) = &module.content {
                         // Recursively search in the module
-                        Self::extract_functions_recursive(items, results);
+                        Self::extract_functions_recursive(items, prefix, paths);
                     }
                 }
                 syn::Item::Impl(impl_block) => {
                     debug!("Searching in impl block");
                     // Search for functions in impl blocks
-                    for impl_item in &impl_block.items {
+                    for (impl_index, impl_item) in impl_block.items.iter().enumerate() {
                         if let syn::ImplItem::Fn(func) = impl_item {
                             trace!("Found impl function: {}", func.sig.ident);
-                            results.push(AstNode::from_impl_function(func));
+                            let mut path = prefix.clone();
+                            path.push(impl_index);
+                            paths.push(path);
                         }
                     }
                 }
@@ -534,6 +970,7 @@ impl<'a> AstQuery<'a> {
                     // Other items (structs, enums..)
                 }
             }
+            prefix.pop();
         }
     }
 }
@@ -547,13 +984,12 @@ struct CallFinder {
 impl<'ast> Visit<'ast> for CallFinder {
     fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
         // Check if this is a call to our target function
-        if let syn::Expr::Path(path) = &*call.func {
-            if let Some(ident) = path.path.get_ident() {
-                if *ident == self.target_function {
-                    self.found = true;
-                    trace!("Found call to target function: {}", self.target_function);
-                }
-            }
+        if let syn::Expr::Path(path) = &*call.func
+            && let Some(ident) = path.path.get_ident()
+            && *ident == self.target_function
+        {
+            self.found = true;
+            trace!("Found call to target function: {}", self.target_function);
         }
         
         // Continue visiting sub-expressions
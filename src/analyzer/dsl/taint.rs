@@ -0,0 +1,493 @@
+use log::trace;
+use std::collections::{BTreeMap, BTreeSet};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{Block, Expr};
+
+use crate::analyzer::dsl::program::ProgramContext;
+use crate::analyzer::dsl::query::{AstNode, NodeData};
+use crate::analyzer::dsl::values::{compact, diverges, key, pattern_names};
+
+/// Token and system instructions whose last argument is the amount moved
+/// (`token::transfer(cpi_ctx, amount)`, `system_instruction::transfer(from, to, lamports)`)
+const CPI_AMOUNT_CALLS: &[&str] = &["transfer", "transfer_checked", "mint_to", "mint_to_checked", "burn", "burn_checked", "approve"];
+
+/// Methods writing the lamports of an account, taking the amount as first argument
+const LAMPORT_METHODS: &[&str] = &["add_lamports", "sub_lamports", "set_lamports"];
+
+/// Fields holding the authority of an account
+const AUTHORITY_FIELDS: &[&str] = &["authority", "owner", "admin", "delegate"];
+
+/// Account types whose data and key Anchor does not check
+const UNCHECKED_TYPES: &[&str] = &["UncheckedAccount", "AccountInfo"];
+
+/// Constraints of `#[account(..)]` that check an account (`has_one` on another field counts too)
+const CHECKING_CONSTRAINTS: &[&str] = &["address", "constraint", "owner", "seeds"];
+
+/// Where untrusted values come from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaintSource {
+    /// Arguments of instruction handlers (the parameters of a function taking a `Context`, the
+    /// context excepted)
+    InstructionArgs,
+    /// `ctx.remaining_accounts`, which Anchor does not check
+    RemainingAccounts,
+    /// `UncheckedAccount` and `AccountInfo` fields of the accounts struct, read through the
+    /// program context
+    UncheckedAccounts,
+    /// Expressions containing the given text (`oracle.price`)
+    Expression(String),
+}
+
+/// Where untrusted values must not flow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaintSink {
+    /// Amount of a token or system instruction (`transfer`, `mint_to`, `burn`...)
+    CpiAmount,
+    /// Write to the lamports of an account (`**account.lamports.borrow_mut() -= amount`,
+    /// `account.sub_lamports(amount)`)
+    LamportWrite,
+    /// Assignment of an authority field (`vault.authority = new_authority`)
+    AuthorityAssignment,
+    /// Arguments of calls to a function or method
+    Call(String),
+}
+
+/// Checks after which a value is trusted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sanitizer {
+    /// `require!`-like and `assert!`-like macros using the value
+    Require,
+    /// Comparisons of the value in an `if` condition, for its block or after it when it returns
+    /// early (`if amount > MAX { return err!(..) }`)
+    Comparison,
+    /// Unchecked accounts with an `address`, `constraint`, `owner` or `seeds` constraint, or named
+    /// by a `has_one` constraint of another account
+    HasOne,
+    /// Calls to a function or method taking the value (`validate_price(price)?`)
+    Call(String),
+}
+
+/// Flow of an untrusted value to a sink
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaintFlow {
+    /// Untrusted expression the value comes from (`amount`, `ctx.remaining_accounts`)
+    pub source: String,
+    /// Line of the source
+    pub source_line: usize,
+    /// Sink the value reaches (`amount of transfer`, `vault.authority`)
+    pub sink: String,
+    /// Line of the sink
+    pub sink_line: usize,
+}
+
+/// Taint analysis of a function: which sources reach which sinks, unless sanitized on the way
+///
+/// The analysis is intraprocedural: values are followed through `let` bindings, assignments
+/// and expressions in statement order. A sanitizer inside a block only covers that block, and
+/// tainted values stay tainted whatever branch taints them.
+///
+/// ```ignore
+/// let config = TaintConfig::new()
+///     .source(TaintSource::InstructionArgs)
+///     .sink(TaintSink::CpiAmount)
+///     .sanitizer(Sanitizer::Require);
+/// AstQuery::new(ast).functions().with_taint_flows(&config)
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaintConfig {
+    sources: Vec<TaintSource>,
+    sinks: Vec<TaintSink>,
+    sanitizers: Vec<Sanitizer>,
+}
+
+impl TaintConfig {
+    /// Creates a configuration without sources, sinks or sanitizers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a source of untrusted values
+    pub fn source(mut self, source: TaintSource) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Adds a sink untrusted values must not reach
+    pub fn sink(mut self, sink: TaintSink) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Adds a check after which values are trusted
+    pub fn sanitizer(mut self, sanitizer: Sanitizer) -> Self {
+        self.sanitizers.push(sanitizer);
+        self
+    }
+
+    /// Returns the flows from a source to a sink in a function node (none for other nodes)
+    ///
+    /// The program context and the path of the file resolve the accounts struct of the
+    /// handler, for [`TaintSource::UncheckedAccounts`].
+    pub fn flows(&self, node: &AstNode<'_>, program: &ProgramContext, file_path: &str) -> Vec<TaintFlow> {
+        let (sig, block) = match &node.data {
+            NodeData::Function(func) => (&func.sig, &*func.block),
+            NodeData::ImplFunction(func) => (&func.sig, &func.block),
+            _ => return Vec::new(),
+        };
+        let mut walker = TaintWalker {
+            config: self,
+            tainted: BTreeMap::new(),
+            flows: Vec::new(),
+        };
+        self.taint_parameters(sig, program, file_path, &mut walker.tainted);
+        walker.visit_block(block);
+        trace!("{} taint flows in {}", walker.flows.len(), sig.ident);
+        walker.flows
+    }
+
+    fn has_source(&self, source: &TaintSource) -> bool {
+        self.sources.contains(source)
+    }
+
+    fn has_sink(&self, sink: &TaintSink) -> bool {
+        self.sinks.contains(sink)
+    }
+
+    fn has_sanitizer(&self, sanitizer: &Sanitizer) -> bool {
+        self.sanitizers.contains(sanitizer)
+    }
+
+    /// Helper function to taint the arguments and unchecked accounts of a handler
+    fn taint_parameters(&self, sig: &syn::Signature, program: &ProgramContext, file_path: &str, tainted: &mut BTreeMap<String, Origin>) {
+        for input in &sig.inputs {
+            let syn::FnArg::Typed(pat_type) = input else {
+                continue;
+            };
+            let syn::Pat::Ident(pat_ident) = &*pat_type.pat else {
+                continue;
+            };
+            let name = pat_ident.ident.to_string();
+            let origin = |source: String| Origin {
+                source,
+                line: pat_ident.ident.span().start().line,
+            };
+            match context_accounts(&pat_type.ty) {
+                Some(accounts) if self.has_source(&TaintSource::UncheckedAccounts) => {
+                    let Some(declaration) = program.resolve(file_path, &accounts) else {
+                        continue;
+                    };
+                    let has_one: BTreeSet<&str> = declaration
+                        .members
+                        .iter()
+                        .filter_map(|member| member.attribute("account"))
+                        .flat_map(|arguments| arguments.split(',').filter_map(|argument| argument.strip_prefix("has_one=")))
+                        .collect();
+                    for member in &declaration.members {
+                        let unchecked = member
+                            .ty
+                            .as_deref()
+                            .is_some_and(|ty| UNCHECKED_TYPES.iter().any(|unchecked| ty.starts_with(unchecked)));
+                        let checked = has_one.contains(member.name.as_str())
+                            || member.attribute("account").is_some_and(|arguments| {
+                                arguments
+                                    .split(',')
+                                    .any(|argument| CHECKING_CONSTRAINTS.iter().any(|constraint| argument.starts_with(&format!("{constraint}="))))
+                            });
+                        if unchecked && !(checked && self.has_sanitizer(&Sanitizer::HasOne)) {
+                            let account = format!("{name}.accounts.{}", member.name);
+                            tainted.insert(account.clone(), origin(account));
+                        }
+                    }
+                }
+                Some(_) => {}
+                None if sig.inputs.iter().any(is_context) && self.has_source(&TaintSource::InstructionArgs) => {
+                    tainted.insert(name.clone(), origin(name));
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+/// Untrusted value a tainted variable comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Origin {
+    source: String,
+    line: usize,
+}
+
+/// Helper visitor following tainted values through a function, in statement order
+struct TaintWalker<'c> {
+    config: &'c TaintConfig,
+    /// Tainted variables and fields (their fields are tainted too)
+    tainted: BTreeMap<String, Origin>,
+    flows: Vec<TaintFlow>,
+}
+
+impl TaintWalker<'_> {
+    /// Returns the untrusted value an expression uses, if any
+    fn origin_of(&self, expr: &Expr) -> Option<Origin> {
+        let mut keys = KeyFinder::default();
+        keys.visit_expr(expr);
+        let tainted = keys.keys.iter().find_map(|key| {
+            self.tainted
+                .iter()
+                .find(|(tainted, _)| key == *tainted || key.strip_prefix(tainted.as_str()).is_some_and(|rest| rest.starts_with('.')))
+                .map(|(_, origin)| origin.clone())
+        });
+        if tainted.is_some() {
+            return tainted;
+        }
+
+        let text = compact(expr);
+        let line = expr.span().start().line;
+        for source in &self.config.sources {
+            match source {
+                TaintSource::RemainingAccounts if text.contains("remaining_accounts") => {
+                    return Some(Origin {
+                        source: "remaining_accounts".to_string(),
+                        line,
+                    });
+                }
+                TaintSource::Expression(pattern) if text.contains(pattern.as_str()) => {
+                    return Some(Origin {
+                        source: pattern.clone(),
+                        line,
+                    });
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Record a flow if the value reaching a sink is tainted
+    fn reach(&mut self, sink: &TaintSink, value: &Expr, description: String) {
+        if !self.config.has_sink(sink) {
+            return;
+        }
+        if let Some(origin) = self.origin_of(value) {
+            let flow = TaintFlow {
+                source: origin.source,
+                source_line: origin.line,
+                sink: description,
+                sink_line: value.span().start().line,
+            };
+            if !self.flows.contains(&flow) {
+                self.flows.push(flow);
+            }
+        }
+    }
+
+    /// Trust the variables and fields an expression uses
+    fn sanitize(&mut self, expr: &Expr) {
+        let mut keys = KeyFinder::default();
+        keys.visit_expr(expr);
+        self.tainted.retain(|tainted, _| {
+            !keys
+                .keys
+                .iter()
+                .any(|key| key == tainted || key.strip_prefix(tainted.as_str()).is_some_and(|rest| rest.starts_with('.')))
+        });
+    }
+
+    /// Taint (or trust) the variable or field assigned with a value
+    fn assign(&mut self, target: &Expr, value: &Expr) {
+        let Some(target) = key(target) else {
+            return;
+        };
+        match self.origin_of(value) {
+            Some(origin) => {
+                self.tainted.insert(target, origin);
+            }
+            None => {
+                self.tainted.remove(&target);
+            }
+        }
+    }
+
+    /// Check the calls reaching a sink or sanitizing their arguments
+    fn call(&mut self, name: &str, args: &Punctuated<Expr, syn::Token![,]>) {
+        if CPI_AMOUNT_CALLS.contains(&name)
+            && let Some(amount) = args.last()
+        {
+            self.reach(&TaintSink::CpiAmount, amount, format!("amount of {name}"));
+        }
+        if self.config.has_sink(&TaintSink::Call(name.to_string())) {
+            for arg in args {
+                self.reach(&TaintSink::Call(name.to_string()), arg, format!("argument of {name}"));
+            }
+        }
+        if self.config.has_sanitizer(&Sanitizer::Call(name.to_string())) {
+            for arg in args {
+                self.sanitize(arg);
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for TaintWalker<'_> {
+    fn visit_block(&mut self, block: &'ast Block) {
+        // Sanitizers of the block do not outlive it, taints do
+        let saved = self.tainted.clone();
+        visit::visit_block(self, block);
+        let inner = std::mem::replace(&mut self.tainted, saved);
+        for (key, origin) in inner {
+            self.tainted.entry(key).or_insert(origin);
+        }
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        visit::visit_local(self, local);
+        let origin = local.init.as_ref().and_then(|init| self.origin_of(&init.expr));
+        for name in pattern_names(&local.pat) {
+            match &origin {
+                Some(origin) => {
+                    self.tainted.insert(name, origin.clone());
+                }
+                None => {
+                    self.tainted.remove(&name);
+                }
+            }
+        }
+    }
+
+    fn visit_expr_assign(&mut self, assign: &'ast syn::ExprAssign) {
+        visit::visit_expr_assign(self, assign);
+        if let Expr::Field(field) = &*assign.left
+            && let syn::Member::Named(member) = &field.member
+            && AUTHORITY_FIELDS.contains(&member.to_string().as_str())
+        {
+            self.reach(&TaintSink::AuthorityAssignment, &assign.right, compact(&assign.left));
+        }
+        self.assign(&assign.left, &assign.right);
+    }
+
+    fn visit_expr_binary(&mut self, binary: &'ast syn::ExprBinary) {
+        visit::visit_expr_binary(self, binary);
+        let compound = matches!(
+            binary.op,
+            syn::BinOp::AddAssign(_) | syn::BinOp::SubAssign(_) | syn::BinOp::MulAssign(_) | syn::BinOp::DivAssign(_)
+        );
+        if !compound {
+            return;
+        }
+        if compact(&binary.left).contains("lamports") {
+            self.reach(&TaintSink::LamportWrite, &binary.right, compact(&binary.left));
+        }
+        // `total += amount` taints `total`, whatever it was
+        if let Some(target) = key(&binary.left)
+            && let Some(origin) = self.origin_of(&binary.right)
+        {
+            self.tainted.insert(target, origin);
+        }
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        visit::visit_expr_call(self, call);
+        if let Expr::Path(path) = &*call.func
+            && let Some(segment) = path.path.segments.last()
+        {
+            self.call(&segment.ident.to_string(), &call.args);
+        }
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        visit::visit_expr_method_call(self, call);
+        let name = call.method.to_string();
+        if LAMPORT_METHODS.contains(&name.as_str())
+            && let Some(amount) = call.args.first()
+        {
+            self.reach(&TaintSink::LamportWrite, amount, format!("{}.{name}", compact(&call.receiver)));
+        }
+        self.call(&name, &call.args);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        let name = mac.path.segments.last().map(|segment| segment.ident.to_string()).unwrap_or_default();
+        if (name.starts_with("require") || name.starts_with("assert")) && self.config.has_sanitizer(&Sanitizer::Require)
+            && let Ok(args) = mac.parse_body_with(Punctuated::<Expr, syn::Token![,]>::parse_terminated)
+        {
+            for arg in &args {
+                self.sanitize(arg);
+            }
+        }
+    }
+
+    fn visit_expr_if(&mut self, expr_if: &'ast syn::ExprIf) {
+        self.visit_expr(&expr_if.cond);
+        let compares = self.config.has_sanitizer(&Sanitizer::Comparison) && is_comparison(&expr_if.cond);
+        let saved = self.tainted.clone();
+        if compares {
+            self.sanitize(&expr_if.cond);
+        }
+        self.visit_block(&expr_if.then_branch);
+        let then_tainted = std::mem::replace(&mut self.tainted, saved);
+        if let Some((_, else_branch)) = &expr_if.else_branch {
+            self.visit_expr(else_branch);
+        }
+        for (key, origin) in then_tainted {
+            self.tainted.entry(key).or_insert(origin);
+        }
+        // Values checked by an `if` returning early are trusted after it
+        if compares && expr_if.else_branch.is_none() && diverges(&expr_if.then_branch) {
+            self.sanitize(&expr_if.cond);
+        }
+    }
+}
+
+/// Helper visitor to collect the variables and fields an expression uses
+#[derive(Default)]
+struct KeyFinder {
+    keys: Vec<String>,
+}
+
+impl<'ast> Visit<'ast> for KeyFinder {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        match key(expr) {
+            Some(key) => self.keys.push(key),
+            None => visit::visit_expr(self, expr),
+        }
+    }
+}
+
+/// Helper function to check if a condition compares values
+fn is_comparison(cond: &Expr) -> bool {
+    match cond {
+        Expr::Binary(binary) => matches!(
+            binary.op,
+            syn::BinOp::Eq(_) | syn::BinOp::Ne(_) | syn::BinOp::Lt(_) | syn::BinOp::Le(_) | syn::BinOp::Gt(_) | syn::BinOp::Ge(_)
+        ) || (matches!(binary.op, syn::BinOp::And(_) | syn::BinOp::Or(_)) && (is_comparison(&binary.left) || is_comparison(&binary.right))),
+        Expr::Paren(paren) => is_comparison(&paren.expr),
+        Expr::Unary(unary) => is_comparison(&unary.expr),
+        _ => false,
+    }
+}
+
+/// Helper function to check if a parameter is an Anchor `Context`
+fn is_context(input: &syn::FnArg) -> bool {
+    matches!(input, syn::FnArg::Typed(pat_type) if context_accounts(&pat_type.ty).is_some())
+}
+
+/// Helper function to get the accounts struct of a `Context<T>` type (empty without arguments)
+fn context_accounts(ty: &syn::Type) -> Option<String> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last().filter(|segment| segment.ident == "Context")?;
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return Some(String::new());
+    };
+    Some(
+        arguments
+            .args
+            .iter()
+            .find_map(|argument| match argument {
+                syn::GenericArgument::Type(syn::Type::Path(accounts)) => accounts.path.segments.last().map(|segment| segment.ident.to_string()),
+                _ => None,
+            })
+            .unwrap_or_default(),
+    )
+}
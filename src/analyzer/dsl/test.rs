@@ -0,0 +1,784 @@
+use crate::analyzer::dsl::cache::{self, CacheScope};
+use crate::analyzer::dsl::call_graph::{CallGraph, ProgramCallGraph};
+use crate::analyzer::dsl::context::NodeContext;
+use crate::analyzer::dsl::message::MessageTemplate;
+use crate::analyzer::dsl::params::{ParamValue, RuleParams};
+use crate::analyzer::dsl::program::{DeclarationKind, ProgramContext, derives};
+use crate::analyzer::dsl::project::{ProjectFile, ProjectQuery};
+use crate::analyzer::dsl::aliases::AccountAliases;
+use crate::analyzer::dsl::builders::{RuleBuildError, RuleBuilder};
+use crate::analyzer::dsl::query::{AstNode, AstQuery};
+use crate::analyzer::dsl::taint::{Sanitizer, TaintConfig, TaintSink, TaintSource};
+use crate::analyzer::dsl::values::{Constants, ValueAnalysis, ValueRange, eval_const};
+use crate::analyzer::engine::RuleEngine;
+use syn::{File, ItemStruct, parse_quote};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program() -> File {
+        parse_quote! {
+            #[program]
+            pub mod vault {
+                use super::*;
+
+                pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+                    helpers::validate_authority(&ctx)?;
+                    transfer(ctx, amount)
+                }
+
+                pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+                    transfer(ctx, amount)
+                }
+            }
+
+            pub fn validate_authority(ctx: &Context<Withdraw>) -> Result<()> {
+                require_keys_eq!(ctx.accounts.vault.authority, ctx.accounts.authority.key());
+                check_signer(&ctx.accounts.authority)
+            }
+
+            fn check_signer(account: &AccountInfo) -> Result<()> {
+                if !account.is_signer {
+                    return err!(ErrorCode::Unauthorized);
+                }
+                Ok(())
+            }
+
+            fn transfer<T>(ctx: Context<T>, amount: u64) -> Result<()> {
+                Ok(())
+            }
+
+            fn unused_helper() {}
+        }
+    }
+
+    fn names(query: AstQuery<'_>) -> Vec<String> {
+        let mut names: Vec<String> = query.collect().iter().map(|node| node.name()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_call_graph_edges() {
+        let ast = program();
+        let graph = CallGraph::from_file(&ast);
+
+        let callees: Vec<&str> = graph.callees("withdraw").collect();
+        assert_eq!(callees, vec!["transfer", "validate_authority"]);
+
+        let callers: Vec<&str> = graph.callers("transfer").collect();
+        assert_eq!(callers, vec!["deposit", "withdraw"]);
+
+        let instructions: Vec<&str> = graph.instructions().collect();
+        assert_eq!(instructions, vec!["deposit", "withdraw"]);
+    }
+
+    #[test]
+    fn test_program_call_graph_credits_helpers() {
+        let lib: File = parse_quote! {
+            #[program]
+            pub mod vault {
+                use super::*;
+
+                #[access_control(Withdraw::validate(&ctx.accounts))]
+                pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+                    if amount > ctx.accounts.vault.limit {
+                        return err!(VaultError::Limit);
+                    }
+                    helpers::pay(&mut ctx.accounts.vault, amount)
+                }
+
+                pub fn close(ctx: Context<Close>) -> Result<()> {
+                    ctx.accounts.validate()
+                }
+            }
+        };
+        let helpers: File = parse_quote! {
+            impl<'info> Withdraw<'info> {
+                pub fn validate(&self) -> Result<()> {
+                    check_owner(&self.authority.to_account_info(), &self.vault)
+                }
+            }
+
+            impl<'info> Close<'info> {
+                pub fn validate(&self) -> Result<()> {
+                    require!(self.receiver.is_signer, VaultError::Unauthorized);
+                    Ok(())
+                }
+            }
+
+            fn check_owner(account: &AccountInfo, vault: &Vault) -> Result<()> {
+                require_keys_eq!(account.key(), vault.authority);
+                check_owner(account, vault)
+            }
+
+            pub fn pay(vault: &mut Vault, amount: u64) -> Result<()> {
+                vault.paid += amount;
+                Ok(())
+            }
+        };
+        let program = ProgramContext::build([("src/lib.rs", &lib), ("src/helpers.rs", &helpers)]);
+        let graph = program.call_graph();
+
+        let instructions: Vec<&str> = graph.instructions().map(|function| function.name.as_str()).collect();
+        assert_eq!(instructions, ["close", "withdraw"]);
+        assert_eq!(graph.callees("withdraw").into_iter().collect::<Vec<_>>(), ["pay", "validate"]);
+        assert_eq!(graph.callers("check_owner").into_iter().collect::<Vec<_>>(), ["check_owner", "validate"]);
+        assert!(graph.reachable_from(["withdraw"]).contains("check_owner"));
+
+        // Checks of helpers are written with the arguments of the handler, through the impl of its accounts
+        let withdraw = graph.handlers_of("Withdraw").next().unwrap();
+        let checks: Vec<(String, String)> = graph.checks_from(withdraw).into_iter().map(|check| (check.file, check.condition)).collect();
+        assert_eq!(checks.len(), 2, "{checks:?}");
+        assert_eq!(checks[0], ("src/lib.rs".to_string(), "amount > ctx.accounts.vault.limit".to_string()));
+        assert_eq!(
+            checks[1],
+            ("src/helpers.rs".to_string(), "require_keys_eq!(ctx.accounts.authority.key(), ctx.accounts.vault.authority)".to_string())
+        );
+        let close = graph.handlers_of("Close").next().unwrap();
+        let checks: Vec<String> = graph.checks_from(close).into_iter().map(|check| check.condition).collect();
+        assert_eq!(checks, ["require!(ctx.accounts.receiver.is_signer, VaultError::Unauthorized)"]);
+
+        // Helpers declared in files outside the program are not followed
+        let alone = ProgramCallGraph::build([("src/lib.rs", &lib)]);
+        assert!(alone.checks_from(alone.handlers_of("Close").next().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_calls_to_transitively_follows_helpers() {
+        let ast = program();
+
+        let direct = AstQuery::new(&ast).functions().calls_to("check_signer");
+        assert_eq!(names(direct), vec!["validate_authority"]);
+
+        let transitive = AstQuery::new(&ast).functions().calls_to_transitively("check_signer");
+        assert_eq!(names(transitive), vec!["validate_authority", "withdraw"]);
+    }
+
+    #[test]
+    fn test_callers_and_callees_of() {
+        let ast = program();
+
+        let callees = AstQuery::new(&ast).functions().with_name("validate_authority").callees_of();
+        assert_eq!(names(callees), vec!["check_signer"]);
+
+        let callers = AstQuery::new(&ast).functions().with_name("check_signer").callers_of();
+        assert_eq!(names(callers), vec!["validate_authority"]);
+    }
+
+    #[test]
+    fn test_reachable_from_instruction() {
+        let ast = program();
+
+        let reachable = AstQuery::new(&ast).functions().reachable_from_instruction();
+        assert_eq!(
+            names(reachable),
+            vec!["check_signer", "deposit", "transfer", "validate_authority", "withdraw"]
+        );
+    }
+
+    #[test]
+    fn test_message_template_interpolation() {
+        let item_struct: ItemStruct = parse_quote! {
+            pub struct Withdraw<'info> {
+                pub authority: AccountInfo<'info>,
+            }
+        };
+        let node = AstNode::from_struct(&item_struct)
+            .with_metadata("field", "authority")
+            .with_metadata("value", 2_usize);
+
+        let template = MessageTemplate::new("{title}: '{field}' in '{struct}' ({value}) {function} {unknown}");
+        assert_eq!(
+            template.render(&node, "Missing Signer", "unused"),
+            "Missing Signer: 'authority' in 'Withdraw' (2) {function} {unknown}"
+        );
+    }
+
+    #[test]
+    fn test_node_context_detects_test_code() {
+        // Parsed from source so nodes carry real spans
+        let ast = syn::parse_file(
+            "pub fn process(amount: u64) -> u64 { amount / 2 }\n\
+             #[cfg(test)]\n\
+             mod tests {\n\
+                 mod helpers {\n\
+                     pub fn split(amount: u64) -> u64 { amount / 3 }\n\
+                 }\n\
+             }\n",
+        )
+        .unwrap();
+        let functions = AstQuery::new(&ast).functions().collect();
+        let (process, split) = (&functions[0], &functions[1]);
+
+        let context = NodeContext::new("programs/vault/src/lib.rs", &ast);
+        assert!(!context.is_test_file());
+        assert!(!context.is_test_code(process));
+        assert!(context.is_test_code(split));
+        assert!(context.module_path(process).is_empty());
+        assert_eq!(context.module_path(split), vec!["tests", "helpers"]);
+        assert!(context.has_attribute(split, "cfg"));
+
+        let context = NodeContext::new("programs/vault/tests/withdraw.rs", &ast);
+        assert!(context.is_test_code(process));
+    }
+
+    #[test]
+    fn test_file_modules_match_inline_modules() {
+        let lib: File = parse_quote! {
+            pub mod processor;
+            #[cfg(test)]
+            mod tests;
+        };
+        let tests: File = parse_quote! {
+            mod helpers {
+                pub fn split(amount: u64) -> u64 { amount / 3 }
+            }
+        };
+        let processor: File = parse_quote! {
+            pub fn process(amount: u64) -> u64 { amount }
+        };
+        let program = ProgramContext::build([
+            ("programs/vault/src/lib.rs", &lib),
+            ("programs/vault/src/tests.rs", &tests),
+            ("programs/vault/src/processor.rs", &processor),
+        ]);
+
+        // Same results as the inline `#[cfg(test)] mod tests { mod helpers { .. } }`
+        let split = &AstQuery::new(&tests).functions().collect()[0];
+        let context = NodeContext::new("programs/vault/src/tests.rs", &tests).with_program(&program);
+        assert!(context.is_test_code(split));
+        assert_eq!(context.module_path(split), vec!["tests", "helpers"]);
+
+        let process = &AstQuery::new(&processor).functions().collect()[0];
+        let context = NodeContext::new("programs/vault/src/processor.rs", &processor).with_program(&program);
+        assert!(!context.is_test_code(process));
+        assert_eq!(context.module_path(process), vec!["processor"]);
+        let module = program.module_of("programs/vault/src/processor.rs").unwrap();
+        assert_eq!(module.path, ["crate", "processor"]);
+    }
+
+    fn rule(id: &str) -> RuleBuilder {
+        RuleBuilder::new()
+            .id(id)
+            .title("Test Rule")
+            .description("Matches every function")
+            .dsl_query(|ast, _file_path, _span_extractor| AstQuery::new(ast).functions())
+    }
+
+    #[test]
+    fn test_rule_build_validation() {
+        assert!(rule("solana-test-rule").build().is_ok());
+        assert_eq!(
+            rule("Solana_Test").build().err(),
+            Some(RuleBuildError::InvalidId { id: "Solana_Test".to_string() })
+        );
+        assert_eq!(rule("").build().err(), Some(RuleBuildError::MissingId));
+        assert_eq!(
+            rule("no-title").title("").build().err(),
+            Some(RuleBuildError::MissingTitle { id: "no-title".to_string() })
+        );
+        assert_eq!(
+            RuleBuilder::new().id("no-query").title("t").description("d").build().err(),
+            Some(RuleBuildError::MissingQuery { id: "no-query".to_string() })
+        );
+        assert!(matches!(
+            rule("old-anchor").anchor_versions("0.x.1").build().err(),
+            Some(RuleBuildError::InvalidAnchorVersions { versions, .. }) if versions == "0.x.1"
+        ));
+
+        // Rules restricted to some anchor-lang versions still apply when the version is unknown
+        let interface = rule("interface-accounts").anchor_versions(">=0.28").build().unwrap();
+        assert!(interface.applies_to_anchor(Some(&semver::Version::new(0, 29, 0))));
+        assert!(!interface.applies_to_anchor(Some(&semver::Version::new(0, 26, 0))));
+        assert!(interface.applies_to_anchor(None));
+        assert!(rule("solana-test-rule").build().unwrap().applies_to_anchor(Some(&semver::Version::new(0, 26, 0))));
+
+        let mut engine = RuleEngine::default();
+        assert!(rule("solana-test-rule").register(&mut engine).is_ok());
+        assert_eq!(
+            rule("solana-test-rule").register(&mut engine),
+            Err(RuleBuildError::DuplicateId { id: "solana-test-rule".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_rule_params_overrides() {
+        let mut params = RuleParams::new();
+        params.declare("max_depth", ParamValue::Int(3), "Maximum depth");
+        params.declare("names", ParamValue::from(&["authority", "admin"][..]), "Names");
+
+        assert_eq!(params.get_int("max_depth"), Some(3));
+        assert!(params.set("test-rule", "max_depth", "5").is_ok());
+        assert!(params.set("test-rule", "names", "owner, payer").is_ok());
+        assert_eq!(params.get_int("max_depth"), Some(5));
+        assert_eq!(params.get_list("names"), Some(&["owner".to_string(), "payer".to_string()][..]));
+
+        assert_eq!(
+            params.set("test-rule", "max_depth", "deep"),
+            Err(RuleBuildError::InvalidParam {
+                id: "test-rule".to_string(),
+                param: "max_depth".to_string(),
+                value: "deep".to_string(),
+                expected: "an integer",
+            })
+        );
+        assert_eq!(
+            params.set("test-rule", "unknown", "1"),
+            Err(RuleBuildError::UnknownParam {
+                id: "test-rule".to_string(),
+                param: "unknown".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_query_set_operators() {
+        let ast = program();
+        let functions = names(AstQuery::new(&ast).functions());
+        let public = names(AstQuery::new(&ast).functions().public_functions());
+        let private = names(AstQuery::new(&ast).functions().except(AstQuery::new(&ast).functions().public_functions()));
+
+        assert!(private.contains(&"check_signer".to_string()));
+        assert!(!private.contains(&"validate_authority".to_string()));
+        assert_eq!(private.len() + public.len(), functions.len());
+        assert!(names(AstQuery::new(&ast).functions().except(AstQuery::new(&ast).functions())).is_empty());
+    }
+
+    #[test]
+    fn test_cached_queries_match_uncached() {
+        let mut ast = program();
+        ast.items.push(parse_quote! {
+            #[derive(Accounts)]
+            pub struct Withdraw<'info> {
+                pub authority: Signer<'info>,
+            }
+        });
+        let uncached_functions = names(AstQuery::new(&ast).functions());
+        let uncached_accounts = names(AstQuery::new(&ast).structs().derives_accounts());
+
+        let _scope = CacheScope::enter(&ast);
+        for _ in 0..2 {
+            assert_eq!(names(AstQuery::new(&ast).functions()), uncached_functions);
+            assert_eq!(names(AstQuery::new(&ast).structs().derives_accounts()), uncached_accounts);
+        }
+
+        let calls = std::cell::Cell::new(0);
+        for _ in 0..2 {
+            let value = cache::memoize("model", || {
+                calls.set(calls.get() + 1);
+                42
+            });
+            assert_eq!(*value, 42);
+        }
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_project_query_across_files() {
+        let state: File = parse_quote! {
+            #[account]
+            pub struct Vault {
+                pub authority: Pubkey,
+            }
+
+            #[account]
+            pub struct Unused {}
+        };
+        let instructions: File = parse_quote! {
+            #[derive(Accounts)]
+            pub struct Withdraw<'info> {
+                pub vault: Account<'info, Vault>,
+            }
+
+            pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+                Ok(())
+            }
+        };
+        let files = [
+            ProjectFile::new("src/state.rs", &state, ""),
+            ProjectFile::new("src/instructions/withdraw.rs", &instructions, ""),
+        ];
+
+        let accounts = ProjectQuery::new(&files).structs().derives_accounts();
+        let used = ProjectQuery::new(&files).structs().referenced_by(&accounts);
+        let used: Vec<(&str, String)> = used.nodes().map(|(file, node)| (file.path, node.name())).collect();
+        assert_eq!(used, vec![("src/state.rs", "Vault".to_string())]);
+
+        let state_structs = ProjectQuery::new(&files).structs().in_files(|path| path.ends_with("state.rs"));
+        let users = ProjectQuery::new(&files).structs().references(&state_structs);
+        assert_eq!(users.count(), 1);
+
+        let mut engine = RuleEngine::default();
+        RuleBuilder::new()
+            .id("solana-project-rule")
+            .title("Project Rule")
+            .description("Matches account data used by an Accounts struct")
+            .project_query(|files| {
+                let accounts = ProjectQuery::new(files).structs().derives_accounts();
+                ProjectQuery::new(files).structs().referenced_by(&accounts)
+            })
+            .register(&mut engine)
+            .unwrap();
+        assert!(engine.has_project_rules());
+        assert!(engine.execute_rules(&state, "src/state.rs", "").unwrap().is_empty());
+
+        let findings = engine.execute_project_rules(&files).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].location.file, "src/state.rs");
+    }
+
+    #[test]
+    fn test_derives_matches_the_last_path_segment() {
+        let accounts: ItemStruct = parse_quote! {
+            #[derive(Clone, anchor_lang::Accounts)]
+            pub struct Withdraw<'info> {}
+        };
+        let other: ItemStruct = parse_quote! {
+            #[derive(MyAccounts)]
+            #[doc = "Accounts"]
+            pub struct Withdraw {}
+        };
+        assert!(derives(&accounts.attrs, "Accounts"));
+        assert!(derives(&accounts.attrs, "Clone"));
+        assert!(!derives(&other.attrs, "Accounts"));
+    }
+
+    #[test]
+    fn test_program_context_resolves_sibling_files() {
+        let state: File = parse_quote! {
+            pub const MAX_WITHDRAWAL: u64 = 1_000;
+
+            #[account]
+            pub struct Vault {
+                pub authority: Pubkey,
+                pub total: u64,
+            }
+
+            #[error_code]
+            pub enum VaultError {
+                Unauthorized,
+                LimitExceeded,
+            }
+        };
+        let instructions: File = parse_quote! {
+            #[derive(Accounts)]
+            pub struct Withdraw<'info> {
+                pub vault: Account<'info, Vault>,
+                pub user: Signer<'info>,
+            }
+
+            pub fn handler(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+                Ok(())
+            }
+        };
+        let other_program: File = parse_quote! {
+            #[account]
+            pub struct Vault {}
+        };
+        let program = ProgramContext::build([
+            ("programs/vault/src/state.rs", &state),
+            ("programs/vault/src/instructions/withdraw.rs", &instructions),
+            ("programs/lending/src/state.rs", &other_program),
+        ]);
+
+        // Types used in a file resolve to their declaration in the same crate
+        let vault = program.resolve("programs/vault/src/instructions/withdraw.rs", "Vault").unwrap();
+        assert_eq!(vault.kind, DeclarationKind::State);
+        assert_eq!(vault.path, "crate::state::Vault");
+        assert_eq!(vault.file, "programs/vault/src/state.rs");
+        assert_eq!(vault.member("total").and_then(|field| field.ty.as_deref()), Some("u64"));
+        let lending = program.resolve("programs/lending/src/lib.rs", "crate::state::Vault").unwrap();
+        assert!(lending.members.is_empty());
+        assert!(program.resolve("programs/vault/src/lib.rs", "crate::Vault").is_none());
+
+        let handler = program.resolve("programs/vault/src/lib.rs", "instructions::withdraw::handler").unwrap();
+        assert_eq!(handler.kind, DeclarationKind::Instruction);
+        assert_eq!(handler.ty.as_deref(), Some("Withdraw"));
+        let limit = program.resolve("programs/vault/src/lib.rs", "MAX_WITHDRAWAL").unwrap();
+        assert_eq!(limit.value.as_deref(), Some("1_000"));
+        let errors: Vec<&str> = program
+            .of_kind(DeclarationKind::ErrorEnum)
+            .flat_map(|declaration| declaration.members.iter().map(|variant| variant.name.as_str()))
+            .collect();
+        assert_eq!(errors, ["Unauthorized", "LimitExceeded"]);
+
+        // Filters of per-file rules see the declarations of the other files
+        let mut engine = RuleEngine::default();
+        RuleBuilder::new()
+            .id("solana-unknown-account-data")
+            .title("Unknown Account Data")
+            .description("Account fields whose data type is not declared in the program")
+            .dsl_query(|ast, _file_path, _span_extractor| AstQuery::new(ast).structs().derives_accounts())
+            .filter(|node, context| {
+                let Some(accounts) = context.resolve(&node.name()) else {
+                    return false;
+                };
+                accounts
+                    .members
+                    .iter()
+                    .filter_map(|field| field.ty.as_deref()?.strip_prefix("Account<'info,")?.strip_suffix('>'))
+                    .any(|data| context.resolve(data).is_none())
+            })
+            .register(&mut engine)
+            .unwrap();
+        let path = "programs/vault/src/instructions/withdraw.rs";
+        assert!(engine.execute_rules_in_program(&instructions, path, "", &program)[0].1.is_empty());
+        assert_eq!(engine.execute_rules_per_rule(&instructions, path, "")[0].1.len(), 1);
+    }
+
+    #[test]
+    fn test_program_context_resolves_field_types() {
+        let state: File = parse_quote! {
+            pub type VaultData = state::Vault;
+
+            #[account(zero_copy)]
+            pub struct Vault {
+                /// Owner of the vault
+                #[max_len(32)]
+                pub authority: Pubkey,
+                pub config: Config,
+            }
+
+            #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+            pub struct Config {
+                pub fee_bps: u16,
+            }
+
+            pub enum Status {
+                Open,
+                Closed,
+            }
+
+            pub type Cycle = Cycle;
+
+            fn fee(amount: u64) -> u64 {
+                amount / 100
+            }
+        };
+        let instructions: File = parse_quote! {
+            #[derive(Accounts)]
+            pub struct Withdraw<'info> {
+                #[account(mut, has_one = authority)]
+                pub vault: Box<AccountLoader<'info, VaultData>>,
+                pub authority: Signer<'info>,
+            }
+        };
+        let program = ProgramContext::build([("src/state.rs", &state), ("src/instructions/withdraw.rs", &instructions)]);
+        let path = "src/instructions/withdraw.rs";
+
+        // Field types resolve to the data they wrap, through the alias
+        let accounts = program.resolve(path, "Withdraw").unwrap();
+        let field = accounts.member("vault").unwrap();
+        assert_eq!(field.attribute("account"), Some("mut,has_one=authority"));
+        let vault = program.resolve_type(path, field.ty.as_deref().unwrap()).unwrap();
+        assert_eq!(vault.path, "crate::state::Vault");
+        assert_eq!(vault.attribute("account"), Some("zero_copy"));
+        assert_eq!(vault.member("authority").unwrap().attributes, ["max_len(32)"]);
+        let config = program.resolve_type("src/state.rs", vault.member("config").unwrap().ty.as_deref().unwrap()).unwrap();
+        assert_eq!(config.kind, DeclarationKind::Struct);
+        assert_eq!(config.attribute("derive"), Some("AnchorSerialize,AnchorDeserialize,Clone"));
+        assert!(program.resolve_type(path, "Signer<'info>").is_none());
+        assert!(program.resolve_type(path, "Option<Vec<u64>>").is_none());
+        assert!(program.resolve_type(path, "Cycle").is_none());
+        assert_eq!(program.resolve_type(path, "&[Status]").map(|status| status.kind), Some(DeclarationKind::Enum));
+
+        // Private functions are indexed with their return type
+        let fee = program.resolve(path, "state::fee").unwrap();
+        assert_eq!(fee.kind, DeclarationKind::Function);
+        assert_eq!(fee.ty.as_deref(), Some("u64"));
+        assert_eq!(fee.member("amount").and_then(|param| param.ty.as_deref()), Some("u64"));
+
+        let context = NodeContext::new(path, &instructions).with_program(&program);
+        assert_eq!(context.resolve_type("Account<'info, Vault>").map(|vault| vault.line), Some(vault.line));
+    }
+
+    #[test]
+    fn test_value_analysis() {
+        let source = r#"
+const SCALE: u64 = 1_000;
+
+impl Pool {
+    const FEE_BPS: u64 = SCALE / 100;
+}
+
+pub fn swap(amount: u64, reserve: u64, fee: u8) -> Result<u64> {
+    let scaled = amount * SCALE;
+    require!(reserve > 0 && fee <= 100, PoolError::Invalid);
+    let fee_amount = scaled * (fee as u64) / Pool::FEE_BPS;
+    let out = scaled / reserve;
+    let mut steps = 3;
+    loop {
+        let step = out / steps;
+        steps -= 1;
+    }
+}
+"#;
+        let ast = syn::parse_file(source).unwrap();
+        let constants = Constants::from_items(&ast.items);
+        assert_eq!(constants.get("crate::SCALE"), Some(1_000));
+        assert_eq!(constants.get("Pool::FEE_BPS"), Some(10));
+        assert_eq!(eval_const(&syn::parse_quote!((SCALE - 1) * 2), &constants), Some(1_998));
+        assert_eq!(eval_const(&syn::parse_quote!(SCALE / 0), &constants), None);
+
+        let query = AstQuery::new(&ast).functions().with_name("swap");
+        let values = ValueAnalysis::of_node(&query.nodes()[0], &constants).unwrap();
+        let syn::Item::Fn(swap) = &ast.items[2] else {
+            panic!("swap is the third item");
+        };
+        let division = |stmt: usize| -> syn::ExprBinary {
+            let syn::Stmt::Local(local) = &swap.block.stmts[stmt] else {
+                panic!("statement {stmt} is a let binding");
+            };
+            match &*local.init.as_ref().unwrap().expr {
+                syn::Expr::Binary(binary) => binary.clone(),
+                _ => panic!("statement {stmt} is a division"),
+            }
+        };
+
+        // Divisors proven nonzero by constants and checks
+        let fee_division = division(2);
+        assert_eq!(values.eval_const(&fee_division.right), Some(10));
+        let syn::Expr::Binary(product) = &*fee_division.left else {
+            panic!("the fee is a product");
+        };
+        assert_eq!(values.range_of(&product.right), Some(ValueRange::new(0, 100)));
+        let reserve = division(3).right;
+        assert!(values.range_of(&reserve).is_some_and(|range| range.excludes_zero()));
+        let guard = values.guarded_by(&reserve).unwrap();
+        assert_eq!((guard.line, guard.check.as_str()), (10, "reserve > 0 && fee <= 100"));
+
+        // Variables assigned in a loop lose their value
+        let syn::Stmt::Expr(syn::Expr::Loop(body), _) = &swap.block.stmts[5] else {
+            panic!("the last statement is a loop");
+        };
+        let syn::Stmt::Local(step) = &body.body.stmts[0] else {
+            panic!("the loop starts with a let binding");
+        };
+        let syn::Expr::Binary(step) = &*step.init.as_ref().unwrap().expr else {
+            panic!("the step is a division");
+        };
+        assert_eq!(values.range_of(&step.right), None);
+        assert!(values.guarded_by(&step.right).is_none());
+    }
+
+    #[test]
+    fn test_taint_flows() {
+        let source = r#"
+pub fn withdraw(ctx: Context<Withdraw>, amount: u64, limit: u64) -> Result<()> {
+    let fee = amount / 100;
+    if limit > MAX_LIMIT {
+        return err!(VaultError::Limit);
+    }
+    **ctx.accounts.vault.to_account_info().lamports.borrow_mut() -= limit;
+    token::transfer(cpi_ctx, amount - fee)?;
+    require!(amount <= limit, VaultError::Amount);
+    token::burn(cpi_ctx, amount)?;
+    ctx.accounts.state.authority = ctx.accounts.new_authority.key();
+    ctx.accounts.state.admin = ctx.accounts.signer.key();
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, has_one = signer)]
+    pub state: Account<'info, State>,
+    pub new_authority: UncheckedAccount<'info>,
+    /// CHECK: checked by has_one
+    pub signer: AccountInfo<'info>,
+}
+"#;
+        let ast = syn::parse_file(source).unwrap();
+        let config = TaintConfig::new()
+            .source(TaintSource::InstructionArgs)
+            .source(TaintSource::UncheckedAccounts)
+            .sink(TaintSink::CpiAmount)
+            .sink(TaintSink::LamportWrite)
+            .sink(TaintSink::AuthorityAssignment)
+            .sanitizer(Sanitizer::Require)
+            .sanitizer(Sanitizer::Comparison)
+            .sanitizer(Sanitizer::HasOne);
+
+        // `limit` is checked before the lamports are written, `amount` only before the burn
+        let query = AstQuery::new(&ast).functions();
+        let node = &query.nodes()[0];
+        let program = ProgramContext::build([("src/lib.rs", &ast)]);
+        let context = NodeContext::new("src/lib.rs", &ast).with_program(&program);
+        let flows: Vec<(String, usize, String, usize)> = context
+            .taint_flows(node, &config)
+            .into_iter()
+            .map(|flow| (flow.source, flow.source_line, flow.sink, flow.sink_line))
+            .collect();
+        assert_eq!(
+            flows,
+            [
+                ("amount".to_string(), 2, "amount of transfer".to_string(), 8),
+                ("ctx.accounts.new_authority".to_string(), 2, "ctx.accounts.state.authority".to_string(), 11),
+            ]
+        );
+
+        // Without sanitizers every source reaching a sink flows
+        let unchecked = TaintConfig::new().source(TaintSource::InstructionArgs).sink(TaintSink::LamportWrite).sink(TaintSink::CpiAmount);
+        assert_eq!(context.taint_flows(node, &unchecked).len(), 3);
+
+        let query = AstQuery::new(&ast).functions().with_taint_flows(&config);
+        assert_eq!(query.nodes().len(), 1);
+        assert_eq!(query.nodes()[0].metadata("taint_source").map(ToString::to_string).as_deref(), Some("amount"));
+        assert!(AstQuery::new(&ast).functions().with_taint_flows(&TaintConfig::new()).nodes().is_empty());
+    }
+
+    #[test]
+    fn test_account_aliases() {
+        let source = r#"
+pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    vault.amount -= amount;
+    let info = vault.to_account_info();
+    **info.lamports.borrow_mut() -= amount;
+    let Withdraw { pool, authority: owner, .. } = &mut ctx.accounts;
+    pool.total = pool.total.checked_sub(amount).unwrap();
+    {
+        let vault = 0;
+        let (state, other) = (ctx.accounts.state.load_mut()?, amount);
+        state.counter += 1;
+        other.add_lamports(1);
+    }
+    let accounts = &mut ctx.accounts;
+    accounts.config.set_inner(Config::default());
+    vault.realloc(64, false)?;
+    owner.key();
+    Ok(())
+}
+"#;
+        let ast = syn::parse_file(source).unwrap();
+        let query = AstQuery::new(&ast).functions();
+        let node = &query.nodes()[0];
+        let aliases = NodeContext::new("src/lib.rs", &ast).aliases(node).unwrap();
+        let writes: Vec<(&str, &[usize])> = aliases.writes().iter().map(|(account, lines)| (account.as_str(), lines.as_slice())).collect();
+        assert_eq!(
+            writes,
+            [("config", &[16][..]), ("pool", &[8]), ("state", &[12]), ("vault", &[4, 6, 17])]
+        );
+
+        // Expressions resolve through the aliases in scope
+        let syn::Item::Fn(withdraw) = &ast.items[0] else {
+            panic!("withdraw is the first item");
+        };
+        let syn::Stmt::Expr(syn::Expr::Binary(decrement), _) = &withdraw.block.stmts[1] else {
+            panic!("the second statement is a compound assignment");
+        };
+        assert_eq!(aliases.resolve(&decrement.left).as_deref(), Some("ctx.accounts.vault.amount"));
+        assert_eq!(aliases.account_of(&decrement.left).as_deref(), Some("vault"));
+        assert_eq!(aliases.resolve(&decrement.right), None);
+        let position = |line: usize| proc_macro2::LineColumn { line, column: 8 };
+        assert_eq!(aliases.alias_target("owner", position(18)), Some("ctx.accounts.authority"));
+        assert_eq!(aliases.alias_target("vault", position(11)), None);
+        assert_eq!(aliases.alias_target("vault", position(17)), Some("ctx.accounts.vault"));
+
+        // Other roots, such as `self` in the methods of an accounts struct
+        let block: syn::Block = syn::parse_str("{\n    let vault = &mut self.vault;\n    vault.amount = 0;\n}").unwrap();
+        let aliases = AccountAliases::of_block(["self"], &block);
+        assert_eq!(aliases.writes().keys().collect::<Vec<_>>(), ["vault"]);
+    }
+}
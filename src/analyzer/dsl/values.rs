@@ -0,0 +1,917 @@
+use log::trace;
+use proc_macro2::LineColumn;
+use quote::ToTokens;
+use std::collections::{BTreeMap, BTreeSet};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{BinOp, Block, Expr, Item, Stmt, UnOp};
+
+use crate::analyzer::dsl::program::{DeclarationKind, ProgramContext};
+use crate::analyzer::dsl::query::{AstNode, NodeData};
+
+/// Maximum number of passes evaluating constants defined from other constants
+const MAX_CONSTANT_PASSES: usize = 4;
+
+/// Range of values an integer expression can take, bounds included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValueRange {
+    /// Lowest value
+    pub min: i128,
+    /// Highest value
+    pub max: i128,
+    /// Whether zero is excluded although it is within the bounds (`x != 0` on a signed value, or
+    /// a non-zero float)
+    pub nonzero: bool,
+}
+
+impl ValueRange {
+    /// Range of a value nothing is known about
+    pub const UNKNOWN: Self = Self {
+        min: i128::MIN,
+        max: i128::MAX,
+        nonzero: false,
+    };
+
+    /// Creates a range from its bounds
+    pub fn new(min: i128, max: i128) -> Self {
+        Self { min, max, nonzero: false }
+    }
+
+    /// Creates the range of a single value
+    pub fn constant(value: i128) -> Self {
+        Self::new(value, value)
+    }
+
+    /// Returns the range of an integer type (`u64`, `i32`, `usize`...), `None` for other types
+    pub fn of_type(name: &str) -> Option<Self> {
+        let range = match name {
+            "u8" => Self::new(0, u8::MAX.into()),
+            "u16" => Self::new(0, u16::MAX.into()),
+            "u32" => Self::new(0, u32::MAX.into()),
+            "u64" | "usize" => Self::new(0, u64::MAX.into()),
+            "u128" => Self::new(0, i128::MAX),
+            "i8" => Self::new(i8::MIN.into(), i8::MAX.into()),
+            "i16" => Self::new(i16::MIN.into(), i16::MAX.into()),
+            "i32" => Self::new(i32::MIN.into(), i32::MAX.into()),
+            "i64" | "isize" => Self::new(i64::MIN.into(), i64::MAX.into()),
+            "i128" => Self::UNKNOWN,
+            _ => return None,
+        };
+        Some(range)
+    }
+
+    /// Returns the value of a range holding a single value
+    pub fn as_constant(&self) -> Option<i128> {
+        (self.min == self.max).then_some(self.min)
+    }
+
+    /// Check if the value cannot be zero
+    pub fn excludes_zero(&self) -> bool {
+        self.nonzero || self.min > 0 || self.max < 0
+    }
+
+    /// Check if the range contains a value
+    pub fn contains(&self, value: i128) -> bool {
+        self.min <= value && value <= self.max && !(value == 0 && self.nonzero)
+    }
+
+    /// Helper function to keep the values of both ranges (the other range if none is left, in
+    /// code that cannot run)
+    fn intersect(self, other: Self) -> Self {
+        let range = Self {
+            min: self.min.max(other.min),
+            max: self.max.min(other.max),
+            nonzero: self.nonzero || other.nonzero,
+        };
+        if range.min > range.max { other } else { range.normalized() }
+    }
+
+    /// Helper function to move a bound off zero when zero is excluded
+    fn normalized(mut self) -> Self {
+        if self.nonzero && self.min == 0 && self.max > 0 {
+            self.min = 1;
+        } else if self.nonzero && self.max == 0 && self.min < 0 {
+            self.max = -1;
+        }
+        self
+    }
+
+    /// Helper function to build the range of the results of an operation on the bounds
+    fn from_corners(corners: [i128; 4]) -> Self {
+        let min = corners.iter().copied().min().unwrap_or(i128::MIN);
+        let max = corners.iter().copied().max().unwrap_or(i128::MAX);
+        Self::new(min, max)
+    }
+
+    /// Helper function to get the range of an arithmetic operation, `None` if nothing is known
+    fn apply(self, op: &BinOp, other: Self) -> Option<Self> {
+        let range = match op {
+            BinOp::Add(_) | BinOp::AddAssign(_) => Self::new(self.min.saturating_add(other.min), self.max.saturating_add(other.max)),
+            BinOp::Sub(_) | BinOp::SubAssign(_) => Self::new(self.min.saturating_sub(other.max), self.max.saturating_sub(other.min)),
+            BinOp::Mul(_) | BinOp::MulAssign(_) => Self::from_corners([
+                self.min.saturating_mul(other.min),
+                self.min.saturating_mul(other.max),
+                self.max.saturating_mul(other.min),
+                self.max.saturating_mul(other.max),
+            ]),
+            BinOp::Div(_) | BinOp::DivAssign(_) if other.min > 0 => {
+                Self::from_corners([self.min / other.min, self.min / other.max, self.max / other.min, self.max / other.max])
+            }
+            BinOp::Rem(_) | BinOp::RemAssign(_) if other.min > 0 && self.min >= 0 => Self::new(0, self.max.min(other.max - 1)),
+            _ => return None,
+        };
+        Some(range)
+    }
+}
+
+/// Check proving a fact about a value (`require!(shares > 0, ..)`, `if shares == 0 { return .. }`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Guard {
+    /// Line of the check
+    pub line: usize,
+    /// Checked condition, as written (`shares > 0`)
+    pub check: String,
+}
+
+/// What is known about a variable or field at some point of a function
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Fact {
+    range: ValueRange,
+    /// Check the range comes from, if any
+    guard: Option<Guard>,
+}
+
+/// Facts by variable or field (`amount`, `ctx.accounts.vault.total`)
+type Facts = BTreeMap<String, Fact>;
+
+/// Integer constants of a file or a program, by name
+///
+/// Constants of `impl` blocks are keyed by their type (`Vault::LEN`). Constants whose
+/// name is declared with several values are left out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Constants {
+    values: BTreeMap<String, i128>,
+    ambiguous: BTreeSet<String>,
+}
+
+impl Constants {
+    /// Collect the constants of a list of items, including inline modules and `impl` blocks
+    pub fn from_items(items: &[Item]) -> Self {
+        let mut pending = Vec::new();
+        collect_constants(items, None, &mut pending);
+        let mut constants = Self::default();
+        constants.evaluate(pending);
+        constants
+    }
+
+    /// Collect the constants indexed by the program context
+    pub fn from_program(program: &ProgramContext) -> Self {
+        let pending = program
+            .of_kind(DeclarationKind::Constant)
+            .filter_map(|declaration| Some((declaration.name.clone(), syn::parse_str::<Expr>(declaration.value.as_deref()?).ok()?)))
+            .collect();
+        let mut constants = Self::default();
+        constants.evaluate(pending);
+        constants
+    }
+
+    /// Add the constants of another set
+    pub fn extend(&mut self, other: &Constants) {
+        for (name, value) in &other.values {
+            self.insert(name, *value);
+        }
+        self.ambiguous.extend(other.ambiguous.iter().cloned());
+        for name in &self.ambiguous {
+            self.values.remove(name);
+        }
+    }
+
+    /// Returns the value of a constant, by path (`FEE_BPS`, `Vault::LEN`, `crate::constants::FEE_BPS`)
+    pub fn get(&self, path: &str) -> Option<i128> {
+        let segments: Vec<&str> = path.split("::").map(str::trim).collect();
+        let last_two = segments.len().checked_sub(2).map(|start| segments[start..].join("::"));
+        last_two
+            .and_then(|name| self.values.get(&name))
+            .or_else(|| self.values.get(*segments.last()?))
+            .copied()
+    }
+
+    /// Helper function to evaluate constants, each pass resolving the ones using the previous ones
+    fn evaluate(&mut self, mut pending: Vec<(String, Expr)>) {
+        for _ in 0..MAX_CONSTANT_PASSES {
+            let before = pending.len();
+            pending.retain(|(name, expr)| match eval_const(expr, self) {
+                Some(value) => {
+                    self.insert(name, value);
+                    false
+                }
+                None => true,
+            });
+            if pending.is_empty() || pending.len() == before {
+                break;
+            }
+        }
+    }
+
+    /// Helper function to add a constant, leaving out names declared with different values
+    fn insert(&mut self, name: &str, value: i128) {
+        if self.ambiguous.contains(name) {
+            return;
+        }
+        match self.values.insert(name.to_string(), value) {
+            Some(previous) if previous != value => {
+                self.values.remove(name);
+                self.ambiguous.insert(name.to_string());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Helper function to collect the constant items of a list of items
+fn collect_constants(items: &[Item], owner: Option<&str>, pending: &mut Vec<(String, Expr)>) {
+    for item in items {
+        match item {
+            Item::Const(item_const) => {
+                let name = item_const.ident.to_string();
+                let name = owner.map_or(name.clone(), |owner| format!("{owner}::{name}"));
+                pending.push((name, (*item_const.expr).clone()));
+            }
+            Item::Mod(item_mod) => {
+                if let Some((_, items)) = &item_mod.content {
+                    collect_constants(items, None, pending);
+                }
+            }
+            Item::Impl(item_impl) => {
+                let owner = match &*item_impl.self_ty {
+                    syn::Type::Path(type_path) => type_path.path.segments.last().map(|segment| segment.ident.to_string()),
+                    _ => None,
+                };
+                for impl_item in &item_impl.items {
+                    if let (syn::ImplItem::Const(item_const), Some(owner)) = (impl_item, &owner) {
+                        pending.push((format!("{owner}::{}", item_const.ident), item_const.expr.clone()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Evaluate a constant expression (literals, constants and arithmetic on them)
+pub fn eval_const(expr: &Expr, constants: &Constants) -> Option<i128> {
+    evaluate(expr, &Facts::new(), constants)?.as_constant()
+}
+
+/// Constants and value ranges of the variables of a function, at each of its statements
+///
+/// Values are propagated through `let` bindings, assignments and arithmetic, and narrowed by the
+/// checks the function makes: `require!`, `require_gt!` and similar Anchor macros, `assert!`,
+/// conditions of `if` and `while` blocks, and `if` blocks returning early
+/// (`if shares == 0 { return err!(..) }`). Variables assigned in a loop or through `&mut` lose
+/// what is known about them, and facts found in a block do not outlive it.
+#[derive(Debug, Clone, Default)]
+pub struct ValueAnalysis {
+    /// Facts at the start of each statement (and after each nested block), in source order
+    snapshots: Vec<(LineColumn, Facts)>,
+    constants: Constants,
+}
+
+impl ValueAnalysis {
+    /// Analyze a block
+    pub fn of_block(block: &Block, constants: &Constants) -> Self {
+        Self::analyze(block, Facts::new(), constants)
+    }
+
+    /// Analyze a function, its integer parameters starting with the range of their type
+    pub fn of_function(sig: &syn::Signature, block: &Block, constants: &Constants) -> Self {
+        let mut facts = Facts::new();
+        for input in &sig.inputs {
+            if let syn::FnArg::Typed(pat_type) = input
+                && let syn::Pat::Ident(pat_ident) = &*pat_type.pat
+                && let Some(range) = type_range(&pat_type.ty)
+            {
+                facts.insert(pat_ident.ident.to_string(), Fact { range, guard: None });
+            }
+        }
+        Self::analyze(block, facts, constants)
+    }
+
+    /// Analyze a function node, `None` for other nodes
+    pub fn of_node(node: &AstNode<'_>, constants: &Constants) -> Option<Self> {
+        match &node.data {
+            NodeData::Function(func) => Some(Self::of_function(&func.sig, &func.block, constants)),
+            NodeData::ImplFunction(func) => Some(Self::of_function(&func.sig, &func.block, constants)),
+            _ => None,
+        }
+    }
+
+    /// Returns the range of values of an expression of the analyzed code, `None` if nothing is known
+    pub fn range_of(&self, expr: &Expr) -> Option<ValueRange> {
+        evaluate(expr, self.facts_at(expr.span().start())?, &self.constants)
+    }
+
+    /// Returns the value of an expression of the analyzed code when it is always the same
+    pub fn eval_const(&self, expr: &Expr) -> Option<i128> {
+        self.range_of(expr)?.as_constant()
+    }
+
+    /// Returns the check narrowing the values of a variable or field of the analyzed code
+    /// (`require!(shares > 0)` for `shares`), if any
+    pub fn guarded_by(&self, expr: &Expr) -> Option<&Guard> {
+        let key = key(expr)?;
+        self.facts_at(expr.span().start())?.get(&key)?.guard.as_ref()
+    }
+
+    /// Returns the constants the analysis uses
+    pub fn constants(&self) -> &Constants {
+        &self.constants
+    }
+
+    /// Helper function to walk a block from the given facts
+    fn analyze(block: &Block, facts: Facts, constants: &Constants) -> Self {
+        let mut walker = Walker {
+            constants,
+            facts,
+            snapshots: Vec::new(),
+        };
+        walker.visit_block(block);
+        let mut snapshots = walker.snapshots;
+        snapshots.sort_by_key(|(position, _)| *position);
+        trace!("Value analysis with {} snapshots", snapshots.len());
+        Self {
+            snapshots,
+            constants: constants.clone(),
+        }
+    }
+
+    /// Helper function to get the facts holding at a position of the analyzed code
+    fn facts_at(&self, position: LineColumn) -> Option<&Facts> {
+        let index = self.snapshots.partition_point(|(start, _)| *start <= position);
+        self.snapshots.get(index.checked_sub(1)?).map(|(_, facts)| facts)
+    }
+}
+
+/// Helper to walk the statements of a function in order, recording the facts at each of them
+struct Walker<'c> {
+    constants: &'c Constants,
+    facts: Facts,
+    snapshots: Vec<(LineColumn, Facts)>,
+}
+
+impl Walker<'_> {
+    fn snapshot(&mut self, position: LineColumn) {
+        self.snapshots.push((position, self.facts.clone()));
+    }
+
+    /// Forget what is known about variables and fields (and the fields of those)
+    fn kill(&mut self, keys: &BTreeSet<String>) {
+        self.facts.retain(|existing, _| {
+            !keys
+                .iter()
+                .any(|key| existing == key || existing.strip_prefix(key.as_str()).is_some_and(|rest| rest.starts_with('.')))
+        });
+    }
+
+    /// Restore the facts after a nested block, forgetting what the block assigned
+    fn leave(&mut self, saved: Facts, region: &dyn Mutates, end: LineColumn) {
+        self.facts = saved;
+        self.kill(&region.mutated());
+        self.snapshot(end);
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        self.snapshot(stmt.span().start());
+        visit::visit_stmt(self, stmt);
+        // `x += n` is computed from the value of `x` before it is forgotten
+        let compound = match stmt {
+            Stmt::Expr(Expr::Binary(binary), _) if is_compound_assignment(&binary.op) => key(&binary.left).zip(
+                evaluate(&binary.left, &self.facts, self.constants)
+                    .zip(evaluate(&binary.right, &self.facts, self.constants))
+                    .and_then(|(left, right)| left.apply(&binary.op, right)),
+            ),
+            _ => None,
+        };
+        self.kill(&stmt.mutated());
+        if let Some((key, range)) = compound {
+            self.facts.insert(key, Fact { range, guard: None });
+        }
+        match stmt {
+            Stmt::Local(local) => self.bind(local),
+            Stmt::Expr(expr, _) => self.effects(expr),
+            Stmt::Macro(stmt_macro) => self.check_macro(&stmt_macro.mac),
+            Stmt::Item(_) => {}
+        }
+    }
+
+    /// Record the value of a `let` binding
+    fn bind(&mut self, local: &syn::Local) {
+        let (pat, ty) = match &local.pat {
+            syn::Pat::Type(pat_type) => (&*pat_type.pat, Some(&*pat_type.ty)),
+            pat => (pat, None),
+        };
+        self.kill(&pattern_names(&local.pat));
+        let syn::Pat::Ident(pat_ident) = pat else {
+            return;
+        };
+        let fact = local.init.as_ref().and_then(|init| self.fact_of(&init.expr));
+        let fact = match (fact, ty.and_then(type_range)) {
+            (Some(fact), Some(range)) => Some(Fact {
+                range: fact.range.intersect(range),
+                guard: fact.guard,
+            }),
+            (Some(fact), None) => Some(fact),
+            (None, Some(range)) => Some(Fact { range, guard: None }),
+            (None, None) => None,
+        };
+        if let Some(fact) = fact {
+            self.facts.insert(pat_ident.ident.to_string(), fact);
+        }
+    }
+
+    /// Record the effects of an expression statement (assignment, check)
+    fn effects(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Assign(assign) => {
+                if let Some(key) = key(&assign.left)
+                    && let Some(fact) = self.fact_of(&assign.right)
+                {
+                    self.facts.insert(key, fact);
+                }
+            }
+            Expr::Macro(expr_macro) => self.check_macro(&expr_macro.mac),
+            Expr::If(expr_if) => {
+                let guard = Guard {
+                    line: expr_if.if_token.span.start().line,
+                    check: compact(&expr_if.cond),
+                };
+                let else_diverges = match &expr_if.else_branch {
+                    Some((_, else_branch)) => matches!(&**else_branch, Expr::Block(block) if diverges(&block.block)),
+                    None => false,
+                };
+                if expr_if.else_branch.is_none() && diverges(&expr_if.then_branch) {
+                    self.refine(&expr_if.cond, false, &guard);
+                } else if else_diverges && !diverges(&expr_if.then_branch) {
+                    self.refine(&expr_if.cond, true, &guard);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Record the facts proven by a `require!`-like macro
+    fn check_macro(&mut self, mac: &syn::Macro) {
+        let Some(name) = mac.path.segments.last().map(|segment| segment.ident.to_string()) else {
+            return;
+        };
+        let Ok(args) = mac.parse_body_with(Punctuated::<Expr, syn::Token![,]>::parse_terminated) else {
+            return;
+        };
+        let args: Vec<&Expr> = args.iter().collect();
+        let line = mac.path.span().start().line;
+        let comparison = match name.as_str() {
+            "require" | "assert" => {
+                if let Some(cond) = args.first() {
+                    let guard = Guard { line, check: compact(cond) };
+                    self.refine(cond, true, &guard);
+                }
+                return;
+            }
+            "require_gt" => Comparison::Gt,
+            "require_gte" => Comparison::Ge,
+            "require_neq" | "assert_ne" => Comparison::Ne,
+            "require_eq" | "assert_eq" => Comparison::Eq,
+            _ => return,
+        };
+        if let [left, right, ..] = args.as_slice() {
+            let guard = Guard {
+                line,
+                check: format!("{} {} {}", compact(left), comparison.symbol(), compact(right)),
+            };
+            self.compare(left, comparison, right, &guard);
+        }
+    }
+
+    /// Narrow the values of the variables of a condition, knowing whether it holds
+    fn refine(&mut self, cond: &Expr, holds: bool, guard: &Guard) {
+        match cond {
+            Expr::Paren(paren) => self.refine(&paren.expr, holds, guard),
+            Expr::Group(group) => self.refine(&group.expr, holds, guard),
+            Expr::Unary(unary) if matches!(unary.op, UnOp::Not(_)) => self.refine(&unary.expr, !holds, guard),
+            Expr::Binary(binary) => match (&binary.op, holds) {
+                (BinOp::And(_), true) | (BinOp::Or(_), false) => {
+                    self.refine(&binary.left, holds, guard);
+                    self.refine(&binary.right, holds, guard);
+                }
+                (op, _) => {
+                    if let Some(comparison) = Comparison::of(op) {
+                        let comparison = if holds { comparison } else { comparison.negated() };
+                        self.compare(&binary.left, comparison, &binary.right, guard);
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Narrow the values of both sides of a comparison that holds
+    fn compare(&mut self, left: &Expr, comparison: Comparison, right: &Expr, guard: &Guard) {
+        let left_range = evaluate(left, &self.facts, self.constants);
+        let right_range = evaluate(right, &self.facts, self.constants);
+        if let (Some(key), Some(other)) = (key(left), right_range) {
+            self.narrow(key, comparison, other, guard);
+        }
+        if let (Some(key), Some(other)) = (key(right), left_range) {
+            self.narrow(key, comparison.flipped(), other, guard);
+        }
+    }
+
+    /// Narrow the values of a variable compared to a range of values
+    fn narrow(&mut self, key: String, comparison: Comparison, other: ValueRange, guard: &Guard) {
+        let current = self.facts.get(&key).map_or(ValueRange::UNKNOWN, |fact| fact.range);
+        let bound = match comparison {
+            Comparison::Gt => ValueRange::new(other.min.saturating_add(1), i128::MAX),
+            Comparison::Ge => ValueRange::new(other.min, i128::MAX),
+            Comparison::Lt => ValueRange::new(i128::MIN, other.max.saturating_sub(1)),
+            Comparison::Le => ValueRange::new(i128::MIN, other.max),
+            Comparison::Eq => other,
+            Comparison::Ne if other.as_constant() == Some(0) => ValueRange { nonzero: true, ..ValueRange::UNKNOWN },
+            Comparison::Ne => return,
+        };
+        let range = current.intersect(bound);
+        if range != current {
+            self.facts.insert(
+                key,
+                Fact {
+                    range,
+                    guard: Some(guard.clone()),
+                },
+            );
+        }
+    }
+
+    /// Returns what is known about the value of an expression
+    fn fact_of(&self, expr: &Expr) -> Option<Fact> {
+        let range = evaluate(expr, &self.facts, self.constants)?;
+        let guard = key(expr).and_then(|key| self.facts.get(&key)?.guard.clone());
+        Some(Fact { range, guard })
+    }
+}
+
+impl<'ast> Visit<'ast> for Walker<'_> {
+    fn visit_block(&mut self, block: &'ast Block) {
+        let saved = self.facts.clone();
+        for stmt in &block.stmts {
+            self.stmt(stmt);
+        }
+        self.leave(saved, block, block.span().end());
+    }
+
+    fn visit_expr_if(&mut self, expr_if: &'ast syn::ExprIf) {
+        self.visit_expr(&expr_if.cond);
+        let saved = self.facts.clone();
+        let guard = Guard {
+            line: expr_if.if_token.span.start().line,
+            check: compact(&expr_if.cond),
+        };
+        self.refine(&expr_if.cond, true, &guard);
+        self.visit_block(&expr_if.then_branch);
+        if let Some((_, else_branch)) = &expr_if.else_branch {
+            self.facts = saved.clone();
+            self.refine(&expr_if.cond, false, &guard);
+            self.visit_expr(else_branch);
+        }
+        self.leave(saved, expr_if, expr_if.span().end());
+    }
+
+    fn visit_expr_while(&mut self, expr_while: &'ast syn::ExprWhile) {
+        let saved = self.facts.clone();
+        self.kill(&expr_while.mutated());
+        let guard = Guard {
+            line: expr_while.while_token.span.start().line,
+            check: compact(&expr_while.cond),
+        };
+        self.refine(&expr_while.cond, true, &guard);
+        self.visit_block(&expr_while.body);
+        self.leave(saved, expr_while, expr_while.span().end());
+    }
+
+    fn visit_expr_for_loop(&mut self, expr_for: &'ast syn::ExprForLoop) {
+        self.visit_expr(&expr_for.expr);
+        let saved = self.facts.clone();
+        self.kill(&expr_for.mutated());
+        self.kill(&pattern_names(&expr_for.pat));
+        self.visit_block(&expr_for.body);
+        self.leave(saved, expr_for, expr_for.span().end());
+    }
+
+    fn visit_expr_loop(&mut self, expr_loop: &'ast syn::ExprLoop) {
+        let saved = self.facts.clone();
+        self.kill(&expr_loop.mutated());
+        self.visit_block(&expr_loop.body);
+        self.leave(saved, expr_loop, expr_loop.span().end());
+    }
+
+    fn visit_expr_closure(&mut self, closure: &'ast syn::ExprClosure) {
+        let saved = self.facts.clone();
+        self.kill(&closure.mutated());
+        for input in &closure.inputs {
+            self.kill(&pattern_names(input));
+        }
+        self.snapshot(closure.body.span().start());
+        self.visit_expr(&closure.body);
+        self.leave(saved, closure, closure.span().end());
+    }
+
+    fn visit_expr_match(&mut self, expr_match: &'ast syn::ExprMatch) {
+        self.visit_expr(&expr_match.expr);
+        let saved = self.facts.clone();
+        for arm in &expr_match.arms {
+            self.facts = saved.clone();
+            self.kill(&pattern_names(&arm.pat));
+            if let Some((_, guard_expr)) = &arm.guard {
+                let guard = Guard {
+                    line: guard_expr.span().start().line,
+                    check: compact(guard_expr),
+                };
+                self.refine(guard_expr, true, &guard);
+            }
+            self.snapshot(arm.body.span().start());
+            self.visit_expr(&arm.body);
+        }
+        self.leave(saved, expr_match, expr_match.span().end());
+    }
+}
+
+/// Comparison operator of a check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn of(op: &BinOp) -> Option<Self> {
+        let comparison = match op {
+            BinOp::Eq(_) => Self::Eq,
+            BinOp::Ne(_) => Self::Ne,
+            BinOp::Lt(_) => Self::Lt,
+            BinOp::Le(_) => Self::Le,
+            BinOp::Gt(_) => Self::Gt,
+            BinOp::Ge(_) => Self::Ge,
+            _ => return None,
+        };
+        Some(comparison)
+    }
+
+    /// Comparison holding when this one does not (`<=` for `>`)
+    fn negated(self) -> Self {
+        match self {
+            Self::Eq => Self::Ne,
+            Self::Ne => Self::Eq,
+            Self::Lt => Self::Ge,
+            Self::Le => Self::Gt,
+            Self::Gt => Self::Le,
+            Self::Ge => Self::Lt,
+        }
+    }
+
+    /// Comparison with the sides swapped (`<` for `>`)
+    fn flipped(self) -> Self {
+        match self {
+            Self::Lt => Self::Gt,
+            Self::Le => Self::Ge,
+            Self::Gt => Self::Lt,
+            Self::Ge => Self::Le,
+            other => other,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        }
+    }
+}
+
+/// Syntax whose variables and fields can be assigned
+trait Mutates {
+    /// Returns the variables and fields assigned or borrowed mutably
+    fn mutated(&self) -> BTreeSet<String>;
+}
+
+macro_rules! impl_mutates {
+    ($($ty:ty => $visit:ident),* $(,)?) => {
+        $(impl Mutates for $ty {
+            fn mutated(&self) -> BTreeSet<String> {
+                let mut finder = MutationFinder::default();
+                finder.$visit(self);
+                finder.keys
+            }
+        })*
+    };
+}
+
+impl_mutates! {
+    Stmt => visit_stmt,
+    Block => visit_block,
+    syn::ExprIf => visit_expr_if,
+    syn::ExprWhile => visit_expr_while,
+    syn::ExprForLoop => visit_expr_for_loop,
+    syn::ExprLoop => visit_expr_loop,
+    syn::ExprClosure => visit_expr_closure,
+    syn::ExprMatch => visit_expr_match,
+}
+
+/// Helper visitor to find the assigned and mutably borrowed variables and fields
+#[derive(Default)]
+struct MutationFinder {
+    keys: BTreeSet<String>,
+}
+
+impl<'ast> Visit<'ast> for MutationFinder {
+    fn visit_expr_assign(&mut self, assign: &'ast syn::ExprAssign) {
+        self.keys.extend(key(&assign.left));
+        visit::visit_expr_assign(self, assign);
+    }
+
+    fn visit_expr_binary(&mut self, binary: &'ast syn::ExprBinary) {
+        if is_compound_assignment(&binary.op) {
+            self.keys.extend(key(&binary.left));
+        }
+        visit::visit_expr_binary(self, binary);
+    }
+
+    fn visit_expr_reference(&mut self, reference: &'ast syn::ExprReference) {
+        if reference.mutability.is_some() {
+            self.keys.extend(key(&reference.expr));
+        }
+        visit::visit_expr_reference(self, reference);
+    }
+}
+
+/// Helper function to evaluate the range of an expression from the known facts
+fn evaluate(expr: &Expr, facts: &Facts, constants: &Constants) -> Option<ValueRange> {
+    match expr {
+        Expr::Lit(expr_lit) => match &expr_lit.lit {
+            syn::Lit::Int(int) => int.base10_parse::<i128>().ok().map(ValueRange::constant),
+            syn::Lit::Float(float) => {
+                let value = float.base10_parse::<f64>().ok()?;
+                // Only whether a float is zero is tracked
+                Some(if value == 0.0 {
+                    ValueRange::constant(0)
+                } else {
+                    ValueRange { nonzero: true, ..ValueRange::UNKNOWN }
+                })
+            }
+            _ => None,
+        },
+        Expr::Path(_) | Expr::Field(_) => {
+            let key = key(expr)?;
+            facts.get(&key).map(|fact| fact.range).or_else(|| constants.get(&key).map(ValueRange::constant))
+        }
+        Expr::Paren(paren) => evaluate(&paren.expr, facts, constants),
+        Expr::Group(group) => evaluate(&group.expr, facts, constants),
+        Expr::Unary(unary) => match unary.op {
+            UnOp::Neg(_) => {
+                let range = evaluate(&unary.expr, facts, constants)?;
+                Some(ValueRange {
+                    min: range.max.saturating_neg(),
+                    max: range.min.saturating_neg(),
+                    nonzero: range.nonzero,
+                })
+            }
+            UnOp::Deref(_) => evaluate(&unary.expr, facts, constants),
+            _ => None,
+        },
+        Expr::Reference(reference) => evaluate(&reference.expr, facts, constants),
+        Expr::Binary(binary) => {
+            let left = evaluate(&binary.left, facts, constants)?;
+            let right = evaluate(&binary.right, facts, constants)?;
+            left.apply(&binary.op, right)
+        }
+        Expr::Cast(cast) => {
+            let range = evaluate(&cast.expr, facts, constants);
+            match (range, type_range(&cast.ty)) {
+                // Values that fit the target type are unchanged, others wrap
+                (Some(range), Some(target)) if target.min <= range.min && range.max <= target.max => Some(range),
+                (_, target) => target,
+            }
+        }
+        Expr::MethodCall(call) => {
+            let receiver = evaluate(&call.receiver, facts, constants)?;
+            let argument = call.args.first().and_then(|arg| evaluate(arg, facts, constants));
+            match (call.method.to_string().as_str(), argument) {
+                ("max", Some(other)) => Some(ValueRange::new(receiver.min.max(other.min), receiver.max.max(other.max))),
+                ("min", Some(other)) => Some(ValueRange::new(receiver.min.min(other.min), receiver.max.min(other.max))),
+                ("saturating_add", Some(other)) => receiver.apply(&BinOp::Add(Default::default()), other),
+                ("saturating_sub", Some(other)) if receiver.min >= 0 && other.min >= 0 => {
+                    Some(ValueRange::new((receiver.min - other.max).max(0), (receiver.max - other.min).max(0)))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Helper function to get the name facts are recorded under (`amount`, `ctx.accounts.vault.total`)
+pub(super) fn key(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Path(expr_path) if expr_path.qself.is_none() => Some(expr_path.path.to_token_stream().to_string().replace(' ', "")),
+        Expr::Field(field) => {
+            let member = match &field.member {
+                syn::Member::Named(ident) => ident.to_string(),
+                syn::Member::Unnamed(index) => index.index.to_string(),
+            };
+            Some(format!("{}.{member}", key(&field.base)?))
+        }
+        Expr::Paren(paren) => key(&paren.expr),
+        Expr::Group(group) => key(&group.expr),
+        Expr::Reference(reference) => key(&reference.expr),
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Deref(_)) => key(&unary.expr),
+        _ => None,
+    }
+}
+
+/// Helper function to get the range of an integer type
+fn type_range(ty: &syn::Type) -> Option<ValueRange> {
+    match ty {
+        syn::Type::Path(type_path) => ValueRange::of_type(&type_path.path.get_ident()?.to_string()),
+        syn::Type::Reference(reference) => type_range(&reference.elem),
+        _ => None,
+    }
+}
+
+/// Helper function to check if an operator assigns its result (`+=`, `/=`...)
+fn is_compound_assignment(op: &BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::AddAssign(_)
+            | BinOp::SubAssign(_)
+            | BinOp::MulAssign(_)
+            | BinOp::DivAssign(_)
+            | BinOp::RemAssign(_)
+            | BinOp::BitXorAssign(_)
+            | BinOp::BitAndAssign(_)
+            | BinOp::BitOrAssign(_)
+            | BinOp::ShlAssign(_)
+            | BinOp::ShrAssign(_)
+    )
+}
+
+/// Helper function to check if a block never completes (returns, fails with `?` or panics)
+pub(super) fn diverges(block: &Block) -> bool {
+    block.stmts.iter().any(|stmt| match stmt {
+        Stmt::Expr(Expr::Return(_), _) => true,
+        Stmt::Expr(Expr::Try(expr_try), _) => matches!(&*expr_try.expr, Expr::Call(call) if key(&call.func).as_deref() == Some("Err")),
+        Stmt::Expr(Expr::Macro(expr_macro), _) => is_panic(&expr_macro.mac),
+        Stmt::Macro(stmt_macro) => is_panic(&stmt_macro.mac),
+        _ => false,
+    })
+}
+
+/// Helper function to check if a macro panics
+fn is_panic(mac: &syn::Macro) -> bool {
+    mac.path.segments.last().is_some_and(|segment| matches!(segment.ident.to_string().as_str(), "panic" | "unreachable"))
+}
+
+/// Helper function to get the variables bound by a pattern
+pub(super) fn pattern_names(pat: &syn::Pat) -> BTreeSet<String> {
+    #[derive(Default)]
+    struct Names(BTreeSet<String>);
+    impl<'ast> Visit<'ast> for Names {
+        fn visit_pat_ident(&mut self, pat_ident: &'ast syn::PatIdent) {
+            self.0.insert(pat_ident.ident.to_string());
+            visit::visit_pat_ident(self, pat_ident);
+        }
+    }
+    let mut names = Names::default();
+    names.visit_pat(pat);
+    names.0
+}
+
+/// Helper function to write an expression (or any tokens) without the spaces the token printer adds
+/// (`ctx.accounts.vault.total > 0`)
+pub(crate) fn compact(tokens: &impl ToTokens) -> String {
+    tokens
+        .to_token_stream()
+        .to_string()
+        .replace(" . ", ".")
+        .replace(" :: ", "::")
+        .replace(" (", "(")
+        .replace("( ", "(")
+        .replace(" )", ")")
+        .replace(" ,", ",")
+        .replace(" ?", "?")
+}
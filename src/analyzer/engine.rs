@@ -1,11 +1,23 @@
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::panic::{AssertUnwindSafe, catch_unwind};
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use log::{debug, info, warn};
+use semver::{Version, VersionReq};
+use serde::{Serialize, Serializer};
 use syn::File;
 
-use crate::analyzer::{Finding, Severity};
+use crate::analyzer::dsl::builders::RuleBuildError;
+use crate::analyzer::dsl::params::RuleParams;
+use crate::analyzer::dsl::{ProgramContext, ProjectFile};
+use crate::analyzer::registry::RuleRegistry;
+use crate::analyzer::rules::{BuiltinRules, RulePlugin};
+use crate::analyzer::vfs::SharedFileSystem;
+use crate::analyzer::{Category, Finding, RuleInfo, Severity};
 
 /// Type of rule
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -14,10 +26,75 @@ pub enum RuleType {
     Solana,
     /// Rules specific to Anchor framework
     Anchor,
+    /// Rules specific to native programs (written without Anchor, with `entrypoint!`), including
+    /// Pinocchio and Steel programs
+    Native,
+    /// Rules specific to Pinocchio programs
+    Pinocchio,
+    /// Rules specific to Steel programs
+    Steel,
     /// General Rust rules
     General,
 }
 
+impl RuleType {
+    /// Every rule type
+    pub const ALL: [RuleType; 6] = [
+        RuleType::Solana,
+        RuleType::Anchor,
+        RuleType::Native,
+        RuleType::Pinocchio,
+        RuleType::Steel,
+        RuleType::General,
+    ];
+
+    /// Returns the name of the rule type (`anchor`...)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleType::Solana => "solana",
+            RuleType::Anchor => "anchor",
+            RuleType::Native => "native",
+            RuleType::Pinocchio => "pinocchio",
+            RuleType::Steel => "steel",
+            RuleType::General => "general",
+        }
+    }
+}
+
+/// Code example of a rule, shown in its documentation (`explain`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleExample {
+    /// Whether the code is safe (else it is vulnerable, reported by the rule)
+    pub safe: bool,
+    /// Rust code of the example
+    pub code: String,
+}
+
+impl RuleExample {
+    /// Creates a vulnerable example, without the indentation and blank lines around the code
+    pub fn vulnerable(code: &str) -> Self {
+        Self { safe: false, code: dedent(code) }
+    }
+
+    /// Creates a safe example, without the indentation and blank lines around the code
+    pub fn safe(code: &str) -> Self {
+        Self { safe: true, code: dedent(code) }
+    }
+}
+
+/// Helper function to remove the indentation shared by the lines of a code block, and the blank
+/// lines around it
+fn dedent(code: &str) -> String {
+    let lines: Vec<&str> = code.trim_matches('\n').lines().map(str::trim_end).collect();
+    let indent = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines.iter().map(|line| line.get(indent..).unwrap_or("")).collect::<Vec<_>>().join("\n").trim_end().to_string()
+}
+
 /// A rule that can be applied to an AST
 pub trait Rule: Send + Sync {
     /// Returns the unique ID of the rule
@@ -40,15 +117,97 @@ pub trait Rule: Send + Sync {
         Vec::new()
     }
 
+    /// Returns why the issue matters: what an attacker can do, or what goes wrong
+    fn rationale(&self) -> Option<String> {
+        None
+    }
+
+    /// Returns the vulnerable and safe code examples of the rule
+    fn examples(&self) -> Vec<RuleExample> {
+        Vec::new()
+    }
+
+    /// Returns the tags classifying the rule (e.g. `security`)
+    fn tags(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns the links to documentation or additional resources about the issue
+    fn references(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns the CWE IDs of the weakness detected by the rule
+    fn cwe(&self) -> Vec<u32> {
+        Vec::new()
+    }
+
+    /// Returns the Solana vulnerability class of the rule
+    fn category(&self) -> Option<Category> {
+        None
+    }
+
+    /// Returns the packs the rule belongs to (see [`crate::analyzer::packs`])
+    fn packs(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns the configuration parameters declared by the rule
+    fn params(&self) -> Option<&RuleParams> {
+        None
+    }
+
+    /// Returns the versions of anchor-lang the rule applies to (every version if `None`)
+    fn anchor_versions(&self) -> Option<&VersionReq> {
+        None
+    }
+
+    /// Check if the rule applies to a crate built with the given anchor-lang version
+    ///
+    /// Rules apply when the version is unknown (no workspace, or no version requirement).
+    fn applies_to_anchor(&self, version: Option<&Version>) -> bool {
+        match (self.anchor_versions(), version) {
+            (Some(versions), Some(version)) => versions.matches(version),
+            _ => true,
+        }
+    }
+
+    /// Apply the user's parameter overrides (raw values keyed by parameter name)
+    fn configure(&mut self, overrides: &BTreeMap<String, String>) -> std::result::Result<(), RuleBuildError> {
+        match overrides.keys().next() {
+            Some(param) => Err(RuleBuildError::UnknownParam {
+                id: self.id().to_string(),
+                param: param.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
     /// Execute the rule on the given AST and return findings
     fn execute(&self, ast: &File, file_path: &str) -> Result<Vec<Finding>>;
 
     /// Execute the rule on the given AST with source code for precise locations
-    fn execute_with_source(&self, ast: &File, file_path: &str, source_code: &str) -> Result<Vec<Finding>> {
+    fn execute_with_source(&self, ast: &File, file_path: &str, _source_code: &str) -> Result<Vec<Finding>> {
         // Default implementation falls back to the old method
         // Rules that need precise locations should override this method
         self.execute(ast, file_path)
     }
+
+    /// Execute the rule on the given AST with the declarations of the whole program, so types
+    /// declared in other files can be resolved
+    fn execute_in_program(&self, ast: &File, file_path: &str, source_code: &str, _program: &ProgramContext) -> Result<Vec<Finding>> {
+        self.execute_with_source(ast, file_path, source_code)
+    }
+
+    /// Check if the rule analyzes the whole program at once instead of each file
+    fn is_project_rule(&self) -> bool {
+        false
+    }
+
+    /// Execute the rule on all the files of the program and return findings
+    fn execute_project(&self, _files: &[ProjectFile<'_>]) -> Result<Vec<Finding>> {
+        Ok(Vec::new())
+    }
 }
 
 /// Configuration for the rule engine
@@ -57,90 +216,335 @@ pub struct RuleEngineConfig {
     /// Path to custom rule templates
     pub custom_templates_path: Option<String>,
 
+    /// Path to WebAssembly rule plugins
+    pub plugins_path: Option<String>,
+
     /// Severities to ignore
     pub ignore_severities: Vec<Severity>,
 
     /// Rule IDs to ignore
     pub ignore_rules: Vec<String>,
 
+    /// Rule IDs to run, all the other rules are ignored (every rule if empty)
+    pub include_rules: Vec<String>,
+
+    /// Tags of the rules to run, a rule runs if it has any of them (every rule if empty)
+    pub include_tags: Vec<String>,
+
     /// Rule types to include
     pub include_rule_types: Vec<RuleType>,
+
+    /// Rule packs to run, resolved from the profile (every rule if `None`)
+    pub packs: Option<BTreeSet<String>>,
+
+    /// Severity of the findings by rule ID, replacing the severity set by the rule
+    pub severity_overrides: BTreeMap<String, Severity>,
+
+    /// Parameter overrides by rule ID (raw values keyed by parameter name)
+    pub rule_params: BTreeMap<String, BTreeMap<String, String>>,
+
+    /// Time budget of a rule on a file (or on the project), no limit if `None`
+    pub rule_timeout: Option<Duration>,
+
+    /// Whether to record the execution time and findings of each rule
+    pub profile_rules: bool,
+
+    /// File system the custom rules are read from
+    pub file_system: SharedFileSystem,
 }
 
 impl Default for RuleEngineConfig {
     fn default() -> Self {
         Self {
             custom_templates_path: None,
+            plugins_path: None,
             ignore_severities: Vec::new(),
             ignore_rules: Vec::new(),
-            include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+            include_rules: Vec::new(),
+            include_tags: Vec::new(),
+            include_rule_types: RuleType::ALL.to_vec(),
+            packs: None,
+            severity_overrides: BTreeMap::new(),
+            rule_params: BTreeMap::new(),
+            rule_timeout: None,
+            profile_rules: false,
+            file_system: SharedFileSystem::default(),
         }
     }
 }
 
+/// Execution profile of a rule over an analysis
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RuleProfile {
+    /// Number of times the rule was executed (once per file, or once for project-level rules)
+    pub runs: usize,
+    /// Total execution time of the rule (in milliseconds in JSON)
+    #[serde(rename = "time_ms", serialize_with = "serialize_millis")]
+    pub time: Duration,
+    /// Number of files with at least one finding of the rule
+    pub files_matched: usize,
+    /// Number of findings produced by the rule (before suppressions and merging)
+    pub findings: usize,
+}
+
+/// Helper function to serialize a duration as fractional milliseconds
+fn serialize_millis<S: Serializer>(time: &Duration, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_f64(time.as_secs_f64() * 1000.0)
+}
+
+impl RuleProfile {
+    /// Record an execution of the rule
+    fn record(&mut self, time: Duration, findings: &[Finding]) {
+        let files: BTreeSet<&str> = findings.iter().map(|finding| finding.location.file.as_str()).collect();
+        self.runs += 1;
+        self.time += time;
+        self.files_matched += files.len();
+        self.findings += findings.len();
+    }
+}
+
+/// Target name of the project-level rules in the timed out rules
+pub const PROJECT_TARGET: &str = "<project>";
+
+/// Current time of the monotonic clock, `None` on `wasm32-unknown-unknown`, which has none: rule
+/// time budgets, rule profiles and analysis times are not measured there
+pub(crate) fn now() -> Option<Instant> {
+    (!cfg!(all(target_family = "wasm", target_os = "unknown"))).then(Instant::now)
+}
+
+thread_local! {
+    /// Deadline of the rule running on this thread, if it has a time budget
+    static RULE_DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Check if the rule running on this thread exceeded its time budget
+///
+/// Rules that can be interrupted (e.g. scripts) poll it to stop early, the others are
+/// checked by the engine once they return.
+pub fn rule_deadline_exceeded() -> bool {
+    RULE_DEADLINE.get().is_some_and(|deadline| now().is_some_and(|now| now >= deadline))
+}
+
 /// Engine for loading and executing rules
 pub struct RuleEngine {
-    /// Rules loaded in the engine
-    rules: Vec<Arc<dyn Rule>>,
+    /// Rules registered in the engine, loaded or filtered out by the configuration
+    registry: RuleRegistry,
 
     /// Configuration for the engine
     config: RuleEngineConfig,
+
+    /// Files (or [`PROJECT_TARGET`]) on which each rule exceeded its time budget, by rule ID
+    timed_out: Mutex<BTreeMap<String, BTreeSet<String>>>,
+
+    /// Execution profiles by rule ID, if enabled in the configuration
+    profiles: Mutex<BTreeMap<String, RuleProfile>>,
+}
+
+impl Default for RuleEngine {
+    /// Creates a new rule engine with default configuration
+    fn default() -> Self {
+        Self::new(RuleEngineConfig::default())
+    }
 }
 
 impl RuleEngine {
     /// Creates a new rule engine with the given configuration
     pub fn new(config: RuleEngineConfig) -> Self {
         Self {
-            rules: Vec::new(),
+            registry: RuleRegistry::new(),
             config,
+            timed_out: Mutex::new(BTreeMap::new()),
+            profiles: Mutex::new(BTreeMap::new()),
         }
     }
 
-    /// Creates a new rule engine with default configuration
-    pub fn default() -> Self {
-        Self::new(RuleEngineConfig::default())
-    }
-
     /// Loads built-in rules
     pub fn load_builtin_rules(&mut self) -> Result<()> {
-        debug!("Loading built-in rules");
+        self.register_plugin(&BuiltinRules)
+    }
 
-        // Register security templates
-        if let Err(e) = crate::analyzer::rules::register_builtin_rules(self) {
-            return Err(anyhow::anyhow!("Failed to register built-in rules: {}", e));
+    /// Registers the rules of a rule set (built-in or from a downstream crate)
+    pub fn register_plugin(&mut self, plugin: &dyn RulePlugin) -> Result<()> {
+        debug!("Loading {} rules", plugin.name());
+
+        let before = self.rule_count();
+        if let Err(e) = plugin.register_rules(self) {
+            return Err(anyhow::anyhow!("Failed to register {} rules: {}", plugin.name(), e));
         }
 
-        info!("Loaded {} built-in rules", self.rule_count());
+        debug!("Loaded {} {} rules", self.rule_count() - before, plugin.name());
 
         Ok(())
     }
 
+    /// Warn about parameters configured for unknown rules, selected rules that do not exist
+    /// and selected packs no rule declares
+    ///
+    /// Called once every rule source has been loaded.
+    pub fn check_rule_params(&self) {
+        let is_known = |rule_id: &String| self.registry.contains(rule_id);
+        for rule_id in self.config.rule_params.keys() {
+            if !is_known(rule_id) {
+                warn!("Parameters configured for unknown rule: {rule_id}");
+            }
+        }
+
+        for rule_id in &self.config.include_rules {
+            if !is_known(rule_id) {
+                warn!("Selected rule does not exist: {rule_id}");
+            }
+        }
+
+        for rule_id in self.config.severity_overrides.keys() {
+            if !is_known(rule_id) {
+                warn!("Severity configured for unknown rule: {rule_id}");
+            }
+        }
+
+        let known_packs = self.registry.packs();
+        for pack in self.config.packs.iter().flatten() {
+            if !known_packs.contains(pack) {
+                warn!("No rule belongs to the selected pack: {pack}");
+            }
+        }
+    }
+
     /// Loads rules from YAML templates
     pub fn load_yaml_rules(&mut self, templates_path: &Path) -> Result<()> {
         debug!("Loading YAML rules from {}", templates_path.display());
 
-        //@todo => implement YAML rule loading
-        info!("YAML rule loading not implemented yet");
+        // Invalid templates are skipped so one broken file does not disable the others
+        let mut loaded = 0;
+        let fs = self.config.file_system.clone();
+        for path in crate::analyzer::templates::find_template_files(&*fs, templates_path, &["yaml", "yml"]) {
+            let result = crate::analyzer::templates::RuleTemplate::load(&*fs, &path)
+                .and_then(|template| template.build())
+                .and_then(|rule| self.add_rule(rule).map_err(crate::analyzer::templates::TemplateError::from));
+            match result {
+                Ok(()) => loaded += 1,
+                Err(e) => warn!("Skipping rule template {}: {}", path.display(), e),
+            }
+        }
+
+        info!("Loaded {loaded} rules from YAML templates");
+
+        Ok(())
+    }
+
+    /// Loads rules from Rhai scripts (`*.rhai`)
+    #[cfg(feature = "scripting")]
+    pub fn load_script_rules(&mut self, templates_path: &Path) -> Result<()> {
+        debug!("Loading script rules from {}", templates_path.display());
+
+        // Invalid scripts are skipped so one broken file does not disable the others
+        let mut loaded = 0;
+        let fs = self.config.file_system.clone();
+        for path in crate::analyzer::templates::find_template_files(&*fs, templates_path, &["rhai"]) {
+            let result = crate::analyzer::scripting::ScriptRule::load(&*fs, &path)
+                .map_err(anyhow::Error::from)
+                .and_then(|rule| Ok(self.add_rule(Arc::new(rule))?));
+            match result {
+                Ok(()) => loaded += 1,
+                Err(e) => warn!("Skipping rule script {}: {}", path.display(), e),
+            }
+        }
+
+        info!("Loaded {loaded} rules from scripts");
 
         Ok(())
     }
 
-    /// Adds a rule to the engine
-    pub fn add_rule(&mut self, rule: Arc<dyn Rule>) {
+    /// Loads rules from WebAssembly plugins (`*.wasm`)
+    #[cfg(feature = "wasm-plugins")]
+    pub fn load_wasm_plugins(&mut self, plugins_path: &Path) -> Result<()> {
+        debug!("Loading rule plugins from {}", plugins_path.display());
+
+        // Invalid plugins are skipped so one broken file does not disable the others
+        let mut loaded = 0;
+        let fs = self.config.file_system.clone();
+        for path in crate::analyzer::templates::find_template_files(&*fs, plugins_path, &["wasm"]) {
+            let result = crate::analyzer::plugins::WasmRule::load(&*fs, &path)
+                .map_err(anyhow::Error::from)
+                .and_then(|rule| Ok(self.add_rule(Arc::new(rule))?));
+            match result {
+                Ok(()) => loaded += 1,
+                Err(e) => warn!("Skipping rule plugin {}: {}", path.display(), e),
+            }
+        }
+
+        info!("Loaded {loaded} rules from plugins");
+
+        Ok(())
+    }
+
+    /// Check if a rule with the given ID is loaded (registered and selected by the configuration)
+    pub fn has_rule(&self, id: &str) -> bool {
+        self.registry.is_enabled(id)
+    }
+
+    /// Returns the registry of the rules, to read their metadata
+    pub fn registry(&self) -> &RuleRegistry {
+        &self.registry
+    }
+
+    /// Adds a rule to the engine, failing if a rule with the same ID is already registered
+    /// (even if filtered out by the configuration)
+    pub fn add_rule(&mut self, mut rule: Arc<dyn Rule>) -> std::result::Result<(), RuleBuildError> {
+        if self.registry.contains(rule.id()) {
+            return Err(RuleBuildError::DuplicateId {
+                id: rule.id().to_string(),
+            });
+        }
+
+        if !self.is_rule_selected(rule.as_ref(), &rule.packs()) {
+            return self.registry.register(rule, false);
+        }
+
+        // Resolve the rule parameters from the user's overrides (invalid values keep their defaults)
+        if let Some(overrides) = self.config.rule_params.get(rule.id()) {
+            match Arc::get_mut(&mut rule) {
+                Some(rule) => {
+                    if let Err(e) = rule.configure(overrides) {
+                        warn!("Invalid configuration: {e}");
+                    }
+                }
+                None => warn!("Rule {} is shared and cannot be configured, using default parameters", rule.id()),
+            }
+        }
+
+        debug!("Adding rule: {}", rule.id());
+        self.registry.register(rule, true)
+    }
+
+    /// Helper function to check if the configuration selects a rule
+    fn is_rule_selected(&self, rule: &dyn Rule, packs: &[String]) -> bool {
         // Check if the rule should be ignored based on severity
-        if self.config.ignore_severities.contains(&rule.severity()) {
+        let severity = self.severity_of(rule);
+        if self.config.ignore_severities.contains(&severity) {
             debug!(
                 "Ignoring rule {} due to severity {:?}",
                 rule.id(),
-                rule.severity()
+                severity
             );
-            return;
+            return false;
         }
 
         // Check if the rule should be ignored based on ID
         if self.config.ignore_rules.contains(&rule.id().to_string()) {
             debug!("Ignoring rule {} due to ID match", rule.id());
-            return;
+            return false;
+        }
+
+        // Check if the rule is selected by ID
+        if !self.config.include_rules.is_empty() && !self.config.include_rules.iter().any(|id| id == rule.id()) {
+            debug!("Ignoring rule {} as it is not selected", rule.id());
+            return false;
+        }
+
+        // Check if the rule has a selected tag
+        if !self.config.include_tags.is_empty() && !rule.tags().iter().any(|tag| self.config.include_tags.contains(tag)) {
+            debug!("Ignoring rule {} due to tags {:?}", rule.id(), rule.tags());
+            return false;
         }
 
         // Check if the rule type is included
@@ -150,29 +554,207 @@ impl RuleEngine {
                 rule.id(),
                 rule.rule_type()
             );
-            return;
+            return false;
         }
 
-        debug!("Adding rule: {}", rule.id());
-        self.rules.push(rule);
+        // Check if the rule belongs to a selected pack
+        if !crate::analyzer::packs::is_selected(packs, self.config.packs.as_ref()) {
+            debug!("Ignoring rule {} due to packs {:?}", rule.id(), packs);
+            return false;
+        }
+
+        true
+    }
+
+    /// Returns the severity of a rule, with the user's override if any
+    pub fn severity_of(&self, rule: &dyn Rule) -> Severity {
+        self.config
+            .severity_overrides
+            .get(rule.id())
+            .cloned()
+            .unwrap_or_else(|| rule.severity())
+    }
+
+    /// Returns the IDs of the rules loaded
+    pub fn rule_ids(&self) -> Vec<&str> {
+        self.registry.enabled().map(|rule| rule.id()).collect()
     }
 
     /// Returns the number of rules loaded
     pub fn rule_count(&self) -> usize {
-        self.rules.len()
+        self.registry.enabled_count()
     }
 
     /// Execute all registered rules on the given AST with source code for precise locations
     pub fn execute_rules(&self, ast: &File, file_path: &str, source_code: &str) -> anyhow::Result<Vec<Finding>> {
-        debug!("Executing {} rules on {}", self.rules.len(), file_path);
+        Ok(self
+            .execute_rules_per_rule(ast, file_path, source_code)
+            .into_iter()
+            .flat_map(|(_, findings)| findings)
+            .collect())
+    }
+
+    /// Execute all registered rules on the given AST and return the findings of each rule, by rule ID
+    ///
+    /// The file is analyzed alone, see [`RuleEngine::execute_rules_in_program`] to resolve the
+    /// declarations of other files.
+    pub fn execute_rules_per_rule(&self, ast: &File, file_path: &str, source_code: &str) -> Vec<(String, Vec<Finding>)> {
+        let program = ProgramContext::build([(file_path, ast)]);
+        self.execute_rules_in_program(ast, file_path, source_code, &program)
+    }
+
+    /// Execute all registered rules on a file of a program and return the findings of each rule, by rule ID
+    pub fn execute_rules_in_program(
+        &self,
+        ast: &File,
+        file_path: &str,
+        source_code: &str,
+        program: &ProgramContext,
+    ) -> Vec<(String, Vec<Finding>)> {
+        self.execute_selected_rules_in_program(ast, file_path, source_code, program, |_| true)
+    }
+
+    /// Execute the registered rules accepted by `selected` on a file of a program and return the
+    /// findings of each rule, by rule ID (e.g. without the Anchor rules for a native program)
+    pub fn execute_selected_rules_in_program<S>(
+        &self,
+        ast: &File,
+        file_path: &str,
+        source_code: &str,
+        program: &ProgramContext,
+        selected: S,
+    ) -> Vec<(String, Vec<Finding>)>
+    where
+        S: Fn(&dyn Rule) -> bool,
+    {
+        debug!("Executing {} rules on {}", self.rule_count(), file_path);
+
+        // Share common traversals between the rules while they analyze this file
+        let _cache_scope = crate::analyzer::dsl::cache::CacheScope::enter(ast);
 
+        self.run_rules(file_path, false, selected, |rule| rule.execute_in_program(ast, file_path, source_code, program))
+    }
+
+    /// Check if any registered rule analyzes the whole program
+    pub fn has_project_rules(&self) -> bool {
+        self.registry.enabled().any(|rule| rule.is_project_rule())
+    }
+
+    /// Execute the project-level rules on all the files of the program
+    pub fn execute_project_rules(&self, files: &[ProjectFile<'_>]) -> anyhow::Result<Vec<Finding>> {
+        Ok(self
+            .execute_project_rules_per_rule(files)
+            .into_iter()
+            .flat_map(|(_, findings)| findings)
+            .collect())
+    }
+
+    /// Execute the project-level rules on all the files of the program and return the findings of each rule, by rule ID
+    pub fn execute_project_rules_per_rule(&self, files: &[ProjectFile<'_>]) -> Vec<(String, Vec<Finding>)> {
+        self.execute_selected_project_rules_per_rule(files, |_| true)
+    }
+
+    /// Execute the project-level rules accepted by `selected` on all the files of the program
+    /// and return the findings of each rule, by rule ID
+    pub fn execute_selected_project_rules_per_rule<S>(&self, files: &[ProjectFile<'_>], selected: S) -> Vec<(String, Vec<Finding>)>
+    where
+        S: Fn(&dyn Rule) -> bool,
+    {
+        debug!("Executing project rules on {} files", files.len());
+        self.run_rules(PROJECT_TARGET, true, selected, |rule| rule.execute_project(files))
+    }
+
+    /// Check if a rule exceeded its time budget on a file (or on [`PROJECT_TARGET`])
+    pub fn timed_out_on(&self, target: &str) -> bool {
+        let timed_out = self.timed_out.lock().unwrap_or_else(PoisonError::into_inner);
+        timed_out.values().any(|targets| targets.contains(target))
+    }
+
+    /// Returns the rules that exceeded their time budget since the last call, with the files
+    /// (or [`PROJECT_TARGET`]) on which they did, by rule ID
+    pub fn take_timed_out_rules(&self) -> BTreeMap<String, BTreeSet<String>> {
+        std::mem::take(&mut *self.timed_out.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Returns the execution profiles recorded since the last call, by rule ID
+    ///
+    /// Every loaded rule has a profile, including the rules that never ran or never fired.
+    pub fn take_rule_profiles(&self) -> BTreeMap<String, RuleProfile> {
+        let mut profiles = std::mem::take(&mut *self.profiles.lock().unwrap_or_else(PoisonError::into_inner));
+        if self.config.profile_rules {
+            for rule in self.registry.enabled() {
+                profiles.entry(rule.id().to_string()).or_default();
+            }
+        }
+        profiles
+    }
+
+    /// Helper function to run the per-file or project-level rules accepted by `selected` on a target
+    /// (file path or [`PROJECT_TARGET`]), logging the rules that fail, panic or exceed their time budget
+    fn run_rules<S, F>(&self, target: &str, project: bool, selected: S, execute: F) -> Vec<(String, Vec<Finding>)>
+    where
+        S: Fn(&dyn Rule) -> bool,
+        F: Fn(&dyn Rule) -> Result<Vec<Finding>>,
+    {
         let mut findings = Vec::new();
 
-        for rule in &self.rules {
-            match rule.execute_with_source(ast, file_path, source_code) {
-                Ok(rule_findings) => {
+        for rule in self.registry.enabled().filter(|rule| rule.is_project_rule() == project && selected(rule.as_ref())) {
+            let started = now();
+            RULE_DEADLINE.set(started.zip(self.config.rule_timeout).map(|(started, timeout)| started + timeout));
+            // A panicking rule only loses its own findings
+            let result = catch_unwind(AssertUnwindSafe(|| execute(rule.as_ref())));
+            RULE_DEADLINE.set(None);
+
+            let elapsed = started.map_or(Duration::ZERO, |started| started.elapsed());
+            let timed_out = self.config.rule_timeout.is_some_and(|timeout| elapsed > timeout);
+            if self.config.profile_rules {
+                let produced = match &result {
+                    Ok(Ok(rule_findings)) if !timed_out => rule_findings.as_slice(),
+                    _ => &[],
+                };
+                let mut profiles = self.profiles.lock().unwrap_or_else(PoisonError::into_inner);
+                profiles.entry(rule.id().to_string()).or_default().record(elapsed, produced);
+            }
+
+            let result = match result {
+                Ok(result) => result,
+                Err(payload) => {
+                    warn!("Rule {} panicked on {target}: {}", rule.id(), panic_message(payload.as_ref()));
+                    continue;
+                }
+            };
+
+            // Findings of a rule over its budget are dropped, interrupted rules may have missed some
+            if let Some(timeout) = self.config.rule_timeout
+                && timed_out
+            {
+                warn!(
+                    "Rule {} exceeded its time budget of {}ms on {target}, ignoring its findings",
+                    rule.id(),
+                    timeout.as_millis()
+                );
+                let mut timed_out = self.timed_out.lock().unwrap_or_else(PoisonError::into_inner);
+                timed_out.entry(rule.id().to_string()).or_default().insert(target.to_string());
+                continue;
+            }
+
+            match result {
+                Ok(mut rule_findings) => {
                     debug!("Rule {} found {} issues", rule.id(), rule_findings.len());
-                    findings.extend(rule_findings);
+                    let severity = self.config.severity_overrides.get(rule.id());
+                    let info = RuleInfo::from_rule(rule.as_ref());
+                    for finding in &mut rule_findings {
+                        if let Some(severity) = severity {
+                            finding.severity = severity.clone();
+                        }
+                        if finding.rules.is_empty() {
+                            finding.rules.push(info.clone());
+                        }
+                        if finding.recommendations.is_empty() {
+                            finding.recommendations = rule.recommendations();
+                        }
+                    }
+                    findings.push((rule.id().to_string(), rule_findings));
                 }
                 Err(e) => {
                     warn!("Error executing rule {}: {}", rule.id(), e);
@@ -180,10 +762,27 @@ impl RuleEngine {
             }
         }
 
-        Ok(findings)
+        findings
     }
 }
 
+/// Helper function to get the message of a panic
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// Check function of a `RustRule` with `SpanExtractor` support and the declarations of the program
+type CheckFn = Box<
+    dyn Fn(&File, &str, &crate::analyzer::span_utils::SpanExtractor, &RuleParams, &ProgramContext) -> Result<Vec<Finding>> + Send + Sync,
+>;
+
+/// Check function of a `RustRule` that analyzes all the files of the program at once
+type ProjectCheckFn = Box<dyn Fn(&[ProjectFile<'_>], &RuleParams) -> Result<Vec<Finding>> + Send + Sync>;
+
 pub struct RustRule {
     /// Unique ID of the rule
     id: String,
@@ -203,8 +802,38 @@ pub struct RustRule {
     /// Recommendations for fixing the issue
     recommendations: Vec<String>,
 
+    /// Why the issue matters
+    rationale: Option<String>,
+
+    /// Vulnerable and safe code examples
+    examples: Vec<RuleExample>,
+
+    /// Tags classifying the rule
+    tags: Vec<String>,
+
+    /// Links to documentation or additional resources
+    references: Vec<String>,
+
+    /// CWE IDs of the weakness detected by the rule
+    cwe: Vec<u32>,
+
+    /// Solana vulnerability class of the rule
+    category: Option<Category>,
+
+    /// Packs the rule belongs to
+    packs: Vec<String>,
+
+    /// Configuration parameters of the rule
+    params: RuleParams,
+
+    /// Versions of anchor-lang the rule applies to
+    anchor_versions: Option<VersionReq>,
+
     /// Function that implements the rule check with `SpanExtractor` support
-    check_fn: Box<dyn Fn(&File, &str, &crate::analyzer::span_utils::SpanExtractor) -> Result<Vec<Finding>> + Send + Sync>,
+    check_fn: CheckFn,
+
+    /// Function that implements the check of project-level rules (replaces `check_fn`)
+    project_check_fn: Option<ProjectCheckFn>,
 }
 
 impl RustRule {
@@ -219,7 +848,10 @@ impl RustRule {
         check_fn: F,
     ) -> Self
     where
-        F: Fn(&File, &str, &crate::analyzer::span_utils::SpanExtractor) -> Result<Vec<Finding>> + Send + Sync + 'static,
+        F: Fn(&File, &str, &crate::analyzer::span_utils::SpanExtractor, &RuleParams, &ProgramContext) -> Result<Vec<Finding>>
+            + Send
+            + Sync
+            + 'static,
     {
         Self {
             id: id.to_string(),
@@ -228,9 +860,72 @@ impl RustRule {
             severity,
             rule_type,
             recommendations,
+            rationale: None,
+            examples: Vec::new(),
+            tags: Vec::new(),
+            references: Vec::new(),
+            cwe: Vec::new(),
+            category: None,
+            packs: Vec::new(),
+            params: RuleParams::new(),
+            anchor_versions: None,
             check_fn: Box::new(check_fn),
+            project_check_fn: None,
         }
     }
+
+    /// Sets the tags classifying the rule
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Sets the links to documentation or additional resources
+    pub fn with_references(mut self, references: Vec<String>) -> Self {
+        self.references = references;
+        self
+    }
+
+    /// Sets why the issue matters and the code examples of the rule
+    pub fn with_documentation(mut self, rationale: Option<String>, examples: Vec<RuleExample>) -> Self {
+        self.rationale = rationale;
+        self.examples = examples;
+        self
+    }
+
+    /// Sets the CWE IDs and the vulnerability class of the rule
+    pub fn with_taxonomy(mut self, cwe: Vec<u32>, category: Option<Category>) -> Self {
+        self.cwe = cwe;
+        self.category = category;
+        self
+    }
+
+    /// Sets the packs the rule belongs to
+    pub fn with_packs(mut self, packs: Vec<String>) -> Self {
+        self.packs = packs;
+        self
+    }
+
+    /// Sets the configuration parameters declared by the rule
+    pub fn with_params(mut self, params: RuleParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Sets the versions of anchor-lang the rule applies to
+    pub fn with_anchor_versions(mut self, anchor_versions: Option<VersionReq>) -> Self {
+        self.anchor_versions = anchor_versions;
+        self
+    }
+
+    /// Makes the rule a project-level rule analyzing all the files of the program at once
+    pub fn with_project_check<F>(mut self, project_check_fn: F) -> Self
+    where
+        F: Fn(&[ProjectFile<'_>], &RuleParams) -> Result<Vec<Finding>> + Send + Sync + 'static,
+    {
+        self.project_check_fn = Some(Box::new(project_check_fn));
+        self
+    }
 }
 
 impl Rule for RustRule {
@@ -258,16 +953,82 @@ impl Rule for RustRule {
         self.recommendations.clone()
     }
 
+    fn rationale(&self) -> Option<String> {
+        self.rationale.clone()
+    }
+
+    fn examples(&self) -> Vec<RuleExample> {
+        self.examples.clone()
+    }
+
+    fn tags(&self) -> Vec<String> {
+        self.tags.clone()
+    }
+
+    fn references(&self) -> Vec<String> {
+        self.references.clone()
+    }
+
+    fn cwe(&self) -> Vec<u32> {
+        self.cwe.clone()
+    }
+
+    fn category(&self) -> Option<Category> {
+        self.category
+    }
+
+    fn packs(&self) -> Vec<String> {
+        self.packs.clone()
+    }
+
+    fn params(&self) -> Option<&RuleParams> {
+        Some(&self.params)
+    }
+
+    fn anchor_versions(&self) -> Option<&VersionReq> {
+        self.anchor_versions.as_ref()
+    }
+
+    fn configure(&mut self, overrides: &BTreeMap<String, String>) -> std::result::Result<(), RuleBuildError> {
+        // Apply every valid override and report the first invalid one
+        let mut result = Ok(());
+        for (name, raw) in overrides {
+            match self.params.set(&self.id, name, raw) {
+                Ok(()) => debug!("Rule {} parameter {} set to {}", self.id, name, raw),
+                Err(e) => {
+                    if result.is_ok() {
+                        result = Err(e);
+                    }
+                }
+            }
+        }
+        result
+    }
+
     fn execute(&self, ast: &File, file_path: &str) -> Result<Vec<Finding>> {
         // Fallback: create SpanExtractor with empty source for backward compatibility
-        let span_extractor = crate::analyzer::span_utils::SpanExtractor::new(String::new(), file_path.to_string());
-        (self.check_fn)(ast, file_path, &span_extractor)
+        self.execute_with_source(ast, file_path, "")
     }
 
     fn execute_with_source(&self, ast: &File, file_path: &str, source_code: &str) -> Result<Vec<Finding>> {
-        // Create SpanExtractor with actual source code for precise locations
-        let span_extractor = crate::analyzer::span_utils::SpanExtractor::new(source_code.to_string(), file_path.to_string());
-        (self.check_fn)(ast, file_path, &span_extractor)
+        self.execute_in_program(ast, file_path, source_code, &ProgramContext::new())
+    }
+
+    fn execute_in_program(&self, ast: &File, file_path: &str, source_code: &str, program: &ProgramContext) -> Result<Vec<Finding>> {
+        // SpanExtractor with actual source code for precise locations, shared by the rules of the file
+        let span_extractor = crate::analyzer::span_utils::SpanExtractor::shared(source_code, file_path);
+        (self.check_fn)(ast, file_path, &span_extractor, &self.params, program)
+    }
+
+    fn is_project_rule(&self) -> bool {
+        self.project_check_fn.is_some()
+    }
+
+    fn execute_project(&self, files: &[ProjectFile<'_>]) -> Result<Vec<Finding>> {
+        match &self.project_check_fn {
+            Some(project_check_fn) => project_check_fn(files, &self.params),
+            None => Ok(Vec::new()),
+        }
     }
 }
 
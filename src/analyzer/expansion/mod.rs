@@ -0,0 +1,408 @@
+use log::debug;
+#[cfg(feature = "native")]
+use log::{info, warn};
+use quote::ToTokens;
+use std::collections::{BTreeSet, HashMap};
+#[cfg(feature = "native")]
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "native")]
+use std::process::Command;
+use syn::spanned::Spanned;
+use syn::{File, ImplItem, Item};
+
+use crate::analyzer::dsl::program::derives;
+use crate::analyzer::dsl::query::MetadataValue;
+use crate::analyzer::{Finding, Location};
+#[cfg(feature = "native")]
+use crate::ast::{modules::ModuleTree, parser::SourceFile};
+
+#[cfg(test)]
+mod test;
+
+/// Metadata key of the findings reported on expanded code, with their location in the expanded file
+pub const EXPANDED_LOCATION_KEY: &str = "expanded_location";
+
+/// Error returned when a crate cannot be expanded with `cargo expand`
+#[derive(Debug)]
+pub enum ExpansionError {
+    /// Cargo cannot be run
+    Io(std::io::Error),
+    /// The `cargo expand` subcommand is not installed
+    NotInstalled,
+    /// `cargo expand` failed (build errors, invalid manifest...)
+    Cargo { crate_dir: PathBuf, stderr: String },
+    /// The expanded code cannot be parsed
+    Parse(syn::Error),
+}
+
+impl fmt::Display for ExpansionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpansionError::Io(error) => write!(f, "cannot run cargo: {error}"),
+            ExpansionError::NotInstalled => write!(f, "cargo-expand is not installed (cargo install cargo-expand)"),
+            ExpansionError::Cargo { crate_dir, stderr } => {
+                write!(f, "`cargo expand` failed in {}: {}", crate_dir.display(), stderr.trim())
+            }
+            ExpansionError::Parse(error) => write!(f, "cannot parse the expanded code: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ExpansionError {}
+
+/// Macro-expanded code analyzed alongside the original files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandedFile {
+    /// File the expanded code is written to (findings not mapped back to the original files point to it)
+    pub path: PathBuf,
+    /// Expanded code
+    pub source: String,
+    /// Files the code was expanded from (every file of the crate for `cargo expand`)
+    pub originals: Vec<PathBuf>,
+}
+
+/// Expand the macros of the crates of the given files, writing the expanded code to `output_dir`
+///
+/// Each crate (`src/lib.rs` or `src/main.rs` next to a `Cargo.toml`) is expanded with
+/// `cargo expand`. Crates that cannot be expanded (cargo-expand not installed, build errors)
+/// and files outside any crate fall back to the Anchor macros (`#[program]` and
+/// `#[derive(Accounts)]`), expanded with anchor-syn without building anything.
+#[cfg(feature = "native")]
+pub fn expand_files(files: &[SourceFile], output_dir: &Path) -> Vec<ExpandedFile> {
+    let tree = ModuleTree::build(files.iter().map(|file| (file.path.as_path(), &file.ast)));
+    let mut crates: BTreeMap<&Path, Vec<PathBuf>> = BTreeMap::new();
+    for (path, module) in tree.iter() {
+        crates.entry(module.crate_root.as_path()).or_default().push(path.to_path_buf());
+    }
+
+    let mut expanded = Vec::new();
+    let mut expanded_files = BTreeSet::new();
+    for (root, originals) in crates {
+        let Some(crate_dir) = root.parent().and_then(Path::parent).filter(|dir| dir.join("Cargo.toml").is_file()) else {
+            continue;
+        };
+        match expand_crate(crate_dir, root.ends_with("lib.rs")) {
+            Ok(source) => {
+                info!("Expanded the crate {} with cargo expand", crate_dir.display());
+                expanded_files.extend(originals.iter().cloned());
+                expanded.push((root.to_path_buf(), source, originals));
+            }
+            Err(ExpansionError::NotInstalled) => {
+                warn!("{}, only the Anchor macros are expanded", ExpansionError::NotInstalled);
+                break;
+            }
+            Err(e) => warn!("{e}, only the Anchor macros of this crate are expanded"),
+        }
+    }
+
+    // Files not expanded with their crate
+    for SourceFile { path, ast, .. } in files {
+        if expanded_files.contains(path) {
+            continue;
+        }
+        if let Some(source) = expand_anchor_macros(ast) {
+            debug!("Expanded the Anchor macros of {}", path.display());
+            expanded.push((path.clone(), source, vec![path.clone()]));
+        }
+    }
+
+    if !expanded.is_empty()
+        && let Err(e) = std::fs::create_dir_all(output_dir)
+    {
+        warn!("Cannot create {}: {e}", output_dir.display());
+        return Vec::new();
+    }
+    expanded
+        .into_iter()
+        .filter_map(|(origin, source, originals)| {
+            let path = output_dir.join(expanded_file_name(&origin));
+            match std::fs::write(&path, &source) {
+                Ok(()) => Some(ExpandedFile { path, source, originals }),
+                Err(e) => {
+                    warn!("Cannot write the expanded code of {} to {}: {e}", origin.display(), path.display());
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Expand a crate with `cargo expand` (its library if `lib` is set, its binary otherwise)
+#[cfg(feature = "native")]
+pub fn expand_crate(crate_dir: &Path, lib: bool) -> Result<String, ExpansionError> {
+    let mut command = Command::new("cargo");
+    command
+        .arg("expand")
+        .arg("--manifest-path")
+        .arg(crate_dir.join("Cargo.toml"))
+        .env("CARGO_TERM_COLOR", "never");
+    if lib {
+        command.arg("--lib");
+    }
+    let output = command.output().map_err(ExpansionError::Io)?;
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    if !output.status.success() {
+        if stderr.contains("no such command") {
+            return Err(ExpansionError::NotInstalled);
+        }
+        return Err(ExpansionError::Cargo {
+            crate_dir: crate_dir.to_path_buf(),
+            stderr,
+        });
+    }
+
+    // Formatted again, so the lines of the findings match the written file
+    let ast = syn::parse_file(&String::from_utf8_lossy(&output.stdout)).map_err(ExpansionError::Parse)?;
+    Ok(prettyplease::unparse(&ast))
+}
+
+/// Expand the Anchor macros of a file (`#[program]` modules and `#[derive(Accounts)]` structs)
+///
+/// Returns `None` if the file has none. Items anchor-syn rejects are kept as they are.
+pub fn expand_anchor_macros(ast: &File) -> Option<String> {
+    let mut expanded = false;
+    let items = expand_items(&ast.items, &mut expanded);
+    expanded.then(|| {
+        prettyplease::unparse(&File {
+            shebang: None,
+            attrs: ast.attrs.clone(),
+            items,
+        })
+    })
+}
+
+/// Helper function to expand the Anchor macros of a list of items, recursively for inline modules
+fn expand_items(items: &[Item], expanded: &mut bool) -> Vec<Item> {
+    let mut result = Vec::with_capacity(items.len());
+    for item in items {
+        match item {
+            Item::Struct(item_struct) if derives(&item_struct.attrs, "Accounts") => {
+                result.push(item.clone());
+                let generated = syn1::parse_str::<anchor_syn::AccountsStruct>(&item.to_token_stream().to_string())
+                    .map_err(|e| e.to_string())
+                    .and_then(|accounts| parse_generated(accounts.to_token_stream()));
+                match generated {
+                    Ok(generated) => {
+                        result.extend(generated);
+                        *expanded = true;
+                    }
+                    Err(e) => debug!("Cannot expand the Accounts of {}: {e}", item_struct.ident),
+                }
+            }
+            Item::Mod(item_mod) if item_mod.attrs.iter().any(|attr| attr.path().is_ident("program")) => {
+                // Attribute macros do not see their own attribute
+                let mut program_mod = item_mod.clone();
+                program_mod.attrs.retain(|attr| !attr.path().is_ident("program"));
+                let generated = syn1::parse_str::<anchor_syn::Program>(&program_mod.to_token_stream().to_string())
+                    .map_err(|e| e.to_string())
+                    .and_then(|program| parse_generated(program.to_token_stream()));
+                match generated {
+                    Ok(generated) => {
+                        result.extend(generated);
+                        *expanded = true;
+                    }
+                    Err(e) => {
+                        debug!("Cannot expand the program {}: {e}", item_mod.ident);
+                        result.push(item.clone());
+                    }
+                }
+            }
+            Item::Mod(item_mod) if item_mod.content.is_some() => {
+                let mut item_mod = item_mod.clone();
+                if let Some((_, content)) = &mut item_mod.content {
+                    *content = expand_items(content, expanded);
+                }
+                result.push(Item::Mod(item_mod));
+            }
+            _ => result.push(item.clone()),
+        }
+    }
+    result
+}
+
+/// Helper function to parse the code generated by anchor-syn
+fn parse_generated(tokens: proc_macro2::TokenStream) -> Result<Vec<Item>, String> {
+    syn::parse2::<File>(tokens).map(|file| file.items).map_err(|e| e.to_string())
+}
+
+/// Helper function to name the expanded file of a crate root or file (`programs/vault/src/lib.rs`
+/// gives `programs_vault_src_lib.expanded.rs`)
+#[cfg(feature = "native")]
+fn expanded_file_name(origin: &Path) -> String {
+    let name: String = origin
+        .with_extension("")
+        .components()
+        .filter_map(|component| match component {
+            std::path::Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("_");
+    format!("{name}.expanded.rs")
+}
+
+/// Map the findings reported on expanded code back to the original files
+///
+/// Findings on code that the expansion left unchanged are dropped: the analysis of the original
+/// files already reports them. Findings on generated code move to the declaration the code was
+/// generated for in the original files (e.g. the `impl Accounts for Withdraw` of the `Withdraw`
+/// struct) and keep their expanded location in the `expanded_location` metadata. Findings
+/// without such a declaration stay in the expanded file.
+pub fn map_findings(findings: Vec<Finding>, expanded: &File, originals: &[(&Path, &File)]) -> Vec<Finding> {
+    let mut unchanged = BTreeSet::new();
+    let mut declarations = HashMap::new();
+    for (path, ast) in originals {
+        collect_units(&ast.items, &mut unchanged);
+        collect_declarations(&ast.items, path, &mut declarations);
+    }
+
+    findings
+        .into_iter()
+        .filter_map(|mut finding| {
+            let (key, owner) = unit_at(&expanded.items, finding.location.line)?;
+            if unchanged.contains(&key) {
+                return None;
+            }
+            let expanded_location = format!("{}:{}", finding.location.file, finding.location.line);
+            if let Some(location) = owner.and_then(|owner| declarations.get(&owner)) {
+                finding.location = Location::clone(location);
+            }
+            finding
+                .metadata
+                .insert(EXPANDED_LOCATION_KEY.to_string(), MetadataValue::Text(expanded_location));
+            Some(finding)
+        })
+        .collect()
+}
+
+/// Helper function to get the item of a file owning a line (the innermost function, impl or other
+/// item), as its code without outer attributes and the name of the declaration it belongs to
+fn unit_at(items: &[Item], line: usize) -> Option<(String, Option<String>)> {
+    let item = items.iter().find(|item| spans_line(*item, line))?;
+    match item {
+        Item::Mod(item_mod) => match &item_mod.content {
+            Some((_, content)) => unit_at(content, line),
+            None => Some((unit_key(item), Some(item_mod.ident.to_string()))),
+        },
+        Item::Impl(item_impl) => {
+            let owner = self_type_name(&item_impl.self_ty);
+            match item_impl.items.iter().find(|impl_item| spans_line(*impl_item, line)) {
+                Some(ImplItem::Fn(impl_fn)) => {
+                    let mut impl_fn = impl_fn.clone();
+                    impl_fn.attrs.clear();
+                    Some((impl_fn.to_token_stream().to_string(), owner))
+                }
+                _ => Some((unit_key(item), owner)),
+            }
+        }
+        _ => Some((unit_key(item), item_name(item))),
+    }
+}
+
+/// Helper function to collect the code of the items of a file, as [`unit_at`] keys them
+fn collect_units(items: &[Item], units: &mut BTreeSet<String>) {
+    for item in items {
+        match item {
+            Item::Mod(item_mod) if item_mod.content.is_some() => {
+                if let Some((_, content)) = &item_mod.content {
+                    collect_units(content, units);
+                }
+            }
+            Item::Impl(item_impl) => {
+                units.insert(unit_key(item));
+                for impl_item in &item_impl.items {
+                    if let ImplItem::Fn(impl_fn) = impl_item {
+                        let mut impl_fn = impl_fn.clone();
+                        impl_fn.attrs.clear();
+                        units.insert(impl_fn.to_token_stream().to_string());
+                    }
+                }
+            }
+            _ => {
+                units.insert(unit_key(item));
+            }
+        }
+    }
+}
+
+/// Helper function to collect the location of the named declarations of a file (the first one
+/// of each name is kept)
+fn collect_declarations(items: &[Item], path: &Path, declarations: &mut HashMap<String, Location>) {
+    for item in items {
+        if let Item::Mod(item_mod) = item
+            && let Some((_, content)) = &item_mod.content
+        {
+            collect_declarations(content, path, declarations);
+        }
+        let Some(ident) = item_ident(item) else {
+            continue;
+        };
+        let span = item.span();
+        let location = Location {
+            file: path.to_string_lossy().to_string(),
+            line: span.start().line,
+            column: Some(span.start().column),
+            end_line: Some(span.end().line),
+            end_column: Some(span.end().column),
+        };
+        declarations.entry(ident.to_string()).or_insert(location);
+    }
+}
+
+/// Helper function to check if a node spans a line
+fn spans_line<T: Spanned>(node: &T, line: usize) -> bool {
+    let span = node.span();
+    span.start().line <= line && line <= span.end().line
+}
+
+/// Helper function to get the code of an item without its outer attributes
+fn unit_key(item: &Item) -> String {
+    let mut item = item.clone();
+    match &mut item {
+        Item::Const(item) => item.attrs.clear(),
+        Item::Enum(item) => item.attrs.clear(),
+        Item::Fn(item) => item.attrs.clear(),
+        Item::Impl(item) => item.attrs.clear(),
+        Item::Mod(item) => item.attrs.clear(),
+        Item::Static(item) => item.attrs.clear(),
+        Item::Struct(item) => item.attrs.clear(),
+        Item::Trait(item) => item.attrs.clear(),
+        Item::Type(item) => item.attrs.clear(),
+        Item::Union(item) => item.attrs.clear(),
+        Item::Use(item) => item.attrs.clear(),
+        _ => {}
+    }
+    item.to_token_stream().to_string()
+}
+
+/// Helper function to get the identifier of a named declaration
+fn item_ident(item: &Item) -> Option<&syn::Ident> {
+    match item {
+        Item::Const(item) => Some(&item.ident),
+        Item::Enum(item) => Some(&item.ident),
+        Item::Fn(item) => Some(&item.sig.ident),
+        Item::Mod(item) => Some(&item.ident),
+        Item::Static(item) => Some(&item.ident),
+        Item::Struct(item) => Some(&item.ident),
+        Item::Trait(item) => Some(&item.ident),
+        Item::Type(item) => Some(&item.ident),
+        Item::Union(item) => Some(&item.ident),
+        _ => None,
+    }
+}
+
+/// Helper function to get the name of a named declaration
+fn item_name(item: &Item) -> Option<String> {
+    item_ident(item).map(ToString::to_string)
+}
+
+/// Helper function to get the name of the type of an impl (`Withdraw` for `impl Accounts for Withdraw<'info>`)
+fn self_type_name(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(type_path) => type_path.path.segments.last().map(|segment| segment.ident.to_string()),
+        syn::Type::Reference(reference) => self_type_name(&reference.elem),
+        _ => None,
+    }
+}
@@ -0,0 +1,90 @@
+use crate::analyzer::dsl::query::MetadataValue;
+use crate::analyzer::expansion::{EXPANDED_LOCATION_KEY, expand_anchor_macros, map_findings};
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{Finding, Severity};
+use std::path::Path;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+use anchor_lang::prelude::*;
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[account]
+pub struct Vault {
+    pub total: u64,
+}
+"#;
+
+    fn finding(line: usize) -> Finding {
+        FindingBuilder::new("Generated code")
+            .severity(Severity::Low)
+            .at("vault.expanded.rs", line)
+            .build()
+    }
+
+    fn line_of(source: &str, pattern: &str) -> usize {
+        source.lines().position(|line| line.contains(pattern)).unwrap() + 1
+    }
+
+    #[test]
+    fn test_anchor_macros_are_expanded() {
+        let ast = syn::parse_file(SOURCE).unwrap();
+        let expanded = expand_anchor_macros(&ast).unwrap();
+        assert!(expanded.contains("impl<'info> anchor_lang::Accounts<'info, WithdrawBumps> for Withdraw<'info>"));
+        assert!(expanded.contains("instruction::Withdraw::DISCRIMINATOR"));
+        // Items without Anchor macros are kept as they are
+        assert!(expanded.contains("pub struct Vault"));
+
+        let plain = syn::parse_file("pub fn add(a: u64, b: u64) -> u64 { a + b }").unwrap();
+        assert!(expand_anchor_macros(&plain).is_none());
+    }
+
+    #[test]
+    fn test_expanded_findings_map_back_to_the_sources() {
+        let original = syn::parse_file(SOURCE).unwrap();
+        let source = expand_anchor_macros(&original).unwrap();
+        let expanded = syn::parse_file(&source).unwrap();
+
+        let try_accounts = line_of(&source, "fn try_accounts(");
+        let vault = line_of(&source, "pub struct Vault {");
+        let dispatch = line_of(&source, "fn dispatch");
+        let findings = map_findings(
+            vec![finding(try_accounts), finding(vault), finding(dispatch)],
+            &expanded,
+            &[(Path::new("programs/vault/src/lib.rs"), &original)],
+        );
+
+        // Unchanged code is left to the analysis of the sources
+        assert_eq!(findings.len(), 2, "{findings:#?}");
+
+        // Generated code points to the declaration it was generated for
+        assert_eq!(findings[0].location.file, "programs/vault/src/lib.rs");
+        assert_eq!(findings[0].location.line, line_of(SOURCE, "#[derive(Accounts)]"));
+        assert_eq!(
+            findings[0].metadata.get(EXPANDED_LOCATION_KEY),
+            Some(&MetadataValue::Text(format!("vault.expanded.rs:{try_accounts}")))
+        );
+
+        // Generated code without such a declaration stays in the expanded file
+        assert_eq!(findings[1].location.file, "vault.expanded.rs");
+        assert_eq!(findings[1].location.line, dispatch);
+    }
+}
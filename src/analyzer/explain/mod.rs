@@ -0,0 +1,183 @@
+use std::fmt::Write;
+
+use crate::analyzer::{RuleExample, RuleMetadata};
+
+#[cfg(test)]
+mod test;
+
+/// Documentation of a rule (`explain <rule-id>`): its description, rationale, code examples,
+/// recommendations, parameters and references
+pub struct RuleDoc<'a> {
+    rule: &'a RuleMetadata,
+}
+
+impl<'a> RuleDoc<'a> {
+    /// Creates the documentation of a rule
+    pub fn new(rule: &'a RuleMetadata) -> Self {
+        Self { rule }
+    }
+
+    /// Render the documentation as plain text, for the terminal
+    pub fn to_text(&self) -> String {
+        let rule = self.rule;
+        let mut text = format!("{} ({})\n", rule.title, rule.id);
+        for (name, value) in self.summary() {
+            let _ = writeln!(text, "{name}: {value}");
+        }
+
+        section(&mut text, "Description", &rule.description);
+        if let Some(rationale) = &rule.rationale {
+            section(&mut text, "Rationale", rationale);
+        }
+        for example in &rule.examples {
+            let _ = writeln!(text, "\n{}:", example_title(example));
+            for line in example.code.lines() {
+                let _ = writeln!(text, "    {line}");
+            }
+        }
+        list(&mut text, "Recommendations", &rule.recommendations);
+        let params: Vec<String> = rule
+            .params
+            .iter()
+            .map(|param| format!("{} (default: {}): {}", param.name, param.default, param.description))
+            .collect();
+        list(&mut text, "Parameters", &params);
+        list(&mut text, "References", &self.references());
+        text
+    }
+
+    /// Render the documentation as Markdown
+    pub fn to_markdown(&self) -> String {
+        let rule = self.rule;
+        let mut markdown = format!("# {}\n\n", rule.title);
+        let _ = writeln!(markdown, "| Rule | `{}` |\n| --- | --- |", rule.id);
+        for (name, value) in self.summary() {
+            let _ = writeln!(markdown, "| {name} | {value} |");
+        }
+
+        let _ = write!(markdown, "\n{}\n", rule.description);
+        if let Some(rationale) = &rule.rationale {
+            let _ = write!(markdown, "\n## Rationale\n\n{rationale}\n");
+        }
+        if !rule.examples.is_empty() {
+            markdown.push_str("\n## Examples\n");
+            for example in &rule.examples {
+                let _ = write!(markdown, "\n{}:\n\n```rust\n{}\n```\n", example_title(example), example.code);
+            }
+        }
+        if !rule.recommendations.is_empty() {
+            markdown.push_str("\n## Recommendations\n\n");
+            for recommendation in &rule.recommendations {
+                let _ = writeln!(markdown, "- {recommendation}");
+            }
+        }
+        if !rule.params.is_empty() {
+            markdown.push_str("\n## Parameters\n\n| Parameter | Default | Description |\n| --- | --- | --- |\n");
+            for param in &rule.params {
+                let _ = writeln!(markdown, "| `{}` | `{}` | {} |", param.name, param.default, param.description);
+            }
+        }
+        let references = self.references();
+        if !references.is_empty() {
+            markdown.push_str("\n## References\n\n");
+            for reference in references {
+                let _ = writeln!(markdown, "- {reference}");
+            }
+        }
+        markdown
+    }
+
+    /// Helper function to list the classification of the rule, by name
+    fn summary(&self) -> Vec<(&'static str, String)> {
+        let rule = self.rule;
+        let mut summary = vec![
+            ("Severity", rule.severity.as_str().to_string()),
+            ("Type", rule.rule_type.as_str().to_string()),
+        ];
+        if let Some(category) = rule.category {
+            summary.push(("Category", category.to_string()));
+        }
+        if !rule.cwe.is_empty() {
+            summary.push(("CWE", rule.cwe.iter().map(|cwe| format!("CWE-{cwe}")).collect::<Vec<_>>().join(", ")));
+        }
+        if !rule.tags.is_empty() {
+            summary.push(("Tags", rule.tags.join(", ")));
+        }
+        if !rule.packs.is_empty() {
+            summary.push(("Packs", rule.packs.join(", ")));
+        }
+        if let Some(versions) = &rule.anchor_versions {
+            summary.push(("Anchor versions", versions.clone()));
+        }
+        if rule.project {
+            summary.push(("Scope", "whole program".to_string()));
+        }
+        summary.push(("Enabled", if rule.enabled { "yes" } else { "no (filtered out by the configuration)" }.to_string()));
+        summary
+    }
+
+    /// Helper function to list the references of the rule, then the pages of its CWE IDs
+    fn references(&self) -> Vec<String> {
+        let mut references = self.rule.references.clone();
+        for cwe in &self.rule.cwe {
+            let url = format!("https://cwe.mitre.org/data/definitions/{cwe}.html");
+            if !references.contains(&url) {
+                references.push(url);
+            }
+        }
+        references
+    }
+}
+
+/// IDs of the rules whose ID is close to `id`, the closest first, to suggest them when no
+/// rule has this ID
+pub fn similar_ids<'a>(id: &str, ids: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut similar: Vec<(usize, &str)> = ids
+        .into_iter()
+        .filter_map(|candidate| {
+            let distance = edit_distance(id, candidate);
+            (distance <= 3 || candidate.contains(id) || id.contains(candidate)).then_some((distance, candidate))
+        })
+        .collect();
+    similar.sort();
+    similar.into_iter().map(|(_, candidate)| candidate).take(3).collect()
+}
+
+/// Helper function to compute the Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Helper function to name an example
+fn example_title(example: &RuleExample) -> &'static str {
+    if example.safe { "Safe" } else { "Vulnerable" }
+}
+
+/// Helper function to write a titled paragraph of the text documentation
+fn section(text: &mut String, title: &str, body: &str) {
+    let _ = write!(text, "\n{title}:\n");
+    for line in body.lines() {
+        let _ = writeln!(text, "  {line}");
+    }
+}
+
+/// Helper function to write a titled list of the text documentation, if it is not empty
+fn list(text: &mut String, title: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    let _ = write!(text, "\n{title}:\n");
+    for item in items {
+        let _ = writeln!(text, "  - {item}");
+    }
+}
@@ -0,0 +1,64 @@
+use crate::analyzer::RuleEngine;
+use crate::analyzer::dsl::ProgramContext;
+use crate::analyzer::explain::{RuleDoc, similar_ids};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine() -> RuleEngine {
+        let mut engine = RuleEngine::default();
+        engine.load_builtin_rules().unwrap();
+        engine
+    }
+
+    #[test]
+    fn test_rule_doc() {
+        let rule = engine().registry().metadata_of("solana-division-by-zero").unwrap();
+        let doc = RuleDoc::new(&rule);
+
+        let text = doc.to_text();
+        assert!(text.starts_with("Division Without Zero Check (solana-division-by-zero)\nSeverity: medium\nType: solana\n"), "{text}");
+        assert!(text.contains("Category: arithmetic\nCWE: CWE-369\n"), "{text}");
+        assert!(text.contains("\nRationale:\n  Integer division by zero panics"), "{text}");
+        assert!(text.contains("\nVulnerable:\n    pub fn share(amount: u64, total_shares: u64) -> u64 {\n        amount / total_shares\n    }\n"), "{text}");
+        assert!(text.contains("\nSafe:\n    pub fn share("), "{text}");
+        assert!(text.contains("\nRecommendations:\n  - Add explicit zero checks"), "{text}");
+        assert!(text.contains("\nReferences:\n  - https://cwe.mitre.org/data/definitions/369.html\n"), "{text}");
+        assert!(!text.contains("Parameters:"), "{text}");
+
+        let markdown = doc.to_markdown();
+        assert!(markdown.starts_with("# Division Without Zero Check\n\n| Rule | `solana-division-by-zero` |\n| --- | --- |\n| Severity | medium |\n"), "{markdown}");
+        assert!(markdown.contains("\n## Rationale\n\n"), "{markdown}");
+        assert!(markdown.contains("\nVulnerable:\n\n```rust\npub fn share(amount: u64, total_shares: u64) -> u64 {\n    amount / total_shares\n}\n```\n"), "{markdown}");
+
+        // Parameters, with their defaults
+        let rule = engine().registry().metadata_of("missing-signer-check").unwrap();
+        assert!(RuleDoc::new(&rule).to_text().contains("\nParameters:\n  - authority_names (default: "));
+        assert!(RuleDoc::new(&rule).to_markdown().contains("| `authority_names` | `"));
+    }
+
+    #[test]
+    fn test_builtin_examples_are_reported() {
+        let engine = engine();
+        for rule in engine.registry().metadata() {
+            assert!(rule.rationale.is_some(), "{} has no rationale", rule.id);
+            let implementation = engine.registry().get(&rule.id).unwrap();
+            for example in &rule.examples {
+                let ast = syn::parse_file(&example.code).unwrap_or_else(|e| panic!("{} example: {e}", rule.id));
+                // Checks are resolved across the program, as when the project is analyzed
+                let program = ProgramContext::build([("src/lib.rs", &ast)]);
+                let findings = implementation.execute_in_program(&ast, "src/lib.rs", &example.code, &program).unwrap();
+                assert_eq!(findings.is_empty(), example.safe, "{} example:\n{}", rule.id, example.code);
+            }
+        }
+    }
+
+    #[test]
+    fn test_similar_ids() {
+        let ids = ["missing-signer-check", "native-missing-signer-check", "solana-division-by-zero"];
+        assert_eq!(similar_ids("missing-signer", ids), ["missing-signer-check", "native-missing-signer-check"]);
+        assert_eq!(similar_ids("solana-divison-by-zero", ids), ["solana-division-by-zero"]);
+        assert!(similar_ids("reentrancy", ids).is_empty());
+    }
+}
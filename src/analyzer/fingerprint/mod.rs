@@ -0,0 +1,138 @@
+use quote::ToTokens;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use syn::spanned::Spanned;
+use syn::{File, ImplItem, Item, TraitItem};
+
+use crate::analyzer::Finding;
+
+#[cfg(test)]
+mod test;
+
+/// Compute the fingerprints of the findings of a file
+///
+/// A fingerprint identifies a finding from its content: the rules that reported it, the file
+/// (relative to `root`), the enclosing item and the whitespace-normalized snippet (or the
+/// description without snippet). It does not change when unrelated edits shift the lines of the
+/// finding. Findings with the same content in a file are told apart by their order.
+pub fn assign_fingerprints(findings: &mut [Finding], file_path: &str, ast: Option<&File>, root: Option<&Path>) {
+    assign_fingerprints_with(findings, file_path, |line| ast.and_then(|ast| enclosing_item(ast, line)), root);
+}
+
+/// Compute the fingerprints of the findings of a file like [`assign_fingerprints`], with the
+/// enclosing item of each line given by `item_of` (e.g. found before the AST was dropped)
+pub fn assign_fingerprints_with<F>(findings: &mut [Finding], file_path: &str, item_of: F, root: Option<&Path>)
+where
+    F: Fn(usize) -> Option<String>,
+{
+    let file = relative_path(file_path, root);
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+
+    for finding in findings.iter_mut().filter(|finding| finding.location.file == file_path) {
+        let item = item_of(finding.location.line).unwrap_or_default();
+        let key = content_key(finding, &file, item);
+        let occurrence = occurrences.entry(key.clone()).or_insert(0);
+        *occurrence += 1;
+        finding.fingerprint = Some(hash(&key, *occurrence));
+    }
+}
+
+/// Returns the fingerprint of a finding, computed from its content without the enclosing item
+/// if the analyzer did not assign one (e.g. findings built by another tool)
+pub fn fingerprint_of(finding: &Finding, root: Option<&Path>) -> String {
+    match &finding.fingerprint {
+        Some(fingerprint) => fingerprint.clone(),
+        None => hash(&content_key(finding, &relative_path(&finding.location.file, root), String::new()), 1),
+    }
+}
+
+/// Helper function to build the content a fingerprint is computed from
+fn content_key(finding: &Finding, file: &str, item: String) -> String {
+    let content = match &finding.code_snippet {
+        Some(snippet) => normalize(snippet),
+        None => normalize(&finding.description),
+    };
+    let mut rule_ids = finding.rule_ids();
+    rule_ids.sort();
+
+    [rule_ids.join(","), file.to_string(), item, content].join("\n")
+}
+
+/// Find the path of the innermost item containing a line (e.g. `mod vault::impl Vault::fn withdraw`)
+pub fn enclosing_item(ast: &File, line: usize) -> Option<String> {
+    let mut path = Vec::new();
+    enclosing_items(&ast.items, line, &mut path);
+    (!path.is_empty()).then(|| path.join("::"))
+}
+
+/// Helper function to collect the items containing a line, from the outermost one
+fn enclosing_items(items: &[Item], line: usize, path: &mut Vec<String>) {
+    let Some(item) = items.iter().find(|item| contains_line(*item, line)) else {
+        return;
+    };
+
+    match item {
+        Item::Fn(item_fn) => path.push(format!("fn {}", item_fn.sig.ident)),
+        Item::Struct(item_struct) => path.push(format!("struct {}", item_struct.ident)),
+        Item::Enum(item_enum) => path.push(format!("enum {}", item_enum.ident)),
+        Item::Const(item_const) => path.push(format!("const {}", item_const.ident)),
+        Item::Static(item_static) => path.push(format!("static {}", item_static.ident)),
+        Item::Mod(item_mod) => {
+            path.push(format!("mod {}", item_mod.ident));
+            if let Some((_, items)) = &item_mod.content {
+                enclosing_items(items, line, path);
+            }
+        }
+        Item::Trait(item_trait) => {
+            path.push(format!("trait {}", item_trait.ident));
+            if let Some(TraitItem::Fn(method)) = item_trait.items.iter().find(|method| contains_line(*method, line)) {
+                path.push(format!("fn {}", method.sig.ident));
+            }
+        }
+        Item::Impl(item_impl) => {
+            let self_ty = normalize(&item_impl.self_ty.to_token_stream().to_string());
+            match &item_impl.trait_ {
+                Some((_, trait_path, _)) => {
+                    path.push(format!("impl {} for {self_ty}", normalize(&trait_path.to_token_stream().to_string())));
+                }
+                None => path.push(format!("impl {self_ty}")),
+            }
+            if let Some(ImplItem::Fn(method)) = item_impl.items.iter().find(|method| contains_line(*method, line)) {
+                path.push(format!("fn {}", method.sig.ident));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Helper function to check if the span of a node contains a line
+fn contains_line<T: Spanned>(node: &T, line: usize) -> bool {
+    let span = node.span();
+    span.start().line <= line && line <= span.end().line
+}
+
+/// Identify a file by its path relative to the project root, with `/` separators
+pub fn relative_path(file_path: &str, root: Option<&Path>) -> String {
+    let path = Path::new(file_path);
+    let path = root.and_then(|root| path.strip_prefix(root).ok()).unwrap_or(path);
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Helper function to collapse the whitespace of a text
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Helper function to hash the content of a finding and its occurrence (32 hex characters)
+fn hash(key: &str, occurrence: usize) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(key.as_bytes());
+    if occurrence > 1 {
+        hasher.update(format!("\n#{occurrence}").as_bytes());
+    }
+    hasher.finalize()[..16].iter().map(|byte| format!("{byte:02x}")).collect()
+}
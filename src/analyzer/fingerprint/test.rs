@@ -0,0 +1,84 @@
+use crate::analyzer::fingerprint::{assign_fingerprints, enclosing_item, fingerprint_of};
+use crate::analyzer::Finding;
+use crate::analyzer::testing::FindingBuilder;
+use std::path::Path;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+#[program]
+pub mod vault {
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64, shares: u64) -> Result<()> {
+        let value = amount / shares;
+        Ok(())
+    }
+}
+
+impl Default for Vault {
+    fn default() -> Self {
+        Self { total: 0 }
+    }
+}
+"#;
+
+    fn finding(line: usize, snippet: &str) -> Finding {
+        FindingBuilder::new("Division Without Zero Check")
+            .span("/project/src/lib.rs", line, 8, 30)
+            .snippet(snippet)
+            .rule_id("solana-division-by-zero")
+            .build()
+    }
+
+    fn fingerprints(source: &str, findings: &mut [Finding], root: &str) -> Vec<String> {
+        let ast = syn::parse_file(source).unwrap();
+        assign_fingerprints(findings, "/project/src/lib.rs", Some(&ast), Some(Path::new(root)));
+        findings.iter().map(|finding| finding.fingerprint.clone().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_enclosing_item() {
+        let ast = syn::parse_file(SOURCE).unwrap();
+        assert_eq!(enclosing_item(&ast, 5).as_deref(), Some("mod vault::fn withdraw"));
+        assert_eq!(enclosing_item(&ast, 11).as_deref(), Some("impl Default for Vault::fn default"));
+        assert_eq!(enclosing_item(&ast, 1), None);
+    }
+
+    #[test]
+    fn test_fingerprints_survive_line_shifts() {
+        let mut before = [finding(5, "let value = amount / shares;")];
+        let before = fingerprints(SOURCE, &mut before, "/project");
+
+        // Unrelated lines added above, and the snippet reformatted
+        let shifted = format!("use anchor_lang::prelude::*;\n\n{SOURCE}");
+        let mut after = [finding(7, "let value =\n    amount / shares;")];
+        assert_eq!(fingerprints(&shifted, &mut after, "/project"), before);
+
+        // Same code in another item, or moved to another root: a different finding
+        let mut other = [finding(11, "let value = amount / shares;")];
+        assert_ne!(fingerprints(SOURCE, &mut other, "/project"), before);
+        let mut rooted = [finding(5, "let value = amount / shares;")];
+        assert_ne!(fingerprints(SOURCE, &mut rooted, "/project/src"), before);
+    }
+
+    #[test]
+    fn test_identical_findings_get_distinct_fingerprints() {
+        let mut findings = [finding(5, "amount / shares"), finding(5, "amount / shares")];
+        let fingerprints = fingerprints(SOURCE, &mut findings, "/project");
+        assert_ne!(fingerprints[0], fingerprints[1]);
+        assert_eq!(fingerprints[0].len(), 32);
+    }
+    #[test]
+    fn test_fingerprint_of() {
+        let mut findings = [finding(5, "amount / shares")];
+        let assigned = fingerprints(SOURCE, &mut findings, "/project");
+        assert_eq!(fingerprint_of(&findings[0], Some(Path::new("/project"))), assigned[0]);
+
+        // Computed from the content when there is none, the same from run to run
+        let unassigned = finding(5, "amount / shares");
+        let computed = fingerprint_of(&unassigned, Some(Path::new("/project")));
+        assert_eq!(computed.len(), 32);
+        assert_eq!(fingerprint_of(&finding(9, "amount  /  shares"), Some(Path::new("/project"))), computed);
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::analyzer::results::Sources;
+use crate::analyzer::{Applicability, Finding, Fix, TextEdit};
+
+#[cfg(test)]
+mod test;
+
+/// Lines of a file changed by a fix
+///
+/// Whole lines are shown: the lines the edits touch, then the same lines once the edits are
+/// applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixDiff {
+    /// First changed line (1-indexed)
+    pub line: usize,
+    /// Lines before the fix
+    pub removed: Vec<String>,
+    /// Lines after the fix
+    pub added: Vec<String>,
+}
+
+impl FixDiff {
+    /// Returns the diff as a unified diff hunk (`@@ -5,1 +5,2 @@` and the lines)
+    pub fn to_unified(&self) -> String {
+        self.unified(self.line)
+    }
+
+    /// Helper function to render the hunk, the changed lines starting at `new_line` once the
+    /// hunks before it are applied
+    fn unified(&self, new_line: usize) -> String {
+        let mut diff = format!("@@ -{},{} +{},{} @@\n", self.line, self.removed.len(), new_line, self.added.len());
+        for line in &self.removed {
+            diff.push_str(&format!("-{line}\n"));
+        }
+        for line in &self.added {
+            diff.push_str(&format!("+{line}\n"));
+        }
+        diff
+    }
+}
+
+/// Label of a fix in the reports, telling whether it can be applied without review
+pub fn label(fix: &Fix) -> &'static str {
+    match fix.applicability {
+        Applicability::MachineApplicable => "Suggested fix (machine-applicable)",
+        Applicability::Advisory => "Suggested fix (advisory, review before applying)",
+    }
+}
+
+/// Compute the lines of `file` (its `lines`) changed by the edits of a fix on this file
+///
+/// Returns `None` if the fix does not edit the file, or if its edits overlap or fall outside
+/// the file (the file changed since the analysis).
+pub fn diff(lines: &[String], fix: &Fix, file: &str) -> Option<FixDiff> {
+    let edits: Vec<&TextEdit> = fix.edits.iter().filter(|edit| edit.location.file == file).collect();
+    lines_diff(lines, &edits)
+}
+
+/// Helper function to compute the lines changed by edits of the same file
+fn lines_diff(lines: &[String], edits: &[&TextEdit]) -> Option<FixDiff> {
+    let first = edits.iter().map(|edit| edit.location.line).min()?;
+    let last = edits.iter().map(|edit| edit.location.end_line.unwrap_or(edit.location.line)).max()?;
+    if first == 0 || last > lines.len() {
+        return None;
+    }
+
+    // Edits are applied to the changed lines only, their lines shifted accordingly
+    let removed = lines[first - 1..last].to_vec();
+    let shifted: Vec<TextEdit> = edits
+        .iter()
+        .map(|edit| {
+            let mut edit = (*edit).clone();
+            edit.location.line -= first - 1;
+            edit.location.end_line = edit.location.end_line.map(|line| line - (first - 1));
+            edit
+        })
+        .collect();
+    let fixed = apply(&removed.join("\n"), &shifted)?;
+    Some(FixDiff { line: first, removed, added: fixed.split('\n').map(str::to_string).collect() })
+}
+
+/// Fix planned for a finding (`--fix`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedFix {
+    /// Rule that reported the finding
+    pub rule_id: String,
+    /// File of the finding
+    pub file: String,
+    /// Line of the finding
+    pub line: usize,
+    /// Fix of the finding
+    pub fix: Fix,
+}
+
+/// Machine-applicable fix left out of a plan, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedFix {
+    /// Fix that is not applied
+    pub fix: PlannedFix,
+    /// Why the fix is not applied
+    pub reason: String,
+}
+
+/// File rewritten by the fixes of a plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedFile {
+    /// Path of the file, as in the findings
+    pub path: String,
+    /// Source of the file before the fixes
+    pub original: String,
+    /// Source of the file once the fixes are applied
+    pub fixed: String,
+    /// Edits of the fixes on the file
+    pub edits: Vec<TextEdit>,
+}
+
+impl FixedFile {
+    /// Returns the changes of the file as a unified diff (`--- path`, `+++ path` and the hunks)
+    ///
+    /// The lines keep their `\r`, so the diff of a file with CRLF line endings has them too.
+    pub fn to_unified(&self) -> String {
+        let lines: Vec<String> = self
+            .original
+            .split_inclusive('\n')
+            .map(|line| line.strip_suffix('\n').unwrap_or(line).to_string())
+            .collect();
+        let mut edits: Vec<&TextEdit> = self.edits.iter().collect();
+        edits.sort_by_key(|edit| (edit.location.line, edit.location.column));
+
+        // Edits touching the same lines make one hunk
+        let mut groups: Vec<(usize, Vec<&TextEdit>)> = Vec::new();
+        for edit in edits {
+            let end_line = edit.location.end_line.unwrap_or(edit.location.line);
+            match groups.last_mut() {
+                Some((last, group)) if edit.location.line <= *last => {
+                    *last = (*last).max(end_line);
+                    group.push(edit);
+                }
+                _ => groups.push((end_line, vec![edit])),
+            }
+        }
+
+        let mut diff = format!("--- {}\n+++ {}\n", self.path, self.path);
+        let mut shift: isize = 0;
+        for (_, group) in groups {
+            let Some(hunk) = lines_diff(&lines, &group) else {
+                continue;
+            };
+            diff.push_str(&hunk.unified(hunk.line.saturating_add_signed(shift)));
+            shift += hunk.added.len() as isize - hunk.removed.len() as isize;
+        }
+        diff
+    }
+}
+
+/// Machine-applicable fixes of the findings of an analysis, and the files they rewrite
+/// (`--fix`, `--fix-dry-run`)
+///
+/// The machine-applicable fixes of the findings are planned in order (a finding reported by
+/// several rules has the fixes of each of them). A fix is applied whole or not at all: it is
+/// skipped when one of its edits overlaps the edit of a fix planned before (identical edits,
+/// suggested by several findings, are applied once), or when a file would no longer parse once
+/// it is applied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixPlan {
+    /// Fixes applied
+    pub applied: Vec<PlannedFix>,
+    /// Fixes left out
+    pub skipped: Vec<SkippedFix>,
+    /// Files rewritten, by path
+    pub files: Vec<FixedFile>,
+}
+
+impl FixPlan {
+    /// Plan the fixes of findings, reading their files from `sources` or else from the disk
+    pub fn new(findings: &[Finding], sources: Option<&Sources>) -> Self {
+        let mut plan = FixPlan::default();
+        // Source and planned edits of each file
+        let mut files: BTreeMap<String, (String, Vec<TextEdit>)> = BTreeMap::new();
+
+        let fixes = findings.iter().flat_map(|finding| {
+            finding.fixes.iter().filter(|fix| fix.applicability == Applicability::MachineApplicable).map(move |fix| (finding, fix))
+        });
+        for (finding, fix) in fixes {
+            let planned = PlannedFix {
+                rule_id: finding.rule().map(|rule| rule.id.clone()).unwrap_or_default(),
+                file: finding.location.file.clone(),
+                line: finding.location.line,
+                fix: fix.clone(),
+            };
+
+            let mut changes: BTreeMap<String, Vec<TextEdit>> = BTreeMap::new();
+            let mut reason = None;
+            for edit in &fix.edits {
+                let path = &edit.location.file;
+                if !files.contains_key(path) {
+                    let source = match sources.and_then(|sources| sources.get(path)) {
+                        Some(source) => Ok(source.clone()),
+                        None => std::fs::read_to_string(path),
+                    };
+                    match source {
+                        Ok(source) => files.insert(path.clone(), (source, Vec::new())),
+                        Err(e) => {
+                            reason = Some(format!("cannot read {path}: {e}"));
+                            break;
+                        }
+                    };
+                }
+                let edits = changes.entry(path.clone()).or_insert_with(|| files[path].1.clone());
+                if !edits.contains(edit) {
+                    edits.push(edit.clone());
+                }
+            }
+            let reason = reason.or_else(|| {
+                changes.iter().find_map(|(path, edits)| match apply(&files[path].0, edits) {
+                    None => Some(format!("its edits overlap another fix in {path}")),
+                    Some(fixed) if syn::parse_file(&fixed).is_err() => Some(format!("{path} would no longer parse")),
+                    Some(_) => None,
+                })
+            });
+
+            match reason {
+                Some(reason) => plan.skipped.push(SkippedFix { fix: planned, reason }),
+                None => {
+                    for (path, edits) in changes {
+                        files.get_mut(&path).expect("file read").1 = edits;
+                    }
+                    plan.applied.push(planned);
+                }
+            }
+        }
+
+        plan.files = files
+            .into_iter()
+            .filter(|(_, (_, edits))| !edits.is_empty())
+            .filter_map(|(path, (original, edits))| {
+                let fixed = apply(&original, &edits)?;
+                Some(FixedFile { path, original, fixed, edits })
+            })
+            .collect();
+        plan
+    }
+
+    /// Returns the changes of the plan as a unified diff
+    pub fn to_unified_diff(&self) -> String {
+        self.files.iter().map(FixedFile::to_unified).collect()
+    }
+
+    /// Rewrite the files with their fixes
+    pub fn write(&self) -> std::io::Result<()> {
+        for file in &self.files {
+            std::fs::write(&file.path, &file.fixed)
+                .map_err(|e| std::io::Error::new(e.kind(), format!("cannot write {}: {e}", file.path)))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the applied fixes whose findings are still reported by the analysis of the fixed
+    /// files (`after`), by rule and file
+    ///
+    /// Lines move with the fixes, so findings are counted by rule and file: of the fixes of a
+    /// rule in a file, as many as the findings that went away are resolved.
+    pub fn unresolved(&self, before: &[Finding], after: &[Finding]) -> BTreeMap<(String, String), usize> {
+        let count = |findings: &[Finding], rule_id: &str, file: &str| {
+            findings
+                .iter()
+                .filter(|finding| finding.rule().is_some_and(|rule| rule.id == rule_id) && finding.location.file == file)
+                .count()
+        };
+        // Findings with fixes applied, once for the several fixes of a finding
+        let mut fixed = BTreeSet::new();
+        let mut applied: BTreeMap<(String, String), usize> = BTreeMap::new();
+        for fix in &self.applied {
+            if fixed.insert((&fix.rule_id, &fix.file, fix.line)) {
+                *applied.entry((fix.rule_id.clone(), fix.file.clone())).or_default() += 1;
+            }
+        }
+        applied
+            .into_iter()
+            .filter_map(|((rule_id, file), fixes)| {
+                let resolved = count(before, &rule_id, &file).saturating_sub(count(after, &rule_id, &file));
+                let unresolved = fixes.saturating_sub(resolved);
+                (unresolved > 0).then_some(((rule_id, file), unresolved))
+            })
+            .collect()
+    }
+}
+
+/// Apply edits to a source
+///
+/// Lines are 1-indexed and columns count characters from 0, like the locations of the parser;
+/// an edit without columns replaces whole lines. Returns `None` if the edits overlap or fall
+/// outside the source.
+pub fn apply(source: &str, edits: &[TextEdit]) -> Option<String> {
+    let mut ranges = Vec::new();
+    for edit in edits {
+        let location = &edit.location;
+        let end_line = location.end_line.unwrap_or(location.line);
+        let start = offset(source, location.line, location.column.unwrap_or(0))?;
+        let end = match location.end_column {
+            Some(column) => offset(source, end_line, column)?,
+            None => offset(source, end_line, usize::MAX)?,
+        };
+        if end < start {
+            return None;
+        }
+        ranges.push((start, end, edit.replacement.as_str()));
+    }
+    ranges.sort_by_key(|&(start, end, _)| (start, end));
+    if ranges.windows(2).any(|pair| pair[0].1 > pair[1].0) {
+        return None;
+    }
+
+    let mut fixed = source.to_string();
+    for (start, end, replacement) in ranges.into_iter().rev() {
+        fixed.replace_range(start..end, replacement);
+    }
+    Some(fixed)
+}
+
+/// Helper function to convert a line (1-indexed) and a column (characters from 0, clamped to
+/// the end of the line) to a byte offset
+fn offset(source: &str, line: usize, column: usize) -> Option<usize> {
+    let start = match line {
+        0 => return None,
+        1 => 0,
+        _ => source.match_indices('\n').nth(line - 2)?.0 + 1,
+    };
+    let text = source[start..].split('\n').next().unwrap_or_default();
+    let byte = text.char_indices().nth(column).map_or(text.len(), |(byte, _)| byte);
+    Some(start + byte)
+}
@@ -0,0 +1,125 @@
+use crate::analyzer::fixes::{FixPlan, apply, diff};
+use crate::analyzer::results::Sources;
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{Confidence, Finding, Fix, Location, TextEdit};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(line: usize, column: usize, end_line: usize, end_column: usize, replacement: &str) -> TextEdit {
+        TextEdit {
+            location: Location::new_precise("src/lib.rs".to_string(), line, Some(column), Some(end_line), Some(end_column)),
+            replacement: replacement.to_string(),
+        }
+    }
+
+    fn finding(rule_id: &str, line: usize, fixes: Vec<Fix>) -> Finding {
+        FindingBuilder::new(format!("{rule_id} at line {line}"))
+            .confidence(Confidence::Medium)
+            .at("src/lib.rs", line)
+            .fixes(fixes)
+            .rule_id(rule_id)
+            .build()
+    }
+
+    #[test]
+    fn test_apply() {
+        let source = "let rate = amount / shares;\nlet fee = amount / 100;\n";
+
+        let fixed = apply(source, &[edit(1, 11, 1, 26, "amount.checked_div(shares)?"), edit(2, 4, 2, 7, "fee_amount")]);
+        assert_eq!(fixed.as_deref(), Some("let rate = amount.checked_div(shares)?;\nlet fee_amount = amount / 100;\n"));
+
+        // Insertions, and columns counted in characters
+        assert_eq!(apply("é = 1", &[edit(1, 1, 1, 1, "x")]).as_deref(), Some("éx = 1"));
+
+        // Overlapping edits and edits outside the source
+        assert_eq!(apply(source, &[edit(1, 4, 1, 8, "a"), edit(1, 6, 1, 10, "b")]), None);
+        assert_eq!(apply(source, &[edit(4, 0, 4, 1, "a")]), None);
+    }
+
+    #[test]
+    fn test_diff() {
+        let lines: Vec<String> =
+            ["pub struct Withdraw<'info> {", "    pub authority: AccountInfo<'info>,", "}"].map(str::to_string).to_vec();
+        let fix = Fix::machine_applicable("Add the signer constraint", vec![edit(2, 4, 2, 4, "#[account(signer)]\n    ")]);
+
+        let changes = diff(&lines, &fix, "src/lib.rs").unwrap();
+        assert_eq!(
+            changes.to_unified(),
+            "@@ -2,1 +2,2 @@\n\
+             -    pub authority: AccountInfo<'info>,\n\
+             +    #[account(signer)]\n\
+             +    pub authority: AccountInfo<'info>,\n"
+        );
+
+        assert_eq!(diff(&lines, &fix, "src/other.rs"), None);
+    }
+
+    #[test]
+    fn test_fix_plan() {
+        let source = "pub fn rate(amount: u64, shares: u64) -> u64 {\n    amount / shares\n}\n\npub struct Vault {\n    pub authority: u64,\n}\n";
+        let sources = Sources::from([("src/lib.rs".to_string(), source.to_string())]);
+        let checked_div = Fix::machine_applicable("Use checked_div", vec![edit(2, 4, 2, 19, "amount.checked_div(shares).unwrap()")]);
+        let signer = Fix::machine_applicable("Add the signer constraint", vec![edit(6, 4, 6, 4, "#[account(signer)]\n    ")]);
+        let findings = vec![
+            finding("solana-division-by-zero", 2, vec![Fix::new("Advisory", vec![edit(2, 4, 2, 10, "x")]), checked_div.clone()]),
+            // The same edit, suggested by another finding, is applied once
+            finding("solana-division-by-zero-duplicate", 2, vec![checked_div]),
+            // Overlaps the first fix
+            finding("solana-overflow", 2, vec![Fix::machine_applicable("Use checked_mul", vec![edit(2, 4, 2, 10, "x")])]),
+            // Leaves the file unparseable
+            finding("broken", 5, vec![Fix::machine_applicable("Break", vec![edit(5, 0, 5, 3, "pub pub")])]),
+            finding("missing-signer-check", 6, vec![signer]),
+            finding("advisory", 1, vec![Fix::new("Advisory", vec![edit(1, 0, 1, 3, "")])]),
+        ];
+
+        let plan = FixPlan::new(&findings, Some(&sources));
+        let applied: Vec<_> = plan.applied.iter().map(|fix| fix.rule_id.as_str()).collect();
+        assert_eq!(applied, ["solana-division-by-zero", "solana-division-by-zero-duplicate", "missing-signer-check"]);
+        let skipped: Vec<_> = plan.skipped.iter().map(|fix| (fix.fix.rule_id.as_str(), fix.reason.as_str())).collect();
+        assert_eq!(
+            skipped,
+            [("solana-overflow", "its edits overlap another fix in src/lib.rs"), ("broken", "src/lib.rs would no longer parse")]
+        );
+
+        assert_eq!(plan.files.len(), 1);
+        assert_eq!(plan.files[0].edits.len(), 2);
+        assert!(plan.files[0].fixed.contains("    amount.checked_div(shares).unwrap()\n"), "{}", plan.files[0].fixed);
+
+        // The hunks after an added line are shifted
+        assert_eq!(
+            plan.to_unified_diff(),
+            "--- src/lib.rs\n+++ src/lib.rs\n\
+             @@ -2,1 +2,1 @@\n\
+             -    amount / shares\n\
+             +    amount.checked_div(shares).unwrap()\n\
+             @@ -6,1 +6,2 @@\n\
+             -    pub authority: u64,\n\
+             +    #[account(signer)]\n\
+             +    pub authority: u64,\n"
+        );
+
+        // Findings still reported once the fixes are applied
+        let after = vec![finding("missing-signer-check", 7, Vec::new())];
+        let unresolved = plan.unresolved(&findings, &after);
+        assert_eq!(unresolved.into_iter().collect::<Vec<_>>(), [(("missing-signer-check".to_string(), "src/lib.rs".to_string()), 1)]);
+        assert!(plan.unresolved(&findings, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_fix_plan_keeps_crlf_line_endings() {
+        let source = "pub fn rate(amount: u64, shares: u64) -> u64 {\r\n    amount / shares\r\n}\r\n";
+        let sources = Sources::from([("src/lib.rs".to_string(), source.to_string())]);
+        let checked_div = Fix::machine_applicable("Use checked_div", vec![edit(2, 4, 2, 19, "amount.checked_div(shares).unwrap()")]);
+
+        let plan = FixPlan::new(&[finding("solana-division-by-zero", 2, vec![checked_div])], Some(&sources));
+        assert_eq!(
+            plan.to_unified_diff(),
+            "--- src/lib.rs\n+++ src/lib.rs\n\
+             @@ -2,1 +2,1 @@\n\
+             -    amount / shares\r\n\
+             +    amount.checked_div(shares).unwrap()\r\n"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::analyzer::{AnalysisResult, Finding, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// GitHub Actions annotations of an analysis (`--format github`)
+///
+/// Each finding is a workflow command printed to the job log, which GitHub shows inline on the
+/// pull request:
+///
+/// ```text
+/// ::error file=programs/vault/src/lib.rs,line=12,endLine=14,col=5,endColumn=6,title=Missing Signer Check::...
+/// ```
+///
+/// High findings are errors, medium findings warnings, and the others notices (see [`level`]).
+#[derive(Debug)]
+pub struct GithubReport<'a> {
+    result: &'a AnalysisResult,
+    base: &'a Path,
+}
+
+impl<'a> GithubReport<'a> {
+    /// Creates the annotations of an analysis, with the paths relative to `base` (the root of
+    /// the repository, where the workflow runs)
+    pub fn new(result: &'a AnalysisResult, base: &'a Path) -> Self {
+        Self { result, base }
+    }
+
+    /// Returns the workflow commands, one per line
+    pub fn to_annotations(&self) -> String {
+        self.result.findings.iter().map(|finding| self.annotation(finding)).collect()
+    }
+
+    fn annotation(&self, finding: &Finding) -> String {
+        let location = &finding.location;
+        let path = Path::new(&location.file);
+        let file = path.strip_prefix(self.base).unwrap_or(path);
+        let file = file.strip_prefix(".").unwrap_or(file);
+
+        let mut properties = format!("file={},line={}", property(&file.to_string_lossy()), location.line);
+        if let Some(end_line) = location.end_line {
+            let _ = write!(properties, ",endLine={end_line}");
+        }
+        // Columns only apply to annotations on a single line. The parser's columns start at 0,
+        // GitHub's at 1.
+        if location.end_line.is_none_or(|end_line| end_line == location.line) {
+            if let Some(column) = location.column {
+                let _ = write!(properties, ",col={}", column + 1);
+            }
+            if let Some(end_column) = location.end_column {
+                let _ = write!(properties, ",endColumn={}", end_column + 1);
+            }
+        }
+        if let Some(rule) = finding.rule() {
+            let _ = write!(properties, ",title={}", property(&rule.title));
+        }
+
+        let mut message = finding.description.clone();
+        if !finding.rules.is_empty() {
+            let _ = write!(message, " [{}]", finding.rule_ids().join(", "));
+        }
+        format!("::{} {properties}::{}\n", level(&finding.severity), data(&message))
+    }
+}
+
+/// Annotation level of a severity: `error`, `warning` or `notice`
+pub fn level(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Informational => "notice",
+    }
+}
+
+/// Escape the message of a workflow command
+fn data(value: &str) -> String {
+    value.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escape a property of a workflow command
+fn property(value: &str) -> String {
+    data(value).replace(':', "%3A").replace(',', "%2C")
+}
@@ -0,0 +1,59 @@
+use std::path::Path;
+
+use crate::analyzer::github::{GithubReport, level};
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Finding, Location, RuleInfo, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(severity: Severity, location: Location, description: &str) -> Finding {
+        FindingBuilder::new(description)
+            .severity(severity)
+            .location(location)
+            .rule(RuleInfo {
+                id: "missing-signer-check".to_string(),
+                title: "Missing Signer Check: authority".to_string(),
+                ..Default::default()
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_annotations() {
+        let result = AnalysisResult {
+            findings: vec![
+                finding(
+                    Severity::High,
+                    Location::new_precise("/repo/programs/vault/src/lib.rs".to_string(), 12, Some(5), Some(12), Some(30)),
+                    "Missing signer, 100% exploitable",
+                ),
+                finding(
+                    Severity::Low,
+                    Location::new_precise("./src/lib.rs".to_string(), 3, Some(1), Some(6), Some(2)),
+                    "Line one\nline two",
+                ),
+            ],
+            stats: AnalysisStats::default(),
+        };
+
+        let annotations = GithubReport::new(&result, Path::new("/repo")).to_annotations();
+
+        assert_eq!(
+            annotations,
+            "::error file=programs/vault/src/lib.rs,line=12,endLine=12,col=6,endColumn=31,\
+             title=Missing Signer Check%3A authority::Missing signer, 100%25 exploitable [missing-signer-check]\n\
+             ::notice file=src/lib.rs,line=3,endLine=6,title=Missing Signer Check%3A authority::\
+             Line one%0Aline two [missing-signer-check]\n"
+        );
+    }
+
+    #[test]
+    fn test_levels() {
+        assert_eq!(level(&Severity::High), "error");
+        assert_eq!(level(&Severity::Medium), "warning");
+        assert_eq!(level(&Severity::Low), "notice");
+        assert_eq!(level(&Severity::Informational), "notice");
+    }
+}
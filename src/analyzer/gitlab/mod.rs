@@ -0,0 +1,99 @@
+use serde::Serialize;
+use std::path::Path;
+
+use crate::analyzer::fingerprint::{fingerprint_of, relative_path};
+use crate::analyzer::{AnalysisResult, Finding, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Issue of a GitLab Code Quality report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CodeQualityIssue {
+    /// Description of the finding
+    pub description: String,
+    /// ID of the rule that reported the finding
+    pub check_name: String,
+    /// Fingerprint of the finding (see [`crate::analyzer::fingerprint`]), which GitLab uses to
+    /// match the issues of the source and target branches
+    pub fingerprint: String,
+    /// GitLab severity (see [`severity`])
+    pub severity: &'static str,
+    /// Location of the finding
+    pub location: CodeQualityLocation,
+}
+
+/// Location of a GitLab Code Quality issue
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CodeQualityLocation {
+    /// Path relative to the root of the repository
+    pub path: String,
+    /// Lines of the finding
+    pub lines: CodeQualityLines,
+}
+
+/// Lines of a GitLab Code Quality issue
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CodeQualityLines {
+    /// First line (1-indexed)
+    pub begin: usize,
+    /// Last line (1-indexed)
+    pub end: usize,
+}
+
+/// GitLab Code Quality report of an analysis (`--format gitlab`)
+///
+/// The report is the JSON array of issues GitLab shows in the merge request widget and diff
+/// when it is uploaded as a `codequality` artifact.
+#[derive(Debug)]
+pub struct GitlabReport<'a> {
+    result: &'a AnalysisResult,
+    base: &'a Path,
+}
+
+impl<'a> GitlabReport<'a> {
+    /// Creates the report of an analysis, with the paths relative to `base` (the root of the
+    /// repository, where the job runs)
+    pub fn new(result: &'a AnalysisResult, base: &'a Path) -> Self {
+        Self { result, base }
+    }
+
+    /// Returns the issues of the report
+    pub fn issues(&self) -> Vec<CodeQualityIssue> {
+        self.result.findings.iter().map(|finding| self.issue(finding)).collect()
+    }
+
+    /// Returns the report as JSON
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(&self.issues()).expect("code quality issues serialize to JSON");
+        json.push('\n');
+        json
+    }
+
+    fn issue(&self, finding: &Finding) -> CodeQualityIssue {
+        let path = relative_path(&finding.location.file, Some(self.base));
+        CodeQualityIssue {
+            description: finding.description.clone(),
+            check_name: finding.rule().map(|rule| rule.id.clone()).unwrap_or_default(),
+            fingerprint: fingerprint_of(finding, Some(self.base)),
+            severity: severity(&finding.severity),
+            location: CodeQualityLocation {
+                path: path.strip_prefix("./").map(str::to_string).unwrap_or(path),
+                lines: CodeQualityLines {
+                    begin: finding.location.line,
+                    end: finding.location.end_line.unwrap_or(finding.location.line),
+                },
+            },
+        }
+    }
+}
+
+/// GitLab severity of a finding: `critical`, `major`, `minor` or `info`
+pub fn severity(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::High => "critical",
+        Severity::Medium => "major",
+        Severity::Low => "minor",
+        Severity::Informational => "info",
+    }
+}
@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use crate::analyzer::gitlab::GitlabReport;
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Finding, Location, RuleInfo, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(severity: Severity, file: &str, line: usize, fingerprint: Option<&str>) -> Finding {
+        let finding = FindingBuilder::new(format!("Division by zero at line {line}"))
+            .severity(severity)
+            .location(Location::new_precise(file.to_string(), line, Some(5), Some(line + 2), Some(6)))
+            .snippet("amount / shares")
+            .rule(RuleInfo {
+                id: "division-by-zero".to_string(),
+                title: "Division by zero".to_string(),
+                ..Default::default()
+            });
+        match fingerprint {
+            Some(fingerprint) => finding.fingerprint(fingerprint).build(),
+            None => finding.build(),
+        }
+    }
+
+    #[test]
+    fn test_code_quality_report() {
+        let result = AnalysisResult {
+            findings: vec![
+                finding(Severity::High, "/repo/programs/vault/src/lib.rs", 12, Some("0123abcd")),
+                finding(Severity::Informational, "./src/lib.rs", 3, None),
+            ],
+            stats: AnalysisStats::default(),
+        };
+
+        let report = GitlabReport::new(&result, Path::new("/repo"));
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+
+        assert_eq!(
+            json[0],
+            serde_json::json!({
+                "description": "Division by zero at line 12",
+                "check_name": "division-by-zero",
+                "fingerprint": "0123abcd",
+                "severity": "critical",
+                "location": { "path": "programs/vault/src/lib.rs", "lines": { "begin": 12, "end": 14 } }
+            })
+        );
+        assert_eq!(json[1]["severity"], "info");
+        assert_eq!(json[1]["location"]["path"], "src/lib.rs");
+        // Findings without fingerprint get one computed from their content
+        assert_eq!(json[1]["fingerprint"].as_str().unwrap().len(), 32);
+    }
+}
@@ -0,0 +1,335 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "native")]
+use {log::warn, std::fs};
+
+use crate::analyzer::fingerprint::{fingerprint_of, relative_path};
+use crate::analyzer::results::SeverityCounts;
+use crate::analyzer::{AnalysisResult, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Directory of the run history, in the analyzed project
+pub const HISTORY_DIR: &str = ".analyzer/history";
+
+/// Runs shown in the trend sections of the reports
+pub const TREND_RUNS: usize = 10;
+
+/// Runs kept in the history, the older ones are removed when a run is recorded
+pub const MAX_RUNS: usize = 100;
+
+/// Error returned when the run history cannot be read or written
+#[derive(Debug)]
+pub enum HistoryError {
+    /// A run cannot be read or written
+    Io { path: PathBuf, error: std::io::Error },
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryError::Io { path, error } => write!(f, "cannot access the history {}: {error}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+/// Summary and findings of one run, a file of the history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// Time of the run (RFC 3339, UTC)
+    pub timestamp: String,
+    /// Commit of the project (short hash), if it is a git repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Version of the analyzer
+    pub version: String,
+    /// Number of findings of each severity
+    pub summary: SeverityCounts,
+    /// Findings of the run, identified by their fingerprints
+    pub findings: Vec<RecordedFinding>,
+}
+
+/// Finding of a recorded run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedFinding {
+    /// Fingerprint of the finding (see [`fingerprint_of`])
+    pub fingerprint: String,
+    /// ID of the rule that reported it
+    pub rule_id: String,
+    /// Severity of the finding
+    pub severity: Severity,
+    /// File of the finding, relative to the project
+    pub file: String,
+    /// Line of the finding
+    pub line: usize,
+}
+
+impl fmt::Display for RecordedFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fingerprint = &self.fingerprint[..self.fingerprint.len().min(12)];
+        write!(f, "{}[{}] {}:{} ({fingerprint})", self.severity.as_str(), self.rule_id, self.file, self.line)
+    }
+}
+
+impl RunRecord {
+    /// Record of the findings of an analysis of the project at `root`
+    pub fn of(result: &AnalysisResult, root: &Path, timestamp: String, commit: Option<String>) -> Self {
+        let findings = result
+            .findings
+            .iter()
+            .map(|finding| RecordedFinding {
+                fingerprint: fingerprint_of(finding, None),
+                rule_id: finding.rule().map(|rule| rule.id.clone()).unwrap_or_default(),
+                severity: finding.severity.clone(),
+                file: relative_path(&finding.location.file, Some(root)),
+                line: finding.location.line,
+            })
+            .collect();
+        Self {
+            timestamp,
+            commit,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            summary: result.severity_counts(),
+            findings,
+        }
+    }
+
+    /// Record of an analysis of the project at `root` run now, at its current commit
+    #[cfg(feature = "native")]
+    pub fn now(result: &AnalysisResult, root: &Path) -> Self {
+        let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+        let commit = crate::analyzer::diff::git(root, &["rev-parse", "--short", "HEAD"])
+            .ok()
+            .map(|commit| commit.trim().to_string());
+        Self::of(result, root, timestamp, commit)
+    }
+
+    /// Date and time of the run, without the fractional seconds
+    pub fn date(&self) -> String {
+        self.timestamp.get(..19).unwrap_or(&self.timestamp).replace('T', " ")
+    }
+}
+
+/// Run history of a project: one JSON file per run in [`HISTORY_DIR`], the last [`MAX_RUNS`] ones
+#[cfg(feature = "native")]
+#[derive(Debug, Clone)]
+pub struct History {
+    dir: PathBuf,
+    max_runs: usize,
+}
+
+#[cfg(feature = "native")]
+impl History {
+    /// History of the project at `root`
+    pub fn new(root: &Path) -> Self {
+        Self { dir: root.join(HISTORY_DIR), max_runs: MAX_RUNS }
+    }
+
+    /// Keep the last `max_runs` runs instead of [`MAX_RUNS`]
+    pub fn with_max_runs(mut self, max_runs: usize) -> Self {
+        self.max_runs = max_runs.max(1);
+        self
+    }
+
+    /// Load the recorded runs, the oldest first (none if nothing was recorded)
+    ///
+    /// Runs that cannot be parsed are skipped.
+    pub fn load(&self) -> Result<Vec<RunRecord>, HistoryError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(HistoryError::Io { path: self.dir.clone(), error }),
+        };
+        let mut runs = Vec::new();
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let json = fs::read_to_string(&path).map_err(|error| HistoryError::Io { path: path.clone(), error })?;
+            match serde_json::from_str::<RunRecord>(&json) {
+                Ok(run) => runs.push(run),
+                Err(e) => warn!("Skipping the run {}: {e}", path.display()),
+            }
+        }
+        runs.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        Ok(runs)
+    }
+
+    /// Record a run, returning the path of its file, and remove the runs beyond the last ones
+    pub fn record(&self, run: &RunRecord) -> Result<PathBuf, HistoryError> {
+        let path = self.dir.join(format!("{}.json", run.timestamp.replace(':', "")));
+        fs::create_dir_all(&self.dir).map_err(|error| HistoryError::Io { path: self.dir.clone(), error })?;
+        // The history is never meant to be committed
+        let gitignore = self.dir.join(".gitignore");
+        if !gitignore.exists() {
+            fs::write(&gitignore, "*\n").map_err(|error| HistoryError::Io { path: gitignore.clone(), error })?;
+        }
+        let json = serde_json::to_string_pretty(run).expect("runs serialize to JSON");
+        fs::write(&path, json + "\n").map_err(|error| HistoryError::Io { path: path.clone(), error })?;
+        self.prune();
+        Ok(path)
+    }
+
+    /// Helper function to remove the oldest runs beyond the last `max_runs` ones (the file names
+    /// follow the timestamps)
+    fn prune(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut runs: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+            .collect();
+        runs.sort();
+        for path in &runs[..runs.len().saturating_sub(self.max_runs)] {
+            if let Err(e) = fs::remove_file(path) {
+                warn!("Cannot remove the run {}: {e}", path.display());
+            }
+        }
+    }
+}
+
+/// A run with the findings that appeared and disappeared since the previous run
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrendPoint<'a> {
+    /// The run
+    pub run: &'a RunRecord,
+    /// Findings the previous run did not report (every finding of the first run)
+    pub new: Vec<&'a RecordedFinding>,
+    /// Findings of the previous run this run no longer reports
+    pub fixed: Vec<&'a RecordedFinding>,
+}
+
+/// Compare each run with the previous one, matching the findings by fingerprint one to one
+pub fn trend(runs: &[RunRecord]) -> Vec<TrendPoint<'_>> {
+    let mut points = Vec::new();
+    let mut previous: &[RecordedFinding] = &[];
+    for run in runs {
+        points.push(TrendPoint { run, new: unmatched(&run.findings, previous), fixed: unmatched(previous, &run.findings) });
+        previous = &run.findings;
+    }
+    points
+}
+
+/// Whether a finding appeared or disappeared in a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Appeared,
+    Disappeared,
+}
+
+/// Run where a finding appeared or disappeared
+#[derive(Debug, Clone, PartialEq)]
+pub struct FindingEvent<'a> {
+    pub change: Change,
+    pub run: &'a RunRecord,
+    pub finding: &'a RecordedFinding,
+}
+
+/// Runs where the findings with this fingerprint appeared and disappeared, the oldest first
+pub fn finding_events<'a>(runs: &'a [RunRecord], fingerprint: &str) -> Vec<FindingEvent<'a>> {
+    let mut events = Vec::new();
+    for point in trend(runs) {
+        for (change, findings) in [(Change::Disappeared, &point.fixed), (Change::Appeared, &point.new)] {
+            for finding in findings.iter().filter(|finding| finding.fingerprint == fingerprint) {
+                events.push(FindingEvent { change, run: point.run, finding });
+            }
+        }
+    }
+    events
+}
+
+/// Fingerprints of the recorded findings starting with `prefix`, sorted
+pub fn matching_fingerprints<'a>(runs: &'a [RunRecord], prefix: &str) -> Vec<&'a str> {
+    let mut fingerprints: Vec<&str> = runs
+        .iter()
+        .flat_map(|run| &run.findings)
+        .map(|finding| finding.fingerprint.as_str())
+        .filter(|fingerprint| fingerprint.starts_with(prefix))
+        .collect();
+    fingerprints.sort_unstable();
+    fingerprints.dedup();
+    fingerprints
+}
+
+/// Finding counts over time and the changes of the last run (`history`)
+pub fn to_text(runs: &[RunRecord], limit: usize) -> String {
+    if runs.is_empty() {
+        return "No runs recorded\n".to_string();
+    }
+    let points = trend(runs);
+    let shown = &points[points.len().saturating_sub(limit)..];
+    let mut text = format!(
+        "{:<19}  {:<10}  {:>5}  {:>4}  {:>6}  {:>4}  {:>4}  {:>4}  {:>5}\n",
+        "Run", "Commit", "Total", "High", "Medium", "Low", "Info", "New", "Fixed"
+    );
+    for point in shown {
+        let summary = &point.run.summary;
+        let _ = writeln!(
+            text,
+            "{:<19}  {:<10}  {:>5}  {:>4}  {:>6}  {:>4}  {:>4}  {:>4}  {:>5}",
+            point.run.date(),
+            point.run.commit.as_deref().unwrap_or("-"),
+            summary.total(),
+            summary.high,
+            summary.medium,
+            summary.low,
+            summary.informational,
+            point.new.len(),
+            point.fixed.len()
+        );
+    }
+
+    if let Some(last) = points.last().filter(|_| points.len() > 1) {
+        text.push_str(&format!("\nSince the previous run ({} new, {} fixed):\n", last.new.len(), last.fixed.len()));
+        for finding in &last.new {
+            let _ = writeln!(text, "+ {finding}");
+        }
+        for finding in &last.fixed {
+            let _ = writeln!(text, "- {finding}");
+        }
+    }
+    text
+}
+
+/// Runs where the findings with this fingerprint appeared and disappeared (`history --finding`)
+pub fn finding_text(runs: &[RunRecord], fingerprint: &str) -> String {
+    let mut text = String::new();
+    for event in finding_events(runs, fingerprint) {
+        let change = match event.change {
+            Change::Appeared => "appeared",
+            Change::Disappeared => "disappeared",
+        };
+        let commit = event.run.commit.as_deref().map(|commit| format!(" at {commit}")).unwrap_or_default();
+        let _ = writeln!(text, "{change:<11}  {}{commit}  {}", event.run.date(), event.finding);
+    }
+    if runs.last().is_some_and(|run| run.findings.iter().any(|finding| finding.fingerprint == fingerprint)) {
+        text.push_str("still reported by the last run\n");
+    }
+    text
+}
+
+/// Helper function to list the findings of `findings` that `others` do not have, matched by
+/// fingerprint one to one
+fn unmatched<'a>(findings: &'a [RecordedFinding], others: &[RecordedFinding]) -> Vec<&'a RecordedFinding> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for finding in others {
+        *counts.entry(finding.fingerprint.as_str()).or_insert(0) += 1;
+    }
+    findings
+        .iter()
+        .filter(|finding| match counts.get_mut(finding.fingerprint.as_str()) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .collect()
+}
@@ -0,0 +1,124 @@
+use crate::analyzer::history::{
+    Change, History, RecordedFinding, RunRecord, finding_events, finding_text, matching_fingerprints, to_text, trend,
+};
+use crate::analyzer::results::SeverityCounts;
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded(fingerprint: &str, severity: Severity) -> RecordedFinding {
+        RecordedFinding {
+            fingerprint: fingerprint.to_string(),
+            rule_id: "solana-division-by-zero".to_string(),
+            severity,
+            file: "programs/vault/src/lib.rs".to_string(),
+            line: 7,
+        }
+    }
+
+    fn run(timestamp: &str, commit: &str, findings: Vec<RecordedFinding>) -> RunRecord {
+        let mut summary = SeverityCounts::default();
+        findings.iter().for_each(|finding| summary.add(&finding.severity));
+        RunRecord {
+            timestamp: timestamp.to_string(),
+            commit: Some(commit.to_string()),
+            version: "0.1.0".to_string(),
+            summary,
+            findings,
+        }
+    }
+
+    fn runs() -> Vec<RunRecord> {
+        vec![
+            run("2026-10-15T09:00:00.000Z", "1111111", vec![recorded("aaaa1111", Severity::High), recorded("bbbb2222", Severity::Medium)]),
+            run("2026-10-16T09:00:00.000Z", "2222222", vec![recorded("bbbb2222", Severity::Medium)]),
+            run("2026-10-17T09:00:00.000Z", "3333333", vec![recorded("bbbb2222", Severity::Medium), recorded("aaaa1111", Severity::High)]),
+        ]
+    }
+
+    #[test]
+    fn test_trend_compares_each_run_with_the_previous_one() {
+        let runs = runs();
+        let points = trend(&runs);
+        let changes: Vec<(usize, usize)> = points.iter().map(|point| (point.new.len(), point.fixed.len())).collect();
+        assert_eq!(changes, vec![(2, 0), (0, 1), (1, 0)]);
+        assert_eq!(points[1].fixed[0].fingerprint, "aaaa1111");
+    }
+
+    #[test]
+    fn test_finding_events() {
+        let runs = runs();
+        let events: Vec<(Change, &str)> =
+            finding_events(&runs, "aaaa1111").iter().map(|event| (event.change, event.run.commit.as_deref().unwrap())).collect();
+        assert_eq!(
+            events,
+            vec![(Change::Appeared, "1111111"), (Change::Disappeared, "2222222"), (Change::Appeared, "3333333")]
+        );
+        assert_eq!(matching_fingerprints(&runs, "bbbb"), vec!["bbbb2222"]);
+        assert!(matching_fingerprints(&runs, "cccc").is_empty());
+
+        let text = finding_text(&runs, "aaaa1111");
+        assert!(
+            text.starts_with("appeared     2026-10-15 09:00:00 at 1111111  high[solana-division-by-zero] programs/vault/src/lib.rs:7 (aaaa1111)\n"),
+            "{text}"
+        );
+        assert!(text.ends_with("still reported by the last run\n"), "{text}");
+    }
+
+    #[test]
+    fn test_history_text() {
+        assert_eq!(to_text(&[], 20), "No runs recorded\n");
+
+        let text = to_text(&runs(), 2);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Run                  Commit      Total  High  Medium   Low  Info   New  Fixed");
+        assert_eq!(lines[1], "2026-10-16 09:00:00  2222222         1     0       1     0     0     0      1");
+        assert_eq!(lines[2], "2026-10-17 09:00:00  3333333         2     1       1     0     0     1      0");
+        assert_eq!(lines[4], "Since the previous run (1 new, 0 fixed):");
+        assert_eq!(lines[5], "+ high[solana-division-by-zero] programs/vault/src/lib.rs:7 (aaaa1111)");
+    }
+
+    #[test]
+    fn test_history_records_runs() {
+        let root = std::env::temp_dir().join(format!("solana-analyzer-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let history = History::new(&root);
+        assert!(history.load().unwrap().is_empty());
+
+        let finding = FindingBuilder::new("Division Without Zero Check")
+            .span(&root.join("src/lib.rs").to_string_lossy(), 7, 5, 20)
+            .rule_id("solana-division-by-zero")
+            .fingerprint("0123456789abcdef")
+            .build();
+        let result = AnalysisResult { findings: vec![finding], stats: AnalysisStats::default() };
+        let later = RunRecord::of(&result, &root, "2026-10-17T09:00:00.000Z".to_string(), None);
+        let empty = AnalysisResult { findings: Vec::new(), stats: AnalysisStats::default() };
+        let earlier = RunRecord::of(&empty, &root, "2026-10-16T09:00:00.000Z".to_string(), None);
+        let expected = RecordedFinding {
+            fingerprint: "0123456789abcdef".to_string(),
+            file: "src/lib.rs".to_string(),
+            ..recorded("", Severity::Medium)
+        };
+        assert_eq!(later.findings, vec![expected]);
+        assert_eq!(later.summary.medium, 1);
+
+        let path = history.record(&later).unwrap();
+        assert_eq!(path, root.join(".analyzer/history/2026-10-17T090000.000Z.json"));
+        history.record(&earlier).unwrap();
+        std::fs::write(root.join(".analyzer/history/broken.json"), "{").unwrap();
+        assert_eq!(history.load().unwrap(), vec![earlier.clone(), later.clone()]);
+        assert_eq!(std::fs::read_to_string(root.join(".analyzer/history/.gitignore")).unwrap(), "*\n");
+
+        // Only the last runs are kept
+        std::fs::remove_file(root.join(".analyzer/history/broken.json")).unwrap();
+        let history = history.with_max_runs(2);
+        let latest = RunRecord::of(&empty, &root, "2026-10-18T09:00:00.000Z".to_string(), None);
+        history.record(&latest).unwrap();
+        assert_eq!(history.load().unwrap(), vec![later, latest]);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
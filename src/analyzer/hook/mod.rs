@@ -0,0 +1,89 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::diff::{self, DiffError};
+
+#[cfg(test)]
+mod test;
+
+/// Name of the git hook installed by `hook install`
+pub const HOOK_NAME: &str = "pre-commit";
+
+/// Comment of the hooks written by `hook install`, which are replaced without `--force`
+pub const HOOK_MARKER: &str = "Installed by `rust-solana-analyzer hook install`";
+
+/// Error returned when the pre-commit hook cannot be installed
+#[derive(Debug)]
+pub enum HookError {
+    /// Another pre-commit hook exists (overwritten with `--force`)
+    Exists(PathBuf),
+    /// The project is not in a git repository
+    Git(DiffError),
+    /// The hook cannot be written
+    Io(PathBuf, std::io::Error),
+}
+
+impl fmt::Display for HookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HookError::Exists(path) => write!(f, "{} already exists (use --force to overwrite it)", path.display()),
+            HookError::Git(e) => write!(f, "{e}"),
+            HookError::Io(path, e) => write!(f, "Cannot write {}: {e}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for HookError {}
+
+impl From<DiffError> for HookError {
+    fn from(error: DiffError) -> Self {
+        HookError::Git(error)
+    }
+}
+
+/// Script of the pre-commit hook analyzing the staged Rust files of the project at `project`
+/// (relative to the root of the repository, where git runs the hooks)
+///
+/// The exit code of `--staged` is the one of the hook: 1 blocks the commit on findings over the
+/// thresholds, 2 when the analysis cannot run.
+pub fn hook_script(project: &Path) -> String {
+    let project = project.to_string_lossy().replace('\\', "/");
+    let project = if project.is_empty() { ".".to_string() } else { project };
+    format!(
+        "#!/bin/sh\n\
+         # {HOOK_MARKER}\n\
+         # Analyzes the staged Rust files before each commit, and blocks the commit on the findings over\n\
+         # the thresholds of solana-analyzer.toml (on any finding without them).\n\
+         # Skip it once with `git commit --no-verify`.\n\
+         exec rust-solana-analyzer --staged --path '{}'\n",
+        project.replace('\'', r"'\''")
+    )
+}
+
+/// Install the pre-commit hook of the project at `root` in the hooks directory of its git
+/// repository (`core.hooksPath` if set), returning the path of the hook
+///
+/// A hook written by `hook install` is replaced, another one only with `force`.
+pub fn install(root: &Path, force: bool) -> Result<PathBuf, HookError> {
+    let top = PathBuf::from(diff::git(root, &["rev-parse", "--show-toplevel"])?.trim());
+    let hooks_dir = root.join(diff::git(root, &["rev-parse", "--git-path", "hooks"])?.trim());
+    let hook = hooks_dir.join(HOOK_NAME);
+
+    let existing = std::fs::read_to_string(&hook).ok();
+    if !force && existing.is_some_and(|script| !script.contains(HOOK_MARKER)) {
+        return Err(HookError::Exists(hook));
+    }
+
+    let root = root.canonicalize().map_err(|e| HookError::Io(root.to_path_buf(), e))?;
+    let top = top.canonicalize().unwrap_or(top);
+    let project = root.strip_prefix(&top).unwrap_or(&root);
+    std::fs::create_dir_all(&hooks_dir).map_err(|e| HookError::Io(hook.clone(), e))?;
+    std::fs::write(&hook, hook_script(project)).map_err(|e| HookError::Io(hook.clone(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| HookError::Io(hook.clone(), e))?;
+    }
+    Ok(hook)
+}
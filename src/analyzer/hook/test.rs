@@ -0,0 +1,20 @@
+use std::path::Path;
+
+use crate::analyzer::hook::{HOOK_MARKER, hook_script};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_script() {
+        let script = hook_script(Path::new(""));
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains(HOOK_MARKER));
+        assert!(script.ends_with("exec rust-solana-analyzer --staged --path '.'\n"));
+
+        // The project is quoted for the shell
+        let script = hook_script(Path::new("programs/it's"));
+        assert!(script.ends_with("--path 'programs/it'\\''s'\n"));
+    }
+}
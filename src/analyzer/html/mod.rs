@@ -0,0 +1,613 @@
+use std::fmt::Write;
+use std::fs;
+
+use crate::analyzer::compare::Comparison;
+use crate::analyzer::history::{self, RunRecord, TREND_RUNS};
+use crate::analyzer::dsl::query::MetadataValue;
+use crate::analyzer::fixes;
+use crate::analyzer::items;
+use crate::analyzer::results::{self, SeverityCounts, Sources};
+use crate::analyzer::{AnalysisResult, CONTEXT_KEY, Finding, Fix, RuleInfo, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Rust keywords highlighted in the code snippets
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn",
+    "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self",
+    "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+];
+
+/// Styles of the report
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Roboto, sans-serif; margin: 0 auto; max-width: 1100px; padding: 1.5rem; color: #1f2328; }
+h1 { margin-bottom: 0.2rem; }
+.meta { color: #59636e; margin-top: 0; }
+.executive { border: 1px solid #d1d9e0; border-radius: 6px; padding: 0.2rem 1rem 0.8rem; margin: 1rem 0; }
+.score { font-size: 1.4rem; font-weight: 600; }
+.score span { border-radius: 6px; color: #fff; padding: 0.1rem 0.6rem; margin-left: 0.4rem; font-size: 1rem; vertical-align: middle; }
+.risk-none { background: #1a7f37; } .risk-low { background: #0969da; } .risk-medium { background: #d4760b; } .risk-high { background: #cf222e; } .risk-critical { background: #82071e; }
+.breakdown { margin: 1rem 0; } .breakdown > summary { cursor: pointer; font-weight: 600; }
+.tables { display: flex; gap: 1.5rem; flex-wrap: wrap; }
+table { border-collapse: collapse; font-size: 0.9rem; }
+th, td { border-bottom: 1px solid #eef1f4; padding: 0.3rem 0.6rem; text-align: left; }
+td.num-cell { text-align: right; }
+.summary { display: flex; gap: 0.8rem; margin: 1rem 0; }
+.summary div { border-radius: 6px; padding: 0.6rem 1rem; color: #fff; min-width: 7rem; }
+.summary b { display: block; font-size: 1.6rem; }
+.high { background: #cf222e; } .medium { background: #d4760b; } .low { background: #0969da; } .informational { background: #6e7781; }
+.filters { position: sticky; top: 0; background: #fff; border-bottom: 1px solid #d1d9e0; padding: 0.6rem 0; display: flex; gap: 1rem; align-items: center; flex-wrap: wrap; }
+.filters input[type=search] { flex: 1; min-width: 12rem; padding: 0.3rem 0.5rem; }
+.rule { border: 1px solid #d1d9e0; border-radius: 6px; margin: 0.8rem 0; }
+.rule > summary { cursor: pointer; padding: 0.6rem 0.8rem; font-weight: 600; }
+.rule .body { padding: 0 0.8rem 0.6rem; }
+.badge { border-radius: 1rem; color: #fff; font-size: 0.75rem; padding: 0.1rem 0.5rem; margin-right: 0.3rem; }
+.count { color: #59636e; font-weight: normal; }
+.links a { margin-right: 0.8rem; }
+.finding { border-top: 1px solid #eef1f4; padding: 0.5rem 0; }
+.location { font-family: ui-monospace, monospace; }
+.hidden { display: none; }
+pre { background: #f6f8fa; border-radius: 6px; overflow-x: auto; padding: 0.6rem; }
+.kw { color: #cf222e; } .str { color: #0a3069; } .com { color: #59636e; font-style: italic; } .num { color: #0550ae; } .mac { color: #8250df; } .ty { color: #953800; }
+.fix-label { border-radius: 1rem; font-size: 0.75rem; padding: 0.1rem 0.5rem; margin-right: 0.3rem; border: 1px solid; }
+.machine-applicable { color: #1a7f37; } .advisory { color: #9a6700; }
+.diff .add { background: #dafbe1; display: block; } .diff .del { background: #ffebe9; display: block; } .diff .hunk { color: #59636e; display: block; }
+"#;
+
+/// Severity filters and search of the report
+const SCRIPT: &str = r#"
+function applyFilters() {
+  const shown = new Set([...document.querySelectorAll('.filters input[type=checkbox]:checked')].map(box => box.value));
+  const query = document.getElementById('search').value.toLowerCase();
+  document.querySelectorAll('.rule').forEach(rule => {
+    let visible = 0;
+    rule.querySelectorAll('.finding').forEach(finding => {
+      const show = shown.has(finding.dataset.severity) && (!query || finding.dataset.text.includes(query) || rule.dataset.rule.includes(query));
+      finding.classList.toggle('hidden', !show);
+      if (show) visible++;
+    });
+    rule.classList.toggle('hidden', visible === 0);
+    rule.querySelector('.visible').textContent = visible;
+  });
+}
+document.querySelectorAll('.filters input').forEach(input => input.addEventListener('input', applyFilters));
+"#;
+
+/// Self-contained HTML report of an analysis (`--format html`)
+///
+/// The report is a single file without external resources: findings are grouped by rule
+/// (the most severe rules first), with severity filters, a search box, collapsible code
+/// snippets highlighted as Rust and links to the documentation (references and CWE) of the rules.
+#[derive(Debug)]
+pub struct HtmlReport<'a> {
+    result: &'a AnalysisResult,
+    project: &'a str,
+    comparison: Option<&'a Comparison>,
+    history: &'a [RunRecord],
+    sources: Option<&'a Sources>,
+}
+
+impl<'a> HtmlReport<'a> {
+    /// Creates the report of an analysis of `project`
+    pub fn new(result: &'a AnalysisResult, project: &'a str) -> Self {
+        Self { result, project, comparison: None, history: &[], sources: None }
+    }
+
+    /// Show the new and fixed findings since a previous run in a trend section
+    pub fn with_comparison(mut self, comparison: &'a Comparison) -> Self {
+        self.comparison = Some(comparison);
+        self
+    }
+
+    /// Show the finding counts of the recorded runs (the current one last) in the trend section
+    pub fn with_history(mut self, history: &'a [RunRecord]) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Show the diffs of the fixes of the files held in memory instead of reading them from the disk
+    pub fn with_sources(mut self, sources: &'a Sources) -> Self {
+        self.sources = Some(sources);
+        self
+    }
+
+    /// Returns the report as an HTML document
+    pub fn to_html(&self) -> String {
+        let counts = self.result.severity_counts();
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        let _ = writeln!(html, "<title>Rust Solana Analyzer Report - {}</title>", escape(self.project));
+        let _ = writeln!(html, "<style>{STYLE}</style>\n</head>\n<body>");
+        html.push_str("<h1>Rust Solana Analyzer Report</h1>\n");
+        let _ = writeln!(
+            html,
+            "<p class=\"meta\">{} &middot; {} findings &middot; rust-solana-analyzer {}</p>",
+            escape(self.project),
+            counts.total(),
+            env!("CARGO_PKG_VERSION")
+        );
+
+        html.push_str(&self.executive_summary());
+        if self.comparison.is_some() || self.history.len() > 1 {
+            html.push_str(&self.trend());
+        }
+        html.push_str(&self.breakdown());
+        html.push_str(&self.summary(&counts));
+        html.push_str(&self.filters(&counts));
+        if self.result.findings.is_empty() {
+            html.push_str("<p>No vulnerabilities found.</p>\n");
+        }
+        for (rule_id, findings) in self.rules() {
+            html.push_str(&self.rule_section(rule_id, &findings));
+        }
+        html.push_str(&self.suppressed());
+
+        let _ = writeln!(html, "<script>{SCRIPT}</script>\n</body>\n</html>");
+        html
+    }
+
+    /// Save the HTML report to a file
+    pub fn save(&self, output_path: &str) -> Result<(), std::io::Error> {
+        fs::write(output_path, self.to_html())
+    }
+
+    /// Returns the findings grouped by rule, the rules with the most severe findings first
+    fn rules(&self) -> Vec<(&'a str, Vec<&'a Finding>)> {
+        let mut rules: Vec<_> = results::group_by_rule(&self.result.findings).into_iter().collect();
+        rules.sort_by_key(|(_, findings)| {
+            let highest = SeverityCounts::of(findings.iter().copied()).highest();
+            std::cmp::Reverse(highest.map_or(0, |severity| severity.rank()))
+        });
+        rules
+    }
+
+    /// One-page overview: the risk score, the riskiest files and the findings of each category
+    fn executive_summary(&self) -> String {
+        let risk = self.result.risk_summary();
+        let mut html = String::from("<section class=\"executive\">\n<h2>Executive Summary</h2>\n");
+        let _ = writeln!(
+            html,
+            "<p class=\"score\">Risk score {}/100<span class=\"risk-{}\">{}</span></p>",
+            risk.score,
+            risk.level.to_string().to_lowercase(),
+            risk.level
+        );
+        if self.result.findings.is_empty() {
+            html.push_str("</section>\n");
+            return html;
+        }
+        html.push_str("<p>The score weighs each finding by its severity and confidence.</p>\n<div class=\"tables\">\n");
+
+        html.push_str("<table>\n<tr><th>Top risky files</th><th>Points</th>");
+        for severity in Severity::ALL {
+            let _ = write!(html, "<th>{severity:?}</th>");
+        }
+        html.push_str("</tr>\n");
+        for file in &risk.top_files {
+            let path = file.file.strip_prefix(self.project).unwrap_or(&file.file).trim_start_matches('/');
+            let _ = writeln!(
+                html,
+                "<tr><td class=\"location\">{}</td><td class=\"num-cell\">{}</td>{}</tr>",
+                escape(path),
+                file.points,
+                severity_cells(&file.severities)
+            );
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<table>\n<tr><th>Category</th><th>Points</th>");
+        for severity in Severity::ALL {
+            let _ = write!(html, "<th>{severity:?}</th>");
+        }
+        html.push_str("</tr>\n");
+        for category in &risk.categories {
+            let name = category.category.map_or("uncategorized", |category| category.as_str());
+            let _ = writeln!(
+                html,
+                "<tr><td>{name}</td><td class=\"num-cell\">{}</td>{}</tr>",
+                category.points,
+                severity_cells(&category.severities)
+            );
+        }
+        html.push_str("</table>\n</div>\n</section>\n");
+        html
+    }
+
+    /// New, fixed and persisting findings since the previous run, and the findings of the last
+    /// recorded runs
+    fn trend(&self) -> String {
+        let mut html = String::from("<section class=\"executive\">\n<h2>Trend</h2>\n");
+        if let Some(comparison) = self.comparison {
+            html.push_str(&self.comparison(comparison));
+        }
+        if self.history.len() > 1 {
+            html.push_str("<table>\n<tr><th>Run</th><th>Commit</th><th>Total</th>");
+            for severity in Severity::ALL {
+                let _ = write!(html, "<th>{severity:?}</th>");
+            }
+            html.push_str("<th>New</th><th>Fixed</th></tr>\n");
+            let points = history::trend(self.history);
+            for point in &points[points.len().saturating_sub(TREND_RUNS)..] {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td class=\"location\">{}</td><td class=\"num-cell\">{}</td>{}<td class=\"num-cell\">{}</td><td class=\"num-cell\">{}</td></tr>",
+                    point.run.date(),
+                    escape(point.run.commit.as_deref().unwrap_or("-")),
+                    point.run.summary.total(),
+                    severity_cells(&point.run.summary),
+                    point.new.len(),
+                    point.fixed.len()
+                );
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str("</section>\n");
+        html
+    }
+
+    /// New, fixed and persisting findings since the previous run (`--compare`)
+    fn comparison(&self, comparison: &Comparison) -> String {
+        let summary = comparison.summary();
+        let mut html = String::new();
+        let _ = writeln!(
+            html,
+            "<p><b>{}</b> new &middot; <b>{}</b> fixed &middot; {} persisting since the previous run</p>",
+            summary.new, summary.fixed, summary.persisting
+        );
+        for (heading, findings) in [("New findings", &comparison.new), ("Fixed findings", &comparison.fixed)] {
+            if findings.is_empty() {
+                continue;
+            }
+            let _ = writeln!(html, "<details><summary>{heading} ({})</summary>\n<ul>", findings.len());
+            for finding in findings {
+                let file = finding.location.file.strip_prefix(self.project).unwrap_or(&finding.location.file).trim_start_matches('/');
+                let _ = writeln!(
+                    html,
+                    "<li><span class=\"badge {}\">{:?}</span>{} <span class=\"location\">{}:{}</span></li>",
+                    finding.severity.as_str(),
+                    finding.severity,
+                    escape(&finding.description),
+                    escape(file),
+                    finding.location.line
+                );
+            }
+            html.push_str("</ul>\n</details>\n");
+        }
+        html
+    }
+
+    /// Findings of each file (with their density per thousand lines of code) and of each rule
+    fn breakdown(&self) -> String {
+        if self.result.findings.is_empty() {
+            return String::new();
+        }
+        let stats = &self.result.stats;
+        let mut html = String::from("<details class=\"breakdown\">\n<summary>Breakdown by file and rule</summary>\n<div class=\"tables\">\n");
+
+        html.push_str("<table>\n<tr><th>File</th><th>Lines of code</th><th>Findings</th><th>Per KLOC</th></tr>\n");
+        for file in self.result.file_summaries() {
+            let path = file.file.strip_prefix(self.project).unwrap_or(&file.file).trim_start_matches('/');
+            let lines = stats.lines_of_code.get(&file.file).copied();
+            let findings = file.severities.total();
+            let density = lines.and_then(|lines| results::density(findings, lines));
+            let _ = writeln!(
+                html,
+                "<tr><td class=\"location\">{}</td><td class=\"num-cell\">{}</td><td class=\"num-cell\">{findings}</td><td class=\"num-cell\">{}</td></tr>",
+                escape(path),
+                lines.map_or("-".to_string(), |lines| lines.to_string()),
+                density.map_or("-".to_string(), |density| format!("{density:.1}"))
+            );
+        }
+        let total_lines = stats.total_lines_of_code();
+        let total_density = results::density(self.result.findings.len(), total_lines);
+        let _ = writeln!(
+            html,
+            "<tr><th>Total</th><th class=\"num-cell\">{total_lines}</th><th class=\"num-cell\">{}</th><th class=\"num-cell\">{}</th></tr>\n</table>",
+            self.result.findings.len(),
+            total_density.map_or("-".to_string(), |density| format!("{density:.1}"))
+        );
+
+        html.push_str("<table>\n<tr><th>Rule</th><th>Findings</th><th>Files</th></tr>\n");
+        for rule in self.result.rule_summaries() {
+            let _ = writeln!(
+                html,
+                "<tr><td>{} <code>{}</code></td><td class=\"num-cell\">{}</td><td class=\"num-cell\">{}</td></tr>",
+                escape(&rule.title),
+                escape(&rule.rule_id),
+                rule.severities.total(),
+                rule.files.len()
+            );
+        }
+        html.push_str("</table>\n</div>\n</details>\n");
+        html
+    }
+
+    /// Appendix with the findings removed by suppression comments and by the severity and
+    /// confidence filters, with their fingerprints
+    fn suppressed(&self) -> String {
+        let suppressed = &self.result.stats.suppressed_findings;
+        if suppressed.is_empty() {
+            return String::new();
+        }
+        let mut html = String::from("<section class=\"executive\">\n<h2>Appendix: Suppressed Findings</h2>\n");
+        let _ = writeln!(
+            html,
+            "<p>{} finding{} removed by <code>analyzer:ignore</code> comments or by the severity and confidence filters.</p>",
+            suppressed.len(),
+            if suppressed.len() == 1 { " was" } else { "s were" }
+        );
+        html.push_str(
+            "<table>\n<tr><th>Rule</th><th>Severity</th><th>Location</th><th>Suppressed by</th><th>Reason</th><th>Fingerprint</th></tr>\n",
+        );
+        for suppressed in suppressed {
+            let finding = &suppressed.finding;
+            let file = finding.location.file.strip_prefix(self.project).unwrap_or(&finding.location.file).trim_start_matches('/');
+            let _ = writeln!(
+                html,
+                "<tr><td><code>{}</code></td><td><span class=\"badge {}\">{:?}</span></td><td class=\"location\">{}:{}</td><td>{}</td><td>{}</td><td><code>{}</code></td></tr>",
+                escape(&suppressed.rule_id),
+                finding.severity.as_str(),
+                finding.severity,
+                escape(file),
+                finding.location.line,
+                suppressed.suppressed_by.as_str(),
+                escape(suppressed.reason.as_deref().unwrap_or("-")),
+                escape(finding.fingerprint.as_deref().unwrap_or("-"))
+            );
+        }
+        html.push_str("</table>\n</section>\n");
+        html
+    }
+
+    fn summary(&self, counts: &SeverityCounts) -> String {
+        let mut html = String::from("<div class=\"summary\">\n");
+        for severity in Severity::ALL {
+            let _ = writeln!(
+                html,
+                "<div class=\"{}\"><b>{}</b>{severity:?}</div>",
+                severity.as_str(),
+                counts.get(&severity)
+            );
+        }
+        html.push_str("</div>\n");
+        html
+    }
+
+    fn filters(&self, counts: &SeverityCounts) -> String {
+        let mut html = String::from("<div class=\"filters\">\n");
+        for severity in Severity::ALL {
+            let _ = writeln!(
+                html,
+                "<label><input type=\"checkbox\" value=\"{}\" checked> {severity:?} ({})</label>",
+                severity.as_str(),
+                counts.get(&severity)
+            );
+        }
+        html.push_str("<input type=\"search\" id=\"search\" placeholder=\"Filter by rule, file or description\">\n</div>\n");
+        html
+    }
+
+    fn rule_section(&self, rule_id: &str, findings: &[&Finding]) -> String {
+        let rule = findings.iter().find_map(|finding| finding.rule());
+        let title = rule.map_or(rule_id, |rule| rule.title.as_str());
+        let counts = SeverityCounts::of(findings.iter().copied());
+
+        let mut html = String::new();
+        let _ = writeln!(html, "<details class=\"rule\" open data-rule=\"{}\">", escape(&rule_id.to_lowercase()));
+        html.push_str("<summary>");
+        for severity in Severity::ALL.iter().filter(|severity| counts.get(severity) > 0) {
+            let _ = write!(html, "<span class=\"badge {}\">{severity:?}</span>", severity.as_str());
+        }
+        let _ = writeln!(
+            html,
+            "{} <code>{}</code> <span class=\"count\">(<span class=\"visible\">{}</span>)</span></summary>",
+            escape(title),
+            escape(rule_id),
+            findings.len()
+        );
+
+        html.push_str("<div class=\"body\">\n");
+        if let Some(rule) = rule {
+            html.push_str(&rule_links(rule));
+        }
+        if let Some(recommendations) = findings.first().map(|finding| &finding.recommendations)
+            && !recommendations.is_empty()
+        {
+            html.push_str("<details><summary>Recommendations</summary>\n<ol>\n");
+            for recommendation in recommendations {
+                let _ = writeln!(html, "<li>{}</li>", escape(recommendation));
+            }
+            html.push_str("</ol>\n</details>\n");
+        }
+        for finding in findings {
+            html.push_str(&self.finding(finding));
+        }
+        html.push_str("</div>\n</details>\n");
+        html
+    }
+
+    fn finding(&self, finding: &Finding) -> String {
+        let file = finding
+            .location
+            .file
+            .strip_prefix(self.project)
+            .unwrap_or(&finding.location.file)
+            .trim_start_matches('/');
+        let text = format!("{file} {}", finding.description).to_lowercase();
+
+        let mut html = String::new();
+        let _ = writeln!(
+            html,
+            "<div class=\"finding\" data-severity=\"{}\" data-text=\"{}\">",
+            finding.severity.as_str(),
+            escape(&text)
+        );
+        let _ = writeln!(
+            html,
+            "<span class=\"badge {}\">{:?}</span><span class=\"location\">{}:{}</span> &middot; confidence {}",
+            finding.severity.as_str(),
+            finding.severity,
+            escape(file),
+            finding.location.line,
+            finding.confidence
+        );
+        if let Some(item) = items::qualified_name(finding) {
+            let _ = write!(html, " &middot; in <code>{}</code>", escape(&item));
+        }
+        if finding.rules.len() > 1 {
+            let _ = write!(html, " &middot; rules {}", escape(&finding.rule_ids().join(", ")));
+        }
+        let _ = writeln!(html, "<p>{}</p>", escape(&finding.description));
+        // The lines around the finding (`--context`) replace its snippet
+        if let Some(MetadataValue::Text(context)) = finding.metadata.get(CONTEXT_KEY) {
+            let _ = writeln!(html, "<details open><summary>Code</summary><pre><code>{}</code></pre></details>", highlight(context));
+        } else if let Some(snippet) = &finding.code_snippet {
+            let _ = writeln!(html, "<details><summary>Code</summary><pre><code>{}</code></pre></details>", highlight(snippet));
+        }
+        if !finding.fixes.is_empty() {
+            let lines = results::source_lines(&finding.location.file, self.sources);
+            for fix in &finding.fixes {
+                html.push_str(&fix_details(fix, lines.as_deref(), &finding.location.file));
+            }
+        }
+        html.push_str("</div>\n");
+        html
+    }
+}
+
+/// Suggested fix of a finding, labeled with its applicability, and its diff if the file can be read
+fn fix_details(fix: &Fix, lines: Option<&[String]>, file: &str) -> String {
+    let mut html = String::from("<details class=\"fix\"><summary>");
+    let _ = write!(
+        html,
+        "<span class=\"fix-label {0}\" title=\"{1}\">{0}</span>{2}</summary>",
+        fix.applicability.as_str(),
+        fixes::label(fix),
+        escape(&fix.description)
+    );
+    if let Some(changes) = lines.and_then(|lines| fixes::diff(lines, fix, file)) {
+        html.push_str("<pre class=\"diff\"><code>");
+        for line in changes.to_unified().lines() {
+            let class = match line.chars().next() {
+                Some('+') => "add",
+                Some('-') => "del",
+                _ => "hunk",
+            };
+            let _ = write!(html, "<span class=\"{class}\">{}</span>", escape(line));
+        }
+        html.push_str("</code></pre>");
+    }
+    html.push_str("</details>\n");
+    html
+}
+
+/// Table cells with the number of findings of each severity, from High to Informational
+fn severity_cells(counts: &SeverityCounts) -> String {
+    Severity::ALL
+        .iter()
+        .map(|severity| format!("<td class=\"num-cell\">{}</td>", counts.get(severity)))
+        .collect()
+}
+
+/// Links to the documentation of a rule: its references and CWE entries
+fn rule_links(rule: &RuleInfo) -> String {
+    let mut links: Vec<String> = rule
+        .references
+        .iter()
+        .map(|reference| format!("<a href=\"{0}\" target=\"_blank\" rel=\"noopener\">{0}</a>", escape(reference)))
+        .collect();
+    links.extend(rule.cwe.iter().map(|id| {
+        format!("<a href=\"https://cwe.mitre.org/data/definitions/{id}.html\" target=\"_blank\" rel=\"noopener\">CWE-{id}</a>")
+    }));
+    if let Some(category) = &rule.category {
+        links.push(format!("<span class=\"count\">Category: {category}</span>"));
+    }
+    if links.is_empty() {
+        return String::new();
+    }
+    format!("<p class=\"links\">{}</p>\n", links.join(""))
+}
+
+/// Escape the HTML special characters of a text
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Highlight Rust code as escaped HTML: keywords, strings, comments, numbers, macros and types
+pub fn highlight(code: &str) -> String {
+    let chars: Vec<char> = code.chars().collect();
+    let mut html = String::with_capacity(code.len() * 2);
+    let mut i = 0;
+    let span = |html: &mut String, class: &str, text: &[char]| {
+        let _ = write!(html, "<span class=\"{class}\">{}</span>", escape(&text.iter().collect::<String>()));
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            span(&mut html, "com", &chars[start..i]);
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i - 1] == '*' && chars[i] == '/') {
+                i += 1;
+            }
+            i = (i + 1).min(chars.len());
+            span(&mut html, "com", &chars[start..i]);
+        } else if c == '"' {
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(chars.len());
+            span(&mut html, "str", &chars[start..i]);
+        } else if c == '\'' && (chars.get(i + 2) == Some(&'\'') || chars.get(i + 1) == Some(&'\\')) {
+            // Character literal (lifetimes have no closing quote)
+            i += 2;
+            while i < chars.len() && chars[i] != '\'' {
+                i += 1;
+            }
+            i = (i + 1).min(chars.len());
+            span(&mut html, "str", &chars[start..i]);
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                i += 1;
+            }
+            span(&mut html, "num", &chars[start..i]);
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            if chars.get(i) == Some(&'!') && chars.get(i + 1) != Some(&'=') {
+                i += 1;
+                span(&mut html, "mac", &chars[start..i]);
+            } else if KEYWORDS.contains(&word.as_str()) {
+                span(&mut html, "kw", &chars[start..i]);
+            } else if c.is_uppercase() {
+                span(&mut html, "ty", &chars[start..i]);
+            } else {
+                html.push_str(&escape(&word));
+            }
+        } else {
+            html.push_str(&escape(&c.to_string()));
+            i += 1;
+        }
+    }
+    html
+}
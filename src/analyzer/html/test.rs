@@ -0,0 +1,142 @@
+use crate::analyzer::html::{HtmlReport, escape, highlight};
+use crate::analyzer::suppressions::{SuppressedBy, SuppressedFinding};
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Category, Finding, Fix, Location, RuleInfo, Severity, TextEdit};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(rule_id: &str, severity: Severity, line: usize) -> Finding {
+        FindingBuilder::new(format!("<{rule_id}> at line {line}"))
+            .severity(severity)
+            .span("/project/src/lib.rs", line, 5, 20)
+            .snippet("let share = amount / shares; // \"unchecked\"")
+            .recommendations(&["Check the divisor"])
+            .rule(RuleInfo {
+                id: rule_id.to_string(),
+                title: format!("Title of {rule_id}"),
+                references: vec!["https://docs.example.com/rules".to_string()],
+                cwe: vec![369],
+                category: Some(Category::Arithmetic),
+                ..Default::default()
+            })
+            .build()
+    }
+
+    fn result() -> AnalysisResult {
+        AnalysisResult {
+            findings: vec![
+                finding("error-handling", Severity::Low, 3),
+                finding("division-by-zero", Severity::High, 8),
+                finding("error-handling", Severity::Low, 12),
+            ],
+            stats: AnalysisStats {
+                lines_of_code: [("/project/src/lib.rs".to_string(), 40)].into_iter().collect(),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_report_groups_findings_by_rule() {
+        let result = result();
+        let html = HtmlReport::new(&result, "/project").to_html();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        // Self-contained: no external scripts or styles
+        assert!(!html.contains("<script src") && !html.contains("<link"));
+
+        // The rule with the most severe findings first
+        let division = html.find("data-rule=\"division-by-zero\"").unwrap();
+        let error_handling = html.find("data-rule=\"error-handling\"").unwrap();
+        assert!(division < error_handling);
+        assert_eq!(html.matches("class=\"finding\"").count(), 3);
+        assert!(html.contains("data-severity=\"low\""));
+        assert!(html.contains("<span class=\"location\">src/lib.rs:12</span>"));
+
+        // Filters, documentation links and escaped descriptions
+        assert!(html.contains("<input type=\"checkbox\" value=\"high\" checked> High (1)"));
+        assert!(html.contains("href=\"https://docs.example.com/rules\""));
+        assert!(html.contains("href=\"https://cwe.mitre.org/data/definitions/369.html\""));
+        assert!(html.contains("&lt;division-by-zero&gt; at line 8"));
+        assert!(!html.contains("<division-by-zero>"));
+
+        // Breakdown by file (3 findings in 40 lines) and rule
+        assert!(html.contains(
+            "<tr><td class=\"location\">src/lib.rs</td><td class=\"num-cell\">40</td><td class=\"num-cell\">3</td><td class=\"num-cell\">75.0</td></tr>"
+        ));
+        assert!(html.contains(
+            "<tr><td>Title of error-handling <code>error-handling</code></td><td class=\"num-cell\">2</td><td class=\"num-cell\">1</td></tr>"
+        ));
+
+        // Executive summary: one high (100 points) and two low (20) findings
+        assert!(html.contains("Risk score 24/100<span class=\"risk-low\">Low</span>"));
+        assert!(html.contains("<tr><td class=\"location\">src/lib.rs</td><td class=\"num-cell\">140</td>"));
+        assert!(html.contains("<tr><td>arithmetic</td><td class=\"num-cell\">140</td><td class=\"num-cell\">1</td>"));
+    }
+
+    #[test]
+    fn test_fixes() {
+        let dir = std::env::temp_dir().join(format!("solana-analyzer-html-fixes-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("lib.rs").to_string_lossy().to_string();
+        std::fs::write(&file, "fn f(a: u64, b: u64) -> u64 {\n    a / b\n}\n").unwrap();
+
+        let edit = TextEdit {
+            location: Location::new_precise(file.clone(), 2, Some(4), Some(2), Some(9)),
+            replacement: "a.checked_div(b).unwrap()".to_string(),
+        };
+        let mut finding = finding("division-by-zero", Severity::Medium, 2);
+        finding.location.file = file;
+        finding.fixes = vec![Fix::machine_applicable("Use checked_div", vec![edit])];
+        let result = AnalysisResult { findings: vec![finding], stats: AnalysisStats::default() };
+        let html = HtmlReport::new(&result, "/project").to_html();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(html.contains("<span class=\"fix-label machine-applicable\" title=\"Suggested fix (machine-applicable)\">machine-applicable</span>Use checked_div</summary>"));
+        assert!(html.contains("<span class=\"del\">-    a / b</span><span class=\"add\">+    a.checked_div(b).unwrap()</span>"));
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let result = AnalysisResult { findings: Vec::new(), stats: AnalysisStats::default() };
+        let html = HtmlReport::new(&result, "/project").to_html();
+
+        assert!(html.contains("No vulnerabilities found."));
+        assert!(html.contains("Risk score 0/100<span class=\"risk-none\">None</span>"));
+        assert!(!html.contains("class=\"rule\""));
+        assert!(!html.contains("Suppressed Findings"));
+    }
+
+    #[test]
+    fn test_suppressed_appendix() {
+        let mut result = AnalysisResult { findings: Vec::new(), stats: AnalysisStats::default() };
+        result.stats.suppressed_findings = vec![SuppressedFinding {
+            rule_id: "unsafe-code".to_string(),
+            suppressed_by: SuppressedBy::Confidence,
+            reason: Some("Confidence Low is below the minimum (Medium)".to_string()),
+            finding: Finding { fingerprint: Some("abc123".to_string()), ..finding("unsafe-code", Severity::Low, 7) },
+        }];
+        let html = HtmlReport::new(&result, "/project").to_html();
+
+        assert!(html.contains("<h2>Appendix: Suppressed Findings</h2>\n<p>1 finding was removed"));
+        assert!(html.contains("<td class=\"location\">src/lib.rs:7</td><td>confidence</td><td>Confidence Low is below the minimum (Medium)</td><td><code>abc123</code></td>"));
+    }
+
+    #[test]
+    fn test_highlight() {
+        assert_eq!(
+            highlight("let x = vec![1]; // a < b"),
+            "<span class=\"kw\">let</span> x = <span class=\"mac\">vec!</span>[<span class=\"num\">1</span>]; \
+             <span class=\"com\">// a &lt; b</span>"
+        );
+        assert_eq!(
+            highlight("Ok(msg!(\"a \\\"b\\\"\", '\"'))"),
+            "<span class=\"ty\">Ok</span>(<span class=\"mac\">msg!</span>(<span class=\"str\">&quot;a \\&quot;b\\&quot;&quot;</span>, \
+             <span class=\"str\">&#39;&quot;&#39;</span>))"
+        );
+        assert_eq!(highlight("fn f<'a>(x: &'a u8)"), "<span class=\"kw\">fn</span> f&lt;&#39;a&gt;(x: &amp;&#39;a u8)");
+        assert_eq!(escape("<a href=\"x\">&</a>"), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+    }
+}
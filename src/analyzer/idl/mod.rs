@@ -0,0 +1,574 @@
+use log::{debug, warn};
+use quote::ToTokens;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use syn::{Attribute, File, Item};
+
+use crate::analyzer::dsl::program::derives;
+use crate::analyzer::dsl::query::{Metadata, MetadataValue};
+use crate::analyzer::vfs::FileSystem;
+use crate::analyzer::workspace::{CrateManifest, PROGRAM_KEY};
+use crate::analyzer::{Confidence, Finding, Location, RuleInfo, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Directory of the IDLs built by `anchor build`, relative to the workspace root
+pub const IDL_DIR: &str = "target/idl";
+
+/// ID under which the differences between the IDL and the sources are reported (can be ignored
+/// like a rule ID)
+pub const IDL_DRIFT_ID: &str = "idl-drift";
+
+/// Account types that are not checked by Anchor, documented with `/// CHECK:` comments
+const UNCHECKED_TYPES: &[&str] = &["UncheckedAccount", "AccountInfo"];
+
+/// Error returned when an IDL cannot be loaded
+#[derive(Debug)]
+pub enum IdlError {
+    /// The IDL cannot be read
+    Io { path: PathBuf, error: std::io::Error },
+    /// The IDL is not valid JSON or has no instructions
+    Parse { path: PathBuf, error: String },
+}
+
+impl fmt::Display for IdlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdlError::Io { path, error } => write!(f, "cannot read IDL {}: {error}", path.display()),
+            IdlError::Parse { path, error } => write!(f, "invalid IDL {}: {error}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for IdlError {}
+
+/// Instructions of an Anchor IDL, with the names in snake case
+///
+/// Both the IDL format of Anchor 0.30 and later (`writable`, `signer`) and the older one
+/// (`isMut`, `isSigner`, camel case names) are read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Idl {
+    /// Path of the IDL file
+    pub path: PathBuf,
+    /// Content of the IDL file, to locate the instructions
+    pub source: String,
+    /// Program ID (`address`, or `metadata.address` in the older format)
+    pub address: Option<String>,
+    /// Instructions of the program
+    pub instructions: Vec<IdlInstruction>,
+}
+
+/// Instruction of an IDL
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdlInstruction {
+    /// Name of the instruction
+    pub name: String,
+    /// Accounts of the instruction (the accounts of composite fields are not listed)
+    pub accounts: Vec<IdlAccount>,
+    /// Names of the composite fields (nested accounts structs)
+    pub composites: BTreeSet<String>,
+    /// Arguments and their type (`u64`, `pubkey`, `vec<u8>`, or the name of a defined type)
+    pub args: Vec<(String, String)>,
+}
+
+/// Account of an IDL instruction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdlAccount {
+    /// Name of the account
+    pub name: String,
+    /// Whether the instruction writes the account
+    pub writable: bool,
+    /// Whether the account signs the transaction
+    pub signer: bool,
+    /// Whether the account has docs
+    pub documented: bool,
+}
+
+impl Idl {
+    /// Read the IDL built for a crate (`target/idl/<lib name>.json`), `None` if it was not built
+    pub fn load(fs: &dyn FileSystem, workspace_root: &Path, manifest: &CrateManifest) -> Result<Option<Self>, IdlError> {
+        let path = workspace_root.join(IDL_DIR).join(format!("{}.json", manifest.lib_name));
+        if !fs.is_file(&path) {
+            return Ok(None);
+        }
+        let source = fs.read_to_string(&path).map_err(|error| IdlError::Io { path: path.clone(), error })?;
+        Self::parse(path, source).map(Some)
+    }
+
+    /// Parse the content of an IDL file
+    pub fn parse(path: PathBuf, source: String) -> Result<Self, IdlError> {
+        let json: Value = serde_json::from_str(&source).map_err(|error| IdlError::Parse {
+            path: path.clone(),
+            error: error.to_string(),
+        })?;
+        let Some(instructions) = json.get("instructions").and_then(Value::as_array) else {
+            return Err(IdlError::Parse {
+                path,
+                error: "no instructions".to_string(),
+            });
+        };
+        let instructions = instructions.iter().map(parse_instruction).collect();
+        let address = json
+            .get("address")
+            .or_else(|| json.get("metadata").and_then(|metadata| metadata.get("address")))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        Ok(Self {
+            path,
+            source,
+            address,
+            instructions,
+        })
+    }
+
+    /// Returns the line of the name of an instruction in the IDL file (its first line if not found)
+    fn instruction_line(&self, name: &str) -> usize {
+        let names = [format!("\"{name}\""), format!("\"{}\"", camel_case(name))];
+        self.source
+            .lines()
+            .position(|line| line.contains("\"name\"") && names.iter().any(|name| line.contains(name.as_str())))
+            .map_or(1, |index| index + 1)
+    }
+}
+
+/// Compare the IDL of a program with its sources (path, AST and source code of each file)
+///
+/// Instructions and arguments missing on either side, accounts missing on either side or whose
+/// mutability or signer status differs are reported (the IDL is stale), as well as unchecked
+/// accounts the IDL has no docs for.
+pub fn compare(idl: &Idl, files: &[(&str, &File, &str)], program: Option<&str>) -> Vec<Finding> {
+    let mut sources = SourceProgram::default();
+    for (file_path, ast, _) in files {
+        sources.collect(file_path, &ast.items, false);
+    }
+    let snippet = |file: &str, line: usize| {
+        files
+            .iter()
+            .find(|(file_path, ..)| *file_path == file)
+            .and_then(|(.., source)| source.lines().nth(line.saturating_sub(1)))
+            .map(|code| code.trim().to_string())
+    };
+    let idl_file = idl.path.to_string_lossy().to_string();
+    let mut findings = Vec::new();
+    let mut report = |severity: Severity, description: String, file: &str, line: usize| {
+        findings.push(drift_finding(severity, description, file, line, snippet(file, line), program));
+    };
+
+    for handler in &sources.handlers {
+        let Some(instruction) = idl.instructions.iter().find(|instruction| instruction.name == handler.name) else {
+            report(
+                Severity::Medium,
+                format!("Instruction '{}' is missing from the IDL {idl_file}, which is stale", handler.name),
+                &handler.file,
+                handler.line,
+            );
+            continue;
+        };
+
+        let idl_args: Vec<&str> = instruction.args.iter().map(|(name, _)| name.as_str()).collect();
+        let handler_args: Vec<&str> = handler.args.iter().map(|(name, _)| name.as_str()).collect();
+        for ((name, ty), (idl_name, idl_ty)) in handler.args.iter().zip(&instruction.args) {
+            if name == idl_name && ty != idl_ty {
+                report(
+                    Severity::Medium,
+                    format!("Argument '{name}' of '{}' has type {ty} in the code but {idl_ty} in the IDL", handler.name),
+                    &handler.file,
+                    handler.line,
+                );
+            }
+        }
+        if idl_args != handler_args {
+            report(
+                Severity::Medium,
+                format!(
+                    "Arguments of '{}' differ from the IDL: ({}) in the code, ({}) in the IDL",
+                    handler.name,
+                    handler_args.join(", "),
+                    idl_args.join(", ")
+                ),
+                &handler.file,
+                handler.line,
+            );
+        }
+
+        let Some(accounts) = handler.accounts.as_ref().and_then(|name| sources.accounts.iter().find(|accounts| &accounts.name == name)) else {
+            continue;
+        };
+        for field in &accounts.fields {
+            if instruction.composites.contains(&field.name) {
+                continue;
+            }
+            let Some(account) = instruction.accounts.iter().find(|account| account.name == field.name) else {
+                report(
+                    Severity::Medium,
+                    format!("Account '{}' of '{}' is missing from the IDL", field.name, accounts.name),
+                    &accounts.file,
+                    field.line,
+                );
+                continue;
+            };
+            if field.writable != account.writable {
+                let (code, idl) = if field.writable { ("mutable", "read-only") } else { ("read-only", "mutable") };
+                report(
+                    Severity::Medium,
+                    format!("Account '{}' of '{}' is {code} in the code but {idl} in the IDL", field.name, accounts.name),
+                    &accounts.file,
+                    field.line,
+                );
+            }
+            if field.signer != account.signer {
+                let (code, idl) = if field.signer { ("a signer", "not a signer") } else { ("not a signer", "a signer") };
+                report(
+                    Severity::Medium,
+                    format!("Account '{}' of '{}' is {code} in the code but {idl} in the IDL", field.name, accounts.name),
+                    &accounts.file,
+                    field.line,
+                );
+            }
+            if field.unchecked && !account.documented {
+                report(
+                    Severity::Informational,
+                    format!(
+                        "Unchecked account '{}' of '{}' has no docs in the IDL, auditors cannot tell why it is safe",
+                        field.name, accounts.name
+                    ),
+                    &accounts.file,
+                    field.line,
+                );
+            }
+        }
+        for account in &instruction.accounts {
+            if !accounts.fields.iter().any(|field| field.name == account.name) {
+                report(
+                    Severity::Medium,
+                    format!("Account '{}' of the IDL instruction '{}' is not in '{}'", account.name, instruction.name, accounts.name),
+                    &accounts.file,
+                    accounts.line,
+                );
+            }
+        }
+    }
+
+    // Instructions removed from the code since the IDL was built
+    for instruction in &idl.instructions {
+        if !sources.handlers.iter().any(|handler| handler.name == instruction.name) && !sources.handlers.is_empty() {
+            report(
+                Severity::Medium,
+                format!("Instruction '{}' of the IDL is not in the program, the IDL is stale", instruction.name),
+                &idl_file,
+                idl.instruction_line(&instruction.name),
+            );
+        }
+    }
+    findings
+}
+
+/// Compare the IDL built for a crate of the workspace, if any, with its sources
+pub fn check(fs: &dyn FileSystem, workspace_root: &Path, manifest: &CrateManifest, files: &[(&str, &File, &str)]) -> Vec<Finding> {
+    match Idl::load(fs, workspace_root, manifest) {
+        Ok(Some(idl)) => {
+            debug!("Comparing {} with the sources of {}", idl.path.display(), manifest.name);
+            compare(&idl, files, Some(&manifest.name))
+        }
+        Ok(None) => Vec::new(),
+        Err(e) => {
+            warn!("{e}");
+            Vec::new()
+        }
+    }
+}
+
+/// Helper function to build the finding of a difference between the IDL and the sources
+fn drift_finding(severity: Severity, description: String, file: &str, line: usize, code_snippet: Option<String>, program: Option<&str>) -> Finding {
+    let mut metadata = Metadata::new();
+    if let Some(program) = program {
+        metadata.insert(PROGRAM_KEY.to_string(), MetadataValue::Text(program.to_string()));
+    }
+    let recommendation = if severity == Severity::Informational {
+        "Document the account with a `/// CHECK:` comment explaining why it is safe, and rebuild the IDL"
+    } else {
+        "Rebuild the IDL (`anchor build`) and check that clients use the current account list"
+    };
+    Finding {
+        description,
+        severity,
+        confidence: Confidence::High,
+        location: Location::new_precise(file.to_string(), line, None, None, None),
+        code_snippet,
+        recommendations: vec![recommendation.to_string()],
+        fixes: Vec::new(),
+        metadata,
+        rules: vec![RuleInfo {
+            id: IDL_DRIFT_ID.to_string(),
+            title: "IDL Drift".to_string(),
+            tags: vec!["anchor".to_string(), "idl".to_string()],
+            ..Default::default()
+        }],
+        fingerprint: None,
+    }
+}
+
+/// Instruction handlers and accounts structs of the sources
+#[derive(Debug, Default)]
+struct SourceProgram {
+    /// Public functions of the `#[program]` module
+    handlers: Vec<Handler>,
+    /// Structs deriving `Accounts`
+    accounts: Vec<AccountsStruct>,
+}
+
+/// Instruction handler of the sources
+#[derive(Debug)]
+struct Handler {
+    name: String,
+    file: String,
+    line: usize,
+    /// Accounts struct of the `Context<T>` parameter
+    accounts: Option<String>,
+    /// Arguments after the context, with their type in the IDL notation
+    args: Vec<(String, String)>,
+}
+
+/// Accounts struct of the sources
+#[derive(Debug)]
+struct AccountsStruct {
+    name: String,
+    file: String,
+    line: usize,
+    fields: Vec<AccountField>,
+}
+
+/// Field of an accounts struct
+#[derive(Debug)]
+struct AccountField {
+    name: String,
+    line: usize,
+    writable: bool,
+    signer: bool,
+    /// Whether the field is an `UncheckedAccount` or an `AccountInfo`
+    unchecked: bool,
+}
+
+impl SourceProgram {
+    /// Collect the handlers and accounts structs of a list of items
+    fn collect(&mut self, file: &str, items: &[Item], in_program: bool) {
+        for item in items {
+            match item {
+                Item::Mod(item_mod) => {
+                    let is_program = item_mod.attrs.iter().any(|attr| attr.path().is_ident("program"));
+                    if let Some((_, content)) = &item_mod.content {
+                        self.collect(file, content, in_program || is_program);
+                    }
+                }
+                Item::Fn(item_fn) if in_program && matches!(item_fn.vis, syn::Visibility::Public(_)) => {
+                    let mut accounts = None;
+                    let mut args = Vec::new();
+                    for input in &item_fn.sig.inputs {
+                        let syn::FnArg::Typed(pat_type) = input else {
+                            continue;
+                        };
+                        match context_accounts(&pat_type.ty) {
+                            Some(context) => accounts = Some(context),
+                            None => args.push((pat_type.pat.to_token_stream().to_string().replace(' ', ""), idl_type(&pat_type.ty))),
+                        }
+                    }
+                    self.handlers.push(Handler {
+                        name: item_fn.sig.ident.to_string(),
+                        file: file.to_string(),
+                        line: item_fn.sig.ident.span().start().line,
+                        accounts,
+                        args,
+                    });
+                }
+                Item::Struct(item_struct) if derives(&item_struct.attrs, "Accounts") => {
+                    let fields = item_struct
+                        .fields
+                        .iter()
+                        .filter_map(|field| {
+                            let constraints = account_constraints(&field.attrs);
+                            let has = |constraint: &str| constraints.iter().any(|name| name == constraint);
+                            let initialized = has("init") || has("init_if_needed");
+                            let ty = outer_type(&field.ty);
+                            let ident = field.ident.as_ref()?;
+                            Some(AccountField {
+                                name: ident.to_string(),
+                                line: ident.span().start().line,
+                                writable: has("mut") || has("zero") || initialized,
+                                // Accounts created without seeds sign their creation
+                                signer: ty.as_deref() == Some("Signer") || has("signer") || (initialized && !has("seeds")),
+                                unchecked: ty.is_some_and(|ty| UNCHECKED_TYPES.contains(&ty.as_str())),
+                            })
+                        })
+                        .collect();
+                    self.accounts.push(AccountsStruct {
+                        name: item_struct.ident.to_string(),
+                        file: file.to_string(),
+                        line: item_struct.ident.span().start().line,
+                        fields,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Helper function to read an instruction of the IDL
+fn parse_instruction(instruction: &Value) -> IdlInstruction {
+    let mut parsed = IdlInstruction {
+        name: snake_case(instruction.get("name").and_then(Value::as_str).unwrap_or_default()),
+        ..Default::default()
+    };
+    for account in instruction.get("accounts").and_then(Value::as_array).into_iter().flatten() {
+        let name = snake_case(account.get("name").and_then(Value::as_str).unwrap_or_default());
+        if account.get("accounts").is_some() {
+            parsed.composites.insert(name);
+            continue;
+        }
+        let flag = |keys: [&str; 2]| keys.iter().any(|key| account.get(*key).and_then(Value::as_bool) == Some(true));
+        parsed.accounts.push(IdlAccount {
+            name,
+            writable: flag(["writable", "isMut"]),
+            signer: flag(["signer", "isSigner"]),
+            documented: account.get("docs").and_then(Value::as_array).is_some_and(|docs| !docs.is_empty()),
+        });
+    }
+    for arg in instruction.get("args").and_then(Value::as_array).into_iter().flatten() {
+        let name = snake_case(arg.get("name").and_then(Value::as_str).unwrap_or_default());
+        parsed.args.push((name, arg.get("type").map(json_type).unwrap_or_default()));
+    }
+    parsed
+}
+
+/// Helper function to write an IDL type in the notation of [`IdlInstruction::args`]
+fn json_type(ty: &Value) -> String {
+    if let Some(name) = ty.as_str() {
+        return match name {
+            "publicKey" => "pubkey".to_string(),
+            _ => name.to_string(),
+        };
+    }
+    let field = |key: &str| ty.get(key);
+    if let Some(inner) = field("vec") {
+        return format!("vec<{}>", json_type(inner));
+    }
+    if let Some(inner) = field("option") {
+        return format!("option<{}>", json_type(inner));
+    }
+    if let Some([inner, len]) = field("array").and_then(Value::as_array).map(Vec::as_slice) {
+        return format!("[{};{len}]", json_type(inner));
+    }
+    // `{"defined": "Name"}` before Anchor 0.30, `{"defined": {"name": "path::Name"}}` since
+    let defined = field("defined").and_then(|defined| defined.as_str().or_else(|| defined.get("name").and_then(Value::as_str)));
+    defined.map_or_else(|| ty.to_string(), |name| name.rsplit("::").next().unwrap_or(name).to_string())
+}
+
+/// Helper function to write a Rust type in the notation of [`IdlInstruction::args`]
+fn idl_type(ty: &syn::Type) -> String {
+    match ty {
+        syn::Type::Path(type_path) => {
+            let Some(segment) = type_path.path.segments.last() else {
+                return String::new();
+            };
+            let name = segment.ident.to_string();
+            let generic = match &segment.arguments {
+                syn::PathArguments::AngleBracketed(arguments) => arguments.args.iter().find_map(|argument| match argument {
+                    syn::GenericArgument::Type(ty) => Some(idl_type(ty)),
+                    _ => None,
+                }),
+                _ => None,
+            };
+            match (name.as_str(), generic) {
+                ("Pubkey", _) => "pubkey".to_string(),
+                ("String", _) => "string".to_string(),
+                ("Vec", Some(inner)) if inner == "u8" => "bytes".to_string(),
+                ("Vec", Some(inner)) => format!("vec<{inner}>"),
+                ("Option", Some(inner)) => format!("option<{inner}>"),
+                ("Box", Some(inner)) => inner,
+                _ => name,
+            }
+        }
+        syn::Type::Array(array) => format!("[{};{}]", idl_type(&array.elem), array.len.to_token_stream().to_string().replace(' ', "")),
+        syn::Type::Reference(reference) => idl_type(&reference.elem),
+        _ => ty.to_token_stream().to_string().replace(' ', ""),
+    }
+}
+
+/// Helper function to get the accounts struct of a `Context<T>` type
+fn context_accounts(ty: &syn::Type) -> Option<String> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last().filter(|segment| segment.ident == "Context")?;
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    arguments.args.iter().find_map(|argument| match argument {
+        syn::GenericArgument::Type(ty) => outer_type(ty),
+        _ => None,
+    })
+}
+
+/// Helper function to get the name of the outer type of a field (`Account` for `Account<'info, Vault>`)
+fn outer_type(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(type_path) => type_path.path.segments.last().map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Helper function to list the constraints of the `#[account(..)]` attributes of a field
+/// (`mut`, `init`, `seeds`...)
+fn account_constraints(attrs: &[Attribute]) -> Vec<String> {
+    let mut constraints = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("account")) {
+        let syn::Meta::List(meta_list) = &attr.meta else {
+            continue;
+        };
+        let mut expects_name = true;
+        for token in meta_list.tokens.clone() {
+            match token {
+                proc_macro2::TokenTree::Punct(punct) if punct.as_char() == ',' => expects_name = true,
+                proc_macro2::TokenTree::Ident(ident) if expects_name => {
+                    constraints.push(ident.to_string());
+                    expects_name = false;
+                }
+                _ => expects_name = false,
+            }
+        }
+    }
+    constraints
+}
+
+/// Helper function to convert a camel case name of an old IDL to snake case
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len());
+    for (index, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if index > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+/// Helper function to convert a snake case name to camel case, as in old IDLs
+fn camel_case(name: &str) -> String {
+    let mut parts = name.split('_');
+    let mut camel = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            camel.push(first.to_ascii_uppercase());
+            camel.extend(chars);
+        }
+    }
+    camel
+}
@@ -0,0 +1,148 @@
+use crate::analyzer::{Finding, Severity};
+use crate::analyzer::idl::{IDL_DRIFT_ID, Idl, IdlError, account_constraints, compare, idl_type};
+use std::path::PathBuf;
+use syn::parse::Parser;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+use anchor_lang::prelude::*;
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64, memo: Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn close(ctx: Context<Close>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: Account<'info, Vault>,
+    pub owner: Signer<'info>,
+    /// CHECK: only receives lamports
+    #[account(mut)]
+    pub fee_receiver: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Close<'info> {
+    #[account(mut, close = owner)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+"#;
+
+    /// IDL of Anchor 0.30 built before `close` was added, `vault` being read-only, `fee_receiver`
+    /// undocumented and `memo` a string
+    const IDL: &str = r#"{
+  "address": "Vau1t11111111111111111111111111111111111111",
+  "instructions": [
+    {
+      "name": "deposit",
+      "accounts": [
+        { "name": "vault" },
+        { "name": "owner", "signer": true },
+        { "name": "fee_receiver", "writable": true }
+      ],
+      "args": [
+        { "name": "amount", "type": "u64" },
+        { "name": "memo", "type": "string" }
+      ]
+    },
+    {
+      "name": "withdraw",
+      "accounts": [],
+      "args": []
+    }
+  ]
+}"#;
+
+    /// Same instruction in the IDL format before Anchor 0.30
+    const LEGACY_IDL: &str = r#"{
+  "version": "0.1.0",
+  "name": "vault",
+  "instructions": [
+    {
+      "name": "deposit",
+      "accounts": [
+        { "name": "vault", "isMut": true, "isSigner": false },
+        { "name": "owner", "isMut": false, "isSigner": true },
+        { "name": "feeReceiver", "isMut": true, "isSigner": false, "docs": ["only receives lamports"] }
+      ],
+      "args": [
+        { "name": "amount", "type": "u64" },
+        { "name": "memo", "type": "bytes" }
+      ]
+    },
+    {
+      "name": "close",
+      "accounts": [
+        { "name": "vault", "isMut": true, "isSigner": false },
+        { "name": "owner", "isMut": true, "isSigner": true }
+      ],
+      "args": []
+    }
+  ]
+}"#;
+
+    fn findings(idl: &str) -> Vec<Finding> {
+        let idl = Idl::parse(PathBuf::from("target/idl/vault.json"), idl.to_string()).unwrap();
+        let ast = syn::parse_file(SOURCE).unwrap();
+        let findings = compare(&idl, &[("src/lib.rs", &ast, SOURCE)], Some("vault"));
+        assert!(findings.iter().all(|finding| finding.rules[0].id == IDL_DRIFT_ID));
+        findings
+    }
+
+    #[test]
+    fn test_stale_idl_is_reported() {
+        let findings = findings(IDL);
+        let lines: Vec<usize> = findings.iter().map(|finding| finding.location.line).collect();
+        assert_eq!(lines, [8, 20, 24, 12, 17], "{findings:#?}");
+        assert!(findings[0].description.contains("'memo' of 'deposit' has type bytes in the code but string in the IDL"));
+        assert!(findings[1].description.contains("'vault' of 'Deposit' is mutable in the code but read-only in the IDL"));
+        assert!(findings[2].description.starts_with("Unchecked account 'fee_receiver'"));
+        assert_eq!(findings[2].severity, Severity::Informational);
+        assert!(findings[3].description.starts_with("Instruction 'close' is missing from the IDL"));
+        assert!(findings[4].description.starts_with("Instruction 'withdraw' of the IDL is not in the program"));
+        assert_eq!(findings[4].location.file, "target/idl/vault.json");
+    }
+
+    #[test]
+    fn test_legacy_idl_format() {
+        assert!(findings(LEGACY_IDL).is_empty(), "{:#?}", findings(LEGACY_IDL));
+
+        // The program ID, under `address` or `metadata.address` depending on the format
+        let idl = Idl::parse(PathBuf::from("vault.json"), IDL.to_string()).unwrap();
+        assert_eq!(idl.address.as_deref(), Some("Vau1t11111111111111111111111111111111111111"));
+        let legacy = r#"{"instructions": [], "metadata": {"address": "Vau1t11111111111111111111111111111111111111"}}"#;
+        let idl = Idl::parse(PathBuf::from("vault.json"), legacy.to_string()).unwrap();
+        assert_eq!(idl.address.as_deref(), Some("Vau1t11111111111111111111111111111111111111"));
+
+        let invalid = Idl::parse(PathBuf::from("vault.json"), "{\"name\": \"vault\"}".to_string());
+        assert!(matches!(invalid, Err(IdlError::Parse { .. })));
+    }
+
+    #[test]
+    fn test_source_notation() {
+        let ty = |source: &str| idl_type(&syn::parse_str(source).unwrap());
+        assert_eq!(ty("Pubkey"), "pubkey");
+        assert_eq!(ty("Option<Vec<Pubkey>>"), "option<vec<pubkey>>");
+        assert_eq!(ty("[u8; 32]"), "[u8;32]");
+        assert_eq!(ty("state::Config"), "Config");
+
+        let field: syn::Field = syn::Field::parse_named
+            .parse_str("#[account(init, payer = owner, space = 8 + Vault::LEN, seeds = [b\"v\", owner.key().as_ref()], bump)] pub vault: Account<'info, Vault>")
+            .unwrap();
+        assert_eq!(account_constraints(&field.attrs), ["init", "payer", "space", "seeds", "bump"]);
+    }
+}
@@ -0,0 +1,308 @@
+use log::{debug, warn};
+use regex::Regex;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::config::PathFilter;
+
+#[cfg(test)]
+mod test;
+
+/// Ignore files read in each directory of the project, the later ones taking precedence
+pub const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// Ignore file of the repository, below its `.git` directory, which the ignore files take
+/// precedence over
+pub const GIT_EXCLUDE_FILE: &str = "info/exclude";
+
+/// Path left out of the analysis, with the rule that excluded it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Exclusion {
+    /// Path relative to the project (everything under an excluded directory is left out)
+    pub path: String,
+    /// Rule that excluded it: `--exclude <glob>`, `paths.exclude <glob>`, or the ignore file and
+    /// its pattern (`.gitignore: target/`)
+    pub rule: String,
+}
+
+/// Patterns of a `.gitignore` (or `.ignore`) file
+///
+/// A pattern with a `/` before its end is anchored to the directory of the file, others match
+/// a name at any depth; a trailing `/` only matches directories, a leading `!` includes again
+/// what an earlier pattern excluded. The last matching pattern wins.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreFile {
+    patterns: Vec<IgnorePattern>,
+}
+
+/// Pattern of an ignore file
+#[derive(Debug, Clone)]
+struct IgnorePattern {
+    /// Pattern as written in the file
+    source: String,
+    regex: Regex,
+    negated: bool,
+    directory_only: bool,
+}
+
+impl IgnoreFile {
+    /// Parse the contents of an ignore file, skipping the patterns that are not valid
+    pub fn parse(contents: &str) -> Self {
+        let patterns = contents
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim_end();
+                if line.is_empty() || line.starts_with('#') {
+                    return None;
+                }
+                let (negated, pattern) = match line.strip_prefix('!') {
+                    Some(pattern) => (true, pattern),
+                    None => (false, line.strip_prefix('\\').unwrap_or(line)),
+                };
+                let (directory_only, pattern) = match pattern.strip_suffix('/') {
+                    Some(pattern) => (true, pattern),
+                    None => (false, pattern),
+                };
+                let anchored = pattern.contains('/');
+                let pattern = pattern.trim_start_matches('/');
+                let prefix = if anchored { "^" } else { "^(?:.*/)?" };
+                match Regex::new(&format!("{prefix}{}$", glob_to_regex(pattern))) {
+                    Ok(regex) => Some(IgnorePattern { source: line.to_string(), regex, negated, directory_only }),
+                    Err(e) => {
+                        warn!("Skipping the ignore pattern '{line}': {e}");
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Whether the file excludes a path relative to its directory (`Some(false)` if a negated
+    /// pattern includes it again), with the pattern that decided it
+    pub fn matches(&self, relative_path: &str, is_dir: bool) -> Option<(bool, &str)> {
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| (is_dir || !pattern.directory_only) && pattern.regex.is_match(relative_path))
+            .map(|pattern| (!pattern.negated, pattern.source.as_str()))
+    }
+
+    /// Whether the file has no pattern
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+/// Decides which paths of a project are left out of the analysis, and records them
+///
+/// Paths are excluded by the `--exclude` globs, the `paths.exclude` globs of the configuration,
+/// and unless disabled by the [`IGNORE_FILES`] of the project directory and its subdirectories.
+/// In a git repository, the ignore files of the directories above the project, up to the root
+/// of the repository, and its [`GIT_EXCLUDE_FILE`] apply too, like they do for git.
+#[derive(Debug)]
+pub struct Excluder {
+    /// Project directory, the paths are relative to it
+    root: PathBuf,
+    /// Globs of `--exclude`
+    cli: PathFilter,
+    /// Globs of `paths.exclude`
+    config: PathFilter,
+    /// Whether the ignore files are read
+    ignore_files: bool,
+    /// Ignore files above the project, the later ones taking precedence, with the path of the
+    /// project relative to their directory
+    outer: Vec<(String, PathBuf, IgnoreFile)>,
+    /// Ignore files of each directory read so far, by path relative to the project
+    loaded: RefCell<HashMap<PathBuf, Vec<(String, IgnoreFile)>>>,
+    /// Paths excluded so far
+    exclusions: RefCell<Vec<Exclusion>>,
+}
+
+impl Excluder {
+    /// Creates the excluder of the project at `root`
+    pub fn new(root: &Path, cli: PathFilter, config: PathFilter, ignore_files: bool) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            cli,
+            config,
+            ignore_files,
+            outer: if ignore_files { outer_ignore_files(root) } else { Vec::new() },
+            loaded: RefCell::new(HashMap::new()),
+            exclusions: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Check if a path (relative to the project) is excluded, recording it if it is
+    ///
+    /// A path under an excluded directory is excluded too.
+    pub fn is_excluded(&self, relative_path: &Path) -> bool {
+        match self.rule(relative_path) {
+            Some(rule) => {
+                debug!("Excluding {} ({rule})", relative_path.display());
+                self.exclusions
+                    .borrow_mut()
+                    .push(Exclusion { path: slash_path(relative_path), rule });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the paths excluded so far, in the order they were found
+    pub fn exclusions(&self) -> Vec<Exclusion> {
+        self.exclusions.borrow().clone()
+    }
+
+    /// Rule excluding a path relative to the project, if any
+    fn rule(&self, relative_path: &Path) -> Option<String> {
+        if let Some(pattern) = self.cli.matching(relative_path) {
+            return Some(format!("--exclude {pattern}"));
+        }
+        if let Some(pattern) = self.config.matching(relative_path) {
+            return Some(format!("paths.exclude {pattern}"));
+        }
+        if !self.ignore_files {
+            return None;
+        }
+        // The directories above the path first: nothing under an excluded directory is included again
+        let mut prefix = PathBuf::new();
+        let components: Vec<_> = relative_path.components().collect();
+        for (index, component) in components.iter().enumerate() {
+            let dir = prefix.clone();
+            prefix.push(component);
+            let is_dir = index + 1 < components.len() || self.root.join(&prefix).is_dir();
+            if let Some(rule) = self.ignored(&dir, &prefix, is_dir) {
+                return Some(rule);
+            }
+        }
+        None
+    }
+
+    /// Rule of the ignore files of `dir` and the directories above it excluding `path`, the
+    /// deepest ignore files taking precedence
+    fn ignored(&self, dir: &Path, path: &Path, is_dir: bool) -> Option<String> {
+        let mut decision = None;
+        for (name, project, file) in &self.outer {
+            if let Some((excluded, pattern)) = file.matches(&slash_path(&project.join(path)), is_dir) {
+                decision = Some((excluded, format!("{name}: {pattern}")));
+            }
+        }
+        for ancestor in dir.ancestors().collect::<Vec<_>>().into_iter().rev() {
+            let relative = slash_path(path.strip_prefix(ancestor).unwrap_or(path));
+            let mut loaded = self.loaded.borrow_mut();
+            let files = loaded.entry(ancestor.to_path_buf()).or_insert_with(|| self.read_ignore_files(ancestor));
+            for (name, file) in files.iter() {
+                if let Some((excluded, pattern)) = file.matches(&relative, is_dir) {
+                    decision = Some((excluded, format!("{name}: {pattern}")));
+                }
+            }
+        }
+        decision.and_then(|(excluded, rule)| excluded.then_some(rule))
+    }
+
+    /// Read the ignore files of a directory of the project
+    fn read_ignore_files(&self, dir: &Path) -> Vec<(String, IgnoreFile)> {
+        IGNORE_FILES
+            .iter()
+            .filter_map(|name| {
+                let contents = std::fs::read_to_string(self.root.join(dir).join(name)).ok()?;
+                let file = IgnoreFile::parse(&contents);
+                (!file.is_empty()).then(|| (slash_path(&dir.join(name)), file))
+            })
+            .collect()
+    }
+}
+
+/// Helper function to read the ignore files applying to a project from outside of it: the
+/// [`GIT_EXCLUDE_FILE`] of its git repository, then the [`IGNORE_FILES`] of the directories from
+/// the root of the repository down to the parent of the project (none outside of a repository)
+fn outer_ignore_files(root: &Path) -> Vec<(String, PathBuf, IgnoreFile)> {
+    let Ok(root) = root.canonicalize() else {
+        return Vec::new();
+    };
+    let Some(repository) = root.ancestors().find(|dir| dir.join(".git").exists()) else {
+        return Vec::new();
+    };
+    // Name of the files relative to the project (`../.gitignore`)
+    let name = |dir: &Path, file: &str| {
+        let depth = root.strip_prefix(dir).map_or(0, |path| path.components().count());
+        slash_path(&PathBuf::from_iter(std::iter::repeat_n("..", depth)).join(file))
+    };
+    let read = |path: &Path| std::fs::read_to_string(path).map(|contents| IgnoreFile::parse(&contents)).ok();
+
+    let mut files = Vec::new();
+    let project = root.strip_prefix(repository).unwrap_or(&root).to_path_buf();
+    if let Some(git_dir) = git_dir(repository)
+        && let Some(file) = read(&git_dir.join(GIT_EXCLUDE_FILE)).filter(|file| !file.is_empty())
+    {
+        files.push((name(repository, &format!(".git/{GIT_EXCLUDE_FILE}")), project.clone(), file));
+    }
+    let above: Vec<&Path> = root.ancestors().skip(1).take_while(|dir| dir.starts_with(repository)).collect();
+    for dir in above.into_iter().rev() {
+        let project = root.strip_prefix(dir).unwrap_or(&root).to_path_buf();
+        for file_name in IGNORE_FILES {
+            if let Some(file) = read(&dir.join(file_name)).filter(|file| !file.is_empty()) {
+                files.push((name(dir, file_name), project.clone(), file));
+            }
+        }
+    }
+    files
+}
+
+/// Helper function to find the git directory of a repository: its `.git` directory, or the one a
+/// `.git` file points to (worktrees and submodules)
+fn git_dir(repository: &Path) -> Option<PathBuf> {
+    let dot_git = repository.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let contents = std::fs::read_to_string(&dot_git).ok()?;
+    let git_dir = Path::new(contents.strip_prefix("gitdir:")?.trim());
+    Some(repository.join(git_dir))
+}
+
+/// Path with `/` separators
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Helper function to convert a glob into a regex, unanchored (`*` and `?` do not match `/`,
+/// `**` matches any number of directories, `[...]` classes are kept)
+pub(crate) fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // `**/` also matches no directory at all
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                let class: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                let class = class.strip_prefix('!').map(|class| format!("^{class}")).unwrap_or(class);
+                regex.push_str(&format!("[{}]", class.replace('\\', "\\\\")));
+            }
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    regex.push_str(&regex::escape(&escaped.to_string()));
+                }
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex
+}
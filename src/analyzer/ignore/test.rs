@@ -0,0 +1,103 @@
+use std::path::Path;
+
+use crate::analyzer::config::PathFilter;
+use crate::analyzer::ignore::{Exclusion, Excluder, IgnoreFile};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignore_patterns() {
+        let file = IgnoreFile::parse("# build output\n/target\nnode_modules/\n*.generated.rs\n!keep.generated.rs\ndocs/**/*.rs\n\\#notes.rs\n\n");
+
+        assert_eq!(file.matches("target", true), Some((true, "/target")));
+        // Anchored to the directory of the file
+        assert_eq!(file.matches("programs/vault/target", true), None);
+        // Only directories
+        assert_eq!(file.matches("web/node_modules", true), Some((true, "node_modules/")));
+        assert_eq!(file.matches("node_modules", false), None);
+        // At any depth, and included again by a later pattern
+        assert_eq!(file.matches("src/idl.generated.rs", false), Some((true, "*.generated.rs")));
+        assert_eq!(file.matches("src/keep.generated.rs", false), Some((false, "!keep.generated.rs")));
+        assert_eq!(file.matches("docs/lib.rs", false), Some((true, "docs/**/*.rs")));
+        assert_eq!(file.matches("docs/a/b/lib.rs", false), Some((true, "docs/**/*.rs")));
+        assert_eq!(file.matches("#notes.rs", false), Some((true, "\\#notes.rs")));
+        assert_eq!(file.matches("src/lib.rs", false), None);
+
+        assert!(IgnoreFile::parse("# only comments\n\n").is_empty());
+    }
+
+    #[test]
+    fn test_excluder() {
+        let root = std::env::temp_dir().join(format!("solana-analyzer-ignore-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("programs/vault/src")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n*.bak.rs\n").unwrap();
+        std::fs::write(root.join("programs/vault/.ignore"), "!lib.bak.rs\nfixtures\n").unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+
+        let cli = PathFilter::new(&["vendor".to_string()]).unwrap();
+        let config = PathFilter::new(&["**/generated/*.rs".to_string()]).unwrap();
+        let excluder = Excluder::new(&root, cli, config, true);
+
+        assert!(excluder.is_excluded(Path::new("target")));
+        assert!(excluder.is_excluded(Path::new("vendor/spl/lib.rs")));
+        assert!(excluder.is_excluded(Path::new("programs/vault/src/generated/idl.rs")));
+        assert!(excluder.is_excluded(Path::new("programs/vault/src/old.bak.rs")));
+        assert!(excluder.is_excluded(Path::new("programs/vault/fixtures/lib.rs")));
+        // The deeper ignore file includes it again
+        assert!(!excluder.is_excluded(Path::new("programs/vault/src/lib.bak.rs")));
+        assert!(!excluder.is_excluded(Path::new("programs/vault/src/lib.rs")));
+
+        let rules: Vec<String> = excluder.exclusions().into_iter().map(|Exclusion { path, rule }| format!("{path} ({rule})")).collect();
+        assert_eq!(
+            rules,
+            vec![
+                "target (.gitignore: target/)",
+                "vendor/spl/lib.rs (--exclude vendor)",
+                "programs/vault/src/generated/idl.rs (paths.exclude **/generated/*.rs)",
+                "programs/vault/src/old.bak.rs (.gitignore: *.bak.rs)",
+                "programs/vault/fixtures/lib.rs (programs/vault/.ignore: fixtures)",
+            ]
+        );
+
+        // Without the ignore files, only the globs exclude paths
+        let excluder = Excluder::new(&root, PathFilter::default(), PathFilter::default(), false);
+        assert!(!excluder.is_excluded(Path::new("target")));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_excluder_reads_the_ignore_files_of_the_repository() {
+        let repository = std::env::temp_dir().join(format!("solana-analyzer-ignore-repository-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&repository);
+        let root = repository.join("programs/vault");
+        std::fs::create_dir_all(repository.join(".git/info")).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(repository.join(".git/info/exclude"), "*.log.rs\nscratch.rs\n").unwrap();
+        std::fs::write(repository.join(".gitignore"), "programs/vault/generated/\n").unwrap();
+        std::fs::write(repository.join("programs/.ignore"), "fixtures\n").unwrap();
+        std::fs::write(root.join(".gitignore"), "!scratch.rs\n").unwrap();
+
+        let excluder = Excluder::new(&root, PathFilter::default(), PathFilter::default(), true);
+        assert!(excluder.is_excluded(Path::new("generated/idl.rs")));
+        assert!(excluder.is_excluded(Path::new("src/debug.log.rs")));
+        assert!(excluder.is_excluded(Path::new("fixtures/lib.rs")));
+        // The ignore files of the project take precedence
+        assert!(!excluder.is_excluded(Path::new("src/scratch.rs")));
+        assert!(!excluder.is_excluded(Path::new("src/lib.rs")));
+
+        let rules: Vec<String> = excluder.exclusions().into_iter().map(|Exclusion { rule, .. }| rule).collect();
+        assert_eq!(
+            rules,
+            vec![
+                "../../.gitignore: programs/vault/generated/",
+                "../../.git/info/exclude: *.log.rs",
+                "../.ignore: fixtures",
+            ]
+        );
+
+        std::fs::remove_dir_all(&repository).unwrap();
+    }
+}
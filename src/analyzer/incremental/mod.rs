@@ -0,0 +1,135 @@
+use log::{debug, warn};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::dsl::ProgramContext;
+use crate::analyzer::{AnalysisOptions, Finding};
+
+#[cfg(test)]
+mod test;
+
+/// Default name of the cache directory, created in the analyzed project
+pub const CACHE_DIR_NAME: &str = ".analyzer-cache";
+
+/// Findings of the per-file rules on a file, by rule ID (before suppressions)
+pub type RuleFindings = Vec<(String, Vec<Finding>)>;
+
+/// Cache of the per-file rule results, reused while a file and the rule set do not change
+///
+/// Entries are stored under a directory named after the key of the rule set (analyzer version,
+/// active rules, their configuration and the content of the custom rules), so changing the
+/// rules or the configuration invalidates every entry. Each entry is keyed by the path and
+/// content of the file, and by the declarations of the program rules can resolve in other files
+/// (see [`program_key`]).
+#[derive(Debug, Clone)]
+pub struct AnalysisCache {
+    /// Directory of the entries of the current rule set
+    dir: PathBuf,
+}
+
+impl AnalysisCache {
+    /// Open the cache in `root` for the rule set with the given key, removing the entries of
+    /// other rule sets
+    pub fn open(root: &Path, rule_set_key: &str) -> std::io::Result<Self> {
+        let dir = root.join(rule_set_key);
+        fs::create_dir_all(&dir)?;
+        // The cache is never meant to be committed
+        let gitignore = root.join(".gitignore");
+        if !gitignore.exists() {
+            fs::write(gitignore, "*\n")?;
+        }
+
+        for entry in fs::read_dir(root)?.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.path().is_dir() && name != rule_set_key && is_key(&name) {
+                debug!("Removing stale cache entries in {}", entry.path().display());
+                if let Err(e) = fs::remove_dir_all(entry.path()) {
+                    warn!("Cannot remove stale cache entries in {}: {e}", entry.path().display());
+                }
+            }
+        }
+        Ok(Self { dir })
+    }
+
+    /// Load the cached results of a file, if neither its content nor the program declarations changed
+    pub fn load(&self, file_path: &str, source_code: &str, program_key: &str) -> Option<RuleFindings> {
+        let content = fs::read(self.entry_path(file_path, source_code, program_key)).ok()?;
+        match serde_json::from_slice(&content) {
+            Ok(findings) => Some(findings),
+            Err(e) => {
+                debug!("Ignoring invalid cache entry for {file_path}: {e}");
+                None
+            }
+        }
+    }
+
+    /// Store the results of a file (failures only disable the cache for this file)
+    pub fn store(&self, file_path: &str, source_code: &str, program_key: &str, findings: &RuleFindings) {
+        let path = self.entry_path(file_path, source_code, program_key);
+        let result = serde_json::to_vec(findings).map_err(std::io::Error::other).and_then(|content| {
+            // Written to a temporary file first so a concurrent run never reads a partial entry
+            let temporary = path.with_extension(format!("{}.tmp", std::process::id()));
+            fs::write(&temporary, content)?;
+            fs::rename(&temporary, &path)
+        });
+        if let Err(e) = result {
+            warn!("Cannot cache the results of {file_path}: {e}");
+        }
+    }
+
+    /// Helper function to get the entry of a file version
+    fn entry_path(&self, file_path: &str, source_code: &str, program_key: &str) -> PathBuf {
+        let key = hash(&[file_path.as_bytes(), source_code.as_bytes(), program_key.as_bytes()]);
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+/// Compute the key of a rule set: analyzer version, active rules, severity and parameter
+/// overrides, and the files of the custom templates and plugins
+pub fn rule_set_key(rule_ids: &[&str], options: &AnalysisOptions) -> String {
+    let mut rule_ids = rule_ids.to_vec();
+    rule_ids.sort_unstable();
+
+    let version = env!("CARGO_PKG_VERSION");
+    let rules = rule_ids.join(",");
+    let severities = format!("{:?}", options.severity_overrides);
+    let params = format!("{:?}", options.rule_params);
+    let mut parts: Vec<Vec<u8>> = vec![version.into(), rules.into(), severities.into(), params.into()];
+
+    // Custom rules are identified by the content of their files
+    let custom_dirs = [&options.custom_templates_path, &options.plugins_path];
+    for dir in custom_dirs.into_iter().flatten() {
+        for path in crate::analyzer::templates::find_template_files(&*options.file_system, Path::new(dir), &["yaml", "yml", "rhai", "wasm"]) {
+            parts.push(path.to_string_lossy().as_bytes().to_vec());
+            parts.push(options.file_system.read(&path).unwrap_or_default());
+        }
+    }
+
+    let parts: Vec<&[u8]> = parts.iter().map(Vec::as_slice).collect();
+    hash(&parts)
+}
+
+/// Compute the key of the declarations of a program: editing a function body without changing
+/// its calls and checks, or moving code around, keeps the cached results of the other files,
+/// changing a declaration (e.g. an account field) invalidates them
+pub fn program_key(program: &ProgramContext) -> String {
+    hash(&[format!("{:?}", program.without_positions()).as_bytes()])
+}
+
+/// Helper function to check if a directory name is the key of a rule set (other directories are
+/// never removed)
+fn is_key(name: &str) -> bool {
+    name.len() == 32 && name.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// Helper function to hash a list of byte strings (hex, 32 characters)
+fn hash(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        // Length prefix so the boundaries between parts are part of the key
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize()[..16].iter().map(|byte| format!("{byte:02x}")).collect()
+}
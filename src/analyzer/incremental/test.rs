@@ -0,0 +1,74 @@
+use crate::analyzer::incremental::{AnalysisCache, RuleFindings, program_key, rule_set_key};
+use crate::analyzer::dsl::ProgramContext;
+use crate::analyzer::{AnalysisOptions, Severity};
+use crate::analyzer::testing::FindingBuilder;
+use std::path::PathBuf;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("solana-analyzer-cache-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        root
+    }
+
+    fn rule_findings() -> RuleFindings {
+        let finding = FindingBuilder::new("Division Without Zero Check in 'share'")
+            .span("src/lib.rs", 2, 4, 19)
+            .snippet("amount / shares")
+            .recommendations(&["Use checked_div"])
+            .build();
+        vec![("solana-division-by-zero".to_string(), vec![finding]), ("owner-check".to_string(), Vec::new())]
+    }
+
+    #[test]
+    fn test_cache_entries_follow_file_content() {
+        let root = cache_root("entries");
+        let cache = AnalysisCache::open(&root, &rule_set_key(&["solana-division-by-zero"], &AnalysisOptions::default())).unwrap();
+        let program = program_key(&ProgramContext::new());
+        assert!(cache.load("src/lib.rs", "fn a() {}", &program).is_none());
+
+        cache.store("src/lib.rs", "fn a() {}", &program, &rule_findings());
+        let loaded = cache.load("src/lib.rs", "fn a() {}", &program).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].1[0].location, rule_findings()[0].1[0].location);
+
+        // Another content, another file or other declarations in the program is a different entry
+        assert!(cache.load("src/lib.rs", "fn a() { }", &program).is_none());
+        assert!(cache.load("src/other.rs", "fn a() {}", &program).is_none());
+        let state = syn::parse_file("#[account] pub struct Vault { pub authority: Pubkey }").unwrap();
+        let other_program = program_key(&ProgramContext::build([("src/state.rs", &state)]));
+        assert!(cache.load("src/lib.rs", "fn a() {}", &other_program).is_none());
+
+        // Shifting the declarations of another file by a few lines keeps the entry
+        let shifted = syn::parse_file("\n\n#[account] pub struct Vault {\n    pub authority: Pubkey,\n}").unwrap();
+        assert_eq!(program_key(&ProgramContext::build([("src/state.rs", &shifted)])), other_program);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_rule_set_changes_invalidate_the_cache() {
+        let options = AnalysisOptions::default();
+        let key = rule_set_key(&["a", "b"], &options);
+        assert_eq!(key, rule_set_key(&["b", "a"], &options));
+        assert_ne!(key, rule_set_key(&["a"], &options));
+
+        let mut overridden = AnalysisOptions::default();
+        overridden.severity_overrides.insert("a".to_string(), Severity::High);
+        let other_key = rule_set_key(&["a", "b"], &overridden);
+        assert_ne!(key, other_key);
+
+        let root = cache_root("invalidation");
+        AnalysisCache::open(&root, &key).unwrap().store("src/lib.rs", "", "", &rule_findings());
+        std::fs::create_dir_all(root.join("keep")).unwrap();
+
+        // Opening the cache for another rule set removes the stale entries only
+        let cache = AnalysisCache::open(&root, &other_key).unwrap();
+        assert!(cache.load("src/lib.rs", "", "").is_none());
+        assert!(!root.join(&key).exists());
+        assert!(root.join("keep").exists());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
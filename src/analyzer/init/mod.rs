@@ -0,0 +1,278 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::config::CONFIG_FILE_NAME;
+use crate::analyzer::native::Framework;
+use crate::analyzer::packs::DEFAULT_PROFILE;
+use crate::analyzer::workspace::{Workspace, WorkspaceError};
+
+#[cfg(test)]
+mod test;
+
+/// Command installing the analyzer in the CI snippets
+pub const INSTALL_COMMAND: &str = "cargo install --git https://github.com/scab24/Solana_Static_Analyzer --locked";
+
+/// Paths excluded in Anchor workspaces: build output, client dependencies and local ledgers
+const ANCHOR_EXCLUDES: &[&str] = &["target", "node_modules", ".anchor", "test-ledger"];
+
+/// Paths excluded in the other projects
+const CARGO_EXCLUDES: &[&str] = &["target"];
+
+/// Error returned when the configuration of a project cannot be scaffolded
+#[derive(Debug)]
+pub enum InitError {
+    /// The file already exists (overwritten with `--force`)
+    Exists(PathBuf),
+    /// The manifests of the project cannot be read
+    Workspace(WorkspaceError),
+    /// A file cannot be written
+    Io(PathBuf, std::io::Error),
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::Exists(path) => write!(f, "{} already exists (use --force to overwrite it)", path.display()),
+            InitError::Workspace(e) => write!(f, "{e}"),
+            InitError::Io(path, e) => write!(f, "Cannot write {}: {e}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for InitError {}
+
+impl From<WorkspaceError> for InitError {
+    fn from(error: WorkspaceError) -> Self {
+        InitError::Workspace(error)
+    }
+}
+
+/// CI provider of the snippet written by `init --ci`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiProvider {
+    /// GitHub Actions workflow uploading a SARIF log to code scanning
+    Github,
+    /// GitLab CI job publishing a Code Quality report
+    Gitlab,
+}
+
+impl CiProvider {
+    /// Path of the snippet, relative to the project
+    pub fn path(&self) -> &'static str {
+        match self {
+            CiProvider::Github => ".github/workflows/solana-analyzer.yml",
+            CiProvider::Gitlab => ".gitlab/solana-analyzer.yml",
+        }
+    }
+
+    /// Contents of the snippet
+    pub fn snippet(&self) -> String {
+        match self {
+            CiProvider::Github => format!(
+                r#"# Static analysis of the Solana programs, generated by `rust-solana-analyzer init --ci github`
+name: Solana static analysis
+
+on:
+  push:
+    branches: [main]
+  pull_request:
+
+jobs:
+  solana-analyzer:
+    runs-on: ubuntu-latest
+    permissions:
+      contents: read
+      security-events: write
+    steps:
+      - uses: actions/checkout@v4
+      - uses: dtolnay/rust-toolchain@stable
+      - run: {INSTALL_COMMAND}
+      # Fails on the thresholds of {CONFIG_FILE_NAME}
+      - run: rust-solana-analyzer --path . --analyze --format sarif --output results.sarif
+      - uses: github/codeql-action/upload-sarif@v3
+        if: always()
+        with:
+          sarif_file: results.sarif
+"#
+            ),
+            CiProvider::Gitlab => format!(
+                r#"# Static analysis of the Solana programs, generated by `rust-solana-analyzer init --ci gitlab`
+# Include it from .gitlab-ci.yml:
+#   include:
+#     - local: {}
+solana-analyzer:
+  image: rust:latest
+  script:
+    - {INSTALL_COMMAND}
+    # Fails on the thresholds of {CONFIG_FILE_NAME}
+    - rust-solana-analyzer --path . --analyze --format gitlab --output gl-code-quality.json
+  artifacts:
+    when: always
+    reports:
+      codequality: gl-code-quality.json
+"#,
+                self.path()
+            ),
+        }
+    }
+}
+
+impl std::str::FromStr for CiProvider {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "github" => Ok(CiProvider::Github),
+            "gitlab" => Ok(CiProvider::Gitlab),
+            _ => Err(format!("unknown CI provider '{value}' (github or gitlab)")),
+        }
+    }
+}
+
+/// Program found in a project, with the profile of its framework
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedProgram {
+    /// Name of the crate
+    pub name: String,
+    /// Profile running the rules of its framework (`anchor`, `native`, `pinocchio`, `steel`)
+    pub profile: &'static str,
+}
+
+/// Type of a project, detected from its `Anchor.toml` and the dependencies of its crates
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectSetup {
+    /// Whether the project is an Anchor workspace (it has an `Anchor.toml`)
+    pub anchor_workspace: bool,
+    /// Whether the project is a Cargo workspace (or a single crate if `false`)
+    pub cargo_workspace: bool,
+    /// Programs of the project
+    pub programs: Vec<DetectedProgram>,
+}
+
+impl ProjectSetup {
+    /// Detect the type of the project at `root`
+    pub fn detect(root: &Path) -> Result<Self, InitError> {
+        let anchor_workspace = root.join("Anchor.toml").is_file();
+        let Some(workspace) = Workspace::load(root)? else {
+            return Ok(Self { anchor_workspace, ..Default::default() });
+        };
+        let programs = workspace
+            .programs()
+            .map(|program| DetectedProgram {
+                name: program.name.clone(),
+                profile: if program.anchor_version().is_some() {
+                    "anchor"
+                } else {
+                    match Framework::of_manifest(program) {
+                        Some(Framework::Pinocchio) => "pinocchio",
+                        Some(Framework::Steel) => "steel",
+                        _ => "native",
+                    }
+                },
+            })
+            .collect();
+        Ok(Self {
+            anchor_workspace,
+            cargo_workspace: workspace.crates.len() > 1 || !workspace.crates.iter().any(|member| member.dir == workspace.root),
+            programs,
+        })
+    }
+
+    /// Profile of the project: the profiles of its programs, `anchor` for an Anchor workspace
+    /// without programs yet, and every rule otherwise
+    pub fn profile(&self) -> String {
+        let mut profiles: Vec<&str> = Vec::new();
+        for program in &self.programs {
+            if !profiles.contains(&program.profile) {
+                profiles.push(program.profile);
+            }
+        }
+        match profiles.is_empty() {
+            true if self.anchor_workspace => "anchor".to_string(),
+            true => DEFAULT_PROFILE.to_string(),
+            false => profiles.join(","),
+        }
+    }
+
+    /// Describes the project (`Anchor workspace with the programs vault (anchor), staking (anchor)`)
+    pub fn describe(&self) -> String {
+        let kind = match (self.anchor_workspace, self.cargo_workspace) {
+            (true, _) => "Anchor workspace",
+            (false, true) => "Cargo workspace",
+            (false, false) => "crate",
+        };
+        match self.programs.as_slice() {
+            [] => format!("{kind} without programs"),
+            [program] => format!("{kind} with the program {} ({})", program.name, program.profile),
+            programs => format!(
+                "{kind} with the programs {}",
+                programs.iter().map(|program| format!("{} ({})", program.name, program.profile)).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+
+    /// Commented configuration of the project, with its profile, the build output excluded and
+    /// the analysis failing from high findings
+    pub fn config(&self) -> String {
+        let excludes = if self.anchor_workspace { ANCHOR_EXCLUDES } else { CARGO_EXCLUDES };
+        let excludes: Vec<String> = excludes.iter().map(|exclude| format!("\"{exclude}\"")).collect();
+        format!(
+            r#"# Configuration of rust-solana-analyzer for this {description},
+# generated by `rust-solana-analyzer init`. Command line flags win over these settings.
+
+[rules]
+# Rules to run: a profile (all, anchor, native, pinocchio, steel) or pack names (anchor-core,
+# spl-token, defi, style...), separated by commas
+profile = "{profile}"
+# exclude = ["anchor-instructions"]        # rule IDs to skip
+# ignore_severities = ["informational"]    # severities to skip
+
+# [rules.severity]                         # severity overrides
+# solana-division-by-zero = "high"
+
+[paths]
+# Paths left out of the analysis (globs relative to this directory), along with the paths of
+# the .gitignore and .ignore files
+exclude = [{excludes}]
+# templates = "rules"                      # YAML templates and Rhai scripts of custom rules
+
+[output]
+# path = "solana-analyzer-report.md"       # report file (stdout if not set)
+# format = "markdown"                      # markdown, json, html, sarif, github, gitlab...
+
+[thresholds]
+# Exit with code 1 when a finding has this severity or a higher one
+fail_on = "high"
+# min_confidence = "medium"                # leave out the findings with a lower confidence
+# max_findings = 0                         # findings tolerated from the fail_on severity
+"#,
+            description = self.describe(),
+            profile = self.profile(),
+            excludes = excludes.join(", "),
+        )
+    }
+}
+
+/// Write the configuration of the project at `root`, and the CI snippet if asked, returning the
+/// type of the project and the files written
+///
+/// Existing files are only overwritten with `force`.
+pub fn init(root: &Path, ci: Option<CiProvider>, force: bool) -> Result<(ProjectSetup, Vec<PathBuf>), InitError> {
+    let setup = ProjectSetup::detect(root)?;
+    let mut files = vec![(root.join(CONFIG_FILE_NAME), setup.config())];
+    if let Some(ci) = ci {
+        files.push((root.join(ci.path()), ci.snippet()));
+    }
+    if !force && let Some((path, _)) = files.iter().find(|(path, _)| path.exists()) {
+        return Err(InitError::Exists(path.clone()));
+    }
+
+    for (path, contents) in &files {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| InitError::Io(path.clone(), e))?;
+        }
+        std::fs::write(path, contents).map_err(|e| InitError::Io(path.clone(), e))?;
+    }
+    Ok((setup, files.into_iter().map(|(path, _)| path).collect()))
+}
+
@@ -0,0 +1,71 @@
+use std::path::Path;
+
+use crate::analyzer::config::Config;
+use crate::analyzer::init::{CiProvider, DetectedProgram, ProjectSetup};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(name: &str, profile: &'static str) -> DetectedProgram {
+        DetectedProgram { name: name.to_string(), profile }
+    }
+
+    #[test]
+    fn test_profile_and_description() {
+        let setup = ProjectSetup {
+            anchor_workspace: true,
+            cargo_workspace: true,
+            programs: vec![program("vault", "anchor"), program("staking", "anchor")],
+        };
+        assert_eq!(setup.profile(), "anchor");
+        assert_eq!(setup.describe(), "Anchor workspace with the programs vault (anchor), staking (anchor)");
+
+        let setup = ProjectSetup {
+            anchor_workspace: false,
+            cargo_workspace: true,
+            programs: vec![program("escrow", "pinocchio"), program("token", "native"), program("pool", "native")],
+        };
+        assert_eq!(setup.profile(), "pinocchio,native");
+
+        let setup = ProjectSetup { programs: vec![program("escrow", "native")], ..Default::default() };
+        assert_eq!(setup.describe(), "crate with the program escrow (native)");
+
+        // Without programs yet
+        assert_eq!(ProjectSetup { anchor_workspace: true, ..Default::default() }.profile(), "anchor");
+        assert_eq!(ProjectSetup::default().profile(), "all");
+        assert_eq!(ProjectSetup::default().describe(), "crate without programs");
+    }
+
+    #[test]
+    fn test_config() {
+        let setup = ProjectSetup { anchor_workspace: true, cargo_workspace: true, programs: vec![program("vault", "anchor")] };
+        let content = setup.config();
+        assert!(content.starts_with("# Configuration of rust-solana-analyzer for this Anchor workspace with the program vault (anchor),\n"));
+
+        // The generated configuration is valid, commented keys aside
+        let config = Config::from_toml(&content, "solana-analyzer.toml", Path::new(".")).unwrap();
+        assert_eq!(config.rules.profile.as_deref(), Some("anchor"));
+        assert_eq!(config.paths.exclude.unwrap(), ["target", "node_modules", ".anchor", "test-ledger"]);
+        assert_eq!(config.thresholds.fail_on.as_deref(), Some("high"));
+        assert!(config.rules.exclude.is_none());
+
+        let config = Config::from_toml(&ProjectSetup::default().config(), "solana-analyzer.toml", Path::new(".")).unwrap();
+        assert_eq!(config.rules.profile.as_deref(), Some("all"));
+        assert_eq!(config.paths.exclude.unwrap(), ["target"]);
+    }
+
+    #[test]
+    fn test_ci_snippets() {
+        assert_eq!("GitHub".parse::<CiProvider>(), Ok(CiProvider::Github));
+        assert!("jenkins".parse::<CiProvider>().is_err());
+
+        let workflow = CiProvider::Github.snippet();
+        assert!(workflow.contains("--format sarif --output results.sarif\n"), "{workflow}");
+        assert!(workflow.contains("sarif_file: results.sarif\n"), "{workflow}");
+
+        let job = CiProvider::Gitlab.snippet();
+        assert!(job.contains("#     - local: .gitlab/solana-analyzer.yml\n"), "{job}");
+        assert!(job.contains("codequality: gl-code-quality.json\n"), "{job}");
+    }
+}
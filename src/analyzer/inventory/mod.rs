@@ -0,0 +1,519 @@
+use serde::Serialize;
+use std::fmt::Write;
+use std::fs;
+
+use crate::analyzer::dsl::ProgramContext;
+use crate::analyzer::dsl::program::{Declaration, DeclarationKind, Member};
+use crate::analyzer::json::Tool;
+
+#[cfg(test)]
+mod test;
+
+/// Version of the inventory schema, incremented on any change that is not an addition
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Size of the discriminator Anchor writes before the data of `#[account]` structs
+pub const ANCHOR_DISCRIMINATOR_SIZE: usize = 8;
+
+/// Maximum number of nested types and aliases followed when computing a size
+const MAX_TYPE_DEPTH: usize = 8;
+
+/// Instructions, accounts structs and state of a program, indexed by its [`ProgramContext`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ProgramInventory {
+    /// Crate of the workspace, `None` when the files are analyzed as one program
+    pub program: Option<String>,
+    /// Instruction handlers, in the order of the files
+    pub instructions: Vec<Instruction>,
+    /// Structs deriving `Accounts`
+    pub accounts: Vec<AccountsStruct>,
+    /// Account data structs (`#[account]`, or deriving `BorshDeserialize` in native programs)
+    pub state: Vec<StateStruct>,
+}
+
+/// Instruction handler
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Instruction {
+    pub name: String,
+    /// Path from the crate root (e.g. `crate::vault::deposit`)
+    pub path: String,
+    pub file: String,
+    pub line: usize,
+    /// Accounts struct of the handler (the `T` of its `Context<T>`)
+    pub accounts: Option<String>,
+    /// Parameters of the handler, its `Context` excepted
+    pub params: Vec<Field>,
+}
+
+/// Field of a struct or parameter of a handler
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Field {
+    pub name: String,
+    /// Type without spaces (e.g. `Account<'info,Vault>`)
+    pub ty: String,
+}
+
+/// Struct deriving `Accounts`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AccountsStruct {
+    pub name: String,
+    /// Path from the crate root (e.g. `crate::instructions::Deposit`)
+    pub path: String,
+    pub file: String,
+    pub line: usize,
+    /// Accounts of the instruction, with their constraints
+    pub fields: Vec<AccountField>,
+    /// Handlers taking the struct as their `Context`
+    pub instructions: Vec<String>,
+}
+
+/// Account of an accounts struct
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AccountField {
+    pub name: String,
+    /// Type without spaces (e.g. `Account<'info,Vault>`)
+    pub ty: String,
+    /// Arguments of its `#[account(...)]` attribute (e.g. `mut`, `has_one=authority`)
+    pub constraints: Vec<String>,
+}
+
+/// Account data struct, with its layout
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StateStruct {
+    pub name: String,
+    /// Path from the crate root (e.g. `crate::state::Vault`)
+    pub path: String,
+    pub file: String,
+    pub line: usize,
+    /// Whether the account is zero-copy (`#[account(zero_copy)]`)
+    pub zero_copy: bool,
+    /// Size of the discriminator before the data (8 for Anchor accounts, 0 otherwise)
+    pub discriminator: usize,
+    /// Fields in the order of the layout
+    pub fields: Vec<StateField>,
+    /// Size of the data in bytes, `None` if a field has a variable or unknown size
+    pub size: Option<usize>,
+    /// Space of the account (discriminator and data), the `space` to allocate
+    pub space: Option<usize>,
+}
+
+/// Field of an account data struct
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StateField {
+    pub name: String,
+    /// Type without spaces (e.g. `Option<Pubkey>`)
+    pub ty: String,
+    /// Offset of the field in the account, discriminator included (`None` after a field of
+    /// variable size)
+    pub offset: Option<usize>,
+    /// Size of the field in bytes (`None` if it is variable or unknown)
+    pub size: Option<usize>,
+}
+
+impl ProgramInventory {
+    /// Builds the inventory of a program from its context, keeping the declarations accepted by
+    /// `include` (e.g. the ones outside of the test code)
+    pub fn of(program: Option<String>, context: &ProgramContext, include: impl Fn(&Declaration) -> bool) -> Self {
+        let instructions: Vec<Instruction> = context
+            .of_kind(DeclarationKind::Instruction)
+            .filter(|declaration| include(declaration))
+            .map(|declaration| Instruction {
+                name: declaration.name.clone(),
+                path: declaration.path.clone(),
+                file: declaration.file.clone(),
+                line: declaration.line,
+                accounts: declaration.ty.clone(),
+                params: declaration
+                    .members
+                    .iter()
+                    .filter(|member| !is_context(member))
+                    .map(|member| Field { name: member.name.clone(), ty: member.ty.clone().unwrap_or_default() })
+                    .collect(),
+            })
+            .collect();
+
+        let accounts = context
+            .of_kind(DeclarationKind::Accounts)
+            .filter(|declaration| include(declaration))
+            .map(|declaration| AccountsStruct {
+                name: declaration.name.clone(),
+                path: declaration.path.clone(),
+                file: declaration.file.clone(),
+                line: declaration.line,
+                fields: declaration
+                    .members
+                    .iter()
+                    .map(|member| AccountField {
+                        name: member.name.clone(),
+                        ty: member.ty.clone().unwrap_or_default(),
+                        constraints: member.attribute("account").map(split_arguments).unwrap_or_default(),
+                    })
+                    .collect(),
+                instructions: instructions
+                    .iter()
+                    .filter(|instruction| instruction.accounts.as_deref() == Some(declaration.name.as_str()))
+                    .map(|instruction| instruction.name.clone())
+                    .collect(),
+            })
+            .collect();
+
+        let state = context
+            .of_kind(DeclarationKind::State)
+            .filter(|declaration| include(declaration))
+            .map(|declaration| state_struct(context, declaration))
+            .collect();
+
+        Self { program, instructions, accounts, state }
+    }
+
+    /// Check if the program has no instruction, accounts struct or state
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty() && self.accounts.is_empty() && self.state.is_empty()
+    }
+}
+
+/// Inventory of the programs of a project (`--inventory`), the scoping document of an audit
+///
+/// ```json
+/// {
+///   "schema_version": 1,
+///   "tool": { "name": "rust-solana-analyzer", "version": "0.1.0" },
+///   "project": "programs/vault",
+///   "programs": [
+///     {
+///       "program": "vault",
+///       "instructions": [{
+///         "name": "deposit", "path": "crate::vault::deposit", "file": "...", "line": 12,
+///         "accounts": "Deposit", "params": [{ "name": "amount", "ty": "u64" }]
+///       }],
+///       "accounts": [{
+///         "name": "Deposit", ..., "instructions": ["deposit"],
+///         "fields": [{ "name": "vault", "ty": "Account<'info,Vault>", "constraints": ["mut", "has_one=authority"] }]
+///       }],
+///       "state": [{
+///         "name": "Vault", ..., "zero_copy": false, "discriminator": 8, "size": 40, "space": 48,
+///         "fields": [{ "name": "authority", "ty": "Pubkey", "offset": 8, "size": 32 }]
+///       }]
+///     }
+///   ]
+/// }
+/// ```
+///
+/// Sizes are the Borsh sizes of the fields: `String` and `Vec` fields only have one with a
+/// `#[max_len(...)]` attribute (`InitSpace`), array lengths can be constants of the program,
+/// and nested structs declared in the program are followed.
+#[derive(Debug, Serialize)]
+pub struct InventoryReport<'a> {
+    /// Version of the schema ([`SCHEMA_VERSION`])
+    pub schema_version: u32,
+    /// Analyzer that produced the report
+    pub tool: Tool,
+    /// Analyzed path
+    pub project: &'a str,
+    /// Inventory of each program
+    pub programs: &'a [ProgramInventory],
+}
+
+impl<'a> InventoryReport<'a> {
+    /// Creates the inventory of the programs of `project`
+    pub fn new(programs: &'a [ProgramInventory], project: &'a str) -> Self {
+        Self { schema_version: SCHEMA_VERSION, tool: Tool::default(), project, programs }
+    }
+
+    /// Returns the inventory as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("inventory serializes to JSON");
+        json.push('\n');
+        json
+    }
+
+    /// Returns the inventory as a Markdown document
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from("# Program Inventory\n\n");
+        let _ = writeln!(markdown, "Instructions, accounts and state of {}.\n", self.project);
+        let programs: Vec<&ProgramInventory> = self.programs.iter().filter(|program| !program.is_empty()).collect();
+        if programs.is_empty() {
+            markdown.push_str("No instruction, accounts struct or account state found.\n");
+        }
+        for program in programs {
+            let _ = writeln!(markdown, "## {}\n", program.program.as_deref().unwrap_or("Program"));
+            self.instructions_markdown(&mut markdown, &program.instructions);
+            self.accounts_markdown(&mut markdown, &program.accounts);
+            self.state_markdown(&mut markdown, &program.state);
+        }
+        markdown
+    }
+
+    /// Save the inventory to a file, as Markdown if its extension is `md` and as JSON otherwise
+    pub fn save(&self, output_path: &str) -> Result<(), std::io::Error> {
+        let markdown = std::path::Path::new(output_path).extension().is_some_and(|extension| extension == "md");
+        fs::write(output_path, if markdown { self.to_markdown() } else { self.to_json() })
+    }
+
+    /// Helper function to render the table of the instructions
+    fn instructions_markdown(&self, markdown: &mut String, instructions: &[Instruction]) {
+        if instructions.is_empty() {
+            return;
+        }
+        markdown.push_str("### Instructions\n\n");
+        markdown.push_str("| Instruction | Accounts | Parameters | Location |\n| --- | --- | --- | --- |\n");
+        for instruction in instructions {
+            let params: Vec<String> =
+                instruction.params.iter().map(|param| format!("`{}: {}`", param.name, pretty(&param.ty))).collect();
+            let _ = writeln!(
+                markdown,
+                "| `{}` | {} | {} | {} |",
+                instruction.name,
+                instruction.accounts.as_ref().map_or("-".to_string(), |accounts| format!("`{accounts}`")),
+                if params.is_empty() { "-".to_string() } else { params.join(", ") },
+                self.location(&instruction.file, instruction.line)
+            );
+        }
+        markdown.push('\n');
+    }
+
+    /// Helper function to render the accounts structs, with a table of their accounts
+    fn accounts_markdown(&self, markdown: &mut String, accounts: &[AccountsStruct]) {
+        if accounts.is_empty() {
+            return;
+        }
+        markdown.push_str("### Accounts Structs\n\n");
+        for accounts_struct in accounts {
+            let _ = writeln!(markdown, "#### `{}`\n", accounts_struct.name);
+            let used_by: Vec<String> =
+                accounts_struct.instructions.iter().map(|instruction| format!("`{instruction}`")).collect();
+            let _ = writeln!(
+                markdown,
+                "{}, used by {}.\n",
+                self.location(&accounts_struct.file, accounts_struct.line),
+                if used_by.is_empty() { "no instruction".to_string() } else { used_by.join(", ") }
+            );
+            markdown.push_str("| Account | Type | Constraints |\n| --- | --- | --- |\n");
+            for field in &accounts_struct.fields {
+                let constraints: Vec<String> = field
+                    .constraints
+                    .iter()
+                    .map(|constraint| format!("`{}`", constraint.replace('|', "\\|")))
+                    .collect();
+                let _ = writeln!(
+                    markdown,
+                    "| `{}` | `{}` | {} |",
+                    field.name,
+                    pretty(&field.ty),
+                    if constraints.is_empty() { "-".to_string() } else { constraints.join(", ") }
+                );
+            }
+            markdown.push('\n');
+        }
+    }
+
+    /// Helper function to render the account data structs, with their layout
+    fn state_markdown(&self, markdown: &mut String, state: &[StateStruct]) {
+        if state.is_empty() {
+            return;
+        }
+        markdown.push_str("### State\n\n");
+        for state_struct in state {
+            let size = match (state_struct.size, state_struct.space) {
+                (Some(size), Some(space)) if state_struct.discriminator > 0 => {
+                    format!("{} + {size} = {space} bytes", state_struct.discriminator)
+                }
+                (Some(size), _) => format!("{size} bytes"),
+                _ => "variable size".to_string(),
+            };
+            let _ = writeln!(markdown, "#### `{}` ({size})\n", state_struct.name);
+            let _ = writeln!(
+                markdown,
+                "{}{}.\n",
+                self.location(&state_struct.file, state_struct.line),
+                if state_struct.zero_copy { ", zero-copy" } else { "" }
+            );
+            markdown.push_str("| Field | Type | Offset | Size |\n| --- | --- | --- | --- |\n");
+            for field in &state_struct.fields {
+                let _ = writeln!(
+                    markdown,
+                    "| `{}` | `{}` | {} | {} |",
+                    field.name,
+                    pretty(&field.ty),
+                    field.offset.map_or("-".to_string(), |offset| offset.to_string()),
+                    field.size.map_or("variable".to_string(), |size| size.to_string())
+                );
+            }
+            markdown.push('\n');
+        }
+    }
+
+    /// Helper function to format the location of a declaration, relative to the project
+    fn location(&self, file: &str, line: usize) -> String {
+        format!("{}:{line}", file.strip_prefix(self.project).unwrap_or(file).trim_start_matches('/'))
+    }
+}
+
+/// Helper function to build the layout of an account data struct
+fn state_struct(context: &ProgramContext, declaration: &Declaration) -> StateStruct {
+    let account = declaration.attribute("account");
+    let discriminator = if account.is_some() { ANCHOR_DISCRIMINATOR_SIZE } else { 0 };
+    let mut offset = Some(discriminator);
+    let fields: Vec<StateField> = declaration
+        .members
+        .iter()
+        .map(|member| {
+            let size = member_size(context, &declaration.file, member, 0);
+            let field = StateField {
+                name: member.name.clone(),
+                ty: member.ty.clone().unwrap_or_default(),
+                offset,
+                size,
+            };
+            offset = offset.zip(size).map(|(offset, size)| offset + size);
+            field
+        })
+        .collect();
+    let size = fields.iter().map(|field| field.size).sum();
+    StateStruct {
+        name: declaration.name.clone(),
+        path: declaration.path.clone(),
+        file: declaration.file.clone(),
+        line: declaration.line,
+        zero_copy: account.is_some_and(|arguments| arguments.contains("zero_copy")),
+        discriminator,
+        fields,
+        size,
+        space: size.map(|size| discriminator + size),
+    }
+}
+
+/// Helper function to compute the size of a field, with the lengths of its `#[max_len(...)]`
+/// attribute
+fn member_size(context: &ProgramContext, file: &str, member: &Member, depth: usize) -> Option<usize> {
+    let ty = syn::parse_str::<syn::Type>(member.ty.as_deref()?).ok()?;
+    let max_len: Vec<Option<usize>> = member
+        .attribute("max_len")
+        .map(|arguments| split_arguments(arguments).iter().map(|length| constant(context, file, length)).collect())
+        .unwrap_or_default();
+    type_size(context, file, &ty, &max_len, depth)
+}
+
+/// Helper function to compute the Borsh size of a type, `max_len` being the maximum lengths of
+/// its strings and vectors from the outermost one
+fn type_size(
+    context: &ProgramContext,
+    file: &str,
+    ty: &syn::Type,
+    max_len: &[Option<usize>],
+    depth: usize,
+) -> Option<usize> {
+    if depth > MAX_TYPE_DEPTH {
+        return None;
+    }
+    match ty {
+        syn::Type::Array(array) => {
+            let length = match &array.len {
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(length), .. }) => length.base10_parse().ok()?,
+                syn::Expr::Path(path) => {
+                    let path: Vec<String> =
+                        path.path.segments.iter().map(|segment| segment.ident.to_string()).collect();
+                    constant(context, file, &path.join("::"))?
+                }
+                _ => return None,
+            };
+            Some(type_size(context, file, &array.elem, max_len, depth + 1)? * length)
+        }
+        syn::Type::Tuple(tuple) => {
+            tuple.elems.iter().map(|elem| type_size(context, file, elem, max_len, depth + 1)).sum()
+        }
+        syn::Type::Paren(paren) => type_size(context, file, &paren.elem, max_len, depth),
+        syn::Type::Path(type_path) => {
+            let segment = type_path.path.segments.last()?;
+            let generic = || match &segment.arguments {
+                syn::PathArguments::AngleBracketed(arguments) => {
+                    arguments.args.iter().find_map(|argument| match argument {
+                        syn::GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    })
+                }
+                _ => None,
+            };
+            match segment.ident.to_string().as_str() {
+                "u8" | "i8" | "bool" => Some(1),
+                "u16" | "i16" => Some(2),
+                "u32" | "i32" | "f32" => Some(4),
+                "u64" | "i64" | "f64" | "usize" | "isize" => Some(8),
+                "u128" | "i128" => Some(16),
+                "Pubkey" => Some(32),
+                "Option" => Some(1 + type_size(context, file, generic()?, max_len, depth + 1)?),
+                "Box" => type_size(context, file, generic()?, max_len, depth + 1),
+                "String" => Some(4 + (*max_len.first()?)?),
+                "Vec" => {
+                    let length = (*max_len.first()?)?;
+                    let inner = max_len.get(1..).unwrap_or_default();
+                    Some(4 + length * type_size(context, file, generic()?, inner, depth + 1)?)
+                }
+                _ => {
+                    let path: Vec<String> =
+                        type_path.path.segments.iter().map(|segment| segment.ident.to_string()).collect();
+                    let declaration = context.resolve(file, &path.join("::"))?;
+                    match declaration.kind {
+                        DeclarationKind::State | DeclarationKind::Struct => declaration
+                            .members
+                            .iter()
+                            .map(|member| member_size(context, &declaration.file, member, depth + 1))
+                            .sum(),
+                        DeclarationKind::TypeAlias => {
+                            let aliased = syn::parse_str::<syn::Type>(declaration.ty.as_deref()?).ok()?;
+                            type_size(context, &declaration.file, &aliased, max_len, depth + 1)
+                        }
+                        _ => None,
+                    }
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Helper function to get the value of an integer literal or of a constant of the program
+fn constant(context: &ProgramContext, file: &str, value: &str) -> Option<usize> {
+    if let Ok(literal) = syn::parse_str::<syn::LitInt>(value) {
+        return literal.base10_parse().ok();
+    }
+    let declaration = context.resolve(file, value).filter(|declaration| declaration.kind == DeclarationKind::Constant)?;
+    syn::parse_str::<syn::LitInt>(declaration.value.as_deref()?).ok()?.base10_parse().ok()
+}
+
+/// Helper function to check if a handler parameter is its `Context`
+fn is_context(member: &Member) -> bool {
+    member.ty.as_deref().is_some_and(|ty| {
+        let ty = ty.split('<').next().unwrap_or(ty);
+        ty == "Context" || ty.ends_with("::Context")
+    })
+}
+
+/// Helper function to split the arguments of an attribute on the commas outside of brackets
+/// (`mut,seeds=[b"vault",user.key().as_ref()],bump`)
+pub(crate) fn split_arguments(arguments: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut current = String::new();
+    for c in arguments.chars() {
+        match c {
+            '"' => in_string = !in_string,
+            '(' | '[' | '{' if !in_string => depth += 1,
+            ')' | ']' | '}' if !in_string => depth = depth.saturating_sub(1),
+            ',' if !in_string && depth == 0 => {
+                parts.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    parts.push(current);
+    parts.into_iter().filter(|part| !part.is_empty()).collect()
+}
+
+/// Helper function to add spaces after the commas of a type without spaces
+fn pretty(ty: &str) -> String {
+    ty.replace(',', ", ")
+}
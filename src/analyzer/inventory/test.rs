@@ -0,0 +1,144 @@
+use crate::analyzer::dsl::ProgramContext;
+use crate::analyzer::inventory::{InventoryReport, ProgramInventory};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB: &str = r#"
+use anchor_lang::prelude::*;
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut, has_one = authority, seeds = [b"vault", authority.key().as_ref()], bump)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+"#;
+
+    const STATE: &str = r#"
+use anchor_lang::prelude::*;
+
+pub const MAX_NAME: usize = 32;
+pub type Slots = [Slot; 2];
+
+#[account]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub bump: u8,
+    pub slots: Slots,
+    #[max_len(MAX_NAME)]
+    pub name: String,
+    #[max_len(4, 8)]
+    pub tags: Vec<String>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Slot {
+    pub owner: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[account]
+pub struct Log {
+    pub count: u16,
+    pub entries: Vec<u64>,
+    pub last: i64,
+}
+
+#[account(zero_copy)]
+pub struct Book {
+    pub orders: [u64; 8],
+}
+"#;
+
+    fn inventory() -> ProgramInventory {
+        let lib = syn::parse_file(LIB).unwrap();
+        let state = syn::parse_file(STATE).unwrap();
+        let context = ProgramContext::build([("src/lib.rs", &lib), ("src/state.rs", &state)]);
+        ProgramInventory::of(Some("vault".to_string()), &context, |_| true)
+    }
+
+    #[test]
+    fn test_instructions_and_accounts() {
+        let inventory = inventory();
+
+        assert_eq!(inventory.instructions.len(), 1);
+        let deposit = &inventory.instructions[0];
+        assert_eq!(deposit.path, "crate::vault::deposit");
+        assert_eq!(deposit.accounts.as_deref(), Some("Deposit"));
+        assert_eq!(deposit.params.len(), 1);
+        assert_eq!((deposit.params[0].name.as_str(), deposit.params[0].ty.as_str()), ("amount", "u64"));
+
+        assert_eq!(inventory.accounts.len(), 1);
+        let accounts = &inventory.accounts[0];
+        assert_eq!(accounts.instructions, vec!["deposit"]);
+        assert_eq!(accounts.fields[0].ty, "Account<'info,Vault>");
+        assert_eq!(
+            accounts.fields[0].constraints,
+            vec!["mut", "has_one=authority", "seeds=[b\"vault\",authority.key().as_ref()]", "bump"]
+        );
+        assert!(accounts.fields[1].constraints.is_empty());
+    }
+
+    #[test]
+    fn test_state_layout() {
+        let inventory = inventory();
+        let names: Vec<&str> = inventory.state.iter().map(|state| state.name.as_str()).collect();
+        assert_eq!(names, vec!["Vault", "Log", "Book"]);
+
+        // Nested structs through an alias, lengths from constants and `max_len`
+        let vault = &inventory.state[0];
+        let layout: Vec<(Option<usize>, Option<usize>)> = vault.fields.iter().map(|field| (field.offset, field.size)).collect();
+        assert_eq!(layout, vec![(Some(8), Some(32)), (Some(40), Some(1)), (Some(41), Some(82)), (Some(123), Some(36)), (Some(159), Some(52))]);
+        assert_eq!(vault.discriminator, 8);
+        assert_eq!(vault.size, Some(203));
+        assert_eq!(vault.space, Some(211));
+
+        // Vectors without a maximum length make the size variable, and the offsets after them unknown
+        let log = &inventory.state[1];
+        assert_eq!(log.fields[1].size, None);
+        assert_eq!(log.fields[2].offset, None);
+        assert_eq!(log.fields[2].size, Some(8));
+        assert_eq!(log.size, None);
+        assert_eq!(log.space, None);
+
+        let book = &inventory.state[2];
+        assert!(book.zero_copy);
+        assert_eq!(book.space, Some(72));
+    }
+
+    #[test]
+    fn test_inventory_report() {
+        let programs = vec![inventory()];
+        let report = InventoryReport::new(&programs, "/project");
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["programs"][0]["program"], "vault");
+        assert_eq!(json["programs"][0]["state"][0]["space"], 211);
+        assert_eq!(json["programs"][0]["state"][1]["size"], serde_json::Value::Null);
+
+        let markdown = report.to_markdown();
+        assert!(markdown.starts_with("# Program Inventory\n\nInstructions, accounts and state of /project.\n\n## vault\n\n### Instructions\n"));
+        assert!(markdown.contains("| `deposit` | `Deposit` | `amount: u64` | src/lib.rs:8 |\n"));
+        assert!(markdown.contains("#### `Deposit`\n\nsrc/lib.rs:14, used by `deposit`.\n"));
+        assert!(markdown.contains("| `vault` | `Account<'info, Vault>` | `mut`, `has_one=authority`, `seeds=[b\"vault\",authority.key().as_ref()]`, `bump` |\n"));
+        assert!(markdown.contains("#### `Vault` (8 + 203 = 211 bytes)\n"));
+        assert!(markdown.contains("| `entries` | `Vec<u64>` | 10 | variable |\n| `last` | `i64` | - | 8 |\n"));
+        assert!(markdown.contains("#### `Log` (variable size)\n"));
+        assert!(markdown.contains("src/state.rs:32, zero-copy.\n"));
+
+        let empty = InventoryReport::new(&[], "/project").to_markdown();
+        assert!(empty.ends_with("No instruction, accounts struct or account state found.\n"));
+    }
+}
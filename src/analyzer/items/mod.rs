@@ -0,0 +1,163 @@
+use quote::ToTokens;
+use syn::spanned::Spanned;
+use syn::{File, ImplItem, Item, TraitItem};
+
+use crate::analyzer::Finding;
+use crate::analyzer::dsl::query::MetadataValue;
+use crate::ast::modules::FileModule;
+
+#[cfg(test)]
+mod test;
+
+/// Metadata key of the innermost function, method or type containing a finding (e.g. `handler`,
+/// or `Vault::withdraw` for a method)
+pub const ENCLOSING_ITEM_KEY: &str = "enclosing_item";
+
+/// Metadata key of the path of the module containing a finding, from the crate root and without
+/// `crate` (e.g. `instructions::withdraw`, not set at the crate root)
+pub const MODULE_PATH_KEY: &str = "module_path";
+
+/// Item and module containing a line of a file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Enclosing {
+    /// Modules from the crate root: the module of the file, then the inline modules
+    pub module_path: Vec<String>,
+    /// Innermost item, qualified by its type for the items of `impl` and `trait` blocks
+    pub item: Option<String>,
+}
+
+impl Enclosing {
+    /// Find the item and the module containing a line, in a file whose module is `file_module`
+    /// (see [`crate::ast::modules::FileModule`], without `crate`)
+    pub fn of(ast: &File, line: usize, file_module: &[String]) -> Self {
+        let mut enclosing = Self { module_path: file_module.to_vec(), item: None };
+        enclosing.visit_items(&ast.items.iter().collect::<Vec<_>>(), line);
+        enclosing
+    }
+
+    /// Helper function to descend into the items containing a line
+    fn visit_items(&mut self, items: &[&Item], line: usize) {
+        let Some(item) = items.iter().find(|item| contains_line(**item, line)) else {
+            return;
+        };
+
+        match item {
+            Item::Fn(item_fn) => {
+                self.item = Some(item_fn.sig.ident.to_string());
+                // Items declared in the body of the function (helpers, nested modules)
+                let nested: Vec<&Item> = item_fn
+                    .block
+                    .stmts
+                    .iter()
+                    .filter_map(|stmt| match stmt {
+                        syn::Stmt::Item(item) => Some(item),
+                        _ => None,
+                    })
+                    .collect();
+                self.visit_items(&nested, line);
+            }
+            Item::Struct(item_struct) => self.item = Some(item_struct.ident.to_string()),
+            Item::Enum(item_enum) => self.item = Some(item_enum.ident.to_string()),
+            Item::Union(item_union) => self.item = Some(item_union.ident.to_string()),
+            Item::Const(item_const) => self.item = Some(item_const.ident.to_string()),
+            Item::Static(item_static) => self.item = Some(item_static.ident.to_string()),
+            Item::Type(item_type) => self.item = Some(item_type.ident.to_string()),
+            Item::Mod(item_mod) => {
+                self.module_path.push(item_mod.ident.to_string());
+                if let Some((_, items)) = &item_mod.content {
+                    self.visit_items(&items.iter().collect::<Vec<_>>(), line);
+                }
+            }
+            Item::Trait(item_trait) => {
+                let name = item_trait.ident.to_string();
+                let method = item_trait.items.iter().find_map(|item| match item {
+                    TraitItem::Fn(method) if contains_line(method, line) => Some(&method.sig.ident),
+                    _ => None,
+                });
+                self.item = Some(match method {
+                    Some(method) => format!("{name}::{method}"),
+                    None => name,
+                });
+            }
+            Item::Impl(item_impl) => {
+                let self_ty = type_name(&item_impl.self_ty);
+                let method = item_impl.items.iter().find_map(|item| match item {
+                    ImplItem::Fn(method) if contains_line(method, line) => Some(&method.sig.ident),
+                    _ => None,
+                });
+                self.item = Some(match method {
+                    Some(method) => format!("{self_ty}::{method}"),
+                    None => self_ty,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the full path of the item (e.g. `instructions::withdraw::handler`), or of the
+    /// module outside any item (`None` at the crate root)
+    pub fn qualified_name(&self) -> Option<String> {
+        let path: Vec<&str> = self.module_path.iter().map(String::as_str).chain(self.item.as_deref()).collect();
+        (!path.is_empty()).then(|| path.join("::"))
+    }
+}
+
+/// Add the enclosing item and the module path to the metadata of the findings of a file
+pub fn assign_enclosing_items(findings: &mut [Finding], file_path: &str, ast: &File, file_module: &[String]) {
+    assign_enclosing(findings, file_path, |line| Enclosing::of(ast, line, file_module));
+}
+
+/// Add the enclosing item and the module path to the metadata of the findings of a file like
+/// [`assign_enclosing_items`], with the item and module of each line given by `enclosing_of`
+pub fn assign_enclosing<F>(findings: &mut [Finding], file_path: &str, enclosing_of: F)
+where
+    F: Fn(usize) -> Enclosing,
+{
+    for finding in findings.iter_mut().filter(|finding| finding.location.file == file_path) {
+        let enclosing = enclosing_of(finding.location.line);
+        if let Some(item) = enclosing.item {
+            finding.metadata.insert(ENCLOSING_ITEM_KEY.to_string(), MetadataValue::Text(item));
+        }
+        if !enclosing.module_path.is_empty() {
+            finding
+                .metadata
+                .insert(MODULE_PATH_KEY.to_string(), MetadataValue::Text(enclosing.module_path.join("::")));
+        }
+    }
+}
+
+/// Returns the module path of a file without `crate` (e.g. `["instructions", "withdraw"]`)
+pub fn module_path(module: &FileModule) -> Vec<String> {
+    module.path.iter().skip(1).cloned().collect()
+}
+
+/// Returns the full path of the item containing a finding, from its metadata (e.g.
+/// `instructions::withdraw::handler`)
+pub fn qualified_name(finding: &Finding) -> Option<String> {
+    let text = |key: &str| match finding.metadata.get(key) {
+        Some(MetadataValue::Text(text)) => Some(text.as_str()),
+        _ => None,
+    };
+    match (text(MODULE_PATH_KEY), text(ENCLOSING_ITEM_KEY)) {
+        (Some(module), Some(item)) => Some(format!("{module}::{item}")),
+        (module, item) => module.or(item).map(str::to_string),
+    }
+}
+
+/// Helper function to name the type of an `impl` block without its generics (e.g. `Vault`)
+fn type_name(ty: &syn::Type) -> String {
+    match ty {
+        syn::Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map_or_else(|| type_path.to_token_stream().to_string(), |segment| segment.ident.to_string()),
+        _ => ty.to_token_stream().to_string().split_whitespace().collect(),
+    }
+}
+
+/// Helper function to check if the span of a node contains a line
+fn contains_line<T: Spanned>(node: &T, line: usize) -> bool {
+    let span = node.span();
+    span.start().line <= line && line <= span.end().line
+}
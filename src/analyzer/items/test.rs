@@ -0,0 +1,58 @@
+use crate::analyzer::items::{Enclosing, assign_enclosing_items, qualified_name};
+use crate::analyzer::Finding;
+use crate::analyzer::testing::FindingBuilder;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+pub mod vault {
+    pub fn withdraw(amount: u64, shares: u64) -> u64 {
+        amount / shares
+    }
+}
+
+impl<'info> Withdraw<'info> {
+    fn rate(&self) -> u64 {
+        self.amount / self.shares
+    }
+}
+
+pub struct Vault {
+    pub total: u64,
+}
+
+use std::fmt;
+"#;
+
+    fn finding(file: &str, line: usize) -> Finding {
+        FindingBuilder::new("Division Without Zero Check").span(file, line, 8, 23).build()
+    }
+
+    #[test]
+    fn test_enclosing_items() {
+        let ast = syn::parse_file(SOURCE).unwrap();
+        let module = ["instructions".to_string()];
+        let enclosing = |line| Enclosing::of(&ast, line, &module).qualified_name();
+
+        assert_eq!(enclosing(4).as_deref(), Some("instructions::vault::withdraw"));
+        assert_eq!(enclosing(10).as_deref(), Some("instructions::Withdraw::rate"));
+        assert_eq!(enclosing(15).as_deref(), Some("instructions::Vault"));
+        // Outside any item, the module only
+        assert_eq!(enclosing(18).as_deref(), Some("instructions"));
+        assert_eq!(Enclosing::of(&ast, 18, &[]).qualified_name(), None);
+    }
+
+    #[test]
+    fn test_assign_enclosing_items() {
+        let ast = syn::parse_file(SOURCE).unwrap();
+        let mut findings = vec![finding("src/processor.rs", 4), finding("src/processor.rs", 18), finding("src/lib.rs", 4)];
+        assign_enclosing_items(&mut findings, "src/processor.rs", &ast, &[]);
+
+        assert_eq!(qualified_name(&findings[0]).as_deref(), Some("vault::withdraw"));
+        assert_eq!(qualified_name(&findings[1]), None);
+        // Findings of other files are left alone
+        assert!(findings[2].metadata.is_empty());
+    }
+}
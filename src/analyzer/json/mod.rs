@@ -0,0 +1,142 @@
+use serde::Serialize;
+use std::fs;
+
+use crate::analyzer::compare::{Comparison, ComparisonSummary};
+use crate::analyzer::results::SeverityCounts;
+use crate::analyzer::suppressions::SuppressedFinding;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Finding};
+
+#[cfg(test)]
+mod test;
+
+/// Version of the JSON report schema, incremented on any change that is not an addition
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Name and version of the analyzer that produced a report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Tool {
+    /// Package name (`rust-solana-analyzer`)
+    pub name: &'static str,
+    /// Package version
+    pub version: &'static str,
+}
+
+impl Default for Tool {
+    fn default() -> Self {
+        Self {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+        }
+    }
+}
+
+/// Machine-readable report of an analysis (`--format json`)
+///
+/// ```json
+/// {
+///   "schema_version": 1,
+///   "tool": { "name": "rust-solana-analyzer", "version": "0.1.0" },
+///   "project": "programs/vault",
+///   "summary": { "high": 1, "medium": 0, "low": 0, "informational": 0 },
+///   "findings": [
+///     {
+///       "description": "...",
+///       "severity": "high",
+///       "confidence": "high",
+///       "location": { "file": "...", "line": 12, "column": 5, "end_line": 12, "end_column": 30 },
+///       "code_snippet": "...",
+///       "recommendations": ["..."],
+///       "fixes": [{ "description": "...", "edits": [{ "location": { ... }, "replacement": "..." }], "applicability": "advisory" }],
+///       "metadata": { "program": { "Text": "vault" } },
+///       "rules": [{ "id": "...", "title": "...", "references": [], "tags": [], "cwe": [862], "category": "missing-signer" }],
+///       "fingerprint": "..."
+///     }
+///   ],
+///   "suppressed": [
+///     { "rule_id": "...", "suppressed_by": "comment", "reason": "checked by the caller", "finding": { ... } }
+///   ],
+///   "stats": { "files_analyzed": 3, "findings_by_severity": { "high": 1 }, ... },
+///   "comparison": { "summary": { "new": 1, "fixed": 0, "persisting": 2 }, "new": ["..."], "fixed": [] }
+/// }
+/// ```
+///
+/// Findings are in the order of the analysis (by file, line, column and rule) with the fields
+/// of [`Finding`]. Their locations are the ones of [`Location`](crate::analyzer::Location):
+/// lines start at 1 and columns at 0 (characters from the start of the line, the end column
+/// excluded). The statistics have the fields of [`AnalysisStats`]. `suppressed` lists the
+/// findings removed by suppression comments (`comment`) and by the ignored severities
+/// (`severity`) or the minimum confidence (`confidence`), with their fingerprints.
+/// `comparison` is only present with `--compare` (see [`ComparisonReport`]). Fields are only
+/// added within a schema version; `schema_version` changes when one is renamed or removed.
+#[derive(Debug, Serialize)]
+pub struct JsonReport<'a> {
+    /// Version of the schema ([`SCHEMA_VERSION`])
+    pub schema_version: u32,
+    /// Analyzer that produced the report
+    pub tool: Tool,
+    /// Analyzed path
+    pub project: &'a str,
+    /// Number of findings of each severity
+    pub summary: SeverityCounts,
+    /// Findings with their rules, metadata and fixes
+    pub findings: &'a [Finding],
+    /// Findings removed by suppression comments and by the severity and confidence filters
+    pub suppressed: &'a [SuppressedFinding],
+    /// Statistics of the analysis
+    pub stats: &'a AnalysisStats,
+    /// Comparison with a previous run (`--compare`), omitted otherwise
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<ComparisonReport<'a>>,
+}
+
+/// Comparison with a previous run in a JSON report
+///
+/// The new findings are in `findings`, so only their fingerprints are listed; the fixed ones
+/// are only in the previous report, so they are included.
+#[derive(Debug, Serialize)]
+pub struct ComparisonReport<'a> {
+    /// Number of new, fixed and persisting findings
+    pub summary: ComparisonSummary,
+    /// Fingerprints of the new findings
+    pub new: Vec<&'a str>,
+    /// Findings of the previous run that are no longer reported
+    pub fixed: &'a [Finding],
+}
+
+impl<'a> JsonReport<'a> {
+    /// Creates the report of an analysis of `project`
+    pub fn new(result: &'a AnalysisResult, project: &'a str) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            tool: Tool::default(),
+            project,
+            summary: result.severity_counts(),
+            findings: &result.findings,
+            suppressed: &result.stats.suppressed_findings,
+            stats: &result.stats,
+            comparison: None,
+        }
+    }
+
+    /// Include the comparison with a previous run
+    pub fn with_comparison(mut self, comparison: &'a Comparison) -> Self {
+        self.comparison = Some(ComparisonReport {
+            summary: comparison.summary(),
+            new: comparison.new.iter().filter_map(|finding| finding.fingerprint.as_deref()).collect(),
+            fixed: &comparison.fixed,
+        });
+        self
+    }
+
+    /// Returns the report as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("findings and statistics serialize to JSON");
+        json.push('\n');
+        json
+    }
+
+    /// Save the JSON report to a file
+    pub fn save(&self, output_path: &str) -> Result<(), std::io::Error> {
+        fs::write(output_path, self.to_json())
+    }
+}
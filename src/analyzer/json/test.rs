@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::analyzer::compare::Comparison;
+use crate::analyzer::engine::RuleProfile;
+use crate::analyzer::json::{JsonReport, SCHEMA_VERSION};
+use crate::analyzer::suppressions::{SuppressedBy, SuppressedFinding};
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Finding, RuleInfo, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(rule_id: &str, severity: Severity, line: usize) -> Finding {
+        FindingBuilder::new(format!("{rule_id} at line {line}"))
+            .severity(severity)
+            .span("src/lib.rs", line, 5, 20)
+            .snippet("amount / shares")
+            .recommendations(&["Check the divisor"])
+            .rule(RuleInfo {
+                id: rule_id.to_string(),
+                title: format!("Title of {rule_id}"),
+                cwe: vec![369],
+                ..Default::default()
+            })
+            .fingerprint(format!("{rule_id}-{line}"))
+            .build()
+    }
+
+    fn result() -> AnalysisResult {
+        let mut stats = AnalysisStats {
+            files_analyzed: 2,
+            findings_by_severity: HashMap::from([(Severity::Low, 1), (Severity::High, 1)]),
+            ..Default::default()
+        };
+        stats.rule_profiles.insert(
+            "division-by-zero".to_string(),
+            RuleProfile { runs: 2, time: Duration::from_micros(1500), files_matched: 1, findings: 1 },
+        );
+        AnalysisResult {
+            findings: vec![finding("division-by-zero", Severity::High, 3), finding("error-handling", Severity::Low, 8)],
+            stats,
+        }
+    }
+
+    #[test]
+    fn test_report_schema() {
+        let result = result();
+        let json: serde_json::Value = serde_json::from_str(&JsonReport::new(&result, "programs/vault").to_json()).unwrap();
+
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert_eq!(json["tool"]["name"], "rust-solana-analyzer");
+        assert_eq!(json["project"], "programs/vault");
+        assert_eq!(json["summary"], serde_json::json!({ "high": 1, "medium": 0, "low": 1, "informational": 0 }));
+
+        let finding = &json["findings"][0];
+        assert_eq!(finding["severity"], "high");
+        assert_eq!(finding["confidence"], "high");
+        assert_eq!(finding["location"]["line"], 3);
+        assert_eq!(finding["location"]["end_column"], 20);
+        assert_eq!(finding["rules"][0]["id"], "division-by-zero");
+        assert_eq!(finding["rules"][0]["cwe"][0], 369);
+        assert_eq!(finding["fingerprint"], "division-by-zero-3");
+
+        assert_eq!(json["stats"]["files_analyzed"], 2);
+        assert_eq!(json["stats"]["rule_profiles"]["division-by-zero"]["time_ms"], 1.5);
+        assert!(json.get("comparison").is_none());
+        assert_eq!(json["suppressed"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_suppressed() {
+        let mut result = result();
+        result.stats.suppressed_findings = vec![
+            SuppressedFinding {
+                rule_id: "unsafe-code".to_string(),
+                suppressed_by: SuppressedBy::Comment,
+                reason: Some("audited".to_string()),
+                finding: finding("unsafe-code", Severity::Medium, 12),
+            },
+            SuppressedFinding {
+                rule_id: "error-handling".to_string(),
+                suppressed_by: SuppressedBy::Severity,
+                reason: Some("Severity informational is ignored".to_string()),
+                finding: finding("error-handling", Severity::Informational, 20),
+            },
+        ];
+        result.stats.findings_suppressed = 2;
+        let json: serde_json::Value = serde_json::from_str(&JsonReport::new(&result, "programs/vault").to_json()).unwrap();
+
+        let suppressed = json["suppressed"].as_array().unwrap();
+        assert_eq!(suppressed.len(), 2);
+        assert_eq!(suppressed[0]["rule_id"], "unsafe-code");
+        assert_eq!(suppressed[0]["suppressed_by"], "comment");
+        assert_eq!(suppressed[0]["reason"], "audited");
+        assert_eq!(suppressed[0]["finding"]["fingerprint"], "unsafe-code-12");
+        assert_eq!(suppressed[1]["suppressed_by"], "severity");
+        assert_eq!(suppressed[1]["finding"]["severity"], "informational");
+        assert_eq!(json["stats"]["findings_suppressed"], 2);
+        assert!(json["stats"].get("suppressed_findings").is_none());
+    }
+
+    #[test]
+    fn test_comparison() {
+        let result = result();
+        let previous = vec![finding("error-handling", Severity::Low, 8), finding("missing-signer", Severity::High, 1)];
+        let comparison = Comparison::of(&previous, &result.findings);
+        let report = JsonReport::new(&result, "programs/vault").with_comparison(&comparison).to_json();
+        let json: serde_json::Value = serde_json::from_str(&report).unwrap();
+
+        assert_eq!(json["comparison"]["summary"], serde_json::json!({ "new": 1, "fixed": 1, "persisting": 1 }));
+        assert_eq!(json["comparison"]["new"], serde_json::json!(["division-by-zero-3"]));
+        assert_eq!(json["comparison"]["fixed"][0]["fingerprint"], "missing-signer-1");
+        assert_eq!(json["comparison"]["fixed"][0]["rules"][0]["id"], "missing-signer");
+    }
+
+    #[test]
+    fn test_report_is_stable() {
+        let result = result();
+        let json = JsonReport::new(&result, "programs/vault").to_json();
+
+        // Severities are listed the most severe first, whatever the order of the map
+        let by_severity = &json[json.find("\"findings_by_severity\"").unwrap()..];
+        assert!(by_severity.find("\"high\"").unwrap() < by_severity.find("\"low\"").unwrap());
+        assert_eq!(json, JsonReport::new(&result, "programs/vault").to_json());
+        assert!(json.ends_with("}\n"));
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt::Write;
+use std::fs;
+
+use crate::analyzer::csv::relative_path;
+use crate::analyzer::html::escape;
+use crate::analyzer::results;
+use crate::analyzer::{AnalysisResult, Finding};
+
+#[cfg(test)]
+mod test;
+
+/// JUnit XML report of an analysis (`--format junit`), for CI systems that display test reports
+///
+/// Each rule with findings is a test suite and each finding a failed test case named after its
+/// location, with the severity as the failure type:
+///
+/// ```xml
+/// <testsuites name="rust-solana-analyzer" tests="1" failures="1">
+///   <testsuite name="missing-signer-check" tests="1" failures="1">
+///     <testcase classname="missing-signer-check" name="src/lib.rs:12" file="src/lib.rs" line="12">
+///       <failure type="high" message="Missing Signer Check in 'Withdraw'...">...</failure>
+///     </testcase>
+///   </testsuite>
+/// </testsuites>
+/// ```
+#[derive(Debug)]
+pub struct JunitReport<'a> {
+    result: &'a AnalysisResult,
+    project: &'a str,
+}
+
+impl<'a> JunitReport<'a> {
+    /// Creates the report of an analysis of `project`
+    pub fn new(result: &'a AnalysisResult, project: &'a str) -> Self {
+        Self { result, project }
+    }
+
+    /// Returns the report as JUnit XML
+    pub fn to_xml(&self) -> String {
+        let findings = self.result.findings.len();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"rust-solana-analyzer\" tests=\"{findings}\" failures=\"{findings}\" time=\"{}\">",
+            seconds(self.result.stats.total_time_ms)
+        );
+        for (rule_id, findings) in results::group_by_rule(&self.result.findings) {
+            let _ = writeln!(
+                xml,
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">",
+                escape(rule_id),
+                findings.len(),
+                findings.len()
+            );
+            for finding in findings {
+                xml.push_str(&self.test_case(rule_id, finding));
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+
+    /// Save the JUnit report to a file
+    pub fn save(&self, output_path: &str) -> Result<(), std::io::Error> {
+        fs::write(output_path, self.to_xml())
+    }
+
+    fn test_case(&self, rule_id: &str, finding: &Finding) -> String {
+        let file = relative_path(&finding.location.file, self.project);
+        let line = finding.location.line;
+
+        let mut details = format!("{}\n\nLocation: {file}:{line}\nConfidence: {}", finding.description, finding.confidence);
+        if let Some(fingerprint) = &finding.fingerprint {
+            let _ = write!(details, "\nFingerprint: {fingerprint}");
+        }
+        if let Some(snippet) = &finding.code_snippet {
+            let _ = write!(details, "\n\n{snippet}");
+        }
+        for recommendation in &finding.recommendations {
+            let _ = write!(details, "\n- {recommendation}");
+        }
+
+        format!(
+            "    <testcase classname=\"{rule_id}\" name=\"{file}:{line}\" file=\"{file}\" line=\"{line}\">\n\
+             \x20     <failure type=\"{severity}\" message=\"{message}\">{details}</failure>\n\
+             \x20   </testcase>\n",
+            rule_id = escape(rule_id),
+            file = escape(file),
+            severity = finding.severity.as_str(),
+            message = escape(&finding.description),
+            details = escape(&details),
+        )
+    }
+}
+
+/// Helper function to format milliseconds as seconds
+fn seconds(milliseconds: u64) -> String {
+    format!("{:.3}", milliseconds as f64 / 1000.0)
+}
@@ -0,0 +1,58 @@
+use crate::analyzer::junit::JunitReport;
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Finding, RuleInfo, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(rule_id: &str, severity: Severity, line: usize) -> Finding {
+        FindingBuilder::new(format!("{rule_id} <in> 'withdraw'"))
+            .severity(severity)
+            .span("/project/src/lib.rs", line, 5, 20)
+            .snippet("amount / shares")
+            .recommendations(&["Check the divisor"])
+            .rule(RuleInfo {
+                id: rule_id.to_string(),
+                title: format!("Title of {rule_id}"),
+                ..Default::default()
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_findings_are_failed_test_cases() {
+        let result = AnalysisResult {
+            findings: vec![
+                finding("division-by-zero", Severity::High, 3),
+                finding("error-handling", Severity::Low, 8),
+                finding("division-by-zero", Severity::High, 12),
+            ],
+            stats: AnalysisStats { total_time_ms: 1250, ..Default::default() },
+        };
+
+        let xml = JunitReport::new(&result, "/project").to_xml();
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(xml.contains("<testsuites name=\"rust-solana-analyzer\" tests=\"3\" failures=\"3\" time=\"1.250\">"));
+        assert!(xml.contains("<testsuite name=\"division-by-zero\" tests=\"2\" failures=\"2\">"));
+        assert!(xml.contains("<testsuite name=\"error-handling\" tests=\"1\" failures=\"1\">"));
+        assert!(xml.contains(
+            "<testcase classname=\"division-by-zero\" name=\"src/lib.rs:12\" file=\"src/lib.rs\" line=\"12\">"
+        ));
+        assert!(xml.contains("<failure type=\"high\" message=\"division-by-zero &lt;in&gt; &#39;withdraw&#39;\">"));
+        assert!(xml.contains("- Check the divisor</failure>"));
+        assert_eq!(xml.matches("<testcase ").count(), 3);
+        assert!(xml.ends_with("</testsuites>\n"));
+    }
+
+    #[test]
+    fn test_no_findings() {
+        let result = AnalysisResult { findings: Vec::new(), stats: AnalysisStats::default() };
+
+        let xml = JunitReport::new(&result, "/project").to_xml();
+
+        assert!(xml.contains("tests=\"0\" failures=\"0\""));
+        assert!(!xml.contains("<testsuite "));
+    }
+}
@@ -0,0 +1,202 @@
+use std::fmt::Write;
+use std::fs;
+
+use crate::analyzer::csv::row;
+use crate::analyzer::inventory::{AccountField, ProgramInventory};
+
+#[cfg(test)]
+mod test;
+
+/// Constraints of an account of an instruction, the cells of the [`ConstraintMatrix`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccountConstraints {
+    /// Writable: `mut`, or created by `init` / `init_if_needed`
+    pub mutable: bool,
+    /// Must sign: `Signer` type or `signer` constraint
+    pub signer: bool,
+    /// Created by the instruction: `init` or `init_if_needed`
+    pub init: bool,
+    /// Closed by the instruction: `close = ...`
+    pub close: bool,
+    /// PDA: `seeds = [...]`
+    pub seeds: bool,
+}
+
+impl AccountConstraints {
+    /// Reads the constraints of an account of an accounts struct
+    pub fn of(field: &AccountField) -> Self {
+        let has = |name: &str| field.constraints.iter().any(|constraint| constraint == name);
+        let has_value = |name: &str| {
+            field.constraints.iter().any(|constraint| constraint.split_once('=').is_some_and(|(key, _)| key == name))
+        };
+        let init = has("init") || has("init_if_needed");
+        let ty = field.ty.split('<').next().unwrap_or(&field.ty);
+        Self {
+            mutable: has("mut") || init,
+            signer: ty == "Signer" || ty.ends_with("::Signer") || has("signer"),
+            init,
+            close: has_value("close"),
+            seeds: has_value("seeds"),
+        }
+    }
+
+    /// Returns the names of the constraints (`mut signer`, empty if there are none)
+    pub fn labels(&self) -> Vec<&'static str> {
+        [
+            (self.mutable, "mut"),
+            (self.signer, "signer"),
+            (self.init, "init"),
+            (self.close, "close"),
+            (self.seeds, "seeds"),
+        ]
+        .into_iter()
+        .filter_map(|(set, label)| set.then_some(label))
+        .collect()
+    }
+}
+
+/// Instructions × accounts matrix of a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramMatrix {
+    /// Crate of the workspace, `None` when the files are analyzed as one program
+    pub program: Option<String>,
+    /// Names of the accounts, in the order they first appear in the instructions
+    pub accounts: Vec<String>,
+    /// Instructions with their cell for each account (`None` if the instruction does not take it)
+    pub rows: Vec<(String, Vec<Option<AccountConstraints>>)>,
+}
+
+impl ProgramMatrix {
+    /// Builds the matrix of a program from its inventory
+    pub fn of(inventory: &ProgramInventory) -> Self {
+        let mut accounts: Vec<String> = Vec::new();
+        let fields: Vec<(String, &[AccountField])> = inventory
+            .instructions
+            .iter()
+            .map(|instruction| {
+                let accounts_struct = inventory
+                    .accounts
+                    .iter()
+                    .find(|accounts_struct| instruction.accounts.as_deref() == Some(accounts_struct.name.as_str()));
+                (instruction.name.clone(), accounts_struct.map_or(&[][..], |accounts_struct| &accounts_struct.fields))
+            })
+            .collect();
+        for field in fields.iter().flat_map(|(_, fields)| fields.iter()) {
+            if !accounts.contains(&field.name) {
+                accounts.push(field.name.clone());
+            }
+        }
+        let rows = fields
+            .into_iter()
+            .map(|(instruction, fields)| {
+                let cells = accounts
+                    .iter()
+                    .map(|account| fields.iter().find(|field| &field.name == account).map(AccountConstraints::of))
+                    .collect();
+                (instruction, cells)
+            })
+            .collect();
+        Self { program: inventory.program.clone(), accounts, rows }
+    }
+}
+
+/// Constraint matrix of the programs of a project (`--constraint-matrix`)
+///
+/// One row per instruction and one column per account name, each cell giving the `mut`,
+/// `signer`, `init`, `close` and `seeds` constraints of the account in the instruction, so an
+/// account missing a constraint in one instruction stands out from the others. Accounts with
+/// the same name in several accounts structs share their column.
+#[derive(Debug)]
+pub struct ConstraintMatrix<'a> {
+    /// Analyzed path
+    project: &'a str,
+    /// Matrix of each program with instructions
+    programs: Vec<ProgramMatrix>,
+}
+
+impl<'a> ConstraintMatrix<'a> {
+    /// Creates the matrix of the programs of `project`, from their inventory
+    pub fn new(inventory: &[ProgramInventory], project: &'a str) -> Self {
+        let programs = inventory
+            .iter()
+            .filter(|program| !program.instructions.is_empty())
+            .map(ProgramMatrix::of)
+            .collect();
+        Self { project, programs }
+    }
+
+    /// Returns the matrix of each program
+    pub fn programs(&self) -> &[ProgramMatrix] {
+        &self.programs
+    }
+
+    /// Returns the matrix as a Markdown document, one table per program
+    ///
+    /// Accounts without constraints are written `-`, accounts an instruction does not take
+    /// are left empty.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from("# Constraint Matrix\n\n");
+        let _ = writeln!(markdown, "Account constraints of the instructions of {}.\n", self.project);
+        if self.programs.is_empty() {
+            markdown.push_str("No instruction found.\n");
+        }
+        for program in &self.programs {
+            let _ = writeln!(markdown, "## {}\n", program.program.as_deref().unwrap_or("Program"));
+            let header: Vec<String> = program.accounts.iter().map(|account| format!("`{account}`")).collect();
+            let _ = writeln!(markdown, "| Instruction | {} |", header.join(" | "));
+            let _ = writeln!(markdown, "| --- |{}", " --- |".repeat(program.accounts.len()));
+            for (instruction, cells) in &program.rows {
+                let cells: Vec<String> = cells
+                    .iter()
+                    .map(|cell| match cell {
+                        Some(constraints) if constraints.labels().is_empty() => "-".to_string(),
+                        Some(constraints) => constraints.labels().join(", "),
+                        None => String::new(),
+                    })
+                    .collect();
+                let _ = writeln!(markdown, "| `{instruction}` | {} |", cells.join(" | "));
+            }
+            markdown.push('\n');
+        }
+        markdown
+    }
+
+    /// Returns the matrix as CSV, one row per program and instruction with a column per
+    /// account name of any program
+    ///
+    /// Cells list the constraints separated by spaces (`mut signer`), `-` for an account
+    /// without constraints.
+    pub fn to_csv(&self) -> String {
+        let mut accounts: Vec<&str> = Vec::new();
+        for account in self.programs.iter().flat_map(|program| &program.accounts) {
+            if !accounts.contains(&account.as_str()) {
+                accounts.push(account);
+            }
+        }
+        let mut csv = row(["program", "instruction"].into_iter().chain(accounts.iter().copied()));
+        for program in &self.programs {
+            for (instruction, cells) in &program.rows {
+                let cells: Vec<String> = accounts
+                    .iter()
+                    .map(|account| {
+                        let index = program.accounts.iter().position(|name| name == account);
+                        match index.and_then(|index| cells[index]) {
+                            Some(constraints) if constraints.labels().is_empty() => "-".to_string(),
+                            Some(constraints) => constraints.labels().join(" "),
+                            None => String::new(),
+                        }
+                    })
+                    .collect();
+                let fields = [program.program.as_deref().unwrap_or_default(), instruction.as_str()];
+                csv.push_str(&row(fields.into_iter().chain(cells.iter().map(String::as_str))));
+            }
+        }
+        csv
+    }
+
+    /// Save the matrix to a file, as CSV if its extension is `csv` and as Markdown otherwise
+    pub fn save(&self, output_path: &str) -> Result<(), std::io::Error> {
+        let csv = std::path::Path::new(output_path).extension().is_some_and(|extension| extension == "csv");
+        fs::write(output_path, if csv { self.to_csv() } else { self.to_markdown() })
+    }
+}
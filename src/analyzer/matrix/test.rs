@@ -0,0 +1,115 @@
+use crate::analyzer::dsl::ProgramContext;
+use crate::analyzer::inventory::ProgramInventory;
+use crate::analyzer::matrix::{AccountConstraints, ConstraintMatrix};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+use anchor_lang::prelude::*;
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    pub fn open(ctx: Context<Open>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn shut(ctx: Context<Shut>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Open<'info> {
+    #[account(init, payer = user, space = 48, seeds = [b"vault", user.key().as_ref()], bump)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, seeds = [b"vault", user.key().as_ref()], bump)]
+    pub vault: Account<'info, Vault>,
+    pub user: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Shut<'info> {
+    #[account(mut, close = user, has_one = user)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut, signer)]
+    pub user: AccountInfo<'info>,
+}
+
+#[account]
+pub struct Vault {
+    pub user: Pubkey,
+}
+"#;
+
+    fn inventory() -> ProgramInventory {
+        let ast = syn::parse_file(SOURCE).unwrap();
+        let context = ProgramContext::build([("src/lib.rs", &ast)]);
+        ProgramInventory::of(Some("vault".to_string()), &context, |_| true)
+    }
+
+    #[test]
+    fn test_account_constraints() {
+        let inventory = inventory();
+        let open = &inventory.accounts[0];
+
+        let vault = AccountConstraints::of(&open.fields[0]);
+        assert_eq!(vault.labels(), vec!["mut", "init", "seeds"]);
+        let user = AccountConstraints::of(&open.fields[1]);
+        assert_eq!(user.labels(), vec!["mut", "signer"]);
+        assert!(AccountConstraints::of(&open.fields[2]).labels().is_empty());
+
+        let shut = &inventory.accounts[2];
+        assert_eq!(AccountConstraints::of(&shut.fields[0]).labels(), vec!["mut", "close"]);
+        assert_eq!(AccountConstraints::of(&shut.fields[1]).labels(), vec!["mut", "signer"]);
+    }
+
+    #[test]
+    fn test_matrix() {
+        let inventory = vec![inventory()];
+        let matrix = ConstraintMatrix::new(&inventory, "/project");
+
+        let program = &matrix.programs()[0];
+        assert_eq!(program.accounts, vec!["vault", "user", "system_program"]);
+        let instructions: Vec<&str> = program.rows.iter().map(|(instruction, _)| instruction.as_str()).collect();
+        assert_eq!(instructions, vec!["open", "withdraw", "shut"]);
+        // `withdraw` does not take the system program
+        assert_eq!(program.rows[1].1[2], None);
+    }
+
+    #[test]
+    fn test_markdown_and_csv() {
+        let inventory = vec![inventory()];
+        let matrix = ConstraintMatrix::new(&inventory, "/project");
+
+        let markdown = matrix.to_markdown();
+        assert!(markdown.starts_with("# Constraint Matrix\n\nAccount constraints of the instructions of /project.\n\n## vault\n\n"));
+        assert!(markdown.contains("| Instruction | `vault` | `user` | `system_program` |\n| --- | --- | --- | --- |\n"));
+        assert!(markdown.contains("| `open` | mut, init, seeds | mut, signer | - |\n"));
+        // The user does not sign the withdrawal
+        assert!(markdown.contains("| `withdraw` | mut, seeds | - |  |\n"));
+
+        let csv = matrix.to_csv();
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(lines[0], "program,instruction,vault,user,system_program");
+        assert_eq!(lines[1], "vault,open,mut init seeds,mut signer,-");
+        assert_eq!(lines[3], "vault,shut,mut close,mut signer,");
+
+        let empty = ConstraintMatrix::new(&[], "/project").to_markdown();
+        assert!(empty.ends_with("No instruction found.\n"));
+    }
+}
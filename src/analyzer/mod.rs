@@ -1,19 +1,91 @@
 // Declare submodules
+pub mod advisories;
+#[cfg(feature = "native")]
+pub mod anchor;
+pub mod audit;
+pub mod budgets;
+pub mod caps;
+pub mod cfg;
+pub mod compare;
+pub mod config;
+pub mod console;
+pub mod cpi;
+pub mod csv;
+pub mod dedup;
+pub mod diff;
 pub mod dsl;
 pub mod engine;
+pub mod expansion;
+pub mod explain;
+pub mod fingerprint;
+pub mod fixes;
+pub mod github;
+pub mod gitlab;
+#[cfg(feature = "native")]
+pub mod hook;
+pub mod history;
+pub mod html;
+pub mod idl;
+pub mod ignore;
+pub mod incremental;
+pub mod init;
+pub mod inventory;
+pub mod items;
+pub mod json;
+pub mod junit;
+pub mod matrix;
+pub mod native;
+pub mod ndjson;
+pub mod output;
+pub mod packs;
+pub mod pdas;
+#[cfg(feature = "wasm-plugins")]
+pub mod plugins;
+pub mod progress;
+pub mod rdjson;
 pub mod rules;
 pub mod reporting;
+pub mod report_templates;
+pub mod registry;
+pub mod remote;
+pub mod results;
+pub mod sarif;
+pub mod scaffold;
+pub mod scope;
+#[cfg(feature = "scripting")]
+pub mod scripting;
 pub mod span_utils;
+pub mod stats;
+pub mod suppressions;
+pub mod templates;
+pub mod testing;
+pub mod vfs;
+pub mod workspace;
 
 // Standard imports
-use anyhow::Context;
-use log::{debug, info, warn};
-use std::collections::HashMap;
+use log::{debug, warn};
+#[cfg(feature = "native")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::Path;
 use syn::File;
 
+use crate::ast::parser::{self, ParseError, SourceFile, SourceText};
+use suppressions::{SuppressedBy, SuppressedFinding, Suppressions, UNUSED_SUPPRESSION_ID};
+
+/// ID of the findings reported on the files that could not be parsed, and so were not analyzed
+pub const PARSE_ERROR_ID: &str = "parse-error";
+
+/// Metadata key of the source lines around a finding (see [`AnalysisOptions::context_lines`])
+pub const CONTEXT_KEY: &str = "context";
+
+/// Maximum number of context lines around a finding, to keep the reports bounded
+pub const MAX_CONTEXT_LINES: usize = 10;
+
 /// Severity level of a vulnerability
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Severity {
     /// High severity vulnerability that must be fixed immediately
     High,
@@ -25,43 +97,337 @@ pub enum Severity {
     Informational,
 }
 
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    /// Parse a severity level (case-insensitive: high, medium, low, informational)
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "high" => Ok(Severity::High),
+            "medium" => Ok(Severity::Medium),
+            "low" => Ok(Severity::Low),
+            "informational" => Ok(Severity::Informational),
+            _ => Err(format!("Unknown severity level: {value}")),
+        }
+    }
+}
+
+impl Severity {
+    /// Every severity, the most severe first
+    pub const ALL: [Severity; 4] = [Severity::High, Severity::Medium, Severity::Low, Severity::Informational];
+
+    /// Returns the name of the severity as written in configurations and reports (`high`...)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::High => "high",
+            Severity::Medium => "medium",
+            Severity::Low => "low",
+            Severity::Informational => "informational",
+        }
+    }
+
+    /// Rank of the severity, from 0 for the least severe
+    pub fn rank(&self) -> u8 {
+        match self {
+            Severity::Informational => 0,
+            Severity::Low => 1,
+            Severity::Medium => 2,
+            Severity::High => 3,
+        }
+    }
+
+    /// Check if the severity is the same as or higher than the given threshold
+    pub fn is_at_least(&self, threshold: &Severity) -> bool {
+        self.rank() >= threshold.rank()
+    }
+}
+
+/// Confidence that a finding is a true positive
+///
+/// Variants are ordered from least to most certain, so `min` keeps the weakest level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    /// Heuristic match that needs manual review
+    Low,
+    /// Likely issue, but the pattern can have legitimate uses
+    Medium,
+    /// Pattern that is an issue whenever it matches
+    #[default]
+    High,
+}
+
+impl std::fmt::Display for Confidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Confidence::Low => write!(f, "Low"),
+            Confidence::Medium => write!(f, "Medium"),
+            Confidence::High => write!(f, "High"),
+        }
+    }
+}
+
+impl std::str::FromStr for Confidence {
+    type Err = String;
+
+    /// Parse a confidence level (case-insensitive: high, medium, low)
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "high" => Ok(Confidence::High),
+            "medium" => Ok(Confidence::Medium),
+            "low" => Ok(Confidence::Low),
+            _ => Err(format!("Unknown confidence level: {value}")),
+        }
+    }
+}
+
+/// Solana vulnerability class of a rule, for vulnerability management systems
+///
+/// Categories are written in kebab-case (`missing-signer`, `arbitrary-cpi`...) in templates,
+/// scripts, plugins and reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Category {
+    /// Account that must sign the transaction is not checked as a signer
+    MissingSigner,
+    /// Account data is trusted without checking the program that owns it
+    MissingOwnerCheck,
+    /// Same account can be passed for several accounts of an instruction
+    DuplicateAccounts,
+    /// Account of one type can be passed as an account of another type
+    TypeCosplay,
+    /// Cross-program invocation to a program that is not checked
+    ArbitraryCpi,
+    /// Program derived address derivation or bump seed issues
+    Pda,
+    /// Account that can be initialized again
+    Reinitialization,
+    /// Account closed without being emptied or marked as closed
+    AccountClosing,
+    /// Instruction restricted to an authority without checking it
+    AccessControl,
+    /// Overflow, underflow, division by zero or precision loss
+    Arithmetic,
+    /// Unsafe Rust code
+    UnsafeCode,
+    /// Errors that are not reported or handled
+    ErrorHandling,
+}
+
+impl Category {
+    /// Every category, in declaration order
+    pub const ALL: [Category; 12] = [
+        Category::MissingSigner,
+        Category::MissingOwnerCheck,
+        Category::DuplicateAccounts,
+        Category::TypeCosplay,
+        Category::ArbitraryCpi,
+        Category::Pda,
+        Category::Reinitialization,
+        Category::AccountClosing,
+        Category::AccessControl,
+        Category::Arithmetic,
+        Category::UnsafeCode,
+        Category::ErrorHandling,
+    ];
+
+    /// Returns the kebab-case name of the category
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Category::MissingSigner => "missing-signer",
+            Category::MissingOwnerCheck => "missing-owner-check",
+            Category::DuplicateAccounts => "duplicate-accounts",
+            Category::TypeCosplay => "type-cosplay",
+            Category::ArbitraryCpi => "arbitrary-cpi",
+            Category::Pda => "pda",
+            Category::Reinitialization => "reinitialization",
+            Category::AccountClosing => "account-closing",
+            Category::AccessControl => "access-control",
+            Category::Arithmetic => "arithmetic",
+            Category::UnsafeCode => "unsafe-code",
+            Category::ErrorHandling => "error-handling",
+        }
+    }
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for Category {
+    type Err = String;
+
+    /// Parse a category from its kebab-case name (case-insensitive)
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let name = value.trim().to_lowercase();
+        Category::ALL
+            .into_iter()
+            .find(|category| category.as_str() == name)
+            .ok_or_else(|| format!("Unknown category: {value}"))
+    }
+}
+
 /// Location of a vulnerability in the source code
-#[derive(Debug, Clone)]
+///
+/// Lines start at 1 and columns at 0, counted in characters like the spans of the parser. The
+/// reports for other tools convert the columns to theirs (SARIF, rdjson, CSV, GitHub...).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Location {
     /// File path
     pub file: String,
     /// Line number (1-indexed)
     pub line: usize,
-    /// Column number (1-indexed)
+    /// Column number (0-indexed, in characters)
     pub column: Option<usize>,
     /// End line number (1-indexed)
     pub end_line: Option<usize>,
-    /// End column number (1-indexed)
+    /// End column number (0-indexed, in characters, exclusive)
     pub end_column: Option<usize>,
 }
 
+/// Replacement of a range of the source code
+///
+/// The range uses the same coordinates as `Location` (1-indexed lines, 0-indexed columns);
+/// an empty range (start == end) is an insertion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEdit {
+    /// Range of the source code to replace
+    pub location: Location,
+    /// Text that replaces the range
+    pub replacement: String,
+}
+
+/// Whether a fix can be applied without review
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Applicability {
+    /// The fix is correct as is and keeps the code compiling
+    MachineApplicable,
+    /// The fix shows the change to make, but may need adapting (e.g. to the error type of the
+    /// function)
+    #[default]
+    Advisory,
+}
+
+impl Applicability {
+    /// Returns the label of the applicability in reports (`machine-applicable` or `advisory`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Applicability::MachineApplicable => "machine-applicable",
+            Applicability::Advisory => "advisory",
+        }
+    }
+}
+
+/// Fix suggested by a rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fix {
+    /// What the fix does (e.g. "Add the signer constraint to 'authority'")
+    pub description: String,
+    /// Edits to apply together
+    pub edits: Vec<TextEdit>,
+    /// Whether the fix can be applied without review (advisory if not set)
+    #[serde(default)]
+    pub applicability: Applicability,
+}
+
+impl Fix {
+    /// Creates an advisory fix from its edits
+    pub fn new(description: &str, edits: Vec<TextEdit>) -> Self {
+        Self {
+            description: description.to_string(),
+            edits,
+            applicability: Applicability::Advisory,
+        }
+    }
+
+    /// Creates a fix that can be applied without review
+    pub fn machine_applicable(description: &str, edits: Vec<TextEdit>) -> Self {
+        Self { applicability: Applicability::MachineApplicable, ..Self::new(description, edits) }
+    }
+}
+
 /// Finding of a vulnerability
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Finding {
     /// Description of the vulnerability
     pub description: String,
     /// Severity level of the vulnerability
     pub severity: Severity,
+    /// Confidence that the finding is a true positive
+    pub confidence: Confidence,
     /// Location of the vulnerability in the source code
     pub location: Location,
     /// Code snippet containing the vulnerability (optional)
     pub code_snippet: Option<String>,
     /// Recommendations for fixing the vulnerability
     pub recommendations: Vec<String>,
+    /// Machine-applicable fixes suggested by the rule
+    pub fixes: Vec<Fix>,
+    /// Metadata attached to the matched node by the rule's filters
+    pub metadata: dsl::query::Metadata,
+    /// Rules that reported the finding, the one that produced the description first (set by
+    /// the rule engine, several when duplicate findings are merged)
+    pub rules: Vec<RuleInfo>,
+    /// Content-based identifier that survives line shifts (set by the analyzer, see
+    /// [`fingerprint::assign_fingerprints`])
+    pub fingerprint: Option<String>,
+}
+
+impl Finding {
+    /// Returns the rule that produced the finding
+    pub fn rule(&self) -> Option<&RuleInfo> {
+        self.rules.first()
+    }
+
+    /// Returns the IDs of the rules that reported the finding
+    pub fn rule_ids(&self) -> Vec<&str> {
+        self.rules.iter().map(|rule| rule.id.as_str()).collect()
+    }
+}
+
+/// Metadata of the rule that reported a finding, for reports and machine output
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RuleInfo {
+    /// Unique ID of the rule
+    pub id: String,
+    /// Title of the rule
+    pub title: String,
+    /// Links to documentation or additional resources
+    pub references: Vec<String>,
+    /// Tags classifying the rule
+    pub tags: Vec<String>,
+    /// CWE IDs of the weakness detected by the rule
+    pub cwe: Vec<u32>,
+    /// Solana vulnerability class of the rule
+    pub category: Option<Category>,
+}
+
+impl RuleInfo {
+    /// Collects the metadata of a rule
+    pub fn from_rule(rule: &dyn Rule) -> Self {
+        Self {
+            id: rule.id().to_string(),
+            title: rule.title().to_string(),
+            references: rule.references(),
+            tags: rule.tags(),
+            cwe: rule.cwe(),
+            category: rule.category(),
+        }
+    }
 }
 
 /// Custom result type for analyzer operations
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 pub use engine::{
-    Rule, RuleEngine, RuleEngineConfig, RuleType, create_rule_engine,
+    Rule, RuleEngine, RuleEngineConfig, RuleExample, RuleProfile, RuleType, create_rule_engine,
     create_rule_engine_with_config,
 };
+pub use rules::{BuiltinRules, RulePlugin};
+pub use registry::{RuleMetadata, RuleRegistry};
 
 /// Creates an analyzer with default options
 pub fn create_analyzer() -> Analyzer {
@@ -74,16 +440,37 @@ pub fn create_analyzer_with_options(options: AnalysisOptions) -> Analyzer {
 }
 
 /// Result of an analysis
-#[derive(Debug)]
+///
+/// Serializes as `{ "findings": [...], "stats": {...} }`, the findings in the shape of
+/// [`Finding`] and the statistics in the one of [`AnalysisStats`].
+#[derive(Debug, Serialize)]
 pub struct AnalysisResult {
-    /// Findings found during the analysis
+    /// Findings found during the analysis, sorted by file, line, column and rule
     pub findings: Vec<Finding>,
     /// Statistics of the analysis
     pub stats: AnalysisStats,
 }
 
+impl AnalysisResult {
+    /// Returns the number of findings with the given severity or a higher one (every finding if `None`)
+    pub fn count_at_least(&self, severity: Option<&Severity>) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| severity.is_none_or(|severity| finding.severity.is_at_least(severity)))
+            .count()
+    }
+
+    /// Check if the analysis must fail a CI pipeline: more than `max_findings` findings (none if
+    /// not set) with the `fail_on` severity or a higher one (any severity if not set)
+    ///
+    /// Never fails if neither threshold is set.
+    pub fn exceeds_thresholds(&self, fail_on: Option<&Severity>, max_findings: Option<usize>) -> bool {
+        (fail_on.is_some() || max_findings.is_some()) && self.count_at_least(fail_on) > max_findings.unwrap_or(0)
+    }
+}
+
 /// Statistics of an analysis
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize)]
 pub struct AnalysisStats {
     /// Number of files analyzed
     pub files_analyzed: usize,
@@ -91,8 +478,77 @@ pub struct AnalysisStats {
     pub rules_executed: usize,
     /// Total analysis time in milliseconds
     pub total_time_ms: u64,
-    /// Breakdown of findings by severity
+    /// Breakdown of findings by severity (the most severe first in JSON)
+    #[serde(serialize_with = "serialize_by_severity")]
     pub findings_by_severity: HashMap<Severity, usize>,
+    /// Number of findings removed by suppression comments and by the severity and confidence filters
+    pub findings_suppressed: usize,
+    /// Findings removed by suppression comments and by the severity and confidence filters, with
+    /// their reasons and fingerprints (the `suppressed` array of [`json::JsonReport`], and the
+    /// appendix of the reports)
+    #[serde(skip)]
+    pub suppressed_findings: Vec<SuppressedFinding>,
+    /// Number of duplicate findings merged into another finding
+    pub findings_merged: usize,
+    /// Number of files whose results were reused from the cache
+    pub files_cached: usize,
+    /// Files (or [`engine::PROJECT_TARGET`]) on which each rule exceeded its time budget, by rule ID
+    pub timed_out_rules: std::collections::BTreeMap<String, std::collections::BTreeSet<String>>,
+    /// Execution profile of each rule, by rule ID (only if [`AnalysisOptions::profile_rules`] is set)
+    pub rule_profiles: std::collections::BTreeMap<String, engine::RuleProfile>,
+    /// Number of findings reported on macro-expanded code
+    pub findings_expanded: usize,
+    /// Number of test files not analyzed (unless [`AnalysisOptions::include_tests`] is set)
+    pub test_files_skipped: usize,
+    /// Number of findings in each crate of the workspace, by crate name (see [`AnalysisOptions::workspace`])
+    pub findings_by_program: std::collections::BTreeMap<String, usize>,
+    /// Number of files not analyzed because they could not be parsed (reported as [`PARSE_ERROR_ID`])
+    pub files_unparsed: usize,
+    /// Why each file not analyzed could not be parsed, by path
+    #[serde(skip)]
+    pub parse_errors: std::collections::BTreeMap<String, ParseError>,
+    /// Number of findings over the caps of [`AnalysisOptions::finding_caps`], by rule ID
+    pub findings_omitted: std::collections::BTreeMap<String, usize>,
+    /// Number of findings in each file, by path
+    pub findings_by_file: std::collections::BTreeMap<String, usize>,
+    /// Number of findings of each rule (the first rule of merged findings), by rule ID
+    pub findings_by_rule: std::collections::BTreeMap<String, usize>,
+    /// Lines of code of each analyzed file, without the blank and comment lines, by path
+    pub lines_of_code: std::collections::BTreeMap<String, usize>,
+    /// Rules registered, enabled and with findings of each rule type, by type name
+    pub rule_types: std::collections::BTreeMap<String, stats::RuleTypeCoverage>,
+    /// Instructions, accounts and state of each program, outside of the test code (only with
+    /// [`AnalysisOptions::inventory`], see [`inventory::InventoryReport`])
+    #[serde(skip)]
+    pub inventory: Vec<inventory::ProgramInventory>,
+    /// PDA derivations of each program, outside of the test code (only with
+    /// [`AnalysisOptions::pdas`], see [`pdas::PdaReport`])
+    #[serde(skip)]
+    pub pdas: Vec<pdas::ProgramPdas>,
+    /// Cross-program invocations of each program, outside of the test code (only with
+    /// [`AnalysisOptions::cpi_graph`], see [`cpi::CpiGraphReport`])
+    #[serde(skip)]
+    pub cpis: Vec<cpi::ProgramCpis>,
+    /// Paths of the project left out of the analysis, with the rules that excluded them (set by
+    /// [`crate::project::analyze_project`])
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclusions: Vec<ignore::Exclusion>,
+}
+
+impl AnalysisStats {
+    /// Returns the lines of code of every analyzed file
+    pub fn total_lines_of_code(&self) -> usize {
+        self.lines_of_code.values().sum()
+    }
+}
+
+
+/// Helper function to serialize counts by severity in a stable order, the most severe first
+fn serialize_by_severity<S: serde::Serializer>(
+    counts: &HashMap<Severity, usize>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_map(Severity::ALL.iter().filter_map(|severity| counts.get(severity).map(|count| (severity, count))))
 }
 
 /// Options for analysis
@@ -104,16 +560,139 @@ pub struct AnalysisOptions {
     /// Path to custom templates
     pub custom_templates_path: Option<String>,
 
+    /// Path to WebAssembly rule plugins
+    pub plugins_path: Option<String>,
+
     /// Severities to ignore
     pub ignore_severities: Vec<Severity>,
 
     /// Rule IDs to ignore
     pub ignore_rules: Vec<String>,
 
+    /// Rule IDs to run, all the other rules are ignored (every rule if empty)
+    pub include_rules: Vec<String>,
+
+    /// Tags of the rules to run (every rule if empty)
+    pub include_tags: Vec<String>,
+
     /// Rule types to include
     pub include_rule_types: Vec<RuleType>,
+
+    /// Profile or packs to run, separated by commas (see [`packs::resolve_profile`])
+    pub profile: Option<String>,
+
+    /// Severity of the findings by rule ID, replacing the severity set by the rule
+    pub severity_overrides: std::collections::BTreeMap<String, Severity>,
+
+    /// Minimum confidence of the reported findings
+    pub min_confidence: Option<Confidence>,
+
+    /// Only report the findings intersecting these lines (diff-aware analysis)
+    pub changed_lines: Option<diff::ChangedLines>,
+
+    /// Macro-expanded code analyzed alongside the files (see [`expansion::expand_files`])
+    pub expanded_files: Vec<expansion::ExpandedFile>,
+
+    /// Keep the duplicate findings reported on the same code instead of merging them
+    pub keep_duplicate_findings: bool,
+
+    /// Maximum number of findings reported per rule and per file (see [`caps::FindingCaps`])
+    pub finding_caps: caps::FindingCaps,
+
+    /// Report the findings in test code (see [`scope::TestCode`]), which is skipped by default
+    pub include_tests: bool,
+
+    /// Root of the analyzed project, finding fingerprints use the file paths relative to it
+    pub project_root: Option<std::path::PathBuf>,
+
+    /// Crates of the analyzed workspace, each one analyzed as its own program
+    pub workspace: Option<workspace::Workspace>,
+
+    /// Advisories checked against the dependencies of the workspace (none by default, see
+    /// [`advisories::AdvisoryDb::embedded`])
+    pub advisories: advisories::AdvisoryDb,
+
+    /// Number of files analyzed in parallel (0 for one per CPU core, one without the `native` feature)
+    ///
+    /// With more than one job, the files whose results are not cached are parsed twice: once
+    /// on the calling thread, and once on the thread analyzing them.
+    pub jobs: usize,
+
+    /// Directory caching the results of unchanged files between runs (no cache if not set)
+    pub cache_dir: Option<std::path::PathBuf>,
+
+    /// Time budget of a rule on a file, rules over it are reported without findings (no limit if not set)
+    pub rule_timeout: Option<std::time::Duration>,
+
+    /// Record the execution time, matched files and findings of each rule
+    pub profile_rules: bool,
+
+    /// Parameter overrides by rule ID (raw values keyed by parameter name)
+    pub rule_params: std::collections::BTreeMap<String, std::collections::BTreeMap<String, String>>,
+
+    /// Lines of source added before and after each finding under [`CONTEXT_KEY`] (none if 0, at
+    /// most [`MAX_CONTEXT_LINES`])
+    pub context_lines: usize,
+
+    /// Build the inventory of the instructions, accounts and state of each program (see
+    /// [`AnalysisStats::inventory`])
+    pub inventory: bool,
+
+    /// Collect the PDA derivations of each program (see [`AnalysisStats::pdas`])
+    pub pdas: bool,
+
+    /// Collect the cross-program invocations of each program (see [`AnalysisStats::cpis`])
+    pub cpi_graph: bool,
+
+    /// File system the custom rules, the manifests of the crates and their IDL are read from
+    /// (the one of the operating system by default)
+    pub file_system: vfs::SharedFileSystem,
+}
+
+/// Result of the per-file rules on a file
+struct FileAnalysis {
+    file_path: String,
+    suppressions: Suppressions,
+    findings: Vec<Finding>,
+    /// Whether the results come from the incremental cache
+    cached: bool,
+}
+
+/// Items containing lines of a file, found from its AST so its findings can be fingerprinted and
+/// annotated once the AST is dropped
+#[derive(Debug, Default)]
+struct LineItems {
+    /// Item of the fingerprints (see [`fingerprint::enclosing_item`]) and enclosing item, by line
+    lines: HashMap<usize, (Option<String>, items::Enclosing)>,
+}
+
+impl LineItems {
+    /// Find the items containing the given lines of a file whose module is `file_module`
+    fn of(ast: &File, file_module: &[String], lines: impl IntoIterator<Item = usize>) -> Self {
+        let lines = lines
+            .into_iter()
+            .map(|line| (line, (fingerprint::enclosing_item(ast, line), items::Enclosing::of(ast, line, file_module))))
+            .collect();
+        Self { lines }
+    }
+
+    /// Item of the fingerprints of the findings on a line
+    fn fingerprint_item(&self, line: usize) -> Option<String> {
+        self.lines.get(&line).and_then(|(item, _)| item.clone())
+    }
+
+    /// Item and module containing a line
+    fn enclosing(&self, line: usize) -> items::Enclosing {
+        self.lines.get(&line).map(|(_, enclosing)| enclosing.clone()).unwrap_or_default()
+    }
 }
 
+/// Callback receiving each finding as soon as it is produced (see [`Analyzer::with_finding_sink`])
+pub type FindingSink = Box<dyn Fn(&Finding) + Send + Sync>;
+
+/// Files of a crate of the workspace, with its manifest (none for the files outside the crates)
+type CrateFiles<'a> = (Option<&'a workspace::CrateManifest>, Vec<(std::path::PathBuf, String)>);
+
 /// Analyzer for Solana contracts
 pub struct Analyzer {
     /// Options for analysis
@@ -121,6 +700,21 @@ pub struct Analyzer {
 
     /// Rule engine
     rule_engine: RuleEngine,
+
+    /// Cache of the per-file results, if enabled
+    cache: Option<incremental::AnalysisCache>,
+
+    /// Receives the findings as they are produced, if set
+    finding_sink: Option<FindingSink>,
+
+    /// Receives the progress of the analysis, if set
+    progress_sink: Option<progress::ProgressSink>,
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Analyzer {
@@ -129,17 +723,45 @@ impl Analyzer {
         Self {
             options: AnalysisOptions::default(),
             rule_engine: create_rule_engine(),
+            cache: None,
+            finding_sink: None,
+            progress_sink: None,
         }
     }
 
     /// Creates a new analyzer with the given options
     pub fn with_options(options: AnalysisOptions) -> Self {
+        Self::with_rule_plugins(options, &[])
+    }
+
+    /// Creates a new analyzer with the given options and the rules of the given rule sets
+    /// registered after the built-in rules
+    pub fn with_rule_plugins(options: AnalysisOptions, plugins: &[&dyn RulePlugin]) -> Self {
+        // Resolve the profile into the packs to run (an invalid profile runs every rule)
+        let packs = match options.profile.as_deref().map(packs::resolve_profile) {
+            Some(Ok(packs)) => packs,
+            Some(Err(e)) => {
+                warn!("Ignoring profile: {e}");
+                None
+            }
+            None => None,
+        };
+
         // Convert analysis options to rule engine config
         let config = RuleEngineConfig {
             custom_templates_path: options.custom_templates_path.clone(),
+            plugins_path: options.plugins_path.clone(),
             ignore_severities: options.ignore_severities.clone(),
             ignore_rules: options.ignore_rules.clone(),
+            include_rules: options.include_rules.clone(),
+            include_tags: options.include_tags.clone(),
             include_rule_types: options.include_rule_types.clone(),
+            packs,
+            severity_overrides: options.severity_overrides.clone(),
+            rule_params: options.rule_params.clone(),
+            rule_timeout: options.rule_timeout,
+            profile_rules: options.profile_rules,
+            file_system: options.file_system.clone(),
         };
 
         let mut rule_engine = create_rule_engine_with_config(config);
@@ -149,13 +771,24 @@ impl Analyzer {
             warn!("Failed to load built-in rules: {e}");
         }
 
+        // Load rules from other crates
+        for plugin in plugins {
+            if let Err(e) = rule_engine.register_plugin(*plugin) {
+                warn!("{e}");
+            }
+        }
+
         // Load custom rules if specified
         if let Some(templates_path) = &options.custom_templates_path {
             let path = Path::new(templates_path);
-            if path.exists() && path.is_dir() {
+            if options.file_system.is_dir(path) {
                 if let Err(e) = rule_engine.load_yaml_rules(path) {
                     warn!("Failed to load YAML rules from {}: {}", path.display(), e);
                 }
+                #[cfg(feature = "scripting")]
+                if let Err(e) = rule_engine.load_script_rules(path) {
+                    warn!("Failed to load script rules from {}: {}", path.display(), e);
+                }
             } else {
                 warn!(
                     "Custom templates path does not exist or is not a directory: {}",
@@ -164,67 +797,768 @@ impl Analyzer {
             }
         }
 
+        // Load WebAssembly rule plugins if specified
+        if let Some(plugins_path) = &options.plugins_path {
+            let path = Path::new(plugins_path);
+            if !options.file_system.is_dir(path) {
+                warn!("Plugins path does not exist or is not a directory: {}", path.display());
+            } else {
+                #[cfg(feature = "wasm-plugins")]
+                if let Err(e) = rule_engine.load_wasm_plugins(path) {
+                    warn!("Failed to load plugins from {}: {}", path.display(), e);
+                }
+                #[cfg(not(feature = "wasm-plugins"))]
+                warn!("Ignoring plugins in {}: built without the `wasm-plugins` feature", path.display());
+            }
+        }
+
+        rule_engine.check_rule_params();
+
+        // Results cached for another rule set are invalidated when the cache is opened
+        let cache = options.cache_dir.as_deref().and_then(|dir| {
+            let key = incremental::rule_set_key(&rule_engine.rule_ids(), &options);
+            match incremental::AnalysisCache::open(dir, &key) {
+                Ok(cache) => Some(cache),
+                Err(e) => {
+                    warn!("Analysis cache disabled, cannot open {}: {e}", dir.display());
+                    None
+                }
+            }
+        });
+
         Self {
             options,
             rule_engine,
+            cache,
+            finding_sink: None,
+            progress_sink: None,
         }
     }
 
-    /// Analyzes a single file
-    pub fn analyze_file(&self, file_path: &str, ast: &File) -> Result<Vec<Finding>> {
+    /// Returns the rules known to the analyzer, including the ones filtered out by the options
+    pub fn rules(&self) -> &RuleRegistry {
+        self.rule_engine.registry()
+    }
+
+    /// Send each finding to `sink` as soon as it is produced, while the files are analyzed
+    ///
+    /// The sink is called from the threads analyzing the files, with the findings that pass the
+    /// severity, confidence and changed lines filters, in the order they are produced. They are
+    /// streamed as the rules report them: before the duplicates are merged, the finding caps
+    /// applied and the fingerprints, enclosing items and context lines added, which only the
+    /// findings of the final [`AnalysisResult`] have.
+    pub fn with_finding_sink(mut self, sink: FindingSink) -> Self {
+        self.finding_sink = Some(sink);
+        self
+    }
+
+    /// Send the progress of the analysis to `sink`: each file parsed, then each file analyzed
+    /// out of the total
+    ///
+    /// The sink is called from the threads analyzing the files, and not for the files analyzed
+    /// one by one ([`Analyzer::analyze_file`], [`Analyzer::analyze_source`]).
+    pub fn with_progress_sink(mut self, sink: progress::ProgressSink) -> Self {
+        self.progress_sink = Some(sink);
+        self
+    }
+
+    /// Analyzes a single file, with the source code its AST was parsed from
+    pub fn analyze_file(&self, file: &SourceFile) -> Result<Vec<Finding>> {
+        let file_path = file.path.to_string_lossy();
         debug!("Analyzing file: {file_path}");
+        if let Some(error) = &file.parse_error {
+            return Ok(self.parse_error_finding(&file.path, &file.source, error).into_iter().collect());
+        }
+        self.analyze_source(&file_path, &file.ast, &file.source)
+    }
+
+    /// Helper function to report a file that could not be parsed, unless parse errors are ignored
+    fn parse_error_finding(&self, path: &Path, source_code: &str, error: &ParseError) -> Option<Finding> {
+        if self.options.ignore_rules.iter().any(|id| id == PARSE_ERROR_ID) {
+            return None;
+        }
+        Some(Finding {
+            description: format!("Analysis skipped due to parse error: {}", error.message),
+            severity: Severity::Informational,
+            confidence: Confidence::High,
+            location: Location::new_precise(path.to_string_lossy().to_string(), error.line, Some(error.column), None, None),
+            code_snippet: source_code.lines().nth(error.line - 1).map(|line| line.trim().to_string()),
+            recommendations: vec![
+                "No rule ran on this file: fix the syntax error, or check that the code is valid Rust of a supported edition".to_string(),
+            ],
+            fixes: Vec::new(),
+            metadata: dsl::query::Metadata::new(),
+            rules: vec![RuleInfo {
+                id: PARSE_ERROR_ID.to_string(),
+                title: "Parse Error".to_string(),
+                ..Default::default()
+            }],
+            fingerprint: None,
+        })
+    }
+
+    /// Analyzes a single file whose source code is already loaded
+    ///
+    /// Findings covered by suppression comments are removed, unused suppressions are reported,
+    /// and duplicate findings are merged.
+    pub fn analyze_source(&self, file_path: &str, ast: &File, source_code: &str) -> Result<Vec<Finding>> {
+        let program = dsl::ProgramContext::build([(file_path, ast)])
+            .with_tests(self.options.include_tests)
+            .with_manifest(self.crate_of(Path::new(file_path)).cloned());
+        let mut test_code = scope::TestCode::default();
+        self.add_test_code(&mut test_code, [(file_path, ast)], &program);
+        if test_code.is_test_file(file_path) {
+            debug!("Skipping the test file {file_path}");
+            return Ok(Vec::new());
+        }
+
+        let mut suppressions = Suppressions::parse(source_code, file_path);
+        let uses_anchor = scope::AnchorCrates::default().uses_anchor(&*self.options.file_system, Path::new(file_path));
+        let mut findings = self.execute_file_rules(file_path, ast, source_code, &program, uses_anchor, &mut suppressions);
+        findings.extend(self.unused_suppressions(file_path, source_code, &suppressions));
+        findings.retain(|finding| !test_code.contains(&finding.location));
+        let mut findings = self.merge_duplicates(findings);
+        let file_module = program.module_of(file_path).map(items::module_path).unwrap_or_default();
+        let line_items = LineItems::of(ast, &file_module, findings.iter().map(|finding| finding.location.line));
+        self.annotate_findings(&mut findings, |file| (file == file_path).then_some(&line_items));
+        self.options.finding_caps.apply(&mut findings);
+        self.add_context(&mut findings, |file| (file == file_path).then_some(source_code));
+        Ok(findings)
+    }
+
+    /// Helper function to add the source lines around the findings, from the source code of
+    /// their file (findings outside the analyzed files get none)
+    fn add_context<'a, F>(&self, findings: &mut [Finding], source_of: F)
+    where
+        F: Fn(&str) -> Option<&'a str>,
+    {
+        let context_lines = self.options.context_lines.min(MAX_CONTEXT_LINES);
+        if context_lines == 0 {
+            return;
+        }
+        let mut extractors: HashMap<String, Option<span_utils::SpanExtractor>> = HashMap::new();
+        for finding in findings {
+            let location = &finding.location;
+            let extractor = extractors.entry(location.file.clone()).or_insert_with(|| {
+                source_of(&location.file).map(|source| span_utils::SpanExtractor::new(source.to_string(), location.file.clone()))
+            });
+            let context = extractor.as_ref().and_then(|extractor| {
+                extractor.extract_lines_context(location.line, location.end_line.unwrap_or(location.line), context_lines)
+            });
+            if let Some(context) = context {
+                finding.metadata.insert(CONTEXT_KEY.to_string(), dsl::query::MetadataValue::Text(context));
+            }
+        }
+    }
+
+    /// Helper function to compute the fingerprints of the findings and to find their enclosing
+    /// items, file by file, from the items found on their lines while the ASTs were held
+    /// (findings of files without items get no enclosing item)
+    fn annotate_findings<'a, F>(&self, findings: &mut [Finding], items_of: F)
+    where
+        F: Fn(&str) -> Option<&'a LineItems>,
+    {
+        let files: BTreeSet<String> = findings.iter().map(|finding| finding.location.file.clone()).collect();
+        for file in files {
+            let line_items = items_of(&file);
+            let item_of = |line| line_items.and_then(|line_items| line_items.fingerprint_item(line));
+            fingerprint::assign_fingerprints_with(findings, &file, item_of, self.options.project_root.as_deref());
+            if let Some(line_items) = line_items {
+                items::assign_enclosing(findings, &file, |line| line_items.enclosing(line));
+            }
+        }
+    }
+
+    /// Helper function to check that a finding passes the severity, confidence and changed lines
+    /// filters
+    fn is_reported(&self, finding: &Finding) -> bool {
+        self.filtered_by(finding).is_none() && self.in_changed_lines(finding)
+    }
+
+    /// Helper function to return the filter that removes a finding, with its reason: the ignored
+    /// severities, then the minimum confidence
+    fn filtered_by(&self, finding: &Finding) -> Option<(SuppressedBy, String)> {
+        // Filter findings by severity
+        if self.options.ignore_severities.contains(&finding.severity) {
+            return Some((SuppressedBy::Severity, format!("Severity {} is ignored", finding.severity.as_str())));
+        }
+
+        // Filter findings by confidence
+        match self.options.min_confidence {
+            Some(min_confidence) if finding.confidence < min_confidence => Some((
+                SuppressedBy::Confidence,
+                format!("Confidence {} is below the minimum ({min_confidence})", finding.confidence),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Helper function to check that a finding is in the changed lines (files are still analyzed
+    /// as a whole)
+    fn in_changed_lines(&self, finding: &Finding) -> bool {
+        match &self.options.changed_lines {
+            Some(changed_lines) => changed_lines.intersects(&finding.location),
+            None => true,
+        }
+    }
+
+    /// Helper function to send the reported findings outside of the test code to the sink
+    fn stream_findings(&self, findings: &[Finding], test_code: &scope::TestCode) {
+        if let Some(sink) = &self.finding_sink {
+            findings
+                .iter()
+                .filter(|finding| self.is_reported(finding) && !test_code.contains(&finding.location))
+                .for_each(sink);
+        }
+    }
+
+    /// Helper function to merge the duplicate findings, unless they are kept
+    fn merge_duplicates(&self, findings: Vec<Finding>) -> Vec<Finding> {
+        if self.options.keep_duplicate_findings {
+            return findings;
+        }
+        dedup::merge_findings(findings)
+    }
+
+    /// Helper function to add the test code of the files, none if tests are included
+    fn add_test_code<'a>(
+        &self,
+        test_code: &mut scope::TestCode,
+        files: impl IntoIterator<Item = (&'a str, &'a File)>,
+        program: &dsl::ProgramContext,
+    ) {
+        if !self.options.include_tests {
+            for (file_path, ast) in files {
+                let test_module = program.module_of(file_path).is_some_and(|module| module.test);
+                test_code.add_file(file_path, ast, test_module, self.options.project_root.as_deref());
+            }
+        }
+    }
 
-        // Read source code for precise locations
-        let source_code = std::fs::read_to_string(file_path)
-            .with_context(|| format!("Failed to read source code from {file_path}"))?;
+    /// Helper function to get the crate of a file in the analyzed workspace
+    fn crate_of(&self, file: &Path) -> Option<&workspace::CrateManifest> {
+        self.options.workspace.as_ref().and_then(|workspace| workspace.crate_of(file))
+    }
 
+    /// Helper function to group the files by crate of the workspace, in the order of their first
+    /// file (a single group without manifest if there is no workspace)
+    ///
+    /// The files are not parsed yet; their lines of code are recorded in `lines_of_code`.
+    fn group_by_crate(&self, files: impl IntoIterator<Item = SourceText>, lines_of_code: &mut BTreeMap<String, usize>) -> Vec<CrateFiles<'_>> {
+        let mut groups: Vec<CrateFiles<'_>> = Vec::new();
+        for SourceText { path, source } in files {
+            lines_of_code.insert(path.to_string_lossy().to_string(), parser::count_lines_of_code(&source));
+            let manifest = self.crate_of(&path);
+            let text = (path, source);
+            match groups.iter_mut().find(|(group_manifest, _)| *group_manifest == manifest) {
+                Some((_, group)) => group.push(text),
+                None => groups.push((manifest, vec![text])),
+            }
+        }
+        groups
+    }
+
+    /// Helper function to execute the per-file rules, removing the suppressed findings
+    fn execute_file_rules(
+        &self,
+        file_path: &str,
+        ast: &File,
+        source_code: &str,
+        program: &dsl::ProgramContext,
+        uses_anchor: bool,
+        suppressions: &mut Suppressions,
+    ) -> Vec<Finding> {
         // Execute rules on the AST with source code for precise locations
-        let findings = self
-            .rule_engine
-            .execute_rules(ast, file_path, &source_code)
-            .with_context(|| format!("Failed to execute rules on {file_path}"))?;
+        let rule_findings = self.execute_rules(ast, file_path, source_code, program, uses_anchor);
+        Self::apply_suppressions(file_path, rule_findings, suppressions)
+    }
+
+    /// Helper function to execute the per-file rules, without the Anchor rules if the crate of
+    /// the file does not use Anchor or is a native program, without the native rules if it is not
+    /// one, without the rules of the frameworks it is not written with (Pinocchio, Steel), and
+    /// without the rules for other versions of anchor-lang
+    fn execute_rules(
+        &self,
+        ast: &File,
+        file_path: &str,
+        source_code: &str,
+        program: &dsl::ProgramContext,
+        uses_anchor: bool,
+    ) -> incremental::RuleFindings {
+        let anchor_version = program.anchor_version();
+        let native = program.is_native();
+        let framework = program.framework();
+        self.rule_engine.execute_selected_rules_in_program(ast, file_path, source_code, program, |rule| {
+            let applies = match rule.rule_type() {
+                RuleType::Anchor => uses_anchor && !native,
+                RuleType::Native => native,
+                RuleType::Pinocchio => framework == Some(native::Framework::Pinocchio),
+                RuleType::Steel => framework == Some(native::Framework::Steel),
+                RuleType::Solana | RuleType::General => true,
+            };
+            applies && rule.applies_to_anchor(anchor_version)
+        })
+    }
+
+    /// Helper function to remove the findings of each rule covered by the suppressions of a file
+    fn apply_suppressions(file_path: &str, rule_findings: incremental::RuleFindings, suppressions: &mut Suppressions) -> Vec<Finding> {
+        let findings: Vec<Finding> = rule_findings
+            .into_iter()
+            .flat_map(|(rule_id, findings)| suppressions.apply(&rule_id, findings))
+            .collect();
 
         debug!("Found {} issues in {}", findings.len(), file_path);
 
-        Ok(findings)
+        findings
+    }
+
+    /// Helper function to remove the findings of a rule covered by the suppressions of their file,
+    /// for findings reported outside the per-file analysis (project rules, expanded code)
+    fn suppress_in_sources(
+        sources: &mut [(String, &str, Suppressions)],
+        rule_id: &str,
+        findings: Vec<Finding>,
+    ) -> Vec<Finding> {
+        let mut kept = Vec::new();
+        for finding in findings {
+            match sources.iter_mut().find(|(file_path, ..)| *file_path == finding.location.file) {
+                Some((.., suppressions)) => kept.extend(suppressions.apply(rule_id, vec![finding])),
+                None => kept.push(finding),
+            }
+        }
+        kept
+    }
+
+    /// Helper function to run the per-file rules on macro-expanded code, mapping the findings
+    /// back to the files it was expanded from, among the parsed files of its crate
+    fn analyze_expanded(&self, expanded: &expansion::ExpandedFile, group: &[(String, &str, File)]) -> incremental::RuleFindings {
+        let file_path = expanded.path.to_string_lossy().to_string();
+        let ast = match syn::parse_file(&expanded.source) {
+            Ok(ast) => ast,
+            Err(e) => {
+                warn!("Error analyzing the expanded code {file_path}: {e}");
+                return Vec::new();
+            }
+        };
+        let manifest = expanded.originals.first().and_then(|original| self.crate_of(original));
+        let program = dsl::ProgramContext::build([(file_path.as_str(), &ast)])
+            .with_tests(self.options.include_tests)
+            .with_manifest(manifest.cloned());
+        let originals: Vec<(&Path, &File)> = group
+            .iter()
+            .map(|(file_path, _, ast)| (Path::new(file_path), ast))
+            .filter(|(path, _)| expanded.originals.iter().any(|original| original == path))
+            .collect();
+
+        debug!("Analyzing the expanded code {file_path}");
+        self.rule_engine
+            .execute_rules_in_program(&ast, &file_path, &expanded.source, &program)
+            .into_iter()
+            .map(|(rule_id, findings)| (rule_id, expansion::map_findings(findings, &ast, &originals)))
+            .collect()
+    }
+
+    /// Helper function to report the unused suppressions of a file, unless they are ignored
+    fn unused_suppressions(&self, file_path: &str, source_code: &str, suppressions: &Suppressions) -> Vec<Finding> {
+        if self.options.ignore_rules.iter().any(|id| id == UNUSED_SUPPRESSION_ID) {
+            return Vec::new();
+        }
+        suppressions.unused_findings(file_path, source_code, |rule_id| self.rule_engine.has_rule(rule_id))
+    }
+
+    /// Helper function to run the per-file rules on every file, on `jobs` threads
+    ///
+    /// Files are given with their source code, their AST and whether their crate uses Anchor.
+    /// The program context indexes the declarations of every file and is shared by all threads.
+    /// Files whose source cannot be parsed give `None`.
+    ///
+    /// On more than one thread, each file whose results are not cached is parsed a second time,
+    /// by the thread analyzing it: syn ASTs are neither `Send` nor `Sync`, and the calling thread
+    /// keeps its own ASTs for the program context, the project rules and the enclosing items.
+    fn analyze_files_parallel(
+        &self,
+        files: &[(&Path, &str, bool)],
+        asts: &[&File],
+        program: &dsl::ProgramContext,
+        test_code: &scope::TestCode,
+        counter: &progress::FileCounter<'_>,
+    ) -> Result<Vec<Option<FileAnalysis>>> {
+        let program_key = self.cache.as_ref().map(|_| incremental::program_key(program)).unwrap_or_default();
+        let analyze = |&(path, source_code, uses_anchor): &(&Path, &str, bool), ast: Option<&File>| -> Option<FileAnalysis> {
+            let file_path = path.to_string_lossy().to_string();
+            // The Anchor rules only run on the files of Anchor crates
+            let cache_key = format!("{program_key}:{uses_anchor}");
+            let result = match self.cache.as_ref().and_then(|cache| cache.load(&file_path, source_code, &cache_key)) {
+                Some(rule_findings) => {
+                    debug!("Reusing the cached results of {file_path}");
+                    Ok((rule_findings, true))
+                }
+                None => {
+                    let parsed = match ast {
+                        Some(ast) => Ok(std::borrow::Cow::Borrowed(ast)),
+                        None => syn::parse_file(source_code).map(std::borrow::Cow::Owned),
+                    };
+                    parsed.map(|ast| {
+                        let rule_findings = self.execute_rules(&ast, &file_path, source_code, program, uses_anchor);
+                        // Results missing the findings of a rule over its time budget are not cached
+                        if let Some(cache) = &self.cache
+                            && !self.rule_engine.timed_out_on(&file_path)
+                        {
+                            cache.store(&file_path, source_code, &cache_key, &rule_findings);
+                        }
+                        (rule_findings, false)
+                    })
+                }
+            };
+            let (rule_findings, cached) = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    warn!("Error analyzing {file_path}: {e}");
+                    counter.analyzed(path);
+                    return None;
+                }
+            };
+            let mut suppressions = Suppressions::parse(source_code, &file_path);
+            let findings = Self::apply_suppressions(&file_path, rule_findings, &mut suppressions);
+            self.stream_findings(&findings, test_code);
+            counter.analyzed(path);
+            Some(FileAnalysis {
+                file_path,
+                suppressions,
+                findings,
+                cached,
+            })
+        };
+
+        // Without the `native` feature, files are analyzed on the calling thread
+        #[cfg(feature = "native")]
+        if self.options.jobs != 1 && files.len() >= 2 {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(self.options.jobs).build()?;
+            debug!("Analyzing {} files on {} threads", files.len(), pool.current_num_threads());
+            return Ok(pool.install(|| files.par_iter().map(|file| analyze(file, None)).collect()));
+        }
+
+        Ok(files.iter().zip(asts).map(|(file, ast)| analyze(file, Some(ast))).collect())
     }
 
     /// Analyzes multiple Rust files
-    pub fn analyze_files(&self, files: &[(std::path::PathBuf, File)]) -> Result<AnalysisResult> {
-        info!("Starting analysis of {} files", files.len());
+    ///
+    /// Files are analyzed in parallel (see [`AnalysisOptions::jobs`]), project-level rules run
+    /// once every file has been analyzed. Files are never read again: locations, fixes and
+    /// suppressions all come from the source code each file was parsed from.
+    ///
+    /// Files can be streamed (e.g. from [`crate::ast::parser::walk_directory_excluding`]): only
+    /// their source code is kept while they are consumed. Each file is parsed with the other
+    /// files of its crate, and the ASTs of a single crate of the workspace are held at a time.
+    /// Files given parsed ([`SourceFile`]) are parsed again from their source code, and so are
+    /// the files analyzed on other threads (see [`AnalysisOptions::jobs`]).
+    pub fn analyze_files<I>(&self, files: I) -> Result<AnalysisResult>
+    where
+        I: IntoIterator,
+        I::Item: Into<SourceText>,
+    {
+        let mut lines_of_code = BTreeMap::new();
+        let mut read = 0;
+        let crates = self.group_by_crate(
+            files.into_iter().map(Into::into).inspect(|file| {
+                read += 1;
+                if let Some(sink) = &self.progress_sink {
+                    sink(progress::Progress::Read { file: &file.path, count: read });
+                }
+            }),
+            &mut lines_of_code,
+        );
+        let total = crates.iter().map(|(_, group)| group.len()).sum::<usize>();
+        debug!("Starting analysis of {total} files");
+        let counter = progress::FileCounter::new(self.progress_sink.as_ref(), total);
 
-        let start_time = std::time::Instant::now();
+        let start_time = engine::now();
         let mut stats = AnalysisStats::default();
-        stats.files_analyzed = files.len();
 
         let mut all_findings = Vec::new();
+        // Findings removed by the severity and confidence filters, kept for the reports
+        let mut filtered = Vec::new();
+        // Findings are streamed as they are recorded, leaving out the test code, except the findings
+        // of the files, which the threads analyzing them stream (`None`)
+        let mut record_findings = |findings: Vec<Finding>, test_code: Option<&scope::TestCode>| {
+            let (findings, removed): (Vec<Finding>, Vec<Finding>) =
+                findings.into_iter().partition(|finding| self.is_reported(finding));
+            filtered.extend(removed.into_iter().filter(|finding| self.in_changed_lines(finding)).filter_map(|finding| {
+                let (suppressed_by, reason) = self.filtered_by(&finding)?;
+                let rule_id = finding.rule().map(|rule| rule.id.clone()).unwrap_or_default();
+                Some(SuppressedFinding { rule_id, suppressed_by, reason: Some(reason), finding })
+            }));
+            if let Some(test_code) = test_code {
+                self.stream_findings(&findings, test_code);
+            }
+            all_findings.extend(findings);
+        };
 
-        for (path, ast) in files {
-            let file_path = path.to_string_lossy().to_string();
-            match self.analyze_file(&file_path, ast) {
-                Ok(mut findings) => {
-                    // Filter findings by severity
-                    findings.retain(|f| !self.options.ignore_severities.contains(&f.severity));
-
-                    // Update statistics
-                    for finding in &findings {
-                        *stats
-                            .findings_by_severity
-                            .entry(finding.severity.clone())
-                            .or_insert(0) += 1;
+        let mut test_code = scope::TestCode::default();
+
+        // Files whose source could be parsed, kept for the project-level rules and with their
+        // suppressions, reported once every rule has run
+        let mut sources = Vec::new();
+        // Items containing the lines of the findings of each analyzed file, found before its AST
+        // is dropped, for the fingerprints and the enclosing items of the findings
+        let mut line_items: HashMap<String, LineItems> = HashMap::new();
+
+        // Each crate of the workspace is analyzed as its own program, with the facts of its manifest
+        let mut anchor_crates = scope::AnchorCrates::default();
+        for (manifest, texts) in &crates {
+            if let Some(manifest) = manifest {
+                debug!("Analyzing the {} files of {}", texts.len(), manifest.name);
+            }
+
+            // The ASTs of the crate are dropped once it is analyzed; files that could not be parsed
+            // are reported instead of being analyzed
+            let mut group: Vec<(String, &str, File)> = Vec::new();
+            for (path, source_code) in texts {
+                match syn::parse_file(source_code) {
+                    Ok(ast) => group.push((path.to_string_lossy().to_string(), source_code.as_str(), ast)),
+                    Err(e) => {
+                        let error = ParseError::from(&e);
+                        warn!("Failed to parse file {}: {}", path.display(), error.message);
+                        stats.files_unparsed += 1;
+                        record_findings(self.parse_error_finding(path, source_code, &error).into_iter().collect(), Some(&test_code));
+                        stats.parse_errors.insert(path.to_string_lossy().to_string(), error);
+                        counter.analyzed(path);
                     }
+                }
+            }
+
+            // Index the declarations of every file so rules can resolve types declared in other files
+            let program = dsl::ProgramContext::build(group.iter().map(|(file_path, _, ast)| (file_path.as_str(), ast)))
+                .with_tests(self.options.include_tests)
+                .with_manifest(manifest.cloned());
 
-                    all_findings.extend(findings);
+            // Test files are not analyzed, unless tests are included
+            self.add_test_code(&mut test_code, group.iter().map(|(file_path, _, ast)| (file_path.as_str(), ast)), &program);
+            if self.options.inventory {
+                let program_name = manifest.map(|manifest| manifest.name.clone());
+                stats.inventory.push(inventory::ProgramInventory::of(program_name, &program, |declaration| {
+                    let location = Location::new_precise(declaration.file.clone(), declaration.line, None, None, None);
+                    !test_code.contains(&location)
+                }));
+            }
+            if self.options.pdas {
+                let program_name = manifest.map(|manifest| manifest.name.clone());
+                stats.pdas.push(pdas::ProgramPdas::of(program_name, &program, |file, line| {
+                    !test_code.contains(&Location::new_precise(file.to_string(), line, None, None, None))
+                }));
+            }
+            if self.options.cpi_graph {
+                let program_name = manifest.map(|manifest| manifest.name.clone());
+                let files = group.iter().map(|(file_path, _, ast)| (file_path.as_str(), ast));
+                stats.cpis.push(cpi::ProgramCpis::build(program_name, files, &program, |file, line| {
+                    !test_code.contains(&Location::new_precise(file.to_string(), line, None, None, None))
+                }));
+            }
+            let analyzed: Vec<&(String, &str, File)> = group
+                .iter()
+                .filter(|(file_path, ..)| !test_code.is_test_file(file_path))
+                .collect();
+            stats.files_analyzed += analyzed.len();
+            stats.test_files_skipped += group.len() - analyzed.len();
+            group
+                .iter()
+                .filter(|(file_path, ..)| test_code.is_test_file(file_path))
+                .for_each(|(file_path, ..)| counter.analyzed(Path::new(file_path)));
+            for (file_path, ..) in &analyzed {
+                if let Some(lines) = lines_of_code.get(file_path) {
+                    stats.lines_of_code.insert(file_path.clone(), *lines);
                 }
-                Err(e) => {
-                    warn!("Error analyzing {file_path}: {e}");
+            }
+
+            // Results are collected in the order of the files, whatever the number of jobs
+            let targets: Vec<(&Path, &str, bool)> = analyzed
+                .iter()
+                .map(|(file_path, source_code, _)| {
+                    let path = Path::new(file_path);
+                    (path, *source_code, anchor_crates.uses_anchor(&*self.options.file_system, path))
+                })
+                .collect();
+            let asts: Vec<&File> = analyzed.iter().map(|(_, _, ast)| ast).collect();
+            let file_results = self.analyze_files_parallel(&targets, &asts, &program, &test_code, &counter)?;
+            let program_sources = sources.len();
+            let mut program_asts = Vec::new();
+            // Lines of the findings of the crate, by file
+            let mut finding_lines: HashMap<String, BTreeSet<usize>> = HashMap::new();
+            let mut note_lines = |findings: &[Finding]| {
+                for finding in findings {
+                    finding_lines.entry(finding.location.file.clone()).or_default().insert(finding.location.line);
+                }
+            };
+            for ((_, source_code, ast), result) in analyzed.iter().zip(file_results) {
+                if let Some(analysis) = result {
+                    note_lines(&analysis.findings);
+                    record_findings(analysis.findings, None);
+                    stats.files_cached += usize::from(analysis.cached);
+                    sources.push((analysis.file_path, *source_code, analysis.suppressions));
+                    program_asts.push(ast);
+                }
+            }
+
+            // Rules matching relationships across files run once on each program
+            if self.rule_engine.has_project_rules() {
+                let program_sources = &mut sources[program_sources..];
+                let project_files: Vec<dsl::ProjectFile<'_>> = program_sources
+                    .iter()
+                    .zip(&program_asts)
+                    .map(|((file_path, source_code, _), ast)| dsl::ProjectFile::new(file_path, ast, source_code))
+                    .collect();
+                let anchor_version = program.anchor_version();
+                let rule_findings = self
+                    .rule_engine
+                    .execute_selected_project_rules_per_rule(&project_files, |rule| rule.applies_to_anchor(anchor_version));
+
+                for (rule_id, findings) in rule_findings {
+                    let findings = Self::suppress_in_sources(program_sources, &rule_id, findings);
+                    note_lines(&findings);
+                    record_findings(findings, Some(&test_code));
+                }
+            }
+
+            // Instructions and accounts of the IDL built for the program that no longer match the code
+            if let (Some(workspace), Some(manifest)) = (&self.options.workspace, manifest)
+                && !self.options.ignore_rules.iter().any(|ignored| ignored == idl::IDL_DRIFT_ID)
+            {
+                let program_sources = &mut sources[program_sources..];
+                let idl_files: Vec<(&str, &File, &str)> = program_sources
+                    .iter()
+                    .zip(&program_asts)
+                    .map(|((file_path, source_code, _), ast)| (file_path.as_str(), *ast, *source_code))
+                    .collect();
+                let findings = idl::check(&*self.options.file_system, &workspace.root, manifest, &idl_files);
+                let findings = Self::suppress_in_sources(program_sources, idl::IDL_DRIFT_ID, findings);
+                note_lines(&findings);
+                record_findings(findings, Some(&test_code));
+            }
+
+            // Macro-expanded code of the crate, whose findings are mapped back to its files
+            let expanded_files = self
+                .options
+                .expanded_files
+                .iter()
+                .filter(|expanded| expanded.originals.first().and_then(|original| self.crate_of(original)) == *manifest);
+            for expanded in expanded_files {
+                for (rule_id, findings) in self.analyze_expanded(expanded, &group) {
+                    let findings = Self::suppress_in_sources(&mut sources[program_sources..], &rule_id, findings);
+                    stats.findings_expanded += findings.len();
+                    note_lines(&findings);
+                    record_findings(findings, Some(&test_code));
                 }
             }
+
+            // Suppressions of the crate no rule used, once every rule has run on its files
+            for (file_path, source_code, suppressions) in &sources[program_sources..] {
+                let findings = self.unused_suppressions(file_path, source_code, suppressions);
+                note_lines(&findings);
+                note_lines(&suppressions.suppressed().iter().map(|suppressed| suppressed.finding.clone()).collect::<Vec<_>>());
+                record_findings(findings, Some(&test_code));
+            }
+
+            // The items on the lines of the findings outlive the ASTs of the crate
+            for (file_path, _, ast) in &group {
+                let file_module = program.module_of(file_path).map(items::module_path).unwrap_or_default();
+                let lines = finding_lines.remove(file_path).unwrap_or_default();
+                line_items.insert(file_path.clone(), LineItems::of(ast, &file_module, lines));
+            }
+        }
+        if stats.test_files_skipped > 0 {
+            debug!("Skipping {} test files", stats.test_files_skipped);
+        }
+        if stats.files_unparsed > 0 {
+            warn!("Skipped {} files that could not be parsed", stats.files_unparsed);
+        }
+
+        // Programs built without overflow checks, reported on the release profile of the workspace,
+        // and dependencies affected by an advisory, reported on their version
+        if let Some(workspace) = &self.options.workspace {
+            let ignored = |id: &str| self.options.ignore_rules.iter().any(|ignored| ignored == id);
+            if !ignored(workspace::OVERFLOW_CHECKS_ID) {
+                record_findings(workspace.manifest_findings(), Some(&test_code));
+            }
+            if !ignored(advisories::ADVISORY_ID) {
+                record_findings(self.options.advisories.check(workspace), Some(&test_code));
+            }
+        }
+
+        let mut suppressed: Vec<SuppressedFinding> = sources
+            .iter()
+            .flat_map(|(.., suppressions)| suppressions.suppressed().iter().cloned())
+            .filter(|suppressed| self.in_changed_lines(&suppressed.finding))
+            .chain(filtered)
+            .collect();
+
+        // Findings in inline test code (`#[cfg(test)]` modules, `#[test]` functions)
+        all_findings.retain(|finding| !test_code.contains(&finding.location));
+        suppressed.retain(|suppressed| !test_code.contains(&suppressed.finding.location));
+
+        // Findings are sorted by location and rule, whatever the order of the files and the number
+        // of jobs, so that merging, fingerprints, caps and reports are the same from run to run
+        all_findings.sort_by(results::compare_location);
+
+        // Merge the findings reported on the same code by overlapping rules
+        let reported = all_findings.len();
+        let mut all_findings = self.merge_duplicates(all_findings);
+        stats.findings_merged = reported - all_findings.len();
+        // A merged finding takes the rule of its most severe finding
+        all_findings.sort_by(results::compare_location);
+        let items_of = |file: &str| line_items.get(file);
+        self.annotate_findings(&mut all_findings, items_of);
+
+        // The suppressed findings are fingerprinted like the reported ones, so they can be tracked
+        suppressed.sort_by(|a, b| results::compare_location(&a.finding, &b.finding));
+        let mut suppressed_findings: Vec<Finding> =
+            suppressed.iter().map(|suppressed| suppressed.finding.clone()).collect();
+        self.annotate_findings(&mut suppressed_findings, items_of);
+        for (suppressed, finding) in suppressed.iter_mut().zip(suppressed_findings) {
+            suppressed.finding = finding;
+        }
+        stats.findings_suppressed = suppressed.len();
+        stats.suppressed_findings = suppressed;
+
+        // Findings over the caps, once fingerprinted so the fingerprints do not depend on the caps
+        stats.findings_omitted = self.options.finding_caps.apply(&mut all_findings);
+
+        // Source lines around the reported findings
+        self.add_context(&mut all_findings, |file| {
+            sources.iter().find(|(file_path, ..)| file_path == file).map(|(_, source_code, _)| *source_code)
+        });
+
+        // Group the findings by crate of the workspace
+        for finding in &mut all_findings {
+            let program = match finding.metadata.get(workspace::PROGRAM_KEY) {
+                Some(dsl::query::MetadataValue::Text(program)) => Some(program.clone()),
+                _ => self.crate_of(Path::new(&finding.location.file)).map(|manifest| manifest.name.clone()),
+            };
+            if let Some(program) = program {
+                *stats.findings_by_program.entry(program.clone()).or_insert(0) += 1;
+                finding.metadata.insert(workspace::PROGRAM_KEY.to_string(), dsl::query::MetadataValue::Text(program));
+            }
+        }
+
+        // Update statistics
+        for finding in &all_findings {
+            *stats
+                .findings_by_severity
+                .entry(finding.severity.clone())
+                .or_insert(0) += 1;
+            *stats.findings_by_file.entry(finding.location.file.clone()).or_insert(0) += 1;
+            if let Some(rule) = finding.rule() {
+                *stats.findings_by_rule.entry(rule.id.clone()).or_insert(0) += 1;
+            }
         }
 
-        stats.total_time_ms = u64::try_from(start_time.elapsed().as_millis())?;
+        stats.rules_executed = self.rule_engine.rule_count();
+        stats.rule_types =
+            crate::analyzer::stats::rule_type_coverage(self.rule_engine.registry(), &stats.findings_by_rule);
+        stats.timed_out_rules = self.rule_engine.take_timed_out_rules();
+        stats.rule_profiles = self.rule_engine.take_rule_profiles();
+        stats.total_time_ms = u64::try_from(start_time.map_or(0, |start_time| start_time.elapsed().as_millis()))?;
 
-        info!(
+        debug!(
             "Analysis completed: {} findings in {}ms",
             all_findings.len(),
             stats.total_time_ms
@@ -0,0 +1,466 @@
+use quote::ToTokens;
+use syn::visit::{self, Visit};
+use syn::{Block, File, Item, Signature};
+
+use crate::analyzer::dsl::ProgramContext;
+use crate::analyzer::dsl::call_graph::{Check, FunctionSummary};
+use crate::analyzer::dsl::query::{AstNode, NodeData};
+use crate::analyzer::workspace::CrateManifest;
+
+#[cfg(test)]
+mod test;
+
+/// Macros declaring the entrypoint of a native program (solana-program, pinocchio and steel)
+pub const ENTRYPOINT_MACROS: &[&str] = &["entrypoint", "entrypoint_no_alloc", "program_entrypoint", "lazy_program_entrypoint"];
+
+/// Calls writing the accounts of a native program: data and lamports borrowed mutably, account
+/// data serialized back or loaded mutably (steel), and cross-program invocations
+pub const ACCOUNT_WRITES: &[&str] = &[
+    "invoke",
+    "invoke_signed",
+    "try_borrow_mut_data",
+    "try_borrow_mut_lamports",
+    "borrow_mut",
+    "borrow_mut_data_unchecked",
+    "borrow_mut_lamports_unchecked",
+    "serialize",
+    "pack",
+    "pack_into_slice",
+    "as_account_mut",
+    "to_account_mut",
+];
+
+/// Calls deserializing data in a native program; they read an account when their argument
+/// borrows its data (`Vault::try_from_slice(&vault.data.borrow())`, `load(&vault.try_borrow_data()?)`)
+pub const ACCOUNT_READS: &[&str] = &[
+    "try_from_slice",
+    "deserialize",
+    "unpack",
+    "unpack_unchecked",
+    "unpack_from_slice",
+    "from_bytes",
+    "try_from_bytes",
+    "load",
+    "load_mut",
+];
+
+/// Fields and methods checking that an account signed (`is_signer` field of solana-program,
+/// `is_signer()` of pinocchio and steel)
+pub const SIGNER_CHECKS: &[&str] = &["is_signer"];
+
+/// Fields and methods checking the owner of an account: the `owner` field, pinocchio's `owner()`
+/// and `is_owned_by()`, and steel's `has_owner()` and loaders checking the owner (`as_account`)
+pub const OWNER_CHECKS: &[&str] = &[
+    "owner",
+    "is_owned_by",
+    "has_owner",
+    "as_account",
+    "as_account_mut",
+    "to_account",
+    "to_account_mut",
+];
+
+/// Methods checking the address of a program account before invoking it (steel)
+pub const PROGRAM_CHECKS: &[&str] = &["is_program", "has_address"];
+
+/// Constructors of instructions taking the program ID first (`Instruction::new_with_bytes(*program.key, ..)`)
+pub const INSTRUCTION_CONSTRUCTORS: &[&str] = &["new_with_bytes", "new_with_borsh", "new_with_bincode"];
+
+/// Framework a native program is written with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Framework {
+    /// solana-program, without a framework
+    Native,
+    /// Pinocchio, zero-copy programs without solana-program
+    Pinocchio,
+    /// Steel, account and instruction macros on top of solana-program
+    Steel,
+}
+
+impl Framework {
+    /// Frameworks recognized from the dependencies and imports of a program, besides `Native`
+    pub const FRAMEWORKS: &[Framework] = &[Framework::Pinocchio, Framework::Steel];
+
+    /// Name of the crate of the framework
+    pub const fn crate_name(self) -> &'static str {
+        match self {
+            Framework::Native => "solana-program",
+            Framework::Pinocchio => "pinocchio",
+            Framework::Steel => "steel",
+        }
+    }
+
+    /// Returns the framework a crate depends on in its `Cargo.toml`, besides `Native`
+    pub fn of_manifest(manifest: &CrateManifest) -> Option<Framework> {
+        Self::FRAMEWORKS
+            .iter()
+            .copied()
+            .find(|framework| manifest.dependencies.contains_key(framework.crate_name()))
+    }
+}
+
+impl std::fmt::Display for Framework {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.crate_name())
+    }
+}
+
+/// Returns the framework a file imports (`use pinocchio::..`, `use steel::*`), if any
+pub fn imported_framework(ast: &File) -> Option<Framework> {
+    fn root(tree: &syn::UseTree) -> Vec<String> {
+        match tree {
+            syn::UseTree::Path(path) => vec![path.ident.to_string()],
+            syn::UseTree::Name(name) => vec![name.ident.to_string()],
+            syn::UseTree::Rename(rename) => vec![rename.ident.to_string()],
+            syn::UseTree::Group(group) => group.items.iter().flat_map(root).collect(),
+            syn::UseTree::Glob(_) => Vec::new(),
+        }
+    }
+    let roots: Vec<String> = ast
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Use(item_use) => Some(root(&item_use.tree)),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    Framework::FRAMEWORKS
+        .iter()
+        .copied()
+        .find(|framework| roots.iter().any(|root| root == framework.crate_name()))
+}
+
+/// Returns the processors a file declares as native entrypoints: the functions given to
+/// `entrypoint!` and the functions with the `process_instruction` signature
+/// (`fn(&Pubkey, &[AccountInfo], &[u8])`), including those of inline modules
+pub fn entrypoints(ast: &File) -> Vec<String> {
+    let mut entrypoints = Vec::new();
+    collect_entrypoints(&ast.items, &mut entrypoints);
+    entrypoints
+}
+
+/// Helper function to collect the entrypoints of a module, recursively
+fn collect_entrypoints(items: &[Item], entrypoints: &mut Vec<String>) {
+    for item in items {
+        match item {
+            Item::Macro(item_macro) => {
+                let is_entrypoint = item_macro
+                    .mac
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|segment| ENTRYPOINT_MACROS.contains(&segment.ident.to_string().as_str()));
+                // `entrypoint!(process_instruction)`, the processor is the first argument
+                let processor = item_macro.mac.tokens.clone().into_iter().next().map(|token| token.to_string());
+                if is_entrypoint && let Some(processor) = processor {
+                    push_unique(entrypoints, processor);
+                }
+            }
+            Item::Fn(func) if is_entrypoint_signature(&func.sig) => push_unique(entrypoints, func.sig.ident.to_string()),
+            Item::Mod(module) => {
+                if let Some((_, items)) = &module.content {
+                    collect_entrypoints(items, entrypoints);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Helper function to add a name once
+fn push_unique(names: &mut Vec<String>, name: String) {
+    if !names.contains(&name) {
+        names.push(name);
+    }
+}
+
+/// Check if a file declares an Anchor program (a `#[program]` module)
+pub fn declares_anchor_program(ast: &File) -> bool {
+    fn in_items(items: &[Item]) -> bool {
+        items.iter().any(|item| match item {
+            Item::Mod(module) => {
+                module.attrs.iter().any(|attr| attr.path().is_ident("program"))
+                    || module.content.as_ref().is_some_and(|(_, items)| in_items(items))
+            }
+            _ => false,
+        })
+    }
+    in_items(&ast.items)
+}
+
+/// Check if a function has the signature of a native entrypoint: `(&Pubkey, &[AccountInfo], &[u8])`
+pub fn is_entrypoint_signature(sig: &Signature) -> bool {
+    let types: Vec<String> = param_types(sig).collect();
+    types.len() == 3 && types[0].ends_with("Pubkey") && is_account_slice(&types[1]) && is_byte_slice(&types[2])
+}
+
+/// Returns the name of the `&[AccountInfo]` parameter of a native processor, if the function is one
+pub fn accounts_param(sig: &Signature) -> Option<String> {
+    typed_params(sig).find(|(_, ty)| is_account_slice(ty)).map(|(name, _)| name)
+}
+
+/// Returns the names of the `&[u8]` parameters of a function (instruction data)
+pub fn byte_slice_params(sig: &Signature) -> Vec<String> {
+    typed_params(sig).filter(|(_, ty)| is_byte_slice(ty)).map(|(name, _)| name).collect()
+}
+
+/// Returns the signature and body of a function node
+pub fn function_parts<'a>(node: &AstNode<'a>) -> Option<(&'a Signature, &'a Block)> {
+    match &node.data {
+        NodeData::Function(func) => Some((&func.sig, &func.block)),
+        NodeData::ImplFunction(func) => Some((&func.sig, &func.block)),
+        _ => None,
+    }
+}
+
+/// Returns the checks of a function and of the helpers it calls, from the call graph of the
+/// program (see [`crate::analyzer::dsl::call_graph::ProgramCallGraph::checks_from`])
+pub fn checks_of(program: &ProgramContext, file_path: &str, sig: &Signature) -> Vec<Check> {
+    let line = sig.ident.span().start().line;
+    program
+        .call_graph()
+        .function(&sig.ident.to_string())
+        .iter()
+        .find(|function: &&FunctionSummary| function.file == file_path && function.line == line)
+        .map(|function| program.call_graph().checks_from(function))
+        .unwrap_or_default()
+}
+
+/// Check if a function checks a condition mentioning `pattern` (`is_signer`, `data.len()`), itself
+/// or in the helpers it calls
+pub fn checks_mention(program: &ProgramContext, file_path: &str, sig: &Signature, pattern: &str) -> bool {
+    checks_of(program, file_path, sig).iter().any(|check| {
+        check.condition.match_indices(pattern).any(|(index, _)| {
+            !check.condition[..index].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+        })
+    })
+}
+
+/// Check if a block deserializes the data of an account with one of [`ACCOUNT_READS`]
+pub fn deserializes_account_data(block: &Block) -> bool {
+    let mut finder = IdentFinder::default();
+    finder.visit_block(block);
+    finder.account_reads > 0
+}
+
+/// Returns the names of the functions and methods a block calls directly
+pub fn called_names(block: &Block) -> Vec<String> {
+    let mut finder = IdentFinder::default();
+    finder.visit_block(block);
+    finder.calls
+}
+
+/// Check if a block reads one of the given fields or calls one of the given methods, e.g.
+/// [`SIGNER_CHECKS`] (`authority.is_signer`, `authority.is_signer()?`)
+pub fn uses_any(block: &Block, names: &[&str]) -> bool {
+    let mut finder = IdentFinder::default();
+    finder.visit_block(block);
+    finder.fields.iter().chain(&finder.calls).any(|name| names.contains(&name.as_str()))
+}
+
+/// Returns the accounts whose key is the program ID of an instruction built in a block
+/// (`Instruction { program_id: *program.key, .. }`, `Instruction::new_with_bytes(*program.key(), ..)`)
+pub fn cpi_program_accounts(block: &Block) -> Vec<String> {
+    let mut finder = ProgramIdFinder::default();
+    finder.visit_block(block);
+    finder.accounts
+}
+
+/// Returns the methods a block calls on a variable (`program.is_program(&spl_token::ID)?`)
+pub fn methods_called_on(block: &Block, variable: &str) -> Vec<String> {
+    let mut finder = IdentFinder::default();
+    finder.visit_block(block);
+    finder
+        .receivers
+        .into_iter()
+        .filter(|(receiver, _)| receiver == variable)
+        .map(|(_, method)| method)
+        .collect()
+}
+
+/// Returns the accounts a block binds by destructuring the accounts slice
+/// (`let [payer, vault, system_program, ..] = accounts else { .. }`)
+pub fn destructured_accounts(block: &Block, accounts: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for stmt in &block.stmts {
+        if let syn::Stmt::Local(local) = stmt
+            && let syn::Pat::Slice(slice) = &local.pat
+            && local.init.as_ref().and_then(|init| variable_name(&init.expr)).as_deref() == Some(accounts)
+        {
+            for elem in &slice.elems {
+                if let syn::Pat::Ident(pat_ident) = elem
+                    && !pat_ident.ident.to_string().starts_with('_')
+                {
+                    push_unique(&mut names, pat_ident.ident.to_string());
+                }
+            }
+        }
+    }
+    names
+}
+
+/// Returns the index expressions of a block on the given variable (`data[0]`, `accounts[1..3]`)
+pub fn indexes_of<'a>(block: &'a Block, variable: &str) -> Vec<&'a syn::ExprIndex> {
+    let mut finder = IndexFinder {
+        variable,
+        indexes: Vec::new(),
+    };
+    finder.visit_block(block);
+    finder.indexes
+}
+
+/// Helper function to iterate over the types of the typed parameters, without spaces
+fn param_types(sig: &Signature) -> impl Iterator<Item = String> + '_ {
+    typed_params(sig).map(|(_, ty)| ty)
+}
+
+/// Helper function to iterate over the typed parameters (name, type without spaces)
+fn typed_params(sig: &Signature) -> impl Iterator<Item = (String, String)> + '_ {
+    sig.inputs.iter().filter_map(|input| match input {
+        syn::FnArg::Typed(pat_type) => {
+            let name = match &*pat_type.pat {
+                syn::Pat::Ident(pat_ident) => pat_ident.ident.to_string(),
+                _ => "_".to_string(),
+            };
+            Some((name, pat_type.ty.to_token_stream().to_string().replace(' ', "")))
+        }
+        syn::FnArg::Receiver(_) => None,
+    })
+}
+
+/// Helper function to check if a type is a slice of accounts (`&[AccountInfo]`, `&'a [AccountInfo<'a>]`)
+fn is_account_slice(ty: &str) -> bool {
+    ty.starts_with('&') && ty.contains("[AccountInfo") && ty.ends_with(']')
+}
+
+/// Helper function to check if a type is a byte slice (`&[u8]`)
+fn is_byte_slice(ty: &str) -> bool {
+    ty.starts_with('&') && ty.ends_with("[u8]")
+}
+
+/// Helper visitor to collect the called names and the fields read in a block
+#[derive(Default)]
+struct IdentFinder {
+    calls: Vec<String>,
+    fields: Vec<String>,
+    account_reads: usize,
+    /// Methods called on variables (variable, method)
+    receivers: Vec<(String, String)>,
+}
+
+impl<'ast> Visit<'ast> for IdentFinder {
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(path) = &*call.func
+            && let Some(segment) = path.path.segments.last()
+        {
+            let name = segment.ident.to_string();
+            let borrows_data = call.args.iter().any(|arg| arg.to_token_stream().to_string().contains("borrow"));
+            if ACCOUNT_READS.contains(&name.as_str()) && borrows_data {
+                self.account_reads += 1;
+            }
+            self.calls.push(name);
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, method_call: &'ast syn::ExprMethodCall) {
+        if let Some(variable) = variable_name(&method_call.receiver) {
+            self.receivers.push((variable, method_call.method.to_string()));
+        }
+        self.calls.push(method_call.method.to_string());
+        visit::visit_expr_method_call(self, method_call);
+    }
+
+    fn visit_expr_field(&mut self, field: &'ast syn::ExprField) {
+        if let syn::Member::Named(name) = &field.member {
+            self.fields.push(name.to_string());
+        }
+        visit::visit_expr_field(self, field);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        // Calls and fields in macro arguments (`msg!`, `assert!`, `require!`)
+        if let Ok(exprs) = mac.parse_body_with(syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated) {
+            for expr in &exprs {
+                self.visit_expr(expr);
+            }
+        }
+    }
+}
+
+/// Helper function to get the variable an expression reads, through references, dereferences
+/// and parentheses
+fn variable_name(expr: &syn::Expr) -> Option<String> {
+    match expr {
+        syn::Expr::Path(path) => path.path.get_ident().map(|ident| ident.to_string()),
+        syn::Expr::Reference(reference) => variable_name(&reference.expr),
+        syn::Expr::Unary(unary) => variable_name(&unary.expr),
+        syn::Expr::Paren(paren) => variable_name(&paren.expr),
+        _ => None,
+    }
+}
+
+/// Helper function to get the account whose key an expression reads (`*program.key`, `program.key()`)
+pub(crate) fn key_owner(expr: &syn::Expr) -> Option<String> {
+    match expr {
+        syn::Expr::Field(field) if matches!(&field.member, syn::Member::Named(name) if name == "key") => {
+            variable_name(&field.base)
+        }
+        syn::Expr::MethodCall(method_call) if method_call.method == "key" => variable_name(&method_call.receiver),
+        syn::Expr::Reference(reference) => key_owner(&reference.expr),
+        syn::Expr::Unary(unary) => key_owner(&unary.expr),
+        syn::Expr::Paren(paren) => key_owner(&paren.expr),
+        _ => None,
+    }
+}
+
+/// Helper visitor to collect the accounts used as the program of an instruction
+#[derive(Default)]
+struct ProgramIdFinder {
+    accounts: Vec<String>,
+}
+
+impl<'ast> Visit<'ast> for ProgramIdFinder {
+    fn visit_expr_struct(&mut self, expr_struct: &'ast syn::ExprStruct) {
+        for field in &expr_struct.fields {
+            if matches!(&field.member, syn::Member::Named(name) if name == "program_id")
+                && let Some(account) = key_owner(&field.expr)
+            {
+                push_unique(&mut self.accounts, account);
+            }
+        }
+        visit::visit_expr_struct(self, expr_struct);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(path) = &*call.func
+            && path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| INSTRUCTION_CONSTRUCTORS.contains(&segment.ident.to_string().as_str()))
+            && let Some(account) = call.args.first().and_then(key_owner)
+        {
+            push_unique(&mut self.accounts, account);
+        }
+        visit::visit_expr_call(self, call);
+    }
+}
+
+/// Helper visitor to collect the index expressions on a variable
+struct IndexFinder<'a, 'ast> {
+    variable: &'a str,
+    indexes: Vec<&'ast syn::ExprIndex>,
+}
+
+impl<'ast> Visit<'ast> for IndexFinder<'_, 'ast> {
+    fn visit_expr_index(&mut self, index: &'ast syn::ExprIndex) {
+        if let syn::Expr::Path(path) = &*index.expr
+            && path.path.is_ident(self.variable)
+        {
+            self.indexes.push(index);
+        }
+        visit::visit_expr_index(self, index);
+    }
+}
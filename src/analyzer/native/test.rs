@@ -0,0 +1,189 @@
+use syn::{File, ItemFn, parse_quote};
+
+use crate::analyzer::dsl::ProgramContext;
+use crate::analyzer::native::{
+    Framework, SIGNER_CHECKS, accounts_param, byte_slice_params, checks_mention, cpi_program_accounts,
+    declares_anchor_program, deserializes_account_data, destructured_accounts, entrypoints, imported_framework,
+    indexes_of, is_entrypoint_signature, methods_called_on, uses_any,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entrypoint_macro_and_signature() {
+        let ast: File = parse_quote! {
+            use solana_program::entrypoint;
+
+            entrypoint!(process_instruction);
+
+            pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+                Ok(())
+            }
+
+            pub mod processor {
+                pub fn process<'a>(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>], data: &[u8]) -> ProgramResult {
+                    Ok(())
+                }
+            }
+
+            pub fn helper(accounts: &[AccountInfo]) -> ProgramResult {
+                Ok(())
+            }
+        };
+
+        assert_eq!(entrypoints(&ast), vec!["process_instruction", "process"]);
+        assert!(!declares_anchor_program(&ast));
+    }
+
+    #[test]
+    fn test_anchor_program_is_detected() {
+        let ast: File = parse_quote! {
+            #[program]
+            pub mod vault {
+                pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+                    Ok(())
+                }
+            }
+        };
+
+        assert!(declares_anchor_program(&ast));
+        assert!(entrypoints(&ast).is_empty());
+    }
+
+    #[test]
+    fn test_native_program_context() {
+        let native: File = parse_quote! {
+            entrypoint!(process_instruction);
+        };
+        let anchor: File = parse_quote! {
+            #[program]
+            pub mod vault {}
+        };
+
+        assert!(ProgramContext::build([("src/lib.rs", &native)]).is_native());
+        assert!(!ProgramContext::build([("src/lib.rs", &anchor)]).is_native());
+        // Anchor programs also declare an entrypoint through `#[program]`, the module wins
+        assert!(!ProgramContext::build([("src/lib.rs", &native), ("src/vault.rs", &anchor)]).is_native());
+    }
+
+    #[test]
+    fn test_framework_from_imports() {
+        let pinocchio: File = parse_quote! {
+            use pinocchio::{account_info::AccountInfo, entrypoint, pubkey::Pubkey, ProgramResult};
+
+            entrypoint!(process_instruction);
+        };
+        let steel: File = parse_quote! {
+            use steel::*;
+
+            entrypoint!(process_instruction);
+        };
+        let native: File = parse_quote! {
+            use solana_program::entrypoint;
+
+            entrypoint!(process_instruction);
+        };
+
+        assert_eq!(imported_framework(&pinocchio), Some(Framework::Pinocchio));
+        assert_eq!(imported_framework(&steel), Some(Framework::Steel));
+        assert_eq!(imported_framework(&native), None);
+
+        assert_eq!(ProgramContext::build([("src/lib.rs", &steel)]).framework(), Some(Framework::Steel));
+        assert_eq!(ProgramContext::build([("src/lib.rs", &native)]).framework(), Some(Framework::Native));
+        let anchor: File = parse_quote! {
+            #[program]
+            pub mod vault {}
+        };
+        assert_eq!(ProgramContext::build([("src/lib.rs", &anchor)]).framework(), None);
+    }
+
+    #[test]
+    fn test_framework_checks_and_invocations() {
+        let func: ItemFn = parse_quote! {
+            fn process(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+                let [signer_info, vault_info, token_program, _rent] = accounts else {
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                };
+                signer_info.is_signer()?;
+                vault_info.is_writable()?.has_seeds(&[VAULT], &crate::ID)?;
+                let instruction = Instruction {
+                    program_id: *token_program.key,
+                    accounts: vec![],
+                    data: data.to_vec(),
+                };
+                invoke(&instruction, accounts)?;
+                invoke(&Instruction::new_with_bytes(*vault_info.key(), data, vec![]), accounts)
+            }
+        };
+
+        assert!(uses_any(&func.block, SIGNER_CHECKS));
+        assert_eq!(destructured_accounts(&func.block, "accounts"), vec!["signer_info", "vault_info", "token_program"]);
+        assert_eq!(methods_called_on(&func.block, "vault_info"), vec!["is_writable", "key"]);
+        assert!(methods_called_on(&func.block, "token_program").is_empty());
+        assert_eq!(cpi_program_accounts(&func.block), vec!["token_program", "vault_info"]);
+    }
+
+    #[test]
+    fn test_processor_params() {
+        let func: ItemFn = parse_quote! {
+            fn process_withdraw(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8], seed: &[u8], amount: u64) {}
+        };
+
+        assert!(!is_entrypoint_signature(&func.sig));
+        assert_eq!(accounts_param(&func.sig).as_deref(), Some("accounts"));
+        assert_eq!(byte_slice_params(&func.sig), vec!["data", "seed"]);
+    }
+
+    #[test]
+    fn test_indexes_and_account_reads() {
+        let func: ItemFn = parse_quote! {
+            fn process(accounts: &[AccountInfo], data: &[u8]) {
+                let vault = &accounts[0];
+                let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+                let instruction = Instruction::try_from_slice(data)?;
+                let state = Vault::try_from_slice(&vault.data.borrow())?;
+            }
+        };
+
+        assert_eq!(indexes_of(&func.block, "accounts").len(), 1);
+        assert_eq!(indexes_of(&func.block, "data").len(), 1);
+        assert!(indexes_of(&func.block, "vault").is_empty());
+        assert!(deserializes_account_data(&func.block));
+
+        let instruction_only: ItemFn = parse_quote! {
+            fn process(accounts: &[AccountInfo], data: &[u8]) {
+                let instruction = Instruction::try_from_slice(data)?;
+            }
+        };
+        assert!(!deserializes_account_data(&instruction_only.block));
+    }
+
+    #[test]
+    fn test_checks_made_in_helpers() {
+        let lib: File = parse_quote! {
+            fn process_withdraw(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+                let authority = next_account_info(&mut accounts.iter())?;
+                check_signer(authority)?;
+                Ok(())
+            }
+        };
+        let helpers: File = parse_quote! {
+            fn check_signer(account: &AccountInfo) -> ProgramResult {
+                if !account.is_signer {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                Ok(())
+            }
+        };
+        let program = ProgramContext::build([("src/lib.rs", &lib), ("src/helpers.rs", &helpers)]);
+        let Some(syn::Item::Fn(func)) = lib.items.first() else {
+            panic!("expected a function");
+        };
+
+        assert!(checks_mention(&program, "src/lib.rs", &func.sig, "is_signer"));
+        assert!(checks_mention(&program, "src/lib.rs", &func.sig, "authority.is_signer"));
+        assert!(!checks_mention(&program, "src/lib.rs", &func.sig, "data.len()"));
+    }
+}
@@ -0,0 +1,55 @@
+use log::warn;
+use std::fs;
+use std::io::Write;
+use std::sync::{Mutex, PoisonError};
+
+use crate::analyzer::{AnalysisResult, Finding, FindingSink};
+
+#[cfg(test)]
+mod test;
+
+/// Findings of an analysis as newline-delimited JSON (`--format ndjson`), one finding per line
+///
+/// Each line is a finding with the fields of the findings of the JSON report, so log pipelines
+/// and dashboards can ingest them one at a time. With `--stream`, the lines are written while
+/// the files are analyzed instead (see [`stream_to`]).
+#[derive(Debug)]
+pub struct NdjsonReport<'a> {
+    result: &'a AnalysisResult,
+}
+
+impl<'a> NdjsonReport<'a> {
+    /// Creates the report of an analysis
+    pub fn new(result: &'a AnalysisResult) -> Self {
+        Self { result }
+    }
+
+    /// Returns the report as NDJSON
+    pub fn to_ndjson(&self) -> String {
+        self.result.findings.iter().map(finding_line).collect()
+    }
+
+    /// Save the NDJSON report to a file
+    pub fn save(&self, output_path: &str) -> Result<(), std::io::Error> {
+        fs::write(output_path, self.to_ndjson())
+    }
+}
+
+/// Returns a finding as a line of NDJSON, with its newline
+pub fn finding_line(finding: &Finding) -> String {
+    let mut line = serde_json::to_string(finding).expect("findings serialize to JSON");
+    line.push('\n');
+    line
+}
+
+/// Returns a finding sink writing each finding to `writer` as a line of NDJSON, flushed at once
+/// so the readers of the stream get it without delay
+pub fn stream_to<W: Write + Send + 'static>(writer: W) -> FindingSink {
+    let writer = Mutex::new(writer);
+    Box::new(move |finding| {
+        let mut writer = writer.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Err(e) = writer.write_all(finding_line(finding).as_bytes()).and_then(|()| writer.flush()) {
+            warn!("Cannot stream finding: {e}");
+        }
+    })
+}
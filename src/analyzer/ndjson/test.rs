@@ -0,0 +1,64 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use crate::analyzer::ndjson::{NdjsonReport, stream_to};
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Confidence, Finding, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Buffer shared with the sink, which owns its writer
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn finding(line: usize) -> Finding {
+        FindingBuilder::new(format!("Division by zero on line {line}"))
+            .severity(Severity::High)
+            .confidence(Confidence::Medium)
+            .span("src/lib.rs", line, 5, 20)
+            .fingerprint(format!("fingerprint-{line}"))
+            .build()
+    }
+
+    #[test]
+    fn test_ndjson_report() {
+        let result = AnalysisResult { findings: vec![finding(3), finding(9)], stats: AnalysisStats::default() };
+        let ndjson = NdjsonReport::new(&result).to_ndjson();
+
+        let lines: Vec<serde_json::Value> = ndjson.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["description"], "Division by zero on line 3");
+        assert_eq!(lines[0]["severity"], "high");
+        assert_eq!(lines[1]["location"]["line"], 9);
+        assert_eq!(lines[1]["fingerprint"], "fingerprint-9");
+        assert!(ndjson.ends_with("}\n"));
+
+        let empty = AnalysisResult { findings: Vec::new(), stats: AnalysisStats::default() };
+        assert_eq!(NdjsonReport::new(&empty).to_ndjson(), "");
+    }
+
+    #[test]
+    fn test_stream_to() {
+        let buffer = Shared::default();
+        let sink = stream_to(buffer.clone());
+        sink(&finding(3));
+        assert_eq!(String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap().lines().count(), 1);
+        sink(&finding(9));
+
+        let streamed = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let expected = AnalysisResult { findings: vec![finding(3), finding(9)], stats: AnalysisStats::default() };
+        assert_eq!(streamed, NdjsonReport::new(&expected).to_ndjson());
+    }
+}
@@ -0,0 +1,373 @@
+use log::error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::analyzer::AnalysisResult;
+use crate::analyzer::audit::{AuditInfo, AuditReport};
+use crate::analyzer::compare::Comparison;
+use crate::analyzer::history::RunRecord;
+use crate::analyzer::config::OUTPUT_FORMATS;
+use crate::analyzer::report_templates::ReportTemplate;
+use crate::analyzer::reporting::ReportGenerator;
+use crate::analyzer::results::Sources;
+use crate::analyzer::{csv, github, gitlab, html, json, junit, ndjson, rdjson, sarif};
+
+#[cfg(test)]
+mod test;
+
+/// Format of a report (`--format`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Markdown,
+    Json,
+    Html,
+    Csv,
+    Junit,
+    Github,
+    Gitlab,
+    Rdjson,
+    Sarif,
+    Audit,
+    AuditHtml,
+    Template,
+    Ndjson,
+}
+
+impl Format {
+    /// Returns the name of the format as written in `--format` (`markdown`...)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Format::Markdown => "markdown",
+            Format::Json => "json",
+            Format::Html => "html",
+            Format::Csv => "csv",
+            Format::Junit => "junit",
+            Format::Github => "github",
+            Format::Gitlab => "gitlab",
+            Format::Rdjson => "rdjson",
+            Format::Sarif => "sarif",
+            Format::Audit => "audit",
+            Format::AuditHtml => "audit-html",
+            Format::Template => "template",
+            Format::Ndjson => "ndjson",
+        }
+    }
+
+    /// Returns the name of the format in the logs
+    pub fn title(&self) -> &'static str {
+        match self {
+            Format::Markdown => "Markdown",
+            Format::Json => "JSON",
+            Format::Html => "HTML",
+            Format::Csv => "CSV",
+            Format::Junit => "JUnit",
+            Format::Github => "GitHub annotations",
+            Format::Gitlab => "GitLab Code Quality",
+            Format::Rdjson => "rdjson",
+            Format::Sarif => "SARIF",
+            Format::Audit => "Audit",
+            Format::AuditHtml => "Audit HTML",
+            Format::Template => "Template",
+            Format::Ndjson => "NDJSON",
+        }
+    }
+
+    /// Returns the extension of the files written in this format when several formats share
+    /// an `--output` path
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Json => "json",
+            Format::Html => "html",
+            Format::Csv => "csv",
+            Format::Junit => "xml",
+            Format::Github => "txt",
+            Format::Gitlab => "gitlab.json",
+            Format::Rdjson => "rdjson",
+            Format::Sarif => "sarif",
+            Format::Audit => "audit.md",
+            Format::AuditHtml => "audit.html",
+            Format::Template => "template.txt",
+            Format::Ndjson => "ndjson",
+        }
+    }
+
+    /// Guess the format of a report from the extension of its path
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        if name.ends_with(".gitlab.json") {
+            return Some(Format::Gitlab);
+        }
+        if name.ends_with(".audit.md") {
+            return Some(Format::Audit);
+        }
+        if name.ends_with(".audit.html") {
+            return Some(Format::AuditHtml);
+        }
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "md" | "markdown" => Some(Format::Markdown),
+            "json" => Some(Format::Json),
+            "html" | "htm" => Some(Format::Html),
+            "csv" => Some(Format::Csv),
+            "xml" => Some(Format::Junit),
+            "rdjson" => Some(Format::Rdjson),
+            "sarif" => Some(Format::Sarif),
+            "ndjson" | "jsonl" => Some(Format::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = OutputError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "markdown" | "md" => Ok(Format::Markdown),
+            "json" => Ok(Format::Json),
+            "html" => Ok(Format::Html),
+            "csv" => Ok(Format::Csv),
+            "junit" => Ok(Format::Junit),
+            "github" => Ok(Format::Github),
+            "gitlab" => Ok(Format::Gitlab),
+            "rdjson" => Ok(Format::Rdjson),
+            "sarif" => Ok(Format::Sarif),
+            "audit" => Ok(Format::Audit),
+            "audit-html" => Ok(Format::AuditHtml),
+            "template" => Ok(Format::Template),
+            "ndjson" | "jsonl" => Ok(Format::Ndjson),
+            _ => Err(OutputError::UnknownFormat(value.trim().to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error returned when the requested reports cannot be written
+#[derive(Debug, PartialEq, Eq)]
+pub enum OutputError {
+    /// The format is not supported
+    UnknownFormat(String),
+    /// The format is requested twice
+    DuplicateFormat(Format),
+    /// Several reports would be written to the same file
+    DuplicateOutput(PathBuf),
+    /// Several formats are requested without an output file, they cannot share stdout
+    SeveralFormatsToStdout,
+    /// The formats cannot be paired with the output files
+    Mismatch { formats: usize, outputs: usize },
+}
+
+impl fmt::Display for OutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputError::UnknownFormat(format) => {
+                write!(f, "unknown format '{format}' (expected one of: {})", OUTPUT_FORMATS.join(", "))
+            }
+            OutputError::DuplicateFormat(format) => write!(f, "format '{format}' is requested twice"),
+            OutputError::DuplicateOutput(path) => write!(f, "several reports would be written to {}", path.display()),
+            OutputError::SeveralFormatsToStdout => write!(f, "several formats need an output file"),
+            OutputError::Mismatch { formats, outputs } => {
+                write!(f, "{formats} formats cannot be paired with {outputs} output files")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OutputError {}
+
+/// Parse a list of formats separated by commas (e.g. `md,json,html`)
+pub fn parse_formats(value: &str) -> Result<Vec<Format>, OutputError> {
+    let mut formats = Vec::new();
+    for format in value.split(',').filter(|format| !format.trim().is_empty()) {
+        let format = format.parse()?;
+        if formats.contains(&format) {
+            return Err(OutputError::DuplicateFormat(format));
+        }
+        formats.push(format);
+    }
+    Ok(formats)
+}
+
+/// Destination of a report: a file, or stdout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sink {
+    pub format: Format,
+    pub path: Option<PathBuf>,
+}
+
+/// Pair the requested formats with the output files
+///
+/// - Without a format, the format of each output is guessed from its extension (Markdown
+///   otherwise), and a single report goes to stdout.
+/// - With one output and several formats, each report is written next to it with the extension
+///   of its format (`report.md` and `report.json`).
+/// - With several outputs, each one gets the format at the same position.
+///
+/// Markdown reports always get an `.md` extension.
+pub fn sinks(formats: &[Format], outputs: &[PathBuf]) -> Result<Vec<Sink>, OutputError> {
+    let sinks: Vec<Sink> = match (formats, outputs) {
+        ([], []) => vec![Sink { format: Format::Markdown, path: None }],
+        ([format], []) => vec![Sink { format: *format, path: None }],
+        (_, []) => return Err(OutputError::SeveralFormatsToStdout),
+        ([], outputs) => outputs
+            .iter()
+            .map(|path| {
+                let format = Format::from_path(path).unwrap_or(Format::Markdown);
+                Sink { format, path: Some(report_path(format, path.clone())) }
+            })
+            .collect(),
+        (formats, [output]) if formats.len() > 1 => formats
+            .iter()
+            .map(|format| Sink { format: *format, path: Some(output.with_extension(format.extension())) })
+            .collect(),
+        (formats, outputs) if formats.len() == outputs.len() => formats
+            .iter()
+            .zip(outputs)
+            .map(|(format, path)| Sink { format: *format, path: Some(report_path(*format, path.clone())) })
+            .collect(),
+        (formats, outputs) => return Err(OutputError::Mismatch { formats: formats.len(), outputs: outputs.len() }),
+    };
+
+    let mut paths = Vec::new();
+    for path in sinks.iter().filter_map(|sink| sink.path.as_ref()) {
+        if paths.contains(&path) {
+            return Err(OutputError::DuplicateOutput(path.clone()));
+        }
+        paths.push(path);
+    }
+    Ok(sinks)
+}
+
+/// Helper function to give Markdown reports an `.md` extension
+fn report_path(format: Format, mut path: PathBuf) -> PathBuf {
+    if format == Format::Markdown && Format::from_path(&path) != Some(Format::Markdown) {
+        path.set_extension("md");
+    }
+    path
+}
+
+/// Renders the reports of one analysis in any format
+pub struct Renderer<'a> {
+    result: &'a AnalysisResult,
+    project: &'a str,
+    base: &'a Path,
+    comparison: Option<&'a Comparison>,
+    history: &'a [RunRecord],
+    audit: AuditInfo,
+    template: Option<&'a ReportTemplate>,
+    sources: Option<&'a Sources>,
+}
+
+impl<'a> Renderer<'a> {
+    /// Creates the renderer of an analysis of `project`; the paths of the CI formats are made
+    /// relative to `base`
+    pub fn new(result: &'a AnalysisResult, project: &'a str, base: &'a Path) -> Self {
+        Self {
+            result,
+            project,
+            base,
+            comparison: None,
+            history: &[],
+            audit: AuditInfo::default(),
+            template: None,
+            sources: None,
+        }
+    }
+
+    /// Adds the comparison with a previous run to the formats that show it
+    pub fn with_comparison(mut self, comparison: &'a Comparison) -> Self {
+        self.comparison = Some(comparison);
+        self
+    }
+
+    /// Adds the recorded runs of the project (the current one last) to the formats that show them
+    pub fn with_history(mut self, history: &'a [RunRecord]) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Fill the cover page of the audit reports
+    pub fn with_audit(mut self, audit: AuditInfo) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    /// Render `--format template` with a custom template (the builtin Markdown one otherwise)
+    pub fn with_template(mut self, template: &'a ReportTemplate) -> Self {
+        self.template = Some(template);
+        self
+    }
+
+    /// Read the code of the files held in memory (stdin) from `sources` instead of the disk
+    pub fn with_sources(mut self, sources: &'a Sources) -> Self {
+        self.sources = Some(sources);
+        self
+    }
+
+    /// Returns the report in a format
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Markdown => {
+                let mut report = ReportGenerator::new(self.result.findings.clone(), self.project.to_string())
+                    .with_omitted(self.result.stats.findings_omitted.clone())
+                    .with_lines_of_code(self.result.stats.lines_of_code.clone())
+                    .with_suppressed(self.result.stats.suppressed_findings.clone())
+                    .with_history(self.history.to_vec());
+                if let Some(comparison) = self.comparison {
+                    report = report.with_comparison(comparison.clone());
+                }
+                if let Some(sources) = self.sources {
+                    report = report.with_sources(sources.clone());
+                }
+                report.generate_markdown_report()
+            }
+            Format::Json => {
+                let mut report = json::JsonReport::new(self.result, self.project);
+                if let Some(comparison) = self.comparison {
+                    report = report.with_comparison(comparison);
+                }
+                report.to_json()
+            }
+            Format::Html => {
+                let mut report = html::HtmlReport::new(self.result, self.project).with_history(self.history);
+                if let Some(comparison) = self.comparison {
+                    report = report.with_comparison(comparison);
+                }
+                if let Some(sources) = self.sources {
+                    report = report.with_sources(sources);
+                }
+                report.to_html()
+            }
+            Format::Csv => csv::CsvReport::new(self.result, self.project).to_csv(),
+            Format::Junit => junit::JunitReport::new(self.result, self.project).to_xml(),
+            Format::Github => github::GithubReport::new(self.result, self.base).to_annotations(),
+            Format::Gitlab => gitlab::GitlabReport::new(self.result, self.base).to_json(),
+            Format::Rdjson => rdjson::RdjsonReport::new(self.result, self.base).to_json(),
+            Format::Sarif => sarif::SarifReport::new(self.result, self.base).to_json(),
+            Format::Audit => AuditReport::new(self.result, self.project).with_info(self.audit.clone()).to_markdown(),
+            Format::AuditHtml => AuditReport::new(self.result, self.project).with_info(self.audit.clone()).to_html(),
+            Format::Template => {
+                let mut report = json::JsonReport::new(self.result, self.project);
+                if let Some(comparison) = self.comparison {
+                    report = report.with_comparison(comparison);
+                }
+                let model = serde_json::to_value(&report).expect("findings and statistics serialize to JSON");
+                let rendered = match self.template {
+                    Some(template) => template.render(&model),
+                    None => ReportTemplate::builtin("markdown").expect("builtin template").render(&model),
+                };
+                rendered.unwrap_or_else(|e| {
+                    error!("{e}");
+                    String::new()
+                })
+            }
+            Format::Ndjson => ndjson::NdjsonReport::new(self.result).to_ndjson(),
+        }
+    }
+}
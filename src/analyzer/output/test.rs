@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::output::{Format, OutputError, Renderer, Sink, parse_formats, sinks};
+use crate::analyzer::report_templates::ReportTemplate;
+use crate::analyzer::{AnalysisResult, AnalysisStats};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sink(format: Format, path: Option<&str>) -> Sink {
+        Sink { format, path: path.map(PathBuf::from) }
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        assert_eq!(parse_formats("md,json, html"), Ok(vec![Format::Markdown, Format::Json, Format::Html]));
+        assert_eq!(parse_formats("json,pdf"), Err(OutputError::UnknownFormat("pdf".to_string())));
+        assert_eq!(parse_formats("json,JSON"), Err(OutputError::DuplicateFormat(Format::Json)));
+        assert_eq!(Format::from_path(Path::new("ci/report.gitlab.json")), Some(Format::Gitlab));
+        assert_eq!(Format::from_path(Path::new("acme.audit.html")), Some(Format::AuditHtml));
+        assert_eq!(parse_formats("audit,audit-html"), Ok(vec![Format::Audit, Format::AuditHtml]));
+        assert_eq!(Format::from_path(Path::new("report.txt")), None);
+        assert_eq!(parse_formats("template"), Ok(vec![Format::Template]));
+        assert_eq!(parse_formats("ndjson,jsonl"), Err(OutputError::DuplicateFormat(Format::Ndjson)));
+        assert_eq!(Format::from_path(Path::new("findings.jsonl")), Some(Format::Ndjson));
+    }
+
+    #[test]
+    fn test_sinks() {
+        let outputs = |paths: &[&str]| paths.iter().map(PathBuf::from).collect::<Vec<_>>();
+
+        // The console, or stdout
+        assert_eq!(sinks(&[], &[]), Ok(vec![sink(Format::Markdown, None)]));
+        assert_eq!(sinks(&[Format::Json], &[]), Ok(vec![sink(Format::Json, None)]));
+        assert_eq!(sinks(&[Format::Json, Format::Csv], &[]), Err(OutputError::SeveralFormatsToStdout));
+
+        // Formats guessed from the extensions, Markdown otherwise
+        assert_eq!(
+            sinks(&[], &outputs(&["report.json", "report.txt"])),
+            Ok(vec![sink(Format::Json, Some("report.json")), sink(Format::Markdown, Some("report.md"))])
+        );
+
+        // Several formats next to one output
+        assert_eq!(
+            sinks(&[Format::Markdown, Format::Json, Format::Gitlab], &outputs(&["out/report.md"])),
+            Ok(vec![
+                sink(Format::Markdown, Some("out/report.md")),
+                sink(Format::Json, Some("out/report.json")),
+                sink(Format::Gitlab, Some("out/report.gitlab.json")),
+            ])
+        );
+
+        // Formats paired with the outputs
+        assert_eq!(
+            sinks(&[Format::Json, Format::Junit], &outputs(&["a.txt", "b.txt"])),
+            Ok(vec![sink(Format::Json, Some("a.txt")), sink(Format::Junit, Some("b.txt"))])
+        );
+        assert_eq!(
+            sinks(&[Format::Json, Format::Junit, Format::Csv], &outputs(&["a.txt", "b.txt"])),
+            Err(OutputError::Mismatch { formats: 3, outputs: 2 })
+        );
+        assert_eq!(
+            sinks(&[], &outputs(&["report.md", "report"])),
+            Err(OutputError::DuplicateOutput(PathBuf::from("report.md")))
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let result = AnalysisResult { findings: Vec::new(), stats: AnalysisStats::default() };
+        let renderer = Renderer::new(&result, "programs/vault", Path::new("."));
+
+        assert!(renderer.render(Format::Markdown).starts_with("# Rust Solana Analyzer Report"));
+        assert!(renderer.render(Format::Json).contains("\"project\": \"programs/vault\""));
+        assert!(renderer.render(Format::Html).starts_with("<!DOCTYPE html>"));
+        assert_eq!(renderer.render(Format::Gitlab), "[]\n");
+        assert_eq!(renderer.render(Format::Ndjson), "");
+
+        // The builtin Markdown template, or a custom one
+        assert!(renderer.render(Format::Template).contains("No vulnerabilities found."));
+        let template = ReportTemplate::parse("summary.txt", "{{project}}: {{summary.high}} high\n").unwrap();
+        let renderer = renderer.with_template(&template);
+        assert_eq!(renderer.render(Format::Template), "programs/vault: 0 high\n");
+    }
+}
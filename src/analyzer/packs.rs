@@ -0,0 +1,127 @@
+use std::collections::BTreeSet;
+
+/// Named set of rules for a kind of program or concern
+///
+/// Rules declare the packs they belong to (`RuleBuilder::pack`), and a profile selects
+/// the packs to run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RulePack {
+    /// Name of the pack, as declared by rules
+    pub name: &'static str,
+    /// What the rules of the pack check
+    pub description: &'static str,
+}
+
+/// Packs used by the built-in rules
+///
+/// Rules can declare other packs (e.g. in a downstream crate), which can be selected by name.
+pub const RULE_PACKS: &[RulePack] = &[
+    RulePack {
+        name: "anchor-core",
+        description: "Account validation and access control of Anchor programs",
+    },
+    RulePack {
+        name: "spl-token",
+        description: "Use of the SPL Token program (transfers, mints, token accounts)",
+    },
+    RulePack {
+        name: "defi",
+        description: "Arithmetic and fund movements of DeFi programs",
+    },
+    RulePack {
+        name: "native",
+        description: "Programs written without Anchor",
+    },
+    RulePack {
+        name: "pinocchio",
+        description: "Programs written with Pinocchio",
+    },
+    RulePack {
+        name: "steel",
+        description: "Programs written with Steel",
+    },
+    RulePack {
+        name: "style",
+        description: "Code quality and best practices",
+    },
+];
+
+/// Curated selection of packs for a program type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Profile {
+    /// Name of the profile, as given to `--profile`
+    pub name: &'static str,
+    /// Programs the profile is meant for
+    pub description: &'static str,
+    /// Packs run by the profile (every rule if empty)
+    pub packs: &'static [&'static str],
+}
+
+/// Profile running every rule
+pub const DEFAULT_PROFILE: &str = "all";
+
+/// Built-in profiles
+pub const PROFILES: &[Profile] = &[
+    Profile {
+        name: DEFAULT_PROFILE,
+        description: "Every rule, including rules without a pack",
+        packs: &[],
+    },
+    Profile {
+        name: "anchor",
+        description: "Anchor programs",
+        packs: &["anchor-core", "spl-token", "defi"],
+    },
+    Profile {
+        name: "native",
+        description: "Programs written without Anchor",
+        packs: &["native", "spl-token", "defi"],
+    },
+    Profile {
+        name: "pinocchio",
+        description: "Programs written with Pinocchio",
+        packs: &["native", "pinocchio", "spl-token", "defi"],
+    },
+    Profile {
+        name: "steel",
+        description: "Programs written with Steel",
+        packs: &["native", "steel", "spl-token", "defi"],
+    },
+];
+
+/// Resolve a profile specification into the packs to run
+///
+/// The specification is a comma-separated list of profile and pack names, e.g.
+/// `anchor,style`. Returns `None` when every rule must run (`all`).
+pub fn resolve_profile(spec: &str) -> Result<Option<BTreeSet<String>>, String> {
+    let mut packs = BTreeSet::new();
+
+    for name in spec.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        if let Some(profile) = PROFILES.iter().find(|profile| profile.name == name) {
+            if profile.packs.is_empty() {
+                return Ok(None);
+            }
+            packs.extend(profile.packs.iter().map(|pack| pack.to_string()));
+        } else if is_valid_pack_name(name) {
+            // Packs of custom rules are only known once the rules are loaded
+            packs.insert(name.to_string());
+        } else {
+            return Err(format!("invalid profile or pack name '{name}'"));
+        }
+    }
+
+    if packs.is_empty() {
+        return Err(format!("empty profile '{spec}'"));
+    }
+    Ok(Some(packs))
+}
+
+/// Check if a pack name follows the naming convention (lowercase words separated by hyphens)
+pub fn is_valid_pack_name(name: &str) -> bool {
+    crate::analyzer::dsl::builders::is_valid_rule_id(name)
+}
+
+/// Check if a rule declaring the given packs runs with the selected packs
+pub fn is_selected(rule_packs: &[String], selected: Option<&BTreeSet<String>>) -> bool {
+    selected.is_none_or(|selected| rule_packs.iter().any(|pack| selected.contains(pack)))
+}
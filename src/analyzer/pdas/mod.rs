@@ -0,0 +1,451 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::fs;
+
+use crate::analyzer::dsl::ProgramContext;
+use crate::analyzer::dsl::call_graph::{CallSite, FunctionSummary};
+use crate::analyzer::dsl::program::{DeclarationKind, Member};
+use crate::analyzer::inventory::split_arguments;
+use crate::analyzer::json::Tool;
+
+#[cfg(test)]
+mod test;
+
+/// Version of the PDA report schema, incremented on any change that is not an addition
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Prefix of the accounts of a handler, left out of the seeds (`ctx.accounts.user` is `user`)
+const ACCOUNTS_PREFIX: &str = "ctx.accounts.";
+
+/// How a PDA is derived
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PdaSource {
+    /// `seeds = [...]` constraint of an account of an accounts struct
+    Constraint,
+    /// `Pubkey::find_program_address(..)` call
+    FindProgramAddress,
+    /// `Pubkey::create_program_address(..)` call
+    CreateProgramAddress,
+}
+
+/// How the bump of a PDA is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bump {
+    /// Canonical bump found on-chain (`bump` constraint without a value, `find_program_address`)
+    Canonical,
+    /// Bump read from an account (`bump = vault.bump`)
+    Stored,
+    /// Bump passed as the last seed of `create_program_address` (`&[bump]`)
+    Provided,
+    /// No bump is known (`create_program_address` without a one-byte last seed)
+    Unknown,
+}
+
+/// PDA derivation of a program, with its normalized seeds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PdaDerivation {
+    pub source: PdaSource,
+    /// Seeds without spaces, references, `.as_ref()` and `ctx.accounts.` (`b"vault"`,
+    /// `user.key()`), constants of the program replaced by their value; the bump of
+    /// `create_program_address` is left out
+    pub seeds: Vec<String>,
+    pub bump: Bump,
+    /// Value of a stored or provided bump (`vault.bump`)
+    pub bump_expression: Option<String>,
+    /// Program the PDA is derived from, `None` for the program itself (`seeds::program`, or the
+    /// program ID argument when it is not `program_id` or `ID`)
+    pub program_id: Option<String>,
+    /// Accounts struct of the account of constraints (`Deposit`)
+    pub accounts_struct: Option<String>,
+    /// Account of constraints (`vault`)
+    pub account: Option<String>,
+    /// Function making the call of `find_program_address` and `create_program_address`
+    /// (`Deposit::validate` for methods)
+    pub function: Option<String>,
+    pub file: String,
+    pub line: usize,
+    /// Handlers taking the accounts struct, or reaching the function through the call graph
+    pub instructions: Vec<String>,
+}
+
+impl PdaDerivation {
+    /// Returns where the PDA is derived: `Deposit.vault` for constraints, the function for calls
+    pub fn derived_by(&self) -> String {
+        match (&self.accounts_struct, &self.account, &self.function) {
+            (Some(accounts_struct), Some(account), _) => format!("{accounts_struct}.{account}"),
+            (_, _, Some(function)) => function.clone(),
+            _ => String::new(),
+        }
+    }
+}
+
+/// Derivations sharing the same seeds and program, the same PDA for the same seed values
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SeedTuple {
+    pub seeds: Vec<String>,
+    pub program_id: Option<String>,
+    /// Where the PDA is derived (see [`PdaDerivation::derived_by`])
+    pub derived_by: Vec<String>,
+    /// Handlers touching the PDA, through any of its derivations
+    pub instructions: Vec<String>,
+}
+
+/// PDA derivations of a program, indexed by its [`ProgramContext`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ProgramPdas {
+    /// Crate of the workspace, `None` when the files are analyzed as one program
+    pub program: Option<String>,
+    /// Derivations: the constraints in the order of the files, then the calls by file and line
+    pub derivations: Vec<PdaDerivation>,
+    /// Distinct seed tuples, sorted by seeds
+    pub seed_tuples: Vec<SeedTuple>,
+}
+
+impl ProgramPdas {
+    /// Collects the PDA derivations of a program from its context, keeping the ones whose file
+    /// and line are accepted by `include` (e.g. the ones outside of the test code)
+    pub fn of(program: Option<String>, context: &ProgramContext, include: impl Fn(&str, usize) -> bool) -> Self {
+        let call_graph = context.call_graph();
+        let instructions: Vec<&FunctionSummary> =
+            call_graph.instructions().filter(|function| include(&function.file, function.line)).collect();
+
+        let mut derivations = Vec::new();
+        for declaration in context.of_kind(DeclarationKind::Accounts) {
+            let handlers: Vec<String> = instructions
+                .iter()
+                .filter(|function| function.accounts.as_deref() == Some(declaration.name.as_str()))
+                .map(|function| function.name.clone())
+                .collect();
+            for member in &declaration.members {
+                if include(&declaration.file, member.line)
+                    && let Some(mut derivation) = constraint_derivation(context, &declaration.file, member)
+                {
+                    derivation.accounts_struct = Some(declaration.name.clone());
+                    derivation.file = declaration.file.clone();
+                    derivation.instructions = handlers.clone();
+                    derivations.push(derivation);
+                }
+            }
+        }
+
+        // Functions reachable from each handler, to credit the derivations of helpers
+        let reachable: Vec<(&str, BTreeSet<String>)> = instructions
+            .iter()
+            .map(|function| (function.name.as_str(), call_graph.reachable_from([function.name.as_str()])))
+            .collect();
+        let mut calls: Vec<(&FunctionSummary, &CallSite)> = call_graph
+            .functions()
+            .flat_map(|function| function.calls.iter().map(move |call| (function, call)))
+            .filter(|(function, call)| include(&function.file, call.line))
+            .collect();
+        calls.sort_by(|(a, a_call), (b, b_call)| (&a.file, a_call.line).cmp(&(&b.file, b_call.line)));
+        for (function, call) in calls {
+            if let Some(mut derivation) = call_derivation(context, &function.file, call) {
+                derivation.function = Some(match &function.owner {
+                    Some(owner) => format!("{owner}::{}", function.name),
+                    None => function.name.clone(),
+                });
+                derivation.file = function.file.clone();
+                derivation.instructions = reachable
+                    .iter()
+                    .filter(|(_, functions)| functions.contains(&function.name))
+                    .map(|(instruction, _)| instruction.to_string())
+                    .collect::<BTreeSet<String>>()
+                    .into_iter()
+                    .collect();
+                derivations.push(derivation);
+            }
+        }
+
+        let seed_tuples = seed_tuples(&derivations);
+        Self { program, derivations, seed_tuples }
+    }
+
+    /// Check if the program derives no PDA
+    pub fn is_empty(&self) -> bool {
+        self.derivations.is_empty()
+    }
+}
+
+/// PDA report of the programs of a project (`--pdas`)
+///
+/// ```json
+/// {
+///   "schema_version": 1,
+///   "tool": { "name": "rust-solana-analyzer", "version": "0.1.0" },
+///   "project": "programs/vault",
+///   "programs": [
+///     {
+///       "program": "vault",
+///       "derivations": [{
+///         "source": "constraint", "seeds": ["b\"vault\"", "user.key()"],
+///         "bump": "stored", "bump_expression": "vault.bump", "program_id": null,
+///         "accounts_struct": "Deposit", "account": "vault", "function": null,
+///         "file": "...", "line": 21, "instructions": ["deposit"]
+///       }],
+///       "seed_tuples": [{
+///         "seeds": ["b\"vault\"", "user.key()"], "program_id": null,
+///         "derived_by": ["Deposit.vault", "withdraw"], "instructions": ["deposit", "withdraw"]
+///       }]
+///     }
+///   ]
+/// }
+/// ```
+///
+/// Seeds are normalized so the same PDA gets the same seed tuple wherever it is derived:
+/// `&[b"vault", ctx.accounts.user.key().as_ref()]` in a handler and
+/// `seeds = [VAULT_SEED, user.key().as_ref()]` in an accounts struct both give
+/// `[b"vault", user.key()]`. The seed tuples are the input of the checks comparing PDAs.
+#[derive(Debug, Serialize)]
+pub struct PdaReport<'a> {
+    /// Version of the schema ([`SCHEMA_VERSION`])
+    pub schema_version: u32,
+    /// Analyzer that produced the report
+    pub tool: Tool,
+    /// Analyzed path
+    pub project: &'a str,
+    /// PDAs of each program
+    pub programs: &'a [ProgramPdas],
+}
+
+impl<'a> PdaReport<'a> {
+    /// Creates the PDA report of the programs of `project`
+    pub fn new(programs: &'a [ProgramPdas], project: &'a str) -> Self {
+        Self { schema_version: SCHEMA_VERSION, tool: Tool::default(), project, programs }
+    }
+
+    /// Returns the report as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("PDA report serializes to JSON");
+        json.push('\n');
+        json
+    }
+
+    /// Returns the report as a Markdown document
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from("# PDA Inventory\n\n");
+        let _ = writeln!(markdown, "Program derived addresses of {}.\n", self.project);
+        let programs: Vec<&ProgramPdas> = self.programs.iter().filter(|program| !program.is_empty()).collect();
+        if programs.is_empty() {
+            markdown.push_str("No PDA derivation found.\n");
+        }
+        for program in programs {
+            let _ = writeln!(markdown, "## {}\n", program.program.as_deref().unwrap_or("Program"));
+            markdown.push_str("### Seed Tuples\n\n");
+            markdown.push_str("| Seeds | Program | Derived By | Instructions |\n| --- | --- | --- | --- |\n");
+            for tuple in &program.seed_tuples {
+                let _ = writeln!(
+                    markdown,
+                    "| {} | {} | {} | {} |",
+                    seeds_markdown(&tuple.seeds),
+                    tuple.program_id.as_ref().map_or("-".to_string(), |program_id| format!("`{program_id}`")),
+                    code_list(&tuple.derived_by),
+                    code_list(&tuple.instructions)
+                );
+            }
+            markdown.push_str("\n### Derivations\n\n");
+            markdown.push_str("| Derived By | Source | Seeds | Bump | Location |\n| --- | --- | --- | --- | --- |\n");
+            for derivation in &program.derivations {
+                let source = match derivation.source {
+                    PdaSource::Constraint => "constraint",
+                    PdaSource::FindProgramAddress => "`find_program_address`",
+                    PdaSource::CreateProgramAddress => "`create_program_address`",
+                };
+                let bump = match (derivation.bump, &derivation.bump_expression) {
+                    (Bump::Canonical, _) => "canonical".to_string(),
+                    (Bump::Stored, Some(expression)) => format!("stored (`{expression}`)"),
+                    (Bump::Provided, Some(expression)) => format!("provided (`{expression}`)"),
+                    (Bump::Stored | Bump::Provided, None) | (Bump::Unknown, _) => "unknown".to_string(),
+                };
+                let _ = writeln!(
+                    markdown,
+                    "| `{}` | {source} | {} | {bump} | {} |",
+                    derivation.derived_by(),
+                    seeds_markdown(&derivation.seeds),
+                    self.location(&derivation.file, derivation.line)
+                );
+            }
+            markdown.push('\n');
+        }
+        markdown
+    }
+
+    /// Save the report to a file, as Markdown if its extension is `md` and as JSON otherwise
+    pub fn save(&self, output_path: &str) -> Result<(), std::io::Error> {
+        let markdown = std::path::Path::new(output_path).extension().is_some_and(|extension| extension == "md");
+        fs::write(output_path, if markdown { self.to_markdown() } else { self.to_json() })
+    }
+
+    /// Helper function to format the location of a derivation, relative to the project
+    fn location(&self, file: &str, line: usize) -> String {
+        format!("{}:{line}", file.strip_prefix(self.project).unwrap_or(file).trim_start_matches('/'))
+    }
+}
+
+/// Helper function to get the derivation of the `seeds` constraint of an account
+fn constraint_derivation(context: &ProgramContext, file: &str, member: &Member) -> Option<PdaDerivation> {
+    let constraints = split_arguments(member.attribute("account")?);
+    let value = |name: &str| {
+        constraints.iter().find_map(|constraint| {
+            let (key, value) = constraint.split_once('=')?;
+            (key == name).then_some(value)
+        })
+    };
+    let seeds = value("seeds")?;
+    let (bump, bump_expression) = match value("bump") {
+        Some(expression) => (Bump::Stored, Some(normalize_seed(context, file, expression))),
+        None if constraints.iter().any(|constraint| constraint == "bump") => (Bump::Canonical, None),
+        None => (Bump::Unknown, None),
+    };
+    Some(PdaDerivation {
+        source: PdaSource::Constraint,
+        seeds: seed_list(context, file, seeds),
+        bump,
+        bump_expression,
+        program_id: value("seeds::program").map(|program_id| normalize_seed(context, file, program_id)),
+        accounts_struct: None,
+        account: Some(member.name.clone()),
+        function: None,
+        file: String::new(),
+        line: member.line,
+        instructions: Vec::new(),
+    })
+}
+
+/// Helper function to get the derivation of a `find_program_address` or
+/// `create_program_address` call
+fn call_derivation(context: &ProgramContext, file: &str, call: &CallSite) -> Option<PdaDerivation> {
+    let source = match call.callee.as_str() {
+        "find_program_address" => PdaSource::FindProgramAddress,
+        "create_program_address" => PdaSource::CreateProgramAddress,
+        _ => return None,
+    };
+    let mut seeds = seed_list(context, file, call.args.first()?);
+    let (bump, bump_expression) = match source {
+        PdaSource::CreateProgramAddress => {
+            // The bump is the last seed, a one-byte slice (`&[bump]`)
+            match seeds.last().and_then(|seed| seed.strip_prefix('[')?.strip_suffix(']')) {
+                Some(expression) if !expression.contains(',') => {
+                    let expression = expression.to_string();
+                    seeds.pop();
+                    (Bump::Provided, Some(expression))
+                }
+                _ => (Bump::Unknown, None),
+            }
+        }
+        _ => (Bump::Canonical, None),
+    };
+    let program_id = call
+        .args
+        .get(1)
+        .map(|program_id| normalize_seed(context, file, program_id))
+        .filter(|program_id| !is_own_program_id(program_id));
+    Some(PdaDerivation {
+        source,
+        seeds,
+        bump,
+        bump_expression,
+        program_id,
+        accounts_struct: None,
+        account: None,
+        function: None,
+        file: String::new(),
+        line: call.line,
+        instructions: Vec::new(),
+    })
+}
+
+/// Helper function to group the derivations by seeds and program
+fn seed_tuples(derivations: &[PdaDerivation]) -> Vec<SeedTuple> {
+    let mut tuples: BTreeMap<(&[String], Option<&str>), SeedTuple> = BTreeMap::new();
+    for derivation in derivations {
+        let tuple = tuples
+            .entry((&derivation.seeds, derivation.program_id.as_deref()))
+            .or_insert_with(|| SeedTuple {
+                seeds: derivation.seeds.clone(),
+                program_id: derivation.program_id.clone(),
+                derived_by: Vec::new(),
+                instructions: Vec::new(),
+            });
+        let derived_by = derivation.derived_by();
+        if !tuple.derived_by.contains(&derived_by) {
+            tuple.derived_by.push(derived_by);
+        }
+        for instruction in &derivation.instructions {
+            if !tuple.instructions.contains(instruction) {
+                tuple.instructions.push(instruction.clone());
+            }
+        }
+    }
+    tuples.into_values().collect()
+}
+
+/// Helper function to split and normalize the seeds of an array (`[b"vault",user.key().as_ref()]`)
+///
+/// Seeds that are not an array literal (`Vault::seeds(&user)`) are kept as one seed.
+fn seed_list(context: &ProgramContext, file: &str, seeds: &str) -> Vec<String> {
+    let seeds = seeds.replace(' ', "");
+    let seeds = seeds.trim_start_matches('&');
+    match seeds.strip_prefix('[').and_then(|seeds| seeds.strip_suffix(']')) {
+        Some(seeds) => split_arguments(seeds).iter().map(|seed| normalize_seed(context, file, seed)).collect(),
+        None => vec![normalize_seed(context, file, seeds)],
+    }
+}
+
+/// Helper function to normalize a seed, constants of the program replaced by their value
+fn normalize_seed(context: &ProgramContext, file: &str, seed: &str) -> String {
+    let seed = normalize(seed);
+    let is_path = !seed.is_empty() && seed.chars().all(|c| c.is_alphanumeric() || c == '_' || c == ':');
+    let constant = context
+        .resolve(file, &seed)
+        .filter(|declaration| is_path && declaration.kind == DeclarationKind::Constant);
+    match constant.and_then(|declaration| declaration.value.as_deref()) {
+        Some(value) => normalize(value),
+        None => seed,
+    }
+}
+
+/// Helper function to write a seed without spaces, references, `.as_ref()` or `ctx.accounts.`,
+/// with `"vault".as_bytes()` written `b"vault"` and the `key` field of native accounts `key()`
+fn normalize(seed: &str) -> String {
+    let mut seed = seed.replace(' ', "");
+    loop {
+        let stripped = seed.trim_start_matches(['&', '*']);
+        let stripped = stripped.strip_suffix(".as_ref()").unwrap_or(stripped);
+        if stripped == seed {
+            break;
+        }
+        seed = stripped.to_string();
+    }
+    let seed = seed.replace(ACCOUNTS_PREFIX, "");
+    if let Some(literal) = seed.strip_suffix(".as_bytes()").filter(|literal| literal.starts_with('"')) {
+        return format!("b{literal}");
+    }
+    match seed.strip_suffix(".key") {
+        Some(account) => format!("{account}.key()"),
+        None => seed,
+    }
+}
+
+/// Helper function to check if a program ID argument is the ID of the program itself
+fn is_own_program_id(program_id: &str) -> bool {
+    let last = program_id.rsplit("::").next().unwrap_or(program_id);
+    program_id.ends_with("program_id") || matches!(last, "ID" | "id()")
+}
+
+/// Helper function to render seeds as a Markdown tuple
+fn seeds_markdown(seeds: &[String]) -> String {
+    let seeds: Vec<String> = seeds.iter().map(|seed| seed.replace('|', "\\|")).collect();
+    format!("`[{}]`", seeds.join(", "))
+}
+
+/// Helper function to render names as a list of code spans (`-` if there are none)
+fn code_list(names: &[String]) -> String {
+    if names.is_empty() {
+        return "-".to_string();
+    }
+    names.iter().map(|name| format!("`{name}`")).collect::<Vec<String>>().join(", ")
+}
@@ -0,0 +1,145 @@
+use crate::analyzer::dsl::ProgramContext;
+use crate::analyzer::pdas::{Bump, PdaReport, PdaSource, ProgramPdas};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB: &str = r#"
+use anchor_lang::prelude::*;
+
+pub const VAULT_SEED: &[u8] = b"vault";
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        let signer = vault_signer(&ctx.accounts.user.key(), ctx.accounts.vault.bump, ctx.program_id)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(init, payer = user, space = 8 + 33, seeds = [VAULT_SEED, user.key().as_ref()], bump)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, seeds = [b"vault", user.key().as_ref()], bump = vault.bump)]
+    pub vault: Account<'info, Vault>,
+    #[account(seeds = [b"config"], bump, seeds::program = registry::ID)]
+    pub config: UncheckedAccount<'info>,
+    pub user: Signer<'info>,
+}
+
+#[account]
+pub struct Vault {
+    pub user: Pubkey,
+    pub bump: u8,
+}
+"#;
+
+    const HELPERS: &str = r#"
+use anchor_lang::prelude::*;
+
+pub fn vault_signer(user: &Pubkey, bump: u8, program_id: &Pubkey) -> Result<Pubkey> {
+    let (expected, _) = Pubkey::find_program_address(&["vault".as_bytes(), user.as_ref()], program_id);
+    let address = Pubkey::create_program_address(&[b"vault", user.as_ref(), &[bump]], program_id).unwrap();
+    Ok(address)
+}
+"#;
+
+    fn pdas() -> ProgramPdas {
+        let lib = syn::parse_file(LIB).unwrap();
+        let helpers = syn::parse_file(HELPERS).unwrap();
+        let context = ProgramContext::build([("src/lib.rs", &lib), ("src/helpers.rs", &helpers)]);
+        ProgramPdas::of(Some("vault".to_string()), &context, |_, _| true)
+    }
+
+    #[test]
+    fn test_constraint_derivations() {
+        let pdas = pdas();
+        let constraints: Vec<_> = pdas.derivations.iter().filter(|derivation| derivation.source == PdaSource::Constraint).collect();
+        assert_eq!(constraints.len(), 3);
+
+        // The constant is replaced by its value, so both accounts get the same seeds
+        let deposit = constraints[0];
+        assert_eq!(deposit.derived_by(), "Deposit.vault");
+        assert_eq!(deposit.seeds, vec!["b\"vault\"", "user.key()"]);
+        assert_eq!((deposit.bump, deposit.bump_expression.as_deref()), (Bump::Canonical, None));
+        assert_eq!(deposit.instructions, vec!["deposit"]);
+        assert_eq!((deposit.file.as_str(), deposit.line), ("src/lib.rs", 23));
+
+        let withdraw = constraints[1];
+        assert_eq!(withdraw.seeds, deposit.seeds);
+        assert_eq!((withdraw.bump, withdraw.bump_expression.as_deref()), (Bump::Stored, Some("vault.bump")));
+        assert_eq!(withdraw.instructions, vec!["withdraw"]);
+
+        let config = constraints[2];
+        assert_eq!(config.program_id.as_deref(), Some("registry::ID"));
+    }
+
+    #[test]
+    fn test_call_derivations() {
+        let pdas = pdas();
+        let calls: Vec<_> = pdas.derivations.iter().filter(|derivation| derivation.source != PdaSource::Constraint).collect();
+        assert_eq!(calls.len(), 2);
+
+        let find = calls[0];
+        assert_eq!(find.source, PdaSource::FindProgramAddress);
+        assert_eq!(find.function.as_deref(), Some("vault_signer"));
+        assert_eq!(find.seeds, vec!["b\"vault\"", "user"]);
+        assert_eq!(find.bump, Bump::Canonical);
+        assert_eq!(find.program_id, None);
+        // The helper is reached from the handler through the call graph
+        assert_eq!(find.instructions, vec!["withdraw"]);
+
+        // The one-byte last seed is the bump
+        let create = calls[1];
+        assert_eq!(create.source, PdaSource::CreateProgramAddress);
+        assert_eq!(create.seeds, vec!["b\"vault\"", "user"]);
+        assert_eq!((create.bump, create.bump_expression.as_deref()), (Bump::Provided, Some("bump")));
+    }
+
+    #[test]
+    fn test_seed_tuples() {
+        let pdas = pdas();
+        let tuples: Vec<(&[String], Option<&str>)> =
+            pdas.seed_tuples.iter().map(|tuple| (tuple.seeds.as_slice(), tuple.program_id.as_deref())).collect();
+        assert_eq!(tuples.len(), 3);
+        assert_eq!(tuples[0], (&["b\"config\"".to_string()][..], Some("registry::ID")));
+
+        let vault = pdas.seed_tuples.iter().find(|tuple| tuple.seeds == ["b\"vault\"", "user.key()"]).unwrap();
+        assert_eq!(vault.derived_by, vec!["Deposit.vault", "Withdraw.vault"]);
+        assert_eq!(vault.instructions, vec!["deposit", "withdraw"]);
+    }
+
+    #[test]
+    fn test_pda_report() {
+        let programs = vec![pdas()];
+        let report = PdaReport::new(&programs, "/project");
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["programs"][0]["derivations"][1]["bump"], "stored");
+        assert_eq!(json["programs"][0]["derivations"][3]["source"], "find_program_address");
+
+        let markdown = report.to_markdown();
+        assert!(markdown.starts_with("# PDA Inventory\n\nProgram derived addresses of /project.\n\n## vault\n\n### Seed Tuples\n"));
+        assert!(markdown.contains("| `[b\"vault\", user.key()]` | - | `Deposit.vault`, `Withdraw.vault` | `deposit`, `withdraw` |\n"));
+        assert!(markdown.contains("| `Withdraw.vault` | constraint | `[b\"vault\", user.key()]` | stored (`vault.bump`) | src/lib.rs:31 |\n"));
+        assert!(markdown.contains("| `vault_signer` | `create_program_address` | `[b\"vault\", user]` | provided (`bump`) | src/helpers.rs:6 |\n"));
+
+        let empty = PdaReport::new(&[], "/project").to_markdown();
+        assert!(empty.ends_with("No PDA derivation found.\n"));
+    }
+}
@@ -0,0 +1,283 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use syn::File;
+use syn::spanned::Spanned;
+
+use crate::analyzer::Location;
+use crate::analyzer::dsl::call_graph::{CallGraph, called_names};
+use crate::analyzer::dsl::context::NodeContext;
+use crate::analyzer::dsl::query::{AstNode, AstQuery, NodeData};
+use crate::analyzer::span_utils::SpanExtractor;
+
+/// Oldest ABI version supported by the host
+pub const ABI_VERSION_MIN: i32 = 1;
+
+/// Newest ABI version supported by the host
+pub const ABI_VERSION_MAX: i32 = 1;
+
+/// Export negotiating the ABI version: `(host_min: i32, host_max: i32) -> i32`
+///
+/// Returns the version the plugin implements within the host range, or a negative value.
+pub const EXPORT_NEGOTIATE: &str = "analyzer_abi_negotiate";
+
+/// Export allocating a buffer in the plugin memory for the host input: `(len: i32) -> i32`
+pub const EXPORT_ALLOC: &str = "analyzer_alloc";
+
+/// Export returning the rule metadata as JSON: `() -> i64` (packed pointer and length)
+pub const EXPORT_METADATA: &str = "analyzer_metadata";
+
+/// Export analyzing a file: `(ptr: i32, len: i32) -> i64`
+///
+/// Receives the `FileFacts` JSON written at `ptr` and returns the `PluginFinding` JSON
+/// array (packed pointer and length).
+pub const EXPORT_CHECK: &str = "analyzer_check";
+
+/// Split a packed `(pointer << 32) | length` result into its parts
+pub fn unpack(packed: i64) -> (usize, usize) {
+    let packed = packed as u64;
+    ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize)
+}
+
+/// Rule metadata returned by `analyzer_metadata`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginMetadata {
+    /// Unique ID of the rule
+    pub id: String,
+    /// Title of the rule
+    pub title: String,
+    /// Description of the rule
+    pub description: String,
+    /// Severity of the findings (high, medium, low, informational)
+    pub severity: String,
+    /// Confidence of the findings (high if not set)
+    #[serde(default)]
+    pub confidence: Option<String>,
+    /// Recommendations for fixing the issue
+    #[serde(default)]
+    pub recommendations: Vec<String>,
+    /// Tags classifying the rule
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Links to documentation or additional resources
+    #[serde(default)]
+    pub references: Vec<String>,
+    /// CWE IDs of the weakness detected by the rule
+    #[serde(default)]
+    pub cwe: Vec<u32>,
+    /// Solana vulnerability class of the rule (e.g. missing-signer)
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Packs the rule belongs to
+    #[serde(default)]
+    pub packs: Vec<String>,
+    /// Whether the facts must include the full AST (syn-serde JSON)
+    #[serde(default)]
+    pub needs_ast: bool,
+}
+
+/// Finding returned by `analyzer_check`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginFinding {
+    /// Description of the finding
+    pub message: String,
+    /// Line of the finding (1-indexed)
+    pub line: usize,
+    /// Column of the finding
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// End line of the finding
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    /// End column of the finding
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    /// Severity overriding the rule's
+    #[serde(default)]
+    pub severity: Option<String>,
+    /// Confidence overriding the rule's (never higher)
+    #[serde(default)]
+    pub confidence: Option<String>,
+    /// Code snippet of the finding
+    #[serde(default)]
+    pub snippet: Option<String>,
+    /// Metadata attached to the finding
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// Range of an item in the file
+#[derive(Debug, Clone, Serialize)]
+pub struct SpanFacts {
+    pub line: usize,
+    pub column: Option<usize>,
+    pub end_line: Option<usize>,
+    pub end_column: Option<usize>,
+}
+
+impl From<Location> for SpanFacts {
+    fn from(location: Location) -> Self {
+        Self {
+            line: location.line,
+            column: location.column,
+            end_line: location.end_line,
+            end_column: location.end_column,
+        }
+    }
+}
+
+/// Function of the analyzed file
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionFacts {
+    pub name: String,
+    pub public: bool,
+    /// Anchor instruction handler (public function taking a `Context`)
+    pub instruction: bool,
+    /// Test code (`#[test]`, `#[cfg(test)]` module or test file)
+    pub test: bool,
+    pub attributes: Vec<String>,
+    /// Return type without whitespace (`()` if none)
+    pub return_type: String,
+    /// Functions and methods called directly
+    pub calls: BTreeSet<String>,
+    /// Functions and methods called directly or through helpers of the file
+    pub transitive_calls: BTreeSet<String>,
+    pub span: SpanFacts,
+}
+
+/// Field of a struct
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldFacts {
+    pub name: String,
+    /// Type without whitespace (e.g. `Account<'info,Vault>`)
+    pub ty: String,
+    pub attributes: Vec<String>,
+    /// Content of the `#[account(...)]` attribute (Anchor constraints), empty if none
+    pub constraints: String,
+    pub line: usize,
+}
+
+/// Top-level struct of the analyzed file
+#[derive(Debug, Clone, Serialize)]
+pub struct StructFacts {
+    pub name: String,
+    pub public: bool,
+    pub derives_accounts: bool,
+    pub test: bool,
+    pub attributes: Vec<String>,
+    pub fields: Vec<FieldFacts>,
+    pub span: SpanFacts,
+}
+
+/// Facts about a file sent to `analyzer_check`
+#[derive(Debug, Clone, Serialize)]
+pub struct FileFacts {
+    /// Negotiated ABI version
+    pub abi_version: i32,
+    pub path: String,
+    pub functions: Vec<FunctionFacts>,
+    pub structs: Vec<StructFacts>,
+    /// Full AST (syn-serde JSON), only for plugins declaring `needs_ast`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ast: Option<serde_json::Value>,
+}
+
+impl FileFacts {
+    /// Collect the facts of a file
+    pub fn new(abi_version: i32, ast: &File, file_path: &str, source_code: &str, include_ast: bool) -> Self {
+        let span_extractor = SpanExtractor::new(source_code.to_string(), file_path.to_string());
+        let graph = CallGraph::from_file(ast);
+        let instructions: BTreeSet<&str> = graph.instructions().collect();
+        let context = NodeContext::new(file_path, ast);
+        let span = |node: &AstNode<'_>| match node.get_spanned_node() {
+            Some(spanned) => SpanFacts::from(span_extractor.extract_location(spanned)),
+            None => SpanFacts::from(span_extractor.span_to_location(proc_macro2::Span::call_site())),
+        };
+
+        let functions = AstQuery::new(ast)
+            .functions()
+            .collect()
+            .iter()
+            .map(|node| {
+                let name = node.name();
+                FunctionFacts {
+                    public: node.is_public(),
+                    instruction: instructions.contains(name.as_str()),
+                    test: context.is_test_code(node),
+                    attributes: node.attrs().iter().map(attribute_name).collect(),
+                    return_type: node.return_type().unwrap_or_default(),
+                    calls: called_names(node),
+                    transitive_calls: graph
+                        .reachable_from([name.as_str()])
+                        .iter()
+                        .flat_map(|function| graph.function(function))
+                        .flat_map(called_names)
+                        .collect(),
+                    span: span(node),
+                    name,
+                }
+            })
+            .collect();
+
+        let accounts: BTreeSet<String> = AstQuery::new(ast)
+            .structs()
+            .derives_accounts()
+            .collect()
+            .iter()
+            .map(AstNode::name)
+            .collect();
+        let structs = AstQuery::new(ast)
+            .structs()
+            .collect()
+            .iter()
+            .map(|node| StructFacts {
+                name: node.name(),
+                public: node.is_public(),
+                derives_accounts: accounts.contains(&node.name()),
+                test: context.is_test_code(node),
+                attributes: node.attrs().iter().map(attribute_name).collect(),
+                fields: match node.data {
+                    NodeData::Struct(struct_item) => struct_item.fields.iter().map(field_facts).collect(),
+                    _ => Vec::new(),
+                },
+                span: span(node),
+            })
+            .collect();
+
+        Self {
+            abi_version,
+            path: file_path.to_string(),
+            functions,
+            structs,
+            ast: include_ast.then(|| serde_json::from_str(&crate::ast::json::ast_to_json(ast)).ok()).flatten(),
+        }
+    }
+}
+
+/// Helper function to get the name of an attribute (last path segment, e.g. `account`)
+fn attribute_name(attr: &syn::Attribute) -> String {
+    attr.path()
+        .segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+        .unwrap_or_default()
+}
+
+/// Helper function to collect the facts of a struct field
+fn field_facts(field: &syn::Field) -> FieldFacts {
+    use quote::ToTokens;
+
+    FieldFacts {
+        name: field.ident.as_ref().map(ToString::to_string).unwrap_or_default(),
+        ty: field.ty.to_token_stream().to_string().replace(' ', ""),
+        attributes: field.attrs.iter().map(attribute_name).collect(),
+        constraints: field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("account"))
+            .filter_map(|attr| attr.meta.require_list().ok())
+            .map(|list| list.tokens.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        line: field.span().start().line,
+    }
+}
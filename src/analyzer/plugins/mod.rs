@@ -0,0 +1,360 @@
+use anyhow::{Result, anyhow};
+use log::debug;
+use std::fmt;
+use std::path::Path;
+use syn::File;
+use wasmi::{Config, Engine, Instance, Linker, Memory, Module, Store};
+
+use crate::analyzer::dsl::builders::is_valid_rule_id;
+use crate::analyzer::dsl::query::{Metadata, MetadataValue};
+use crate::analyzer::packs::is_valid_pack_name;
+use crate::analyzer::vfs::FileSystem;
+use crate::analyzer::{Category, Confidence, Finding, Location, Rule, RuleType, Severity};
+
+pub mod abi;
+
+use abi::{FileFacts, PluginFinding, PluginMetadata};
+
+#[cfg(test)]
+mod test;
+
+/// Default fuel (roughly, executed instructions) a plugin can consume per call (stops runaway loops)
+pub const DEFAULT_FUEL: u64 = 1_000_000_000;
+
+/// Maximum size of the JSON a plugin can return
+const MAX_OUTPUT_SIZE: usize = 16 << 20;
+
+/// Error returned when a plugin cannot be loaded
+#[derive(Debug)]
+pub enum PluginError {
+    /// The plugin file cannot be read
+    Io { path: String, error: std::io::Error },
+    /// The plugin is not a valid WebAssembly module or cannot be instantiated
+    Load { path: String, error: String },
+    /// The plugin does not implement a supported ABI version
+    Abi { path: String, reason: String },
+    /// The plugin returns invalid rule metadata
+    Metadata { path: String, reason: String },
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginError::Io { path, error } => write!(f, "cannot read plugin {path}: {error}"),
+            PluginError::Load { path, error } => write!(f, "cannot load plugin {path}: {error}"),
+            PluginError::Abi { path, reason } => write!(f, "unsupported plugin ABI in {path}: {reason}"),
+            PluginError::Metadata { path, reason } => write!(f, "invalid rule metadata in plugin {path}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+/// Running instance of a plugin
+///
+/// Each call gets a fresh instance, so plugins cannot keep state between files.
+struct PluginInstance {
+    store: Store<()>,
+    instance: Instance,
+    memory: Memory,
+}
+
+impl PluginInstance {
+    /// Instantiate a module without any host import (plugins are sandboxed)
+    fn new(engine: &Engine, module: &Module, fuel: u64) -> Result<Self> {
+        let mut store = Store::new(engine, ());
+        store.set_fuel(fuel)?;
+        let instance = Linker::<()>::new(engine).instantiate_and_start(&mut store, module)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| anyhow!("missing exported `memory`"))?;
+        Ok(Self { store, instance, memory })
+    }
+
+    /// Negotiate the ABI version with the plugin
+    fn negotiate(&mut self) -> Result<i32> {
+        let negotiate = self
+            .instance
+            .get_typed_func::<(i32, i32), i32>(&self.store, abi::EXPORT_NEGOTIATE)?;
+        let version = negotiate.call(&mut self.store, (abi::ABI_VERSION_MIN, abi::ABI_VERSION_MAX))?;
+        if !(abi::ABI_VERSION_MIN..=abi::ABI_VERSION_MAX).contains(&version) {
+            return Err(anyhow!(
+                "the plugin answered version {version}, the host supports versions {} to {}",
+                abi::ABI_VERSION_MIN,
+                abi::ABI_VERSION_MAX
+            ));
+        }
+        Ok(version)
+    }
+
+    /// Call the metadata export and read the JSON it returns
+    fn metadata(&mut self) -> Result<Vec<u8>> {
+        let metadata = self.instance.get_typed_func::<(), i64>(&self.store, abi::EXPORT_METADATA)?;
+        let packed = metadata.call(&mut self.store, ())?;
+        self.read_output(packed)
+    }
+
+    /// Write the input in the plugin memory, call the check export and read the JSON it returns
+    fn check(&mut self, input: &[u8]) -> Result<Vec<u8>> {
+        let len = i32::try_from(input.len()).map_err(|_| anyhow!("input too large ({} bytes)", input.len()))?;
+        let alloc = self.instance.get_typed_func::<i32, i32>(&self.store, abi::EXPORT_ALLOC)?;
+        let ptr = alloc.call(&mut self.store, len)?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, input)
+            .map_err(|e| anyhow!("cannot write the input at {ptr}: {e}"))?;
+
+        let check = self.instance.get_typed_func::<(i32, i32), i64>(&self.store, abi::EXPORT_CHECK)?;
+        let packed = check.call(&mut self.store, (ptr, len))?;
+        self.read_output(packed)
+    }
+
+    /// Helper function to read a packed `(pointer, length)` buffer from the plugin memory
+    fn read_output(&self, packed: i64) -> Result<Vec<u8>> {
+        let (ptr, len) = abi::unpack(packed);
+        if len > MAX_OUTPUT_SIZE {
+            return Err(anyhow!("output too large ({len} bytes)"));
+        }
+        let mut buffer = vec![0; len];
+        self.memory
+            .read(&self.store, ptr, &mut buffer)
+            .map_err(|e| anyhow!("cannot read the output at {ptr}: {e}"))?;
+        Ok(buffer)
+    }
+}
+
+/// Rule implemented by a WebAssembly plugin
+///
+/// Plugins can be written in any language compiling to WebAssembly. They export their
+/// `memory` and the functions described in [`abi`]:
+///
+/// 1. `analyzer_abi_negotiate(host_min, host_max)` returns the ABI version implemented
+///    by the plugin (the plugin is rejected if it is outside the host range)
+/// 2. `analyzer_metadata()` returns the rule metadata ([`PluginMetadata`] JSON)
+/// 3. for each file, `analyzer_alloc(len)` reserves a buffer where the host writes the
+///    file facts ([`FileFacts`] JSON), then `analyzer_check(ptr, len)` returns the
+///    findings ([`PluginFinding`] JSON array)
+///
+/// Buffers are returned as `(pointer << 32) | length` packed in an `i64`. Plugins get no
+/// host imports and a bounded amount of fuel per call.
+pub struct WasmRule {
+    id: String,
+    title: String,
+    description: String,
+    severity: Severity,
+    confidence: Confidence,
+    recommendations: Vec<String>,
+    tags: Vec<String>,
+    references: Vec<String>,
+    cwe: Vec<u32>,
+    category: Option<Category>,
+    packs: Vec<String>,
+    needs_ast: bool,
+    abi_version: i32,
+    fuel: u64,
+    path: String,
+    engine: Engine,
+    module: Module,
+}
+
+impl WasmRule {
+    /// Load a plugin rule from a file
+    #[cfg(feature = "native")]
+    pub fn from_file(path: &Path) -> std::result::Result<Self, PluginError> {
+        Self::load(&crate::analyzer::vfs::OsFileSystem, path)
+    }
+
+    /// Load a plugin rule from a file of a file system
+    pub fn load(fs: &dyn FileSystem, path: &Path) -> std::result::Result<Self, PluginError> {
+        let path_str = path.display().to_string();
+        let wasm = fs.read(path).map_err(|error| PluginError::Io {
+            path: path_str.clone(),
+            error,
+        })?;
+        Self::from_bytes(&wasm, &path_str)
+    }
+
+    /// Compile a plugin, negotiate its ABI version and read its metadata
+    ///
+    /// Accepts WebAssembly binaries as well as the text format.
+    pub fn from_bytes(wasm: &[u8], path: &str) -> std::result::Result<Self, PluginError> {
+        let load = |error: String| PluginError::Load {
+            path: path.to_string(),
+            error,
+        };
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm).map_err(|e| load(e.to_string()))?;
+        let mut plugin = PluginInstance::new(&engine, &module, DEFAULT_FUEL).map_err(|e| load(e.to_string()))?;
+
+        let abi_version = plugin.negotiate().map_err(|e| PluginError::Abi {
+            path: path.to_string(),
+            reason: e.to_string(),
+        })?;
+
+        let invalid = |reason: String| PluginError::Metadata {
+            path: path.to_string(),
+            reason,
+        };
+        let metadata = plugin.metadata().map_err(|e| invalid(format!("`{}` failed: {e}", abi::EXPORT_METADATA)))?;
+        let metadata: PluginMetadata = serde_json::from_slice(&metadata).map_err(|e| invalid(e.to_string()))?;
+
+        if !is_valid_rule_id(&metadata.id) {
+            return Err(invalid(format!("invalid rule ID '{}'", metadata.id)));
+        }
+        for (field, value) in [("title", &metadata.title), ("description", &metadata.description)] {
+            if value.trim().is_empty() {
+                return Err(invalid(format!("`{field}` must be a non-empty string")));
+            }
+        }
+        if let Some(pack) = metadata.packs.iter().find(|pack| !is_valid_pack_name(pack)) {
+            return Err(invalid(format!("invalid pack name '{pack}'")));
+        }
+        let severity = metadata.severity.parse().map_err(invalid)?;
+        let confidence = match &metadata.confidence {
+            Some(confidence) => confidence.parse().map_err(invalid)?,
+            None => Confidence::High,
+        };
+        let category = match &metadata.category {
+            Some(category) => Some(category.parse().map_err(invalid)?),
+            None => None,
+        };
+
+        Ok(Self {
+            id: metadata.id,
+            title: metadata.title,
+            description: metadata.description,
+            severity,
+            confidence,
+            recommendations: metadata.recommendations,
+            tags: metadata.tags,
+            references: metadata.references,
+            cwe: metadata.cwe,
+            category,
+            packs: metadata.packs,
+            needs_ast: metadata.needs_ast,
+            abi_version,
+            fuel: DEFAULT_FUEL,
+            path: path.to_string(),
+            engine,
+            module,
+        })
+    }
+
+    /// Sets the fuel the plugin can consume per file
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    /// Helper function to convert a finding returned by the plugin
+    fn convert_finding(&self, finding: PluginFinding, file_path: &str, source_code: &str) -> Result<Finding> {
+        let severity = match &finding.severity {
+            Some(severity) => severity.parse().map_err(|e: String| anyhow!(e))?,
+            None => self.severity.clone(),
+        };
+        let confidence = match &finding.confidence {
+            Some(confidence) => confidence.parse::<Confidence>().map_err(|e: String| anyhow!(e))?,
+            None => self.confidence,
+        };
+        let metadata: Metadata = finding
+            .metadata
+            .into_iter()
+            .map(|(key, value)| (key, MetadataValue::Text(value)))
+            .collect();
+        // Without a snippet from the plugin, show the line of the finding
+        let code_snippet = finding.snippet.or_else(|| {
+            let line = source_code.lines().nth(finding.line.checked_sub(1)?)?;
+            Some(line.trim().to_string())
+        });
+
+        Ok(Finding {
+            description: finding.message,
+            severity,
+            // Findings never report a higher confidence than the rule's
+            confidence: confidence.min(self.confidence),
+            location: Location::new_precise(
+                file_path.to_string(),
+                finding.line,
+                finding.column,
+                finding.end_line,
+                finding.end_column,
+            ),
+            code_snippet,
+            recommendations: self.recommendations.clone(),
+            fixes: Vec::new(),
+            metadata,
+            rules: Vec::new(),
+            fingerprint: None,
+        })
+    }
+}
+
+impl Rule for WasmRule {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity.clone()
+    }
+
+    fn rule_type(&self) -> RuleType {
+        RuleType::General
+    }
+
+    fn recommendations(&self) -> Vec<String> {
+        self.recommendations.clone()
+    }
+
+    fn tags(&self) -> Vec<String> {
+        self.tags.clone()
+    }
+
+    fn references(&self) -> Vec<String> {
+        self.references.clone()
+    }
+
+    fn cwe(&self) -> Vec<u32> {
+        self.cwe.clone()
+    }
+
+    fn category(&self) -> Option<Category> {
+        self.category
+    }
+
+    fn packs(&self) -> Vec<String> {
+        self.packs.clone()
+    }
+
+    fn execute(&self, ast: &File, file_path: &str) -> Result<Vec<Finding>> {
+        self.execute_with_source(ast, file_path, "")
+    }
+
+    fn execute_with_source(&self, ast: &File, file_path: &str, source_code: &str) -> Result<Vec<Finding>> {
+        debug!("Executing plugin rule {} ({}) in {file_path}", self.id, self.path);
+        let facts = FileFacts::new(self.abi_version, ast, file_path, source_code, self.needs_ast);
+        let input = serde_json::to_vec(&facts)?;
+
+        // A failing plugin (trap, fuel exhausted, invalid output) only loses its own findings
+        let output = PluginInstance::new(&self.engine, &self.module, self.fuel)
+            .and_then(|mut plugin| plugin.check(&input))
+            .map_err(|e| anyhow!("plugin {} failed: {e}", self.path))?;
+        let findings: Vec<PluginFinding> = serde_json::from_slice(&output)
+            .map_err(|e| anyhow!("plugin {} returned invalid findings: {e}", self.path))?;
+
+        findings
+            .into_iter()
+            .map(|finding| self.convert_finding(finding, file_path, source_code))
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| anyhow!("plugin {} returned an invalid finding: {e}", self.path))
+    }
+}
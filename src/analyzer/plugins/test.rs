@@ -0,0 +1,138 @@
+use crate::analyzer::plugins::abi::{ABI_VERSION_MAX, FileFacts};
+use crate::analyzer::plugins::{PluginError, WasmRule};
+use crate::analyzer::{Confidence, Rule, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    move_funds(&ctx, amount)
+}
+
+fn move_funds(ctx: &Context<Withdraw>, amount: u64) -> Result<()> {
+    transfer(ctx, amount)
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, has_one = authority)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+"#;
+
+    const METADATA: &str = r#"{"id":"wasm-test-rule","title":"Wasm Test Rule","description":"Rule implemented by a plugin","severity":"medium","confidence":"medium"}"#;
+
+    const FINDINGS: &str = r#"[{"message":"Withdraw found by the plugin","line":2,"confidence":"high","metadata":{"plugin":"test"}}]"#;
+
+    /// Helper function to build a plugin in the text format
+    ///
+    /// The plugin answers `version` to the negotiation, returns `metadata` and, from
+    /// `analyzer_check`, `findings` after running `check_body`.
+    fn plugin(version: i32, metadata: &str, findings: &str, check_body: &str) -> String {
+        let escape = |json: &str| json.replace('\\', "\\\\").replace('"', "\\\"");
+        format!(
+            r#"(module
+  (memory (export "memory") 4)
+  (data (i32.const 1024) "{metadata}")
+  (data (i32.const 8192) "{findings}")
+  (func (export "analyzer_abi_negotiate") (param i32 i32) (result i32)
+    i32.const {version})
+  (func (export "analyzer_alloc") (param i32) (result i32)
+    i32.const 65536)
+  (func (export "analyzer_metadata") (result i64)
+    i64.const {metadata_packed})
+  (func (export "analyzer_check") (param i32 i32) (result i64)
+    {check_body}
+    i64.const {findings_packed}))"#,
+            metadata = escape(metadata),
+            findings = escape(findings),
+            metadata_packed = (1024_i64 << 32) | metadata.len() as i64,
+            findings_packed = (8192_i64 << 32) | findings.len() as i64,
+        )
+    }
+
+    fn load(wat: &str) -> Result<WasmRule, PluginError> {
+        WasmRule::from_bytes(wat.as_bytes(), "test.wasm")
+    }
+
+    #[test]
+    fn test_plugin_rule() {
+        let rule = load(&plugin(ABI_VERSION_MAX, METADATA, FINDINGS, "")).unwrap();
+        assert_eq!(rule.id(), "wasm-test-rule");
+        assert_eq!(rule.severity(), Severity::Medium);
+
+        let ast = syn::parse_file(SOURCE).unwrap();
+        let findings = rule.execute_with_source(&ast, "programs/vault/src/lib.rs", SOURCE).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].description, "Withdraw found by the plugin");
+        assert_eq!(findings[0].location.line, 2);
+        assert_eq!(findings[0].location.file, "programs/vault/src/lib.rs");
+        // Findings cannot raise the confidence of the rule
+        assert_eq!(findings[0].confidence, Confidence::Medium);
+        assert_eq!(
+            findings[0].code_snippet.as_deref(),
+            Some("pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {")
+        );
+        assert!(findings[0].metadata.contains_key("plugin"));
+    }
+
+    #[test]
+    fn test_plugin_abi_negotiation() {
+        let error = load(&plugin(ABI_VERSION_MAX + 1, METADATA, FINDINGS, "")).err().unwrap();
+        assert!(matches!(error, PluginError::Abi { .. }), "{error}");
+
+        let error = load(&plugin(-1, METADATA, FINDINGS, "")).err().unwrap();
+        assert!(matches!(error, PluginError::Abi { .. }), "{error}");
+    }
+
+    #[test]
+    fn test_invalid_plugins() {
+        let error = load("not a module").err().unwrap();
+        assert!(matches!(error, PluginError::Load { .. }), "{error}");
+
+        let error = load(&plugin(ABI_VERSION_MAX, r#"{"id":"Bad Id"}"#, FINDINGS, "")).err().unwrap();
+        assert!(matches!(error, PluginError::Metadata { .. }), "{error}");
+
+        let metadata = METADATA.replace("medium", "critical");
+        let error = load(&plugin(ABI_VERSION_MAX, &metadata, FINDINGS, "")).err().unwrap();
+        assert!(matches!(error, PluginError::Metadata { .. }), "{error}");
+    }
+
+    #[test]
+    fn test_failing_plugin_is_reported() {
+        let ast = syn::parse_file(SOURCE).unwrap();
+
+        // Runaway loops are stopped when the plugin runs out of fuel
+        let rule = load(&plugin(ABI_VERSION_MAX, METADATA, FINDINGS, "(loop (br 0))")).unwrap().with_fuel(100_000);
+        assert!(rule.execute_with_source(&ast, "lib.rs", SOURCE).is_err());
+
+        let rule = load(&plugin(ABI_VERSION_MAX, METADATA, FINDINGS, "unreachable")).unwrap();
+        assert!(rule.execute_with_source(&ast, "lib.rs", SOURCE).is_err());
+
+        let rule = load(&plugin(ABI_VERSION_MAX, METADATA, r#"{"message":1}"#, "")).unwrap();
+        assert!(rule.execute_with_source(&ast, "lib.rs", SOURCE).is_err());
+    }
+
+    #[test]
+    fn test_file_facts() {
+        let ast = syn::parse_file(SOURCE).unwrap();
+        let facts = FileFacts::new(ABI_VERSION_MAX, &ast, "lib.rs", SOURCE, false);
+        let json = serde_json::to_value(&facts).unwrap();
+
+        assert_eq!(json["abi_version"], ABI_VERSION_MAX);
+        assert!(json.get("ast").is_none());
+        assert_eq!(json["functions"][0]["name"], "withdraw");
+        assert_eq!(json["functions"][0]["instruction"], true);
+        assert_eq!(json["functions"][0]["span"]["line"], 2);
+        assert!(facts.functions[0].transitive_calls.contains("transfer"));
+        assert!(!facts.functions[0].calls.contains("transfer"));
+        assert_eq!(json["structs"][0]["derives_accounts"], true);
+        assert_eq!(json["structs"][0]["fields"][0]["constraints"], "mut , has_one = authority");
+
+        let facts = FileFacts::new(ABI_VERSION_MAX, &ast, "lib.rs", SOURCE, true);
+        assert!(facts.ast.is_some());
+    }
+}
@@ -0,0 +1,142 @@
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+mod test;
+
+/// Width of the bar itself, between its brackets
+const BAR_WIDTH: usize = 24;
+
+/// Minimum time between two redraws of the progress bar, the last one aside
+const REDRAW_INTERVAL: Duration = Duration::from_millis(80);
+
+/// Progress of an analysis, reported file by file to a [`ProgressSink`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress<'a> {
+    /// A file was read (`count` files so far, the total is not known yet)
+    Read { file: &'a Path, count: usize },
+    /// The rules ran on a file (`done` of the `total` files, skipped test files included)
+    Analyzed { file: &'a Path, done: usize, total: usize },
+}
+
+/// Callback receiving the progress of an analysis (see [`super::Analyzer::with_progress_sink`])
+pub type ProgressSink = Box<dyn Fn(Progress<'_>) + Send + Sync>;
+
+/// Returns the line showing a progress, at most `width` characters
+/// (`Analyzing [=========>      ] 12/40 programs/vault/src/lib.rs`)
+pub fn render(progress: &Progress<'_>, width: usize) -> String {
+    let (status, file) = match progress {
+        Progress::Read { file, count } => (format!("Reading files: {count}"), file),
+        Progress::Analyzed { file, done, total } => {
+            let filled = (BAR_WIDTH * done).checked_div(*total).unwrap_or(BAR_WIDTH).min(BAR_WIDTH);
+            let bar = match filled {
+                BAR_WIDTH => "=".repeat(BAR_WIDTH),
+                filled => format!("{}>{}", "=".repeat(filled), " ".repeat(BAR_WIDTH - filled - 1)),
+            };
+            (format!("Analyzing [{bar}] {done}/{total}"), file)
+        }
+    };
+
+    // The end of the path is the part telling the files apart
+    let file = file.to_string_lossy();
+    let room = width.saturating_sub(status.chars().count() + 1);
+    let length = file.chars().count();
+    match room {
+        0 | 1 => status.chars().take(width).collect(),
+        room if length > room => format!("{status} …{}", file.chars().skip(length - room + 1).collect::<String>()),
+        _ => format!("{status} {file}"),
+    }
+}
+
+/// Progress bar redrawn on one line of stderr while the files are parsed and analyzed
+///
+/// Log lines written while the bar is visible clear it first (see [`ProgressBar::is_visible`]),
+/// and the bar is drawn again with the next progress.
+#[derive(Debug)]
+pub struct ProgressBar {
+    /// Width of the line, from `COLUMNS` (80 otherwise)
+    width: usize,
+    /// Whether the bar is drawn on the current line of stderr
+    visible: AtomicBool,
+    /// Time of the last redraw
+    last_draw: Mutex<Option<Instant>>,
+}
+
+impl ProgressBar {
+    /// Progress bar on stderr, if it is a terminal
+    pub fn stderr() -> Option<Arc<Self>> {
+        if !std::io::stderr().is_terminal() {
+            return None;
+        }
+        let width = std::env::var("COLUMNS").ok().and_then(|columns| columns.parse().ok()).unwrap_or(80);
+        // The last column is left empty, some terminals wrap when it is written
+        Some(Arc::new(Self { width: width.max(20) - 1, visible: AtomicBool::new(false), last_draw: Mutex::new(None) }))
+    }
+
+    /// Sink drawing the progress reported by an analysis on the bar
+    pub fn sink(self: &Arc<Self>) -> ProgressSink {
+        let bar = Arc::clone(self);
+        Box::new(move |progress| bar.update(&progress))
+    }
+
+    /// Draw a progress, unless the bar was drawn less than [`REDRAW_INTERVAL`] ago (the end of
+    /// the analysis is always drawn)
+    pub fn update(&self, progress: &Progress<'_>) {
+        let last = matches!(progress, Progress::Analyzed { done, total, .. } if done == total);
+        let mut last_draw = self.last_draw.lock().unwrap_or_else(|e| e.into_inner());
+        if !last && last_draw.is_some_and(|time| time.elapsed() < REDRAW_INTERVAL) {
+            return;
+        }
+        *last_draw = Some(Instant::now());
+
+        let mut stderr = std::io::stderr().lock();
+        let _ = write!(stderr, "\r\x1b[2K{}", render(progress, self.width));
+        let _ = stderr.flush();
+        self.visible.store(true, Ordering::Relaxed);
+    }
+
+    /// Check if the bar is drawn on the current line of stderr, which a log line should clear
+    /// (with `\r\x1b[2K`)
+    pub fn is_visible(&self) -> bool {
+        self.visible.load(Ordering::Relaxed)
+    }
+
+    /// Record that the line of the bar was cleared (by a log line)
+    pub fn hidden(&self) {
+        self.visible.store(false, Ordering::Relaxed);
+    }
+
+    /// Clear the bar once the analysis is over
+    pub fn finish(&self) {
+        if self.visible.swap(false, Ordering::Relaxed) {
+            let mut stderr = std::io::stderr().lock();
+            let _ = write!(stderr, "\r\x1b[2K");
+            let _ = stderr.flush();
+        }
+    }
+}
+
+/// Counter of the analyzed files reporting them to a progress sink, shared by the threads
+/// analyzing the files
+pub(crate) struct FileCounter<'a> {
+    sink: Option<&'a ProgressSink>,
+    done: AtomicUsize,
+    total: usize,
+}
+
+impl<'a> FileCounter<'a> {
+    pub(crate) fn new(sink: Option<&'a ProgressSink>, total: usize) -> Self {
+        Self { sink, done: AtomicUsize::new(0), total }
+    }
+
+    /// Count a file as analyzed (or skipped)
+    pub(crate) fn analyzed(&self, file: &Path) {
+        if let Some(sink) = self.sink {
+            let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+            sink(Progress::Analyzed { file, done: done.min(self.total), total: self.total });
+        }
+    }
+}
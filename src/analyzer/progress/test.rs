@@ -0,0 +1,45 @@
+use std::path::Path;
+
+use crate::analyzer::progress::{FileCounter, Progress, ProgressSink, render};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let file = Path::new("programs/vault/src/lib.rs");
+        assert_eq!(render(&Progress::Read { file, count: 3 }, 80), "Reading files: 3 programs/vault/src/lib.rs");
+        assert_eq!(
+            render(&Progress::Analyzed { file, done: 12, total: 40 }, 80),
+            "Analyzing [=======>                ] 12/40 programs/vault/src/lib.rs"
+        );
+        assert_eq!(
+            render(&Progress::Analyzed { file, done: 40, total: 40 }, 80),
+            "Analyzing [========================] 40/40 programs/vault/src/lib.rs"
+        );
+
+        // Long paths keep their end, and the status is cut last
+        assert_eq!(render(&Progress::Read { file, count: 3 }, 30), "Reading files: 3 …t/src/lib.rs");
+        assert_eq!(render(&Progress::Read { file, count: 3 }, 10), "Reading fi");
+    }
+
+    #[test]
+    fn test_file_counter() {
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = std::sync::Arc::clone(&events);
+        let sink: ProgressSink = Box::new(move |progress| {
+            if let Progress::Analyzed { done, total, .. } = progress {
+                recorded.lock().unwrap().push((done, total));
+            }
+        });
+
+        let counter = FileCounter::new(Some(&sink), 2);
+        counter.analyzed(Path::new("a.rs"));
+        counter.analyzed(Path::new("b.rs"));
+        assert_eq!(*events.lock().unwrap(), [(1, 2), (2, 2)]);
+
+        // Without a sink, nothing is counted
+        FileCounter::new(None, 2).analyzed(Path::new("a.rs"));
+    }
+}
@@ -0,0 +1,182 @@
+use serde::Serialize;
+use std::path::Path;
+
+use crate::analyzer::fingerprint::relative_path;
+use crate::analyzer::{AnalysisResult, Finding, Location, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Diagnostics of an analysis in reviewdog's rdjson format (`--format rdjson`)
+///
+/// ```json
+/// {
+///   "source": { "name": "rust-solana-analyzer" },
+///   "diagnostics": [
+///     {
+///       "message": "...",
+///       "location": { "path": "programs/vault/src/lib.rs", "range": { "start": { "line": 12, "column": 5 }, ... } },
+///       "severity": "ERROR",
+///       "code": { "value": "division-by-zero" },
+///       "suggestions": [{ "range": { ... }, "text": "amount.checked_div(shares)" }]
+///     }
+///   ]
+/// }
+/// ```
+///
+/// High findings are errors, medium findings warnings, and the others infos (see [`severity`]).
+/// The edits of the first fix of a finding become its suggestions, which reviewdog posts as
+/// suggested changes.
+#[derive(Debug)]
+pub struct RdjsonReport<'a> {
+    result: &'a AnalysisResult,
+    base: &'a Path,
+}
+
+/// rdjson `DiagnosticResult`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiagnosticResult {
+    /// Tool that produced the diagnostics
+    pub source: Source,
+    /// Diagnostics, one per finding
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// rdjson `Source`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Source {
+    /// Name of the tool
+    pub name: &'static str,
+}
+
+/// rdjson `Diagnostic`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// Description of the finding, with the IDs of the rules that reported it
+    pub message: String,
+    /// Span of the finding
+    pub location: DiagnosticLocation,
+    /// `ERROR`, `WARNING` or `INFO`
+    pub severity: &'static str,
+    /// Rule that reported the finding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Code>,
+    /// Edits of the fix of the finding
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// rdjson `Location`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiagnosticLocation {
+    /// Path relative to the root of the repository
+    pub path: String,
+    /// Span in the file
+    pub range: Range,
+}
+
+/// rdjson `Range`, the end is exclusive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Range {
+    pub start: Position,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<Position>,
+}
+
+/// rdjson `Position` (1-indexed line and column)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Position {
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+/// rdjson `Code`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Code {
+    /// Rule ID
+    pub value: String,
+    /// First reference of the rule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// rdjson `Suggestion`, the replacement of a range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Suggestion {
+    pub range: Range,
+    pub text: String,
+}
+
+impl<'a> RdjsonReport<'a> {
+    /// Creates the diagnostics of an analysis, with the paths relative to `base` (the root of
+    /// the repository, where reviewdog runs)
+    pub fn new(result: &'a AnalysisResult, base: &'a Path) -> Self {
+        Self { result, base }
+    }
+
+    /// Returns the diagnostics of the analysis
+    pub fn diagnostics(&self) -> DiagnosticResult {
+        DiagnosticResult {
+            source: Source { name: env!("CARGO_PKG_NAME") },
+            diagnostics: self.result.findings.iter().map(|finding| self.diagnostic(finding)).collect(),
+        }
+    }
+
+    /// Returns the report as JSON
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(&self.diagnostics()).expect("rdjson diagnostics serialize to JSON");
+        json.push('\n');
+        json
+    }
+
+    fn diagnostic(&self, finding: &Finding) -> Diagnostic {
+        let path = relative_path(&finding.location.file, Some(self.base));
+        let mut message = finding.description.clone();
+        if finding.rules.len() > 1 {
+            message.push_str(&format!(" [{}]", finding.rule_ids().join(", ")));
+        }
+
+        // Alternative fixes would conflict, reviewdog gets the first one
+        let suggestions = finding
+            .fixes
+            .first()
+            .map(|fix| {
+                fix.edits
+                    .iter()
+                    .map(|edit| Suggestion { range: range(&edit.location), text: edit.replacement.clone() })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Diagnostic {
+            message,
+            location: DiagnosticLocation {
+                path: path.strip_prefix("./").map(str::to_string).unwrap_or(path),
+                range: range(&finding.location),
+            },
+            severity: severity(&finding.severity),
+            code: finding.rule().map(|rule| Code { value: rule.id.clone(), url: rule.references.first().cloned() }),
+            suggestions,
+        }
+    }
+}
+
+/// rdjson range of a location
+///
+/// The parser's columns start at 0, rdjson's at 1.
+fn range(location: &Location) -> Range {
+    Range {
+        start: Position { line: location.line, column: location.column.map(|column| column + 1) },
+        end: location.end_line.map(|line| Position { line, column: location.end_column.map(|column| column + 1) }),
+    }
+}
+
+/// rdjson severity of a finding: `ERROR`, `WARNING` or `INFO`
+pub fn severity(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::High => "ERROR",
+        Severity::Medium => "WARNING",
+        Severity::Low | Severity::Informational => "INFO",
+    }
+}
@@ -0,0 +1,64 @@
+use std::path::Path;
+
+use crate::analyzer::rdjson::RdjsonReport;
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Finding, Fix, Location, RuleInfo, Severity, TextEdit};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(severity: Severity, file: &str, line: usize, fixes: Vec<Fix>) -> Finding {
+        FindingBuilder::new("Division by zero")
+            .severity(severity)
+            .span(file, line, 4, 19)
+            .snippet("amount / shares")
+            .fixes(fixes)
+            .rule(RuleInfo {
+                id: "division-by-zero".to_string(),
+                title: "Division by zero".to_string(),
+                references: vec!["https://example.com/division-by-zero".to_string()],
+                ..Default::default()
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let edit = TextEdit {
+            location: Location::new_precise("/repo/src/lib.rs".to_string(), 12, Some(4), Some(12), Some(19)),
+            replacement: "amount.checked_div(shares)".to_string(),
+        };
+        let result = AnalysisResult {
+            findings: vec![
+                finding(Severity::High, "/repo/programs/vault/src/lib.rs", 12, vec![Fix::new("Use checked_div", vec![edit])]),
+                finding(Severity::Low, "./src/lib.rs", 3, Vec::new()),
+            ],
+            stats: AnalysisStats::default(),
+        };
+
+        let report = RdjsonReport::new(&result, Path::new("/repo"));
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+
+        assert_eq!(json["source"]["name"], "rust-solana-analyzer");
+        assert_eq!(
+            json["diagnostics"][0],
+            serde_json::json!({
+                "message": "Division by zero",
+                "location": {
+                    "path": "programs/vault/src/lib.rs",
+                    "range": { "start": { "line": 12, "column": 5 }, "end": { "line": 12, "column": 20 } }
+                },
+                "severity": "ERROR",
+                "code": { "value": "division-by-zero", "url": "https://example.com/division-by-zero" },
+                "suggestions": [{
+                    "range": { "start": { "line": 12, "column": 5 }, "end": { "line": 12, "column": 20 } },
+                    "text": "amount.checked_div(shares)"
+                }]
+            })
+        );
+        assert_eq!(json["diagnostics"][1]["severity"], "INFO");
+        assert_eq!(json["diagnostics"][1]["location"]["path"], "src/lib.rs");
+        assert!(json["diagnostics"][1].get("suggestions").is_none());
+    }
+}
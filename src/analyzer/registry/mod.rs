@@ -0,0 +1,182 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use crate::analyzer::dsl::builders::RuleBuildError;
+use crate::analyzer::dsl::params::ParamValue;
+use crate::analyzer::{Category, Rule, RuleExample, RuleType, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Metadata of a registered rule, readable without running it (rule listings, documentation)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleMetadata {
+    /// Unique ID of the rule
+    pub id: String,
+    /// Title of the rule
+    pub title: String,
+    /// What the rule detects
+    pub description: String,
+    /// Severity declared by the rule (before the user's overrides)
+    pub severity: Severity,
+    /// Type of the rule
+    pub rule_type: RuleType,
+    /// Tags classifying the rule
+    pub tags: Vec<String>,
+    /// Links to documentation or additional resources
+    pub references: Vec<String>,
+    /// How to fix the findings of the rule
+    pub recommendations: Vec<String>,
+    /// Why the issue matters
+    pub rationale: Option<String>,
+    /// Vulnerable and safe code examples
+    pub examples: Vec<RuleExample>,
+    /// CWE IDs of the weakness detected by the rule
+    pub cwe: Vec<u32>,
+    /// Solana vulnerability class of the rule
+    pub category: Option<Category>,
+    /// Packs the rule belongs to
+    pub packs: Vec<String>,
+    /// Configuration parameters of the rule
+    pub params: Vec<ParamMetadata>,
+    /// Versions of anchor-lang the rule applies to (every version if `None`)
+    pub anchor_versions: Option<String>,
+    /// Whether the rule analyzes the whole program instead of each file
+    pub project: bool,
+    /// Whether the configuration selects the rule (rules filtered out are still registered)
+    pub enabled: bool,
+}
+
+/// Configuration parameter declared by a rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamMetadata {
+    /// Name of the parameter
+    pub name: String,
+    /// What the parameter controls
+    pub description: String,
+    /// Value used when the user does not configure the parameter
+    pub default: ParamValue,
+    /// Value the rule runs with
+    pub value: ParamValue,
+}
+
+impl RuleMetadata {
+    /// Collects the metadata of a rule
+    pub fn from_rule(rule: &dyn Rule, enabled: bool) -> Self {
+        let params = rule
+            .params()
+            .map(|params| {
+                params
+                    .iter()
+                    .map(|(name, param)| ParamMetadata {
+                        name: name.to_string(),
+                        description: param.description.clone(),
+                        default: param.default.clone(),
+                        value: param.value.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            id: rule.id().to_string(),
+            title: rule.title().to_string(),
+            description: rule.description().to_string(),
+            severity: rule.severity(),
+            rule_type: rule.rule_type(),
+            tags: rule.tags(),
+            references: rule.references(),
+            recommendations: rule.recommendations(),
+            rationale: rule.rationale(),
+            examples: rule.examples(),
+            cwe: rule.cwe(),
+            category: rule.category(),
+            packs: rule.packs(),
+            params,
+            anchor_versions: rule.anchor_versions().map(ToString::to_string),
+            project: rule.is_project_rule(),
+            enabled,
+        }
+    }
+}
+
+/// Rules known to the engine, in the order they were registered
+///
+/// Every rule is kept, including the rules the configuration filters out, so rule IDs are
+/// unique across all the sources of rules (built-in, plugins, templates, scripts) and the
+/// metadata of every rule can be listed. Only the enabled rules run.
+#[derive(Default)]
+pub struct RuleRegistry {
+    /// Registered rules, with whether the configuration selects them
+    rules: Vec<(Arc<dyn Rule>, bool)>,
+}
+
+impl RuleRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a rule, failing if a rule with the same ID is already registered
+    pub fn register(&mut self, rule: Arc<dyn Rule>, enabled: bool) -> Result<(), RuleBuildError> {
+        if self.contains(rule.id()) {
+            return Err(RuleBuildError::DuplicateId {
+                id: rule.id().to_string(),
+            });
+        }
+        self.rules.push((rule, enabled));
+        Ok(())
+    }
+
+    /// Check if a rule with the given ID is registered, enabled or not
+    pub fn contains(&self, id: &str) -> bool {
+        self.rules.iter().any(|(rule, _)| rule.id() == id)
+    }
+
+    /// Check if a rule with the given ID is registered and enabled
+    pub fn is_enabled(&self, id: &str) -> bool {
+        self.rules.iter().any(|(rule, enabled)| *enabled && rule.id() == id)
+    }
+
+    /// Returns the rule with the given ID, enabled or not
+    pub fn get(&self, id: &str) -> Option<&Arc<dyn Rule>> {
+        self.rules.iter().map(|(rule, _)| rule).find(|rule| rule.id() == id)
+    }
+
+    /// Returns the enabled rules
+    pub fn enabled(&self) -> impl Iterator<Item = &Arc<dyn Rule>> {
+        self.rules.iter().filter(|(_, enabled)| *enabled).map(|(rule, _)| rule)
+    }
+
+    /// Returns the metadata of every registered rule, enabled or not
+    pub fn metadata(&self) -> impl Iterator<Item = RuleMetadata> + '_ {
+        self.rules.iter().map(|(rule, enabled)| RuleMetadata::from_rule(rule.as_ref(), *enabled))
+    }
+
+    /// Returns the metadata of the rule with the given ID
+    pub fn metadata_of(&self, id: &str) -> Option<RuleMetadata> {
+        self.rules
+            .iter()
+            .find(|(rule, _)| rule.id() == id)
+            .map(|(rule, enabled)| RuleMetadata::from_rule(rule.as_ref(), *enabled))
+    }
+
+    /// Returns the packs declared by the registered rules, enabled or not
+    pub fn packs(&self) -> BTreeSet<String> {
+        self.rules.iter().flat_map(|(rule, _)| rule.packs()).collect()
+    }
+
+    /// Returns the number of enabled rules
+    pub fn enabled_count(&self) -> usize {
+        self.rules.iter().filter(|(_, enabled)| *enabled).count()
+    }
+
+    /// Returns the number of registered rules, enabled or not
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Check if no rule is registered
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
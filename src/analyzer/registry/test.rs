@@ -0,0 +1,94 @@
+use crate::analyzer::dsl::builders::RuleBuildError;
+use crate::analyzer::dsl::params::ParamValue;
+use crate::analyzer::dsl::{AstQuery, RuleBuilder};
+use crate::analyzer::registry::{ParamMetadata, RuleRegistry};
+use crate::analyzer::{RuleEngine, RuleEngineConfig, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: &str) -> RuleBuilder {
+        RuleBuilder::new()
+            .id(id)
+            .title("Test Rule")
+            .description("Matches every function")
+            .severity(Severity::Low)
+            .tags(vec!["testing"])
+            .references(vec!["https://example.com/test-rule"])
+            .dsl_query(|ast, _file_path, _span_extractor| AstQuery::new(ast).functions())
+    }
+
+    #[test]
+    fn test_registry_rejects_duplicate_ids() {
+        let mut registry = RuleRegistry::new();
+        assert!(registry.register(rule("solana-test-rule").build().unwrap(), false).is_ok());
+        assert_eq!(
+            registry.register(rule("solana-test-rule").build().unwrap(), true),
+            Err(RuleBuildError::DuplicateId { id: "solana-test-rule".to_string() })
+        );
+        assert_eq!(registry.len(), 1);
+        assert!(registry.contains("solana-test-rule"));
+        assert!(!registry.is_enabled("solana-test-rule"));
+        assert_eq!(registry.enabled().count(), 0);
+    }
+
+    #[test]
+    fn test_registry_metadata() {
+        let config = RuleEngineConfig {
+            ignore_rules: vec!["solana-ignored-rule".to_string()],
+            ..Default::default()
+        };
+        let mut engine = RuleEngine::new(config);
+        rule("solana-test-rule")
+            .param("max_depth", 3, "Maximum depth")
+            .register(&mut engine)
+            .unwrap();
+        rule("solana-ignored-rule").register(&mut engine).unwrap();
+
+        // Rules filtered out by the configuration keep their ID
+        assert_eq!(
+            rule("solana-ignored-rule").register(&mut engine),
+            Err(RuleBuildError::DuplicateId { id: "solana-ignored-rule".to_string() })
+        );
+        assert!(!engine.has_rule("solana-ignored-rule"));
+        assert_eq!(engine.rule_ids(), vec!["solana-test-rule"]);
+
+        let registry = engine.registry();
+        let ids: Vec<(String, bool)> = registry.metadata().map(|metadata| (metadata.id, metadata.enabled)).collect();
+        assert_eq!(
+            ids,
+            vec![("solana-test-rule".to_string(), true), ("solana-ignored-rule".to_string(), false)]
+        );
+
+        let metadata = registry.metadata_of("solana-test-rule").unwrap();
+        assert_eq!(metadata.title, "Test Rule");
+        assert_eq!(metadata.severity, Severity::Low);
+        assert_eq!(metadata.tags, vec!["testing"]);
+        assert_eq!(metadata.references, vec!["https://example.com/test-rule"]);
+        assert!(!metadata.project);
+        assert_eq!(
+            metadata.params,
+            vec![ParamMetadata {
+                name: "max_depth".to_string(),
+                description: "Maximum depth".to_string(),
+                default: ParamValue::Int(3),
+                value: ParamValue::Int(3),
+            }]
+        );
+        assert!(registry.metadata_of("solana-missing-rule").is_none());
+    }
+
+    #[test]
+    fn test_builtin_rules_are_listed() {
+        let mut engine = RuleEngine::default();
+        engine.load_builtin_rules().unwrap();
+
+        let metadata: Vec<_> = engine.registry().metadata().collect();
+        assert_eq!(metadata.len(), engine.registry().len());
+        assert!(metadata.iter().all(|rule| !rule.title.is_empty() && !rule.description.is_empty()));
+        let signer = metadata.iter().find(|rule| rule.id == "missing-signer-check").unwrap();
+        assert_eq!(signer.severity, Severity::High);
+        assert!(signer.params.iter().any(|param| param.name == "authority_names"));
+    }
+}
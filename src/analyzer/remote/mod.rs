@@ -0,0 +1,323 @@
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+#[cfg(feature = "native")]
+use {
+    log::{debug, info, warn},
+    std::fs,
+    std::process::Command,
+};
+
+#[cfg(test)]
+mod test;
+
+/// Directory of the fetched rule packs, in the cache directory of the user
+pub const PACKS_DIR: &str = "solana-analyzer/packs";
+
+/// Name of the file written in a pack directory once the pack is fetched and verified
+#[cfg(feature = "native")]
+const COMPLETE_MARKER: &str = ".solana-analyzer-pack";
+
+/// Error returned when a remote rule pack cannot be used
+#[derive(Debug)]
+pub enum PackError {
+    /// The pack reference is not valid
+    Invalid { spec: String, reason: String },
+    /// A command fetching the pack failed (git, curl, tar)
+    Command { command: String, stderr: String },
+    /// The archive does not have the expected checksum
+    Checksum { expected: String, actual: String },
+    /// The fetched commit is not the pinned one
+    Commit { expected: String, actual: String },
+    /// The signature of the fetched tag or commit cannot be verified
+    Signature { reference: String, stderr: String },
+    /// The pack cannot be written to the cache
+    Io(std::io::Error),
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackError::Invalid { spec, reason } => write!(f, "invalid rule pack '{spec}': {reason}"),
+            PackError::Command { command, stderr } => write!(f, "`{command}` failed: {}", stderr.trim()),
+            PackError::Checksum { expected, actual } => {
+                write!(f, "checksum mismatch: expected sha256 {expected}, got {actual}")
+            }
+            PackError::Commit { expected, actual } => write!(f, "commit mismatch: expected {expected}, got {actual}"),
+            PackError::Signature { reference, stderr } => {
+                write!(f, "cannot verify the signature of {reference}: {}", stderr.trim())
+            }
+            PackError::Io(error) => write!(f, "cannot write the rule pack: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
+
+impl From<std::io::Error> for PackError {
+    fn from(error: std::io::Error) -> Self {
+        PackError::Io(error)
+    }
+}
+
+/// How a remote rule pack is fetched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackKind {
+    /// A git repository (`.git` URL, or `git+` prefix)
+    Git,
+    /// A `.tar.gz`, `.tgz` or `.tar` archive, downloaded with curl
+    Archive,
+}
+
+/// Rule pack fetched from a URL instead of a local templates directory
+///
+/// The fragment of the URL pins and verifies the pack, its parts separated by `&`:
+///
+/// - `<ref>`: tag, branch or commit of a git repository (default: its `HEAD`), not starting with `-`
+/// - `commit=<sha>`: the fetched commit of a git repository must be this one
+/// - `signed`: the tag (or else the commit) must have a valid signature (`git verify-tag`,
+///   `git verify-commit`, with the keys of the user)
+/// - `sha256=<hex>`: checksum of an archive, which is required
+/// - `path=<dir>`: directory of the rules in the pack (default: the whole pack)
+///
+/// ```text
+/// https://github.com/org/rules.git#v1.2&signed
+/// https://example.com/rules-v1.2.tar.gz#sha256=9f86d081884c7d65...&path=rules
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemotePack {
+    /// Reference of the pack as given, the key of its cache directory
+    pub spec: String,
+    /// How the pack is fetched
+    pub kind: PackKind,
+    /// URL of the repository or the archive, without the `git+` prefix and the fragment
+    pub url: String,
+    /// Tag, branch or commit of a git repository
+    pub reference: Option<String>,
+    /// Commit a git repository must be at
+    pub commit: Option<String>,
+    /// Whether the signature of the tag or commit is verified
+    pub signed: bool,
+    /// Checksum of an archive (lowercase hex)
+    pub sha256: Option<String>,
+    /// Directory of the rules in the pack
+    pub path: Option<PathBuf>,
+}
+
+/// Check if a templates path is a remote rule pack rather than a local directory
+pub fn is_remote(spec: &str) -> bool {
+    ["https://", "http://", "file://", "ssh://", "git+"].iter().any(|scheme| spec.starts_with(scheme))
+}
+
+impl RemotePack {
+    /// Parse the reference of a remote rule pack
+    pub fn parse(spec: &str) -> Result<Self, PackError> {
+        let invalid = |reason: &str| PackError::Invalid { spec: spec.to_string(), reason: reason.to_string() };
+        let (url, fragment) = spec.split_once('#').unwrap_or((spec, ""));
+        let (url, git) = match url.strip_prefix("git+") {
+            Some(url) => (url, true),
+            None => (url, false),
+        };
+        let kind = if git || url.ends_with(".git") {
+            PackKind::Git
+        } else if [".tar.gz", ".tgz", ".tar"].iter().any(|extension| url.ends_with(extension)) {
+            PackKind::Archive
+        } else {
+            return Err(invalid("expected a .git repository or a .tar.gz, .tgz or .tar archive"));
+        };
+
+        let mut pack = RemotePack {
+            spec: spec.to_string(),
+            kind,
+            url: url.to_string(),
+            reference: None,
+            commit: None,
+            signed: false,
+            sha256: None,
+            path: None,
+        };
+        for part in fragment.split('&').filter(|part| !part.is_empty()) {
+            match part.split_once('=') {
+                Some(("sha256", hex)) if is_hex(hex, 64) => pack.sha256 = Some(hex.to_lowercase()),
+                Some(("sha256", _)) => return Err(invalid("sha256 must be 64 hexadecimal digits")),
+                Some(("commit", hex)) if (7..=40).contains(&hex.len()) && is_hex(hex, hex.len()) => {
+                    pack.commit = Some(hex.to_lowercase())
+                }
+                Some(("commit", _)) => return Err(invalid("commit must be 7 to 40 hexadecimal digits")),
+                Some(("path", path)) if is_relative(path) => pack.path = Some(PathBuf::from(path)),
+                Some(("path", _)) => return Err(invalid("path must stay in the pack")),
+                Some((key, _)) => return Err(invalid(&format!("unknown fragment parameter '{key}'"))),
+                None if part == "signed" => pack.signed = true,
+                // git would read the reference as an option
+                None if part.starts_with('-') => return Err(invalid("git references cannot start with '-'")),
+                None if pack.reference.is_none() => pack.reference = Some(part.to_string()),
+                None => return Err(invalid("more than one git reference")),
+            }
+        }
+
+        match kind {
+            PackKind::Archive if pack.sha256.is_none() => Err(invalid("archives must be pinned with #sha256=<checksum>")),
+            PackKind::Archive if pack.reference.is_some() || pack.commit.is_some() || pack.signed => {
+                Err(invalid("git references, commits and signatures only apply to git repositories"))
+            }
+            PackKind::Git if pack.sha256.is_some() => Err(invalid("sha256 only applies to archives, pin a commit instead")),
+            _ => Ok(pack),
+        }
+    }
+
+    /// Name of the cache directory of the pack, from its reference
+    pub fn cache_key(&self) -> String {
+        hex(&Sha256::digest(self.spec.as_bytes()))[..16].to_string()
+    }
+
+    /// Fetch and verify the pack into `cache_root` unless it is already there, and return the
+    /// directory of its rules
+    ///
+    /// A pack is cached by its reference: a pinned tag, commit or checksum is fetched once, and
+    /// changing the reference fetches the pack again.
+    #[cfg(feature = "native")]
+    pub fn fetch(&self, cache_root: &Path) -> Result<PathBuf, PackError> {
+        let dir = cache_root.join(self.cache_key());
+        if dir.join(COMPLETE_MARKER).is_file() {
+            debug!("Rule pack {} cached in {}", self.spec, dir.display());
+            return Ok(self.rules_dir(dir));
+        }
+
+        info!("Fetching the rule pack {}", self.spec);
+        let staging = cache_root.join(format!("{}.tmp-{}", self.cache_key(), std::process::id()));
+        let _ = fs::remove_dir_all(&staging);
+        fs::create_dir_all(&staging)?;
+        let fetched = match self.kind {
+            PackKind::Git => self.fetch_git(&staging),
+            PackKind::Archive => self.fetch_archive(&staging),
+        };
+        if let Err(e) = fetched {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+        fs::write(staging.join(COMPLETE_MARKER), format!("{}\n", self.spec))?;
+        let _ = fs::remove_dir_all(&dir);
+        fs::rename(&staging, &dir)?;
+        Ok(self.rules_dir(dir))
+    }
+
+    /// Directory of the rules in the pack directory
+    #[cfg(feature = "native")]
+    fn rules_dir(&self, dir: PathBuf) -> PathBuf {
+        match &self.path {
+            Some(path) => dir.join(path),
+            None => dir,
+        }
+    }
+
+    /// Fetch the reference of a git repository into `dir`, then check its commit and signature
+    #[cfg(feature = "native")]
+    fn fetch_git(&self, dir: &Path) -> Result<(), PackError> {
+        let git = |args: &[&str]| run(Command::new("git").arg("-C").arg(dir).args(args));
+        let reference = self.reference.as_deref().unwrap_or("HEAD");
+        git(&["init", "-q"])?;
+
+        // A signed tag is fetched once with its commit, which is the one checked out and verified
+        let tag = format!("refs/tags/{reference}");
+        let fetched_tag = self.signed
+            && self.reference.is_some()
+            && git(&["fetch", "-q", "--depth", "1", "--end-of-options", &self.url, &format!("{tag}:{tag}")]).is_ok();
+        if fetched_tag {
+            git(&["checkout", "-q", &format!("{tag}^{{commit}}")])?;
+        } else {
+            git(&["fetch", "-q", "--depth", "1", "--end-of-options", &self.url, reference])?;
+            git(&["checkout", "-q", "FETCH_HEAD"])?;
+        }
+
+        let head = git(&["rev-parse", "HEAD"])?.trim().to_string();
+        if let Some(expected) = &self.commit
+            && !head.starts_with(expected.as_str())
+        {
+            return Err(PackError::Commit { expected: expected.clone(), actual: head });
+        }
+        if self.signed {
+            let signature_error = |stderr: String| PackError::Signature { reference: reference.to_string(), stderr };
+            let verified = if fetched_tag { git(&["verify-tag", &tag]) } else { git(&["verify-commit", "HEAD"]) };
+            verified.map_err(|e| {
+                signature_error(match e {
+                    PackError::Command { stderr, .. } => stderr,
+                    e => e.to_string(),
+                })
+            })?;
+            if fetched_tag {
+                let tagged = git(&["rev-parse", &format!("{tag}^{{commit}}")])?.trim().to_string();
+                if tagged != head {
+                    return Err(signature_error(format!("the tag points to {tagged}, not to the fetched commit {head}")));
+                }
+            }
+        } else if self.commit.is_none() {
+            warn!("Rule pack {} is neither pinned to a commit nor signed, its content is not verified", self.spec);
+        }
+        fs::remove_dir_all(dir.join(".git"))?;
+        Ok(())
+    }
+
+    /// Download an archive, check its checksum and extract it into `dir`
+    #[cfg(feature = "native")]
+    fn fetch_archive(&self, dir: &Path) -> Result<(), PackError> {
+        let archive = dir.join(".archive");
+        run(Command::new("curl").args(["-fsSL", "-o"]).arg(&archive).arg(&self.url))?;
+        let actual = hex(&Sha256::digest(fs::read(&archive)?));
+        if let Some(expected) = &self.sha256
+            && actual != *expected
+        {
+            return Err(PackError::Checksum { expected: expected.clone(), actual });
+        }
+        run(Command::new("tar").arg("-xf").arg(&archive).arg("-C").arg(dir))?;
+        fs::remove_file(&archive)?;
+        Ok(())
+    }
+}
+
+/// Cache directory of the fetched rule packs: `$XDG_CACHE_HOME/solana-analyzer/packs`, or
+/// `~/.cache/solana-analyzer/packs`
+#[cfg(feature = "native")]
+pub fn default_cache_dir() -> PathBuf {
+    let cache = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .unwrap_or_else(std::env::temp_dir);
+    cache.join(PACKS_DIR)
+}
+
+/// Helper function to run a command and return its output
+#[cfg(feature = "native")]
+fn run(command: &mut Command) -> Result<String, PackError> {
+    let describe = |command: &Command| {
+        std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let output = command.output().map_err(|e| PackError::Command { command: describe(command), stderr: e.to_string() })?;
+    if !output.status.success() {
+        return Err(PackError::Command {
+            command: describe(command),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Helper function to check that a path only names directories below the pack (no root, `.`
+/// or `..`)
+fn is_relative(path: &str) -> bool {
+    !path.is_empty() && Path::new(path).components().all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Helper function to check that a value has `len` hexadecimal digits
+fn is_hex(value: &str, len: usize) -> bool {
+    value.len() == len && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Helper function to format bytes as lowercase hex
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
@@ -0,0 +1,67 @@
+use crate::analyzer::remote::{PackKind, RemotePack, is_remote};
+use std::path::PathBuf;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA256: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    #[test]
+    fn test_remote_templates_paths() {
+        assert!(is_remote("https://github.com/org/rules.git#v1.2"));
+        assert!(is_remote("git+ssh://git@github.com/org/rules"));
+        assert!(is_remote("file:///srv/packs/rules.tar.gz#sha256=00"));
+        assert!(!is_remote("rules"));
+        assert!(!is_remote("/srv/rules"));
+    }
+
+    #[test]
+    fn test_parse_git_packs() {
+        let pack = RemotePack::parse("https://github.com/org/rules.git#v1.2&signed&path=solana").unwrap();
+        assert_eq!(pack.kind, PackKind::Git);
+        assert_eq!(pack.url, "https://github.com/org/rules.git");
+        assert_eq!(pack.reference.as_deref(), Some("v1.2"));
+        assert!(pack.signed);
+        assert_eq!(pack.path, Some(PathBuf::from("solana")));
+
+        let pack = RemotePack::parse("git+ssh://git@github.com/org/rules#commit=4F2C9E1").unwrap();
+        assert_eq!(pack.kind, PackKind::Git);
+        assert_eq!(pack.url, "ssh://git@github.com/org/rules");
+        assert_eq!(pack.reference, None);
+        assert_eq!(pack.commit.as_deref(), Some("4f2c9e1"));
+
+        // The cache is keyed by the reference
+        let v1 = RemotePack::parse("https://github.com/org/rules.git#v1").unwrap();
+        let v2 = RemotePack::parse("https://github.com/org/rules.git#v2").unwrap();
+        assert_eq!(v1.cache_key().len(), 16);
+        assert_ne!(v1.cache_key(), v2.cache_key());
+    }
+
+    #[test]
+    fn test_parse_archive_packs() {
+        let pack = RemotePack::parse(&format!("https://example.com/rules.tar.gz#sha256={}", SHA256.to_uppercase())).unwrap();
+        assert_eq!(pack.kind, PackKind::Archive);
+        assert_eq!(pack.url, "https://example.com/rules.tar.gz");
+        assert_eq!(pack.sha256.as_deref(), Some(SHA256));
+        assert_eq!(pack.path, None);
+    }
+
+    #[test]
+    fn test_invalid_packs() {
+        let reason = |spec: &str| RemotePack::parse(spec).unwrap_err().to_string();
+        assert!(reason("https://example.com/rules").contains("expected a .git repository"));
+        assert!(reason("https://example.com/rules.tgz").contains("must be pinned with #sha256"));
+        assert!(reason("https://example.com/rules.tgz#sha256=abc").contains("64 hexadecimal digits"));
+        assert!(reason(&format!("https://example.com/rules.tgz#sha256={SHA256}&signed")).contains("only apply to git"));
+        assert!(reason(&format!("https://github.com/org/rules.git#sha256={SHA256}")).contains("pin a commit"));
+        assert!(reason("https://github.com/org/rules.git#v1&v2").contains("more than one git reference"));
+        assert!(reason("https://github.com/org/rules.git#--all").contains("cannot start with '-'"));
+        assert!(reason("https://github.com/org/rules.git#-v1").contains("cannot start with '-'"));
+        assert!(reason("https://github.com/org/rules.git#commit=xyz1234").contains("hexadecimal digits"));
+        assert!(reason("https://github.com/org/rules.git#path=../outside").contains("stay in the pack"));
+        assert!(reason("https://github.com/org/rules.git#path=/etc/rules").contains("stay in the pack"));
+        assert!(reason("https://github.com/org/rules.git#path=./rules").contains("stay in the pack"));
+        assert!(reason("https://github.com/org/rules.git#key=value").contains("unknown fragment parameter 'key'"));
+    }
+}
@@ -0,0 +1,249 @@
+use handlebars::template::{HelperTemplate, Parameter, Template, TemplateElement};
+use handlebars::{Handlebars, handlebars_helper, no_escape};
+use serde_json::Value;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::analyzer::html::escape;
+
+#[cfg(test)]
+mod test;
+
+/// Builtin report templates (`--report-template markdown`): name, file name, escaping and source
+pub const BUILTIN_TEMPLATES: &[(&str, &str, Escape, &str)] = &[
+    ("markdown", "report.md.hbs", Escape::None, include_str!("builtin/report.md.hbs")),
+    ("html", "report.html.hbs", Escape::Html, include_str!("builtin/report.html.hbs")),
+];
+
+/// Helpers callable in the templates, with their minimum number of arguments: the ones of
+/// Handlebars and the ones registered by [`registry`]
+const HELPERS: &[(&str, usize)] = &[
+    ("if", 1),
+    ("unless", 1),
+    ("each", 1),
+    ("with", 1),
+    ("lookup", 2),
+    ("eq", 2),
+    ("ne", 2),
+    ("gt", 2),
+    ("gte", 2),
+    ("lt", 2),
+    ("lte", 2),
+    ("and", 2),
+    ("or", 2),
+    ("not", 1),
+    ("len", 1),
+    ("add", 2),
+    ("upper", 1),
+    ("lower", 1),
+    ("json", 1),
+];
+
+/// Error returned when a report template cannot be loaded or rendered
+#[derive(Debug)]
+pub enum TemplateError {
+    /// The template file cannot be read
+    Io { path: String, error: std::io::Error },
+    /// The template is not valid
+    Syntax { template: String, line: usize, message: String },
+    /// The template cannot be rendered with the model
+    Render { template: String, message: String },
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Io { path, error } => write!(f, "cannot read template {path}: {error}"),
+            TemplateError::Syntax { template, line, message } => {
+                write!(f, "invalid template {template}:{line}: {message}")
+            }
+            TemplateError::Render { template, message } => write!(f, "cannot render template {template}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// Escaping of the values a template inserts with `{{value}}` (`--template-escape`);
+/// `{{{value}}}` inserts them as is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Escape {
+    /// Values are inserted as is (Markdown, text...)
+    #[default]
+    None,
+    /// Values are HTML-escaped
+    Html,
+}
+
+impl FromStr for Escape {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "none" => Ok(Escape::None),
+            "html" => Ok(Escape::Html),
+            other => Err(format!("unknown escaping '{other}' (expected none or html)")),
+        }
+    }
+}
+
+/// Report template (`--format template`), a Handlebars template rendered with the model of the
+/// JSON report
+///
+/// Besides the Handlebars helpers (`if`, `unless`, `each`, `with`, `lookup`, `eq`, `ne`, `gt`,
+/// `gte`, `lt`, `lte`, `and`, `or`, `not` and `len`), templates can call `add`, `upper`, `lower`
+/// and `json`. Calls of unknown helpers, partials and decorators are rejected when the template
+/// is parsed rather than when it is rendered.
+///
+/// Values are inserted as is, unless the template escapes them (see [`ReportTemplate::with_escape`]).
+#[derive(Debug, Clone)]
+pub struct ReportTemplate {
+    name: String,
+    registry: Handlebars<'static>,
+}
+
+impl ReportTemplate {
+    /// Parse the template `source`; `name` is shown in the errors
+    pub fn parse(name: &str, source: &str) -> Result<Self, TemplateError> {
+        let error = |line: usize, message: String| TemplateError::Syntax { template: name.to_string(), line, message };
+        let template = Template::compile_with_name(source, name.to_string())
+            .map_err(|e| error(e.pos().map_or(1, |(line, _)| line), e.reason().to_string()))?;
+        check_template(&template).map_err(|(line, message)| error(line, message))?;
+
+        let mut registry = registry();
+        registry.register_template(name, template);
+        Ok(Self { name: name.to_string(), registry })
+    }
+
+    /// Escape the values the template inserts
+    pub fn with_escape(mut self, escape_values: Escape) -> Self {
+        match escape_values {
+            Escape::None => self.registry.register_escape_fn(no_escape),
+            Escape::Html => self.registry.register_escape_fn(escape),
+        }
+        self
+    }
+
+    /// Returns a builtin template by name (`markdown` or `html`)
+    pub fn builtin(name: &str) -> Option<Self> {
+        BUILTIN_TEMPLATES
+            .iter()
+            .find(|(builtin, _, _, _)| *builtin == name)
+            .map(|(_, file_name, escape_values, source)| {
+                Self::parse(file_name, source).expect("builtin templates are valid").with_escape(*escape_values)
+            })
+    }
+
+    /// Load a template file, or a builtin template if there is no such file
+    pub fn load(path: &Path) -> Result<Self, TemplateError> {
+        if !path.exists()
+            && let Some(template) = path.to_str().and_then(Self::builtin)
+        {
+            return Ok(template);
+        }
+        let source = std::fs::read_to_string(path).map_err(|error| TemplateError::Io {
+            path: path.display().to_string(),
+            error,
+        })?;
+        let name = path
+            .file_name()
+            .map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().to_string());
+        Self::parse(&name, &source)
+    }
+
+    /// Returns the name of the template
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Render the template with a model
+    pub fn render(&self, model: &Value) -> Result<String, TemplateError> {
+        self.registry.render(&self.name, model).map_err(|e| TemplateError::Render {
+            template: self.name.clone(),
+            message: e.to_string(),
+        })
+    }
+}
+
+handlebars_helper!(add: |a: Value, b: Value| match (a.as_i64(), b.as_i64()) {
+    (Some(a), Some(b)) => Value::from(a + b),
+    _ => match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => Value::from(a + b),
+        _ => Value::Null,
+    },
+});
+handlebars_helper!(upper: |value: Value| display(&value).to_uppercase());
+handlebars_helper!(lower: |value: Value| display(&value).to_lowercase());
+handlebars_helper!(json: |value: Value| serde_json::to_string_pretty(&value).unwrap_or_default());
+
+/// Helper function to create the registry of a template, which inserts values as is
+fn registry() -> Handlebars<'static> {
+    let mut registry = Handlebars::new();
+    registry.register_escape_fn(no_escape);
+    registry.register_helper("add", Box::new(add));
+    registry.register_helper("upper", Box::new(upper));
+    registry.register_helper("lower", Box::new(lower));
+    registry.register_helper("json", Box::new(json));
+    registry
+}
+
+/// Helper function to write a value as text, like `{{value}}`
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Helper function to check the helpers called by a template and its blocks, which Handlebars
+/// only looks up when rendering
+fn check_template(template: &Template) -> Result<(), (usize, String)> {
+    for (index, element) in template.elements.iter().enumerate() {
+        let line = template.mapping.get(index).map_or(1, |mapping| mapping.0);
+        match element {
+            // `{{project}}` inserts a value, `{{upper severity}}` calls a helper
+            TemplateElement::Expression(helper) | TemplateElement::HtmlExpression(helper)
+                if !helper.params.is_empty() || !helper.hash.is_empty() =>
+            {
+                check_call(helper).map_err(|message| (line, message))?;
+            }
+            TemplateElement::HelperBlock(helper) => {
+                check_call(helper).map_err(|message| (line, message))?;
+                for block in helper.template.iter().chain(&helper.inverse) {
+                    check_template(block)?;
+                }
+            }
+            TemplateElement::DecoratorExpression(_)
+            | TemplateElement::DecoratorBlock(_)
+            | TemplateElement::PartialExpression(_)
+            | TemplateElement::PartialBlock(_) => {
+                return Err((line, "partials and decorators are not supported".to_string()));
+            }
+            // Text and comments
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Helper function to check a helper call and the sub-expressions of its arguments
+fn check_call(helper: &HelperTemplate) -> Result<(), String> {
+    if let Parameter::Name(name) = &helper.name {
+        let Some((_, arity)) = HELPERS.iter().find(|(known, _)| known == name) else {
+            return Err(format!("unknown helper '{name}'"));
+        };
+        if helper.params.len() < *arity {
+            return Err(format!("wrong number of arguments for '{name}': {}", helper.params.len()));
+        }
+    }
+    for param in helper.params.iter().chain(helper.hash.values()) {
+        if let Parameter::Subexpression(subexpression) = param
+            && let TemplateElement::Expression(call) = subexpression.element.as_ref()
+        {
+            check_call(call)?;
+        }
+    }
+    Ok(())
+}
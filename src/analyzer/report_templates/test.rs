@@ -0,0 +1,118 @@
+use serde_json::json;
+
+use crate::analyzer::report_templates::{BUILTIN_TEMPLATES, Escape, ReportTemplate, TemplateError};
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Severity, json};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(source: &str, model: serde_json::Value) -> String {
+        ReportTemplate::parse("report.md.hbs", source).unwrap().render(&model).unwrap()
+    }
+
+    fn syntax_error(source: &str) -> (usize, String) {
+        match ReportTemplate::parse("report.md.hbs", source) {
+            Err(TemplateError::Syntax { line, message, .. }) => (line, message),
+            other => panic!("expected a syntax error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_values_and_paths() {
+        let model = json!({
+            "project": "vault",
+            "summary": { "high": 2 },
+            "rules": [{ "id": "missing-signer-check" }],
+            "tags": ["security", "anchor"],
+        });
+
+        assert_eq!(render("{{project}}: {{summary.high}} high", model.clone()), "vault: 2 high");
+        assert_eq!(render("{{rules.0.id}} {{ this.project }}", model.clone()), "missing-signer-check vault");
+        assert_eq!(render("[{{missing.field}}] {{tags.1}}", model.clone()), "[] anchor");
+        assert_eq!(render("{{#with summary}}{{high}} in {{../project}} ({{@root.project}}){{/with}}", model), "2 in vault (vault)");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let model = json!({ "findings": [{ "severity": "high" }, { "severity": "low" }], "empty": [], "zero": 0 });
+
+        assert_eq!(
+            render("{{#each findings}}{{@index}}:{{severity}}{{#unless @last}}, {{/unless}}{{/each}}", model.clone()),
+            "0:high, 1:low"
+        );
+        assert_eq!(render("{{#each empty}}x{{else}}none{{/each}}", model.clone()), "none");
+        assert_eq!(render("{{#if zero}}yes{{else}}no{{/if}} {{#if findings}}yes{{/if}}", model.clone()), "no yes");
+        assert_eq!(render("{{#each findings.0}}{{@key}}={{this}}{{/each}}", model.clone()), "severity=high");
+        assert_eq!(
+            render("{{#each findings}}{{#if (eq severity \"high\")}}{{upper severity}} {{add @index 1}}{{/if}}{{/each}}", model.clone()),
+            "HIGH 1"
+        );
+        assert_eq!(render("{{len findings}} {{#if (and findings (not empty))}}ok{{/if}}", model), "2 ok");
+    }
+
+    #[test]
+    fn test_whitespace() {
+        let model = json!({ "items": ["a", "b"] });
+
+        // The lines holding only a block tag or a comment are removed
+        let source = "List:\n{{! items }}\n{{#each items}}\n  - {{this}}\n{{/each}}\nEnd\n";
+        assert_eq!(render(source, model.clone()), "List:\n  - a\n  - b\nEnd\n");
+        assert_eq!(render("{{#each items}}  {{~this~}}  {{/each}}", model.clone()), "ab");
+        assert_eq!(render("{{!-- a }} comment --}}x", model), "x");
+    }
+
+    #[test]
+    fn test_escaping() {
+        let model = json!({ "code": "a < b && c" });
+
+        // Whatever the name of the template, values are escaped on request only
+        let html = ReportTemplate::parse("report.html.hbs", "{{code}} {{{code}}}").unwrap();
+        assert_eq!(html.render(&model).unwrap(), "a < b && c a < b && c");
+        let html = html.with_escape(Escape::Html);
+        assert_eq!(html.render(&model).unwrap(), "a &lt; b &amp;&amp; c a < b && c");
+        assert_eq!("HTML".parse::<Escape>(), Ok(Escape::Html));
+        assert!("xml".parse::<Escape>().is_err());
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        // Parse errors of Handlebars
+        assert_eq!(syntax_error("a\n{{#if x}}\nb").0, 3);
+        assert_eq!(syntax_error("{{#if x}}{{/each}}"), (1, "helper \"if\" was opened, but \"each\" is closing".to_string()));
+        assert_eq!(syntax_error("{{project").0, 1);
+        // Helpers are checked before rendering
+        assert_eq!(syntax_error("\n\n{{#loop x}}{{/loop}}"), (3, "unknown helper 'loop'".to_string()));
+        assert_eq!(syntax_error("{{shout project}}"), (1, "unknown helper 'shout'".to_string()));
+        assert_eq!(syntax_error("{{#if (shout x)}}{{/if}}"), (1, "unknown helper 'shout'".to_string()));
+        assert_eq!(syntax_error("{{#each items}}\n{{eq project}}\n{{/each}}"), (2, "wrong number of arguments for 'eq': 1".to_string()));
+        assert_eq!(syntax_error("{{> footer}}"), (1, "partials and decorators are not supported".to_string()));
+    }
+
+    #[test]
+    fn test_builtin_templates() {
+        let finding = FindingBuilder::new("Missing signer check on <authority>")
+            .severity(Severity::High)
+            .span("src/lib.rs", 12, 5, 30)
+            .snippet("let authority = &ctx.accounts.authority;")
+            .recommendations(&["Add a Signer constraint"])
+            .build();
+        let result = AnalysisResult { findings: vec![finding], stats: AnalysisStats::default() };
+        let model = serde_json::to_value(json::JsonReport::new(&result, "programs/vault")).unwrap();
+
+        assert_eq!(BUILTIN_TEMPLATES.len(), 2);
+        let markdown = ReportTemplate::builtin("markdown").unwrap().render(&model).unwrap();
+        assert!(markdown.starts_with("# Rust Solana Analyzer Report\n"));
+        assert!(markdown.contains("| High | 1 |"));
+        assert!(markdown.contains("### 1. Finding (HIGH)\n\n**Location:** `src/lib.rs:12`\n"));
+        assert!(markdown.contains("Missing signer check on <authority>"));
+        assert!(markdown.contains("- Add a Signer constraint\n"));
+
+        let html = ReportTemplate::builtin("html").unwrap().render(&model).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Missing signer check on &lt;authority&gt;"));
+        assert!(html.contains("<li>Add a Signer constraint</li>"));
+        assert!(ReportTemplate::builtin("pdf").is_none());
+    }
+}
@@ -0,0 +1,663 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::analyzer::compare::Comparison;
+use crate::analyzer::dedup::MERGED_DESCRIPTIONS_KEY;
+use crate::analyzer::dsl::query::MetadataValue;
+use crate::analyzer::fixes;
+use crate::analyzer::history::{self, RunRecord, TREND_RUNS};
+use crate::analyzer::items;
+use crate::analyzer::results::{self, SeverityCounts, Sources};
+use crate::analyzer::suppressions::SuppressedFinding;
+use crate::analyzer::{CONTEXT_KEY, Finding, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Lines of code shown before and after each finding
+pub const CONTEXT_LINES: usize = 2;
+
+pub struct ReportGenerator {
+    findings: Vec<Finding>,
+    project_path: String,
+    /// Findings left out of the report by the finding caps, by rule ID
+    omitted: BTreeMap<String, usize>,
+    /// Lines of code of each analyzed file, by path
+    lines_of_code: BTreeMap<String, usize>,
+    /// Findings compared with a previous run (`--compare`)
+    comparison: Option<Comparison>,
+    /// Recorded runs of the project, the current one last
+    history: Vec<RunRecord>,
+    /// Findings removed by suppression comments and filters, listed in an appendix
+    suppressed: Vec<SuppressedFinding>,
+    /// Source code of the files held in memory
+    sources: Option<Sources>,
+}
+
+impl ReportGenerator {
+    pub fn new(findings: Vec<Finding>, project_path: String) -> Self {
+        Self {
+            findings,
+            project_path,
+            omitted: BTreeMap::new(),
+            lines_of_code: BTreeMap::new(),
+            comparison: None,
+            history: Vec::new(),
+            suppressed: Vec::new(),
+            sources: None,
+        }
+    }
+
+    /// Count the findings left out by the finding caps in the summary
+    pub fn with_omitted(mut self, omitted: BTreeMap<String, usize>) -> Self {
+        self.omitted = omitted;
+        self
+    }
+
+    /// Show the lines of code of the files and the findings per thousand lines in the summary
+    /// (see [`crate::analyzer::AnalysisStats::lines_of_code`])
+    pub fn with_lines_of_code(mut self, lines_of_code: BTreeMap<String, usize>) -> Self {
+        self.lines_of_code = lines_of_code;
+        self
+    }
+
+    /// Show the new and fixed findings since a previous run in a trend section
+    pub fn with_comparison(mut self, comparison: Comparison) -> Self {
+        self.comparison = Some(comparison);
+        self
+    }
+
+    /// Show the finding counts of the recorded runs in the trend section
+    pub fn with_history(mut self, history: Vec<RunRecord>) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// List the suppressed findings in an appendix (see
+    /// [`crate::analyzer::AnalysisStats::suppressed_findings`])
+    pub fn with_suppressed(mut self, suppressed: Vec<SuppressedFinding>) -> Self {
+        self.suppressed = suppressed;
+        self
+    }
+
+    /// Show the code of the files held in memory instead of reading them from the disk
+    pub fn with_sources(mut self, sources: Sources) -> Self {
+        self.sources = Some(sources);
+        self
+    }
+
+    pub fn generate_markdown_report(&self) -> String {
+        let mut report = String::new();
+        
+        // Header
+        report.push_str(&self.generate_header());
+
+        // Executive Summary
+        report.push_str(&self.generate_executive_summary());
+
+        // Trend since the previous run, and over the recorded runs
+        if self.has_trend() {
+            report.push_str(&self.generate_trend());
+        }
+        
+        // Table of Contents
+        report.push_str(&self.generate_table_of_contents());
+        
+        // Summary Section
+        report.push_str(&self.generate_summary());
+        
+        // Issues by Severity
+        report.push_str(&self.generate_issues_by_severity());
+
+        // Suppressed findings, so the report shows everything that was left out
+        if !self.suppressed.is_empty() {
+            report.push_str(&self.generate_suppressed());
+        }
+        
+        report
+    }
+
+    /// Save the Markdown report to a file
+    pub fn save_markdown_report(&self, output_path: &str) -> Result<(), std::io::Error> {
+        let report = self.generate_markdown_report();
+        fs::write(output_path, report)
+    }
+
+    fn generate_header(&self) -> String {
+        "# Rust Solana Analyzer Report\n\n\
+            This report was generated by Rust Solana Analyzer, a static analysis tool for Solana smart contracts. \
+            This report is not a substitute for manual audit or security review. It should not be relied upon for any purpose \
+            other than to assist in the identification of potential security vulnerabilities.\n".to_string()
+    }
+
+    /// One-page overview: the risk score, the riskiest files and the findings of each category
+    fn generate_executive_summary(&self) -> String {
+        let risk = results::risk_summary(&self.findings);
+        let counts = SeverityCounts::of(&self.findings);
+        let mut summary = String::from("\n# Executive Summary\n\n");
+
+        summary.push_str(&format!("**Risk Score: {}/100 ({})**\n\n", risk.score, risk.level));
+        if self.findings.is_empty() {
+            summary.push_str("No vulnerabilities found.\n\n");
+            return summary;
+        }
+        let severities: Vec<String> = Severity::ALL
+            .iter()
+            .filter(|severity| counts.get(severity) > 0)
+            .map(|severity| format!("{} {}", counts.get(severity), badge(severity)))
+            .collect();
+        let files = results::group_by_file(&self.findings).len();
+        summary.push_str(&format!(
+            "{} findings in {files} file{}: {}. The score weighs each finding by its severity and confidence.\n\n",
+            counts.total(),
+            if files == 1 { "" } else { "s" },
+            severities.join(", ")
+        ));
+
+        // Top Risky Files
+        summary.push_str("| Top Risky Files | Risk Points | High | Medium | Low | Informational |\n");
+        summary.push_str("| --- | --- | --- | --- | --- | --- |\n");
+        for file in &risk.top_files {
+            let display_file = file.file.strip_prefix(&self.project_path)
+                .unwrap_or(&file.file)
+                .trim_start_matches('/');
+            summary.push_str(&format!("| {display_file} | {} |{}\n", file.points, severity_cells(&file.severities)));
+        }
+        summary.push('\n');
+
+        // Categories
+        summary.push_str("| Category | Risk Points | High | Medium | Low | Informational |\n");
+        summary.push_str("| --- | --- | --- | --- | --- | --- |\n");
+        for category in &risk.categories {
+            let name = category.category.map_or("uncategorized", |category| category.as_str());
+            summary.push_str(&format!("| {name} | {} |{}\n", category.points, severity_cells(&category.severities)));
+        }
+        summary.push_str("\n\n");
+
+        summary
+    }
+
+    /// Check if the report has a trend section: a comparison, or runs recorded before this one
+    fn has_trend(&self) -> bool {
+        self.comparison.is_some() || self.history.len() > 1
+    }
+
+    /// New, fixed and persisting findings since the previous run, and the findings of the
+    /// last recorded runs
+    fn generate_trend(&self) -> String {
+        let mut trend = String::from("# Trend\n\n");
+        if let Some(comparison) = &self.comparison {
+            trend.push_str(&self.generate_comparison(comparison));
+        }
+        if self.history.len() > 1 {
+            trend.push_str("## Findings Over Time\n\n");
+            trend.push_str("| Run | Commit | Total | High | Medium | Low | Informational | New | Fixed |\n");
+            trend.push_str("| --- | --- | --- | --- | --- | --- | --- | --- | --- |\n");
+            let points = history::trend(&self.history);
+            for point in &points[points.len().saturating_sub(TREND_RUNS)..] {
+                let summary = &point.run.summary;
+                trend.push_str(&format!(
+                    "| {} | {} | {} | {} | {} | {} | {} | {} | {} |\n",
+                    point.run.date(),
+                    point.run.commit.as_deref().unwrap_or("-"),
+                    summary.total(),
+                    summary.high,
+                    summary.medium,
+                    summary.low,
+                    summary.informational,
+                    point.new.len(),
+                    point.fixed.len()
+                ));
+            }
+            trend.push_str("\n\n");
+        }
+        trend
+    }
+
+    /// New, fixed and persisting findings since the previous run (`--compare`)
+    fn generate_comparison(&self, comparison: &Comparison) -> String {
+        let summary = comparison.summary();
+        let mut trend = String::new();
+        trend.push_str("| Findings | Count |\n");
+        trend.push_str("| --- | --- |\n");
+        trend.push_str(&format!("| New | {} |\n", summary.new));
+        trend.push_str(&format!("| Fixed | {} |\n", summary.fixed));
+        trend.push_str(&format!("| Persisting | {} |\n\n", summary.persisting));
+
+        for (heading, findings) in [("New Findings", &comparison.new), ("Fixed Findings", &comparison.fixed)] {
+            if findings.is_empty() {
+                continue;
+            }
+            trend.push_str(&format!("## {heading}\n\n"));
+            for finding in findings {
+                let display_location = finding.location.file.strip_prefix(&self.project_path)
+                    .unwrap_or(&finding.location.file)
+                    .trim_start_matches('/');
+                let (title, _) = self.extract_title_and_description(&finding.description);
+                trend.push_str(&format!(
+                    "- {} {title} ({display_location} [Line: {}])\n",
+                    badge(&finding.severity),
+                    finding.location.line
+                ));
+            }
+            trend.push('\n');
+        }
+        trend.push('\n');
+        trend
+    }
+
+    fn generate_table_of_contents(&self) -> String {
+        let mut toc = String::from("# Table of Contents\n\n");
+        
+        toc.push_str("- [Executive Summary](#executive-summary)\n");
+        if self.has_trend() {
+            toc.push_str("- [Trend](#trend)\n");
+        }
+        toc.push_str("- [Summary](#summary)\n");
+        toc.push_str("  - [Files Summary](#files-summary)\n");
+        toc.push_str("  - [Files Details](#files-details)\n");
+        toc.push_str("  - [Rules Details](#rules-details)\n");
+        toc.push_str("  - [Issue Summary](#issue-summary)\n");
+        if !self.omitted.is_empty() {
+            toc.push_str("  - [Omitted Issues](#omitted-issues)\n");
+        }
+
+        // A section for each severity level that has findings, with an entry for each issue
+        for (severity, findings) in results::group_by_severity(&self.findings) {
+            let heading = format!("{severity:?} Issues");
+            toc.push_str(&format!("- [{heading}](#{})\n", anchor(&heading)));
+            for (index, (_, findings)) in results::group_by_rule(findings).into_iter().enumerate() {
+                let heading = self.issue_heading(&severity, index, &findings);
+                toc.push_str(&format!("  - [{heading}](#{}) ({})\n", anchor(&heading), findings.len()));
+            }
+        }
+        if !self.suppressed.is_empty() {
+            toc.push_str("- [Appendix: Suppressed Findings](#appendix-suppressed-findings)\n");
+        }
+
+        toc.push_str("\n\n");
+        toc
+    }
+
+    fn generate_summary(&self) -> String {
+        let mut summary = String::from("# Summary\n\n");
+        
+        // Files Summary
+        summary.push_str("## Files Summary\n\n");
+        summary.push_str("| Key | Value |\n");
+        summary.push_str("| --- | --- |\n");
+        
+        let file_count = results::group_by_file(&self.findings).len();
+        let total_lines: usize = self.lines_of_code.values().sum();
+        summary.push_str(&format!("| .rs Files | {file_count} |\n"));
+        if total_lines > 0 {
+            summary.push_str(&format!("| Lines of Code | {total_lines} |\n"));
+        }
+        summary.push_str(&format!("| Total Issues | {} |\n", self.findings.len()));
+        if total_lines > 0 {
+            summary.push_str(&format!("| Issues per KLOC | {} |\n", format_density(self.findings.len(), Some(total_lines))));
+        }
+        summary.push('\n');
+
+        // Files Details, with the density of findings of each file
+        summary.push_str("## Files Details\n\n");
+        summary.push_str("| Filepath | Lines of Code | Issues | Issues per KLOC |\n");
+        summary.push_str("| --- | --- | --- | --- |\n");
+        
+        for file_summary in results::file_summaries(&self.findings) {
+            let display_file = file_summary.file.strip_prefix(&self.project_path)
+                .unwrap_or(&file_summary.file)
+                .trim_start_matches('/');
+            let lines = self.lines_of_code.get(&file_summary.file).copied();
+            let issues = file_summary.severities.total();
+            summary.push_str(&format!(
+                "| {display_file} | {} | {issues} | {} |\n",
+                lines.map_or("-".to_string(), |lines| lines.to_string()),
+                format_density(issues, lines)
+            ));
+        }
+        summary.push_str(&format!("| **Total** | | **{}** | |\n\n", self.findings.len()));
+
+        // Rules Details
+        summary.push_str("## Rules Details\n\n");
+        summary.push_str("| Rule | Title | Issues | Files |\n");
+        summary.push_str("| --- | --- | --- | --- |\n");
+        for rule_summary in results::rule_summaries(&self.findings) {
+            summary.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                rule_summary.rule_id,
+                rule_summary.title,
+                rule_summary.severities.total(),
+                rule_summary.files.len()
+            ));
+        }
+        summary.push_str(&format!("| **Total** | | **{}** | |\n\n", self.findings.len()));
+
+        // Issue Summary
+        summary.push_str("## Issue Summary\n\n");
+        summary.push_str("| Category | No. of Issues |\n");
+        summary.push_str("| --- | --- |\n");
+        
+        let severity_counts = SeverityCounts::of(&self.findings);
+        for severity in Severity::ALL {
+            summary.push_str(&format!("| {} | {} |\n", badge(&severity), severity_counts.get(&severity)));
+        }
+        summary.push('\n');
+
+        // Omitted Issues
+        if !self.omitted.is_empty() {
+            summary.push_str("## Omitted Issues\n\n");
+            summary.push_str("Findings over the per-rule and per-file caps are not detailed in this report.\n\n");
+            summary.push_str("| Rule | Omitted Issues |\n");
+            summary.push_str("| --- | --- |\n");
+            for (rule_id, count) in &self.omitted {
+                summary.push_str(&format!("| {rule_id} | {count} |\n"));
+            }
+            summary.push_str(&format!("| **Total** | **{}** |\n\n", self.omitted.values().sum::<usize>()));
+        }
+
+        summary
+    }
+
+    /// Appendix with the findings removed by suppression comments and by the severity and
+    /// confidence filters, with their fingerprints
+    fn generate_suppressed(&self) -> String {
+        let mut appendix = String::from("# Appendix: Suppressed Findings\n\n");
+        appendix.push_str(&format!(
+            "{} finding{} removed by `analyzer:ignore` comments or by the severity and confidence filters.\n\n",
+            self.suppressed.len(),
+            if self.suppressed.len() == 1 { " was" } else { "s were" }
+        ));
+        appendix.push_str("| Rule | Severity | Location | Suppressed By | Reason | Fingerprint |\n");
+        appendix.push_str("| --- | --- | --- | --- | --- | --- |\n");
+        for suppressed in &self.suppressed {
+            let finding = &suppressed.finding;
+            let display_location = finding.location.file.strip_prefix(&self.project_path)
+                .unwrap_or(&finding.location.file)
+                .trim_start_matches('/');
+            appendix.push_str(&format!(
+                "| {} | {} | {display_location}:{} | {} | {} | {} |\n",
+                suppressed.rule_id,
+                badge(&finding.severity),
+                finding.location.line,
+                suppressed.suppressed_by.as_str(),
+                suppressed.reason.as_deref().unwrap_or("-").replace('|', "\\|"),
+                finding.fingerprint.as_deref().map_or("-".to_string(), |fingerprint| format!("`{fingerprint}`"))
+            ));
+        }
+        appendix.push('\n');
+        appendix
+    }
+
+    fn generate_issues_by_severity(&self) -> String {
+        let mut issues = String::new();
+        let sources = self.load_sources();
+
+        for (severity, findings) in results::group_by_severity(&self.findings) {
+            issues.push_str(&format!("# {severity:?} Issues\n\n"));
+            // One issue per rule, in the order of the rule IDs
+            for (index, (_, findings)) in results::group_by_rule(findings).into_iter().enumerate() {
+                issues.push_str(&self.generate_issue(&severity, index, &findings, &sources));
+            }
+        }
+        
+        issues
+    }
+
+    /// Heading of the issue of a rule, e.g. "H-1: Missing Signer Check"
+    fn issue_heading(&self, severity: &Severity, index: usize, findings: &[&Finding]) -> String {
+        let severity_prefix = match severity {
+            Severity::High => "H",
+            Severity::Medium => "M",
+            Severity::Low => "L",
+            Severity::Informational => "I",
+        };
+        let title = match findings.first().and_then(|finding| finding.rule()) {
+            Some(rule) if !rule.title.is_empty() => rule.title.clone(),
+            _ => findings
+                .first()
+                .map(|finding| self.extract_title_and_description(&finding.description).0)
+                .unwrap_or_default(),
+        };
+        format!("{severity_prefix}-{}: {title}", index + 1)
+    }
+
+    /// Section of the findings of a rule with the same severity
+    fn generate_issue(
+        &self,
+        severity: &Severity,
+        index: usize,
+        findings: &[&Finding],
+        sources: &BTreeMap<&str, Option<Vec<String>>>,
+    ) -> String {
+        let mut section = String::new();
+
+        // Title (concise)
+        section.push_str(&format!("## {}\n\n", self.issue_heading(severity, index, findings)));
+
+        // Severity and occurrences
+        let files = results::group_by_file(findings.iter().copied()).len();
+        section.push_str(&format!(
+            "**Severity:** {} | **Occurrences:** {} in {files} file{}\n\n",
+            badge(severity),
+            findings.len(),
+            if files == 1 { "" } else { "s" }
+        ));
+
+        // Description (detailed explanation)
+        if let Some(finding) = findings.first() {
+            let (_, description) = self.extract_title_and_description(&finding.description);
+            section.push_str(&format!("{description}\n\n"));
+        }
+
+        // Rule that reported the issue
+        let rule = findings.first().and_then(|finding| finding.rule());
+        if let Some(rule) = rule {
+            section.push_str(&format!("Rule: `{}` ({})", rule.id, rule.title));
+            if let Some(category) = &rule.category {
+                section.push_str(&format!(" [Category: {category}]"));
+            }
+            if !rule.cwe.is_empty() {
+                let cwe: Vec<String> = rule
+                    .cwe
+                    .iter()
+                    .map(|id| format!("[CWE-{id}](https://cwe.mitre.org/data/definitions/{id}.html)"))
+                    .collect();
+                section.push_str(&format!(" {}", cwe.join(" ")));
+            }
+            if !rule.tags.is_empty() {
+                section.push_str(&format!(" [Tags: {}]", rule.tags.join(", ")));
+            }
+            section.push_str("\n\n");
+        }
+
+        // Recommendations of every instance, without repeating them
+        let mut recommendations: Vec<&String> = Vec::new();
+        for recommendation in findings.iter().flat_map(|finding| &finding.recommendations) {
+            if !recommendations.contains(&recommendation) {
+                recommendations.push(recommendation);
+            }
+        }
+        if !recommendations.is_empty() {
+            section.push_str("**Recommendations**\n\n");
+            for (i, recommendation) in recommendations.iter().enumerate() {
+                section.push_str(&format!("{}. {}\n", i + 1, recommendation));
+            }
+            section.push('\n');
+        }
+
+        // References
+        if let Some(rule) = rule
+            && !rule.references.is_empty()
+        {
+            section.push_str("**References**\n\n");
+            for reference in &rule.references {
+                section.push_str(&format!("- <{reference}>\n"));
+            }
+            section.push('\n');
+        }
+
+        // Instances
+        section.push_str(&format!("<details><summary>{} Found Instance{}</summary>\n\n", 
+            findings.len(), 
+            if findings.len() == 1 { "" } else { "s" }
+        ));
+        
+        for finding in findings {
+            let display_location = finding.location.file.strip_prefix(&self.project_path)
+                .unwrap_or(&finding.location.file)
+                .trim_start_matches('/');
+            let (title, _) = self.extract_title_and_description(&finding.description);
+            
+            section.push_str(&format!(
+                "- Found in {} [Line: {}] [Confidence: {}]: {title}",
+                display_location, finding.location.line, finding.confidence
+            ));
+            if let Some(item) = items::qualified_name(finding) {
+                section.push_str(&format!(" [In: `{item}`]"));
+            }
+            // Findings merged from several rules list them, with the other issues reported
+            if finding.rules.len() > 1 {
+                section.push_str(&format!(" [Rules: {}]", finding.rule_ids().join(", ")));
+            }
+            // Hidden when rendered, to match the findings across reports
+            if let Some(fingerprint) = &finding.fingerprint {
+                section.push_str(&format!(" <!-- fingerprint: {fingerprint} -->"));
+            }
+            section.push_str("\n\n");
+            if let Some(MetadataValue::List(descriptions)) = finding.metadata.get(MERGED_DESCRIPTIONS_KEY) {
+                for description in descriptions {
+                    section.push_str(&format!("\tAlso reported: {description}\n\n"));
+                }
+            }
+
+            let source = sources.get(finding.location.file.as_str()).and_then(Option::as_ref);
+            // The context added by the analysis (`--context`), otherwise `CONTEXT_LINES` lines
+            let context = match finding.metadata.get(CONTEXT_KEY) {
+                Some(MetadataValue::Text(context)) => Some(context.clone()),
+                _ => source.and_then(|lines| code_with_context(lines, finding)),
+            };
+            if let Some(code) = context {
+                section.push_str("\t```rust\n");
+                for line in code.lines() {
+                    section.push_str(&format!("\t{line}\n"));
+                }
+                section.push_str("\t```\n\n");
+            } else if let Some(ref code) = finding.code_snippet {
+                section.push_str("\t```rust\n");
+                for line in code.lines() {
+                    section.push_str(&format!("\t{line}\n"));
+                }
+                section.push_str("\t```\n\n");
+            }
+
+            for fix in &finding.fixes {
+                section.push_str(&format!("\t**{}:** {}\n\n", fixes::label(fix), fix.description));
+                if let Some(changes) = source.and_then(|lines| fixes::diff(lines, fix, &finding.location.file)) {
+                    section.push_str("\t```diff\n");
+                    for line in changes.to_unified().lines() {
+                        section.push_str(&format!("\t{line}\n"));
+                    }
+                    section.push_str("\t```\n\n");
+                }
+            }
+        }
+        
+        section.push_str("</details>\n\n\n\n");
+        section
+    }
+
+    /// Read the files of the findings, to show the code around them (`None` if a file cannot be read)
+    fn load_sources(&self) -> BTreeMap<&str, Option<Vec<String>>> {
+        results::group_by_file(&self.findings)
+            .into_keys()
+            .map(|file| (file, results::source_lines(file, self.sources.as_ref())))
+            .collect()
+    }
+
+    /// Extract title and description from finding description
+    /// Format: "Title. Description with more details."
+    fn extract_title_and_description(&self, full_description: &str) -> (String, String) {
+        // Find the first sentence as title
+        if let Some(first_dot) = full_description.find(". ") {
+            let title = full_description[..first_dot].trim().to_string();
+            let description = full_description[first_dot + 2..].trim().to_string();
+            (title, description)
+        } else {
+            // If no clear separation, use a simplified approach
+            let parts: Vec<&str> = full_description.splitn(2, " has ").collect();
+            if parts.len() == 2 {
+                let title = format!("{} Issue", parts[0]);
+                let description = format!("This {} has {}. Consider reviewing and fixing this issue.", parts[0].to_lowercase(), parts[1]);
+                (title, description)
+            } else {
+                // Fallback: use first part as title, rest as description
+                let words: Vec<&str> = full_description.split_whitespace().collect();
+                if words.len() > 6 {
+                    let title = words[..6].join(" ");
+                    let description = words[6..].join(" ");
+                    (title, description)
+                } else {
+                    (full_description.to_string(), "Review and fix this issue.".to_string())
+                }
+            }
+        }
+    }
+}
+
+/// Severity with the colored marker used in the report, e.g. "🔴 High"
+pub fn badge(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::High => "🔴 High",
+        Severity::Medium => "🟠 Medium",
+        Severity::Low => "🟡 Low",
+        Severity::Informational => "🔵 Informational",
+    }
+}
+
+/// Findings per thousand lines of code with one decimal, "-" if the lines are unknown
+fn format_density(findings: usize, lines_of_code: Option<usize>) -> String {
+    lines_of_code
+        .and_then(|lines| results::density(findings, lines))
+        .map_or("-".to_string(), |density| format!("{density:.1}"))
+}
+
+/// Table cells with the number of findings of each severity, from High to Informational
+fn severity_cells(counts: &SeverityCounts) -> String {
+    Severity::ALL.iter().map(|severity| format!(" {} |", counts.get(severity))).collect()
+}
+
+/// Anchor of a Markdown heading, the way GitHub generates it
+/// ("H-1: Missing Signer Check" is `h-1-missing-signer-check`)
+fn anchor(heading: &str) -> String {
+    heading
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Lines of a finding with `CONTEXT_LINES` lines around them, numbered, the lines of the finding
+/// marked with `>` (`None` if the location is not in the source)
+fn code_with_context(lines: &[String], finding: &Finding) -> Option<String> {
+    let location = &finding.location;
+    let end_line = location.end_line.unwrap_or(location.line).max(location.line);
+    if location.line == 0 || end_line > lines.len() {
+        return None;
+    }
+
+    let first = location.line.saturating_sub(CONTEXT_LINES).max(1);
+    let last = (end_line + CONTEXT_LINES).min(lines.len());
+    let width = last.to_string().len();
+    let mut code = String::new();
+    for number in first..=last {
+        let marker = if (location.line..=end_line).contains(&number) { '>' } else { ' ' };
+        code.push_str(format!("{marker} {number:>width$} | {}", lines[number - 1]).trim_end());
+        code.push('\n');
+    }
+    Some(code)
+}
@@ -0,0 +1,156 @@
+use crate::analyzer::compare::Comparison;
+use crate::analyzer::reporting::ReportGenerator;
+use crate::analyzer::suppressions::{SuppressedBy, SuppressedFinding};
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{Finding, Fix, Location, RuleInfo, TextEdit};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "use anchor_lang::prelude::*;\n\npub fn withdraw(amount: u64, shares: u64) -> u64 {\n    let rate = 2;\n    amount / shares * rate\n}\n\npub fn sweep(amount: u64, shares: u64) -> u64 {\n    amount / shares\n}\n";
+
+    fn finding(file: &str, line: usize, function: &str, recommendations: &[&str]) -> Finding {
+        FindingBuilder::new(format!("Division Without Zero Check in '{function}'. Detects division operations without zero verification"))
+            .span(file, line, 4, 19)
+            .snippet("amount / shares")
+            .recommendations(recommendations)
+            .rule(RuleInfo {
+                id: "solana-division-by-zero".to_string(),
+                title: "Division Without Zero Check".to_string(),
+                references: vec!["https://example.com/division-by-zero".to_string()],
+                ..Default::default()
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_markdown_report() {
+        let dir = std::env::temp_dir().join(format!("solana-analyzer-report-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("lib.rs");
+        std::fs::write(&file, SOURCE).unwrap();
+        let file = file.to_string_lossy().to_string();
+
+        let findings = vec![
+            finding(&file, 5, "withdraw", &["Check that the divisor is not zero"]),
+            finding(&file, 9, "sweep", &["Check that the divisor is not zero", "Use checked_div"]),
+            finding("missing.rs", 3, "missing", &[]),
+        ];
+        let lines_of_code = [(file.clone(), 8)].into_iter().collect();
+        let report = ReportGenerator::new(findings, dir.to_string_lossy().to_string())
+            .with_lines_of_code(lines_of_code)
+            .generate_markdown_report();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Executive summary: three medium findings with high confidence (50 points each)
+        assert!(report.contains("# Executive Summary\n\n**Risk Score: 26/100 (Medium)**\n\n3 findings in 2 files: 3 🟠 Medium."));
+        assert!(report.contains("| lib.rs | 100 | 0 | 2 | 0 | 0 |\n| missing.rs | 50 | 0 | 1 | 0 | 0 |\n"));
+        assert!(report.contains("| uncategorized | 150 | 0 | 3 | 0 | 0 |\n"));
+
+        // Files and rules details, with the density of findings of the files with known lines of code
+        assert!(report.contains("| Lines of Code | 8 |\n| Total Issues | 3 |\n| Issues per KLOC | 375.0 |\n"));
+        assert!(report.contains("| lib.rs | 8 | 2 | 250.0 |\n| missing.rs | - | 1 | - |\n"));
+        assert!(report.contains("| solana-division-by-zero | Division Without Zero Check | 3 | 2 |\n"));
+
+        // One issue for the rule, listed in the table of contents with its occurrences
+        assert!(report.contains("- [Medium Issues](#medium-issues)\n"));
+        assert!(report.contains("  - [M-1: Division Without Zero Check](#m-1-division-without-zero-check) (3)\n"));
+        assert_eq!(report.matches("## M-").count(), 1);
+        assert!(report.contains("**Severity:** 🟠 Medium | **Occurrences:** 3 in 2 files\n"));
+
+        // Recommendations of every instance and references of the rule
+        assert!(report.contains("1. Check that the divisor is not zero\n2. Use checked_div\n"));
+        assert!(report.contains("- <https://example.com/division-by-zero>\n"));
+
+        // Code with context lines, the finding marked
+        assert!(report.contains("- Found in lib.rs [Line: 5] [Confidence: High]: Division Without Zero Check in 'withdraw'"));
+        assert!(report.contains("\t  3 | pub fn withdraw(amount: u64, shares: u64) -> u64 {\n\t  4 |     let rate = 2;\n\t> 5 |     amount / shares * rate\n\t  6 | }\n\t  7 |\n"));
+        assert!(report.contains("\t>  9 |     amount / shares\n\t  10 | }\n"));
+        // The snippet when the file cannot be read
+        assert!(report.contains("\t```rust\n\tamount / shares\n\t```\n"));
+    }
+
+    #[test]
+    fn test_trend() {
+        // Findings are matched by fingerprint, here the name of their function
+        let fingerprinted = |file: &str, line: usize, function: &str| Finding {
+            fingerprint: Some(function.to_string()),
+            ..finding(file, line, function, &[])
+        };
+        let previous = vec![fingerprinted("/project/src/lib.rs", 5, "withdraw"), fingerprinted("/project/src/lib.rs", 9, "sweep")];
+        let current = vec![fingerprinted("/project/src/lib.rs", 9, "sweep"), fingerprinted("/project/src/vault.rs", 2, "deposit")];
+        let comparison = Comparison::of(&previous, &current);
+
+        let report = ReportGenerator::new(current, "/project".to_string())
+            .with_comparison(comparison)
+            .generate_markdown_report();
+
+        assert!(report.contains("- [Trend](#trend)\n"));
+        assert!(report.contains("# Trend\n\n| Findings | Count |\n| --- | --- |\n| New | 1 |\n| Fixed | 1 |\n| Persisting | 1 |\n"));
+        assert!(report.contains("## New Findings\n\n- 🟠 Medium Division Without Zero Check in 'deposit' (src/vault.rs [Line: 2])\n"));
+        assert!(report.contains("## Fixed Findings\n\n- 🟠 Medium Division Without Zero Check in 'withdraw' (src/lib.rs [Line: 5])\n"));
+
+        let report = ReportGenerator::new(Vec::new(), "/project".to_string()).generate_markdown_report();
+        assert!(!report.contains("Trend"));
+    }
+
+    #[test]
+    fn test_suppressed_appendix() {
+        let suppressed = vec![
+            SuppressedFinding {
+                rule_id: "solana-division-by-zero".to_string(),
+                suppressed_by: SuppressedBy::Comment,
+                reason: Some("shares > 0 | checked".to_string()),
+                finding: Finding { fingerprint: Some("withdraw".to_string()), ..finding("/project/src/lib.rs", 5, "withdraw", &[]) },
+            },
+            SuppressedFinding {
+                rule_id: "solana-division-by-zero".to_string(),
+                suppressed_by: SuppressedBy::Severity,
+                reason: Some("Severity medium is ignored".to_string()),
+                finding: finding("/project/src/lib.rs", 9, "sweep", &[]),
+            },
+        ];
+        let report = ReportGenerator::new(Vec::new(), "/project".to_string())
+            .with_suppressed(suppressed)
+            .generate_markdown_report();
+
+        assert!(report.contains("- [Appendix: Suppressed Findings](#appendix-suppressed-findings)\n"));
+        assert!(report.contains("# Appendix: Suppressed Findings\n\n2 findings were removed"));
+        assert!(report.contains("| solana-division-by-zero | 🟠 Medium | src/lib.rs:5 | comment | shares > 0 \\| checked | `withdraw` |\n"));
+        assert!(report.contains("| solana-division-by-zero | 🟠 Medium | src/lib.rs:9 | severity | Severity medium is ignored | - |\n"));
+
+        let report = ReportGenerator::new(Vec::new(), "/project".to_string()).generate_markdown_report();
+        assert!(!report.contains("Suppressed"));
+    }
+
+    #[test]
+    fn test_fixes() {
+        let dir = std::env::temp_dir().join(format!("solana-analyzer-report-fixes-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("lib.rs");
+        std::fs::write(&file, SOURCE).unwrap();
+        let file = file.to_string_lossy().to_string();
+
+        let edit = |file: &str| TextEdit {
+            location: Location::new_precise(file.to_string(), 9, Some(4), Some(9), Some(19)),
+            replacement: "amount.checked_div(shares).unwrap()".to_string(),
+        };
+        let findings = vec![
+            Finding { fixes: vec![Fix::new("Replace the division with checked_div", vec![edit(&file)])], ..finding(&file, 9, "sweep", &[]) },
+            Finding {
+                fixes: vec![Fix::machine_applicable("Replace the division with checked_div", vec![edit("missing.rs")])],
+                ..finding("missing.rs", 9, "missing", &[])
+            },
+        ];
+        let report = ReportGenerator::new(findings, dir.to_string_lossy().to_string()).generate_markdown_report();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(report.contains(
+            "\t**Suggested fix (advisory, review before applying):** Replace the division with checked_div\n\n\
+             \t```diff\n\t@@ -9,1 +9,1 @@\n\t-    amount / shares\n\t+    amount.checked_div(shares).unwrap()\n\t```\n"
+        ));
+        // Without the source, only the description of the fix
+        assert!(report.contains("\t**Suggested fix (machine-applicable):** Replace the division with checked_div\n\n</details>"));
+    }
+}
@@ -0,0 +1,399 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::analyzer::{AnalysisResult, Category, Confidence, Finding, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Number of findings of each severity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeverityCounts {
+    /// High severity findings
+    pub high: usize,
+    /// Medium severity findings
+    pub medium: usize,
+    /// Low severity findings
+    pub low: usize,
+    /// Informational findings
+    pub informational: usize,
+}
+
+impl SeverityCounts {
+    /// Counts the findings of each severity
+    pub fn of<'a>(findings: impl IntoIterator<Item = &'a Finding>) -> Self {
+        let mut counts = Self::default();
+        for finding in findings {
+            counts.add(&finding.severity);
+        }
+        counts
+    }
+
+    /// Count one more finding of the given severity
+    pub fn add(&mut self, severity: &Severity) {
+        match severity {
+            Severity::High => self.high += 1,
+            Severity::Medium => self.medium += 1,
+            Severity::Low => self.low += 1,
+            Severity::Informational => self.informational += 1,
+        }
+    }
+
+    /// Returns the number of findings of the given severity
+    pub fn get(&self, severity: &Severity) -> usize {
+        match severity {
+            Severity::High => self.high,
+            Severity::Medium => self.medium,
+            Severity::Low => self.low,
+            Severity::Informational => self.informational,
+        }
+    }
+
+    /// Returns the number of findings of every severity
+    pub fn total(&self) -> usize {
+        self.high + self.medium + self.low + self.informational
+    }
+
+    /// Returns the highest severity with findings
+    pub fn highest(&self) -> Option<Severity> {
+        Severity::ALL.into_iter().find(|severity| self.get(severity) > 0)
+    }
+}
+
+/// Findings reported in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSummary {
+    /// Path of the file
+    pub file: String,
+    /// Number of findings of each severity
+    pub severities: SeverityCounts,
+    /// IDs of the rules that reported the findings, sorted
+    pub rules: Vec<String>,
+}
+
+/// Findings reported by a rule (the first rule of merged findings)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleSummary {
+    /// ID of the rule
+    pub rule_id: String,
+    /// Title of the rule
+    pub title: String,
+    /// Number of findings of each severity (rules can adjust the severity of their findings)
+    pub severities: SeverityCounts,
+    /// Files with findings of the rule, sorted
+    pub files: Vec<String>,
+}
+
+/// Overall risk of an analysis, for executive summaries (see [`risk_summary`])
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RiskSummary {
+    /// Risk score, from 0 (no findings) to 100
+    pub score: u32,
+    /// Risk level of the score
+    pub level: RiskLevel,
+    /// Files with the most risk points, at most [`TOP_RISKY_FILES`]
+    pub top_files: Vec<FileRisk>,
+    /// Findings of each category, the categories with the most risk points first
+    pub categories: Vec<CategoryRisk>,
+}
+
+/// Risk level of a score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskLevel {
+    /// No findings weigh in the score
+    None,
+    /// Score under 25
+    Low,
+    /// Score from 25 to 49
+    Medium,
+    /// Score from 50 to 74
+    High,
+    /// Score from 75
+    Critical,
+}
+
+impl RiskLevel {
+    /// Returns the level of a risk score
+    pub fn of(score: u32) -> Self {
+        match score {
+            0 => RiskLevel::None,
+            1..=24 => RiskLevel::Low,
+            25..=49 => RiskLevel::Medium,
+            50..=74 => RiskLevel::High,
+            _ => RiskLevel::Critical,
+        }
+    }
+}
+
+impl std::fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// Risk of the findings of a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileRisk {
+    /// Path of the file
+    pub file: String,
+    /// Sum of the risk points of the findings (see [`risk_points`])
+    pub points: u32,
+    /// Number of findings of each severity
+    pub severities: SeverityCounts,
+}
+
+/// Risk of the findings of a category
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CategoryRisk {
+    /// Vulnerability class of the rules (`None` for the rules without category)
+    pub category: Option<Category>,
+    /// Sum of the risk points of the findings (see [`risk_points`])
+    pub points: u32,
+    /// Number of findings of each severity
+    pub severities: SeverityCounts,
+}
+
+/// Number of files listed in [`RiskSummary::top_files`]
+pub const TOP_RISKY_FILES: usize = 5;
+
+/// Risk points at which the score reaches 63, the score tends to 100
+const RISK_SCALE: f64 = 500.0;
+
+/// Risk points of a finding: the weight of its severity (high 10, medium 5, low 2,
+/// informational 0) times the weight of its confidence (high 10, medium 6, low 3)
+pub fn risk_points(finding: &Finding) -> u32 {
+    let severity = match finding.severity {
+        Severity::High => 10,
+        Severity::Medium => 5,
+        Severity::Low => 2,
+        Severity::Informational => 0,
+    };
+    let confidence = match finding.confidence {
+        Confidence::High => 10,
+        Confidence::Medium => 6,
+        Confidence::Low => 3,
+    };
+    severity * confidence
+}
+
+/// Summarize the risk of findings
+///
+/// The score grows with the risk points of the findings and flattens out: one high finding
+/// with high confidence scores 18, five of them 63 and ten of them 86.
+pub fn risk_summary<'a>(findings: impl IntoIterator<Item = &'a Finding>) -> RiskSummary {
+    let findings: Vec<&Finding> = findings.into_iter().collect();
+    let points: u32 = findings.iter().map(|finding| risk_points(finding)).sum();
+    let score = (100.0 * (1.0 - (-f64::from(points) / RISK_SCALE).exp())).round() as u32;
+
+    let mut top_files: Vec<FileRisk> = group_by_file(findings.iter().copied())
+        .into_iter()
+        .map(|(file, findings)| FileRisk {
+            file: file.to_string(),
+            points: findings.iter().map(|finding| risk_points(finding)).sum(),
+            severities: SeverityCounts::of(findings),
+        })
+        .collect();
+    // Stable sort, the files with the same points stay sorted by path
+    top_files.sort_by_key(|file| std::cmp::Reverse(file.points));
+    top_files.truncate(TOP_RISKY_FILES);
+
+    let mut by_category: BTreeMap<Option<Category>, Vec<&Finding>> = BTreeMap::new();
+    for finding in &findings {
+        by_category.entry(finding.rule().and_then(|rule| rule.category)).or_default().push(finding);
+    }
+    let mut categories: Vec<CategoryRisk> = by_category
+        .into_iter()
+        .map(|(category, findings)| CategoryRisk {
+            category,
+            points: findings.iter().map(|finding| risk_points(finding)).sum(),
+            severities: SeverityCounts::of(findings),
+        })
+        .collect();
+    categories.sort_by_key(|category| std::cmp::Reverse(category.points));
+
+    RiskSummary { score, level: RiskLevel::of(score), top_files, categories }
+}
+
+/// Findings per thousand lines of code (KLOC), `None` without code
+pub fn density(findings: usize, lines_of_code: usize) -> Option<f64> {
+    (lines_of_code > 0).then(|| findings as f64 * 1000.0 / lines_of_code as f64)
+}
+
+/// Criteria selecting findings, for [`AnalysisResult::filter`]; unset criteria match every finding
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FindingFilter {
+    /// Lowest severity of the findings
+    pub min_severity: Option<Severity>,
+    /// Lowest confidence of the findings
+    pub min_confidence: Option<Confidence>,
+    /// Rules that reported the findings, one of them is enough (every rule if empty)
+    pub rules: Vec<String>,
+    /// Files or directories the findings are in (everywhere if empty)
+    pub paths: Vec<String>,
+}
+
+impl FindingFilter {
+    /// Check if a finding matches every criteria
+    pub fn matches(&self, finding: &Finding) -> bool {
+        self.min_severity.as_ref().is_none_or(|severity| finding.severity.is_at_least(severity))
+            && self.min_confidence.is_none_or(|confidence| finding.confidence >= confidence)
+            && (self.rules.is_empty() || finding.rules.iter().any(|rule| self.rules.contains(&rule.id)))
+            && (self.paths.is_empty()
+                || self.paths.iter().any(|path| {
+                    let path = path.trim_end_matches('/');
+                    finding.location.file == path || finding.location.file.starts_with(&format!("{path}/"))
+                }))
+    }
+}
+
+/// Deterministic order of findings: the most severe first, then by file, position, rule and description
+pub fn compare(a: &Finding, b: &Finding) -> Ordering {
+    b.severity.rank().cmp(&a.severity.rank()).then_with(|| compare_location(a, b))
+}
+
+/// Order of findings in the code: by file, line, column and rule, then by end position, severity
+/// and description so that no two different findings are equal
+pub fn compare_location(a: &Finding, b: &Finding) -> Ordering {
+    a.location
+        .file
+        .cmp(&b.location.file)
+        .then_with(|| a.location.line.cmp(&b.location.line))
+        .then_with(|| a.location.column.cmp(&b.location.column))
+        .then_with(|| rule_id(a).cmp(rule_id(b)))
+        .then_with(|| a.location.end_line.cmp(&b.location.end_line))
+        .then_with(|| a.location.end_column.cmp(&b.location.end_column))
+        .then_with(|| b.severity.rank().cmp(&a.severity.rank()))
+        .then_with(|| a.description.cmp(&b.description))
+}
+
+/// Source code of the files held in memory (e.g. read from stdin) by path, read by the reports
+/// instead of the files on disk
+pub type Sources = BTreeMap<String, String>;
+
+/// Lines of a file, from its source in memory or from the disk (`None` if it cannot be read)
+pub fn source_lines(file: &str, sources: Option<&Sources>) -> Option<Vec<String>> {
+    let lines = |source: &str| source.lines().map(str::to_string).collect();
+    match sources.and_then(|sources| sources.get(file)) {
+        Some(source) => Some(lines(source)),
+        None => std::fs::read_to_string(file).ok().map(|source| lines(&source)),
+    }
+}
+
+/// Group findings by file, sorted by path
+pub fn group_by_file<'a>(findings: impl IntoIterator<Item = &'a Finding>) -> BTreeMap<&'a str, Vec<&'a Finding>> {
+    let mut groups: BTreeMap<&str, Vec<&Finding>> = BTreeMap::new();
+    for finding in findings {
+        groups.entry(finding.location.file.as_str()).or_default().push(finding);
+    }
+    groups
+}
+
+/// Group findings by the rule that reported them (the first rule of merged findings), sorted by ID
+pub fn group_by_rule<'a>(findings: impl IntoIterator<Item = &'a Finding>) -> BTreeMap<&'a str, Vec<&'a Finding>> {
+    let mut groups: BTreeMap<&str, Vec<&Finding>> = BTreeMap::new();
+    for finding in findings {
+        groups.entry(rule_id(finding)).or_default().push(finding);
+    }
+    groups
+}
+
+/// Group findings by severity, the most severe first, leaving out the severities without findings
+pub fn group_by_severity<'a>(findings: impl IntoIterator<Item = &'a Finding>) -> Vec<(Severity, Vec<&'a Finding>)> {
+    let findings: Vec<&Finding> = findings.into_iter().collect();
+    Severity::ALL
+        .into_iter()
+        .map(|severity| {
+            let group = findings.iter().copied().filter(|finding| finding.severity == severity).collect();
+            (severity, group)
+        })
+        .filter(|(_, group): &(Severity, Vec<&Finding>)| !group.is_empty())
+        .collect()
+}
+
+/// Summarize the findings of each file, sorted by path
+pub fn file_summaries<'a>(findings: impl IntoIterator<Item = &'a Finding>) -> Vec<FileSummary> {
+    group_by_file(findings)
+        .into_iter()
+        .map(|(file, findings)| FileSummary {
+            file: file.to_string(),
+            severities: SeverityCounts::of(findings.iter().copied()),
+            rules: findings
+                .iter()
+                .flat_map(|finding| finding.rule_ids())
+                .map(str::to_string)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect(),
+        })
+        .collect()
+}
+
+/// Summarize the findings of each rule, sorted by rule ID
+pub fn rule_summaries<'a>(findings: impl IntoIterator<Item = &'a Finding>) -> Vec<RuleSummary> {
+    group_by_rule(findings)
+        .into_iter()
+        .map(|(rule_id, findings)| RuleSummary {
+            rule_id: rule_id.to_string(),
+            title: findings
+                .iter()
+                .find_map(|finding| finding.rule().map(|rule| rule.title.clone()))
+                .unwrap_or_default(),
+            severities: SeverityCounts::of(findings.iter().copied()),
+            files: group_by_file(findings).into_keys().map(str::to_string).collect(),
+        })
+        .collect()
+}
+
+/// Helper function to get the ID of the rule that reported a finding (empty if unknown)
+fn rule_id(finding: &Finding) -> &str {
+    finding.rule().map_or("", |rule| rule.id.as_str())
+}
+
+impl AnalysisResult {
+    /// Sort the findings in a deterministic order (see [`compare`])
+    pub fn sort(&mut self) {
+        self.findings.sort_by(compare);
+    }
+
+    /// Returns the findings matching a filter, in their order
+    pub fn filter(&self, filter: &FindingFilter) -> Vec<&Finding> {
+        self.findings.iter().filter(|finding| filter.matches(finding)).collect()
+    }
+
+    /// Returns the findings grouped by file, sorted by path
+    pub fn by_file(&self) -> BTreeMap<&str, Vec<&Finding>> {
+        group_by_file(&self.findings)
+    }
+
+    /// Returns the findings grouped by rule, sorted by rule ID
+    pub fn by_rule(&self) -> BTreeMap<&str, Vec<&Finding>> {
+        group_by_rule(&self.findings)
+    }
+
+    /// Returns the findings grouped by severity, the most severe first
+    pub fn by_severity(&self) -> Vec<(Severity, Vec<&Finding>)> {
+        group_by_severity(&self.findings)
+    }
+
+    /// Returns the number of findings of each severity
+    pub fn severity_counts(&self) -> SeverityCounts {
+        SeverityCounts::of(&self.findings)
+    }
+
+    /// Returns the summary of the findings of each file, sorted by path
+    pub fn file_summaries(&self) -> Vec<FileSummary> {
+        file_summaries(&self.findings)
+    }
+
+    /// Returns the summary of the findings of each rule, sorted by rule ID
+    pub fn rule_summaries(&self) -> Vec<RuleSummary> {
+        rule_summaries(&self.findings)
+    }
+
+    /// Returns the overall risk of the findings (see [`risk_summary`])
+    pub fn risk_summary(&self) -> RiskSummary {
+        risk_summary(&self.findings)
+    }
+}
@@ -0,0 +1,216 @@
+use crate::analyzer::results::{self, FindingFilter, RiskLevel, SeverityCounts};
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Category, Confidence, Finding, RuleInfo, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(rule_id: &str, severity: Severity, file: &str, line: usize) -> Finding {
+        FindingBuilder::new(format!("{rule_id} at line {line}"))
+            .severity(severity)
+            .confidence(Confidence::Medium)
+            .span(file, line, 5, 20)
+            .rule(RuleInfo {
+                id: rule_id.to_string(),
+                title: format!("Title of {rule_id}"),
+                ..Default::default()
+            })
+            .build()
+    }
+
+    fn result() -> AnalysisResult {
+        AnalysisResult {
+            findings: vec![
+                finding("error-handling", Severity::Low, "programs/vault/src/lib.rs", 9),
+                finding("signer-check", Severity::High, "programs/vault/src/state.rs", 4),
+                finding("error-handling", Severity::Low, "programs/vault/src/lib.rs", 2),
+                finding("overflow", Severity::Medium, "programs/vault/src/lib.rs", 7),
+                finding("signer-check", Severity::High, "programs/lending/src/lib.rs", 3),
+            ],
+            stats: AnalysisStats::default(),
+        }
+    }
+
+    fn lines(findings: &[&Finding]) -> Vec<(String, usize)> {
+        findings
+            .iter()
+            .map(|finding| (finding.location.file.clone(), finding.location.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_sort_is_deterministic() {
+        let mut result = result();
+        result.sort();
+        let sorted: Vec<&Finding> = result.findings.iter().collect();
+
+        assert_eq!(
+            lines(&sorted),
+            vec![
+                ("programs/lending/src/lib.rs".to_string(), 3),
+                ("programs/vault/src/state.rs".to_string(), 4),
+                ("programs/vault/src/lib.rs".to_string(), 7),
+                ("programs/vault/src/lib.rs".to_string(), 2),
+                ("programs/vault/src/lib.rs".to_string(), 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_location_order() {
+        let mut findings = result().findings;
+        findings.push(finding("access-control", Severity::Low, "programs/vault/src/lib.rs", 7));
+        findings.sort_by(results::compare_location);
+        let sorted: Vec<&Finding> = findings.iter().collect();
+
+        assert_eq!(
+            lines(&sorted),
+            vec![
+                ("programs/lending/src/lib.rs".to_string(), 3),
+                ("programs/vault/src/lib.rs".to_string(), 2),
+                ("programs/vault/src/lib.rs".to_string(), 7),
+                ("programs/vault/src/lib.rs".to_string(), 7),
+                ("programs/vault/src/lib.rs".to_string(), 9),
+                ("programs/vault/src/state.rs".to_string(), 4),
+            ]
+        );
+        // Same position: by rule ID
+        assert_eq!(sorted[2].rule_ids(), ["access-control"]);
+        assert_eq!(sorted[3].rule_ids(), ["overflow"]);
+    }
+
+    #[test]
+    fn test_groups() {
+        let result = result();
+
+        let by_file = result.by_file();
+        assert_eq!(
+            by_file.keys().copied().collect::<Vec<_>>(),
+            vec!["programs/lending/src/lib.rs", "programs/vault/src/lib.rs", "programs/vault/src/state.rs"]
+        );
+        assert_eq!(by_file["programs/vault/src/lib.rs"].len(), 3);
+
+        let by_rule = result.by_rule();
+        assert_eq!(by_rule.keys().copied().collect::<Vec<_>>(), vec!["error-handling", "overflow", "signer-check"]);
+
+        let by_severity: Vec<(Severity, usize)> = result
+            .by_severity()
+            .into_iter()
+            .map(|(severity, findings)| (severity, findings.len()))
+            .collect();
+        assert_eq!(by_severity, vec![(Severity::High, 2), (Severity::Medium, 1), (Severity::Low, 2)]);
+    }
+
+    #[test]
+    fn test_summaries() {
+        let result = result();
+
+        let counts = result.severity_counts();
+        assert_eq!(
+            counts,
+            SeverityCounts {
+                high: 2,
+                medium: 1,
+                low: 2,
+                informational: 0
+            }
+        );
+        assert_eq!(counts.total(), 5);
+        assert_eq!(counts.highest(), Some(Severity::High));
+        assert_eq!(SeverityCounts::default().highest(), None);
+
+        let files = result.file_summaries();
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[1].file, "programs/vault/src/lib.rs");
+        assert_eq!(files[1].severities.total(), 3);
+        assert_eq!(files[1].rules, vec!["error-handling", "overflow"]);
+
+        let rules = result.rule_summaries();
+        assert_eq!(rules.len(), 3);
+        assert_eq!(rules[2].rule_id, "signer-check");
+        assert_eq!(rules[2].title, "Title of signer-check");
+        assert_eq!(rules[2].severities.high, 2);
+        assert_eq!(rules[2].files, vec!["programs/lending/src/lib.rs", "programs/vault/src/state.rs"]);
+    }
+
+    #[test]
+    fn test_risk_summary() {
+        let mut result = result();
+        for finding in &mut result.findings {
+            finding.rules[0].category = match finding.rules[0].id.as_str() {
+                "signer-check" => Some(Category::MissingSigner),
+                "overflow" => Some(Category::Arithmetic),
+                _ => None,
+            };
+        }
+
+        // 2 high (60 points each), 1 medium (30) and 2 low (12) findings with medium confidence
+        let risk = result.risk_summary();
+        assert_eq!(results::risk_points(&result.findings[1]), 60);
+        assert_eq!(risk.score, 29);
+        assert_eq!(risk.level, RiskLevel::Medium);
+
+        // The riskiest files first, then by path
+        let files: Vec<(&str, u32)> = risk.top_files.iter().map(|file| (file.file.as_str(), file.points)).collect();
+        assert_eq!(
+            files,
+            vec![("programs/lending/src/lib.rs", 60), ("programs/vault/src/state.rs", 60), ("programs/vault/src/lib.rs", 54)]
+        );
+
+        let categories: Vec<(Option<Category>, u32, usize)> =
+            risk.categories.iter().map(|category| (category.category, category.points, category.severities.total())).collect();
+        assert_eq!(
+            categories,
+            vec![(Some(Category::MissingSigner), 120, 2), (Some(Category::Arithmetic), 30, 1), (None, 24, 2)]
+        );
+
+        // The score flattens out
+        let no_findings = results::risk_summary(&[]);
+        assert_eq!((no_findings.score, no_findings.level), (0, RiskLevel::None));
+        let mut high = finding("signer-check", Severity::High, "lib.rs", 1);
+        high.confidence = Confidence::High;
+        let scores: Vec<u32> = [1, 5, 10, 100]
+            .iter()
+            .map(|count| results::risk_summary(&vec![high.clone(); *count]).score)
+            .collect();
+        assert_eq!(scores, vec![18, 63, 86, 100]);
+        assert_eq!(RiskLevel::of(100), RiskLevel::Critical);
+    }
+
+    #[test]
+    fn test_filter() {
+        let result = result();
+
+        assert_eq!(result.filter(&FindingFilter::default()).len(), 5);
+
+        let severe = FindingFilter {
+            min_severity: Some(Severity::Medium),
+            ..Default::default()
+        };
+        assert_eq!(result.filter(&severe).len(), 3);
+
+        let in_vault = FindingFilter {
+            rules: vec!["signer-check".to_string()],
+            paths: vec!["programs/vault/".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            lines(&result.filter(&in_vault)),
+            vec![("programs/vault/src/state.rs".to_string(), 4)]
+        );
+
+        // Paths match whole directory names
+        let partial = FindingFilter {
+            paths: vec!["programs/vau".to_string()],
+            ..Default::default()
+        };
+        assert!(result.filter(&partial).is_empty());
+
+        let confident = FindingFilter {
+            min_confidence: Some(Confidence::High),
+            ..Default::default()
+        };
+        assert!(result.filter(&confident).is_empty());
+    }
+}
@@ -1,8 +1,65 @@
 pub mod solana;
 
+use std::sync::Arc;
+
 use crate::analyzer::Result;
-use crate::analyzer::engine::RuleEngine;
+use crate::analyzer::dsl::RuleBuildError;
+use crate::analyzer::engine::{Rule, RuleEngine};
+
+/// Built-in rule registered with `rule!` (or `inventory::submit!` for rules written with `RuleBuilder`)
+pub struct RuleRegistration {
+    /// Constructor of the rule
+    pub create: fn() -> std::result::Result<Arc<dyn Rule>, RuleBuildError>,
+}
+
+impl RuleRegistration {
+    /// Creates a registration from the rule constructor
+    pub const fn new(create: fn() -> std::result::Result<Arc<dyn Rule>, RuleBuildError>) -> Self {
+        Self { create }
+    }
+}
+
+inventory::collect!(RuleRegistration);
+
+/// Set of rules that can be registered in a rule engine
+///
+/// Downstream crates implement this trait to compile their own rules against this crate
+/// and compose them with the built-in ones (see `Analyzer::with_rule_plugins`):
+///
+/// ```ignore
+/// struct MyRules;
+///
+/// impl RulePlugin for MyRules {
+///     fn name(&self) -> &str {
+///         "my-rules"
+///     }
+///
+///     fn register_rules(&self, engine: &mut RuleEngine) -> Result<()> {
+///         engine.add_rule(my_rule::create_rule()?)?;
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait RulePlugin {
+    /// Name of the rule set, used in logs and errors
+    fn name(&self) -> &str;
 
+    /// Register the rules of the set in the rule engine
+    fn register_rules(&self, engine: &mut RuleEngine) -> Result<()>;
+}
+
+/// Rules shipped with the analyzer
+pub struct BuiltinRules;
+
+impl RulePlugin for BuiltinRules {
+    fn name(&self) -> &str {
+        "built-in"
+    }
+
+    fn register_rules(&self, engine: &mut RuleEngine) -> Result<()> {
+        register_builtin_rules(engine)
+    }
+}
 
 /// Register all built-in rules in the rule engine
 pub fn register_builtin_rules(engine: &mut RuleEngine) -> Result<()> {
@@ -12,20 +69,19 @@ pub fn register_builtin_rules(engine: &mut RuleEngine) -> Result<()> {
     Ok(())
 }
 
-/// Register Solana specific rules
+/// Register Solana specific rules (every rule module under `solana/` registers itself)
 fn register_solana_rules(engine: &mut RuleEngine) -> Result<()> {
-    // High severity rules
-    engine.add_rule(solana::high::unsafe_code::create_rule());
-    engine.add_rule(solana::high::missing_signer_check::create_rule());
-
-    // Medium severity rules
-    engine.add_rule(solana::medium::duplicate_mutable_accounts::create_rule());
-    engine.add_rule(solana::medium::division_by_zero::create_rule());
-    engine.add_rule(solana::medium::owner_check::create_rule());
-
-    // Low severity rules
-    engine.add_rule(solana::low::missing_error_handling::create_rule());
-    engine.add_rule(solana::low::anchor_instructions::create_rule());
+    let mut rules = inventory::iter::<RuleRegistration>
+        .into_iter()
+        .map(|registration| (registration.create)())
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    // Registration order depends on the linker, so sort for a stable rule order
+    rules.sort_by(|a, b| a.id().cmp(b.id()));
+
+    for rule in rules {
+        engine.add_rule(rule)?;
+    }
 
     Ok(())
 }
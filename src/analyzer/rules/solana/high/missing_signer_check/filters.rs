@@ -1,45 +1,147 @@
 
 use syn::{ItemStruct, Field, Attribute};
+use syn::spanned::Spanned;
 use quote::{quote, ToTokens};
 use log::debug;
 use anchor_syn::{AccountsStruct, AccountField};
 use syn1;
 
+use crate::analyzer::dsl::cache;
+use crate::analyzer::dsl::ProgramContext;
+use crate::analyzer::span_utils::SpanExtractor;
+use crate::analyzer::{Confidence, Fix};
+
+/// Default field names that usually hold the account authorizing an instruction
+pub const DEFAULT_AUTHORITY_NAMES: &[&str] = &["authority", "admin", "owner", "signer", "payer", "user", "creator", "manager"];
+
+/// Account field that needs signer verification
+pub struct UnsignedField {
+    /// Field name
+    pub name: String,
+    /// Whether the field is marked as mutable
+    pub mutable: bool,
+    /// Confidence that the field actually needs to sign
+    pub confidence: Confidence,
+}
+
 /// Filter for structs that have missing signer checks using anchor-syn
+#[cfg(test)]
 pub fn has_missing_signer_checks(item_struct: &ItemStruct) -> bool {
+    find_field_missing_signer_check(item_struct, &[], |_| false).is_some()
+}
+
+/// Returns the first account field that needs signer verification
+///
+/// Fields whose name contains one of `authority_names` are reported with high confidence.
+/// Fields for which `signed_in_code` holds (checked by the handlers, see [`signed_in_handlers`])
+/// are skipped.
+pub fn find_field_missing_signer_check(
+    item_struct: &ItemStruct,
+    authority_names: &[String],
+    signed_in_code: impl Fn(&str) -> bool,
+) -> Option<UnsignedField> {
     debug!("Checking struct '{}' for missing signer checks using anchor-syn", item_struct.ident);
     
     if !is_accounts_struct(item_struct) {
         debug!("Struct '{}' is not an Accounts struct, skipping", item_struct.ident);
-        return false;
+        return None;
     }
     
-    match convert_to_anchor_struct_optimized(item_struct) {
+    // The anchor-syn model is shared with other rules analyzing the same struct
+    let key = format!("anchor-accounts:{}:{:?}", item_struct.ident, item_struct.span().start());
+    let accounts_struct = cache::memoize(&key, || convert_to_anchor_struct_optimized(item_struct));
+
+    match accounts_struct.as_ref() {
         Ok(accounts_struct) => {
             debug!("Successfully parsed AccountsStruct with {} fields", accounts_struct.fields.len());
             
             for anchor_field in &accounts_struct.fields {
-                if let AccountField::Field(field) = anchor_field {
-                    if matches!(
+                if let AccountField::Field(field) = anchor_field
+                    && matches!(
                         field.ty,
                         anchor_syn::Ty::AccountInfo | anchor_syn::Ty::UncheckedAccount | anchor_syn::Ty::SystemAccount
-                    ) && !field.constraints.is_signer()
-                    {
-                        debug!("Found vulnerable field '{}' that needs signer verification", field.ident);
-                        return true;
-                    }
+                    )
+                    && !field.constraints.is_signer()
+                    && !signed_in_code(&field.ident.to_string())
+                {
+                    debug!("Found vulnerable field '{}' that needs signer verification", field.ident);
+                    let name = field.ident.to_string();
+                    return Some(UnsignedField {
+                        confidence: name_confidence(&name, authority_names),
+                        name,
+                        mutable: field.constraints.is_mutable(),
+                    });
                 }
             }
-            false
+            None
         },
         Err(e) => {
             debug!("Failed to parse struct with anchor-syn: {e}, using fallback");
             // Fallback to basic syn analysis
-            has_missing_signer_checks_fallback(item_struct)
+            find_field_missing_signer_check_fallback(item_struct, authority_names, signed_in_code)
         }
     }
 }
 
+/// Check if every instruction handler taking an accounts struct checks that a field signed,
+/// itself or in the helpers it calls (`require!(ctx.accounts.authority.is_signer, ..)`,
+/// `ctx.accounts.validate()?` checking `self.authority.is_signer`)
+///
+/// Structs no handler of the program takes are never considered checked.
+pub fn signed_in_handlers(program: &ProgramContext, accounts: &str, field: &str) -> bool {
+    let graph = program.call_graph();
+    let handlers: Vec<_> = graph.handlers_of(accounts).collect();
+    let pattern = format!("{field}.is_signer");
+    !handlers.is_empty()
+        && handlers.iter().all(|handler| {
+            graph.checks_from(handler).iter().any(|check| {
+                check.condition.match_indices(&pattern).any(|(index, _)| {
+                    !check.condition[..index].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+                })
+            })
+        })
+}
+
+/// Confidence that a field must sign, based on its name (authority-like names are expected to sign)
+fn name_confidence(name: &str, authority_names: &[String]) -> Confidence {
+    let name = name.to_lowercase();
+    if authority_names.iter().any(|authority| name.contains(&authority.to_lowercase())) {
+        Confidence::High
+    } else {
+        Confidence::Medium
+    }
+}
+
+/// Suggest adding the signer constraint to a field, extending its `#[account(...)]` attribute if present
+pub fn signer_fix(item_struct: &ItemStruct, field_name: &str, span_extractor: &SpanExtractor) -> Option<Fix> {
+    let syn::Fields::Named(fields_named) = &item_struct.fields else {
+        return None;
+    };
+    let field = fields_named
+        .named
+        .iter()
+        .find(|field| field.ident.as_ref().is_some_and(|ident| ident == field_name))?;
+
+    let account_attr = field.attrs.iter().find_map(|attr| match &attr.meta {
+        syn::Meta::List(meta_list) if meta_list.path.is_ident("account") => Some(meta_list),
+        _ => None,
+    });
+
+    let edit = match account_attr {
+        Some(meta_list) if meta_list.tokens.is_empty() => span_extractor.replace_edit(&meta_list.delimiter.span().join(), "(signer)"),
+        Some(meta_list) => span_extractor.insert_before_edit(&meta_list.tokens, "signer, "),
+        None => {
+            let indentation = span_extractor.indentation_of(field);
+            span_extractor.insert_before_edit(field, &format!("#[account(signer)]\n{indentation}"))
+        }
+    };
+
+    Some(Fix::machine_applicable(
+        &format!("Add the signer constraint to '{field_name}'"),
+        vec![edit],
+    ))
+}
+
 fn is_accounts_struct(item_struct: &ItemStruct) -> bool {
     for attr in &item_struct.attrs {
         if attr.path().is_ident("derive") {
@@ -123,7 +225,11 @@ fn generate_clean_struct_source(item_struct: &ItemStruct) -> String {
 }
 
 /// Fallback analysis using basic syn when anchor-syn fails
-fn has_missing_signer_checks_fallback(item_struct: &ItemStruct) -> bool {
+fn find_field_missing_signer_check_fallback(
+    item_struct: &ItemStruct,
+    authority_names: &[String],
+    signed_in_code: impl Fn(&str) -> bool,
+) -> Option<UnsignedField> {
     debug!("Using fallback syn analysis for struct '{}'", item_struct.ident);
     
     if let syn::Fields::Named(fields_named) = &item_struct.fields {
@@ -131,15 +237,21 @@ fn has_missing_signer_checks_fallback(item_struct: &ItemStruct) -> bool {
             if let Some(field_name) = &field.ident {
                 let field_type = quote::quote!(#field.ty).to_string();
                 
-                if field_needs_signer_check(field, &field_type) {
+                if field_needs_signer_check(field, &field_type) && !signed_in_code(&field_name.to_string()) {
                     debug!("Found field '{field_name}' that may need signer verification");
-                    return true;
+                    let name = field_name.to_string();
+                    // The fallback only matches type names, so it is never better than a guess
+                    return Some(UnsignedField {
+                        confidence: name_confidence(&name, authority_names).min(Confidence::Low),
+                        name,
+                        mutable: has_mut_constraint(&field.attrs),
+                    });
                 }
             }
         }
     }
     
-    false
+    None
 }
 
 /// Check if a specific field needs signer verification (fallback method)
@@ -168,3 +280,15 @@ fn has_signer_constraint(attrs: &[Attribute]) -> bool {
     false
 }
 
+/// Check if field has mut constraint in attributes (syn2 compatible)
+fn has_mut_constraint(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("account")
+            && attr
+                .meta
+                .to_token_stream()
+                .to_string()
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .any(|token| token == "mut")
+    })
+}
@@ -1,5 +1,6 @@
-use crate::analyzer::dsl::{AstQuery, RuleBuilder};
-use crate::analyzer::{Rule, Severity};
+use crate::analyzer::dsl::query::MetadataValue;
+use crate::analyzer::dsl::{AstQuery, RuleBuildError, RuleBuilder};
+use crate::analyzer::{Category, Rule, RuleType, Severity};
 use std::sync::Arc;
 use log::debug;
 
@@ -8,12 +9,17 @@ mod filters;
 #[cfg(test)]
 mod test;
 
-pub fn create_rule() -> Arc<dyn Rule> {
+pub fn create_rule() -> Result<Arc<dyn Rule>, RuleBuildError> {
     RuleBuilder::new()
         .id("missing-signer-check")
         .title("Missing Signer Check")
         .description("Detects Anchor account fields that may need signer verification")
         .severity(Severity::High)
+        .rule_type(RuleType::Anchor)
+        .tags(vec!["security", "access-control", "anchor"])
+        .pack("anchor-core")
+        .category(Category::MissingSigner)
+        .cwe(862)
         .recommendations(vec![
             "Add signer constraint to account fields that should be signed: #[account(signer)]",
             "Use Signer<'info> type for accounts that must be signers of the transaction",
@@ -21,19 +27,68 @@ pub fn create_rule() -> Arc<dyn Rule> {
             "Consider using #[account(constraint = account.key() == signer.key())] for explicit signer validation",
             "Review all account fields to ensure proper authorization and access control"
         ])
-        .dsl_query(|ast, file_path, span_extractor| {
+        .rationale("Anchor only checks that an account signed the transaction when its type is Signer or it has a signer constraint. An authority declared as AccountInfo or UncheckedAccount can be any account: an attacker passes the public key of the real authority without its signature and withdraws or reconfigures the accounts it controls.")
+        .vulnerable_example(r#"
+            #[derive(Accounts)]
+            pub struct Withdraw<'info> {
+                #[account(mut, has_one = authority)]
+                pub vault: Account<'info, Vault>,
+                #[account(mut)]
+                pub authority: AccountInfo<'info>,
+            }
+        "#)
+        .safe_example(r#"
+            #[derive(Accounts)]
+            pub struct Withdraw<'info> {
+                #[account(mut, has_one = authority)]
+                pub vault: Account<'info, Vault>,
+                pub authority: Signer<'info>,
+            }
+        "#)
+        .param(
+            "authority_names",
+            filters::DEFAULT_AUTHORITY_NAMES,
+            "Field names (substrings) of accounts that are expected to sign; other fields are reported with medium confidence",
+        )
+        .dsl_query_in_program_with_params(|ast, _file_path, _span_extractor, params, program| {
             debug!("Analyzing missing signer checks using DSL with specialized filters");
-            
+            let authority_names = params.get_list("authority_names").unwrap_or_default();
+
+            // Signer checks made by the handlers, directly or in helpers, count as constraints
             AstQuery::new(ast)
                 .structs()
                 .derives_accounts()
-                .filter(|node| {
-                    if let crate::analyzer::dsl::query::NodeData::Struct(item_struct) = &node.data {
-                        filters::has_missing_signer_checks(item_struct)
-                    } else {
-                        false
+                .annotate(|node| {
+                    if let crate::analyzer::dsl::query::NodeData::Struct(item_struct) = &node.data
+                        && let Some(field) = filters::find_field_missing_signer_check(item_struct, authority_names, |field| {
+                            filters::signed_in_handlers(program, &item_struct.ident.to_string(), field)
+                        })
+                    {
+                        node.set_metadata("field", field.name);
+                        node.set_metadata("mutable", field.mutable);
+                        node.downgrade_confidence(field.confidence);
                     }
                 })
+                .with_metadata("field")
+        })
+        .suggest_fix(|node, _context, span_extractor| {
+            match (&node.data, node.metadata("field")) {
+                (crate::analyzer::dsl::query::NodeData::Struct(item_struct), Some(MetadataValue::Text(field))) => {
+                    filters::signer_fix(item_struct, field, span_extractor)
+                }
+                _ => None,
+            }
+        })
+        .transform(|mut finding| {
+            // Read-only accounts cannot be drained or overwritten by an impostor
+            if finding.metadata.get("mutable") == Some(&MetadataValue::Bool(false)) {
+                finding.severity = Severity::Medium;
+            }
+            Some(finding)
         })
         .build()
 }
+
+inventory::submit! {
+    crate::analyzer::rules::RuleRegistration::new(create_rule)
+}
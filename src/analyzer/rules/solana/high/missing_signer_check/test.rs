@@ -1,4 +1,8 @@
-use crate::analyzer::rules::solana::high::missing_signer_check::filters::has_missing_signer_checks;
+use crate::analyzer::Confidence;
+use crate::analyzer::span_utils::SpanExtractor;
+use crate::analyzer::rules::solana::high::missing_signer_check::filters::{
+    DEFAULT_AUTHORITY_NAMES, find_field_missing_signer_check, has_missing_signer_checks, signer_fix,
+};
 use syn::{ItemStruct, parse_quote};
 
 #[cfg(test)]
@@ -97,4 +101,47 @@ mod tests {
         assert!(!has_missing_signer_checks(&struct_def), 
                 "Should not detect empty struct as vulnerable");
     }
+
+    #[test]
+    fn test_confidence_depends_on_field_name() {
+        let authority: ItemStruct = parse_quote! {
+            #[derive(Accounts)]
+            pub struct Withdraw<'info> {
+                pub vault_authority: AccountInfo<'info>,
+            }
+        };
+        let recipient: ItemStruct = parse_quote! {
+            #[derive(Accounts)]
+            pub struct Withdraw<'info> {
+                pub recipient: AccountInfo<'info>,
+            }
+        };
+
+        let authority_names: Vec<String> = DEFAULT_AUTHORITY_NAMES.iter().map(|name| name.to_string()).collect();
+        let field = find_field_missing_signer_check(&authority, &authority_names, |_| false).unwrap();
+        assert_eq!(field.confidence, Confidence::High);
+        let field = find_field_missing_signer_check(&recipient, &authority_names, |_| false).unwrap();
+        assert_eq!(field.confidence, Confidence::Medium);
+
+        // Configured names replace the defaults
+        let field = find_field_missing_signer_check(&recipient, &["recipient".to_string()], |_| false).unwrap();
+        assert_eq!(field.confidence, Confidence::High);
+    }
+
+    #[test]
+    fn test_signer_fix_edits() {
+        let source = "#[derive(Accounts)]\npub struct Withdraw<'info> {\n    #[account(mut)]\n    pub vault: AccountInfo<'info>,\n    pub authority: AccountInfo<'info>,\n}\n";
+        let item_struct: ItemStruct = syn::parse_str(source).unwrap();
+        let span_extractor = SpanExtractor::new(source.to_string(), "lib.rs".to_string());
+
+        // Existing #[account(...)] attributes are extended
+        let fix = signer_fix(&item_struct, "vault", &span_extractor).unwrap();
+        assert_eq!(fix.edits[0].replacement, "signer, ");
+        assert_eq!((fix.edits[0].location.line, fix.edits[0].location.column), (3, Some(14)));
+
+        // Otherwise a new attribute is inserted with the field's indentation
+        let fix = signer_fix(&item_struct, "authority", &span_extractor).unwrap();
+        assert_eq!(fix.edits[0].replacement, "#[account(signer)]\n    ");
+        assert_eq!((fix.edits[0].location.line, fix.edits[0].location.column), (5, Some(4)));
+    }
 }
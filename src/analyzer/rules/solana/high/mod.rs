@@ -1,3 +1,5 @@
 pub mod missing_signer_check;
+pub mod native_arbitrary_cpi;
+pub mod native_missing_signer_check;
 pub mod unsafe_code;
 
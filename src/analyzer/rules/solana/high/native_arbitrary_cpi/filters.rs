@@ -0,0 +1,20 @@
+use crate::analyzer::dsl::context::NodeContext;
+use crate::analyzer::dsl::query::AstNode;
+use crate::analyzer::native;
+
+/// Check if a native processor invokes an instruction whose program ID is the key of an account
+/// it does not check (no condition on `program.key`, nor a steel `is_program`/`has_address` call)
+pub fn invokes_unchecked_program(node: &AstNode<'_>, context: &NodeContext<'_>) -> bool {
+    let Some((sig, block)) = native::function_parts(node) else {
+        return false;
+    };
+    if native::accounts_param(sig).is_none() || !native::called_names(block).iter().any(|name| name.starts_with("invoke")) {
+        return false;
+    }
+    native::cpi_program_accounts(block).iter().any(|account| {
+        !native::methods_called_on(block, account)
+            .iter()
+            .any(|method| native::PROGRAM_CHECKS.contains(&method.as_str()))
+            && !native::checks_mention(context.program, context.file_path, sig, &format!("{account}.key"))
+    })
+}
@@ -0,0 +1,50 @@
+use crate::analyzer::dsl::rule;
+use crate::analyzer::engine::RuleType;
+
+mod filters;
+
+rule! {
+    id: "native-arbitrary-cpi",
+    severity: High,
+    rule_type: RuleType::Native,
+    title: "Arbitrary Cross-Program Invocation",
+    description: "Detects native processors invoking a program whose ID is taken from an account without checking its address, letting the caller substitute a malicious program",
+    recommendations: vec![
+        "Check the program account before invoking it: if program.key != &spl_token::ID { return Err(ProgramError::IncorrectProgramId) }",
+        "Build the instruction with the known program ID (spl_token::ID) instead of the key of the account passed",
+        "With steel, validate the program account with is_program(&ID)? or has_address(&ID)?"
+    ],
+    rationale: "The caller chooses every account of an instruction, including the program accounts. A program invoked through an account whose address is not checked can be replaced by a program of the attacker, which receives the accounts and signer privileges of the invocation: it can fake a transfer or use the signatures to move the user's tokens.",
+    vulnerable_example: r#"
+        fn transfer(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+            let accounts_iter = &mut accounts.iter();
+            let source = next_account_info(accounts_iter)?;
+            let destination = next_account_info(accounts_iter)?;
+            let token_program = next_account_info(accounts_iter)?;
+            let instruction = Instruction::new_with_bytes(*token_program.key, &amount.to_le_bytes(), vec![]);
+            invoke(&instruction, &[source.clone(), destination.clone()])
+        }
+    "#,
+    safe_example: r#"
+        fn transfer(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+            let accounts_iter = &mut accounts.iter();
+            let source = next_account_info(accounts_iter)?;
+            let destination = next_account_info(accounts_iter)?;
+            let token_program = next_account_info(accounts_iter)?;
+            if token_program.key != &spl_token::ID {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            let instruction = Instruction::new_with_bytes(*token_program.key, &amount.to_le_bytes(), vec![]);
+            invoke(&instruction, &[source.clone(), destination.clone()])
+        }
+    "#,
+    tag: "security",
+    tag: "cpi",
+    tag: "native",
+    pack: "native",
+    category: ArbitraryCpi,
+    cwe: 829,
+    query: functions(),
+    // Address checks made by the helpers the processor calls count
+    filter: filters::invokes_unchecked_program,
+}
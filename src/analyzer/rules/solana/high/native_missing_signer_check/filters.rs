@@ -0,0 +1,18 @@
+use crate::analyzer::dsl::context::NodeContext;
+use crate::analyzer::dsl::query::AstNode;
+use crate::analyzer::native;
+
+/// Check if a native processor (a function taking `&[AccountInfo]`) writes accounts without
+/// reading `is_signer` (the field, or the method of pinocchio and steel), in its body or in the
+/// checks of the helpers it calls
+pub fn writes_without_signer_check(node: &AstNode<'_>, context: &NodeContext<'_>) -> bool {
+    let Some((sig, block)) = native::function_parts(node) else {
+        return false;
+    };
+    native::accounts_param(sig).is_some()
+        && native::called_names(block).iter().any(|name| native::ACCOUNT_WRITES.contains(&name.as_str()))
+        && !native::uses_any(block, native::SIGNER_CHECKS)
+        && !native::SIGNER_CHECKS
+            .iter()
+            .any(|check| native::checks_mention(context.program, context.file_path, sig, check))
+}
@@ -0,0 +1,51 @@
+use crate::analyzer::dsl::rule;
+use crate::analyzer::engine::RuleType;
+
+mod filters;
+
+rule! {
+    id: "native-missing-signer-check",
+    severity: High,
+    rule_type: RuleType::Native,
+    title: "Missing Signer Check in Native Processor",
+    description: "Detects native processors that write accounts (mutable borrows, serialization, cross-program invocations) without checking that an account signed the transaction",
+    recommendations: vec![
+        "Check the authority of the instruction before modifying accounts: if !authority.is_signer { return Err(ProgramError::MissingRequiredSignature) }",
+        "Check the signer in a helper shared by the processors that need it",
+        "Compare the signer with the authority stored in the account data before trusting it"
+    ],
+    rationale: "Native programs get every account unchecked. Without an is_signer check, anyone can send the instruction with the accounts of another user and modify them: withdraw the lamports of a vault or change the authority of an account.",
+    vulnerable_example: r#"
+        fn withdraw(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+            let accounts_iter = &mut accounts.iter();
+            let vault = next_account_info(accounts_iter)?;
+            let destination = next_account_info(accounts_iter)?;
+            **vault.try_borrow_mut_lamports()? -= amount;
+            **destination.try_borrow_mut_lamports()? += amount;
+            Ok(())
+        }
+    "#,
+    safe_example: r#"
+        fn withdraw(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+            let accounts_iter = &mut accounts.iter();
+            let authority = next_account_info(accounts_iter)?;
+            let vault = next_account_info(accounts_iter)?;
+            let destination = next_account_info(accounts_iter)?;
+            if !authority.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            **vault.try_borrow_mut_lamports()? -= amount;
+            **destination.try_borrow_mut_lamports()? += amount;
+            Ok(())
+        }
+    "#,
+    tag: "security",
+    tag: "access-control",
+    tag: "native",
+    pack: "native",
+    category: MissingSigner,
+    cwe: 862,
+    query: functions(),
+    // Signer checks made by the helpers the processor calls count
+    filter: filters::writes_without_signer_check,
+}
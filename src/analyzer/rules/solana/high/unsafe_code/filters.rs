@@ -30,7 +30,7 @@ impl<'a> UnsafeCodeFilters<'a> for AstQuery<'a> {
             }
         }
 
-        AstQuery::from_nodes(new_results)
+        self.with_results(new_results)
     }
 }
 
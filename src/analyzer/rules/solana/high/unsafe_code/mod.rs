@@ -1,36 +1,39 @@
-use log::debug;
-use std::sync::Arc;
-
-use crate::analyzer::dsl::{RuleBuilder, AstQuery};
-use crate::analyzer::{Rule, Severity};
+use crate::analyzer::dsl::rule;
 use crate::analyzer::engine::RuleType;
 
 // Import our specific filters
 mod filters;
 use filters::UnsafeCodeFilters;
 
-pub fn create_rule() -> Arc<dyn Rule> {
-    RuleBuilder::new()
-        .id("solana-unsafe-code")
-        .title("Unsafe Code Usage")
-        .description("Using unsafe code in Solana programs can lead to security vulnerabilities")
-        .severity(Severity::High)
-        .rule_type(RuleType::Solana)
-        .tag("security")
-        .tag("unsafe")
-        .reference(".")
-        .reference("https://doc.rust-lang.org/book/ch20-01-unsafe-rust.html")
-        .recommendations(vec![
-            "Avoid using unsafe code in Solana programs unless absolutely necessary",
-            "If unsafe is required, thoroughly document why it's needed and ensure all invariants are maintained",
-            "Consider using safe alternatives like checked arithmetic operations"
-        ])
-        .dsl_query(|ast, _file_path, _span_extractor| {
-            debug!("Analyzing unsafe code");
-            
-            AstQuery::new(ast)
-                .functions()                           
-                .uses_unsafe()                         
-        })
-        .build()
+rule! {
+    id: "solana-unsafe-code",
+    title: "Unsafe Code Usage",
+    description: "Using unsafe code in Solana programs can lead to security vulnerabilities",
+    severity: High,
+    rule_type: RuleType::Solana,
+    tag: "security",
+    tag: "unsafe",
+    reference: ".",
+    reference: "https://doc.rust-lang.org/book/ch20-01-unsafe-rust.html",
+    recommendations: vec![
+        "Avoid using unsafe code in Solana programs unless absolutely necessary",
+        "If unsafe is required, thoroughly document why it's needed and ensure all invariants are maintained",
+        "Consider using safe alternatives like checked arithmetic operations"
+    ],
+    rationale: "Unsafe code opts out of the guarantees of the borrow checker: a misaligned or out-of-bounds read of account data is undefined behavior, and can read memory of other accounts or let crafted data violate the invariants of the program.",
+    vulnerable_example: r#"
+        pub fn read_balance(data: &[u8]) -> u64 {
+            unsafe { *(data.as_ptr() as *const u64) }
+        }
+    "#,
+    safe_example: r#"
+        pub fn read_balance(data: &[u8]) -> Option<u64> {
+            Some(u64::from_le_bytes(data.get(..8)?.try_into().ok()?))
+        }
+    "#,
+    pack: "native",
+    pack: "anchor-core",
+    category: UnsafeCode,
+    cwe: 119,
+    query: functions().uses_unsafe(),
 }
@@ -48,6 +48,6 @@ impl<'a> AnchorInstructionsFilters<'a> for AstQuery<'a> {
             }
         }
 
-        AstQuery::from_nodes(new_results)
+        self.with_results(new_results)
     }
 }
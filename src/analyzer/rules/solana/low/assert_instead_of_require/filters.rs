@@ -0,0 +1,124 @@
+use log::{debug, trace};
+use syn::punctuated::Punctuated;
+use syn::visit::Visit;
+use syn::{Expr, Token};
+
+use crate::analyzer::dsl::query::{AstQuery, NodeData};
+use crate::analyzer::span_utils::SpanExtractor;
+use crate::analyzer::{Applicability, Fix};
+
+/// Assertions and the Anchor macros replacing them, with the error they return
+const ASSERTIONS: &[(&str, &str, &str)] = &[
+    ("assert", "require", "RequireViolated"),
+    ("assert_eq", "require_eq", "RequireEqViolated"),
+    ("assert_ne", "require_neq", "RequireNeqViolated"),
+];
+
+pub trait AssertInsteadOfRequireFilters<'a> {
+    fn has_assertions(self) -> AstQuery<'a>;
+}
+
+impl<'a> AssertInsteadOfRequireFilters<'a> for AstQuery<'a> {
+    fn has_assertions(self) -> AstQuery<'a> {
+        debug!("Filtering functions returning a Result that panic with assertions");
+        let mut new_results = Vec::new();
+
+        for node in self.results() {
+            let (sig, block) = match node.data {
+                NodeData::Function(func) => (&func.sig, &*func.block),
+                NodeData::ImplFunction(func) => (&func.sig, &func.block),
+                _ => continue,
+            };
+            if !find_assertions(sig, block).is_empty() {
+                trace!("Found assertions in function: {}", sig.ident);
+                new_results.push(node.clone());
+            }
+        }
+
+        self.with_results(new_results)
+    }
+}
+
+/// Returns the `assert!`, `assert_eq!` and `assert_ne!` invocations of a function returning
+/// a `Result`, which can return an error instead of panicking
+pub fn find_assertions<'a>(sig: &syn::Signature, block: &'a syn::Block) -> Vec<&'a syn::Macro> {
+    if !returns_result(&sig.output) {
+        return Vec::new();
+    }
+    let mut finder = AssertionFinder { assertions: Vec::new() };
+    finder.visit_block(block);
+    finder.assertions
+}
+
+/// Suggest replacing the assertions of a function with `require!`, `require_eq!` and `require_neq!`
+///
+/// `assert!` without a message becomes `require!` as is; the other assertions lose their
+/// message and need values that implement `Display`, so the fix is advisory then.
+pub fn require_fix(sig: &syn::Signature, block: &syn::Block, span_extractor: &SpanExtractor) -> Option<Fix> {
+    let mut applicability = Applicability::MachineApplicable;
+    let mut edits = Vec::new();
+    for assertion in find_assertions(sig, block) {
+        let Some((name, require, error)) = assertion_of(assertion) else {
+            continue;
+        };
+        let Ok(arguments) = assertion.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) else {
+            continue;
+        };
+        let operands = if name == "assert" { 1 } else { 2 };
+        if arguments.len() < operands {
+            continue;
+        }
+        if name != "assert" || arguments.len() > operands {
+            applicability = Applicability::Advisory;
+        }
+
+        let operands: Vec<String> = arguments.iter().take(operands).map(|argument| span_extractor.extract_snippet(argument)).collect();
+        edits.push(span_extractor.replace_edit(
+            assertion,
+            &format!("{require}!({}, anchor_lang::error::ErrorCode::{error})", operands.join(", ")),
+        ));
+    }
+    if edits.is_empty() {
+        return None;
+    }
+
+    Some(match applicability {
+        Applicability::MachineApplicable => Fix::machine_applicable("Replace the assertions with require!", edits),
+        Applicability::Advisory => Fix::new("Replace the assertions with require!", edits),
+    })
+}
+
+/// Helper function to find the assertion of a macro invocation
+fn assertion_of(mac: &syn::Macro) -> Option<(&'static str, &'static str, &'static str)> {
+    let name = mac.path.segments.last()?.ident.to_string();
+    ASSERTIONS.iter().copied().find(|(assertion, ..)| *assertion == name)
+}
+
+/// Helper function to check if a function returns a `Result`
+fn returns_result(output: &syn::ReturnType) -> bool {
+    match output {
+        syn::ReturnType::Type(_, ty) => match &**ty {
+            syn::Type::Path(path) => path.path.segments.last().is_some_and(|segment| segment.ident == "Result"),
+            _ => false,
+        },
+        syn::ReturnType::Default => false,
+    }
+}
+
+/// Helper visitor to collect the assertions of a function, outside of closures
+struct AssertionFinder<'ast> {
+    assertions: Vec<&'ast syn::Macro>,
+}
+
+impl<'ast> Visit<'ast> for AssertionFinder<'ast> {
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if assertion_of(mac).is_some() {
+            self.assertions.push(mac);
+        }
+    }
+
+    // `require!` returns from the closure, not from the function
+    fn visit_expr_closure(&mut self, _closure: &'ast syn::ExprClosure) {}
+
+    fn visit_item(&mut self, _item: &'ast syn::Item) {}
+}
@@ -0,0 +1,49 @@
+use crate::analyzer::dsl::query::NodeData;
+use crate::analyzer::dsl::rule;
+use crate::analyzer::engine::RuleType;
+
+// Import our specific filters
+mod filters;
+use filters::AssertInsteadOfRequireFilters;
+
+#[cfg(test)]
+mod test;
+
+rule! {
+    id: "assert-instead-of-require",
+    severity: Low,
+    rule_type: RuleType::Anchor,
+    title: "Assertion Instead of require!",
+    description: "Detects assert!, assert_eq! and assert_ne! in functions returning a Result, which panic instead of returning an error",
+    recommendations: vec![
+        "Replace assert!(condition) with require!(condition, ErrorCode::YourError)",
+        "Replace assert_eq! and assert_ne! with require_eq! and require_neq! (require_keys_eq! and require_keys_neq! for public keys)",
+        "Declare the errors of the program with #[error_code] so clients can tell them apart"
+    ],
+    rationale: "A failed assertion panics: the transaction aborts with a generic program error and the message is only in the logs, so clients and other programs calling through CPI cannot tell the failure apart. require! returns an error of the program instead, with its code, and keeps the control flow visible in the signature of the handler.",
+    vulnerable_example: r#"
+        pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+            assert!(amount <= ctx.accounts.vault.balance);
+            ctx.accounts.vault.balance -= amount;
+            Ok(())
+        }
+    "#,
+    safe_example: r#"
+        pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+            require!(amount <= ctx.accounts.vault.balance, VaultError::InsufficientFunds);
+            ctx.accounts.vault.balance -= amount;
+            Ok(())
+        }
+    "#,
+    tag: "error-handling",
+    tag: "anchor",
+    pack: "style",
+    category: ErrorHandling,
+    cwe: 617,
+    query: functions().has_assertions(),
+    suggest_fix: |node, _context, span_extractor| match &node.data {
+        NodeData::Function(func) => filters::require_fix(&func.sig, &func.block, span_extractor),
+        NodeData::ImplFunction(func) => filters::require_fix(&func.sig, &func.block, span_extractor),
+        _ => None,
+    },
+}
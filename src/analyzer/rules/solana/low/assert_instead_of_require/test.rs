@@ -0,0 +1,55 @@
+use crate::analyzer::Applicability;
+use crate::analyzer::rules::solana::low::assert_instead_of_require::filters::{find_assertions, require_fix};
+use crate::analyzer::span_utils::SpanExtractor;
+use syn::{ItemFn, parse_quote};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_assertions() {
+        let func: ItemFn = parse_quote! {
+            pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+                assert!(amount > 0);
+                assert_eq!(ctx.accounts.vault.owner, ctx.accounts.owner.key(), "wrong owner");
+                debug_assert!(amount < u64::MAX);
+                let check = |value: u64| assert!(value > 0);
+                check(amount);
+                Ok(())
+            }
+        };
+        // Closures and debug assertions are left out
+        assert_eq!(find_assertions(&func.sig, &func.block).len(), 2);
+
+        // Functions that cannot return an error can only panic
+        let func: ItemFn = parse_quote! {
+            fn share(amount: u64, shares: u64) -> u64 {
+                assert!(shares > 0);
+                amount / shares
+            }
+        };
+        assert!(find_assertions(&func.sig, &func.block).is_empty());
+    }
+
+    #[test]
+    fn test_require_fix_edits() {
+        let source = "pub fn deposit(amount: u64, limit: u64) -> Result<()> {\n    assert!(amount > 0);\n    assert!(amount <= limit);\n    Ok(())\n}\n";
+        let func: ItemFn = syn::parse_str(source).unwrap();
+        let span_extractor = SpanExtractor::new(source.to_string(), "lib.rs".to_string());
+
+        let fix = require_fix(&func.sig, &func.block, &span_extractor).unwrap();
+        assert_eq!(fix.applicability, Applicability::MachineApplicable);
+        assert_eq!(fix.edits[0].replacement, "require!(amount > 0, anchor_lang::error::ErrorCode::RequireViolated)");
+        assert_eq!((fix.edits[0].location.line, fix.edits[0].location.column), (2, Some(4)));
+        assert_eq!(fix.edits[1].replacement, "require!(amount <= limit, anchor_lang::error::ErrorCode::RequireViolated)");
+
+        // Messages are dropped and require_eq! needs Display values: the fix is advisory
+        let source = "pub fn close(owner: Pubkey, signer: Pubkey) -> Result<()> {\n    assert_eq!(owner, signer, \"wrong owner\");\n    Ok(())\n}\n";
+        let func: ItemFn = syn::parse_str(source).unwrap();
+        let span_extractor = SpanExtractor::new(source.to_string(), "lib.rs".to_string());
+        let fix = require_fix(&func.sig, &func.block, &span_extractor).unwrap();
+        assert_eq!(fix.applicability, Applicability::Advisory);
+        assert_eq!(fix.edits[0].replacement, "require_eq!(owner, signer, anchor_lang::error::ErrorCode::RequireEqViolated)");
+    }
+}
@@ -34,7 +34,7 @@ impl<'a> MissingErrorHandlingFilters<'a> for AstQuery<'a> {
             }
         }
 
-        AstQuery::from_nodes(new_results)
+        self.with_results(new_results)
     }
 }
 
@@ -1,36 +1,38 @@
-use log::debug;
-use std::sync::Arc;
-
-use crate::analyzer::dsl::{RuleBuilder, AstQuery};
-use crate::analyzer::{Rule, Severity};
+use crate::analyzer::dsl::rule;
 use crate::analyzer::engine::RuleType;
 
 // Import our specific filters
 mod filters;
 use filters::MissingErrorHandlingFilters;
 
-pub fn create_rule() -> Arc<dyn Rule> {
-    RuleBuilder::new()
-        .id("solana-missing-error-handling")
-        .severity(Severity::Low)
-        .title("Missing Error Handling in Public Functions")
-        .description("Detects public functions that don't return Result<T> and may fail silently. In Solana contracts, proper error handling is essential for security and debugging.")
-        .recommendations(vec![
-            "Change function return type to Result<T, YourErrorType> to handle potential failures",
-            "Use Anchor's Result<()> for instruction handlers to properly propagate errors",
-            "Implement custom error types using #[error_code] for better error reporting",
-            "Add proper error handling with ? operator or explicit error returns",
-            "Consider using anchor_lang::Result for Anchor-specific error handling"
-        ])
-        .rule_type(RuleType::Solana)
-        .tag("error-handling")
-        .tag("best-practices")
-        .dsl_query(|ast, _file_path, _span_extractor| {
-            debug!("Analyzing missing error handling");
-            
-            AstQuery::new(ast)
-                .functions()                           
-                .missing_error_handling()              
-        })
-        .build()
+rule! {
+    id: "solana-missing-error-handling",
+    severity: Low,
+    title: "Missing Error Handling in Public Functions",
+    description: "Detects public functions that don't return Result<T> and may fail silently. In Solana contracts, proper error handling is essential for security and debugging.",
+    recommendations: vec![
+        "Change function return type to Result<T, YourErrorType> to handle potential failures",
+        "Use Anchor's Result<()> for instruction handlers to properly propagate errors",
+        "Implement custom error types using #[error_code] for better error reporting",
+        "Add proper error handling with ? operator or explicit error returns",
+        "Consider using anchor_lang::Result for Anchor-specific error handling"
+    ],
+    rationale: "A public function that cannot return an error has to panic, saturate or return a default value when its inputs are invalid. Panics abort the transaction without a meaningful error, and silent defaults let the program continue with a wrong amount or state.",
+    vulnerable_example: r#"
+        pub fn compute_fee(amount: u64) -> u64 {
+            amount.saturating_mul(3) / 1000
+        }
+    "#,
+    safe_example: r#"
+        pub fn compute_fee(amount: u64) -> Result<u64> {
+            amount.checked_mul(3).map(|fee| fee / 1000).ok_or(error!(ErrorCode::Overflow))
+        }
+    "#,
+    rule_type: RuleType::Solana,
+    tag: "error-handling",
+    tag: "best-practices",
+    pack: "style",
+    category: ErrorHandling,
+    cwe: 703,
+    query: functions().missing_error_handling(),
 }
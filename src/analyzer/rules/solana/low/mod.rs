@@ -1,3 +1,6 @@
 pub mod missing_error_handling;
 pub mod anchor_instructions;
+pub mod assert_instead_of_require;
 
+pub mod native_unchecked_account_index;
+pub mod native_unchecked_data_length;
@@ -0,0 +1,15 @@
+use crate::analyzer::dsl::context::NodeContext;
+use crate::analyzer::dsl::query::AstNode;
+use crate::analyzer::native;
+
+/// Check if a native processor indexes its `&[AccountInfo]` parameter without checking its
+/// length, itself or in the helpers it calls
+pub fn indexes_unchecked_accounts(node: &AstNode<'_>, context: &NodeContext<'_>) -> bool {
+    let Some((sig, block)) = native::function_parts(node) else {
+        return false;
+    };
+    native::accounts_param(sig).is_some_and(|accounts| {
+        !native::indexes_of(block, &accounts).is_empty()
+            && !native::checks_mention(context.program, context.file_path, sig, &format!("{accounts}.len()"))
+    })
+}
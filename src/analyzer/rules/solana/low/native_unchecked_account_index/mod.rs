@@ -0,0 +1,45 @@
+use crate::analyzer::dsl::rule;
+use crate::analyzer::engine::RuleType;
+
+mod filters;
+
+rule! {
+    id: "native-unchecked-account-index",
+    severity: Low,
+    rule_type: RuleType::Native,
+    title: "Unchecked Account Index",
+    description: "Detects native processors that index the accounts slice without checking its length, making the program panic when fewer accounts are passed",
+    recommendations: vec![
+        "Iterate over the accounts with next_account_info(&mut accounts.iter())?, which returns NotEnoughAccountKeys",
+        "Check the number of accounts before indexing: if accounts.len() < 3 { return Err(ProgramError::NotEnoughAccountKeys) }",
+        "Destructure the slice with a pattern: let [payer, vault, ..] = accounts else { return Err(..) }"
+    ],
+    rationale: "The caller decides how many accounts an instruction gets. Indexing the accounts slice past its end panics, and the transaction fails with an opaque error instead of NotEnoughAccountKeys, which makes integrations hard to debug and hides the intended account layout.",
+    vulnerable_example: r#"
+        fn close(accounts: &[AccountInfo]) -> ProgramResult {
+            let vault = &accounts[0];
+            let destination = &accounts[1];
+            **destination.try_borrow_mut_lamports()? += vault.lamports();
+            **vault.try_borrow_mut_lamports()? = 0;
+            Ok(())
+        }
+    "#,
+    safe_example: r#"
+        fn close(accounts: &[AccountInfo]) -> ProgramResult {
+            let accounts_iter = &mut accounts.iter();
+            let vault = next_account_info(accounts_iter)?;
+            let destination = next_account_info(accounts_iter)?;
+            **destination.try_borrow_mut_lamports()? += vault.lamports();
+            **vault.try_borrow_mut_lamports()? = 0;
+            Ok(())
+        }
+    "#,
+    tag: "security",
+    tag: "validation",
+    tag: "native",
+    pack: "native",
+    category: ErrorHandling,
+    cwe: 129,
+    query: functions(),
+    filter: filters::indexes_unchecked_accounts,
+}
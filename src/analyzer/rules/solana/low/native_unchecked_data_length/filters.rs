@@ -0,0 +1,16 @@
+use crate::analyzer::dsl::context::NodeContext;
+use crate::analyzer::dsl::query::AstNode;
+use crate::analyzer::native;
+
+/// Check if a function indexes or slices one of its `&[u8]` parameters without checking its
+/// length (`data.len()`, `data.is_empty()`), itself or in the helpers it calls
+pub fn indexes_unchecked_data(node: &AstNode<'_>, context: &NodeContext<'_>) -> bool {
+    let Some((sig, block)) = native::function_parts(node) else {
+        return false;
+    };
+    native::byte_slice_params(sig).iter().any(|param| {
+        !native::indexes_of(block, param).is_empty()
+            && !native::checks_mention(context.program, context.file_path, sig, &format!("{param}.len()"))
+            && !native::checks_mention(context.program, context.file_path, sig, &format!("{param}.is_empty()"))
+    })
+}
@@ -0,0 +1,47 @@
+use crate::analyzer::dsl::rule;
+use crate::analyzer::engine::RuleType;
+
+mod filters;
+
+rule! {
+    id: "native-unchecked-data-length",
+    severity: Low,
+    rule_type: RuleType::Native,
+    title: "Unchecked Instruction Data Length",
+    description: "Detects native functions that index or slice instruction data without checking its length, making the program panic on short inputs",
+    recommendations: vec![
+        "Check the length before indexing: if instruction_data.len() < 9 { return Err(ProgramError::InvalidInstructionData) }",
+        "Use split_first(), get() or try_into() to read the data without panicking",
+        "Deserialize the instruction with Borsh (try_from_slice) and handle the error"
+    ],
+    rationale: "Instruction data is arbitrary bytes chosen by the caller. Indexing or slicing it without checking its length panics on short inputs instead of returning InvalidInstructionData, and a parser that trusts the length can read the fields of another instruction layout.",
+    vulnerable_example: r#"
+        pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+            let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+            match instruction_data[0] {
+                0 => deposit(accounts, amount),
+                _ => withdraw(accounts, amount),
+            }
+        }
+    "#,
+    safe_example: r#"
+        pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+            if instruction_data.len() < 9 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+            match instruction_data[0] {
+                0 => deposit(accounts, amount),
+                _ => withdraw(accounts, amount),
+            }
+        }
+    "#,
+    tag: "security",
+    tag: "validation",
+    tag: "native",
+    pack: "native",
+    category: ErrorHandling,
+    cwe: 129,
+    query: functions(),
+    filter: filters::indexes_unchecked_data,
+}
@@ -1,7 +1,10 @@
 use log::{debug, trace};
 use syn::visit::{self, Visit};
-use std::collections::HashMap;
-use crate::analyzer::dsl::query::{AstQuery, NodeData};
+use crate::analyzer::dsl::context::NodeContext;
+use crate::analyzer::dsl::query::{AstNode, AstQuery, NodeData};
+use crate::analyzer::dsl::values::{Constants, ValueAnalysis};
+use crate::analyzer::span_utils::SpanExtractor;
+use crate::analyzer::Fix;
 
 pub trait DivisionByZeroFilters<'a> {
     fn has_unsafe_divisions(self) -> AstQuery<'a>;
@@ -11,122 +14,233 @@ impl<'a> DivisionByZeroFilters<'a> for AstQuery<'a> {
     fn has_unsafe_divisions(self) -> AstQuery<'a> {
         debug!("Filtering functions with unsafe division operations");
         
+        // Divisors can be constants of the file
+        let constants = self.root().map(|ast| Constants::from_items(&ast.items)).unwrap_or_default();
         let mut new_results = Vec::new();
         
         for node in self.results() {
-            match &node.data {
-                NodeData::Function(func) => {
-                    let mut finder = UnsafeDivisionFinder {
-                        found: false,
-                        safe_variables: HashMap::new(),
-                    };
-                    
-                    syn::visit::visit_block(&mut finder, &func.block);
-                    
-                    if finder.found {
-                        trace!("Found function with unsafe divisions: {}", func.sig.ident);
-                        new_results.push(node.clone());
-                    }
-                }
-                NodeData::ImplFunction(func) => {
-                    let mut finder = UnsafeDivisionFinder {
-                        found: false,
-                        safe_variables: HashMap::new(),
-                    };
-                    
-                    syn::visit::visit_block(&mut finder, &func.block);
-                    
-                    if finder.found {
-                        trace!("Found impl function with unsafe divisions: {}", func.sig.ident);
-                        new_results.push(node.clone());
-                    }
-                }
-                _ => {}
+            if has_unsafe_divisions(node, &constants) {
+                trace!("Found function with unsafe divisions: {}", node.name());
+                new_results.push(node.clone());
             }
         }
         
-        AstQuery::from_nodes(new_results)
+        self.with_results(new_results)
     }
 }
 
-/// Helper visitor to find unsafe division operations
-struct UnsafeDivisionFinder {
-    found: bool,
-    safe_variables: HashMap<String, bool>,
+/// Check if a function node divides by a value that may be zero, given the known constants
+pub fn has_unsafe_divisions(node: &AstNode<'_>, constants: &Constants) -> bool {
+    match &node.data {
+        NodeData::Function(func) => !find_unsafe_divisions(&func.sig, &func.block, constants).is_empty(),
+        NodeData::ImplFunction(func) => !find_unsafe_divisions(&func.sig, &func.block, constants).is_empty(),
+        _ => false,
+    }
+}
+
+/// Returns the division operations of a function whose divisor may be zero
+///
+/// Divisors proven nonzero by the value analysis (nonzero constants, variables checked with
+/// `require!(divisor > 0)` or an early return) are safe.
+pub fn find_unsafe_divisions<'a>(sig: &syn::Signature, block: &'a syn::Block, constants: &Constants) -> Vec<&'a syn::ExprBinary> {
+    let values = ValueAnalysis::of_function(sig, block, constants);
+    unsafe_divisions(block, &values).into_iter().map(|division| division.expr).collect()
+}
+
+/// Suggest replacing each unsafe division of a function with `checked_div`
+///
+/// Only divisions of a variable or an integer literal by another, outside closures, async
+/// blocks and nested items, are rewritten. The fix is machine-applicable when the operands are
+/// known integers, the function returns a `ProgramError` with `?` (`ProgramResult`,
+/// `Result<T, ProgramError>`, or the `Result<T>` of Anchor's prelude) and the file imports
+/// `ProgramError`, advisory otherwise.
+pub fn checked_div_fix(
+    sig: &syn::Signature,
+    block: &syn::Block,
+    context: &NodeContext<'_>,
+    span_extractor: &SpanExtractor,
+) -> Option<Fix> {
+    let values = ValueAnalysis::of_function(sig, block, &context.constants());
+    let divisions: Vec<_> = unsafe_divisions(block, &values)
+        .into_iter()
+        // `?` in a closure returns from the closure, and other operands would change precedence
+        .filter(|division| !division.nested && is_plain_operand(&division.expr.left) && is_plain_operand(&division.expr.right))
+        .map(|division| division.expr)
+        .collect();
+    if divisions.is_empty() {
+        return None;
+    }
+
+    // Floats have no checked_div, and the type of other variables is unknown
+    let integers = divisions
+        .iter()
+        .all(|division| values.range_of(&division.left).is_some() && values.range_of(&division.right).is_some());
+    let edits = divisions
+        .into_iter()
+        .map(|division| {
+            let left = span_extractor.extract_snippet(&*division.left);
+            let right = span_extractor.extract_snippet(&*division.right);
+            span_extractor.replace_edit(
+                division,
+                &format!("{left}.checked_div({right}).ok_or(ProgramError::ArithmeticOverflow)?"),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if integers && returns_program_error(&sig.output, context.ast) && imports_program_error(context.ast) {
+        Some(Fix::machine_applicable("Replace the division with checked_div", edits))
+    } else {
+        Some(Fix::new("Replace the division with checked_div", edits))
+    }
+}
+
+/// Helper function to check if an operand can receive a method call as is: a variable, a
+/// constant or an integer literal
+fn is_plain_operand(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Path(_) => true,
+        syn::Expr::Lit(lit) => matches!(&lit.lit, syn::Lit::Int(int) if !int.suffix().starts_with('f')),
+        _ => false,
+    }
+}
+
+/// Helper function to check if `?` converts a `ProgramError` to the error of a function
+///
+/// A `Result<T>` is Anchor's when the file imports Anchor's prelude and declares no other
+/// `Result` alias.
+fn returns_program_error(output: &syn::ReturnType, file: &syn::File) -> bool {
+    let syn::ReturnType::Type(_, ty) = output else {
+        return false;
+    };
+    let syn::Type::Path(path) = &**ty else {
+        return false;
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return false;
+    };
+    if segment.ident == "ProgramResult" {
+        return true;
+    }
+    if segment.ident != "Result" {
+        return false;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(arguments) => match arguments.args.len() {
+            1 => path.path.segments.len() == 1 && uses_anchor_result(file),
+            _ => arguments.args.iter().nth(1).is_some_and(|error| quote::quote!(#error).to_string().ends_with("ProgramError")),
+        },
+        _ => false,
+    }
+}
+
+/// Helper function to check if a file imports `ProgramError`, by name or with Anchor's prelude
+/// (`use anchor_lang::prelude::*`) or a `program_error` module glob
+fn imports_program_error(file: &syn::File) -> bool {
+    file.items
+        .iter()
+        .any(|item| matches!(item, syn::Item::Use(item_use) if brings_program_error(&item_use.tree, None)))
+}
+
+/// Helper function to check if a use tree brings `ProgramError` in scope, `parent` being the
+/// last segment before it
+fn brings_program_error(tree: &syn::UseTree, parent: Option<&syn::Ident>) -> bool {
+    match tree {
+        syn::UseTree::Path(path) => brings_program_error(&path.tree, Some(&path.ident)),
+        syn::UseTree::Name(name) => name.ident == "ProgramError",
+        syn::UseTree::Rename(rename) => rename.rename == "ProgramError",
+        syn::UseTree::Glob(_) => parent.is_some_and(|parent| parent == "prelude" || parent == "program_error"),
+        syn::UseTree::Group(group) => group.items.iter().any(|item| brings_program_error(item, parent)),
+    }
 }
 
-impl<'ast> Visit<'ast> for UnsafeDivisionFinder {
-    fn visit_local(&mut self, local: &'ast syn::Local) {
-        if let Some(init) = &local.init {
-            if let syn::Pat::Ident(pat_ident) = &local.pat {
-                let var_name = pat_ident.ident.to_string();
-
-                if let syn::Expr::Lit(lit_expr) = &*init.expr {
-                    match &lit_expr.lit {
-                        syn::Lit::Int(int_lit) => {
-                            let value = int_lit.base10_digits();
-                            if value != "0" {
-                                self.safe_variables.insert(var_name, true);
-                            }
-                        }
-                        syn::Lit::Float(float_lit) => {
-                            let value = float_lit.base10_digits();
-                            if value != "0" && value != "0.0" {
-                                self.safe_variables.insert(var_name, true);
-                            }
-                        }
-                        _ => {}
-                    }
-                }
+/// Helper function to check if the `Result` of a file is the one of Anchor's prelude
+fn uses_anchor_result(file: &syn::File) -> bool {
+    let mut imports_prelude = false;
+    for item in &file.items {
+        match item {
+            syn::Item::Type(alias) if alias.ident == "Result" => return false,
+            syn::Item::Use(item_use) => {
+                let path = quote::quote!(#item_use).to_string().replace(' ', "");
+                imports_prelude |= path.contains("anchor_lang::prelude::*");
             }
+            _ => {}
         }
-
-        visit::visit_local(self, local);
     }
+    imports_prelude
+}
 
+/// Helper function to list the divisions of a block whose divisor may be zero
+fn unsafe_divisions<'a>(block: &'a syn::Block, values: &ValueAnalysis) -> Vec<Division<'a>> {
+    let mut finder = UnsafeDivisionFinder { divisions: Vec::new(), nested: 0 };
+    finder.visit_block(block);
+    finder
+        .divisions
+        .into_iter()
+        .filter(|division| !values.range_of(&division.expr.right).is_some_and(|range| range.excludes_zero()))
+        .collect()
+}
+
+/// Division found by [`UnsafeDivisionFinder`]
+struct Division<'ast> {
+    expr: &'ast syn::ExprBinary,
+    /// Whether the division is in a closure, an async block or an item nested in the function,
+    /// where `?` does not return from the function
+    nested: bool,
+}
+
+/// Helper visitor to find the division operations whose divisor may be zero, whatever its value
+struct UnsafeDivisionFinder<'ast> {
+    divisions: Vec<Division<'ast>>,
+    /// Depth of the closures, async blocks and nested items being visited
+    nested: usize,
+}
+
+impl<'ast> Visit<'ast> for UnsafeDivisionFinder<'ast> {
     fn visit_expr_binary(&mut self, expr: &'ast syn::ExprBinary) {
         if matches!(expr.op, syn::BinOp::Div(_)) {
             let divisor = &expr.right;
 
             if self.is_potentially_dangerous(divisor) {
-                self.found = true;
+                self.divisions.push(Division { expr, nested: self.nested > 0 });
                 trace!("Found unsafe division operation");
             }
         }
 
         visit::visit_expr_binary(self, expr);
     }
-}
 
-impl UnsafeDivisionFinder {
-    fn is_potentially_dangerous(&self, expr: &syn::Expr) -> bool {
-        match expr {
-            syn::Expr::Lit(lit) => {
-                match &lit.lit {
-                    syn::Lit::Int(int_lit) => {
-                        let value = int_lit.base10_digits();
-                        value == "0"
-                    }
-                    syn::Lit::Float(float_lit) => {
-                        let value = float_lit.base10_digits();
-                        value == "0.0" || value == "0"
-                    }
-                    _ => false,
-                }
-            }
+    fn visit_expr_closure(&mut self, closure: &'ast syn::ExprClosure) {
+        self.nested += 1;
+        visit::visit_expr_closure(self, closure);
+        self.nested -= 1;
+    }
 
-            syn::Expr::Path(path) => {
-                if let Some(ident) = path.path.get_ident() {
-                    let var_name = ident.to_string();
+    fn visit_expr_async(&mut self, block: &'ast syn::ExprAsync) {
+        self.nested += 1;
+        visit::visit_expr_async(self, block);
+        self.nested -= 1;
+    }
 
-                    if self.safe_variables.contains_key(&var_name) {
-                        return false;
-                    }
-                }
+    fn visit_item_fn(&mut self, item_fn: &'ast syn::ItemFn) {
+        self.nested += 1;
+        visit::visit_item_fn(self, item_fn);
+        self.nested -= 1;
+    }
 
-                true
-            }
+    fn visit_item_impl(&mut self, item_impl: &'ast syn::ItemImpl) {
+        self.nested += 1;
+        visit::visit_item_impl(self, item_impl);
+        self.nested -= 1;
+    }
+}
 
+impl UnsafeDivisionFinder<'_> {
+    /// Divisors that can be zero: literals, variables, fields, calls and subtractions (whether
+    /// they are zero is left to the value analysis)
+    fn is_potentially_dangerous(&self, expr: &syn::Expr) -> bool {
+        match expr {
+            syn::Expr::Lit(lit) => matches!(lit.lit, syn::Lit::Int(_) | syn::Lit::Float(_)),
+            syn::Expr::Path(_) => true,
             syn::Expr::Call(_) => true,
             syn::Expr::Field(_) => true,
             syn::Expr::Binary(binary) => matches!(binary.op, syn::BinOp::Sub(_)),
@@ -1,33 +1,46 @@
-use log::debug;
-use std::sync::Arc;
-
-use crate::analyzer::dsl::{RuleBuilder, AstQuery};
-use crate::analyzer::{Rule, Severity};
+use crate::analyzer::dsl::query::NodeData;
+use crate::analyzer::dsl::rule;
 
 // Import our specific filters
 mod filters;
 use filters::DivisionByZeroFilters;
 
-/// Crea la regla para detectar divisiones sin verificación de cero
-pub fn create_rule() -> Arc<dyn Rule> {
-    RuleBuilder::new()
-        .id("solana-division-by-zero")
-        .severity(Severity::Medium)
-        .title("Division Without Zero Check")
-        .description("Detects division operations without zero verification")
-        .recommendations(vec![
-            "Add explicit zero checks before division operations: if divisor == 0 { return Err(...) }",
-            "Use checked division methods: checked_div() which returns Option<T>",
-            "Implement proper error handling for division by zero cases",
-            "Consider using safe arithmetic operations provided by Anchor or custom error types",
-            "Validate input parameters at the beginning of instruction handlers"
-        ])
-        .dsl_query(|ast, _file_path, _span_extractor| {
-            debug!("Analyzing unsafe divisions");
-            
-            AstQuery::new(ast)
-                .functions()
-                .has_unsafe_divisions()
-        })
-        .build()
+// Crea la regla para detectar divisiones sin verificación de cero
+rule! {
+    id: "solana-division-by-zero",
+    severity: Medium,
+    title: "Division Without Zero Check",
+    description: "Detects division operations without zero verification",
+    recommendations: vec![
+        "Add explicit zero checks before division operations: if divisor == 0 { return Err(...) }",
+        "Use checked division methods: checked_div() which returns Option<T>",
+        "Implement proper error handling for division by zero cases",
+        "Consider using safe arithmetic operations provided by Anchor or custom error types",
+        "Validate input parameters at the beginning of instruction handlers"
+    ],
+    rationale: "Integer division by zero panics, aborting the transaction. A divisor taken from an instruction argument or an account (total shares, reserves, a price) can be zero when a pool is empty or by the choice of the caller, which makes the instruction unusable, or lets an attacker block the other users.",
+    vulnerable_example: r#"
+        pub fn share(amount: u64, total_shares: u64) -> u64 {
+            amount / total_shares
+        }
+    "#,
+    safe_example: r#"
+        pub fn share(amount: u64, total_shares: u64) -> Result<u64> {
+            amount.checked_div(total_shares).ok_or(error!(VaultError::NoShares))
+        }
+    "#,
+    tag: "security",
+    tag: "arithmetic",
+    pack: "defi",
+    pack: "native",
+    category: Arithmetic,
+    cwe: 369,
+    query: functions().has_unsafe_divisions(),
+    suggest_fix: |node, context, span_extractor| match &node.data {
+        NodeData::Function(func) => filters::checked_div_fix(&func.sig, &func.block, context, span_extractor),
+        NodeData::ImplFunction(func) => filters::checked_div_fix(&func.sig, &func.block, context, span_extractor),
+        _ => None,
+    },
+    // Divisors can be constants declared in other files of the program
+    filter: |node, context| filters::has_unsafe_divisions(node, &context.constants()),
 }
@@ -15,6 +15,7 @@ impl<'a> DuplicateMutableAccountsFilters<'a> for AstQuery<'a> {
             if let NodeData::Struct(struct_item) = &node.data {
                 let mut mutable_account_count = 0;
                 let mut mutable_accounts_with_constraints = 0;
+                let mut unconstrained_fields = Vec::new();
                 
                 // Check if struct has fields
                 if let Fields::Named(fields) = &struct_item.fields {
@@ -24,12 +25,12 @@ impl<'a> DuplicateMutableAccountsFilters<'a> for AstQuery<'a> {
                     // First pass: collect all constraints
                     for field in &fields.named {
                         for attr in &field.attrs {
-                            if let Meta::List(meta_list) = &attr.meta {
-                                if meta_list.path.is_ident("account") {
-                                    let tokens_str = meta_list.tokens.to_string();
-                                    if tokens_str.contains("constraint") {
-                                        all_constraints.push(tokens_str.clone());
-                                    }
+                            if let Meta::List(meta_list) = &attr.meta
+                                && meta_list.path.is_ident("account")
+                            {
+                                let tokens_str = meta_list.tokens.to_string();
+                                if tokens_str.contains("constraint") {
+                                    all_constraints.push(tokens_str.clone());
                                 }
                             }
                         }
@@ -42,38 +43,39 @@ impl<'a> DuplicateMutableAccountsFilters<'a> for AstQuery<'a> {
                         
                         // Check field attributes
                         for attr in &field.attrs {
-                            if let Meta::List(meta_list) = &attr.meta {
-                                if meta_list.path.is_ident("account") {
-                                    let tokens_str = meta_list.tokens.to_string();
-                                    
-                                    // Check if it's mutable
-                                    if tokens_str.contains("mut") {
-                                        is_mutable = true;
-                                    }
-                                    
-                                    // Check if it has constraints that prevent duplication
-                                    if tokens_str.contains("constraint") || 
-                                       tokens_str.contains("seeds") ||
-                                       tokens_str.contains("bump") ||
-                                       tokens_str.contains("!=") ||
-                                       tokens_str.contains("key()") {
-                                        has_field_constraint = true;
-                                        trace!("Field {:?} has constraint that prevents duplication: {}", field.ident, tokens_str);
-                                    }
+                            if let Meta::List(meta_list) = &attr.meta
+                                && meta_list.path.is_ident("account")
+                            {
+                                let tokens_str = meta_list.tokens.to_string();
+                                
+                                // Check if it's mutable
+                                if tokens_str.contains("mut") {
+                                    is_mutable = true;
+                                }
+                                
+                                // Check if it has constraints that prevent duplication
+                                if tokens_str.contains("constraint") || 
+                                   tokens_str.contains("seeds") ||
+                                   tokens_str.contains("bump") ||
+                                   tokens_str.contains("!=") ||
+                                   tokens_str.contains("key()") {
+                                    has_field_constraint = true;
+                                    trace!("Field {:?} has constraint that prevents duplication: {}", field.ident, tokens_str);
                                 }
                             }
                         }
                         
                         // Check if this field is referenced in any constraint
-                        if is_mutable && !has_field_constraint {
-                            if let Some(field_name) = &field.ident {
-                                let field_name_str = field_name.to_string();
-                                for constraint in &all_constraints {
-                                    if constraint.contains(&field_name_str) && constraint.contains("!=") {
-                                        has_field_constraint = true;
-                                        trace!("Field {:?} is protected by bidirectional constraint: {}", field.ident, constraint);
-                                        break;
-                                    }
+                        if is_mutable
+                            && !has_field_constraint
+                            && let Some(field_name) = &field.ident
+                        {
+                            let field_name_str = field_name.to_string();
+                            for constraint in &all_constraints {
+                                if constraint.contains(&field_name_str) && constraint.contains("!=") {
+                                    has_field_constraint = true;
+                                    trace!("Field {:?} is protected by bidirectional constraint: {}", field.ident, constraint);
+                                    break;
                                 }
                             }
                         }
@@ -85,6 +87,9 @@ impl<'a> DuplicateMutableAccountsFilters<'a> for AstQuery<'a> {
                                 mutable_accounts_with_constraints += 1;
                             } else {
                                 trace!("Found mutable account without constraints: {:?}", field.ident);
+                                if let Some(field_name) = &field.ident {
+                                    unconstrained_fields.push(field_name.to_string());
+                                }
                             }
                         }
                     }
@@ -94,11 +99,11 @@ impl<'a> DuplicateMutableAccountsFilters<'a> for AstQuery<'a> {
                 if mutable_account_count >= 2 && mutable_account_count != mutable_accounts_with_constraints {
                     trace!("SOLANA-001: Found struct '{}' with {} mutable accounts without constraints", 
                            struct_item.ident, mutable_account_count - mutable_accounts_with_constraints);
-                    new_results.push(node.clone());
+                    new_results.push(node.clone().with_metadata("fields", unconstrained_fields));
                 }
             }
         }
         
-        AstQuery::from_nodes(new_results)
+        self.with_results(new_results)
     }
 }
@@ -1,32 +1,48 @@
-use log::debug;
-use std::sync::Arc;
-
-use crate::analyzer::dsl::{RuleBuilder, AstQuery};
-use crate::analyzer::{Rule, Severity};
+use crate::analyzer::dsl::rule;
+use crate::analyzer::engine::RuleType;
 
 // Import our specific filters
 mod filters;
 use filters::DuplicateMutableAccountsFilters;
 
-pub fn create_rule() -> Arc<dyn Rule> {
-    RuleBuilder::new()
-        .id("duplicate-mutable-accounts")
-        .severity(Severity::Medium)
-        .title("Duplicate Mutable Accounts")
-        .description("Detects account structs with multiple mutable references to the same account type, which can lead to unexpected behavior")
-        .recommendations(vec![
-            "Add constraints to ensure accounts are different: #[account(constraint = account1.key() != account2.key())]",
-            "Use a single mutable account reference instead of multiple ones when possible",
-            "Implement explicit validation in your instruction handler to prevent the same account being passed multiple times",
-            "Consider using Anchor's constraint system to enforce account uniqueness at the framework level"
-        ])
-        .dsl_query(|ast, _file_path, _span_extractor| {
-            debug!("Analyzing duplicate mutable accounts");
-            
-            AstQuery::new(ast)
-                .structs()
-                .derives_accounts()
-                .has_duplicate_mutable_accounts()
-        })
-        .build()
+rule! {
+    id: "duplicate-mutable-accounts",
+    severity: Medium,
+    rule_type: RuleType::Anchor,
+    title: "Duplicate Mutable Accounts",
+    description: "Detects account structs with multiple mutable references to the same account type, which can lead to unexpected behavior",
+    message: "{title} in '{struct}'. Mutable accounts without a uniqueness constraint: {fields}. The same account can be passed for each of them, which can lead to unexpected behavior",
+    recommendations: vec![
+        "Add constraints to ensure accounts are different: #[account(constraint = account1.key() != account2.key())]",
+        "Use a single mutable account reference instead of multiple ones when possible",
+        "Implement explicit validation in your instruction handler to prevent the same account being passed multiple times",
+        "Consider using Anchor's constraint system to enforce account uniqueness at the framework level"
+    ],
+    rationale: "Anchor does not check that the accounts of an instruction are different. When the same account is passed for two mutable accounts, the handler writes it twice and the last write wins: a transfer from an account to itself can mint a balance, or the changes made to the first account are lost.",
+    vulnerable_example: r#"
+        #[derive(Accounts)]
+        pub struct Transfer<'info> {
+            #[account(mut)]
+            pub from: Account<'info, TokenAccount>,
+            #[account(mut)]
+            pub to: Account<'info, TokenAccount>,
+            pub authority: Signer<'info>,
+        }
+    "#,
+    safe_example: r#"
+        #[derive(Accounts)]
+        pub struct Transfer<'info> {
+            #[account(mut)]
+            pub from: Account<'info, TokenAccount>,
+            #[account(mut, constraint = to.key() != from.key())]
+            pub to: Account<'info, TokenAccount>,
+            pub authority: Signer<'info>,
+        }
+    "#,
+    tag: "security",
+    tag: "anchor",
+    pack: "anchor-core",
+    category: DuplicateAccounts,
+    cwe: 694,
+    query: structs().derives_accounts().has_duplicate_mutable_accounts(),
 }
@@ -0,0 +1,132 @@
+use log::{debug, trace};
+use proc_macro2::{TokenStream, TokenTree};
+use syn::{Fields, ItemStruct, Meta};
+
+use crate::analyzer::Fix;
+use crate::analyzer::dsl::query::{AstQuery, NodeData};
+use crate::analyzer::span_utils::SpanExtractor;
+
+/// Space sizes that never include the discriminator (`InitSpace`, `std::mem::size_of`)
+const SIZES_WITHOUT_DISCRIMINATOR: &[&str] = &["INIT_SPACE", "size_of"];
+
+pub trait MissingDiscriminatorSpaceFilters<'a> {
+    fn has_space_without_discriminator(self) -> AstQuery<'a>;
+}
+
+impl<'a> MissingDiscriminatorSpaceFilters<'a> for AstQuery<'a> {
+    fn has_space_without_discriminator(self) -> AstQuery<'a> {
+        debug!("Filtering account structs initializing accounts without the discriminator space");
+        let mut new_results = Vec::new();
+
+        for node in self.results() {
+            if let NodeData::Struct(struct_item) = &node.data {
+                let fields: Vec<String> =
+                    find_spaces_without_discriminator(struct_item).into_iter().map(|(field, _)| field).collect();
+                if !fields.is_empty() {
+                    trace!("Found space without discriminator in '{}': {fields:?}", struct_item.ident);
+                    new_results.push(node.clone().with_metadata("fields", fields));
+                }
+            }
+        }
+
+        self.with_results(new_results)
+    }
+}
+
+/// Returns the `space = ...` expressions of the initialized accounts of a struct that are
+/// sized with `INIT_SPACE` or `size_of` without the 8 bytes of the discriminator, by field
+pub fn find_spaces_without_discriminator(item_struct: &ItemStruct) -> Vec<(String, Vec<TokenTree>)> {
+    let Fields::Named(fields) = &item_struct.fields else {
+        return Vec::new();
+    };
+
+    let mut spaces = Vec::new();
+    for field in &fields.named {
+        let Some(name) = &field.ident else {
+            continue;
+        };
+        for attr in &field.attrs {
+            let Meta::List(meta_list) = &attr.meta else {
+                continue;
+            };
+            if !meta_list.path.is_ident("account") {
+                continue;
+            }
+
+            let arguments = split_arguments(meta_list.tokens.clone());
+            let initialized = arguments
+                .iter()
+                .any(|argument| matches!(argument.as_slice(), [TokenTree::Ident(ident)] if ident == "init" || ident == "init_if_needed"));
+            let space = arguments.into_iter().find_map(|argument| match argument.as_slice() {
+                [TokenTree::Ident(ident), TokenTree::Punct(punct), ..] if ident == "space" && punct.as_char() == '=' => {
+                    Some(argument[2..].to_vec())
+                }
+                _ => None,
+            });
+            if let Some(space) = space
+                && initialized
+                && misses_discriminator(&space)
+            {
+                spaces.push((name.to_string(), space));
+            }
+        }
+    }
+    spaces
+}
+
+/// Suggest adding the 8 bytes of the discriminator to the space of the accounts
+pub fn discriminator_space_fix(item_struct: &ItemStruct, span_extractor: &SpanExtractor) -> Option<Fix> {
+    let spaces = find_spaces_without_discriminator(item_struct);
+    let edits = spaces
+        .iter()
+        .filter_map(|(_, space)| space.first())
+        .map(|first| span_extractor.insert_before_edit(first, "8 + "))
+        .collect::<Vec<_>>();
+    if edits.is_empty() {
+        return None;
+    }
+
+    let fields = spaces.iter().map(|(field, _)| format!("'{field}'")).collect::<Vec<_>>().join(", ");
+    Some(Fix::machine_applicable(&format!("Add the 8 bytes of the discriminator to the space of {fields}"), edits))
+}
+
+/// Helper function to split the arguments of an attribute at its top-level commas
+fn split_arguments(tokens: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut arguments = vec![Vec::new()];
+    for token in tokens {
+        match &token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => arguments.push(Vec::new()),
+            _ => arguments.last_mut().expect("at least one argument").push(token),
+        }
+    }
+    arguments.retain(|argument| !argument.is_empty());
+    arguments
+}
+
+/// Helper function to check if a space is sized without the discriminator: with a size that
+/// never includes it, and without an `8` or a `DISCRIMINATOR` constant
+fn misses_discriminator(space: &[TokenTree]) -> bool {
+    fn visit(tokens: &[TokenTree], sized: &mut bool, discriminator: &mut bool) {
+        for token in tokens {
+            match token {
+                TokenTree::Ident(ident) => {
+                    let ident = ident.to_string();
+                    *sized |= SIZES_WITHOUT_DISCRIMINATOR.contains(&ident.as_str());
+                    *discriminator |= ident.contains("DISCRIMINATOR");
+                }
+                TokenTree::Literal(literal) => {
+                    let literal = literal.to_string();
+                    *discriminator |= literal == "8" || literal.starts_with("8u") || literal.starts_with("8_");
+                }
+                TokenTree::Group(group) => {
+                    visit(&group.stream().into_iter().collect::<Vec<_>>(), sized, discriminator);
+                }
+                TokenTree::Punct(_) => {}
+            }
+        }
+    }
+
+    let (mut sized, mut discriminator) = (false, false);
+    visit(space, &mut sized, &mut discriminator);
+    sized && !discriminator
+}
@@ -0,0 +1,53 @@
+use crate::analyzer::dsl::query::NodeData;
+use crate::analyzer::dsl::rule;
+use crate::analyzer::engine::RuleType;
+
+// Import our specific filters
+mod filters;
+use filters::MissingDiscriminatorSpaceFilters;
+
+#[cfg(test)]
+mod test;
+
+rule! {
+    id: "missing-discriminator-space",
+    severity: Medium,
+    rule_type: RuleType::Anchor,
+    title: "Account Space Without Discriminator",
+    description: "Detects accounts initialized with a space of INIT_SPACE or size_of without the 8 bytes of the Anchor discriminator",
+    message: "{title} in '{struct}'. The space of {fields} leaves out the 8 bytes of the discriminator, the account is too small for its data",
+    recommendations: vec![
+        "Add the 8 bytes of the discriminator to the space: #[account(init, payer = payer, space = 8 + Vault::INIT_SPACE)]",
+        "Use the DISCRIMINATOR constant of the account type for accounts with custom discriminators: Vault::DISCRIMINATOR.len() + Vault::INIT_SPACE"
+    ],
+    rationale: "Anchor writes an 8-byte discriminator before the data of every account. `INIT_SPACE` and `size_of` only count the fields, so an account created with this space is 8 bytes too small: serializing its data fails, and every instruction writing the account errors once the last fields are set, which can lock funds in a vault or a pool.",
+    vulnerable_example: r#"
+        #[derive(Accounts)]
+        pub struct Initialize<'info> {
+            #[account(init, payer = payer, space = Vault::INIT_SPACE)]
+            pub vault: Account<'info, Vault>,
+            #[account(mut)]
+            pub payer: Signer<'info>,
+            pub system_program: Program<'info, System>,
+        }
+    "#,
+    safe_example: r#"
+        #[derive(Accounts)]
+        pub struct Initialize<'info> {
+            #[account(init, payer = payer, space = 8 + Vault::INIT_SPACE)]
+            pub vault: Account<'info, Vault>,
+            #[account(mut)]
+            pub payer: Signer<'info>,
+            pub system_program: Program<'info, System>,
+        }
+    "#,
+    tag: "security",
+    tag: "anchor",
+    pack: "anchor-core",
+    cwe: 131,
+    query: structs().derives_accounts().has_space_without_discriminator(),
+    suggest_fix: |node, _context, span_extractor| match &node.data {
+        NodeData::Struct(item_struct) => filters::discriminator_space_fix(item_struct, span_extractor),
+        _ => None,
+    },
+}
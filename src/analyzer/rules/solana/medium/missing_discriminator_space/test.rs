@@ -0,0 +1,54 @@
+use crate::analyzer::Applicability;
+use crate::analyzer::rules::solana::medium::missing_discriminator_space::filters::{
+    discriminator_space_fix, find_spaces_without_discriminator,
+};
+use crate::analyzer::span_utils::SpanExtractor;
+use syn::{ItemStruct, parse_quote};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(item_struct: &ItemStruct) -> Vec<String> {
+        find_spaces_without_discriminator(item_struct).into_iter().map(|(field, _)| field).collect()
+    }
+
+    #[test]
+    fn test_spaces_without_discriminator() {
+        let struct_def: ItemStruct = parse_quote! {
+            #[derive(Accounts)]
+            pub struct Initialize<'info> {
+                #[account(init, payer = payer, space = Vault::INIT_SPACE)]
+                pub vault: Account<'info, Vault>,
+                #[account(init_if_needed, payer = payer, space = std::mem::size_of::<Config>())]
+                pub config: Account<'info, Config>,
+                #[account(init, payer = payer, space = 8 + Pool::INIT_SPACE)]
+                pub pool: Account<'info, Pool>,
+                #[account(init, payer = payer, space = Position::DISCRIMINATOR.len() + Position::INIT_SPACE)]
+                pub position: Account<'info, Position>,
+                #[account(init, payer = payer, space = Ticket::LEN)]
+                pub ticket: Account<'info, Ticket>,
+                #[account(mut, realloc = Vault::INIT_SPACE, realloc::payer = payer, realloc::zero = false)]
+                pub resized: Account<'info, Vault>,
+                #[account(mut)]
+                pub payer: Signer<'info>,
+            }
+        };
+
+        // Sizes that may include the discriminator (LEN) and reallocations are left out
+        assert_eq!(fields(&struct_def), ["vault", "config"]);
+    }
+
+    #[test]
+    fn test_discriminator_space_fix_edits() {
+        let source = "#[derive(Accounts)]\npub struct Initialize<'info> {\n    #[account(init, payer = payer, space = Vault::INIT_SPACE)]\n    pub vault: Account<'info, Vault>,\n}\n";
+        let item_struct: ItemStruct = syn::parse_str(source).unwrap();
+        let span_extractor = SpanExtractor::new(source.to_string(), "lib.rs".to_string());
+
+        let fix = discriminator_space_fix(&item_struct, &span_extractor).unwrap();
+        assert_eq!(fix.applicability, Applicability::MachineApplicable);
+        assert_eq!(fix.description, "Add the 8 bytes of the discriminator to the space of 'vault'");
+        assert_eq!(fix.edits[0].replacement, "8 + ");
+        assert_eq!((fix.edits[0].location.line, fix.edits[0].location.column), (3, Some(43)));
+    }
+}
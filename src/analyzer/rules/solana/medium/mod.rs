@@ -1,4 +1,8 @@
 pub mod division_by_zero;
 pub mod duplicate_mutable_accounts;
+pub mod missing_discriminator_space;
+pub mod native_missing_owner_check;
 pub mod owner_check;
+pub mod pinocchio_unchecked_account_load;
+pub mod steel_unvalidated_account;
 
@@ -0,0 +1,17 @@
+use crate::analyzer::dsl::context::NodeContext;
+use crate::analyzer::dsl::query::AstNode;
+use crate::analyzer::native;
+
+/// Check if a native processor deserializes the data of an account without checking its owner
+/// (see [`native::OWNER_CHECKS`]), in its body or in the checks of the helpers it calls
+pub fn reads_without_owner_check(node: &AstNode<'_>, context: &NodeContext<'_>) -> bool {
+    let Some((sig, block)) = native::function_parts(node) else {
+        return false;
+    };
+    native::accounts_param(sig).is_some()
+        && native::deserializes_account_data(block)
+        && !native::uses_any(block, native::OWNER_CHECKS)
+        && !native::OWNER_CHECKS
+            .iter()
+            .any(|check| native::checks_mention(context.program, context.file_path, sig, check))
+}
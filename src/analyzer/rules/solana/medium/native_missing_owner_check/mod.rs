@@ -0,0 +1,44 @@
+use crate::analyzer::dsl::rule;
+use crate::analyzer::engine::RuleType;
+
+mod filters;
+
+rule! {
+    id: "native-missing-owner-check",
+    severity: Medium,
+    rule_type: RuleType::Native,
+    title: "Missing Owner Check in Native Processor",
+    description: "Detects native processors that deserialize account data without checking the owner of the account, letting an attacker pass an account with forged data",
+    recommendations: vec![
+        "Check the owner before reading the data: if account.owner != program_id { return Err(ProgramError::IncorrectProgramId) }",
+        "Check that token accounts are owned by the token program before unpacking them",
+        "Derive program accounts from seeds and compare their address with the one passed"
+    ],
+    rationale: "Anyone can create an account with arbitrary data, owned by another program. A processor that deserializes account data without checking that the program owns the account trusts forged state: balances, authorities or flags written by the attacker.",
+    vulnerable_example: r#"
+        fn read_balance(accounts: &[AccountInfo]) -> Result<u64, ProgramError> {
+            let vault = next_account_info(&mut accounts.iter())?;
+            let state = Vault::try_from_slice(&vault.data.borrow())?;
+            Ok(state.balance)
+        }
+    "#,
+    safe_example: r#"
+        fn read_balance(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<u64, ProgramError> {
+            let vault = next_account_info(&mut accounts.iter())?;
+            if vault.owner != program_id {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            let state = Vault::try_from_slice(&vault.data.borrow())?;
+            Ok(state.balance)
+        }
+    "#,
+    tag: "security",
+    tag: "validation",
+    tag: "native",
+    pack: "native",
+    category: MissingOwnerCheck,
+    cwe: 345,
+    query: functions(),
+    // Owner checks made by the helpers the processor calls count
+    filter: filters::reads_without_owner_check,
+}
@@ -0,0 +1,19 @@
+use crate::analyzer::dsl::context::NodeContext;
+use crate::analyzer::dsl::query::AstNode;
+use crate::analyzer::native;
+
+/// Pinocchio constructors loading account state without checking the owner and data length
+pub const UNCHECKED_LOADS: &[&str] = &["from_account_info_unchecked", "from_bytes_unchecked"];
+
+/// Check if a function loads account state with an unchecked constructor without checking the
+/// owner of the account (see [`native::OWNER_CHECKS`]), itself or in the helpers it calls
+pub fn loads_unchecked_account(node: &AstNode<'_>, context: &NodeContext<'_>) -> bool {
+    let Some((sig, block)) = native::function_parts(node) else {
+        return false;
+    };
+    native::called_names(block).iter().any(|name| UNCHECKED_LOADS.contains(&name.as_str()))
+        && !native::uses_any(block, native::OWNER_CHECKS)
+        && !native::OWNER_CHECKS
+            .iter()
+            .any(|check| native::checks_mention(context.program, context.file_path, sig, check))
+}
@@ -0,0 +1,49 @@
+use crate::analyzer::dsl::rule;
+use crate::analyzer::engine::RuleType;
+
+mod filters;
+
+rule! {
+    id: "pinocchio-unchecked-account-load",
+    severity: Medium,
+    rule_type: RuleType::Pinocchio,
+    title: "Unchecked Account Load in Pinocchio Program",
+    description: "Detects Pinocchio functions loading accounts with the unchecked constructors (from_account_info_unchecked, from_bytes_unchecked), which skip the owner and length checks, without checking the owner themselves",
+    recommendations: vec![
+        "Load token accounts and mints with from_account_info(), which checks the owner and the data length",
+        "Check the owner before an unchecked load: if !account.is_owned_by(&pinocchio_token::ID) { return Err(ProgramError::InvalidAccountOwner) }"
+    ],
+    rationale: "The unchecked constructors reinterpret the bytes of any account as the requested type. Without an owner check, an attacker passes an account of their own program whose data looks like a token account with any balance or owner.",
+    vulnerable_example: r#"
+        fn deposit(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+            let [vault, depositor_token, ..] = accounts else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            let token_account = unsafe { TokenAccount::from_account_info_unchecked(depositor_token)? };
+            if token_account.amount() < amount {
+                return Err(ProgramError::InsufficientFunds);
+            }
+            Ok(())
+        }
+    "#,
+    safe_example: r#"
+        fn deposit(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+            let [vault, depositor_token, ..] = accounts else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            let token_account = TokenAccount::from_account_info(depositor_token)?;
+            if token_account.amount() < amount {
+                return Err(ProgramError::InsufficientFunds);
+            }
+            Ok(())
+        }
+    "#,
+    tag: "security",
+    tag: "validation",
+    tag: "pinocchio",
+    pack: "pinocchio",
+    category: MissingOwnerCheck,
+    cwe: 345,
+    query: functions(),
+    filter: filters::loads_unchecked_account,
+}
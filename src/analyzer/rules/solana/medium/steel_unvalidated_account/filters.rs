@@ -0,0 +1,36 @@
+use crate::analyzer::dsl::query::AstNode;
+use crate::analyzer::native;
+
+/// Steel methods validating an account info (`AccountInfoValidation` and the owner-checking loaders)
+pub const VALIDATIONS: &[&str] = &[
+    "is_signer",
+    "is_writable",
+    "is_executable",
+    "is_empty",
+    "is_program",
+    "is_sysvar",
+    "is_type",
+    "has_address",
+    "has_owner",
+    "has_seeds",
+    "as_account",
+    "as_account_mut",
+    "to_account",
+    "to_account_mut",
+];
+
+/// Check if a processor destructures its accounts slice and leaves an account without any
+/// validation method called on it
+pub fn has_unvalidated_account(node: &AstNode<'_>) -> bool {
+    let Some((sig, block)) = native::function_parts(node) else {
+        return false;
+    };
+    let Some(accounts) = native::accounts_param(sig) else {
+        return false;
+    };
+    native::destructured_accounts(block, &accounts).iter().any(|account| {
+        !native::methods_called_on(block, account)
+            .iter()
+            .any(|method| VALIDATIONS.contains(&method.as_str()))
+    })
+}
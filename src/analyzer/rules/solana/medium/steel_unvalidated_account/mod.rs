@@ -0,0 +1,55 @@
+use crate::analyzer::dsl::rule;
+use crate::analyzer::engine::RuleType;
+
+mod filters;
+
+rule! {
+    id: "steel-unvalidated-account",
+    severity: Medium,
+    rule_type: RuleType::Steel,
+    title: "Unvalidated Account in Steel Processor",
+    description: "Detects Steel processors destructuring the accounts slice and using an account without any validation (is_signer, is_writable, has_address, has_owner, has_seeds, is_program, as_account)",
+    recommendations: vec![
+        "Validate every account after destructuring the slice: signer_info.is_signer()?; vault_info.is_writable()?.has_seeds(&[VAULT, signer_info.key.as_ref()], &crate::ID)?",
+        "Load program accounts with as_account::<T>(&crate::ID)?, which checks the owner and the discriminator",
+        "Check program accounts with is_program(&ID)? before invoking them"
+    ],
+    rationale: "Steel gives the processors the raw accounts slice. An account used without any check (signer, writable, address, owner, seeds or type) can be any account the caller chooses, for instance a destination of the attacker or an account of another program with forged data.",
+    vulnerable_example: r#"
+        pub fn process_withdraw(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+            let args = Withdraw::try_from_bytes(data)?;
+            let [signer_info, vault_info, destination_info] = accounts else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            signer_info.is_signer()?;
+            let vault = vault_info.as_account_mut::<Vault>(&crate::ID)?;
+            vault.balance -= args.amount;
+            destination_info.collect(args.amount, vault_info)?;
+            Ok(())
+        }
+    "#,
+    safe_example: r#"
+        pub fn process_withdraw(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+            let args = Withdraw::try_from_bytes(data)?;
+            let [signer_info, vault_info, destination_info] = accounts else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            signer_info.is_signer()?;
+            let vault = vault_info
+                .as_account_mut::<Vault>(&crate::ID)?
+                .assert_mut(|vault| vault.authority == *signer_info.key)?;
+            destination_info.is_writable()?.has_address(&vault.destination)?;
+            vault.balance -= args.amount;
+            destination_info.collect(args.amount, vault_info)?;
+            Ok(())
+        }
+    "#,
+    tag: "security",
+    tag: "validation",
+    tag: "steel",
+    pack: "steel",
+    category: AccessControl,
+    cwe: 20,
+    query: functions(),
+    filter: |node, _context| filters::has_unvalidated_account(node),
+}
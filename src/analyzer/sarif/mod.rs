@@ -0,0 +1,300 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::analyzer::fingerprint::relative_path;
+use crate::analyzer::{AnalysisResult, Finding, Fix, Location, RuleInfo, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Version of SARIF the report follows
+pub const SARIF_VERSION: &str = "2.1.0";
+
+/// JSON schema of SARIF 2.1.0
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Key of the finding fingerprints in `partialFingerprints`
+pub const FINGERPRINT_KEY: &str = "solanaAnalyzer/v1";
+
+/// Findings of an analysis as a SARIF 2.1.0 log (`--format sarif`), for GitHub code scanning
+/// and other SARIF viewers
+///
+/// ```json
+/// {
+///   "version": "2.1.0",
+///   "runs": [{
+///     "tool": { "driver": { "name": "rust-solana-analyzer", "rules": [{ "id": "division-by-zero", ... }] } },
+///     "results": [{
+///       "ruleId": "division-by-zero",
+///       "level": "warning",
+///       "locations": [{ "physicalLocation": { "artifactLocation": { "uri": "src/lib.rs" }, "region": { ... } } }],
+///       "fixes": [{ "description": { "text": "..." }, "artifactChanges": [...], "properties": { "applicability": "advisory" } }]
+///     }]
+///   }]
+/// }
+/// ```
+///
+/// Columns are 1-indexed, and paths relative to `base`. Each fix of a finding is a SARIF fix
+/// whose `applicability` property tells whether it is `machine-applicable` or `advisory`.
+#[derive(Debug)]
+pub struct SarifReport<'a> {
+    result: &'a AnalysisResult,
+    base: &'a Path,
+}
+
+/// SARIF `sarifLog`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub version: &'static str,
+    pub runs: Vec<Run>,
+}
+
+/// SARIF `run`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Run {
+    pub tool: Tool,
+    pub results: Vec<SarifResult>,
+}
+
+/// SARIF `tool`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Tool {
+    pub driver: Driver,
+}
+
+/// SARIF `toolComponent` of the analyzer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Driver {
+    pub name: &'static str,
+    pub version: &'static str,
+    /// Rules that reported the findings
+    pub rules: Vec<ReportingDescriptor>,
+}
+
+/// SARIF `reportingDescriptor` of a rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportingDescriptor {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_description: Option<Message>,
+    /// First reference of the rule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub help_uri: Option<String>,
+    pub properties: RuleProperties,
+}
+
+/// Properties of a rule: its tags and CWE entries (`external/cwe/cwe-369`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleProperties {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// SARIF `message`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Message {
+    pub text: String,
+}
+
+/// SARIF `result` of a finding
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    /// `error`, `warning`, `note` or `none`
+    pub level: &'static str,
+    pub message: Message,
+    pub locations: Vec<SarifLocation>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub partial_fingerprints: BTreeMap<&'static str, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fixes: Vec<SarifFix>,
+}
+
+/// SARIF `location`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLocation {
+    pub physical_location: PhysicalLocation,
+}
+
+/// SARIF `physicalLocation`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalLocation {
+    pub artifact_location: ArtifactLocation,
+    pub region: Region,
+}
+
+/// SARIF `artifactLocation`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArtifactLocation {
+    pub uri: String,
+}
+
+/// SARIF `region` (1-indexed lines and columns, the end column is exclusive)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    pub start_line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<usize>,
+}
+
+/// SARIF `fix`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifFix {
+    pub description: Message,
+    /// Edits of the fix, by file
+    pub artifact_changes: Vec<ArtifactChange>,
+    pub properties: FixProperties,
+}
+
+/// Properties of a fix
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FixProperties {
+    /// `machine-applicable` or `advisory`
+    pub applicability: &'static str,
+}
+
+/// SARIF `artifactChange`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactChange {
+    pub artifact_location: ArtifactLocation,
+    pub replacements: Vec<Replacement>,
+}
+
+/// SARIF `replacement`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Replacement {
+    pub deleted_region: Region,
+    pub inserted_content: Message,
+}
+
+impl<'a> SarifReport<'a> {
+    /// Creates the SARIF log of an analysis, with the paths relative to `base` (the root of the
+    /// repository)
+    pub fn new(result: &'a AnalysisResult, base: &'a Path) -> Self {
+        Self { result, base }
+    }
+
+    /// Returns the SARIF log of the analysis
+    pub fn log(&self) -> SarifLog {
+        let mut rules: BTreeMap<&str, &RuleInfo> = BTreeMap::new();
+        for rule in self.result.findings.iter().flat_map(|finding| &finding.rules) {
+            rules.entry(rule.id.as_str()).or_insert(rule);
+        }
+
+        SarifLog {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![Run {
+                tool: Tool {
+                    driver: Driver {
+                        name: env!("CARGO_PKG_NAME"),
+                        version: env!("CARGO_PKG_VERSION"),
+                        rules: rules.into_values().map(descriptor).collect(),
+                    },
+                },
+                results: self.result.findings.iter().map(|finding| self.sarif_result(finding)).collect(),
+            }],
+        }
+    }
+
+    /// Returns the report as JSON
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(&self.log()).expect("SARIF logs serialize to JSON");
+        json.push('\n');
+        json
+    }
+
+    fn sarif_result(&self, finding: &Finding) -> SarifResult {
+        SarifResult {
+            rule_id: finding.rule().map(|rule| rule.id.clone()),
+            level: level(&finding.severity),
+            message: Message { text: finding.description.clone() },
+            locations: vec![SarifLocation {
+                physical_location: PhysicalLocation {
+                    artifact_location: self.artifact(&finding.location.file),
+                    region: region(&finding.location),
+                },
+            }],
+            partial_fingerprints: finding
+                .fingerprint
+                .iter()
+                .map(|fingerprint| (FINGERPRINT_KEY, fingerprint.clone()))
+                .collect(),
+            fixes: finding.fixes.iter().map(|fix| self.fix(fix)).collect(),
+        }
+    }
+
+    fn fix(&self, fix: &Fix) -> SarifFix {
+        let mut changes: BTreeMap<&str, Vec<Replacement>> = BTreeMap::new();
+        for edit in &fix.edits {
+            changes.entry(edit.location.file.as_str()).or_default().push(Replacement {
+                deleted_region: region(&edit.location),
+                inserted_content: Message { text: edit.replacement.clone() },
+            });
+        }
+        SarifFix {
+            description: Message { text: fix.description.clone() },
+            artifact_changes: changes
+                .into_iter()
+                .map(|(file, replacements)| ArtifactChange { artifact_location: self.artifact(file), replacements })
+                .collect(),
+            properties: FixProperties { applicability: fix.applicability.as_str() },
+        }
+    }
+
+    /// Location of a file, relative to the base
+    fn artifact(&self, file: &str) -> ArtifactLocation {
+        let path = relative_path(file, Some(self.base));
+        ArtifactLocation { uri: path.strip_prefix("./").map(str::to_string).unwrap_or(path) }
+    }
+}
+
+/// Descriptor of a rule
+fn descriptor(rule: &RuleInfo) -> ReportingDescriptor {
+    let mut tags = rule.tags.clone();
+    tags.extend(rule.cwe.iter().map(|id| format!("external/cwe/cwe-{id}")));
+    ReportingDescriptor {
+        id: rule.id.clone(),
+        short_description: (!rule.title.is_empty()).then(|| Message { text: rule.title.clone() }),
+        help_uri: rule.references.first().cloned(),
+        properties: RuleProperties { tags },
+    }
+}
+
+/// SARIF region of a location
+///
+/// The parser's columns start at 0, SARIF's at 1.
+fn region(location: &Location) -> Region {
+    Region {
+        start_line: location.line,
+        start_column: location.column.map(|column| column + 1),
+        end_line: location.end_line,
+        end_column: location.end_column.map(|column| column + 1),
+    }
+}
+
+/// SARIF level of a finding: `error`, `warning`, `note` or `none`
+pub fn level(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low => "note",
+        Severity::Informational => "none",
+    }
+}
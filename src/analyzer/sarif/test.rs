@@ -0,0 +1,79 @@
+use std::path::Path;
+
+use crate::analyzer::sarif::{SARIF_VERSION, SarifReport};
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, Finding, Fix, Location, RuleInfo, Severity, TextEdit};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(severity: Severity, file: &str, line: usize, fixes: Vec<Fix>) -> Finding {
+        FindingBuilder::new("Division by zero")
+            .severity(severity)
+            .span(file, line, 4, 19)
+            .snippet("amount / shares")
+            .fixes(fixes)
+            .rule(RuleInfo {
+                id: "division-by-zero".to_string(),
+                title: "Division by zero".to_string(),
+                references: vec!["https://example.com/division-by-zero".to_string()],
+                cwe: vec![369],
+                ..Default::default()
+            })
+            .fingerprint("0123abcd")
+            .build()
+    }
+
+    #[test]
+    fn test_sarif_log() {
+        let edit = TextEdit {
+            location: Location::new_precise("/repo/src/lib.rs".to_string(), 12, Some(4), Some(12), Some(19)),
+            replacement: "amount.checked_div(shares)".to_string(),
+        };
+        let result = AnalysisResult {
+            findings: vec![
+                finding(Severity::High, "/repo/src/lib.rs", 12, vec![Fix::new("Use checked_div", vec![edit])]),
+                finding(Severity::Informational, "./src/lib.rs", 3, Vec::new()),
+            ],
+            stats: AnalysisStats::default(),
+        };
+
+        let json: serde_json::Value = serde_json::from_str(&SarifReport::new(&result, Path::new("/repo")).to_json()).unwrap();
+
+        assert_eq!(json["version"], SARIF_VERSION);
+        let run = &json["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "rust-solana-analyzer");
+        assert_eq!(
+            run["tool"]["driver"]["rules"],
+            serde_json::json!([{
+                "id": "division-by-zero",
+                "shortDescription": { "text": "Division by zero" },
+                "helpUri": "https://example.com/division-by-zero",
+                "properties": { "tags": ["external/cwe/cwe-369"] }
+            }])
+        );
+
+        let region = serde_json::json!({ "startLine": 12, "startColumn": 5, "endLine": 12, "endColumn": 20 });
+        assert_eq!(
+            run["results"][0],
+            serde_json::json!({
+                "ruleId": "division-by-zero",
+                "level": "error",
+                "message": { "text": "Division by zero" },
+                "locations": [{ "physicalLocation": { "artifactLocation": { "uri": "src/lib.rs" }, "region": region } }],
+                "partialFingerprints": { "solanaAnalyzer/v1": "0123abcd" },
+                "fixes": [{
+                    "description": { "text": "Use checked_div" },
+                    "artifactChanges": [{
+                        "artifactLocation": { "uri": "src/lib.rs" },
+                        "replacements": [{ "deletedRegion": region, "insertedContent": { "text": "amount.checked_div(shares)" } }]
+                    }],
+                    "properties": { "applicability": "advisory" }
+                }]
+            })
+        );
+        assert_eq!(run["results"][1]["level"], "none");
+        assert!(run["results"][1].get("fixes").is_none());
+    }
+}
@@ -0,0 +1,460 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::{RuleEngine, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Directory of the built-in Solana rules, relative to the repository
+pub const RULES_DIR: &str = "src/analyzer/rules/solana";
+
+/// Directory of the rule fixtures, relative to the repository
+pub const FIXTURES_DIR: &str = "tests/fixtures";
+
+/// Error returned when a rule cannot be scaffolded
+#[derive(Debug)]
+pub enum ScaffoldError {
+    /// The rule ID is not kebab-case
+    InvalidId(String),
+    /// A built-in rule already has this ID
+    DuplicateId(String),
+    /// The directory is not a checkout of the analyzer
+    NotRepository(PathBuf),
+    /// The module of the rule already exists
+    Exists(PathBuf),
+    /// A file cannot be read or written
+    Io(PathBuf, std::io::Error),
+}
+
+impl fmt::Display for ScaffoldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScaffoldError::InvalidId(id) => {
+                write!(f, "Invalid rule ID '{id}': lowercase letters, digits and dashes, starting with a letter")
+            }
+            ScaffoldError::DuplicateId(id) => write!(f, "A built-in rule already has the ID '{id}'"),
+            ScaffoldError::NotRepository(path) => write!(f, "{} is not a checkout of the analyzer (no {RULES_DIR})", path.display()),
+            ScaffoldError::Exists(path) => write!(f, "{} already exists", path.display()),
+            ScaffoldError::Io(path, e) => write!(f, "Cannot write {}: {e}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ScaffoldError {}
+
+/// Items a scaffolded rule checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleKind {
+    /// Account structs (`#[derive(Accounts)]`)
+    Struct,
+    /// Functions and methods
+    Function,
+}
+
+impl std::str::FromStr for RuleKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "struct" => Ok(RuleKind::Struct),
+            "function" => Ok(RuleKind::Function),
+            _ => Err(format!("unknown rule kind '{value}' (struct or function)")),
+        }
+    }
+}
+
+/// Skeleton of a built-in rule (`new-rule <id>`): the module of the rule (`mod.rs`), its
+/// filter (`filters.rs`), its unit tests (`test.rs`) and its `ok`/`bad` fixtures
+/// (`tests/fixtures/<id>`)
+///
+/// The rule registers itself with `rule!`; the module is declared in the `mod.rs` of its
+/// severity. The condition of the filter is left to write: until then, the vulnerable
+/// fixture and example are not reported and their tests fail.
+#[derive(Debug, Clone)]
+pub struct RuleScaffold {
+    /// ID of the rule
+    pub id: String,
+    /// Severity of the rule, which is also its directory
+    pub severity: Severity,
+    /// Items the rule checks
+    pub kind: RuleKind,
+}
+
+impl RuleScaffold {
+    /// Creates the skeleton of a rule, checking its ID
+    pub fn new(id: &str, severity: Severity, kind: RuleKind) -> Result<Self, ScaffoldError> {
+        let valid = id.starts_with(|c: char| c.is_ascii_lowercase())
+            && !id.ends_with('-')
+            && !id.contains("--")
+            && id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid {
+            return Err(ScaffoldError::InvalidId(id.to_string()));
+        }
+        Ok(Self { id: id.to_string(), severity, kind })
+    }
+
+    /// Name of the module (the ID in snake case, without the `solana-` prefix)
+    pub fn module(&self) -> String {
+        self.id.strip_prefix("solana-").unwrap_or(&self.id).replace('-', "_")
+    }
+
+    /// Directory of the rule, relative to the repository
+    pub fn dir(&self) -> PathBuf {
+        Path::new(RULES_DIR).join(self.severity.as_str()).join(self.module())
+    }
+
+    /// Fixtures directory of the rule, relative to the repository
+    pub fn fixtures_dir(&self) -> PathBuf {
+        Path::new(FIXTURES_DIR).join(&self.id)
+    }
+
+    /// Files of the rule (`mod.rs`, `filters.rs`, `test.rs`, then the fixtures), relative to
+    /// the repository
+    pub fn files(&self) -> Vec<(PathBuf, String)> {
+        let module = self.module();
+        let title = module
+            .split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars.next().map(|first| first.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let filters_trait = title.replace(' ', "") + "Filters";
+        let severity = format!("{:?}", self.severity);
+        let (template_mod, template_filters, template_test, kind) = match self.kind {
+            RuleKind::Struct => (STRUCT_MOD, STRUCT_FILTERS, STRUCT_TEST, "struct"),
+            RuleKind::Function => (FUNCTION_MOD, FUNCTION_FILTERS, FUNCTION_TEST, "function"),
+        };
+        let (vulnerable, safe, line) = match self.kind {
+            RuleKind::Struct => (STRUCT_VULNERABLE, STRUCT_SAFE, 3),
+            RuleKind::Function => (FUNCTION_VULNERABLE, FUNCTION_SAFE, 1),
+        };
+        let render = |template: &str| {
+            template
+                .replace("__ID__", &self.id)
+                .replace("__TITLE__", &title)
+                .replace("__SEVERITY_DIR__", self.severity.as_str())
+                .replace("__SEVERITY__", &severity)
+                .replace("__MODULE__", &module)
+                .replace("__TRAIT__", &filters_trait)
+        };
+
+        let dir = self.dir();
+        let fixtures_dir = self.fixtures_dir();
+        vec![
+            (dir.join("mod.rs"), render(template_mod)),
+            (dir.join("filters.rs"), render(template_filters)),
+            (dir.join("test.rs"), render(template_test)),
+            (fixtures_dir.join(format!("ok/safe_{kind}.rs")), safe.to_string()),
+            (fixtures_dir.join(format!("bad/vulnerable_{kind}.rs")), vulnerable.to_string()),
+            (
+                fixtures_dir.join("bad/expected.json"),
+                format!("{{\n  \"vulnerable_{kind}.rs\": [{{ \"line\": {line}, \"severity\": \"{severity}\" }}]\n}}\n"),
+            ),
+        ]
+    }
+
+    /// Write the files of the rule in the repository at `root` and declare its module,
+    /// returning the files written and modified
+    pub fn write(&self, root: &Path) -> Result<Vec<PathBuf>, ScaffoldError> {
+        let rules_dir = root.join(RULES_DIR);
+        if !rules_dir.is_dir() {
+            return Err(ScaffoldError::NotRepository(root.to_path_buf()));
+        }
+        let mut engine = RuleEngine::default();
+        if engine.load_builtin_rules().is_ok() && engine.registry().metadata_of(&self.id).is_some() {
+            return Err(ScaffoldError::DuplicateId(self.id.clone()));
+        }
+        for dir in [root.join(self.dir()), root.join(self.fixtures_dir())] {
+            if dir.exists() {
+                return Err(ScaffoldError::Exists(dir));
+            }
+        }
+
+        let mut written = Vec::new();
+        for (path, content) in self.files() {
+            let path = root.join(path);
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| ScaffoldError::Io(path.clone(), e))?;
+            }
+            std::fs::write(&path, content).map_err(|e| ScaffoldError::Io(path.clone(), e))?;
+            written.push(path);
+        }
+
+        // Severities without rules yet get their module too
+        let severity_mod = rules_dir.join(self.severity.as_str()).join("mod.rs");
+        if !severity_mod.exists() {
+            let solana_mod = rules_dir.join("mod.rs");
+            declare_module(&solana_mod, self.severity.as_str())?;
+            written.push(solana_mod);
+        }
+        declare_module(&severity_mod, &self.module())?;
+        written.push(severity_mod);
+        Ok(written)
+    }
+}
+
+/// Helper function to add `pub mod <name>;` to a module file, keeping the declarations sorted
+fn declare_module(path: &Path, name: &str) -> Result<(), ScaffoldError> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(ScaffoldError::Io(path.to_path_buf(), e)),
+    };
+    let declaration = format!("pub mod {name};");
+    let mut lines: Vec<&str> = content.lines().collect();
+    let position = lines
+        .iter()
+        .position(|line| line.starts_with("pub mod ") && **line > *declaration)
+        .or_else(|| lines.iter().rposition(|line| line.starts_with("pub mod ")).map(|last| last + 1))
+        .unwrap_or(0);
+    lines.insert(position, &declaration);
+    let content = lines.join("\n") + "\n";
+    std::fs::write(path, content).map_err(|e| ScaffoldError::Io(path.to_path_buf(), e))
+}
+
+const STRUCT_VULNERABLE: &str = r#"use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Vulnerable<'info> {
+    #[account(mut)]
+    pub vault: AccountInfo<'info>,
+}
+"#;
+
+const STRUCT_SAFE: &str = r#"use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Safe<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+}
+"#;
+
+const FUNCTION_VULNERABLE: &str = r#"pub fn withdraw(amount: u64, balance: u64) -> u64 {
+    balance - amount
+}
+"#;
+
+const FUNCTION_SAFE: &str = r#"pub fn withdraw(amount: u64, balance: u64) -> Option<u64> {
+    balance.checked_sub(amount)
+}
+"#;
+
+const STRUCT_MOD: &str = r###"use crate::analyzer::dsl::rule;
+use crate::analyzer::engine::RuleType;
+
+// Import our specific filters
+mod filters;
+use filters::__TRAIT__;
+
+#[cfg(test)]
+mod test;
+
+rule! {
+    id: "__ID__",
+    title: "__TITLE__",
+    // TODO: what the rule detects, why it matters and how to fix it
+    description: "Detects account structs ...",
+    message: "{title} in '{struct}'",
+    severity: __SEVERITY__,
+    rule_type: RuleType::Anchor,
+    recommendations: vec![
+        "..."
+    ],
+    rationale: "...",
+    vulnerable_example: r#"
+        #[derive(Accounts)]
+        pub struct Vulnerable<'info> {
+            #[account(mut)]
+            pub vault: AccountInfo<'info>,
+        }
+    "#,
+    safe_example: r#"
+        #[derive(Accounts)]
+        pub struct Safe<'info> {
+            #[account(mut)]
+            pub vault: Account<'info, Vault>,
+        }
+    "#,
+    tag: "security",
+    tag: "anchor",
+    pack: "anchor-core",
+    query: structs().derives_accounts().has___MODULE__(),
+}
+"###;
+
+const STRUCT_FILTERS: &str = r###"use log::{debug, trace};
+use syn::ItemStruct;
+use crate::analyzer::dsl::query::{AstQuery, NodeData};
+
+pub trait __TRAIT__<'a> {
+    fn has___MODULE__(self) -> AstQuery<'a>;
+}
+
+impl<'a> __TRAIT__<'a> for AstQuery<'a> {
+    fn has___MODULE__(self) -> AstQuery<'a> {
+        debug!("Filtering account structs for __ID__");
+        let mut new_results = Vec::new();
+
+        for node in self.results() {
+            if let NodeData::Struct(struct_item) = &node.data
+                && is_vulnerable(struct_item)
+            {
+                trace!("Found __ID__ in struct: {}", struct_item.ident);
+                new_results.push(node.clone());
+            }
+        }
+
+        self.with_results(new_results)
+    }
+}
+
+/// Check if an account struct has the issue
+pub fn is_vulnerable(struct_item: &ItemStruct) -> bool {
+    // TODO: the condition of the rule
+    let _ = struct_item;
+    false
+}
+"###;
+
+const STRUCT_TEST: &str = r###"use crate::analyzer::rules::solana::__SEVERITY_DIR__::__MODULE__::filters::is_vulnerable;
+use syn::{ItemStruct, parse_quote};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vulnerable_struct() {
+        let struct_def: ItemStruct = parse_quote! {
+            #[derive(Accounts)]
+            pub struct Vulnerable<'info> {
+                #[account(mut)]
+                pub vault: AccountInfo<'info>,
+            }
+        };
+
+        assert!(is_vulnerable(&struct_def), "Should detect the vulnerable struct");
+    }
+
+    #[test]
+    fn test_safe_struct() {
+        let struct_def: ItemStruct = parse_quote! {
+            #[derive(Accounts)]
+            pub struct Safe<'info> {
+                #[account(mut)]
+                pub vault: Account<'info, Vault>,
+            }
+        };
+
+        assert!(!is_vulnerable(&struct_def), "Should not detect the safe struct");
+    }
+}
+"###;
+
+const FUNCTION_MOD: &str = r###"use crate::analyzer::dsl::rule;
+use crate::analyzer::engine::RuleType;
+
+// Import our specific filters
+mod filters;
+use filters::__TRAIT__;
+
+#[cfg(test)]
+mod test;
+
+rule! {
+    id: "__ID__",
+    title: "__TITLE__",
+    // TODO: what the rule detects, why it matters and how to fix it
+    description: "Detects functions ...",
+    severity: __SEVERITY__,
+    rule_type: RuleType::Solana,
+    recommendations: vec![
+        "..."
+    ],
+    rationale: "...",
+    vulnerable_example: r#"
+        pub fn withdraw(amount: u64, balance: u64) -> u64 {
+            balance - amount
+        }
+    "#,
+    safe_example: r#"
+        pub fn withdraw(amount: u64, balance: u64) -> Option<u64> {
+            balance.checked_sub(amount)
+        }
+    "#,
+    tag: "security",
+    query: functions().has___MODULE__(),
+}
+"###;
+
+const FUNCTION_FILTERS: &str = r###"use log::{debug, trace};
+use syn::{Block, Signature};
+use crate::analyzer::dsl::query::{AstQuery, NodeData};
+
+pub trait __TRAIT__<'a> {
+    fn has___MODULE__(self) -> AstQuery<'a>;
+}
+
+impl<'a> __TRAIT__<'a> for AstQuery<'a> {
+    fn has___MODULE__(self) -> AstQuery<'a> {
+        debug!("Filtering functions for __ID__");
+        let mut new_results = Vec::new();
+
+        for node in self.results() {
+            let (sig, block) = match node.data {
+                NodeData::Function(func) => (&func.sig, &*func.block),
+                NodeData::ImplFunction(func) => (&func.sig, &func.block),
+                _ => continue,
+            };
+            if is_vulnerable(sig, block) {
+                trace!("Found __ID__ in function: {}", sig.ident);
+                new_results.push(node.clone());
+            }
+        }
+
+        self.with_results(new_results)
+    }
+}
+
+/// Check if a function has the issue
+pub fn is_vulnerable(sig: &Signature, block: &Block) -> bool {
+    // TODO: the condition of the rule
+    let _ = (sig, block);
+    false
+}
+"###;
+
+const FUNCTION_TEST: &str = r###"use crate::analyzer::rules::solana::__SEVERITY_DIR__::__MODULE__::filters::is_vulnerable;
+use syn::{ItemFn, parse_quote};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vulnerable_function() {
+        let func: ItemFn = parse_quote! {
+            pub fn withdraw(amount: u64, balance: u64) -> u64 {
+                balance - amount
+            }
+        };
+
+        assert!(is_vulnerable(&func.sig, &func.block), "Should detect the vulnerable function");
+    }
+
+    #[test]
+    fn test_safe_function() {
+        let func: ItemFn = parse_quote! {
+            pub fn withdraw(amount: u64, balance: u64) -> Option<u64> {
+                balance.checked_sub(amount)
+            }
+        };
+
+        assert!(!is_vulnerable(&func.sig, &func.block), "Should not detect the safe function");
+    }
+}
+"###;
@@ -0,0 +1,71 @@
+use std::path::Path;
+
+use crate::analyzer::Severity;
+use crate::analyzer::scaffold::{RULES_DIR, RuleKind, RuleScaffold, ScaffoldError};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_files() {
+        let scaffold = RuleScaffold::new("solana-unchecked-close", Severity::High, RuleKind::Function).unwrap();
+        assert_eq!(scaffold.module(), "unchecked_close");
+        assert_eq!(scaffold.dir(), Path::new("src/analyzer/rules/solana/high/unchecked_close"));
+
+        let files = scaffold.files();
+        let names: Vec<_> = files.iter().map(|(path, _)| path.file_name().unwrap().to_str().unwrap()).collect();
+        assert_eq!(names, ["mod.rs", "filters.rs", "test.rs", "safe_function.rs", "vulnerable_function.rs", "expected.json"]);
+        assert_eq!(files[3].0, Path::new("tests/fixtures/solana-unchecked-close/ok/safe_function.rs"));
+        assert_eq!(files[5].1, "{\n  \"vulnerable_function.rs\": [{ \"line\": 1, \"severity\": \"High\" }]\n}\n");
+        assert!(files[0].1.contains("    id: \"solana-unchecked-close\",\n    title: \"Unchecked Close\",\n"), "{}", files[0].1);
+        assert!(files[0].1.contains("    severity: High,\n"), "{}", files[0].1);
+        assert!(files[0].1.contains("    query: functions().has_unchecked_close(),\n"), "{}", files[0].1);
+        assert!(files[1].1.contains("impl<'a> UncheckedCloseFilters<'a> for AstQuery<'a> {\n"), "{}", files[1].1);
+        assert!(files[2].1.starts_with("use crate::analyzer::rules::solana::high::unchecked_close::filters::is_vulnerable;\n"));
+
+        let files = RuleScaffold::new("shared-vault-seeds", Severity::Medium, RuleKind::Struct).unwrap().files();
+        assert!(files[0].1.contains("    query: structs().derives_accounts().has_shared_vault_seeds(),\n"), "{}", files[0].1);
+        assert!(files[5].1.contains("[{ \"line\": 3, \"severity\": \"Medium\" }]"), "{}", files[5].1);
+
+        for id in ["Missing-Check", "missing_check", "-missing", "missing-", "missing--check", "1-missing"] {
+            assert!(matches!(RuleScaffold::new(id, Severity::Low, RuleKind::Struct), Err(ScaffoldError::InvalidId(_))), "{id}");
+        }
+    }
+
+    #[test]
+    fn test_write() {
+        let root = std::env::temp_dir().join(format!("solana-analyzer-scaffold-{}", std::process::id()));
+        let rules_dir = root.join(RULES_DIR);
+        std::fs::create_dir_all(rules_dir.join("high")).unwrap();
+        std::fs::write(rules_dir.join("mod.rs"), "pub mod high;\npub mod low;\npub mod medium;\n").unwrap();
+        std::fs::write(rules_dir.join("high/mod.rs"), "pub mod missing_signer_check;\npub mod unsafe_code;\n").unwrap();
+
+        let scaffold = RuleScaffold::new("solana-unchecked-close", Severity::High, RuleKind::Function).unwrap();
+        let written = scaffold.write(&root).unwrap();
+        assert_eq!(written.len(), 7);
+        assert!(rules_dir.join("high/unchecked_close/test.rs").is_file());
+        assert!(root.join("tests/fixtures/solana-unchecked-close/bad/expected.json").is_file());
+        assert_eq!(
+            std::fs::read_to_string(rules_dir.join("high/mod.rs")).unwrap(),
+            "pub mod missing_signer_check;\npub mod unchecked_close;\npub mod unsafe_code;\n"
+        );
+        assert!(matches!(scaffold.write(&root), Err(ScaffoldError::Exists(_))));
+
+        // A severity without rules yet is declared too
+        let scaffold = RuleScaffold::new("stale-comments", Severity::Informational, RuleKind::Function).unwrap();
+        scaffold.write(&root).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(rules_dir.join("mod.rs")).unwrap(),
+            "pub mod high;\npub mod informational;\npub mod low;\npub mod medium;\n"
+        );
+        assert_eq!(std::fs::read_to_string(rules_dir.join("informational/mod.rs")).unwrap(), "pub mod stale_comments;\n");
+
+        // Built-in IDs are taken
+        let scaffold = RuleScaffold::new("solana-unsafe-code", Severity::High, RuleKind::Function).unwrap();
+        assert!(matches!(scaffold.write(&root), Err(ScaffoldError::DuplicateId(_))));
+        assert!(matches!(scaffold.write(&root.join("src")), Err(ScaffoldError::NotRepository(_))));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+use log::debug;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use syn::spanned::Spanned;
+use syn::{Attribute, File, ImplItem, Item};
+
+use crate::analyzer::Location;
+use crate::analyzer::vfs::FileSystem;
+use crate::ast::modules::is_test_attribute;
+
+#[cfg(test)]
+mod test;
+
+/// Directories of code that is not deployed: integration tests, benchmarks and deployment scripts
+pub const TEST_DIRECTORIES: &[&str] = &["tests", "benches", "migrations"];
+
+/// Crate the Anchor-specific rules require
+pub const ANCHOR_CRATE: &str = "anchor-lang";
+
+/// Check if a file belongs to a test directory, by its path relative to the project root
+pub fn is_test_path(path: &Path, project_root: Option<&Path>) -> bool {
+    let relative = project_root.and_then(|root| path.strip_prefix(root).ok()).unwrap_or(path);
+    relative.parent().is_some_and(|dir| {
+        dir.components().any(|component| {
+            matches!(component, Component::Normal(name) if TEST_DIRECTORIES.iter().any(|test_dir| name == *test_dir))
+        })
+    })
+}
+
+/// Test code of the analyzed files, whose findings are not reported
+///
+/// Whole files are test code when they live in a test directory (see [`TEST_DIRECTORIES`]) or
+/// are declared with `#[cfg(test)] mod tests;`. Inside other files, `#[cfg(test)]` modules and
+/// `#[test]` functions are.
+#[derive(Debug, Clone, Default)]
+pub struct TestCode {
+    /// Line ranges (1-indexed, inclusive) of the test code, by file path (`None` for the whole file)
+    files: HashMap<String, Option<Vec<(usize, usize)>>>,
+}
+
+impl TestCode {
+    /// Find the test code of a file, `test_module` being set if the file is a test module
+    pub fn add_file(&mut self, file_path: &str, ast: &File, test_module: bool, project_root: Option<&Path>) {
+        if test_module || is_test_path(Path::new(file_path), project_root) {
+            self.files.insert(file_path.to_string(), None);
+            return;
+        }
+        let mut ranges = Vec::new();
+        collect_test_ranges(&ast.items, &mut ranges);
+        if !ranges.is_empty() {
+            self.files.insert(file_path.to_string(), Some(ranges));
+        }
+    }
+
+    /// Check if a whole file is test code
+    pub fn is_test_file(&self, file_path: &str) -> bool {
+        matches!(self.files.get(file_path), Some(None))
+    }
+
+    /// Check if a location is in test code
+    pub fn contains(&self, location: &Location) -> bool {
+        match self.files.get(&location.file) {
+            Some(None) => true,
+            Some(Some(ranges)) => ranges.iter().any(|(start, end)| (*start..=*end).contains(&location.line)),
+            None => false,
+        }
+    }
+}
+
+/// Helper function to collect the line ranges of the test modules and functions of a list of items
+fn collect_test_ranges(items: &[Item], ranges: &mut Vec<(usize, usize)>) {
+    for item in items {
+        match item {
+            Item::Mod(item_mod) if is_test_item(&item_mod.attrs) => ranges.push(line_range(item)),
+            Item::Mod(item_mod) => {
+                if let Some((_, content)) = &item_mod.content {
+                    collect_test_ranges(content, ranges);
+                }
+            }
+            Item::Fn(item_fn) if is_test_item(&item_fn.attrs) => ranges.push(line_range(item)),
+            Item::Impl(item_impl) => {
+                for impl_item in &item_impl.items {
+                    if let ImplItem::Fn(impl_fn) = impl_item
+                        && is_test_item(&impl_fn.attrs)
+                    {
+                        ranges.push(line_range(impl_fn));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Helper function to get the lines of a node
+fn line_range<T: Spanned>(node: &T) -> (usize, usize) {
+    let span = node.span();
+    (span.start().line, span.end().line)
+}
+
+/// Helper function to check if an item is test code (`#[test]`, `#[cfg(test)]`)
+fn is_test_item(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(is_test_attribute)
+}
+
+/// Anchor usage of the crates of the analyzed files, read from their `Cargo.toml`
+#[derive(Debug, Clone, Default)]
+pub struct AnchorCrates {
+    /// Whether each manifest depends on anchor-lang, by manifest path
+    manifests: HashMap<PathBuf, bool>,
+}
+
+impl AnchorCrates {
+    /// Check if the crate of a file (nearest `Cargo.toml` above it) depends on anchor-lang
+    ///
+    /// Files without a manifest, or with a manifest that cannot be read, are assumed to use Anchor.
+    pub fn uses_anchor(&mut self, fs: &dyn FileSystem, file: &Path) -> bool {
+        let Some(manifest) = file.ancestors().skip(1).map(|dir| dir.join("Cargo.toml")).find(|manifest| fs.is_file(manifest)) else {
+            return true;
+        };
+        *self.manifests.entry(manifest).or_insert_with_key(|manifest| {
+            let uses_anchor = fs.read_to_string(manifest).map_or(true, |content| depends_on(&content, ANCHOR_CRATE));
+            debug!("{} {} {ANCHOR_CRATE}", manifest.display(), if uses_anchor { "uses" } else { "does not use" });
+            uses_anchor
+        })
+    }
+}
+
+/// Check if a manifest declares a dependency (in `[dependencies]`, including the target-specific
+/// ones). Invalid and virtual manifests are assumed to declare it.
+pub fn depends_on(manifest: &str, dependency: &str) -> bool {
+    // Virtual manifests (workspace roots) do not tell what their files depend on
+    let Some(manifest) = manifest.parse::<toml::Table>().ok().filter(|manifest| manifest.contains_key("package")) else {
+        return true;
+    };
+    let declares = |table: Option<&toml::Value>| {
+        table.and_then(toml::Value::as_table).is_some_and(|dependencies| dependencies.contains_key(dependency))
+    };
+    declares(manifest.get("dependencies"))
+        || manifest
+            .get("target")
+            .and_then(toml::Value::as_table)
+            .is_some_and(|targets| targets.values().any(|target| declares(target.get("dependencies"))))
+}
@@ -0,0 +1,68 @@
+use crate::analyzer::Location;
+use crate::analyzer::scope::{ANCHOR_CRATE, TestCode, depends_on, is_test_path};
+use std::path::Path;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+pub fn process(amount: u64) -> u64 {
+    amount / 2
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_process() {}
+}
+
+#[test]
+fn test_split() {}
+"#;
+
+    fn location(file: &str, line: usize) -> Location {
+        Location::new_precise(file.to_string(), line, None, None, None)
+    }
+
+    #[test]
+    fn test_test_code_covers_test_modules_and_directories() {
+        let root = Path::new("/home/dev/tests/vault");
+        assert!(!is_test_path(&root.join("programs/vault/src/lib.rs"), Some(root)));
+        assert!(is_test_path(&root.join("tests/withdraw.rs"), Some(root)));
+        assert!(is_test_path(&root.join("programs/vault/benches/swap.rs"), Some(root)));
+        assert!(is_test_path(Path::new("migrations/deploy.rs"), None));
+        assert!(!is_test_path(Path::new("src/tests.rs"), None));
+
+        let ast = syn::parse_file(SOURCE).unwrap();
+        let mut test_code = TestCode::default();
+        test_code.add_file("src/lib.rs", &ast, false, None);
+        test_code.add_file("src/helpers.rs", &ast, true, None);
+        test_code.add_file("tests/withdraw.rs", &ast, false, None);
+
+        assert!(!test_code.contains(&location("src/lib.rs", 3)));
+        assert!(test_code.contains(&location("src/lib.rs", 9)));
+        assert!(test_code.contains(&location("src/lib.rs", 13)));
+        assert!(!test_code.is_test_file("src/lib.rs"));
+        assert!(test_code.is_test_file("src/helpers.rs"));
+        assert!(test_code.contains(&location("tests/withdraw.rs", 3)));
+        assert!(!test_code.contains(&location("src/other.rs", 9)));
+    }
+
+    #[test]
+    fn test_anchor_dependency_is_read_from_the_manifest() {
+        let package = "[package]\nname = \"vault\"\n";
+        assert!(depends_on(&format!("{package}[dependencies]\nanchor-lang = \"0.31\"\n"), ANCHOR_CRATE));
+        assert!(depends_on(&format!("{package}[dependencies]\nanchor-lang.workspace = true\n"), ANCHOR_CRATE));
+        assert!(depends_on(
+            &format!("{package}[target.'cfg(not(target_os = \"solana\"))'.dependencies]\nanchor-lang = \"0.31\"\n"),
+            ANCHOR_CRATE
+        ));
+        assert!(!depends_on(&format!("{package}[dependencies]\nsolana-program = \"2\"\n"), ANCHOR_CRATE));
+        assert!(!depends_on(&format!("{package}[dev-dependencies]\nanchor-lang = \"0.31\"\n"), ANCHOR_CRATE));
+
+        // Workspace roots and invalid manifests do not tell
+        assert!(depends_on("[workspace]\nmembers = [\"programs/*\"]\n", ANCHOR_CRATE));
+        assert!(depends_on("[package", ANCHOR_CRATE));
+    }
+}
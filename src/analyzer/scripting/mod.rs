@@ -0,0 +1,545 @@
+use anyhow::{Result, anyhow};
+use log::{debug, info};
+use rhai::{AST, Array, Dynamic, Engine, Map, Scope};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::Path;
+use syn::File;
+use syn::spanned::Spanned;
+
+use crate::analyzer::dsl::builders::is_valid_rule_id;
+use crate::analyzer::engine::rule_deadline_exceeded;
+use crate::analyzer::packs::is_valid_pack_name;
+use crate::analyzer::dsl::call_graph::{CallGraph, called_names};
+use crate::analyzer::dsl::context::NodeContext;
+use crate::analyzer::dsl::query::{AstNode, AstQuery, MetadataValue, Metadata, NodeData};
+use crate::analyzer::span_utils::SpanExtractor;
+use crate::analyzer::vfs::FileSystem;
+use crate::analyzer::{Category, Confidence, Finding, Location, Rule, RuleType, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Maximum number of operations a script can run per file (stops runaway loops)
+const MAX_OPERATIONS: u64 = 5_000_000;
+
+/// Number of operations between two checks of the time budget of the rule
+const DEADLINE_CHECK_INTERVAL: u64 = 1024;
+
+/// Error returned when a script rule cannot be loaded
+#[derive(Debug)]
+pub enum ScriptError {
+    /// The script file cannot be read
+    Io { path: String, error: std::io::Error },
+    /// The script does not compile
+    Compile { path: String, error: String },
+    /// The `rule()` function is missing or returns invalid metadata
+    Metadata { path: String, reason: String },
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::Io { path, error } => write!(f, "cannot read script {path}: {error}"),
+            ScriptError::Compile { path, error } => write!(f, "script {path} does not compile: {error}"),
+            ScriptError::Metadata { path, reason } => write!(f, "invalid rule metadata in script {path}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// AST item exposed to scripts
+///
+/// Scripts cannot hold references into the AST, so each item is an owned snapshot
+/// of the properties rules usually check, computed with the same queries as the DSL.
+#[derive(Debug, Clone)]
+pub struct ScriptNode {
+    kind: &'static str,
+    name: String,
+    public: bool,
+    instruction: bool,
+    accounts: bool,
+    test: bool,
+    attributes: Array,
+    return_type: String,
+    calls: BTreeSet<String>,
+    transitive_calls: BTreeSet<String>,
+    fields: Array,
+    location: Location,
+    snippet: String,
+}
+
+impl ScriptNode {
+    /// Helper function to snapshot a function or struct node
+    fn from_node(node: &AstNode<'_>, file: &FileModel<'_>) -> Self {
+        let (location, snippet) = match node.get_spanned_node() {
+            Some(spanned) => (
+                file.span_extractor.extract_location(spanned),
+                file.span_extractor.extract_snippet(spanned),
+            ),
+            None => (file.span_extractor.span_to_location(proc_macro2::Span::call_site()), node.snippet()),
+        };
+
+        let name = node.name();
+        let transitive_calls = file
+            .graph
+            .reachable_from([name.as_str()])
+            .iter()
+            .flat_map(|function| file.graph.function(function))
+            .flat_map(called_names)
+            .collect();
+
+        Self {
+            kind: if node.node_type == crate::analyzer::dsl::query::NodeType::Function {
+                "function"
+            } else {
+                "struct"
+            },
+            public: node.is_public(),
+            instruction: file.instructions.contains(&name),
+            accounts: false,
+            test: file.context.is_test_code(node),
+            attributes: node.attrs().iter().map(|attr| Dynamic::from(attribute_name(attr))).collect(),
+            return_type: node.return_type().unwrap_or_default(),
+            calls: called_names(node),
+            transitive_calls,
+            fields: match node.data {
+                NodeData::Struct(struct_item) => struct_item.fields.iter().map(field_map).collect(),
+                _ => Array::new(),
+            },
+            name,
+            location,
+            snippet,
+        }
+    }
+}
+
+/// Helper function to get the name of an attribute (last path segment, e.g. `account`)
+fn attribute_name(attr: &syn::Attribute) -> String {
+    attr.path()
+        .segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+        .unwrap_or_default()
+}
+
+/// Helper function to describe a struct field to scripts
+fn field_map(field: &syn::Field) -> Dynamic {
+    use quote::ToTokens;
+
+    let mut map = Map::new();
+    map.insert(
+        "name".into(),
+        field.ident.as_ref().map(ToString::to_string).unwrap_or_default().into(),
+    );
+    map.insert("ty".into(), field.ty.to_token_stream().to_string().replace(' ', "").into());
+    map.insert(
+        "attributes".into(),
+        field.attrs.iter().map(|attr| Dynamic::from(attribute_name(attr))).collect::<Array>().into(),
+    );
+    // Content of the #[account(...)] attribute (Anchor constraints), empty if none
+    let constraints = field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("account"))
+        .filter_map(|attr| attr.meta.require_list().ok())
+        .map(|list| list.tokens.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    map.insert("constraints".into(), constraints.into());
+    map.insert("line".into(), (field.span().start().line as i64).into());
+    Dynamic::from_map(map)
+}
+
+/// Analyses of a file shared while its nodes are snapshotted
+struct FileModel<'a> {
+    graph: CallGraph<'a>,
+    instructions: BTreeSet<String>,
+    context: NodeContext<'a>,
+    span_extractor: &'a SpanExtractor,
+}
+
+/// File exposed to scripts
+#[derive(Debug, Clone)]
+pub struct ScriptFile {
+    path: String,
+    functions: Array,
+    structs: Array,
+}
+
+impl ScriptFile {
+    /// Snapshot the functions and structs of a file
+    pub fn new(ast: &File, file_path: &str, span_extractor: &SpanExtractor) -> Self {
+        let graph = CallGraph::from_file(ast);
+        let model = FileModel {
+            instructions: graph.instructions().map(str::to_string).collect(),
+            graph,
+            context: NodeContext::new(file_path, ast),
+            span_extractor,
+        };
+
+        let accounts: BTreeSet<String> = AstQuery::new(ast)
+            .structs()
+            .derives_accounts()
+            .collect()
+            .iter()
+            .map(AstNode::name)
+            .collect();
+
+        let functions = AstQuery::new(ast)
+            .functions()
+            .collect()
+            .iter()
+            .map(|node| Dynamic::from(ScriptNode::from_node(node, &model)))
+            .collect();
+        let structs = AstQuery::new(ast)
+            .structs()
+            .collect()
+            .iter()
+            .map(|node| {
+                let mut script_node = ScriptNode::from_node(node, &model);
+                script_node.accounts = accounts.contains(&script_node.name);
+                Dynamic::from(script_node)
+            })
+            .collect();
+
+        Self {
+            path: file_path.to_string(),
+            functions,
+            structs,
+        }
+    }
+}
+
+/// Creates the sandboxed script engine with the AST API registered
+///
+/// Scripts have no access to the file system or the network, and the number of
+/// operations, call depth and sizes of strings and collections are limited. Scripts are
+/// interrupted once the rule exceeds its time budget.
+fn create_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(64)
+        .set_max_expr_depths(64, 64)
+        .set_max_string_size(1 << 20)
+        .set_max_array_size(100_000)
+        .set_max_map_size(10_000);
+    engine.on_progress(|operations| {
+        (operations % DEADLINE_CHECK_INTERVAL == 0 && rule_deadline_exceeded()).then(|| "time budget exceeded".into())
+    });
+    engine.on_print(|text| info!("[script] {text}"));
+    engine.on_debug(|text, source, position| debug!("[script {}:{position}] {text}", source.unwrap_or("")));
+
+    engine
+        .register_type_with_name::<ScriptFile>("File")
+        .register_get("path", |file: &mut ScriptFile| file.path.clone())
+        .register_fn("functions", |file: &mut ScriptFile| file.functions.clone())
+        .register_fn("structs", |file: &mut ScriptFile| file.structs.clone())
+        .register_fn("accounts", |file: &mut ScriptFile| {
+            file.structs
+                .iter()
+                .filter(|node| Dynamic::clone(node).try_cast::<ScriptNode>().is_some_and(|node| node.accounts))
+                .cloned()
+                .collect::<Array>()
+        });
+
+    engine
+        .register_type_with_name::<ScriptNode>("Node")
+        .register_get("kind", |node: &mut ScriptNode| node.kind.to_string())
+        .register_get("name", |node: &mut ScriptNode| node.name.clone())
+        .register_get("is_public", |node: &mut ScriptNode| node.public)
+        .register_get("is_instruction", |node: &mut ScriptNode| node.instruction)
+        .register_get("derives_accounts", |node: &mut ScriptNode| node.accounts)
+        .register_get("is_test", |node: &mut ScriptNode| node.test)
+        .register_get("attributes", |node: &mut ScriptNode| node.attributes.clone())
+        .register_get("return_type", |node: &mut ScriptNode| node.return_type.clone())
+        .register_get("fields", |node: &mut ScriptNode| node.fields.clone())
+        .register_get("line", |node: &mut ScriptNode| node.location.line as i64)
+        .register_get("snippet", |node: &mut ScriptNode| node.snippet.clone())
+        .register_fn("has_attribute", |node: &mut ScriptNode, name: &str| {
+            node.attributes.iter().any(|attr| attr.clone().into_string().is_ok_and(|attr| attr == name))
+        })
+        .register_fn("calls", |node: &mut ScriptNode, name: &str| node.calls.contains(name))
+        .register_fn("calls_transitively", |node: &mut ScriptNode, name: &str| {
+            node.transitive_calls.contains(name)
+        });
+
+    // finding(node, message) => #{ node, message }, severity/confidence/metadata can be set on the map
+    engine.register_fn("finding", |node: ScriptNode, message: &str| {
+        let mut map = Map::new();
+        map.insert("node".into(), Dynamic::from(node));
+        map.insert("message".into(), message.into());
+        map
+    });
+
+    engine
+}
+
+/// Rule implemented by a Rhai script
+///
+/// The script defines two functions:
+///
+/// ```rhai
+/// fn rule() {
+///     #{
+///         id: "transfer-without-authority-check",
+///         title: "Transfer Without Authority Check",
+///         description: "Instructions moving funds should verify the authority",
+///         severity: "high",                 // high, medium, low, informational
+///         confidence: "medium",             // optional
+///         tags: ["access-control"],         // optional, selectable with --tags
+///         references: ["https://..."],      // optional
+///         category: "access-control",       // optional, see `Category`
+///         cwe: [862],                       // optional
+///         packs: ["defi", "spl-token"],     // optional, selectable with --profile
+///         recommendations: ["Call check_authority before transferring"],
+///     }
+/// }
+///
+/// fn check(file) {
+///     let findings = [];
+///     for func in file.functions() {
+///         if func.is_instruction && func.calls_transitively("transfer")
+///             && !func.calls_transitively("check_authority") {
+///             findings.push(finding(func, "Transfer without authority check in '" + func.name + "'"));
+///         }
+///     }
+///     findings
+/// }
+/// ```
+pub struct ScriptRule {
+    id: String,
+    title: String,
+    description: String,
+    severity: Severity,
+    confidence: Confidence,
+    recommendations: Vec<String>,
+    tags: Vec<String>,
+    references: Vec<String>,
+    cwe: Vec<u32>,
+    category: Option<Category>,
+    packs: Vec<String>,
+    path: String,
+    engine: Engine,
+    ast: AST,
+}
+
+impl ScriptRule {
+    /// Load a script rule from a file
+    #[cfg(feature = "native")]
+    pub fn from_file(path: &Path) -> std::result::Result<Self, ScriptError> {
+        Self::load(&crate::analyzer::vfs::OsFileSystem, path)
+    }
+
+    /// Load a script rule from a file of a file system
+    pub fn load(fs: &dyn FileSystem, path: &Path) -> std::result::Result<Self, ScriptError> {
+        let path_str = path.display().to_string();
+        let script = fs.read_to_string(path).map_err(|error| ScriptError::Io {
+            path: path_str.clone(),
+            error,
+        })?;
+        Self::from_script(&script, &path_str)
+    }
+
+    /// Compile a script rule and read its metadata
+    pub fn from_script(script: &str, path: &str) -> std::result::Result<Self, ScriptError> {
+        let engine = create_engine();
+        let mut ast = engine.compile(script).map_err(|error| ScriptError::Compile {
+            path: path.to_string(),
+            error: error.to_string(),
+        })?;
+        ast.set_source(path);
+
+        let invalid = |reason: String| ScriptError::Metadata {
+            path: path.to_string(),
+            reason,
+        };
+        if !ast.iter_functions().any(|function| function.name == "check" && function.params.len() == 1) {
+            return Err(invalid("missing `fn check(file)`".to_string()));
+        }
+
+        let metadata: Map = engine
+            .call_fn(&mut Scope::new(), &ast, "rule", ())
+            .map_err(|e| invalid(format!("`fn rule()` failed: {e}")))?;
+        let text = |key: &str| -> std::result::Result<String, ScriptError> {
+            match metadata.get(key).map(|value| value.clone().into_string()) {
+                Some(Ok(value)) if !value.trim().is_empty() => Ok(value),
+                _ => Err(invalid(format!("`{key}` must be a non-empty string"))),
+            }
+        };
+
+        let id = text("id")?;
+        if !is_valid_rule_id(&id) {
+            return Err(invalid(format!("invalid rule ID '{id}'")));
+        }
+        let severity = text("severity")?.parse().map_err(invalid)?;
+        let confidence = match metadata.get("confidence") {
+            Some(_) => text("confidence")?.parse().map_err(invalid)?,
+            None => Confidence::High,
+        };
+        let list = |key: &str| -> std::result::Result<Vec<String>, ScriptError> {
+            match metadata.get(key) {
+                Some(value) => value
+                    .clone()
+                    .into_typed_array::<String>()
+                    .map_err(|_| invalid(format!("`{key}` must be an array of strings"))),
+                None => Ok(Vec::new()),
+            }
+        };
+        let recommendations = list("recommendations")?;
+        let tags = list("tags")?;
+        let references = list("references")?;
+        let cwe = match metadata.get("cwe") {
+            Some(value) => value
+                .clone()
+                .into_typed_array::<i64>()
+                .ok()
+                .and_then(|ids| ids.into_iter().map(|id| u32::try_from(id).ok()).collect::<Option<Vec<_>>>())
+                .ok_or_else(|| invalid("`cwe` must be an array of CWE numbers".to_string()))?,
+            None => Vec::new(),
+        };
+        let category = match metadata.get("category") {
+            Some(_) => Some(text("category")?.parse().map_err(invalid)?),
+            None => None,
+        };
+        let packs = list("packs")?;
+        if let Some(pack) = packs.iter().find(|pack| !is_valid_pack_name(pack)) {
+            return Err(invalid(format!("invalid pack name '{pack}'")));
+        }
+
+        Ok(Self {
+            title: text("title")?,
+            description: text("description")?,
+            id,
+            severity,
+            confidence,
+            recommendations,
+            tags,
+            references,
+            cwe,
+            category,
+            packs,
+            path: path.to_string(),
+            engine,
+            ast,
+        })
+    }
+
+    /// Helper function to convert a finding map returned by the script
+    fn convert_finding(&self, value: Dynamic) -> Result<Finding> {
+        let map = value
+            .try_cast::<Map>()
+            .ok_or_else(|| anyhow!("findings must be created with finding(node, message)"))?;
+        let node = map
+            .get("node")
+            .and_then(|node| node.clone().try_cast::<ScriptNode>())
+            .ok_or_else(|| anyhow!("finding without a node"))?;
+        let message = map
+            .get("message")
+            .and_then(|message| message.clone().into_string().ok())
+            .ok_or_else(|| anyhow!("finding without a message"))?;
+
+        let parse_field = |key: &str| map.get(key).and_then(|value| value.clone().into_string().ok());
+        let severity = match parse_field("severity") {
+            Some(severity) => severity.parse().map_err(|e: String| anyhow!(e))?,
+            None => self.severity.clone(),
+        };
+        let confidence = match parse_field("confidence") {
+            Some(confidence) => confidence.parse::<Confidence>().map_err(|e: String| anyhow!(e))?,
+            None => self.confidence,
+        };
+        let metadata: Metadata = map
+            .get("metadata")
+            .and_then(|metadata| metadata.clone().try_cast::<Map>())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), MetadataValue::Text(value.to_string())))
+            .collect();
+
+        Ok(Finding {
+            description: message,
+            severity,
+            // Findings never report a higher confidence than the rule's
+            confidence: confidence.min(self.confidence),
+            location: node.location,
+            code_snippet: Some(node.snippet),
+            recommendations: self.recommendations.clone(),
+            fixes: Vec::new(),
+            metadata,
+            rules: Vec::new(),
+            fingerprint: None,
+        })
+    }
+}
+
+impl Rule for ScriptRule {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity.clone()
+    }
+
+    fn rule_type(&self) -> RuleType {
+        RuleType::General
+    }
+
+    fn recommendations(&self) -> Vec<String> {
+        self.recommendations.clone()
+    }
+
+    fn tags(&self) -> Vec<String> {
+        self.tags.clone()
+    }
+
+    fn references(&self) -> Vec<String> {
+        self.references.clone()
+    }
+
+    fn cwe(&self) -> Vec<u32> {
+        self.cwe.clone()
+    }
+
+    fn category(&self) -> Option<Category> {
+        self.category
+    }
+
+    fn packs(&self) -> Vec<String> {
+        self.packs.clone()
+    }
+
+    fn execute(&self, ast: &File, file_path: &str) -> Result<Vec<Finding>> {
+        self.execute_with_source(ast, file_path, "")
+    }
+
+    fn execute_with_source(&self, ast: &File, file_path: &str, source_code: &str) -> Result<Vec<Finding>> {
+        debug!("Executing script rule {} ({}) in {file_path}", self.id, self.path);
+        let span_extractor = SpanExtractor::shared(source_code, file_path);
+        let file = ScriptFile::new(ast, file_path, &span_extractor);
+
+        // A failing script only loses its own findings (errors and panics are reported to the engine)
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            self.engine.call_fn::<Array>(&mut Scope::new(), &self.ast, "check", (file,))
+        }))
+        .map_err(|_| anyhow!("script {} panicked", self.path))?
+        .map_err(|e| anyhow!("script {} failed: {e}", self.path))?;
+
+        result
+            .into_iter()
+            .map(|value| self.convert_finding(value))
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| anyhow!("script {} returned an invalid finding: {e}", self.path))
+    }
+}
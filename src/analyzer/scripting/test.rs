@@ -0,0 +1,101 @@
+use crate::analyzer::scripting::{ScriptError, ScriptRule};
+use crate::analyzer::{Category, Confidence, Rule, Severity};
+use std::path::Path;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    move_funds(&ctx, amount)
+}
+
+pub fn withdraw_checked(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    check_authority(&ctx)?;
+    move_funds(&ctx, amount)
+}
+
+fn move_funds(ctx: &Context<Withdraw>, amount: u64) -> Result<()> {
+    transfer(ctx, amount)
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, has_one = authority)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+"#;
+
+    fn load(script: &str) -> Result<ScriptRule, ScriptError> {
+        ScriptRule::from_script(script, "test.rhai")
+    }
+
+    #[test]
+    fn test_example_script_rule() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates").join("transfer-without-authority-check.rhai");
+        let rule = ScriptRule::from_file(&path).unwrap();
+        assert_eq!(rule.id(), "transfer-without-authority-check");
+        assert_eq!(rule.severity(), Severity::High);
+        assert_eq!(rule.packs(), ["defi", "spl-token"]);
+        assert_eq!(rule.category(), Some(Category::AccessControl));
+        assert_eq!(rule.cwe(), [862]);
+
+        let ast = syn::parse_file(SOURCE).unwrap();
+        let findings = rule.execute_with_source(&ast, "src/lib.rs", SOURCE).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].location.line, 2);
+        assert_eq!(findings[0].confidence, Confidence::Medium);
+        assert_eq!(findings[0].description, "Transfer without authority check in 'withdraw'");
+    }
+
+    #[test]
+    fn test_script_api_and_finding_overrides() {
+        let rule = load(r#"
+            fn rule() { #{ id: "script-accounts", title: "Accounts", description: "Accounts structs", severity: "low" } }
+            fn check(file) {
+                let findings = [];
+                for item in file.accounts() {
+                    let field = item.fields[0];
+                    if item.derives_accounts && field.constraints.contains("has_one") && field.ty == "Account<'info,Vault>" {
+                        let f = finding(item, item.kind + " " + item.name);
+                        f.severity = "medium";
+                        f.metadata = #{ field: field.name };
+                        findings.push(f);
+                    }
+                }
+                findings
+            }
+        "#).unwrap();
+
+        let ast = syn::parse_file(SOURCE).unwrap();
+        let findings = rule.execute_with_source(&ast, "src/lib.rs", SOURCE).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].description, "struct Withdraw");
+        assert_eq!(findings[0].severity, Severity::Medium);
+        assert_eq!(findings[0].metadata["field"].to_string(), "vault");
+    }
+
+    #[test]
+    fn test_script_errors_are_isolated() {
+        assert!(matches!(load("fn rule( {"), Err(ScriptError::Compile { .. })));
+        assert!(matches!(
+            load(r#"fn rule() { #{ id: "no-check", title: "t", description: "d", severity: "low" } }"#),
+            Err(ScriptError::Metadata { .. })
+        ));
+        assert!(matches!(
+            load(r#"fn rule() { #{ id: "Bad_Id", title: "t", description: "d", severity: "low" } } fn check(file) { [] }"#),
+            Err(ScriptError::Metadata { .. })
+        ));
+
+        let ast = syn::parse_file(SOURCE).unwrap();
+        let metadata = r#"fn rule() { #{ id: "broken", title: "t", description: "d", severity: "low" } }"#;
+
+        // Runtime errors and runaway loops fail the rule instead of the run
+        for check in ["fn check(file) { file.unknown() }", "fn check(file) { loop {} }", "fn check(file) { [1] }"] {
+            let rule = load(&format!("{metadata} {check}")).unwrap();
+            assert!(rule.execute_with_source(&ast, "src/lib.rs", SOURCE).is_err(), "{check}");
+        }
+    }
+}
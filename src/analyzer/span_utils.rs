@@ -1,7 +1,9 @@
-use proc_macro2::Span;
+use proc_macro2::{LineColumn, Span};
 use syn::spanned::Spanned;
-use crate::analyzer::Location;
+use crate::analyzer::{Location, TextEdit};
+use crate::analyzer::dsl::cache;
 use crate::analyzer::dsl::query::NodeData;
+use std::rc::Rc;
 
 pub struct SpanExtractor {
     source_code: String,
@@ -16,6 +18,12 @@ impl SpanExtractor {
         }
     }
 
+    /// Extractor for the file being analyzed, built once and shared by every rule running on it
+    /// (see [`cache::memoize`]; a new extractor is built outside the rule engine)
+    pub fn shared(source_code: &str, file_path: &str) -> Rc<Self> {
+        cache::memoize(&format!("span-extractor:{file_path}"), || Self::new(source_code.to_string(), file_path.to_string()))
+    }
+
     /// Extract precise location from a span
     pub fn extract_location<T: Spanned + ?Sized>(&self, node: &T) -> Location {
         let span = node.span();
@@ -46,6 +54,51 @@ impl SpanExtractor {
         }
     }
 
+    /// Convert a single position to an empty Location (used for insertions)
+    fn point_location(&self, position: LineColumn) -> Location {
+        Location {
+            file: self.file_path.clone(),
+            line: position.line,
+            column: Some(position.column),
+            end_line: Some(position.line),
+            end_column: Some(position.column),
+        }
+    }
+
+    /// Edit replacing the code of a node
+    pub fn replace_edit<T: Spanned + ?Sized>(&self, node: &T, replacement: &str) -> TextEdit {
+        TextEdit {
+            location: self.extract_location(node),
+            replacement: replacement.to_string(),
+        }
+    }
+
+    /// Edit inserting text right before a node
+    pub fn insert_before_edit<T: Spanned + ?Sized>(&self, node: &T, text: &str) -> TextEdit {
+        TextEdit {
+            location: self.point_location(node.span().start()),
+            replacement: text.to_string(),
+        }
+    }
+
+    /// Edit inserting text right after a node
+    pub fn insert_after_edit<T: Spanned + ?Sized>(&self, node: &T, text: &str) -> TextEdit {
+        TextEdit {
+            location: self.point_location(node.span().end()),
+            replacement: text.to_string(),
+        }
+    }
+
+    /// Leading whitespace of the line where a node starts (to indent inserted lines)
+    pub fn indentation_of<T: Spanned + ?Sized>(&self, node: &T) -> String {
+        let line = node.span().start().line;
+        self.source_code
+            .lines()
+            .nth(line.saturating_sub(1))
+            .map(|text| text.chars().take_while(|c| c.is_whitespace()).collect())
+            .unwrap_or_default()
+    }
+
     /// Extract a code snippet from the source code based on span
     pub fn extract_snippet<T: Spanned + ?Sized>(&self, node: &T) -> String {
         let span = node.span();
@@ -67,7 +120,7 @@ impl SpanExtractor {
             return "// Code snippet out of bounds".to_string();
         }
 
-        let start_line_idx = (start.line - 1).max(0);
+        let start_line_idx = start.line - 1;
         let end_line_idx = (end.line - 1).min(lines.len() - 1);
 
         if start_line_idx == end_line_idx {
@@ -113,32 +166,28 @@ impl SpanExtractor {
     /// Get context around a span (includes surrounding lines)
     pub fn extract_context<T: Spanned>(&self, node: &T, context_lines: usize) -> String {
         let span = node.span();
-        let start = span.start();
-        let end = span.end();
-        
-        if start.line == 0 || end.line == 0 {
-            return "// Context unavailable".to_string();
-        }
+        self.extract_lines_context(span.start().line, span.end().line, context_lines)
+            .unwrap_or_else(|| "// Context unavailable".to_string())
+    }
 
+    /// Get `context_lines` lines before and after the lines `line..=end_line` (1-indexed), numbered,
+    /// the lines themselves marked with `→` (`None` if they are not in the source)
+    pub fn extract_lines_context(&self, line: usize, end_line: usize, context_lines: usize) -> Option<String> {
         let lines: Vec<&str> = self.source_code.lines().collect();
-        
-        let context_start = (start.line.saturating_sub(context_lines + 1)).max(0);
-        let context_end = (end.line + context_lines - 1).min(lines.len());
-        
+        let end_line = end_line.max(line);
+        if line == 0 || end_line > lines.len() {
+            return None;
+        }
+
+        let context_start = line.saturating_sub(context_lines).max(1);
+        let context_end = (end_line + context_lines).min(lines.len());
+
         let mut context = String::new();
-        
-        for (i, line_idx) in (context_start..context_end).enumerate() {
-            let actual_line_num = line_idx + 1;
-            let line = lines.get(line_idx).unwrap_or(&"");
-            
-            if actual_line_num >= start.line && actual_line_num <= end.line {
-                context.push_str(&format!("→ {actual_line_num:3} | {line}\n"));
-            } else {
-                context.push_str(&format!("  {actual_line_num:3} | {line}\n"));
-            }
+        for number in context_start..=context_end {
+            let marker = if (line..=end_line).contains(&number) { '→' } else { ' ' };
+            context.push_str(&format!("{marker} {number:3} | {}\n", lines[number - 1]));
         }
-        
-        context
+        Some(context)
     }
 
     /// Extract function signature or struct definition
@@ -0,0 +1,244 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::fs;
+
+use crate::analyzer::engine::RuleProfile;
+use crate::analyzer::ignore::Exclusion;
+use crate::analyzer::json::Tool;
+use crate::analyzer::registry::RuleRegistry;
+use crate::analyzer::results::SeverityCounts;
+use crate::analyzer::{AnalysisResult, RuleType, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Version of the statistics report schema, incremented on any change that is not an addition
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Number of rules listed in the slowest rules of the summary
+const SLOWEST_RULES: usize = 10;
+
+/// Rules of a rule type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RuleTypeCoverage {
+    /// Rules registered, enabled or not
+    pub registered: usize,
+    /// Rules selected by the configuration
+    pub enabled: usize,
+    /// Enabled rules with at least one reported finding
+    pub with_findings: usize,
+}
+
+/// Count the rules of each rule type, by type name
+///
+/// Every rule type is listed, even without rules. Rules have findings if they are in
+/// `findings_by_rule` (see [`AnalysisStats::findings_by_rule`](crate::analyzer::AnalysisStats)).
+pub fn rule_type_coverage(
+    registry: &RuleRegistry,
+    findings_by_rule: &BTreeMap<String, usize>,
+) -> BTreeMap<String, RuleTypeCoverage> {
+    let mut coverage: BTreeMap<String, RuleTypeCoverage> =
+        RuleType::ALL.iter().map(|rule_type| (rule_type.as_str().to_string(), RuleTypeCoverage::default())).collect();
+    for rule in registry.metadata() {
+        let rules = coverage.entry(rule.rule_type.as_str().to_string()).or_default();
+        rules.registered += 1;
+        if rule.enabled {
+            rules.enabled += 1;
+            rules.with_findings += usize::from(findings_by_rule.contains_key(&rule.id));
+        }
+    }
+    coverage
+}
+
+/// Statistics of an analysis (`--stats` and `--stats-output`), for performance work and to
+/// show the coverage of an audit
+///
+/// ```json
+/// {
+///   "schema_version": 1,
+///   "tool": { "name": "rust-solana-analyzer", "version": "0.1.0" },
+///   "project": "programs/vault",
+///   "files": { "analyzed": 12, "unparsed": 1, "cached": 0, "test_files_skipped": 2 },
+///   "lines_of_code": { "total": 1840, "by_file": { "programs/vault/src/lib.rs": 310 } },
+///   "time_ms": 412,
+///   "rules": {
+///     "executed": 45,
+///     "without_findings": ["unsafe-code"],
+///     "timed_out": {},
+///     "profiles": { "missing-signer-check": { "runs": 12, "time_ms": 3.2, "files_matched": 1, "findings": 2 } }
+///   },
+///   "rule_types": { "anchor": { "registered": 20, "enabled": 20, "with_findings": 3 } },
+///   "findings": { "high": 2, "medium": 1, "low": 0, "informational": 4 }
+/// }
+/// ```
+///
+/// The per-rule timings and the rules without findings come from the rule profiles, recorded
+/// with [`AnalysisOptions::profile_rules`](crate::analyzer::AnalysisOptions::profile_rules).
+#[derive(Debug, Serialize)]
+pub struct StatsReport<'a> {
+    /// Version of the schema ([`SCHEMA_VERSION`])
+    pub schema_version: u32,
+    /// Analyzer that produced the report
+    pub tool: Tool,
+    /// Analyzed path
+    pub project: &'a str,
+    /// Files analyzed and skipped
+    pub files: FileStats,
+    /// Paths left out of the analysis, with the rules that excluded them
+    pub exclusions: &'a [Exclusion],
+    /// Lines of code of the analyzed files
+    pub lines_of_code: LinesOfCode<'a>,
+    /// Total analysis time in milliseconds
+    pub time_ms: u64,
+    /// Rules executed, their timings and the ones without findings
+    pub rules: RuleStats<'a>,
+    /// Rules of each rule type, by type name
+    pub rule_types: &'a BTreeMap<String, RuleTypeCoverage>,
+    /// Number of findings of each severity
+    pub findings: SeverityCounts,
+}
+
+/// Files of an analysis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FileStats {
+    /// Files analyzed
+    pub analyzed: usize,
+    /// Files not analyzed because they could not be parsed
+    pub unparsed: usize,
+    /// Analyzed files whose results were reused from the cache
+    pub cached: usize,
+    /// Test files not analyzed
+    pub test_files_skipped: usize,
+    /// Paths (files or directories) left out by the exclusions
+    pub excluded: usize,
+}
+
+/// Lines of code of an analysis, without the blank and comment lines
+#[derive(Debug, Serialize)]
+pub struct LinesOfCode<'a> {
+    /// Lines of code of every analyzed file
+    pub total: usize,
+    /// Lines of code of each analyzed file, by path
+    pub by_file: &'a BTreeMap<String, usize>,
+}
+
+/// Rules of an analysis
+#[derive(Debug, Serialize)]
+pub struct RuleStats<'a> {
+    /// Number of rules executed
+    pub executed: usize,
+    /// Profiled rules without any reported finding (after suppressions and merging)
+    pub without_findings: Vec<&'a str>,
+    /// Files (or the project) on which each rule exceeded its time budget, by rule ID
+    pub timed_out: &'a BTreeMap<String, BTreeSet<String>>,
+    /// Execution profile of each rule, by rule ID
+    pub profiles: &'a BTreeMap<String, RuleProfile>,
+}
+
+impl<'a> StatsReport<'a> {
+    /// Creates the statistics of an analysis of `project`
+    pub fn new(result: &'a AnalysisResult, project: &'a str) -> Self {
+        let stats = &result.stats;
+        Self {
+            schema_version: SCHEMA_VERSION,
+            tool: Tool::default(),
+            project,
+            files: FileStats {
+                analyzed: stats.files_analyzed,
+                unparsed: stats.files_unparsed,
+                cached: stats.files_cached,
+                test_files_skipped: stats.test_files_skipped,
+                excluded: stats.exclusions.len(),
+            },
+            exclusions: &stats.exclusions,
+            lines_of_code: LinesOfCode { total: stats.total_lines_of_code(), by_file: &stats.lines_of_code },
+            time_ms: stats.total_time_ms,
+            rules: RuleStats {
+                executed: stats.rules_executed,
+                without_findings: stats
+                    .rule_profiles
+                    .keys()
+                    .filter(|rule_id| !stats.findings_by_rule.contains_key(*rule_id))
+                    .map(String::as_str)
+                    .collect(),
+                timed_out: &stats.timed_out_rules,
+                profiles: &stats.rule_profiles,
+            },
+            rule_types: &stats.rule_types,
+            findings: result.severity_counts(),
+        }
+    }
+
+    /// Returns the statistics as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("statistics serialize to JSON");
+        json.push('\n');
+        json
+    }
+
+    /// Save the statistics to a JSON file
+    pub fn save(&self, output_path: &str) -> Result<(), std::io::Error> {
+        fs::write(output_path, self.to_json())
+    }
+
+    /// Returns the summary of the statistics printed with `--stats`
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("Analysis statistics\n");
+        let files = &self.files;
+        let _ = writeln!(
+            text,
+            "- Files: {} analyzed, {} unparsed, {} from the cache, {} test files skipped, {} paths excluded",
+            files.analyzed, files.unparsed, files.cached, files.test_files_skipped, files.excluded
+        );
+        let _ = writeln!(text, "- Lines of code: {}", self.lines_of_code.total);
+        let _ = writeln!(text, "- Time: {}ms", self.time_ms);
+        let _ = writeln!(
+            text,
+            "- Rules: {} executed, {} without reported findings, {} timed out",
+            self.rules.executed,
+            self.rules.without_findings.len(),
+            self.rules.timed_out.len()
+        );
+        let findings: Vec<String> = Severity::ALL
+            .iter()
+            .map(|severity| format!("{} {}", self.findings.get(severity), severity.as_str()))
+            .collect();
+        let _ = writeln!(text, "- Findings: {}", findings.join(", "));
+
+        text.push_str("- Rule types (enabled/registered):\n");
+        for (rule_type, rules) in self.rule_types.iter().filter(|(_, rules)| rules.registered > 0) {
+            let _ = writeln!(
+                text,
+                "  - {rule_type}: {}/{}, {} with findings",
+                rules.enabled, rules.registered, rules.with_findings
+            );
+        }
+
+        let mut profiles: Vec<(&String, &RuleProfile)> =
+            self.rules.profiles.iter().filter(|(_, profile)| profile.runs > 0).collect();
+        profiles.sort_by(|(a_id, a), (b_id, b)| b.time.cmp(&a.time).then_with(|| a_id.cmp(b_id)));
+        if !profiles.is_empty() {
+            text.push_str("- Slowest rules:\n");
+            for (rule_id, profile) in profiles.into_iter().take(SLOWEST_RULES) {
+                let _ = writeln!(
+                    text,
+                    "  - {rule_id}: {:.2}ms, {} runs, {} findings",
+                    profile.time.as_secs_f64() * 1000.0,
+                    profile.runs,
+                    profile.findings
+                );
+            }
+        }
+        if !self.rules.without_findings.is_empty() {
+            let _ = writeln!(text, "- Rules without reported findings: {}", self.rules.without_findings.join(", "));
+        }
+        if !self.exclusions.is_empty() {
+            text.push_str("- Excluded paths:\n");
+            for exclusion in self.exclusions {
+                let _ = writeln!(text, "  - {} ({})", exclusion.path, exclusion.rule);
+            }
+        }
+        text
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+use crate::analyzer::dsl::{AstQuery, RuleBuilder};
+use crate::analyzer::engine::RuleProfile;
+use crate::analyzer::registry::RuleRegistry;
+use crate::analyzer::stats::{RuleTypeCoverage, StatsReport, rule_type_coverage};
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{AnalysisResult, AnalysisStats, RuleType, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: &str, rule_type: RuleType) -> RuleBuilder {
+        RuleBuilder::new()
+            .id(id)
+            .title("Test Rule")
+            .description("Matches every function")
+            .severity(Severity::Low)
+            .rule_type(rule_type)
+            .dsl_query(|ast, _file_path, _span_extractor| AstQuery::new(ast).functions())
+    }
+
+    fn profile(runs: usize, millis: u64, findings: usize) -> RuleProfile {
+        RuleProfile { runs, time: Duration::from_millis(millis), files_matched: usize::from(findings > 0), findings }
+    }
+
+    fn result() -> AnalysisResult {
+        let mut stats = AnalysisStats {
+            files_analyzed: 2,
+            files_unparsed: 1,
+            test_files_skipped: 3,
+            total_time_ms: 40,
+            rules_executed: 3,
+            ..Default::default()
+        };
+        stats.lines_of_code.insert("src/lib.rs".to_string(), 120);
+        stats.lines_of_code.insert("src/state.rs".to_string(), 30);
+        stats.rule_profiles.insert("anchor-slow-rule".to_string(), profile(2, 25, 1));
+        stats.rule_profiles.insert("anchor-quiet-rule".to_string(), profile(2, 5, 0));
+        stats.rule_profiles.insert("solana-quiet-rule".to_string(), profile(2, 1, 0));
+        stats.findings_by_rule.insert("anchor-slow-rule".to_string(), 1);
+        stats.timed_out_rules.insert("anchor-slow-rule".to_string(), BTreeSet::from(["src/lib.rs".to_string()]));
+        stats.rule_types.insert("anchor".to_string(), RuleTypeCoverage { registered: 3, enabled: 2, with_findings: 1 });
+        stats.rule_types.insert("steel".to_string(), RuleTypeCoverage::default());
+        let finding = FindingBuilder::new("Slow finding").severity(Severity::High).span("src/lib.rs", 4, 1, 10).build();
+        AnalysisResult { findings: vec![finding], stats }
+    }
+
+    #[test]
+    fn test_rule_type_coverage() {
+        let mut registry = RuleRegistry::new();
+        registry.register(rule("anchor-a", RuleType::Anchor).build().unwrap(), true).unwrap();
+        registry.register(rule("anchor-b", RuleType::Anchor).build().unwrap(), true).unwrap();
+        registry.register(rule("anchor-c", RuleType::Anchor).build().unwrap(), false).unwrap();
+        registry.register(rule("steel-a", RuleType::Steel).build().unwrap(), false).unwrap();
+        let findings_by_rule = BTreeMap::from([("anchor-a".to_string(), 2), ("anchor-c".to_string(), 1)]);
+
+        let coverage = rule_type_coverage(&registry, &findings_by_rule);
+        assert_eq!(coverage.len(), RuleType::ALL.len());
+        assert_eq!(coverage["anchor"], RuleTypeCoverage { registered: 3, enabled: 2, with_findings: 1 });
+        assert_eq!(coverage["steel"], RuleTypeCoverage { registered: 1, enabled: 0, with_findings: 0 });
+        assert_eq!(coverage["native"], RuleTypeCoverage::default());
+    }
+
+    #[test]
+    fn test_stats_report_json() {
+        let result = result();
+        let json: serde_json::Value = serde_json::from_str(&StatsReport::new(&result, "programs/vault").to_json()).unwrap();
+
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["project"], "programs/vault");
+        assert_eq!(json["files"]["analyzed"], 2);
+        assert_eq!(json["files"]["unparsed"], 1);
+        assert_eq!(json["files"]["test_files_skipped"], 3);
+        assert_eq!(json["lines_of_code"]["total"], 150);
+        assert_eq!(json["lines_of_code"]["by_file"]["src/state.rs"], 30);
+        assert_eq!(json["time_ms"], 40);
+        assert_eq!(json["rules"]["executed"], 3);
+        assert_eq!(json["rules"]["without_findings"], serde_json::json!(["anchor-quiet-rule", "solana-quiet-rule"]));
+        assert_eq!(json["rules"]["timed_out"]["anchor-slow-rule"], serde_json::json!(["src/lib.rs"]));
+        assert_eq!(json["rules"]["profiles"]["anchor-slow-rule"]["time_ms"], 25.0);
+        assert_eq!(json["rule_types"]["anchor"]["with_findings"], 1);
+        assert_eq!(json["findings"]["high"], 1);
+    }
+
+    #[test]
+    fn test_stats_report_text() {
+        let result = result();
+        let text = StatsReport::new(&result, "programs/vault").to_text();
+
+        assert!(text.contains("- Files: 2 analyzed, 1 unparsed, 0 from the cache, 3 test files skipped, 0 paths excluded\n"));
+        assert!(text.contains("- Lines of code: 150\n"));
+        assert!(text.contains("- Rules: 3 executed, 2 without reported findings, 1 timed out\n"));
+        assert!(text.contains("- Findings: 1 high, 0 medium, 0 low, 0 informational\n"));
+        assert!(text.contains("  - anchor: 2/3, 1 with findings\n"));
+        assert!(!text.contains("steel"));
+        let slowest = text.find("anchor-slow-rule: 25.00ms, 2 runs, 1 findings").unwrap();
+        assert!(slowest < text.find("anchor-quiet-rule: 5.00ms").unwrap());
+        assert!(text.ends_with("- Rules without reported findings: anchor-quiet-rule, solana-quiet-rule\n"));
+    }
+
+    #[test]
+    fn test_stats_report_without_profiles() {
+        let result = AnalysisResult { findings: Vec::new(), stats: AnalysisStats::default() };
+        let text = StatsReport::new(&result, ".").to_text();
+
+        assert!(!text.contains("Slowest rules"));
+        assert!(!text.contains("Rules without reported findings"));
+    }
+}
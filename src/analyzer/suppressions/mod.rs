@@ -0,0 +1,432 @@
+use log::warn;
+use regex::Regex;
+use serde::Serialize;
+use std::sync::LazyLock;
+
+use crate::analyzer::dsl::builders::is_valid_rule_id;
+use crate::analyzer::dsl::query::{Metadata, MetadataValue};
+use crate::analyzer::{Confidence, Finding, Location, RuleInfo, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// ID under which unused suppressions are reported (can be ignored like a rule ID)
+pub const UNUSED_SUPPRESSION_ID: &str = "unused-suppression";
+
+/// Suppression directive: `analyzer:ignore`, `analyzer:ignore-start[ids]` or `analyzer:ignore-end`,
+/// with optional rule IDs and `reason="..."`
+static DIRECTIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^analyzer:ignore(?P<scope>-start|-end)?(?:\[(?P<rules>[^\]]*)\])?\s*(?:reason\s*=\s*"(?P<reason>[^"]*)")?\s*$"#)
+        .expect("valid suppression regex")
+});
+
+/// What removed a suppressed finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuppressedBy {
+    /// An `analyzer:ignore` comment
+    Comment,
+    /// An ignored severity (`--ignore`, `rules.ignore_severities`)
+    Severity,
+    /// The minimum confidence (`--min-confidence`, `thresholds.min_confidence`)
+    Confidence,
+}
+
+impl SuppressedBy {
+    /// Returns the name of the cause (`comment`...)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SuppressedBy::Comment => "comment",
+            SuppressedBy::Severity => "severity",
+            SuppressedBy::Confidence => "confidence",
+        }
+    }
+}
+
+/// Finding removed by a suppression comment or by the severity and confidence filters
+#[derive(Debug, Clone, Serialize)]
+pub struct SuppressedFinding {
+    /// ID of the rule that reported the finding
+    pub rule_id: String,
+    /// What removed the finding
+    pub suppressed_by: SuppressedBy,
+    /// Reason of the suppression, if it gives one (the filter for the severity and confidence)
+    pub reason: Option<String>,
+    pub finding: Finding,
+}
+
+/// `//` comment of the source code
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comment {
+    /// Line of the comment (1-indexed)
+    line: usize,
+    /// Whether code precedes the comment on its line
+    trailing: bool,
+    /// Text after `//`
+    text: String,
+}
+
+/// Suppression of the findings of some rules on a range of lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    /// Suppressed rule IDs (`*` for every rule)
+    pub rules: Vec<String>,
+    /// Why the findings are suppressed
+    pub reason: Option<String>,
+    /// Line of the suppression comment
+    pub line: usize,
+    /// First suppressed line
+    pub start_line: usize,
+    /// Last suppressed line
+    pub end_line: usize,
+    /// Whether each rule of `rules` suppressed a finding
+    used: Vec<bool>,
+}
+
+impl Suppression {
+    fn new(rules: Vec<String>, reason: Option<String>, line: usize, start_line: usize, end_line: usize) -> Self {
+        let used = vec![false; rules.len()];
+        Self {
+            rules,
+            reason,
+            line,
+            start_line,
+            end_line,
+            used,
+        }
+    }
+
+    /// Index of the entry of `rules` suppressing a finding of the rule spanning the given lines
+    fn matching_rule(&self, rule_id: &str, line: usize, end_line: usize) -> Option<usize> {
+        if self.start_line > end_line || self.end_line < line {
+            return None;
+        }
+        self.rules.iter().position(|rule| rule == rule_id || rule == "*")
+    }
+}
+
+/// Suppression comments of a file
+///
+/// ```ignore
+/// let value = amount / shares; // analyzer:ignore[solana-division-by-zero] reason="shares > 0 checked above"
+///
+/// // analyzer:ignore[owner-check, duplicate-mutable-accounts]
+/// #[derive(Accounts)]
+///
+/// // analyzer:ignore-start[solana-unsafe-code] reason="audited"
+/// ...
+/// // analyzer:ignore-end
+/// ```
+///
+/// A trailing comment suppresses the findings of its own line, a comment on its own line
+/// suppresses the findings of the next line of code, and `ignore-start` / `ignore-end`
+/// suppress the findings between them. A suppression applies to the findings whose span
+/// overlaps its lines, so a comment inside a function also covers a finding on the whole function.
+#[derive(Debug, Clone, Default)]
+pub struct Suppressions {
+    suppressions: Vec<Suppression>,
+    suppressed: Vec<SuppressedFinding>,
+}
+
+impl Suppressions {
+    /// Parse the suppression comments of a file (malformed comments are logged and ignored)
+    pub fn parse(source_code: &str, file_path: &str) -> Self {
+        let (comments, code_lines) = scan_comments(source_code);
+        let last_line = source_code.lines().count().max(1);
+        let mut suppressions = Vec::new();
+        let mut open_blocks: Vec<Suppression> = Vec::new();
+
+        for comment in comments {
+            let text = comment.text.trim();
+            if !text.starts_with("analyzer:ignore") {
+                continue;
+            }
+            let Some(captures) = DIRECTIVE.captures(text) else {
+                warn!("Malformed suppression in {file_path}:{}: {text}", comment.line);
+                continue;
+            };
+            let rules: Vec<String> = captures
+                .name("rules")
+                .map(|rules| {
+                    rules
+                        .as_str()
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|rule| !rule.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+            if let Some(rule) = rules.iter().find(|rule| *rule != "*" && !is_valid_rule_id(rule)) {
+                warn!("Invalid rule ID '{rule}' in suppression at {file_path}:{}", comment.line);
+                continue;
+            }
+            let reason = captures.name("reason").map(|reason| reason.as_str().to_string());
+
+            match captures.name("scope").map(|scope| scope.as_str()) {
+                Some("-end") => {
+                    // Close the last block, or the last block with the same rules
+                    let position = if rules.is_empty() {
+                        open_blocks.len().checked_sub(1)
+                    } else {
+                        open_blocks.iter().rposition(|block| block.rules == rules)
+                    };
+                    match position {
+                        Some(position) => {
+                            let mut block = open_blocks.remove(position);
+                            block.end_line = comment.line;
+                            suppressions.push(block);
+                        }
+                        None => warn!("Suppression end without start at {file_path}:{}", comment.line),
+                    }
+                }
+                _ if rules.is_empty() => {
+                    warn!("Suppression without rule IDs at {file_path}:{} (use [rule-id] or [*])", comment.line);
+                }
+                Some("-start") => {
+                    open_blocks.push(Suppression::new(rules, reason, comment.line, comment.line, last_line));
+                }
+                _ => {
+                    let target = if comment.trailing {
+                        Some(comment.line)
+                    } else {
+                        code_lines.iter().copied().find(|line| *line > comment.line)
+                    };
+                    match target {
+                        Some(target) => suppressions.push(Suppression::new(rules, reason, comment.line, target, target)),
+                        None => warn!("Suppression without code after it at {file_path}:{}", comment.line),
+                    }
+                }
+            }
+        }
+
+        for block in open_blocks {
+            warn!("Suppression at {file_path}:{} is never closed, it applies until the end of the file", block.line);
+            suppressions.push(block);
+        }
+        suppressions.sort_by_key(|suppression| suppression.line);
+
+        Self {
+            suppressions,
+            suppressed: Vec::new(),
+        }
+    }
+
+    /// Check if the file has no suppression
+    pub fn is_empty(&self) -> bool {
+        self.suppressions.is_empty()
+    }
+
+    /// Suppressions of the file
+    pub fn suppressions(&self) -> &[Suppression] {
+        &self.suppressions
+    }
+
+    /// Number of findings removed by the suppressions
+    pub fn suppressed_count(&self) -> usize {
+        self.suppressed.len()
+    }
+
+    /// Findings removed by the suppressions, in the order they were removed
+    pub fn suppressed(&self) -> &[SuppressedFinding] {
+        &self.suppressed
+    }
+
+    /// Remove the findings of a rule covered by a suppression, recording the suppressions used
+    pub fn apply(&mut self, rule_id: &str, findings: Vec<Finding>) -> Vec<Finding> {
+        let mut kept = Vec::new();
+        for finding in findings {
+            let line = finding.location.line;
+            let end_line = finding.location.end_line.unwrap_or(line).max(line);
+            // Every matching suppression is used, the first one gives the reason
+            let mut suppressed: Option<Option<String>> = None;
+            for suppression in &mut self.suppressions {
+                if let Some(index) = suppression.matching_rule(rule_id, line, end_line) {
+                    suppression.used[index] = true;
+                    suppressed.get_or_insert_with(|| suppression.reason.clone());
+                }
+            }
+            match suppressed {
+                Some(reason) => self.suppressed.push(SuppressedFinding {
+                    rule_id: rule_id.to_string(),
+                    suppressed_by: SuppressedBy::Comment,
+                    reason,
+                    finding,
+                }),
+                None => kept.push(finding),
+            }
+        }
+        kept
+    }
+
+    /// Report the suppressions of active rules that suppressed no finding, as Informational findings
+    pub fn unused_findings<F>(&self, file_path: &str, source_code: &str, is_active: F) -> Vec<Finding>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let mut findings = Vec::new();
+        for suppression in &self.suppressions {
+            for (rule, used) in suppression.rules.iter().zip(&suppression.used) {
+                if *used || !(rule == "*" || is_active(rule)) {
+                    continue;
+                }
+                let mut metadata = Metadata::new();
+                metadata.insert("rule".to_string(), MetadataValue::Text(rule.clone()));
+                findings.push(Finding {
+                    description: format!("Unused suppression for '{rule}': no finding to suppress"),
+                    severity: Severity::Informational,
+                    confidence: Confidence::High,
+                    location: Location::new_precise(file_path.to_string(), suppression.line, None, None, None),
+                    code_snippet: source_code
+                        .lines()
+                        .nth(suppression.line - 1)
+                        .map(|line| line.trim().to_string()),
+                    recommendations: vec!["Remove the suppression, the finding it was added for no longer occurs".to_string()],
+                    fixes: Vec::new(),
+                    metadata,
+                    rules: vec![RuleInfo {
+                        id: UNUSED_SUPPRESSION_ID.to_string(),
+                        title: "Unused Suppression".to_string(),
+                        ..Default::default()
+                    }],
+                    fingerprint: None,
+                });
+            }
+        }
+        findings
+    }
+}
+
+/// Helper function to collect the `//` comments of the source code and the lines containing code
+///
+/// String, raw string and char literals are skipped, so `//` inside them is not a comment.
+/// Block comments are skipped too (suppressions are line comments).
+fn scan_comments(source_code: &str) -> (Vec<Comment>, Vec<usize>) {
+    let chars: Vec<char> = source_code.chars().collect();
+    let mut comments = Vec::new();
+    let mut code_lines = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    fn mark_code(line: usize, code_lines: &mut Vec<usize>) {
+        if code_lines.last() != Some(&line) {
+            code_lines.push(line);
+        }
+    }
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            '\n' => {
+                line += 1;
+                i += 1;
+            }
+            '/' if next == Some('/') => {
+                let start = i + 2;
+                let end = chars[start..].iter().position(|c| *c == '\n').map_or(chars.len(), |p| start + p);
+                let text: String = chars[start..end].iter().collect();
+                // Doc comments are documentation, not directives
+                if !text.starts_with('/') && !text.starts_with('!') {
+                    comments.push(Comment {
+                        line,
+                        trailing: code_lines.last() == Some(&line),
+                        text,
+                    });
+                }
+                i = end;
+            }
+            '/' if next == Some('*') => {
+                let mut depth = 0;
+                while i < chars.len() {
+                    match (chars[i], chars.get(i + 1).copied()) {
+                        ('/', Some('*')) => {
+                            depth += 1;
+                            i += 2;
+                        }
+                        ('*', Some('/')) => {
+                            depth -= 1;
+                            i += 2;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        ('\n', _) => {
+                            line += 1;
+                            i += 1;
+                        }
+                        _ => i += 1,
+                    }
+                }
+            }
+            '"' => {
+                mark_code(line, &mut code_lines);
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    // Escaped characters include line continuations
+                    if chars[i] == '\\' {
+                        i += 1;
+                    }
+                    if chars.get(i) == Some(&'\n') {
+                        line += 1;
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            'r' if is_raw_string_start(&chars, i) => {
+                mark_code(line, &mut code_lines);
+                let hashes = chars[i + 1..].iter().take_while(|c| **c == '#').count();
+                i += hashes + 2;
+                while i < chars.len() {
+                    if chars[i] == '"' && chars[i + 1..].iter().take(hashes).filter(|c| **c == '#').count() == hashes {
+                        i += hashes + 1;
+                        break;
+                    }
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    i += 1;
+                }
+            }
+            '\'' => {
+                mark_code(line, &mut code_lines);
+                // Char literal ('a', '\n', '\u{..}'), otherwise a lifetime
+                if next == Some('\\') {
+                    i += 2;
+                    while i < chars.len() && chars[i] != '\'' && chars[i] != '\n' {
+                        i += 1;
+                    }
+                    i += 1;
+                } else if chars.get(i + 2) == Some(&'\'') {
+                    i += 3;
+                } else {
+                    i += 1;
+                }
+            }
+            c if c.is_whitespace() => i += 1,
+            _ => {
+                mark_code(line, &mut code_lines);
+                i += 1;
+            }
+        }
+    }
+
+    (comments, code_lines)
+}
+
+/// Helper function to check if a raw string literal (`r"..."`, `r#"..."#`, `br"..."`) starts at `i`
+fn is_raw_string_start(chars: &[char], i: usize) -> bool {
+    let previous = i.checked_sub(1).map(|p| chars[p]);
+    let starts_token = match previous {
+        Some('b') => i < 2 || !is_ident_char(chars[i - 2]),
+        Some(c) => !is_ident_char(c),
+        None => true,
+    };
+    let hashes = chars[i + 1..].iter().take_while(|c| **c == '#').count();
+    starts_token && chars.get(i + 1 + hashes) == Some(&'"')
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
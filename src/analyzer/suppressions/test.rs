@@ -0,0 +1,98 @@
+use crate::analyzer::suppressions::{Suppressions, scan_comments};
+use crate::analyzer::testing::FindingBuilder;
+use crate::analyzer::{Finding, Severity};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+pub fn withdraw(amount: u64, shares: u64) -> u64 {
+    amount / shares // analyzer:ignore[solana-division-by-zero] reason="shares checked by the caller"
+}
+
+// analyzer:ignore[owner-check, duplicate-mutable-accounts]
+
+#[derive(Accounts)]
+pub struct Withdraw {}
+
+// analyzer:ignore-start[*] reason="vendored code"
+fn vendored() {}
+// analyzer:ignore-end
+
+const URL: &str = "https://example.com // analyzer:ignore[owner-check]";
+/// analyzer:ignore[owner-check] in documentation
+"#;
+
+    fn finding(line: usize) -> Finding {
+        FindingBuilder::new(format!("Finding at line {line}")).at("lib.rs", line).build()
+    }
+
+    #[test]
+    fn test_suppression_scopes() {
+        let suppressions = Suppressions::parse(SOURCE, "lib.rs");
+        let scopes: Vec<_> = suppressions
+            .suppressions()
+            .iter()
+            .map(|suppression| (suppression.line, suppression.start_line, suppression.end_line))
+            .collect();
+        // Trailing comment, comment before the next line of code, block
+        assert_eq!(scopes, [(3, 3, 3), (6, 8, 8), (11, 11, 13)]);
+        assert_eq!(suppressions.suppressions()[0].reason.as_deref(), Some("shares checked by the caller"));
+        assert_eq!(suppressions.suppressions()[1].rules, ["owner-check", "duplicate-mutable-accounts"]);
+    }
+
+    #[test]
+    fn test_apply_and_unused_suppressions() {
+        let mut suppressions = Suppressions::parse(SOURCE, "lib.rs");
+
+        let kept = suppressions.apply("solana-division-by-zero", vec![finding(3), finding(2)]);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].location.line, 2);
+        // Suppressions only apply to their rules
+        assert_eq!(suppressions.apply("owner-check", vec![finding(3)]).len(), 1);
+        assert!(suppressions.apply("owner-check", vec![finding(8)]).is_empty());
+        assert!(suppressions.apply("solana-unsafe-code", vec![finding(12)]).is_empty());
+        assert_eq!(suppressions.suppressed_count(), 3);
+        let suppressed: Vec<(&str, usize, Option<&str>)> = suppressions
+            .suppressed()
+            .iter()
+            .map(|suppressed| (suppressed.rule_id.as_str(), suppressed.finding.location.line, suppressed.reason.as_deref()))
+            .collect();
+        assert_eq!(
+            suppressed,
+            [
+                ("solana-division-by-zero", 3, Some("shares checked by the caller")),
+                ("owner-check", 8, None),
+                ("solana-unsafe-code", 12, Some("vendored code")),
+            ]
+        );
+
+        // Unused suppressions are reported for active rules only
+        let unused = suppressions.unused_findings("lib.rs", SOURCE, |rule_id| rule_id == "duplicate-mutable-accounts");
+        assert_eq!(unused.len(), 1);
+        assert_eq!(unused[0].severity, Severity::Informational);
+        assert_eq!(unused[0].location.line, 6);
+        assert!(suppressions.unused_findings("lib.rs", SOURCE, |_| false).is_empty());
+    }
+
+    #[test]
+    fn test_malformed_suppressions_are_ignored() {
+        let source = "// analyzer:ignore\nfn a() {}\n// analyzer:ignore[Bad Id]\nfn b() {}\n// analyzer:ignore-end\n";
+        assert!(Suppressions::parse(source, "lib.rs").is_empty());
+
+        // Unclosed blocks apply until the end of the file
+        let source = "// analyzer:ignore-start[owner-check]\nfn a() {}\nfn b() {}\n";
+        let suppressions = Suppressions::parse(source, "lib.rs");
+        assert_eq!(suppressions.suppressions()[0].end_line, 3);
+    }
+
+    #[test]
+    fn test_comments_skip_literals() {
+        let source = "let a = r#\"// not a comment\"#; // comment\nlet c = '/'; let l: &'a str = \"\\\"//\"; // other\n";
+        let (comments, code_lines) = scan_comments(source);
+        let texts: Vec<_> = comments.iter().map(|comment| (comment.line, comment.trailing, comment.text.trim())).collect();
+        assert_eq!(texts, [(1, true, "comment"), (2, true, "other")]);
+        assert_eq!(code_lines, [1, 2]);
+    }
+}
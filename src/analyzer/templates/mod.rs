@@ -0,0 +1,380 @@
+use log::debug;
+use regex::Regex;
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::analyzer::dsl::query::AstNode;
+use crate::analyzer::dsl::{AstQuery, RuleBuildError, RuleBuilder};
+use crate::analyzer::vfs::FileSystem;
+use crate::analyzer::{Category, Confidence, Rule, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Error returned when a rule template cannot be loaded
+#[derive(Debug)]
+pub enum TemplateError {
+    /// The template file cannot be read
+    Io { path: String, error: std::io::Error },
+    /// The template is not valid YAML or does not follow the schema
+    Parse { path: String, error: serde_yaml::Error },
+    /// A field of the template has an invalid value
+    InvalidField { id: String, field: &'static str, reason: String },
+    /// The rule defined by the template is invalid
+    Build(RuleBuildError),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Io { path, error } => write!(f, "cannot read template {path}: {error}"),
+            TemplateError::Parse { path, error } => write!(f, "invalid template {path}: {error}"),
+            TemplateError::InvalidField { id, field, reason } => {
+                write!(f, "invalid field '{field}' in template '{id}': {reason}")
+            }
+            TemplateError::Build(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl From<RuleBuildError> for TemplateError {
+    fn from(error: RuleBuildError) -> Self {
+        TemplateError::Build(error)
+    }
+}
+
+/// Rule defined in a YAML template
+///
+/// ```yaml
+/// id: instruction-without-access-control
+/// title: Instruction Without Access Control
+/// description: Instruction handlers should check who is calling them
+/// severity: medium            # high, medium, low, informational
+/// confidence: low             # optional: high (default), medium, low
+/// tags: [access-control]
+/// packs: [anchor-core]        # optional: packs selectable with --profile
+/// category: access-control    # optional: Solana vulnerability class (see `Category`)
+/// cwe: [284]                  # optional: CWE IDs
+/// anchor_versions: ">=0.28"   # optional: anchor-lang versions the rule applies to
+/// recommendations:
+///   - Add an #[access_control] attribute or validate the signer in the handler
+/// references: []
+/// rationale: Anyone can call the handler   # optional: why the issue matters (shown by explain)
+/// examples:                   # optional: code reported by the rule and fixed code (shown by explain)
+///   vulnerable:
+///     - |
+///       pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> { ... }
+///   safe:
+///     - |
+///       #[access_control(is_admin(&ctx))]
+///       pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> { ... }
+/// message: "{title} in '{function}'"   # optional, same placeholders as RuleBuilder::message
+/// match:
+///   kind: function            # function, struct or accounts (structs deriving Accounts)
+///   name: "^(withdraw|close)" # optional regex on the item name
+///   public: true              # optional visibility constraint
+///   attributes:
+///     present: []             # attributes the item must have (e.g. access_control)
+///     absent: [access_control]
+///   calls: [transfer]         # functions the item must call (functions only)
+///   not_calls: [check_signer] # functions the item must not call (functions only)
+///   returns: "^Result"        # optional regex on the return type, `()` if none (functions only)
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleTemplate {
+    /// Unique ID of the rule
+    pub id: String,
+    /// Title of the rule
+    pub title: String,
+    /// Description of the rule
+    pub description: String,
+    /// Severity of the findings
+    pub severity: String,
+    /// Confidence of the findings
+    #[serde(default)]
+    pub confidence: Option<String>,
+    /// Tags to classify the rule
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Packs the rule belongs to
+    #[serde(default)]
+    pub packs: Vec<String>,
+    /// Solana vulnerability class of the rule
+    #[serde(default)]
+    pub category: Option<String>,
+    /// CWE IDs of the weakness detected by the rule
+    #[serde(default)]
+    pub cwe: Vec<u32>,
+    /// Versions of anchor-lang the rule applies to (a Cargo version requirement)
+    #[serde(default)]
+    pub anchor_versions: Option<String>,
+    /// Recommendations for fixing the issue
+    #[serde(default)]
+    pub recommendations: Vec<String>,
+    /// References to documentation or additional resources
+    #[serde(default)]
+    pub references: Vec<String>,
+    /// Why the issue matters
+    #[serde(default)]
+    pub rationale: Option<String>,
+    /// Code examples of the rule
+    #[serde(default)]
+    pub examples: TemplateExamples,
+    /// Template for the finding descriptions
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Items matched by the rule
+    #[serde(rename = "match")]
+    pub matcher: TemplateMatcher,
+}
+
+/// Code examples of a template rule
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateExamples {
+    /// Code reported by the rule
+    #[serde(default)]
+    pub vulnerable: Vec<String>,
+    /// Code fixed as the recommendations suggest
+    #[serde(default)]
+    pub safe: Vec<String>,
+}
+
+/// Kind of item matched by a template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeKind {
+    /// Functions, including impl methods and functions of inline modules
+    Function,
+    /// Top-level structs
+    Struct,
+    /// Top-level structs deriving Anchor's `Accounts`
+    Accounts,
+}
+
+/// Declarative constraints on the matched items
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateMatcher {
+    /// Kind of item
+    pub kind: NodeKind,
+    /// Regex the item name must match
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Required visibility (`true` for `pub` items only, `false` for private items only)
+    #[serde(default)]
+    pub public: Option<bool>,
+    /// Attribute constraints
+    #[serde(default)]
+    pub attributes: AttributeMatcher,
+    /// Functions the item must call
+    #[serde(default)]
+    pub calls: Vec<String>,
+    /// Functions the item must not call
+    #[serde(default)]
+    pub not_calls: Vec<String>,
+    /// Regex the return type must match
+    #[serde(default)]
+    pub returns: Option<String>,
+}
+
+/// Attributes an item must have or not have, by name (e.g. `access_control`, `account`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AttributeMatcher {
+    /// Attributes the item must have
+    #[serde(default)]
+    pub present: Vec<String>,
+    /// Attributes the item must not have
+    #[serde(default)]
+    pub absent: Vec<String>,
+}
+
+/// Matcher with its regexes compiled, shared by the rule's query
+struct CompiledMatcher {
+    kind: NodeKind,
+    name: Option<Regex>,
+    public: Option<bool>,
+    attributes: AttributeMatcher,
+    calls: Vec<String>,
+    not_calls: Vec<String>,
+    returns: Option<Regex>,
+}
+
+impl CompiledMatcher {
+    /// Check if a node found for the matcher's kind satisfies the other constraints
+    fn matches(&self, node: &AstNode<'_>) -> bool {
+        if let Some(name) = &self.name
+            && !node.name.as_ref().is_some_and(|node_name| name.is_match(node_name))
+        {
+            return false;
+        }
+        if let Some(public) = self.public
+            && node.is_public() != public
+        {
+            return false;
+        }
+
+        let has_attribute = |name: &String| {
+            node.attrs()
+                .iter()
+                .any(|attr| attr.path().segments.last().is_some_and(|segment| segment.ident == name))
+        };
+        if !self.attributes.present.iter().all(has_attribute) || self.attributes.absent.iter().any(has_attribute) {
+            return false;
+        }
+
+        if let Some(returns) = &self.returns
+            && !node.return_type().is_some_and(|return_type| returns.is_match(&return_type))
+        {
+            return false;
+        }
+
+        let calls = |function_name: &String| AstQuery::from_node(node).calls_to(function_name).exists();
+        self.calls.iter().all(calls) && !self.not_calls.iter().any(calls)
+    }
+}
+
+impl RuleTemplate {
+    /// Parse a template from YAML
+    pub fn from_yaml(yaml: &str, path: &str) -> Result<Self, TemplateError> {
+        serde_yaml::from_str(yaml).map_err(|error| TemplateError::Parse {
+            path: path.to_string(),
+            error,
+        })
+    }
+
+    /// Load a template from a YAML file
+    #[cfg(feature = "native")]
+    pub fn from_file(path: &Path) -> Result<Self, TemplateError> {
+        Self::load(&crate::analyzer::vfs::OsFileSystem, path)
+    }
+
+    /// Load a template from a YAML file of a file system
+    pub fn load(fs: &dyn FileSystem, path: &Path) -> Result<Self, TemplateError> {
+        let path_str = path.display().to_string();
+        let yaml = fs.read_to_string(path).map_err(|error| TemplateError::Io {
+            path: path_str.clone(),
+            error,
+        })?;
+        Self::from_yaml(&yaml, &path_str)
+    }
+
+    /// Helper function to report an invalid field of the template
+    fn invalid(&self, field: &'static str, reason: impl Into<String>) -> TemplateError {
+        TemplateError::InvalidField {
+            id: self.id.clone(),
+            field,
+            reason: reason.into(),
+        }
+    }
+
+    /// Helper function to compile an optional regex field
+    fn regex(&self, field: &'static str, pattern: Option<&String>) -> Result<Option<Regex>, TemplateError> {
+        pattern
+            .map(|pattern| Regex::new(pattern).map_err(|e| self.invalid(field, e.to_string())))
+            .transpose()
+    }
+
+    /// Build the rule defined by the template
+    pub fn build(self) -> Result<Arc<dyn Rule>, TemplateError> {
+        debug!("Building rule from template: {}", self.id);
+
+        let severity: Severity = self.severity.parse().map_err(|e: String| self.invalid("severity", e))?;
+        let confidence: Confidence = match &self.confidence {
+            Some(confidence) => confidence.parse().map_err(|e: String| self.invalid("confidence", e))?,
+            None => Confidence::High,
+        };
+        let category: Option<Category> = match &self.category {
+            Some(category) => Some(category.parse().map_err(|e: String| self.invalid("category", e))?),
+            None => None,
+        };
+
+        let matcher = &self.matcher;
+        if matcher.kind != NodeKind::Function {
+            for (field, used) in [
+                ("match.calls", !matcher.calls.is_empty()),
+                ("match.not_calls", !matcher.not_calls.is_empty()),
+                ("match.returns", matcher.returns.is_some()),
+            ] {
+                if used {
+                    return Err(self.invalid(field, "only applies to functions"));
+                }
+            }
+        }
+
+        let compiled = Arc::new(CompiledMatcher {
+            kind: matcher.kind,
+            name: self.regex("match.name", matcher.name.as_ref())?,
+            public: matcher.public,
+            attributes: matcher.attributes.clone(),
+            calls: matcher.calls.clone(),
+            not_calls: matcher.not_calls.clone(),
+            returns: self.regex("match.returns", matcher.returns.as_ref())?,
+        });
+
+        let mut builder = RuleBuilder::new()
+            .id(&self.id)
+            .title(&self.title)
+            .description(&self.description)
+            .severity(severity)
+            .confidence(confidence)
+            .tags(self.tags.iter().map(String::as_str).collect())
+            .packs(self.packs.iter().map(String::as_str).collect())
+            .recommendations(self.recommendations.iter().map(String::as_str).collect())
+            .references(self.references.iter().map(String::as_str).collect())
+            .dsl_query(move |ast, _file_path, _span_extractor| {
+                let query = AstQuery::new(ast);
+                let candidates = match compiled.kind {
+                    NodeKind::Function => query.functions(),
+                    NodeKind::Struct => query.structs(),
+                    NodeKind::Accounts => query.structs().derives_accounts(),
+                };
+                candidates.filter(|node| compiled.matches(node))
+            });
+
+        if let Some(message) = &self.message {
+            builder = builder.message(message);
+        }
+        if let Some(rationale) = &self.rationale {
+            builder = builder.rationale(rationale);
+        }
+        for code in &self.examples.vulnerable {
+            builder = builder.vulnerable_example(code);
+        }
+        for code in &self.examples.safe {
+            builder = builder.safe_example(code);
+        }
+        if let Some(category) = category {
+            builder = builder.category(category);
+        }
+        for cwe in &self.cwe {
+            builder = builder.cwe(*cwe);
+        }
+        if let Some(versions) = &self.anchor_versions {
+            builder = builder.anchor_versions(versions);
+        }
+
+        Ok(builder.build()?)
+    }
+}
+
+/// Find the template files with the given extensions (e.g. `yaml`, `yml`) under a directory, sorted by path
+pub fn find_template_files(fs: &dyn FileSystem, dir: &Path, extensions: &[&str]) -> Vec<std::path::PathBuf> {
+    let mut paths: Vec<_> = fs
+        .files_under(dir)
+        .into_iter()
+        .filter(|path| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| extensions.contains(&extension))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
@@ -0,0 +1,129 @@
+use crate::analyzer::{Category, RuleExample, Severity};
+use crate::analyzer::dsl::RuleBuildError;
+use crate::analyzer::templates::{RuleTemplate, TemplateError};
+use crate::analyzer::vfs::OsFileSystem;
+use std::path::Path;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = r#"
+id: privileged-transfer
+title: Privileged Transfer
+description: Handlers moving funds without an authority check
+severity: high
+tags: [access-control]
+packs: [anchor-core, defi]
+category: access-control
+cwe: [862, 284]
+references:
+  - https://www.anchor-lang.com/docs/account-constraints
+rationale: Anyone can call the handler and move the funds
+examples:
+  vulnerable:
+    - |
+      pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+          transfer(ctx)
+      }
+match:
+  kind: function
+  name: "^withdraw"
+  public: true
+  attributes:
+    absent: [access_control]
+  calls: [transfer]
+  not_calls: [check_authority]
+  returns: "^Result<"
+"#;
+
+    const SOURCE: &str = r#"
+pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+    transfer(ctx)
+}
+
+pub fn withdraw_checked(ctx: Context<Withdraw>) -> Result<()> {
+    check_authority(&ctx)?;
+    transfer(ctx)
+}
+
+#[access_control(admin(&ctx))]
+pub fn withdraw_admin(ctx: Context<Withdraw>) -> Result<()> {
+    transfer(ctx)
+}
+
+pub fn withdraw_all(ctx: Context<Withdraw>) {
+    transfer(ctx)
+}
+"#;
+
+    #[test]
+    fn test_template_matchers() {
+        let rule = RuleTemplate::from_yaml(TEMPLATE, "privileged-transfer.yaml").unwrap().build().unwrap();
+        assert_eq!(rule.id(), "privileged-transfer");
+        assert_eq!(rule.severity(), Severity::High);
+        assert_eq!(rule.packs(), ["anchor-core", "defi"]);
+        assert_eq!(rule.references(), ["https://www.anchor-lang.com/docs/account-constraints"]);
+        assert_eq!(rule.category(), Some(Category::AccessControl));
+        assert_eq!(rule.cwe(), [862, 284]);
+        assert_eq!(rule.rationale().as_deref(), Some("Anyone can call the handler and move the funds"));
+        assert_eq!(
+            rule.examples(),
+            [RuleExample::vulnerable("pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {\n    transfer(ctx)\n}")]
+        );
+
+        let ast = syn::parse_file(SOURCE).unwrap();
+        let findings = rule.execute_with_source(&ast, "src/lib.rs", SOURCE).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].location.line, 2);
+    }
+
+    #[test]
+    fn test_template_errors() {
+        let unknown_field = TEMPLATE.replace("  public: true", "  visibility: pub");
+        assert!(matches!(
+            RuleTemplate::from_yaml(&unknown_field, "t.yaml"),
+            Err(TemplateError::Parse { .. })
+        ));
+
+        let bad_regex = TEMPLATE.replace("\"^withdraw\"", "\"(\"");
+        assert!(matches!(
+            RuleTemplate::from_yaml(&bad_regex, "t.yaml").unwrap().build(),
+            Err(TemplateError::InvalidField { field: "match.name", .. })
+        ));
+
+        let struct_calls = TEMPLATE.replace("kind: function", "kind: struct");
+        assert!(matches!(
+            RuleTemplate::from_yaml(&struct_calls, "t.yaml").unwrap().build(),
+            Err(TemplateError::InvalidField { field: "match.calls", .. })
+        ));
+
+        let bad_pack = TEMPLATE.replace("packs: [anchor-core, defi]", "packs: [DeFi]");
+        assert!(matches!(
+            RuleTemplate::from_yaml(&bad_pack, "t.yaml").unwrap().build(),
+            Err(TemplateError::Build(RuleBuildError::InvalidPack { .. }))
+        ));
+
+        let bad_severity = TEMPLATE.replace("severity: high", "severity: critical");
+        assert!(matches!(
+            RuleTemplate::from_yaml(&bad_severity, "t.yaml").unwrap().build(),
+            Err(TemplateError::InvalidField { field: "severity", .. })
+        ));
+
+        let bad_category = TEMPLATE.replace("category: access-control", "category: reentrancy");
+        assert!(matches!(
+            RuleTemplate::from_yaml(&bad_category, "t.yaml").unwrap().build(),
+            Err(TemplateError::InvalidField { field: "category", .. })
+        ));
+    }
+
+    #[test]
+    fn test_example_templates_build() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates");
+        let templates = crate::analyzer::templates::find_template_files(&OsFileSystem, &dir, &["yaml", "yml"]);
+        assert!(!templates.is_empty());
+        for path in templates {
+            assert!(RuleTemplate::from_file(&path).and_then(RuleTemplate::build).is_ok(), "{}", path.display());
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use crate::analyzer::dsl::ProjectFile;
+use crate::analyzer::scope::TestCode;
+#[cfg(test)]
+use crate::analyzer::{Confidence, Fix, Location, RuleInfo, dsl::query::MetadataValue};
+use crate::analyzer::{Finding, RuleEngine, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Path under which test sources are analyzed (outside `tests/`, whose files are test code)
+const DEFAULT_TEST_PATH: &str = "programs/fixture/src/lib.rs";
+
+/// Finding a test case expects a rule to report
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ExpectedFinding {
+    /// Line of the finding (1-indexed)
+    pub line: usize,
+    /// Severity of the finding (any severity if not set)
+    #[serde(default)]
+    pub severity: Option<String>,
+}
+
+impl ExpectedFinding {
+    /// Check if a finding matches the expectation
+    fn matches(&self, finding: &Finding) -> bool {
+        finding.location.line == self.line
+            && self
+                .severity
+                .as_ref()
+                .is_none_or(|severity| severity.eq_ignore_ascii_case(&format!("{:?}", finding.severity)))
+    }
+}
+
+/// Source code analyzed with the built-in rules and the findings expected from them
+///
+/// Only the rules named by the expectations are checked, and each of them must report
+/// exactly the expected findings:
+///
+/// ```ignore
+/// RuleTestCase::from_source("pub fn helper(x: u64) -> u64 {\n    unsafe { x }\n}")
+///     .expect_finding("solana-unsafe-code", 1)
+///     .expect_no_findings("solana-division-by-zero")
+///     .assert();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RuleTestCase {
+    /// Source code to analyze
+    source: String,
+    /// Path the source is analyzed under
+    file_path: String,
+    /// Expected findings, by rule ID
+    expected: BTreeMap<String, Vec<ExpectedFinding>>,
+}
+
+impl RuleTestCase {
+    /// Creates a test case from source code
+    pub fn from_source(source: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            file_path: DEFAULT_TEST_PATH.to_string(),
+            expected: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the path the source is analyzed under (e.g. to test rules that skip test files)
+    pub fn with_path(mut self, file_path: &str) -> Self {
+        self.file_path = file_path.to_string();
+        self
+    }
+
+    /// Expects the rule to report a finding on the given line
+    pub fn expect_finding(self, rule_id: &str, line: usize) -> Self {
+        self.expect(rule_id, ExpectedFinding { line, severity: None })
+    }
+
+    /// Expects the rule to report a finding of the given severity on the given line
+    pub fn expect_finding_with_severity(self, rule_id: &str, line: usize, severity: Severity) -> Self {
+        self.expect(
+            rule_id,
+            ExpectedFinding {
+                line,
+                severity: Some(format!("{severity:?}")),
+            },
+        )
+    }
+
+    /// Expects the rule to report the given finding
+    pub fn expect(mut self, rule_id: &str, finding: ExpectedFinding) -> Self {
+        self.expected.entry(rule_id.to_string()).or_default().push(finding);
+        self
+    }
+
+    /// Expects the rule to report no findings
+    pub fn expect_no_findings(mut self, rule_id: &str) -> Self {
+        self.expected.entry(rule_id.to_string()).or_default();
+        self
+    }
+
+    /// Run the built-in rules on the source and return the findings of each rule, by rule ID
+    pub fn run(&self) -> Result<BTreeMap<String, Vec<Finding>>, String> {
+        let ast = syn::parse_file(&self.source).map_err(|e| format!("{}: failed to parse source: {e}", self.file_path))?;
+
+        let mut engine = RuleEngine::default();
+        engine
+            .load_builtin_rules()
+            .map_err(|e| format!("failed to load built-in rules: {e}"))?;
+
+        let files = [ProjectFile::new(&self.file_path, &ast, &self.source)];
+        let mut findings: BTreeMap<String, Vec<Finding>> = BTreeMap::new();
+        for (rule_id, rule_findings) in engine
+            .execute_rules_per_rule(&ast, &self.file_path, &self.source)
+            .into_iter()
+            .chain(engine.execute_project_rules_per_rule(&files))
+        {
+            findings.entry(rule_id).or_default().extend(rule_findings);
+        }
+
+        // Test code is skipped like in an analysis (see `scope::TestCode`)
+        let mut test_code = TestCode::default();
+        test_code.add_file(&self.file_path, &ast, false, None);
+        for rule_findings in findings.values_mut() {
+            rule_findings.retain(|finding| !test_code.contains(&finding.location));
+        }
+        Ok(findings)
+    }
+
+    /// Check that every rule named by the expectations reports exactly the expected findings
+    pub fn check(&self) -> Result<(), String> {
+        let findings = self.run()?;
+        let mut errors = Vec::new();
+
+        for (rule_id, expected) in &self.expected {
+            let Some(rule_findings) = findings.get(rule_id) else {
+                errors.push(format!("unknown rule '{rule_id}'"));
+                continue;
+            };
+
+            let mut unmatched: Vec<&Finding> = rule_findings.iter().collect();
+            for expectation in expected {
+                match unmatched.iter().position(|finding| expectation.matches(finding)) {
+                    Some(index) => {
+                        unmatched.remove(index);
+                    }
+                    None => errors.push(format!(
+                        "{rule_id}: expected a finding on line {}{}",
+                        expectation.line,
+                        expectation
+                            .severity
+                            .as_ref()
+                            .map(|severity| format!(" with severity {severity}"))
+                            .unwrap_or_default(),
+                    )),
+                }
+            }
+            for finding in unmatched {
+                errors.push(format!(
+                    "{rule_id}: unexpected {:?} finding on line {}: {}",
+                    finding.severity, finding.location.line, finding.description
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("{}:\n  {}", self.file_path, errors.join("\n  ")))
+        }
+    }
+
+    /// Check the expectations and panic with the mismatches if they are not met
+    pub fn assert(&self) {
+        if let Err(e) = self.check() {
+            panic!("{e}");
+        }
+    }
+}
+
+/// Run the fixtures of a rule
+///
+/// A fixture directory is named after the rule ID and contains:
+/// - `ok/*.rs` - sources on which the rule must report nothing
+/// - `bad/*.rs` - sources on which the rule must report the findings listed in `bad/expected.json`
+///   (`{ "file.rs": [{ "line": 7, "severity": "High" }] }`)
+///
+/// Returns the number of fixture files checked.
+pub fn run_rule_fixtures(rule_dir: &Path) -> Result<usize, String> {
+    let rule_id = rule_dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("invalid fixture directory: {}", rule_dir.display()))?;
+
+    let manifest_path = rule_dir.join("bad").join("expected.json");
+    let manifest: BTreeMap<String, Vec<ExpectedFinding>> = match fs::read_to_string(&manifest_path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| format!("{}: {e}", manifest_path.display()))?,
+        Err(_) => BTreeMap::new(),
+    };
+
+    let mut errors = Vec::new();
+    let mut checked = 0;
+    let mut listed: BTreeSet<&str> = manifest.keys().map(String::as_str).collect();
+
+    for (kind, fixtures) in [("ok", fixture_files(&rule_dir.join("ok"))?), ("bad", fixture_files(&rule_dir.join("bad"))?)] {
+        for (file_name, source) in fixtures {
+            let mut test_case = RuleTestCase::from_source(&source)
+                .with_path(&format!("programs/fixture/src/{rule_id}/{kind}/{file_name}"))
+                .expect_no_findings(rule_id);
+
+            if kind == "bad" {
+                let Some(expected) = manifest.get(&file_name) else {
+                    errors.push(format!("{rule_id}/bad/{file_name} is not listed in {}", manifest_path.display()));
+                    continue;
+                };
+                listed.remove(file_name.as_str());
+                for finding in expected {
+                    test_case = test_case.expect(rule_id, finding.clone());
+                }
+            }
+
+            checked += 1;
+            if let Err(e) = test_case.check() {
+                errors.push(e);
+            }
+        }
+    }
+
+    errors.extend(listed.iter().map(|file_name| format!("{rule_id}/bad/{file_name} is listed but does not exist")));
+
+    if errors.is_empty() {
+        Ok(checked)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// Helper function to read the `.rs` fixtures of a directory, sorted by name
+fn fixture_files(dir: &Path) -> Result<Vec<(String, String)>, String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(Vec::new());
+    };
+
+    let mut fixtures = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|extension| extension == "rs") {
+            let source = fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+            fixtures.push((entry.file_name().to_string_lossy().to_string(), source));
+        }
+    }
+    fixtures.sort();
+    Ok(fixtures)
+}
+
+/// Builder of the findings of the unit tests: a medium severity, high confidence finding on
+/// line 1 of `src/lib.rs`, without rule, snippet or fingerprint, until they are set
+#[cfg(test)]
+pub(crate) struct FindingBuilder {
+    finding: Finding,
+}
+
+#[cfg(test)]
+impl FindingBuilder {
+    pub(crate) fn new(description: impl Into<String>) -> Self {
+        Self {
+            finding: Finding {
+                description: description.into(),
+                severity: Severity::Medium,
+                confidence: Confidence::High,
+                location: Location::new_precise("src/lib.rs".to_string(), 1, None, None, None),
+                code_snippet: None,
+                recommendations: Vec::new(),
+                fixes: Vec::new(),
+                metadata: Default::default(),
+                rules: Vec::new(),
+                fingerprint: None,
+            },
+        }
+    }
+
+    pub(crate) fn severity(mut self, severity: Severity) -> Self {
+        self.finding.severity = severity;
+        self
+    }
+
+    pub(crate) fn confidence(mut self, confidence: Confidence) -> Self {
+        self.finding.confidence = confidence;
+        self
+    }
+
+    pub(crate) fn location(mut self, location: Location) -> Self {
+        self.finding.location = location;
+        self
+    }
+
+    /// Line of a file, without columns
+    pub(crate) fn at(self, file: &str, line: usize) -> Self {
+        self.location(Location::new_precise(file.to_string(), line, None, None, None))
+    }
+
+    /// Columns of a line of a file
+    pub(crate) fn span(self, file: &str, line: usize, column: usize, end_column: usize) -> Self {
+        self.location(Location::new_precise(file.to_string(), line, Some(column), Some(line), Some(end_column)))
+    }
+
+    pub(crate) fn snippet(mut self, snippet: &str) -> Self {
+        self.finding.code_snippet = Some(snippet.to_string());
+        self
+    }
+
+    pub(crate) fn recommendations(mut self, recommendations: &[&str]) -> Self {
+        self.finding.recommendations = recommendations.iter().map(|recommendation| recommendation.to_string()).collect();
+        self
+    }
+
+    pub(crate) fn fixes(mut self, fixes: Vec<Fix>) -> Self {
+        self.finding.fixes = fixes;
+        self
+    }
+
+    pub(crate) fn metadata(mut self, key: &str, value: MetadataValue) -> Self {
+        self.finding.metadata.insert(key.to_string(), value);
+        self
+    }
+
+    /// Rule reporting the finding
+    pub(crate) fn rule(mut self, rule: RuleInfo) -> Self {
+        self.finding.rules = vec![rule];
+        self
+    }
+
+    /// Rule reporting the finding, with only an ID
+    pub(crate) fn rule_id(self, id: &str) -> Self {
+        self.rule(RuleInfo { id: id.to_string(), ..Default::default() })
+    }
+
+    pub(crate) fn fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
+        self.finding.fingerprint = Some(fingerprint.into());
+        self
+    }
+
+    pub(crate) fn build(self) -> Finding {
+        self.finding
+    }
+}
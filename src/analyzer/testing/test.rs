@@ -0,0 +1,58 @@
+use crate::analyzer::Severity;
+use crate::analyzer::testing::{RuleTestCase, run_rule_fixtures};
+use std::path::Path;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_test_case_expectations() {
+        let test_case = RuleTestCase::from_source("pub fn helper(x: u64) -> u64 {\n    unsafe { x }\n}\n");
+        test_case
+            .clone()
+            .expect_finding_with_severity("solana-unsafe-code", 1, Severity::High)
+            .expect_no_findings("solana-division-by-zero")
+            .assert();
+
+        assert!(test_case.clone().expect_no_findings("solana-unsafe-code").check().is_err());
+        assert!(test_case.clone().expect_finding("solana-unsafe-code", 2).check().is_err());
+        assert!(test_case.expect_no_findings("unknown-rule").check().is_err());
+
+        // Test code is skipped by the rule
+        RuleTestCase::from_source("pub fn helper(x: u64) -> u64 {\n    unsafe { x }\n}\n")
+            .with_path("programs/vault/tests/helpers.rs")
+            .expect_no_findings("solana-unsafe-code")
+            .assert();
+    }
+
+    #[test]
+    fn test_builtin_rule_fixtures() {
+        let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures");
+        let mut rule_dirs: Vec<_> = std::fs::read_dir(&fixtures_dir)
+            .expect("fixtures directory")
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        rule_dirs.sort();
+
+        // Every built-in rule has fixtures
+        let rule_ids: Vec<String> = RuleTestCase::from_source("").run().unwrap().into_keys().collect();
+        let fixture_ids: Vec<String> = rule_dirs
+            .iter()
+            .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+            .collect();
+        assert_eq!(fixture_ids, rule_ids);
+
+        let errors: Vec<String> = rule_dirs
+            .iter()
+            .filter_map(|rule_dir| match run_rule_fixtures(rule_dir) {
+                Ok(0) => Some(format!("{}: no fixtures", rule_dir.display())),
+                Ok(_) => None,
+                Err(e) => Some(e),
+            })
+            .collect();
+        assert!(errors.is_empty(), "fixture mismatches:\n{}", errors.join("\n"));
+    }
+}
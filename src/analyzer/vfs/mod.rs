@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::ops::Deref;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+#[cfg(test)]
+mod test;
+
+/// Files read by the analysis: the custom rules, the manifests of the analyzed crates and the
+/// IDL built for their programs
+///
+/// The analysis only reads files through this trait, so it runs where there is no file system
+/// (in a browser, with a [`MemoryFileSystem`]).
+pub trait FileSystem: fmt::Debug + Send + Sync {
+    /// Read the content of a file
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Check if a file exists at `path`
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Check if a directory exists at `path`
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// Paths of the files under a directory and its subdirectories, in no particular order
+    fn files_under(&self, dir: &Path) -> Vec<PathBuf>;
+
+    /// Read the content of a UTF-8 file
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// File system of the operating system
+#[cfg(feature = "native")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsFileSystem;
+
+#[cfg(feature = "native")]
+impl FileSystem for OsFileSystem {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn files_under(&self, dir: &Path) -> Vec<PathBuf> {
+        walkdir::WalkDir::new(dir)
+            .into_iter()
+            .flatten()
+            .map(walkdir::DirEntry::into_path)
+            .filter(|path| path.is_file())
+            .collect()
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+}
+
+/// File system held in memory, the contents of its files by path
+///
+/// Directories are the ancestors of the files, and `./` prefixes are ignored (`./src/lib.rs` is
+/// `src/lib.rs`).
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFileSystem {
+    files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl MemoryFileSystem {
+    /// Creates an empty file system
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file, replacing the file at the same path
+    pub fn insert(&mut self, path: impl AsRef<Path>, content: impl Into<Vec<u8>>) {
+        self.files.insert(normalize(path.as_ref()), content.into());
+    }
+
+    /// Add a file, replacing the file at the same path
+    pub fn with_file(mut self, path: impl AsRef<Path>, content: impl Into<Vec<u8>>) -> Self {
+        self.insert(path, content);
+        self
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files
+            .get(&normalize(path))
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} not found", path.display())))
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(&normalize(path))
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        let dir = normalize(path);
+        self.files.keys().any(|file| file != &dir && file.starts_with(&dir))
+    }
+
+    fn files_under(&self, dir: &Path) -> Vec<PathBuf> {
+        let dir = normalize(dir);
+        self.files.keys().filter(|file| *file != &dir && file.starts_with(&dir)).cloned().collect()
+    }
+}
+
+/// Helper function to drop the `.` components of a path
+fn normalize(path: &Path) -> PathBuf {
+    path.components().filter(|component| *component != Component::CurDir).collect()
+}
+
+/// File system of an analysis (see [`AnalysisOptions::file_system`](crate::analyzer::AnalysisOptions::file_system)),
+/// shared with the rule engine
+///
+/// The file system of the operating system by default, an empty [`MemoryFileSystem`] when built
+/// without the `native` feature.
+#[derive(Debug, Clone)]
+pub struct SharedFileSystem(Arc<dyn FileSystem>);
+
+impl SharedFileSystem {
+    pub fn new(file_system: impl FileSystem + 'static) -> Self {
+        Self(Arc::new(file_system))
+    }
+}
+
+impl Default for SharedFileSystem {
+    fn default() -> Self {
+        #[cfg(feature = "native")]
+        return Self::new(OsFileSystem);
+        #[cfg(not(feature = "native"))]
+        return Self::new(MemoryFileSystem::new());
+    }
+}
+
+impl Deref for SharedFileSystem {
+    type Target = dyn FileSystem;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
@@ -0,0 +1,30 @@
+use crate::analyzer::vfs::{FileSystem, MemoryFileSystem};
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_file_system() {
+        let fs = MemoryFileSystem::new()
+            .with_file("Cargo.toml", "[package]")
+            .with_file("./src/lib.rs", "pub mod state;")
+            .with_file("src/state/mod.rs", vec![0xff]);
+
+        assert_eq!(fs.read_to_string(Path::new("./Cargo.toml")).unwrap(), "[package]");
+        assert_eq!(fs.read_to_string(Path::new("src/lib.rs")).unwrap(), "pub mod state;");
+        assert_eq!(fs.read_to_string(Path::new("src/state/mod.rs")).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(fs.read(Path::new("src/main.rs")).unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+        assert!(fs.is_file(Path::new("src/lib.rs")));
+        assert!(!fs.is_file(Path::new("src")));
+        assert!(fs.is_dir(Path::new("src")) && fs.is_dir(Path::new("./src/state")));
+        assert!(!fs.is_dir(Path::new("src/lib.rs")) && !fs.is_dir(Path::new("tests")));
+
+        let mut files = fs.files_under(Path::new("src"));
+        files.sort();
+        assert_eq!(files, [PathBuf::from("src/lib.rs"), PathBuf::from("src/state/mod.rs")]);
+        assert_eq!(fs.files_under(Path::new(".")).len(), 3);
+    }
+}
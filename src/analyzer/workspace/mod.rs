@@ -0,0 +1,455 @@
+use log::{debug, warn};
+use semver::{Version, VersionReq};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+use crate::analyzer::advisories::{locked_packages, lowest_version};
+use crate::analyzer::dsl::query::{Metadata, MetadataValue};
+use crate::analyzer::scope::ANCHOR_CRATE;
+use crate::analyzer::{Category, Confidence, Finding, Location, RuleInfo, Severity};
+
+#[cfg(test)]
+mod test;
+
+/// Metadata key of the findings holding the name of the crate they were found in
+pub const PROGRAM_KEY: &str = "program";
+
+/// ID under which programs built without overflow checks are reported (can be ignored like a rule ID)
+pub const OVERFLOW_CHECKS_ID: &str = "overflow-checks-disabled";
+
+/// Dependencies making a crate a Solana program
+const PROGRAM_DEPENDENCIES: &[&str] = &[ANCHOR_CRATE, "solana-program", "pinocchio", "steel"];
+
+/// Error returned when the manifests of a workspace cannot be read
+#[derive(Debug)]
+pub enum WorkspaceError {
+    /// A manifest cannot be read
+    Io { path: PathBuf, error: std::io::Error },
+    /// A manifest is not valid TOML
+    Parse { path: PathBuf, error: String },
+}
+
+impl fmt::Display for WorkspaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkspaceError::Io { path, error } => write!(f, "cannot read {}: {error}", path.display()),
+            WorkspaceError::Parse { path, error } => write!(f, "invalid manifest {}: {error}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for WorkspaceError {}
+
+/// Facts of a crate read from its `Cargo.toml` (and from `Anchor.toml` for the program ID)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CrateManifest {
+    /// Package name
+    pub name: String,
+    /// Library name (`[lib] name`, or the package name with `_` instead of `-`)
+    pub lib_name: String,
+    /// Directory of the crate, containing its `Cargo.toml`
+    pub dir: PathBuf,
+    /// Program ID, from `Anchor.toml` or the `declare_id!` of `src/lib.rs`
+    pub program_id: Option<String>,
+    /// Features of the crate and the features they enable
+    pub features: BTreeMap<String, Vec<String>>,
+    /// Version requirements of the dependencies by package name (`*` without one, e.g. path dependencies)
+    pub dependencies: BTreeMap<String, String>,
+    /// Whether release builds keep the integer overflow checks (`[profile.release] overflow-checks`)
+    pub overflow_checks: bool,
+    /// Whether the library is built as a shared object (`crate-type = ["cdylib"]`), like on-chain programs
+    pub cdylib: bool,
+    /// Version of anchor-lang the crate builds with: the version locked in `Cargo.lock`, or else
+    /// the lowest version its requirement allows (`None` if it does not depend on anchor-lang or
+    /// the version cannot be told)
+    pub anchor_lang: Option<Version>,
+}
+
+impl CrateManifest {
+    /// Check if the crate is a Solana program: a `cdylib` or a dependent of a program framework
+    pub fn is_program(&self) -> bool {
+        self.cdylib || PROGRAM_DEPENDENCIES.iter().any(|dependency| self.dependencies.contains_key(*dependency))
+    }
+
+    /// Returns the version requirement of a dependency
+    pub fn dependency_version(&self, name: &str) -> Option<&str> {
+        self.dependencies.get(name).map(String::as_str)
+    }
+
+    /// Returns the version requirement of anchor-lang, if the crate depends on it
+    pub fn anchor_version(&self) -> Option<&str> {
+        self.dependency_version(ANCHOR_CRATE)
+    }
+
+    /// Returns the features enabled by default: `default` and the features it enables in turn
+    pub fn default_features(&self) -> BTreeSet<&str> {
+        let mut enabled = BTreeSet::new();
+        let mut pending = vec!["default"];
+        while let Some(feature) = pending.pop() {
+            for enables in self.features.get(feature).into_iter().flatten() {
+                // `dep:foo` and `foo/bar` enable dependencies, not features of the crate
+                if self.features.contains_key(enables) && enabled.insert(enables.as_str()) {
+                    pending.push(enables);
+                }
+            }
+        }
+        enabled
+    }
+
+    /// Read the facts of a crate from its manifest, `None` if it has no `[package]`
+    fn from_manifest(manifest: &Table, dir: PathBuf, workspace: Option<&Table>) -> Option<Self> {
+        let name = manifest.get("package")?.get("name")?.as_str()?.to_string();
+        let lib = manifest.get("lib");
+        let lib_name = lib
+            .and_then(|lib| lib.get("name"))
+            .and_then(Value::as_str)
+            .map_or_else(|| name.replace('-', "_"), str::to_string);
+        let cdylib = strings(lib.and_then(|lib| lib.get("crate-type"))).any(|crate_type| crate_type == "cdylib");
+
+        let features = manifest
+            .get("features")
+            .and_then(Value::as_table)
+            .map(|features| {
+                features
+                    .iter()
+                    .map(|(feature, enables)| (feature.clone(), strings(Some(enables)).map(str::to_string).collect()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let workspace_dependencies = workspace.and_then(|workspace| workspace.get("dependencies")).and_then(Value::as_table);
+        let target_dependencies = manifest
+            .get("target")
+            .and_then(Value::as_table)
+            .into_iter()
+            .flat_map(|targets| targets.values().filter_map(|target| target.get("dependencies")));
+        let mut dependencies = BTreeMap::new();
+        for table in manifest.get("dependencies").into_iter().chain(target_dependencies).filter_map(Value::as_table) {
+            for (key, dependency) in table {
+                let inherited = dependency.get("workspace").and_then(Value::as_bool) == Some(true);
+                let declaration = if inherited {
+                    workspace_dependencies.and_then(|dependencies| dependencies.get(key)).unwrap_or(dependency)
+                } else {
+                    dependency
+                };
+                // Renamed dependencies (`token = { package = "spl-token" }`) are kept under their package name
+                let package = dependency.get("package").or_else(|| declaration.get("package")).and_then(Value::as_str);
+                let version = declaration.as_str().or_else(|| declaration.get("version").and_then(Value::as_str));
+                dependencies
+                    .entry(package.unwrap_or(key).to_string())
+                    .or_insert_with(|| version.unwrap_or("*").to_string());
+            }
+        }
+
+        Some(Self {
+            name,
+            lib_name,
+            dir,
+            program_id: None,
+            features,
+            dependencies,
+            overflow_checks: false,
+            cdylib,
+            anchor_lang: None,
+        })
+    }
+}
+
+/// Crates of a Cargo workspace, or the crate alone when the root manifest is not a workspace
+///
+/// Members come from `[workspace] members` (with `*` and `?` wildcards, minus `exclude`) and
+/// the root package, if any. Program IDs come from the `[programs.<cluster>]` tables of
+/// `Anchor.toml`, the cluster of `[provider]` first, or else from `declare_id!`.
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    /// Directory of the root manifest
+    pub root: PathBuf,
+    /// Crates of the workspace, in the order of the members
+    pub crates: Vec<CrateManifest>,
+    /// Content of the root manifest, where the release profile is
+    root_manifest: String,
+}
+
+impl Workspace {
+    /// Read the workspace whose root manifest is in `root`, `None` if there is no `Cargo.toml`
+    ///
+    /// Members whose manifest cannot be read are skipped with a warning.
+    pub fn load(root: &Path) -> Result<Option<Self>, WorkspaceError> {
+        let manifest_path = root.join("Cargo.toml");
+        if !manifest_path.is_file() {
+            return Ok(None);
+        }
+        let root_manifest = read(&manifest_path)?;
+        let manifest = parse(&manifest_path, &root_manifest)?;
+        let workspace = manifest.get("workspace").and_then(Value::as_table);
+
+        let mut dirs = Vec::new();
+        if manifest.contains_key("package") {
+            dirs.push(root.to_path_buf());
+        }
+        if let Some(workspace) = workspace {
+            let excluded: Vec<PathBuf> = strings(workspace.get("exclude")).map(|dir| root.join(dir)).collect();
+            for pattern in strings(workspace.get("members")) {
+                for dir in expand_members(root, pattern) {
+                    if !excluded.contains(&dir) && !dirs.contains(&dir) {
+                        dirs.push(dir);
+                    }
+                }
+            }
+        }
+
+        let program_ids = anchor_program_ids(root)?;
+        let locked_anchor: Vec<Version> = std::fs::read_to_string(root.join("Cargo.lock"))
+            .map(|lock| {
+                locked_packages(&lock)
+                    .into_iter()
+                    .filter(|(package, ..)| package == ANCHOR_CRATE)
+                    .filter_map(|(_, version, _)| Version::parse(&version).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut crates = Vec::new();
+        for dir in dirs {
+            let member = if dir == root {
+                manifest.clone()
+            } else {
+                let path = dir.join("Cargo.toml");
+                match read(&path).and_then(|content| parse(&path, &content)) {
+                    Ok(member) => member,
+                    Err(e) => {
+                        warn!("Skipping the workspace member {}: {e}", dir.display());
+                        continue;
+                    }
+                }
+            };
+            let Some(mut manifest_crate) = CrateManifest::from_manifest(&member, dir, workspace) else {
+                continue;
+            };
+            // Profiles are only read from the root manifest
+            manifest_crate.overflow_checks = overflow_checks(&manifest, &manifest_crate.name);
+            manifest_crate.program_id = program_ids
+                .get(&manifest_crate.lib_name)
+                .cloned()
+                .or_else(|| declared_id(&manifest_crate.dir));
+            manifest_crate.anchor_lang = manifest_crate
+                .anchor_version()
+                .and_then(|requirement| anchor_lang_version(requirement, &locked_anchor));
+            crates.push(manifest_crate);
+        }
+        debug!("Workspace {} with {} crates", root.display(), crates.len());
+
+        Ok(Some(Self {
+            root: root.to_path_buf(),
+            crates,
+            root_manifest,
+        }))
+    }
+
+    /// Returns the crates that are Solana programs
+    pub fn programs(&self) -> impl Iterator<Item = &CrateManifest> {
+        self.crates.iter().filter(|manifest_crate| manifest_crate.is_program())
+    }
+
+    /// Returns the crate a file belongs to (the innermost crate directory containing it)
+    pub fn crate_of(&self, file: &Path) -> Option<&CrateManifest> {
+        self.crates
+            .iter()
+            .filter(|manifest_crate| file.starts_with(&manifest_crate.dir))
+            .max_by_key(|manifest_crate| manifest_crate.dir.components().count())
+    }
+
+    /// Report the programs whose release builds do not check integer overflows
+    ///
+    /// Overflows then wrap silently on-chain. The findings point to the release profile of
+    /// the root manifest (its first line if there is none).
+    pub fn manifest_findings(&self) -> Vec<Finding> {
+        let line = release_profile_line(&self.root_manifest);
+        let file = self.root.join("Cargo.toml").to_string_lossy().to_string();
+        self.programs()
+            .filter(|program| !program.overflow_checks)
+            .map(|program| {
+                let mut metadata = Metadata::new();
+                metadata.insert(PROGRAM_KEY.to_string(), MetadataValue::Text(program.name.clone()));
+                Finding {
+                    description: format!(
+                        "Overflow checks disabled in the release builds of '{}': integer overflows wrap silently",
+                        program.name
+                    ),
+                    severity: Severity::Medium,
+                    confidence: Confidence::High,
+                    location: Location::new_precise(file.clone(), line, None, None, None),
+                    code_snippet: self.root_manifest.lines().nth(line - 1).map(|code| code.trim().to_string()),
+                    recommendations: vec![
+                        "Set `overflow-checks = true` in the `[profile.release]` section of the workspace Cargo.toml".to_string(),
+                        "Use checked arithmetic (checked_add, checked_mul...) for amounts and balances".to_string(),
+                    ],
+                    fixes: Vec::new(),
+                    metadata,
+                    rules: vec![RuleInfo {
+                        id: OVERFLOW_CHECKS_ID.to_string(),
+                        title: "Overflow Checks Disabled".to_string(),
+                        tags: vec!["security".to_string(), "arithmetic".to_string()],
+                        cwe: vec![190],
+                        category: Some(Category::Arithmetic),
+                        ..Default::default()
+                    }],
+                    fingerprint: None,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Helper function to read a manifest
+fn read(path: &Path) -> Result<String, WorkspaceError> {
+    std::fs::read_to_string(path).map_err(|error| WorkspaceError::Io {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+/// Helper function to parse a manifest
+fn parse(path: &Path, content: &str) -> Result<Table, WorkspaceError> {
+    content.parse::<Table>().map_err(|error| WorkspaceError::Parse {
+        path: path.to_path_buf(),
+        error: error.message().to_string(),
+    })
+}
+
+/// Helper function to get the version of anchor-lang a requirement resolves to: the highest
+/// locked version it allows, or else its lowest version
+fn anchor_lang_version(requirement: &str, locked: &[Version]) -> Option<Version> {
+    let allowed = VersionReq::parse(requirement).ok();
+    locked
+        .iter()
+        .filter(|version| allowed.as_ref().is_none_or(|allowed| allowed.matches(version)))
+        .max()
+        .cloned()
+        .or_else(|| lowest_version(requirement))
+}
+
+/// Helper function to iterate over the strings of a TOML array
+fn strings(value: Option<&Value>) -> impl Iterator<Item = &str> {
+    value.and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str)
+}
+
+/// Helper function to find the crate directories matching a member pattern (`programs/*`)
+fn expand_members(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![root.to_path_buf()];
+    for segment in pattern.split('/').filter(|segment| !segment.is_empty() && *segment != ".") {
+        if !segment.contains(['*', '?']) {
+            dirs = dirs.into_iter().map(|dir| dir.join(segment)).collect();
+            continue;
+        }
+        dirs = dirs
+            .iter()
+            .flat_map(|dir| {
+                let mut matches: Vec<PathBuf> = std::fs::read_dir(dir)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(std::result::Result::ok)
+                    .filter(|entry| entry.path().is_dir() && wildcard_match(segment, &entry.file_name().to_string_lossy()))
+                    .map(|entry| entry.path())
+                    .collect();
+                matches.sort();
+                matches
+            })
+            .collect();
+    }
+    dirs.retain(|dir| dir.join("Cargo.toml").is_file());
+    dirs
+}
+
+/// Helper function to match a name against a pattern where `*` matches any characters and `?` one
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // Position in the pattern after the last `*`, and in the name where it started matching
+    let (mut p, mut n, mut star) = (0, 0, None);
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p + 1, n));
+            p += 1;
+        } else if let Some((after_star, matched)) = star {
+            p = after_star;
+            n = matched + 1;
+            star = Some((after_star, n));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Helper function to check if the release profile of the root manifest keeps the overflow checks
+/// of a package (`[profile.release.package.<name>]` overrides `[profile.release]`)
+fn overflow_checks(root_manifest: &Table, package: &str) -> bool {
+    let release = root_manifest.get("profile").and_then(|profile| profile.get("release"));
+    let setting = |profile: Option<&Value>| profile.and_then(|profile| profile.get("overflow-checks")).and_then(Value::as_bool);
+    setting(release.and_then(|release| release.get("package")).and_then(|packages| packages.get(package)))
+        .or_else(|| setting(release))
+        .unwrap_or(false)
+}
+
+/// Helper function to get the line of the overflow checks setting of the release profile (or of
+/// the profile itself, or the first line)
+fn release_profile_line(manifest: &str) -> usize {
+    let mut profile_line = None;
+    for (index, line) in manifest.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            if profile_line.is_some() {
+                break;
+            }
+            if line == "[profile.release]" {
+                profile_line = Some(index + 1);
+            }
+        } else if profile_line.is_some() && line.starts_with("overflow-checks") {
+            return index + 1;
+        }
+    }
+    profile_line.unwrap_or(1)
+}
+
+/// Helper function to read the program IDs of `Anchor.toml`, by library name
+fn anchor_program_ids(root: &Path) -> Result<BTreeMap<String, String>, WorkspaceError> {
+    let path = root.join("Anchor.toml");
+    let mut ids = BTreeMap::new();
+    if !path.is_file() {
+        return Ok(ids);
+    }
+    let anchor = parse(&path, &read(&path)?)?;
+    let Some(programs) = anchor.get("programs").and_then(Value::as_table) else {
+        return Ok(ids);
+    };
+    let cluster = anchor
+        .get("provider")
+        .and_then(|provider| provider.get("cluster"))
+        .and_then(Value::as_str)
+        .map_or_else(|| "localnet".to_string(), str::to_lowercase);
+
+    // The programs of the provider cluster come first, then those only deployed elsewhere
+    for table in programs.get(&cluster).into_iter().chain(programs.values()).filter_map(Value::as_table) {
+        for (name, program) in table {
+            // `name = "<id>"` or `name = { address = "<id>", idl = "..." }`
+            let id = program.as_str().or_else(|| program.get("address").and_then(Value::as_str));
+            if let Some(id) = id {
+                ids.entry(name.clone()).or_insert_with(|| id.to_string());
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// Helper function to read the program ID declared in `src/lib.rs` (`declare_id!("<id>")`)
+fn declared_id(dir: &Path) -> Option<String> {
+    let source = std::fs::read_to_string(dir.join("src").join("lib.rs")).ok()?;
+    let (_, declaration) = source.split_once("declare_id!(")?;
+    let (_, id) = declaration.split_once('"')?;
+    id.split_once('"').map(|(id, _)| id.to_string())
+}
@@ -0,0 +1,154 @@
+use crate::analyzer::workspace::{OVERFLOW_CHECKS_ID, Workspace, release_profile_line, wildcard_match};
+use semver::Version;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOT_MANIFEST: &str = r#"
+[workspace]
+members = ["programs/*", "crates/cli"]
+exclude = ["programs/legacy"]
+
+[workspace.dependencies]
+anchor-lang = { version = "0.30.1", features = ["init-if-needed"] }
+
+[profile.release]
+lto = "fat"
+overflow-checks = true
+
+[profile.release.package.escrow]
+overflow-checks = false
+"#;
+
+    const VAULT_MANIFEST: &str = r#"
+[package]
+name = "vault"
+version = "0.1.0"
+
+[lib]
+crate-type = ["cdylib", "lib"]
+
+[features]
+default = ["logging"]
+logging = ["tracing"]
+tracing = []
+cpi = ["no-entrypoint"]
+no-entrypoint = []
+
+[dependencies]
+anchor-lang.workspace = true
+token = { package = "spl-token", version = "4.0" }
+vault-math = { path = "../../crates/math" }
+"#;
+
+    /// Helper function to write the files of a workspace in a temporary directory
+    fn write_workspace(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("solana-analyzer-workspace-{name}-{}", std::process::id()));
+        for (file, content) in files {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn test_workspace_members_and_manifests() {
+        let root = write_workspace(
+            "members",
+            &[
+                ("Cargo.toml", ROOT_MANIFEST),
+                ("Anchor.toml", "[provider]\ncluster = \"Devnet\"\n\n[programs.localnet]\nvault = \"Local111\"\n\n[programs.devnet]\nvault = \"Dev111\"\n"),
+                ("programs/vault/Cargo.toml", VAULT_MANIFEST),
+                ("programs/escrow/Cargo.toml", "[package]\nname = \"escrow\"\n\n[dependencies]\nsolana-program = \"1.18\"\n"),
+                ("programs/escrow/src/lib.rs", "solana_program::declare_id!(\"Escrow111\");\n"),
+                ("programs/legacy/Cargo.toml", "[package]\nname = \"legacy\"\n"),
+                ("programs/docs/README.md", ""),
+                ("crates/cli/Cargo.toml", "[package]\nname = \"vault-cli\"\n"),
+            ],
+        );
+        let workspace = Workspace::load(&root).unwrap().unwrap();
+
+        let names: Vec<&str> = workspace.crates.iter().map(|manifest| manifest.name.as_str()).collect();
+        assert_eq!(names, ["escrow", "vault", "vault-cli"]);
+        let programs: Vec<&str> = workspace.programs().map(|program| program.name.as_str()).collect();
+        assert_eq!(programs, ["escrow", "vault"]);
+
+        let vault = &workspace.crates[1];
+        assert_eq!(vault.program_id.as_deref(), Some("Dev111"));
+        assert_eq!(vault.anchor_version(), Some("0.30.1"));
+        assert_eq!(vault.anchor_lang, Some(Version::new(0, 30, 1)));
+        assert_eq!(vault.dependency_version("spl-token"), Some("4.0"));
+        assert_eq!(vault.dependency_version("vault-math"), Some("*"));
+        assert_eq!(vault.default_features().into_iter().collect::<Vec<_>>(), ["logging", "tracing"]);
+        assert!(vault.overflow_checks);
+
+        let escrow = &workspace.crates[0];
+        assert_eq!(escrow.program_id.as_deref(), Some("Escrow111"));
+        assert_eq!(escrow.anchor_version(), None);
+        assert_eq!(escrow.anchor_lang, None);
+        assert!(!escrow.overflow_checks);
+
+        let file = root.join("programs/vault/src/instructions/withdraw.rs");
+        assert_eq!(workspace.crate_of(&file).map(|manifest| manifest.name.as_str()), Some("vault"));
+        assert!(workspace.crate_of(Path::new("/elsewhere/src/lib.rs")).is_none());
+
+        // Only the program built without overflow checks is reported, on the release profile
+        let findings = workspace.manifest_findings();
+        assert_eq!(findings.len(), 1, "{findings:#?}");
+        assert_eq!(findings[0].rules[0].id, OVERFLOW_CHECKS_ID);
+        assert!(findings[0].description.contains("'escrow'"));
+        assert_eq!(findings[0].location.line, release_profile_line(ROOT_MANIFEST));
+        assert_eq!(findings[0].code_snippet.as_deref(), Some("overflow-checks = true"));
+
+        assert!(Workspace::load(&root.join("programs/docs")).unwrap().is_none());
+        std::fs::write(root.join("programs/docs/Cargo.toml"), "[package").unwrap();
+        assert!(Workspace::load(&root.join("programs/docs")).is_err());
+    }
+
+    #[test]
+    fn test_anchor_version_from_the_lock_file() {
+        let lock = "[[package]]\nname = \"anchor-lang\"\nversion = \"0.29.0\"\n\n[[package]]\nname = \"anchor-lang\"\nversion = \"0.30.1\"\n";
+        let root = write_workspace(
+            "anchor-lock",
+            &[
+                ("Cargo.toml", "[workspace]\nmembers = [\"programs/*\"]\n"),
+                ("Cargo.lock", lock),
+                ("programs/legacy/Cargo.toml", "[package]\nname = \"legacy\"\n\n[dependencies]\nanchor-lang = \"~0.29\"\n"),
+                ("programs/vault/Cargo.toml", "[package]\nname = \"vault\"\n\n[dependencies]\nanchor-lang = \"0.30\"\n"),
+                ("programs/escrow/Cargo.toml", "[package]\nname = \"escrow\"\n\n[dependencies]\nanchor-lang = \">=0.31\"\n"),
+            ],
+        );
+        let workspace = Workspace::load(&root).unwrap().unwrap();
+        let versions: Vec<(&str, Option<String>)> = workspace
+            .crates
+            .iter()
+            .map(|manifest| (manifest.name.as_str(), manifest.anchor_lang.as_ref().map(ToString::to_string)))
+            .collect();
+        // The highest locked version allowed by the requirement, or its lowest version if none is locked
+        assert_eq!(
+            versions,
+            [
+                ("escrow", Some("0.31.0".to_string())),
+                ("legacy", Some("0.29.0".to_string())),
+                ("vault", Some("0.30.1".to_string())),
+            ]
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_member_patterns() {
+        assert!(wildcard_match("*", "vault"));
+        assert!(wildcard_match("program-*", "program-vault"));
+        assert!(wildcard_match("v?ult*", "vault-v2"));
+        assert!(wildcard_match("*-v*2", "vault-v1-v2"));
+        assert!(!wildcard_match("program-*", "vault"));
+        assert!(!wildcard_match("v?ult", "vault-v2"));
+
+        assert_eq!(release_profile_line("[package]\nname = \"vault\"\n"), 1);
+        assert_eq!(release_profile_line("[package]\n\n[profile.release]\nlto = true\n\n[dependencies]\n"), 3);
+    }
+}
@@ -1,10 +1,293 @@
-use anyhow::Result;
 use log::debug;
+use quote::ToTokens;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 use syn::File;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn_serde::Syn;
+
+use crate::ast::modules::ModuleTree;
 
 /// Convert an AST to JSON
 pub fn ast_to_json(ast: &File) -> String {
     debug!("Converting AST to JSON");
-    let json_string = syn_serde::json::to_string_pretty(ast);
-    json_string
+    syn_serde::json::to_string_pretty(ast)
+}
+
+/// Position of a node in its source: lines are 1-indexed and columns 0-indexed, like the
+/// locations of the findings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeSpan {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// Node of the outline of an AST, with its span
+///
+/// Items, fields, variants, `let` statements, expressions, patterns, types and macros are
+/// nodes; the other syntax (e.g. a function signature) belongs to the enclosing node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AstNode {
+    /// Type of the node in syn (`ItemFn`, `ExprMethodCall`...)
+    pub kind: &'static str,
+    /// Name of the items, fields and variants, and method of the method calls
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub span: NodeSpan,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<AstNode>,
+}
+
+/// AST of a file, as exported by `--ast`
+#[derive(Serialize)]
+pub struct FileAst {
+    /// Path of the file, relative to the analyzed project
+    pub file: String,
+    /// Module of the file (e.g. `crate::instructions::withdraw`), in a combined program AST
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    /// Outline of the AST with the spans of its nodes
+    pub nodes: Vec<AstNode>,
+    /// Full AST (syn-serde)
+    pub ast: syn_serde::File,
+}
+
+impl FileAst {
+    /// Creates the export of the AST of a file
+    pub fn new(file: impl Into<String>, ast: &File) -> Self {
+        Self { file: file.into(), module: None, nodes: span_tree(ast), ast: ast.to_adapter() }
+    }
+
+    /// Returns the export as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("ASTs serialize to JSON")
+    }
+}
+
+/// ASTs of the files of a crate, in the order of their modules
+#[derive(Serialize)]
+pub struct ProgramAst {
+    /// Root file of the crate (`src/lib.rs` or `src/main.rs`), relative to the analyzed project
+    pub crate_root: String,
+    /// Files of the crate, the crate root first
+    pub modules: Vec<FileAst>,
+}
+
+impl ProgramAst {
+    /// Combine the ASTs of parsed files into one per crate, following the `mod` declarations
+    /// from the crate roots; paths are written relative to `root`
+    ///
+    /// Files that no crate root reaches are left out.
+    pub fn combine(files: &[(PathBuf, File)], root: &Path) -> Vec<ProgramAst> {
+        let tree = ModuleTree::build(files.iter().map(|(path, ast)| (path.as_path(), ast)));
+        let mut modules: Vec<_> = files
+            .iter()
+            .filter_map(|(path, ast)| tree.module_of(path).map(|module| (module, path, ast)))
+            .collect();
+        modules.sort_by(|(a, ..), (b, ..)| (&a.crate_root, &a.path).cmp(&(&b.crate_root, &b.path)));
+
+        let mut programs: Vec<ProgramAst> = Vec::new();
+        for (module, path, ast) in modules {
+            let crate_root = relative(&module.crate_root, root);
+            let mut file = FileAst::new(relative(path, root), ast);
+            file.module = Some(module.path.join("::"));
+            match programs.last_mut() {
+                Some(program) if program.crate_root == crate_root => program.modules.push(file),
+                _ => programs.push(ProgramAst { crate_root, modules: vec![file] }),
+            }
+        }
+        programs
+    }
+
+    /// Returns the export as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("ASTs serialize to JSON")
+    }
+}
+
+/// Helper function to write a path relative to `root`, with `/` separators
+fn relative(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path).to_string_lossy().replace('\\', "/")
+}
+
+/// Build the outline of an AST, with the spans of its nodes
+pub fn span_tree(ast: &File) -> Vec<AstNode> {
+    let mut collector = SpanCollector::default();
+    collector.visit_file(ast);
+    collector.roots
+}
+
+/// Helper to build the outline of an AST while visiting it
+#[derive(Default)]
+struct SpanCollector {
+    /// Nodes being visited, the innermost last
+    stack: Vec<AstNode>,
+    /// Visited top-level nodes
+    roots: Vec<AstNode>,
+}
+
+impl SpanCollector {
+    /// Add a node, visiting its children with `visit_children`
+    fn node<T: Spanned>(&mut self, kind: &'static str, name: Option<String>, node: &T, visit_children: impl FnOnce(&mut Self)) {
+        let span = node.span();
+        let (start, end) = (span.start(), span.end());
+        self.stack.push(AstNode {
+            kind,
+            name,
+            span: NodeSpan { line: start.line, column: start.column, end_line: end.line, end_column: end.column },
+            children: Vec::new(),
+        });
+        visit_children(self);
+        let node = self.stack.pop().expect("the node was pushed");
+        match self.stack.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => self.roots.push(node),
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for SpanCollector {
+    fn visit_item(&mut self, item: &'ast syn::Item) {
+        let (kind, name) = match item {
+            syn::Item::Const(item) => ("ItemConst", Some(item.ident.to_string())),
+            syn::Item::Enum(item) => ("ItemEnum", Some(item.ident.to_string())),
+            syn::Item::ExternCrate(item) => ("ItemExternCrate", Some(item.ident.to_string())),
+            syn::Item::Fn(item) => ("ItemFn", Some(item.sig.ident.to_string())),
+            syn::Item::ForeignMod(_) => ("ItemForeignMod", None),
+            syn::Item::Impl(item) => ("ItemImpl", Some(item.self_ty.to_token_stream().to_string())),
+            syn::Item::Macro(item) => ("ItemMacro", item.ident.as_ref().map(ToString::to_string)),
+            syn::Item::Mod(item) => ("ItemMod", Some(item.ident.to_string())),
+            syn::Item::Static(item) => ("ItemStatic", Some(item.ident.to_string())),
+            syn::Item::Struct(item) => ("ItemStruct", Some(item.ident.to_string())),
+            syn::Item::Trait(item) => ("ItemTrait", Some(item.ident.to_string())),
+            syn::Item::TraitAlias(item) => ("ItemTraitAlias", Some(item.ident.to_string())),
+            syn::Item::Type(item) => ("ItemType", Some(item.ident.to_string())),
+            syn::Item::Union(item) => ("ItemUnion", Some(item.ident.to_string())),
+            syn::Item::Use(_) => ("ItemUse", None),
+            _ => ("Item", None),
+        };
+        self.node(kind, name, item, |collector| visit::visit_item(collector, item));
+    }
+
+    fn visit_impl_item(&mut self, item: &'ast syn::ImplItem) {
+        let (kind, name) = match item {
+            syn::ImplItem::Const(item) => ("ImplItemConst", Some(item.ident.to_string())),
+            syn::ImplItem::Fn(item) => ("ImplItemFn", Some(item.sig.ident.to_string())),
+            syn::ImplItem::Type(item) => ("ImplItemType", Some(item.ident.to_string())),
+            syn::ImplItem::Macro(_) => ("ImplItemMacro", None),
+            _ => ("ImplItem", None),
+        };
+        self.node(kind, name, item, |collector| visit::visit_impl_item(collector, item));
+    }
+
+    fn visit_trait_item(&mut self, item: &'ast syn::TraitItem) {
+        let (kind, name) = match item {
+            syn::TraitItem::Const(item) => ("TraitItemConst", Some(item.ident.to_string())),
+            syn::TraitItem::Fn(item) => ("TraitItemFn", Some(item.sig.ident.to_string())),
+            syn::TraitItem::Type(item) => ("TraitItemType", Some(item.ident.to_string())),
+            syn::TraitItem::Macro(_) => ("TraitItemMacro", None),
+            _ => ("TraitItem", None),
+        };
+        self.node(kind, name, item, |collector| visit::visit_trait_item(collector, item));
+    }
+
+    fn visit_field(&mut self, field: &'ast syn::Field) {
+        let name = field.ident.as_ref().map(ToString::to_string);
+        self.node("Field", name, field, |collector| visit::visit_field(collector, field));
+    }
+
+    fn visit_variant(&mut self, variant: &'ast syn::Variant) {
+        let name = Some(variant.ident.to_string());
+        self.node("Variant", name, variant, |collector| visit::visit_variant(collector, variant));
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        self.node("Local", None, local, |collector| visit::visit_local(collector, local));
+    }
+
+    fn visit_expr(&mut self, expr: &'ast syn::Expr) {
+        let (kind, name) = match expr {
+            syn::Expr::Array(_) => ("ExprArray", None),
+            syn::Expr::Assign(_) => ("ExprAssign", None),
+            syn::Expr::Async(_) => ("ExprAsync", None),
+            syn::Expr::Await(_) => ("ExprAwait", None),
+            syn::Expr::Binary(_) => ("ExprBinary", None),
+            syn::Expr::Block(_) => ("ExprBlock", None),
+            syn::Expr::Break(_) => ("ExprBreak", None),
+            syn::Expr::Call(_) => ("ExprCall", None),
+            syn::Expr::Cast(_) => ("ExprCast", None),
+            syn::Expr::Closure(_) => ("ExprClosure", None),
+            syn::Expr::Const(_) => ("ExprConst", None),
+            syn::Expr::Continue(_) => ("ExprContinue", None),
+            syn::Expr::Field(_) => ("ExprField", None),
+            syn::Expr::ForLoop(_) => ("ExprForLoop", None),
+            syn::Expr::Group(_) => ("ExprGroup", None),
+            syn::Expr::If(_) => ("ExprIf", None),
+            syn::Expr::Index(_) => ("ExprIndex", None),
+            syn::Expr::Infer(_) => ("ExprInfer", None),
+            syn::Expr::Let(_) => ("ExprLet", None),
+            syn::Expr::Lit(_) => ("ExprLit", None),
+            syn::Expr::Loop(_) => ("ExprLoop", None),
+            syn::Expr::Macro(_) => ("ExprMacro", None),
+            syn::Expr::Match(_) => ("ExprMatch", None),
+            syn::Expr::MethodCall(call) => ("ExprMethodCall", Some(call.method.to_string())),
+            syn::Expr::Paren(_) => ("ExprParen", None),
+            syn::Expr::Path(_) => ("ExprPath", None),
+            syn::Expr::Range(_) => ("ExprRange", None),
+            syn::Expr::RawAddr(_) => ("ExprRawAddr", None),
+            syn::Expr::Reference(_) => ("ExprReference", None),
+            syn::Expr::Repeat(_) => ("ExprRepeat", None),
+            syn::Expr::Return(_) => ("ExprReturn", None),
+            syn::Expr::Struct(_) => ("ExprStruct", None),
+            syn::Expr::Try(_) => ("ExprTry", None),
+            syn::Expr::TryBlock(_) => ("ExprTryBlock", None),
+            syn::Expr::Tuple(_) => ("ExprTuple", None),
+            syn::Expr::Unary(_) => ("ExprUnary", None),
+            syn::Expr::Unsafe(_) => ("ExprUnsafe", None),
+            syn::Expr::While(_) => ("ExprWhile", None),
+            syn::Expr::Yield(_) => ("ExprYield", None),
+            _ => ("Expr", None),
+        };
+        self.node(kind, name, expr, |collector| visit::visit_expr(collector, expr));
+    }
+
+    fn visit_pat(&mut self, pat: &'ast syn::Pat) {
+        let kind = match pat {
+            syn::Pat::Ident(_) => "PatIdent",
+            syn::Pat::Or(_) => "PatOr",
+            syn::Pat::Reference(_) => "PatReference",
+            syn::Pat::Slice(_) => "PatSlice",
+            syn::Pat::Struct(_) => "PatStruct",
+            syn::Pat::Tuple(_) => "PatTuple",
+            syn::Pat::TupleStruct(_) => "PatTupleStruct",
+            syn::Pat::Type(_) => "PatType",
+            syn::Pat::Wild(_) => "PatWild",
+            _ => "Pat",
+        };
+        self.node(kind, None, pat, |collector| visit::visit_pat(collector, pat));
+    }
+
+    fn visit_type(&mut self, ty: &'ast syn::Type) {
+        let kind = match ty {
+            syn::Type::Array(_) => "TypeArray",
+            syn::Type::BareFn(_) => "TypeBareFn",
+            syn::Type::ImplTrait(_) => "TypeImplTrait",
+            syn::Type::Path(_) => "TypePath",
+            syn::Type::Ptr(_) => "TypePtr",
+            syn::Type::Reference(_) => "TypeReference",
+            syn::Type::Slice(_) => "TypeSlice",
+            syn::Type::TraitObject(_) => "TypeTraitObject",
+            syn::Type::Tuple(_) => "TypeTuple",
+            _ => "Type",
+        };
+        self.node(kind, None, ty, |collector| visit::visit_type(collector, ty));
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        let name = mac.path.segments.last().map(|segment| segment.ident.to_string());
+        self.node("Macro", name, mac, |collector| visit::visit_macro(collector, mac));
+    }
 }
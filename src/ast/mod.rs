@@ -1,4 +1,5 @@
 pub mod json;
+pub mod modules;
 pub mod parser;
 
 
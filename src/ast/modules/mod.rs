@@ -0,0 +1,268 @@
+use log::debug;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use syn::punctuated::Punctuated;
+use syn::{Attribute, File, Item, ItemMod, Meta, Token};
+
+#[cfg(test)]
+mod test;
+
+/// Module of a file, resolved by following the `mod` declarations from the crate root
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileModule {
+    /// Root file of the crate (`src/lib.rs` or `src/main.rs`)
+    pub crate_root: PathBuf,
+    /// Path of the module, starting with `crate` (e.g. `["crate", "instructions", "withdraw"]`)
+    pub path: Vec<String>,
+    /// Whether the module, or one of its parents, is declared under `#[cfg(test)]`
+    pub test: bool,
+}
+
+/// Logical tree of the crates of the parsed files
+///
+/// Starts from the crate roots (`src/lib.rs` and `src/main.rs`) and follows the `mod foo;`
+/// declarations to `foo.rs` or `foo/mod.rs`, including the ones nested in inline modules and
+/// the ones with a `#[path]` attribute, the way rustc loads them. Only the given files are part
+/// of the tree: modules declared but not parsed (missing or excluded files) are skipped, and
+/// files that no crate root reaches have no module.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleTree {
+    /// Module of each file, by normalized path
+    modules: BTreeMap<PathBuf, FileModule>,
+}
+
+impl ModuleTree {
+    /// Resolve the modules of the given files (path and AST)
+    pub fn build<'a>(files: impl IntoIterator<Item = (&'a Path, &'a File)>) -> Self {
+        let files: BTreeMap<PathBuf, &File> = files.into_iter().map(|(path, ast)| (normalize(path), ast)).collect();
+        let mut tree = Self::default();
+
+        // `BTreeMap` order: `lib.rs` comes before `main.rs`, so a module of both is owned by the library
+        let roots = files.keys().filter(|path| is_crate_root(path));
+        for root in roots {
+            if tree.modules.contains_key(root) {
+                continue;
+            }
+            let mut resolver = Resolver {
+                files: &files,
+                crate_root: root,
+                modules: &mut tree.modules,
+            };
+            resolver.enter_file(root, vec!["crate".to_string()], false, true);
+        }
+        debug!("Module tree built with {} files", tree.modules.len());
+        tree
+    }
+
+    /// Returns the module of a file, if a crate root reaches it
+    pub fn module_of(&self, file: &Path) -> Option<&FileModule> {
+        self.modules.get(&normalize(file))
+    }
+
+    /// Returns the files and their modules, by path
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &FileModule)> {
+        self.modules.iter().map(|(path, module)| (path.as_path(), module))
+    }
+
+    /// Returns the number of files reached from a crate root
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    /// Check if no file is reached from a crate root
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+}
+
+/// Location of the items being resolved
+struct Scope<'p> {
+    /// File of the items
+    file: &'p Path,
+    /// Directory of the file, base of the `#[path]` attributes outside inline modules
+    file_dir: &'p Path,
+    /// Directory of the modules declared by the items
+    module_dir: PathBuf,
+    /// Whether the items belong to an inline module
+    inline: bool,
+}
+
+/// Helper to follow the `mod` declarations of a crate
+struct Resolver<'a> {
+    files: &'a BTreeMap<PathBuf, &'a File>,
+    crate_root: &'a Path,
+    modules: &'a mut BTreeMap<PathBuf, FileModule>,
+}
+
+impl Resolver<'_> {
+    /// Assign a module to a file, then resolve the modules it declares
+    ///
+    /// Files owning their directory (crate roots, `mod.rs` files and files loaded by a `#[path]`
+    /// attribute) declare their modules next to them, other files in a directory named after them.
+    fn enter_file(&mut self, file: &Path, path: Vec<String>, test: bool, owns_directory: bool) {
+        // A file reached twice (e.g. by two `#[path]` attributes) keeps its first module
+        if self.modules.contains_key(file) {
+            return;
+        }
+        let Some(ast) = self.files.get(file) else {
+            return;
+        };
+        self.modules.insert(
+            file.to_path_buf(),
+            FileModule {
+                crate_root: self.crate_root.to_path_buf(),
+                path: path.clone(),
+                test,
+            },
+        );
+
+        let file_dir = file.parent().unwrap_or(Path::new(""));
+        let scope = Scope {
+            file,
+            file_dir,
+            module_dir: if owns_directory {
+                file_dir.to_path_buf()
+            } else {
+                file_dir.join(file.file_stem().unwrap_or_default())
+            },
+            inline: false,
+        };
+        self.resolve_items(&ast.items, &scope, &path, test);
+    }
+
+    /// Resolve the `mod` declarations of a list of items, recursively for inline modules
+    fn resolve_items(&mut self, items: &[Item], scope: &Scope<'_>, path: &[String], test: bool) {
+        for item in items {
+            let Item::Mod(item_mod) = item else {
+                continue;
+            };
+            let mut nested = path.to_vec();
+            nested.push(item_mod.ident.to_string());
+            let test = test || item_mod.attrs.iter().any(is_test_attribute);
+            let path_attribute = path_attribute(&item_mod.attrs);
+
+            match &item_mod.content {
+                Some((_, content)) => {
+                    let inline = Scope {
+                        module_dir: scope.module_dir.join(path_attribute.unwrap_or_else(|| item_mod.ident.to_string())),
+                        inline: true,
+                        ..*scope
+                    };
+                    self.resolve_items(content, &inline, &nested, test);
+                }
+                None => match path_attribute {
+                    // Relative to the file outside inline modules, to the module directory inside them
+                    Some(relative) => {
+                        let base = if scope.inline { &scope.module_dir } else { scope.file_dir };
+                        let target = normalize(&base.join(relative));
+                        if self.files.contains_key(&target) {
+                            self.enter_file(&target, nested, test, true);
+                        } else {
+                            self.skip(item_mod, scope.file, &[target]);
+                        }
+                    }
+                    None => {
+                        let name = item_mod.ident.to_string();
+                        let candidates = [
+                            normalize(&scope.module_dir.join(format!("{name}.rs"))),
+                            normalize(&scope.module_dir.join(&name).join("mod.rs")),
+                        ];
+                        match candidates.iter().find(|candidate| self.files.contains_key(*candidate)) {
+                            Some(target) => {
+                                let owns_directory = target.ends_with("mod.rs");
+                                self.enter_file(target, nested, test, owns_directory);
+                            }
+                            None => self.skip(item_mod, scope.file, &candidates),
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    /// Log a module declaration whose file was not parsed
+    fn skip(&self, item_mod: &ItemMod, file: &Path, candidates: &[PathBuf]) {
+        let candidates: Vec<String> = candidates.iter().map(|path| path.display().to_string()).collect();
+        debug!(
+            "Module {} declared in {} not found among the parsed files ({})",
+            item_mod.ident,
+            file.display(),
+            candidates.join(", ")
+        );
+    }
+}
+
+/// Helper function to check if a file is a crate root (`lib.rs` or `main.rs` of a `src` directory)
+fn is_crate_root(path: &Path) -> bool {
+    let in_src = path.parent().and_then(Path::file_name).is_some_and(|dir| dir == "src");
+    in_src && path.file_name().is_some_and(|name| name == "lib.rs" || name == "main.rs")
+}
+
+/// Helper function to get the value of a `#[path = "..."]` attribute
+fn path_attribute(attrs: &[Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| {
+        let syn::Meta::NameValue(name_value) = &attr.meta else {
+            return None;
+        };
+        if !name_value.path.is_ident("path") {
+            return None;
+        }
+        match &name_value.value {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(value), ..
+            }) => Some(value.value()),
+            _ => None,
+        }
+    })
+}
+
+/// Check if an attribute marks test code: `#[test]`, or a `#[cfg]` whose predicate only holds
+/// in tests (`cfg(test)`, `cfg(all(test, feature = "x"))`, but not `cfg(not(test))` nor
+/// `cfg(any(test, feature = "x"))`)
+pub fn is_test_attribute(attr: &Attribute) -> bool {
+    if attr.path().is_ident("test") {
+        return true;
+    }
+    match &attr.meta {
+        Meta::List(meta_list) if meta_list.path.is_ident("cfg") => {
+            meta_list.parse_args::<Meta>().is_ok_and(|predicate| requires_test(&predicate))
+        }
+        _ => false,
+    }
+}
+
+/// Helper function to check if a `cfg` predicate only holds when `test` is set
+fn requires_test(predicate: &Meta) -> bool {
+    match predicate {
+        Meta::Path(path) => path.is_ident("test"),
+        Meta::List(meta_list) => {
+            let Ok(predicates) = meta_list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) else {
+                return false;
+            };
+            if meta_list.path.is_ident("all") {
+                predicates.iter().any(requires_test)
+            } else if meta_list.path.is_ident("any") {
+                !predicates.is_empty() && predicates.iter().all(requires_test)
+            } else {
+                // `not(..)`
+                false
+            }
+        }
+        Meta::NameValue(_) => false,
+    }
+}
+
+/// Helper function to normalize a path lexically (without `.` and, where possible, `..`)
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(normalized.components().next_back(), Some(Component::Normal(_))) => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
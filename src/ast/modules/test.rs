@@ -0,0 +1,87 @@
+use crate::ast::modules::{ModuleTree, is_test_attribute};
+use std::path::{Path, PathBuf};
+use syn::File;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(files: &[(&str, &str)]) -> Vec<(PathBuf, File)> {
+        files
+            .iter()
+            .map(|(path, source)| (PathBuf::from(path), syn::parse_file(source).unwrap()))
+            .collect()
+    }
+
+    fn module(tree: &ModuleTree, file: &str) -> Option<String> {
+        tree.module_of(Path::new(file)).map(|module| module.path.join("::"))
+    }
+
+    #[test]
+    fn test_module_tree_follows_mod_declarations() {
+        let files = parse(&[
+            ("programs/vault/src/lib.rs", "pub mod instructions; pub mod state; mod generated { pub mod ids; }"),
+            ("programs/vault/src/instructions/mod.rs", "pub mod withdraw;"),
+            ("programs/vault/src/instructions/withdraw.rs", "mod checks;"),
+            ("programs/vault/src/instructions/withdraw/checks.rs", ""),
+            ("programs/vault/src/state.rs", "#[path = \"../legacy/vault.rs\"] pub mod vault;"),
+            ("programs/vault/legacy/vault.rs", "pub mod layout;"),
+            ("programs/vault/legacy/layout.rs", ""),
+            ("programs/vault/src/generated/ids.rs", ""),
+            ("programs/vault/src/unused.rs", ""),
+        ]);
+        let tree = ModuleTree::build(files.iter().map(|(path, ast)| (path.as_path(), ast)));
+
+        let root = "programs/vault/src";
+        assert_eq!(module(&tree, &format!("{root}/lib.rs")).as_deref(), Some("crate"));
+        assert_eq!(module(&tree, &format!("{root}/instructions/mod.rs")).as_deref(), Some("crate::instructions"));
+        assert_eq!(
+            module(&tree, &format!("{root}/instructions/withdraw/checks.rs")).as_deref(),
+            Some("crate::instructions::withdraw::checks")
+        );
+        assert_eq!(module(&tree, "programs/vault/legacy/vault.rs").as_deref(), Some("crate::state::vault"));
+        // A file loaded by `#[path]` owns its directory
+        assert_eq!(module(&tree, "programs/vault/legacy/layout.rs").as_deref(), Some("crate::state::vault::layout"));
+        assert_eq!(module(&tree, &format!("{root}/generated/ids.rs")).as_deref(), Some("crate::generated::ids"));
+        assert_eq!(module(&tree, &format!("{root}/unused.rs")), None);
+        assert_eq!(tree.len(), 8);
+
+        let withdraw = tree.module_of(Path::new("programs/vault/src/./instructions/withdraw.rs")).unwrap();
+        assert_eq!(withdraw.crate_root, Path::new("programs/vault/src/lib.rs"));
+    }
+
+    #[test]
+    fn test_module_tree_marks_test_modules() {
+        let files = parse(&[
+            ("src/main.rs", "mod processor; #[cfg(test)] mod tests { mod helpers; }"),
+            ("src/processor.rs", "#[cfg(test)] #[path = \"processor_tests.rs\"] mod tests;"),
+            ("src/processor_tests.rs", ""),
+            ("src/tests/helpers.rs", ""),
+        ]);
+        let tree = ModuleTree::build(files.iter().map(|(path, ast)| (path.as_path(), ast)));
+
+        let test = |file: &str| tree.module_of(Path::new(file)).unwrap().test;
+        assert!(!test("src/processor.rs"));
+        assert!(test("src/processor_tests.rs"));
+        assert!(test("src/tests/helpers.rs"));
+        assert_eq!(module(&tree, "src/tests/helpers.rs").as_deref(), Some("crate::tests::helpers"));
+    }
+
+    #[test]
+    fn test_test_attributes() {
+        let is_test = |attribute: &str| {
+            let item: syn::ItemFn = syn::parse_str(&format!("{attribute} fn f() {{}}")).unwrap();
+            is_test_attribute(&item.attrs[0])
+        };
+
+        assert!(is_test("#[test]"));
+        assert!(is_test("#[cfg(test)]"));
+        assert!(is_test("#[cfg(all(test, feature = \"bench\"))]"));
+        assert!(is_test("#[cfg(any(test, all(test, unix)))]"));
+        assert!(!is_test("#[cfg(not(test))]"));
+        assert!(!is_test("#[cfg(any(test, feature = \"testing\"))]"));
+        assert!(!is_test("#[cfg(all(feature = \"test-utils\", not(test)))]"));
+        assert!(!is_test("#[cfg(target_os = \"solana\")]"));
+        assert!(!is_test("#[inline]"));
+    }
+}
@@ -1,15 +1,197 @@
 use anyhow::{Context, Result};
-use log::{error, info};
+use log::{debug, error};
+#[cfg(feature = "native")]
+use log::warn;
 use std::fs;
 use std::path::{Path, PathBuf};
+#[cfg(feature = "native")]
 use walkdir::WalkDir;
 
+/// Rust source file read and parsed once, kept with the text its AST was parsed from
+///
+/// Analyzing the text the AST was parsed from keeps locations and fixes consistent even if the
+/// file changes on disk during the analysis, and lets in-memory sources (stdin, editor buffers)
+/// be analyzed like files.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    /// Path of the file (a virtual path for in-memory sources)
+    pub path: PathBuf,
+    /// Source code of the file
+    pub source: String,
+    /// AST parsed from the source code (empty if the source could not be parsed)
+    pub ast: syn::File,
+    /// Why the source could not be parsed (see [`SourceFile::load`])
+    pub parse_error: Option<ParseError>,
+    /// Lines of code of the source (see [`count_lines_of_code`])
+    pub lines_of_code: usize,
+}
+
+/// Rust source file read but not parsed yet
+///
+/// Directory walks give their files as text: [`crate::analyzer::Analyzer::analyze_files`] parses
+/// them once, crate by crate, and [`SourceText::parse`] parses them for other uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceText {
+    /// Path of the file (a virtual path for in-memory sources)
+    pub path: PathBuf,
+    /// Source code of the file
+    pub source: String,
+}
+
+impl SourceText {
+    /// Read a Rust file
+    pub fn read(path: &Path) -> Result<Self> {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read file {}", path.display()))?;
+        Ok(Self { path: path.to_path_buf(), source })
+    }
+
+    /// Parse the source code like [`SourceFile::from_source`], keeping its parse error
+    pub fn parse(self) -> SourceFile {
+        SourceFile::from_source(self.path, self.source)
+    }
+}
+
+impl From<SourceFile> for SourceText {
+    fn from(file: SourceFile) -> Self {
+        Self { path: file.path, source: file.source }
+    }
+}
+
+impl From<&SourceFile> for SourceText {
+    fn from(file: &SourceFile) -> Self {
+        Self { path: file.path.clone(), source: file.source.clone() }
+    }
+}
+
+impl From<&SourceText> for SourceText {
+    fn from(file: &SourceText) -> Self {
+        file.clone()
+    }
+}
+
+/// Error of syn on a source it could not parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Error message
+    pub message: String,
+    /// Line of the error (1-indexed)
+    pub line: usize,
+    /// Column of the error (0-indexed)
+    pub column: usize,
+}
+
+impl From<&syn::Error> for ParseError {
+    fn from(error: &syn::Error) -> Self {
+        let start = error.span().start();
+        Self {
+            message: error.to_string(),
+            // Errors at the end of the input have no position
+            line: start.line.max(1),
+            column: start.column,
+        }
+    }
+}
+
+impl SourceFile {
+    /// Read and parse a Rust file
+    pub fn read(path: &Path) -> Result<Self> {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read file {}", path.display()))?;
+        Self::parse(path, source)
+    }
+
+    /// Parse Rust source code held in memory, reported under the given path
+    pub fn parse(path: impl Into<PathBuf>, source: String) -> Result<Self> {
+        let path = path.into();
+        let ast = parse_rust_code(&source).with_context(|| format!("Failed to parse file {}", path.display()))?;
+        let lines_of_code = count_lines_of_code(&source);
+        Ok(Self { path, source, ast, parse_error: None, lines_of_code })
+    }
+
+    /// Read a Rust file, keeping it with its parse error (and an empty AST) if syn cannot parse it,
+    /// so the analysis can report it instead of silently skipping it
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read file {}", path.display()))?;
+        Ok(Self::from_source(path, source))
+    }
+
+    /// Parse Rust source code held in memory like [`SourceFile::load`], keeping its parse error
+    /// instead of failing
+    pub fn from_source(path: impl Into<PathBuf>, source: String) -> Self {
+        let (ast, parse_error) = match syn::parse_file(&source) {
+            Ok(ast) => (ast, None),
+            Err(e) => (syn::File { shebang: None, attrs: Vec::new(), items: Vec::new() }, Some(ParseError::from(&e))),
+        };
+        let lines_of_code = count_lines_of_code(&source);
+        Self { path: path.into(), source, ast, parse_error, lines_of_code }
+    }
+}
+
+/// Count the lines of Rust source code with code on them, leaving out the blank lines and the
+/// lines with only comments (line, block and doc comments)
+///
+/// Lines inside string literals count as code; raw strings are not recognized, so comment
+/// markers inside them can be miscounted.
+pub fn count_lines_of_code(source: &str) -> usize {
+    // Nesting depth of the block comment, block comments can span lines and nest
+    let mut comment_depth = 0;
+    let mut in_string = false;
+    let mut count = 0;
+    for line in source.lines() {
+        let mut code = in_string;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if comment_depth > 0 {
+                match (c, chars.peek()) {
+                    ('*', Some('/')) => {
+                        chars.next();
+                        comment_depth -= 1;
+                    }
+                    ('/', Some('*')) => {
+                        chars.next();
+                        comment_depth += 1;
+                    }
+                    _ => {}
+                }
+            } else if in_string {
+                match c {
+                    '\\' => {
+                        chars.next();
+                    }
+                    '"' => in_string = false,
+                    _ => {}
+                }
+            } else {
+                match (c, chars.peek()) {
+                    ('/', Some('/')) => break,
+                    ('/', Some('*')) => {
+                        chars.next();
+                        comment_depth += 1;
+                    }
+                    // The quote of '"' and the escape of '\'' do not open a string
+                    ('\'', Some('"' | '\\')) => {
+                        code = true;
+                        chars.next();
+                        chars.next();
+                    }
+                    _ if c.is_whitespace() => {}
+                    _ => {
+                        code = true;
+                        in_string = c == '"';
+                    }
+                }
+            }
+        }
+        count += usize::from(code);
+    }
+    count
+}
+
 /// Parse a Rust file and return the AST
 pub fn parse_rust_file(path: &Path) -> Result<syn::File> {
-    let content = fs::read_to_string(path)
-        .with_context(|| format!("Failed to read file {}", path.display()))?;
-
-    parse_rust_code(&content).with_context(|| format!("Failed to parse file {}", path.display()))
+    SourceFile::read(path).map(|file| file.ast)
 }
 
 /// Parse a string of Rust code and return the AST
@@ -18,28 +200,81 @@ pub fn parse_rust_code(content: &str) -> Result<syn::File> {
         .map_err(|e| anyhow::anyhow!("Failed to parse Rust code: {}", e))
 }
 
-/// Process a directory and return a vector of (path, AST) pairs
-pub fn process_directory(dir_path: &Path) -> Vec<(PathBuf, syn::File)> {
-    let mut results = Vec::new();
+/// Process a directory and return its Rust files, parsed
+#[cfg(feature = "native")]
+pub fn process_directory(dir_path: &Path) -> Vec<SourceFile> {
+    process_directory_excluding(dir_path, |_| false)
+}
+
+/// Process a directory, skipping the files and directories for which `is_excluded` returns
+/// true (called with the path relative to `dir_path`)
+#[cfg(feature = "native")]
+pub fn process_directory_excluding<F>(dir_path: &Path, is_excluded: F) -> Vec<SourceFile>
+where
+    F: Fn(&Path) -> bool,
+{
+    let results: Vec<SourceFile> = walk_directory_excluding(dir_path, is_excluded)
+        .map(SourceText::parse)
+        .filter(|file| match &file.parse_error {
+            Some(e) => {
+                warn!("Failed to parse file {}: {}", file.path.display(), e.message);
+                false
+            }
+            None => true,
+        })
+        .collect();
+    debug!("Processed {} Rust files", results.len());
+    results
+}
 
-    for entry in WalkDir::new(dir_path)
+/// Walk a directory like [`process_directory_excluding`], reading each Rust file only when the
+/// iteration reaches it, without parsing it
+///
+/// Files come in the order of their paths (sorted by name in each directory), whatever the
+/// filesystem order. Files that cannot be read are skipped.
+///
+/// Consumers that do not keep the files (e.g. [`crate::analyzer::Analyzer::analyze_files`])
+/// never hold every source of the directory at once.
+#[cfg(feature = "native")]
+pub fn walk_directory_excluding<F>(dir_path: &Path, is_excluded: F) -> impl Iterator<Item = SourceText>
+where
+    F: Fn(&Path) -> bool,
+{
+    WalkDir::new(dir_path)
         .follow_links(true)
+        .sort_by_file_name()
         .into_iter()
+        .filter_entry(move |entry| {
+            let excluded = entry
+                .path()
+                .strip_prefix(dir_path)
+                .is_ok_and(&is_excluded);
+            if excluded {
+                debug!("Skipping excluded path {}", entry.path().display());
+            }
+            !excluded
+        })
         .filter_map(std::result::Result::ok)
-    {
-        let path = entry.path();
-
         // Only process Rust files
-        if path.is_file() && path.extension().is_some_and(|ext| ext == "rs") {
-            match parse_rust_file(path) {
-                Ok(ast) => {
-                    info!("Successfully parsed file {}", path.display());
-                    results.push((path.to_path_buf(), ast));
-                }
-                Err(e) => error!("Failed to parse file {}: {}", path.display(), e),
-            }
+        .filter(|entry| entry.path().is_file() && entry.path().extension().is_some_and(|ext| ext == "rs"))
+        .filter_map(|entry| read_logged(entry.path()))
+}
+
+/// Read the Rust files at these paths, in their order, like [`walk_directory_excluding`]
+pub fn load_files(paths: Vec<PathBuf>) -> impl Iterator<Item = SourceText> {
+    paths.into_iter().filter_map(|path| read_logged(&path))
+}
+
+/// Read a Rust file, logging why it cannot be read (`None` then)
+fn read_logged(path: &Path) -> Option<SourceText> {
+    match SourceText::read(path) {
+        Ok(file) => {
+            debug!("Read file {}", path.display());
+            Some(file)
+        }
+        Err(e) => {
+            error!("Failed to read file {}: {}", path.display(), e);
+            None
         }
     }
-    info!("Processed {} Rust files", results.len());
-    results
 }
@@ -0,0 +1,54 @@
+//! Static analyzer for Solana/Anchor contracts in Rust
+//!
+//! The `rust-solana-analyzer` binary is a thin CLI over this library. Downstream crates
+//! can depend on it to run the analyzer from their own binary and compose their own rule
+//! sets with the built-in rules through `analyzer::RulePlugin`.
+//!
+//! [`analyze_project`] analyzes a project directory the way the binary does, and returns
+//! its programs, files, findings and statistics:
+//!
+//! ```no_run
+//! use rust_solana_analyzer::{analyze_project, ProjectOptions};
+//!
+//! let project = analyze_project("programs".as_ref(), ProjectOptions::default())?;
+//! for finding in &project.result.findings {
+//!     println!("{:?} {}:{} {}", finding.severity, finding.location.file, finding.location.line, finding.description);
+//! }
+//! # Ok::<(), rust_solana_analyzer::project::ProjectError>(())
+//! ```
+//!
+//! The types of the public API are re-exported here: the [`Analyzer`] and its
+//! [`AnalysisOptions`] to analyze files already read, the [`Finding`]s of an
+//! [`AnalysisResult`] (all serializable with serde, findings deserializable too), the
+//! [`dsl`] to write rules, and the report [`Format`]s rendered by a [`Renderer`]:
+//!
+//! ```
+//! use rust_solana_analyzer::{AnalysisOptions, Analyzer, Format, Renderer, Severity};
+//! use rust_solana_analyzer::ast::parser::SourceFile;
+//!
+//! let file = SourceFile::parse("src/lib.rs", "pub fn share(a: u64, b: u64) -> u64 { a / b }".to_string())?;
+//! let result = Analyzer::with_options(AnalysisOptions::default()).analyze_files(&[file])?;
+//! let high = result.count_at_least(Some(&Severity::High));
+//! let json = serde_json::to_string(&result)?;
+//! let markdown = Renderer::new(&result, ".", ".".as_ref()).render(Format::Markdown);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Everything else stays reachable through its module (`analyzer::sarif`, `analyzer::inventory`).
+
+pub mod analyzer;
+pub mod ast;
+#[cfg(feature = "native")]
+pub mod lsp;
+pub mod playground;
+#[cfg(feature = "native")]
+pub mod project;
+
+pub use analyzer::dsl;
+pub use analyzer::output::{Format, Renderer};
+pub use analyzer::{
+    AnalysisOptions, AnalysisResult, AnalysisStats, Analyzer, Applicability, Category, Confidence, Finding, Fix,
+    Location, RuleExample, RuleInfo, RuleMetadata, RulePlugin, RuleType, Severity, TextEdit,
+};
+#[cfg(feature = "native")]
+pub use project::{analyze_project, analyze_sources, ProjectAnalysis, ProjectOptions};
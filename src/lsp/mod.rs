@@ -0,0 +1,497 @@
+//! Language server (`--lsp`) publishing the findings of the analyzer as diagnostics
+//!
+//! The server speaks JSON-RPC over stdin/stdout with the `Content-Length` framing of the
+//! Language Server Protocol. When a document is opened or saved, the crate of the workspace it
+//! belongs to is analyzed again, with the open documents read from their editor buffers, and
+//! the diagnostics of its open documents are published (`textDocument/publishDiagnostics`).
+//! Unchanged files reuse the results of the analysis cache, so only the edited files run the
+//! file rules again. The fixes of the findings are offered as quick fixes
+//! (`textDocument/codeAction`).
+
+use log::{debug, info, warn};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::config::{Config, PathFilter};
+use crate::analyzer::ignore::Excluder;
+use crate::analyzer::{AnalysisOptions, Analyzer, Applicability, Finding, Location, Severity};
+use crate::ast::parser::{self, SourceText};
+use crate::project::{self, ProjectError, ProjectOptions};
+
+#[cfg(test)]
+mod test;
+
+/// Name of the server, the source of its diagnostics
+pub const SERVER_NAME: &str = "solana-analyzer";
+
+/// JSON-RPC error code of an unknown method
+const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code of a request after the shutdown
+const INVALID_REQUEST: i64 = -32600;
+/// JSON-RPC error code of invalid parameters
+const INVALID_PARAMS: i64 = -32602;
+
+/// Serve the project directory at `root` over stdin/stdout until the client exits
+pub fn run(root: &Path, options: ProjectOptions<'_>) -> Result<(), ProjectError> {
+    let mut server = Server::new(root, options)?;
+    let stdin = io::stdin();
+    server.serve(stdin.lock(), io::stdout()).map_err(|e| ProjectError::Analysis(format!("language server: {e}")))
+}
+
+/// Language server of a project directory
+pub struct Server {
+    /// Analyzed directory
+    root: PathBuf,
+    /// Analysis options of the project (configuration and CLI flags)
+    options: AnalysisOptions,
+    /// Excluded paths of the configuration (the ignore files are read at each analysis)
+    path_filter: PathFilter,
+    /// Open documents by path, with the text of their editor buffers
+    documents: BTreeMap<PathBuf, Document>,
+    /// Findings of the last analysis of each file, for the code actions
+    findings: BTreeMap<PathBuf, Vec<Finding>>,
+    /// Whether the client asked the server to shut down
+    shutdown: bool,
+}
+
+/// Document open in the editor
+#[derive(Debug, Clone)]
+struct Document {
+    /// URI of the document, as sent by the client
+    uri: String,
+    /// Text of the editor buffer
+    text: String,
+}
+
+/// LSP `Diagnostic`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    /// Span of the finding
+    pub range: Range,
+    /// `1` (error) to `4` (hint), see [`severity`]
+    pub severity: u8,
+    /// Rule that reported the finding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// First reference of the rule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_description: Option<CodeDescription>,
+    /// Always [`SERVER_NAME`]
+    pub source: &'static str,
+    /// Description of the finding, with the IDs of the rules that reported it
+    pub message: String,
+    /// Code changed by the fixes of the finding
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub related_information: Vec<RelatedInformation>,
+}
+
+/// LSP `CodeDescription`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CodeDescription {
+    pub href: String,
+}
+
+/// LSP `DiagnosticRelatedInformation`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RelatedInformation {
+    pub location: LspLocation,
+    pub message: String,
+}
+
+/// LSP `Location`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LspLocation {
+    pub uri: String,
+    pub range: Range,
+}
+
+/// LSP `Range`, the end is exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// LSP `Position` (0-indexed line, character in UTF-16 code units)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+impl Server {
+    /// Creates the server of the project directory at `root`, with the configuration and the
+    /// analysis options `analyze_project` would use
+    pub fn new(root: &Path, options: ProjectOptions<'_>) -> Result<Self, ProjectError> {
+        // Documents are identified by absolute paths
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let (config, mut options) = project::analysis_options(&root, options)?;
+        options.project_root = Some(root.clone());
+        Self::with_options(root, &config, options)
+    }
+
+    /// Creates the server of the project directory at `root` with these analysis options
+    pub fn with_options(root: PathBuf, config: &Config, options: AnalysisOptions) -> Result<Self, ProjectError> {
+        Ok(Self {
+            root,
+            options,
+            path_filter: config.path_filter()?,
+            documents: BTreeMap::new(),
+            findings: BTreeMap::new(),
+            shutdown: false,
+        })
+    }
+
+    /// Serve the messages of `input` until the client exits, writing the responses and the
+    /// notifications to `output`
+    pub fn serve<R: BufRead, W: Write>(&mut self, mut input: R, mut output: W) -> io::Result<()> {
+        while let Some(message) = read_message(&mut input)? {
+            let method = message["method"].as_str().unwrap_or_default();
+            if method == "exit" {
+                info!("Language server exiting");
+                break;
+            }
+            for reply in self.handle(&message) {
+                write_message(&mut output, &reply)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle a request or a notification, returning the messages to send back
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let id = message.get("id").cloned();
+        debug!("Language server received {method}");
+
+        let result = match method {
+            _ if self.shutdown => Err((INVALID_REQUEST, format!("{method} after shutdown"))),
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    // Full text of the documents on each change
+                    "textDocumentSync": { "openClose": true, "change": 1, "save": { "includeText": false } },
+                    "codeActionProvider": { "codeActionKinds": ["quickfix"] },
+                },
+                "serverInfo": { "name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                return match (document["uri"].as_str(), document["text"].as_str()) {
+                    (Some(uri), Some(text)) => {
+                        let path = uri_to_path(uri);
+                        self.documents.insert(path.clone(), Document { uri: uri.to_string(), text: text.to_string() });
+                        self.analyze(&path)
+                    }
+                    _ => Vec::new(),
+                };
+            }
+            "textDocument/didChange" => {
+                // Only the buffer changes, the crate is analyzed again when it is saved
+                let path = uri_to_path(params["textDocument"]["uri"].as_str().unwrap_or_default());
+                let text = params["contentChanges"].as_array().and_then(|changes| changes.last()).and_then(|change| change["text"].as_str());
+                if let (Some(document), Some(text)) = (self.documents.get_mut(&path), text) {
+                    document.text = text.to_string();
+                }
+                return Vec::new();
+            }
+            "textDocument/didSave" => {
+                let path = uri_to_path(params["textDocument"]["uri"].as_str().unwrap_or_default());
+                if let (Some(document), Some(text)) = (self.documents.get_mut(&path), params["text"].as_str()) {
+                    document.text = text.to_string();
+                }
+                return self.analyze(&path);
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                self.documents.remove(&uri_to_path(uri));
+                return vec![publish(uri, Vec::new())];
+            }
+            "textDocument/codeAction" => match serde_json::from_value::<RangeParams>(params["range"].clone()) {
+                Ok(range) => Ok(self.code_actions(params["textDocument"]["uri"].as_str().unwrap_or_default(), range.into())),
+                Err(e) => Err((INVALID_PARAMS, format!("invalid range: {e}"))),
+            },
+            _ => Err((METHOD_NOT_FOUND, format!("method not found: {method}"))),
+        };
+
+        // Notifications get no response, the unknown ones are ignored
+        let Some(id) = id else {
+            return Vec::new();
+        };
+        vec![match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }),
+        }]
+    }
+
+    /// Analyze the crate of the file at `path` again, returning the diagnostics of its open
+    /// documents
+    fn analyze(&mut self, path: &Path) -> Vec<Value> {
+        // The files of the crate of the workspace the file belongs to, or the whole project
+        let dir = self
+            .options
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.crate_of(path))
+            .map(|manifest_crate| manifest_crate.dir.clone())
+            .unwrap_or_else(|| self.root.clone());
+        let dir = dir.canonicalize().unwrap_or(dir);
+        if !path.starts_with(&dir) {
+            debug!("{} is outside of the project, not analyzed", path.display());
+            return Vec::new();
+        }
+
+        let root = &self.root;
+        let excluder = Excluder::new(root, PathFilter::default(), self.path_filter.clone(), true);
+        let is_excluded = |file: &Path| dir.join(file).strip_prefix(root).is_ok_and(|file| excluder.is_excluded(file));
+        // The open documents are analyzed from their buffers, even before they are saved
+        let mut files: Vec<SourceText> = parser::walk_directory_excluding(&dir, is_excluded)
+            .map(|file| match self.documents.get(&file.path) {
+                Some(document) => SourceText { path: file.path, source: document.text.clone() },
+                None => file,
+            })
+            .collect();
+        for (document_path, document) in &self.documents {
+            if document_path.starts_with(&dir) && !files.iter().any(|file| &file.path == document_path) {
+                files.push(SourceText { path: document_path.clone(), source: document.text.clone() });
+            }
+        }
+
+        info!("Analyzing {} files of {}", files.len(), dir.display());
+        let result = match Analyzer::with_options(self.options.clone()).analyze_files(&files) {
+            Ok(result) => result,
+            Err(e) => {
+                warn!("Analysis of {} failed: {e}", dir.display());
+                return Vec::new();
+            }
+        };
+
+        self.findings.retain(|file, _| !file.starts_with(&dir));
+        for finding in result.findings {
+            self.findings.entry(PathBuf::from(&finding.location.file)).or_default().push(finding);
+        }
+        self.documents
+            .iter()
+            .filter(|(document_path, _)| document_path.starts_with(&dir))
+            .map(|(document_path, document)| {
+                let findings = self.findings.get(document_path).map(Vec::as_slice).unwrap_or_default();
+                publish(&document.uri, findings.iter().map(|finding| self.diagnostic(finding)).collect())
+            })
+            .collect()
+    }
+
+    /// Diagnostic of a finding
+    pub fn diagnostic(&self, finding: &Finding) -> Diagnostic {
+        let mut message = finding.description.clone();
+        if finding.rules.len() > 1 {
+            message.push_str(&format!(" [{}]", finding.rule_ids().join(", ")));
+        }
+        let related_information = finding
+            .fixes
+            .iter()
+            .flat_map(|fix| fix.edits.iter().map(move |edit| (fix, edit)))
+            .filter(|(_, edit)| edit.location != finding.location)
+            .map(|(fix, edit)| RelatedInformation {
+                location: LspLocation { uri: path_to_uri(Path::new(&edit.location.file)), range: self.range(&edit.location) },
+                message: fix.description.clone(),
+            })
+            .collect();
+
+        Diagnostic {
+            range: self.range(&finding.location),
+            severity: severity(&finding.severity),
+            code: finding.rule().map(|rule| rule.id.clone()),
+            code_description: finding
+                .rule()
+                .and_then(|rule| rule.references.iter().find(|reference| reference.starts_with("http")))
+                .map(|href| CodeDescription { href: href.clone() }),
+            source: SERVER_NAME,
+            message,
+            related_information,
+        }
+    }
+
+    /// Quick fixes of the findings of a document whose span meets `range`
+    fn code_actions(&self, uri: &str, range: Range) -> Value {
+        let findings = self.findings.get(&uri_to_path(uri)).map(Vec::as_slice).unwrap_or_default();
+        let actions: Vec<Value> = findings
+            .iter()
+            .filter(|finding| {
+                let span = self.range(&finding.location);
+                span.start <= range.end && range.start <= span.end
+            })
+            .flat_map(|finding| {
+                let diagnostic = self.diagnostic(finding);
+                finding.fixes.iter().map(move |fix| {
+                    // Edits of the fix by document
+                    let mut changes: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+                    for edit in &fix.edits {
+                        changes
+                            .entry(path_to_uri(Path::new(&edit.location.file)))
+                            .or_default()
+                            .push(json!({ "range": self.range(&edit.location), "newText": edit.replacement }));
+                    }
+                    json!({
+                        "title": fix.description,
+                        "kind": "quickfix",
+                        "diagnostics": [diagnostic],
+                        "isPreferred": fix.applicability == Applicability::MachineApplicable,
+                        "edit": { "changes": changes },
+                    })
+                })
+            })
+            .collect();
+        Value::Array(actions)
+    }
+
+    /// LSP range of a location, up to the end of its line without an end
+    ///
+    /// The parser's lines start at 1 and LSP's at 0. Its columns count characters, which are
+    /// converted to UTF-16 code units with the text of the line.
+    fn range(&self, location: &Location) -> Range {
+        let text = self.text(&location.file).unwrap_or_default();
+        let lines: Vec<&str> = text.lines().collect();
+        let position = |line: usize, column: Option<usize>| {
+            let text = lines.get(line.saturating_sub(1)).copied().unwrap_or_default();
+            Position {
+                line: line.saturating_sub(1) as u32,
+                character: match column {
+                    Some(column) => text.chars().take(column).map(char::len_utf16).sum::<usize>() as u32,
+                    None => text.encode_utf16().count() as u32,
+                },
+            }
+        };
+        let start = position(location.line, Some(location.column.unwrap_or_default()));
+        let end = match location.end_line {
+            Some(end_line) => position(end_line, location.end_column),
+            None => position(location.line, None),
+        };
+        Range { start, end: end.max(start) }
+    }
+
+    /// Text of a file: its editor buffer if it is open, its contents on disk otherwise
+    fn text(&self, file: &str) -> Option<Cow<'_, str>> {
+        match self.documents.get(Path::new(file)) {
+            Some(document) => Some(Cow::Borrowed(&document.text)),
+            None => std::fs::read_to_string(file).ok().map(Cow::Owned),
+        }
+    }
+}
+
+/// Range of the parameters of a request
+#[derive(Debug, serde::Deserialize)]
+struct RangeParams {
+    start: PositionParams,
+    end: PositionParams,
+}
+
+/// Position of the parameters of a request
+#[derive(Debug, serde::Deserialize)]
+struct PositionParams {
+    line: u32,
+    character: u32,
+}
+
+impl From<RangeParams> for Range {
+    fn from(range: RangeParams) -> Self {
+        Range {
+            start: Position { line: range.start.line, character: range.start.character },
+            end: Position { line: range.end.line, character: range.end.character },
+        }
+    }
+}
+
+/// `textDocument/publishDiagnostics` notification of a document
+fn publish(uri: &str, diagnostics: Vec<Diagnostic>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+/// LSP severity of a finding: error for high findings, warning for medium ones, information
+/// for low ones and hint for informational ones
+pub fn severity(severity: &Severity) -> u8 {
+    match severity {
+        Severity::High => 1,
+        Severity::Medium => 2,
+        Severity::Low => 3,
+        Severity::Informational => 4,
+    }
+}
+
+/// Read a message framed with its `Content-Length` header, `None` at the end of the input
+pub fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("Content-Length")
+        {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let Some(length) = length else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "message without Content-Length"));
+    };
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body).map(Some).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Write a message framed with its `Content-Length` header
+pub fn write_message<W: Write>(output: &mut W, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    output.flush()
+}
+
+/// Path of a `file://` URI, with its percent-encoded bytes decoded
+pub fn uri_to_path(uri: &str) -> PathBuf {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], bytes.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    PathBuf::from(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+/// `file://` URI of a path, with the bytes outside of the unreserved characters percent-encoded
+pub fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
@@ -0,0 +1,92 @@
+use crate::analyzer::config::Config;
+use crate::analyzer::{AnalysisOptions, Location, RuleInfo, Severity};
+use crate::lsp::{path_to_uri, read_message, severity, uri_to_path, write_message, Position, Range, Server};
+use crate::analyzer::testing::FindingBuilder;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::{Path, PathBuf};
+
+    fn server() -> Server {
+        Server::with_options(PathBuf::from("/project"), &Config::default(), AnalysisOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_message_framing() {
+        let mut output = Vec::new();
+        write_message(&mut output, &json!({ "jsonrpc": "2.0", "id": 1, "result": null })).unwrap();
+        let text = String::from_utf8(output.clone()).unwrap();
+        assert_eq!(text, "Content-Length: 38\r\n\r\n{\"id\":1,\"jsonrpc\":\"2.0\",\"result\":null}");
+
+        // Other headers are ignored
+        let mut input = b"Content-Type: application/vscode-jsonrpc; charset=utf-8\r\n".to_vec();
+        input.extend(output);
+        let mut input = input.as_slice();
+        assert_eq!(read_message(&mut input).unwrap().unwrap()["id"], 1);
+        assert!(read_message(&mut input).unwrap().is_none());
+
+        assert!(read_message(&mut b"\r\n{}".as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_uris() {
+        let path = Path::new("/home/dev/my vault/src/lib.rs");
+        let uri = path_to_uri(path);
+        assert_eq!(uri, "file:///home/dev/my%20vault/src/lib.rs");
+        assert_eq!(uri_to_path(&uri), path);
+        assert_eq!(uri_to_path("file:///c%3A/src/lib.rs"), Path::new("/c:/src/lib.rs"));
+    }
+
+    #[test]
+    fn test_severities() {
+        assert_eq!(severity(&Severity::High), 1);
+        assert_eq!(severity(&Severity::Medium), 2);
+        assert_eq!(severity(&Severity::Low), 3);
+        assert_eq!(severity(&Severity::Informational), 4);
+    }
+
+    #[test]
+    fn test_diagnostic_ranges() {
+        let mut server = server();
+        server.handle(&json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+            "textDocument": { "uri": "file:///elsewhere/lib.rs", "text": "let a = 1;\nlet é = \"😀\" / b;\n" },
+        } }));
+
+        let finding = |location: Location| {
+            FindingBuilder::new("Division by zero")
+                .severity(Severity::High)
+                .location(location)
+                .rule(RuleInfo {
+                    id: "division-by-zero".to_string(),
+                    references: vec!["CWE-369".to_string(), "https://example.com/division-by-zero".to_string()],
+                    ..Default::default()
+                })
+                .build()
+        };
+        // Columns count characters, LSP counts UTF-16 code units
+        let diagnostic = server.diagnostic(&finding(Location::new_precise("/elsewhere/lib.rs".to_string(), 2, Some(8), Some(2), Some(16))));
+        assert_eq!(
+            diagnostic.range,
+            Range { start: Position { line: 1, character: 8 }, end: Position { line: 1, character: 17 } }
+        );
+        assert_eq!((diagnostic.severity, diagnostic.source), (1, "solana-analyzer"));
+        assert_eq!(diagnostic.code.as_deref(), Some("division-by-zero"));
+        // The first reference that is a link
+        assert_eq!(diagnostic.code_description.unwrap().href, "https://example.com/division-by-zero");
+
+        // Without an end, up to the end of the line
+        let diagnostic = server.diagnostic(&finding(Location::new_precise("/elsewhere/lib.rs".to_string(), 2, Some(4), None, None)));
+        assert_eq!(diagnostic.range.end, Position { line: 1, character: 17 });
+    }
+
+    #[test]
+    fn test_requests_after_shutdown() {
+        let mut server = server();
+        let replies = server.handle(&json!({ "jsonrpc": "2.0", "id": 1, "method": "shutdown" }));
+        assert_eq!(replies, vec![json!({ "jsonrpc": "2.0", "id": 1, "result": null })]);
+        let replies = server.handle(&json!({ "jsonrpc": "2.0", "id": 2, "method": "initialize" }));
+        assert_eq!(replies[0]["error"]["code"], -32600);
+    }
+}
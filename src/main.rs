@@ -1,27 +1,63 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use log::{debug, error, info, warn};
-use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-mod analyzer;
-mod ast;
+use rust_solana_analyzer::{analyzer, project};
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, subcommand_negates_reqs = true)]
 struct Cli {
-    /// Contract path or directory to analyze
-    #[arg(short, long)]
+    /// Directory, Rust file or glob of Rust files to analyze (the project served with --lsp,
+    /// default: .), or - to analyze the code read from stdin
+    #[arg(short, long, required_unless_present_any = ["lsp", "staged", "anchor"], default_value = ".", hide_default_value = true)]
     path: PathBuf,
 
-    /// Custom templates path
+    /// Configuration file, applied on top of the project's solana-analyzer.toml
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Custom templates path, or URL of a remote rule pack: a git repository (.git) or a .tar.gz
+    /// archive, pinned and verified by its fragment (#<ref>&commit=<sha>&signed, #sha256=<hex>)
     #[arg(short, long)]
     templates: Option<PathBuf>,
 
-    /// Output file path
+    /// WebAssembly rule plugins path (`*.wasm`)
+    #[arg(long)]
+    plugins: Option<PathBuf>,
+
+    /// Advisory table checked along with the embedded one (TOML, same format)
+    #[arg(long, value_name = "FILE")]
+    advisories: Option<PathBuf>,
+
+    /// Output file path (repeatable, paired with the formats in order)
     #[arg(short, long)]
-    output: Option<PathBuf>,
+    output: Vec<PathBuf>,
+
+    /// Report formats separated by commas (markdown, json, html, csv, junit, github, gitlab, rdjson, sarif, audit,
+    /// audit-html, template, ndjson; default: from the extension of the output, markdown otherwise). With a single
+    /// output, each format is written next to it
+    #[arg(long)]
+    format: Option<String>,
+
+    /// Template of --format template (implied without --format): a Handlebars file, or a builtin template
+    /// (markdown, html)
+    #[arg(long, value_name = "FILE")]
+    report_template: Option<PathBuf>,
+
+    /// Escaping of the values inserted by the report template: none, or html (the default of the builtin html
+    /// template)
+    #[arg(long, value_name = "ESCAPE")]
+    template_escape: Option<analyzer::report_templates::Escape>,
+
+    /// Write the findings of --format ndjson while the files are analyzed, as the rules report them (before the
+    /// duplicates are merged and the caps applied)
+    #[arg(long)]
+    stream: bool,
+
+    /// JSON report of a previous run, to report the new, fixed and persisting findings since then
+    #[arg(long, value_name = "FILE")]
+    compare: Option<PathBuf>,
 
     /// Severities to ignore (separated by commas: low,medium,high,informational)
     #[arg(short, long)]
@@ -31,194 +67,1160 @@ struct Cli {
     #[arg(long)]
     ignore_rules: Option<String>,
 
-    /// Generate AST JSON along with the report
+    /// Only run these rule IDs (separated by commas)
+    #[arg(long)]
+    rules: Option<String>,
+
+    /// Only run rules with any of these tags (separated by commas: security,anchor)
+    #[arg(long)]
+    tags: Option<String>,
+
+    /// Rule profile or packs to run (separated by commas: all, anchor, native, pinocchio, steel, or pack names
+    /// such as anchor-core, spl-token, defi, style)
+    #[arg(long)]
+    profile: Option<String>,
+
+    /// Minimum confidence of the reported findings (low, medium, high)
+    #[arg(long)]
+    min_confidence: Option<String>,
+
+    /// Rule severity overrides (<rule-id>=<severity>, can be repeated)
+    #[arg(long = "severity")]
+    severity_overrides: Vec<String>,
+
+    /// Only report findings on the lines changed since this git reference (e.g. origin/main)
+    #[arg(long)]
+    changed_since: Option<String>,
+
+    /// Only analyze the Rust files staged for the next commit, as staged (the pre-commit hook, see
+    /// `hook install`); fails on any finding without --fail-on or --max-findings, and with exit
+    /// code 2 if the analysis cannot run
+    #[arg(long, conflicts_with_all = ["stdin", "lsp", "changed_since", "fix", "fix_dry_run"])]
+    staged: bool,
+
+    /// Analyze the Anchor workspace of --path (the directory of its Anchor.toml) after `anchor build`,
+    /// checking its IDLs and TypeScript types, and write the reports of each program, named after its
+    /// program ID, to the --output directory (default: target/solana-analyzer)
+    #[arg(long, conflicts_with_all = ["stdin", "staged", "lsp", "fix", "fix_dry_run", "stream", "compare"])]
+    anchor: bool,
+
+    /// Number of files analyzed in parallel (default: one per CPU core)
+    #[arg(short, long, default_value_t = 0, hide_default_value = true)]
+    jobs: usize,
+
+    /// Directory caching the results of unchanged files (default: <path>/.analyzer-cache)
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+
+    /// Analyze every file again instead of reusing the cached results
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Do not record the run in the history of the project (<path>/.analyzer/history), which
+    /// `history` and the trend sections of the reports show
+    #[arg(long)]
+    no_history: bool,
+
+    /// Time budget of a rule on a file in milliseconds, slower rules are reported without findings
+    #[arg(long, value_name = "MS")]
+    rule_timeout: Option<u64>,
+
+    /// Keep the duplicate findings reported on the same code instead of merging them
+    #[arg(long)]
+    keep_duplicates: bool,
+
+    /// Report at most this many findings per rule, the others are only counted
+    #[arg(long, value_name = "N")]
+    max_per_rule: Option<usize>,
+
+    /// Report at most this many findings per file (the most severe ones), the others are only counted
+    #[arg(long, value_name = "N")]
+    max_per_file: Option<usize>,
+
+    /// Include this many lines of source before and after each finding in the reports (at most 10)
+    #[arg(long, value_name = "N")]
+    context: Option<usize>,
+
+    /// Leave out the paths matching this glob, relative to --path, along with the paths.exclude of
+    /// the configuration (can be repeated)
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Analyze the paths of the .gitignore and .ignore files too
+    #[arg(long)]
+    no_ignore: bool,
+
+    /// Also analyze the macro-expanded code (cargo expand, or the Anchor macros if it is unavailable)
+    #[arg(long)]
+    expanded: bool,
+
+    /// Also report findings in test code (`#[cfg(test)]` modules, tests/, benches/, migrations/)
+    #[arg(long)]
+    include_tests: bool,
+
+    /// Color the findings printed to the console and the logs (auto, always, never)
+    #[arg(long, value_name = "WHEN", default_value = "auto")]
+    color: String,
+
+    /// Format of the findings printed to the console: human (diagnostics with the code, and a summary)
+    /// or short (one `path:line:col: severity[rule-id]: message` line per finding)
+    #[arg(long, value_name = "FMT", default_value = "human")]
+    message_format: String,
+
+    /// Log more: -v for the debug logs, -vv for the trace logs (RUST_LOG overrides it)
+    #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// Only log the errors, without the progress bar, for scripts and CI jobs reading the report
+    /// (RUST_LOG overrides it)
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Show the execution time, matched files and findings of each rule
+    #[arg(long)]
+    profile_rules: bool,
+
+    /// Print the statistics of the analysis: lines of code, parse failures, rule timings and coverage
+    #[arg(long)]
+    stats: bool,
+
+    /// Write the statistics of the analysis as JSON to this file
+    #[arg(long, value_name = "FILE")]
+    stats_output: Option<PathBuf>,
+
+    /// Write the inventory of the instructions, accounts structs and account state of the programs
+    /// to this file (Markdown for a .md file, JSON otherwise)
+    #[arg(long, value_name = "FILE")]
+    inventory: Option<PathBuf>,
+
+    /// Write the PDA derivations of the programs, with their seeds, bumps and instructions, to
+    /// this file (Markdown for a .md file, JSON otherwise)
+    #[arg(long, value_name = "FILE")]
+    pdas: Option<PathBuf>,
+
+    /// Write the matrix of the instructions and the constraints of their accounts to this file
+    /// (CSV for a .csv file, Markdown otherwise)
+    #[arg(long, value_name = "FILE")]
+    constraint_matrix: Option<PathBuf>,
+
+    /// Write the graph of the programs invoked by each instruction, with the checks of their
+    /// addresses, to this file (DOT for a .dot or .gv file, JSON otherwise)
+    #[arg(long, value_name = "FILE")]
+    cpi_graph: Option<PathBuf>,
+
+    /// Fail (exit code 1) if a finding has this severity or a higher one
+    #[arg(long)]
+    fail_on: Option<String>,
+
+    /// Fail (exit code 1) if there are more findings than this (counting from the --fail-on severity)
+    #[arg(long)]
+    max_findings: Option<usize>,
+
+    /// Rule parameter overrides (<rule-id>.<param>=<value>, can be repeated)
+    #[arg(long = "rule-param")]
+    rule_params: Vec<String>,
+
+    /// Write the AST of each file as JSON, with the spans of its nodes, to --ast-dir
     #[arg(long)]
     ast: bool,
 
+    /// Directory of the AST JSON files, at the paths of the files relative to --path (implies --ast;
+    /// default: <PATH>/.analyzer-ast)
+    #[arg(long, value_name = "DIR")]
+    ast_dir: Option<PathBuf>,
+
+    /// Also write the AST of each crate, its files in module order, as <crate root>.crate.json (implies --ast)
+    #[arg(long)]
+    ast_combined: bool,
+
     /// Analyze vulnerabilities
     #[arg(long)]
     analyze: bool,
+
+    /// Run a language server on stdin/stdout, publishing the findings of the opened and saved files
+    /// as diagnostics with their fixes as quick fixes
+    #[arg(long)]
+    lsp: bool,
+
+    /// Analyze the Rust code read from stdin as a file of the project at --path (default: .), and
+    /// write the findings to stdout
+    #[arg(long, conflicts_with = "lsp")]
+    stdin: bool,
+
+    /// Path of the code read from stdin, relative to the project; it places the code in its crate
+    /// and is the path of its findings (default: stdin.rs)
+    #[arg(long, value_name = "PATH")]
+    stdin_filename: Option<PathBuf>,
+
+    /// Apply the machine-applicable fixes of the findings to the files, then analyze them again
+    /// and report the findings left
+    #[arg(long, conflicts_with_all = ["stdin", "stream", "fix_dry_run"])]
+    fix: bool,
+
+    /// Print the machine-applicable fixes of the findings as a unified diff, without changing the
+    /// files, and check them on an analysis of the fixed code
+    #[arg(long)]
+    fix_dry_run: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-fn main() -> Result<()> {
-    // Initialize logger
-    env_logger::init();
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the documentation of a rule: its description, rationale, vulnerable and safe code
+    /// examples, recommendations and references (the custom rules of the project at --path included)
+    Explain {
+        /// ID of the rule
+        rule_id: String,
+
+        /// Output format (text, md)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+    /// Write a commented solana-analyzer.toml for the project at --path (default: .), with the
+    /// profile of its programs, the build output excluded and a fail-on threshold
+    Init {
+        /// Also write a CI workflow running the analysis (github, gitlab)
+        #[arg(long)]
+        ci: Option<String>,
+
+        /// Overwrite the existing files
+        #[arg(long)]
+        force: bool,
+    },
+    /// Generate the skeleton of a built-in rule (mod.rs, filters.rs, test.rs with fixtures) in
+    /// the analyzer checkout at --path (default: .) and declare its module
+    NewRule {
+        /// ID of the rule (kebab-case)
+        id: String,
 
+        /// Severity of the rule, which is also its directory (high, medium, low, informational)
+        #[arg(long)]
+        severity: String,
+
+        /// Items the rule checks (struct: account structs, function: functions and methods)
+        #[arg(long, default_value = "function")]
+        kind: String,
+    },
+    /// Manage the git pre-commit hook analyzing the staged Rust files of the project at --path
+    /// (default: .) with --staged
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+    /// Analyze the project at --path (default: .) and fail (exit code 1) when the findings of a
+    /// severity exceed its budget ([budgets] of solana-analyzer.toml), printing the budgets exceeded
+    Check {
+        /// Budget overrides (<severity>=<number of findings or unlimited>, can be repeated)
+        #[arg(long)]
+        budget: Vec<String>,
+    },
+    /// Show the finding counts of the runs recorded for the project at --path (default: .) and
+    /// the findings that appeared and disappeared since the previous run
+    History {
+        /// Show when the finding with this fingerprint (or fingerprint prefix) appeared and disappeared
+        #[arg(long, value_name = "FINGERPRINT")]
+        finding: Option<String>,
+
+        /// Number of runs shown
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum HookAction {
+    /// Write the pre-commit hook in the hooks directory of the repository (core.hooksPath if set)
+    Install {
+        /// Overwrite a pre-commit hook not written by the analyzer
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+fn main() -> Result<()> {
     // Parse arguments from command line
-    let args = Cli::parse();
+    let mut args = Cli::parse();
+    // `--path -` reads the code from stdin, in the project of the current directory
+    if args.path.as_os_str() == "-" {
+        args.stdin = true;
+        args.path = PathBuf::from(".");
+    }
+    let color = match args.color.parse::<analyzer::console::ColorChoice>() {
+        Ok(color) => color,
+        Err(e) => anyhow::bail!("Invalid --color: {e}"),
+    };
+    let message_format = match args.message_format.parse::<analyzer::console::MessageFormat>() {
+        Ok(message_format) => message_format,
+        Err(e) => anyhow::bail!("Invalid --message-format: {e}"),
+    };
+
+    // Progress of the analysis on stderr, unless the logs are verbose or only the errors are shown
+    let rust_log = std::env::var_os("RUST_LOG").is_some();
+    let progress = if args.quiet || args.verbose > 0 || rust_log || args.lsp {
+        None
+    } else {
+        analyzer::progress::ProgressBar::stderr()
+    };
+    init_logger(&args, color, rust_log, progress.clone());
     debug!("CLI arguments: {args:?}");
 
-    // Verify that the path exists
-    if !args.path.exists() {
-        anyhow::bail!("Path {} does not exist", args.path.display());
+    match &args.command {
+        Some(Command::Explain { rule_id, format }) => return explain(&args, rule_id, format),
+        Some(Command::Init { ci, force }) => return init(&args.path, ci.as_deref(), *force),
+        Some(Command::NewRule { id, severity, kind }) => return new_rule(&args.path, id, severity, kind),
+        Some(Command::Hook { action: HookAction::Install { force } }) => return install_hook(&args.path, *force),
+        Some(Command::History { finding, limit, format }) => return history(&args.path, finding.as_deref(), *limit, format),
+        Some(Command::Check { .. }) | None => {}
+    }
+
+    // Validate the flags before the analysis (analyze_project validates the configuration)
+    let fail_on = match &args.fail_on {
+        Some(fail_on) => match fail_on.parse::<analyzer::Severity>() {
+            Ok(severity) => Some(severity),
+            Err(e) => anyhow::bail!("Invalid --fail-on: {e}"),
+        },
+        None => None,
+    };
+    // `check` analyzes the project and compares its findings with the budgets
+    let budget_overrides = match &args.command {
+        Some(Command::Check { budget }) => {
+            if args.lsp || args.fix_dry_run {
+                anyhow::bail!("check cannot be used with --lsp or --fix-dry-run");
+            }
+            let overrides = budget.iter().map(|budget| analyzer::budgets::Budgets::parse_override(budget));
+            match overrides.collect::<Result<Vec<_>, _>>() {
+                Ok(overrides) => Some(overrides),
+                Err(e) => anyhow::bail!("Invalid --budget: {e}"),
+            }
+        }
+        _ => None,
+    };
+    if let Some(profile) = &args.profile
+        && let Err(e) = analyzer::packs::resolve_profile(profile)
+    {
+        anyhow::bail!("Invalid profile: {e}");
+    }
+    if let Some(format) = &args.format
+        && let Err(e) = analyzer::output::parse_formats(format)
+    {
+        anyhow::bail!("Invalid --format: {e}");
+    }
+    if let Some(context) = args.context
+        && context > analyzer::MAX_CONTEXT_LINES
+    {
+        anyhow::bail!("Invalid --context: at most {} lines", analyzer::MAX_CONTEXT_LINES);
     }
 
-    // Verify that the path is a directory
-    if !args.path.is_dir() {
-        anyhow::bail!("Path {} is not a directory", args.path.display());
+    // The whole Anchor workspace is analyzed, wherever in it the path is
+    if args.anchor {
+        let Some(root) = analyzer::anchor::find_root(&args.path) else {
+            anyhow::bail!("No {} in {} or above it", analyzer::anchor::ANCHOR_MANIFEST, args.path.display());
+        };
+        info!("Anchor workspace {}", root.display());
+        args.path = root;
     }
 
-    info!("Starting analysis on directory: {}", args.path.display());
-    let results = ast::parser::process_directory(&args.path);
-    info!("Found {} Rust files to analyze", results.len());
+    // Report template, loaded before the analysis so a bad template fails fast
+    let report_template = match &args.report_template {
+        Some(path) => match analyzer::report_templates::ReportTemplate::load(path) {
+            Ok(template) => Some(template),
+            Err(e) => anyhow::bail!("Invalid --report-template: {e}"),
+        },
+        None => None,
+    };
+
+    // With --stream, the NDJSON findings are written while the files are analyzed
+    let finding_sink = if args.stream { Some(stream_sink(&args)?) } else { None };
+
+    // Findings of the previous run, loaded before the analysis so a bad report fails fast
+    let previous = match &args.compare {
+        Some(path) => match analyzer::compare::load_findings(path) {
+            Ok(findings) => Some(findings),
+            Err(e) => anyhow::bail!("Invalid --compare: {e}"),
+        },
+        None => None,
+    };
 
-    if args.ast {
-        for (path, ast) in &results {
-            let json = ast::json::ast_to_json(ast);
-            let mut json_path = path.clone();
-            json_path.set_extension("json");
-            fs::write(json_path, json)?;
-            info!("AST JSON generated for {}", path.display());
+    let mut options = project_options(&args, finding_sink);
+    options.progress_sink = progress.as_ref().map(analyzer::progress::ProgressBar::sink);
+
+    // The language server analyzes the files as they are opened and saved
+    if args.lsp {
+        info!("Starting the language server on {}", args.path.display());
+        rust_solana_analyzer::lsp::run(&args.path, options)?;
+        return Ok(());
+    }
+
+    // The code read from stdin and the staged files are analyzed without touching their files,
+    // and the reports show them
+    let in_memory = if args.stdin {
+        Some(vec![read_stdin(&args)?])
+    } else if args.staged {
+        let staged = read_staged(&args, &options);
+        if staged.is_empty() {
+            info!("No staged Rust files to analyze");
+            return Ok(());
         }
+        Some(staged)
+    } else {
+        None
+    };
+    // Only the runs on a whole project directory are comparable over time
+    let record_history = !args.no_history && in_memory.is_none() && args.changed_since.is_none() && args.path.is_dir();
+    let sources: analyzer::results::Sources = in_memory
+        .iter()
+        .flatten()
+        .map(|file| (file.path.to_string_lossy().to_string(), file.source.clone()))
+        .collect();
+
+    // Without --analyze, the files are only parsed (and their ASTs written)
+    if !args.analyze && in_memory.is_none() && !args.fix && !args.fix_dry_run && !args.anchor && budget_overrides.is_none() {
+        let parsed = project::parse_project(&args.path, &options);
+        progress.iter().for_each(|bar| bar.finish());
+        parsed?;
+        info!("Analysis completed.");
+        return Ok(());
     }
 
-    // Analyze vulnerabilities if requested
-    if args.analyze {
-        info!("Analyzing vulnerabilities");
+    info!("Analyzing vulnerabilities");
+    let analysis = match in_memory {
+        Some(files) => project::analyze_sources(&args.path, files, options),
+        None => project::analyze_project(&args.path, options),
+    };
+    let project = match analysis {
+        Ok(project) => {
+            progress.iter().for_each(|bar| bar.finish());
+            project
+        }
+        // The pre-commit hook must not let the commit through
+        Err(e) if args.staged => {
+            progress.iter().for_each(|bar| bar.finish());
+            error!("Cannot analyze the staged files: {e}");
+            std::process::exit(2);
+        }
+        Err(project::ProjectError::Analysis(e)) => {
+            progress.iter().for_each(|bar| bar.finish());
+            // A failed analysis must not let a pipeline pass
+            error!("Error during analysis: {e}");
+            std::process::exit(2);
+        }
+        Err(e) => return Err(e.into()),
+    };
 
-        // Create analysis options based on CLI arguments
-        let mut options = analyzer::AnalysisOptions::default();
-        options.generate_ast = args.ast;
+    // The fixes are applied before the reports, which show the findings left
+    let project = if args.fix || args.fix_dry_run {
+        let files: Vec<PathBuf> = project.files.iter().map(|file| file.path.clone()).collect();
+        let plan = analyzer::fixes::FixPlan::new(&project.result.findings, Some(&sources));
+        for skipped in &plan.skipped {
+            warn!("Fix of {} ({}:{}) skipped: {}", skipped.fix.rule_id, skipped.fix.file, skipped.fix.line, skipped.reason);
+        }
+        if args.fix_dry_run {
+            print!("{}", plan.to_unified_diff());
+            check_fixes(&args, &plan, &project.result.findings, files, &sources)?;
+            return Ok(());
+        }
+        plan.write()?;
+        info!("Applied {} fixes to {} files", plan.applied.len(), plan.files.len());
+        let mut options = project_options(&args, None);
+        options.progress_sink = progress.as_ref().map(analyzer::progress::ProgressBar::sink);
+        let fixed = project::analyze_project(&args.path, options);
+        progress.iter().for_each(|bar| bar.finish());
+        let fixed = fixed?;
+        log_unresolved_fixes(&plan, &project.result.findings, &fixed.result.findings);
+        fixed
+    } else {
+        project
+    };
 
-        // Set default rule types to include
-        options.include_rule_types = vec![
-            analyzer::RuleType::Solana,
-            analyzer::RuleType::Anchor,
-            analyzer::RuleType::General,
-        ];
+    let fail_on = match fail_on {
+        Some(severity) => Some(severity),
+        None => project.config.fail_on()?,
+    };
+    let max_findings = match args.max_findings.or(project.config.thresholds.max_findings) {
+        // Without thresholds, the pre-commit hook blocks the commit on any finding
+        None if args.staged && fail_on.is_none() => Some(0),
+        max_findings => max_findings,
+    };
+    let budgets = match budget_overrides {
+        Some(overrides) => {
+            let mut budgets = project.config.budgets()?;
+            for (severity, budget) in overrides {
+                budgets.set(severity, budget);
+            }
+            if budgets.is_empty() {
+                anyhow::bail!("No budgets to check: set them in the [budgets] section of solana-analyzer.toml or with --budget");
+            }
+            Some(budgets)
+        }
+        None => None,
+    };
+    let outputs = if args.output.is_empty() {
+        project.config.output.path.clone().into_iter().collect()
+    } else {
+        args.output.clone()
+    };
+    let report_template = match report_template {
+        Some(template) => Some(template),
+        None => project.config.report_template()?,
+    };
+    let report_template = match args.template_escape.or(project.config.template_escape()?) {
+        Some(escape) => report_template.map(|template| template.with_escape(escape)),
+        None => report_template,
+    };
+    let formats = match args.format.as_deref().or(project.config.output_format()?) {
+        Some(format) => analyzer::output::parse_formats(format)?,
+        // A report template alone selects its format
+        None if report_template.is_some() => vec![analyzer::output::Format::Template],
+        None => Vec::new(),
+    };
+    if let Some(template) = &report_template
+        && !formats.contains(&analyzer::output::Format::Template)
+    {
+        warn!("The report template {} is only used by --format template", template.name());
+    }
+    // --anchor writes the reports of each program instead
+    let sinks = if args.anchor {
+        if outputs.len() > 1 {
+            anyhow::bail!("Invalid --output: --anchor writes the reports to one directory");
+        }
+        Vec::new()
+    } else {
+        analyzer::output::sinks(&formats, &outputs).map_err(|e| anyhow::anyhow!("Invalid --output: {e}"))?
+    };
+    let analysis_result = project.result;
+    let comparison = previous.map(|previous| analyzer::compare::Comparison::of(&previous, &analysis_result.findings));
+    let history = if record_history { record_run(&args.path, &analysis_result) } else { Vec::new() };
+
+    // Number of findings over the thresholds, if the analysis fails
+    let failed = analysis_result
+        .exceeds_thresholds(fail_on.as_ref(), max_findings)
+        .then(|| analysis_result.count_at_least(fail_on.as_ref()));
+
+    info!(
+        "Analysis completed: {} findings",
+        analysis_result.findings.len()
+    );
 
-        if let Some(templates) = &args.templates {
-            options.custom_templates_path = Some(templates.to_string_lossy().to_string());
+    // Show summary of findings by severity (High to Informational)
+    for (severity, findings) in analysis_result.by_severity() {
+        info!("- {severity:?}: {}", findings.len());
+    }
+    if analysis_result.stats.findings_by_program.len() > 1 {
+        for (program, count) in &analysis_result.stats.findings_by_program {
+            info!("- In {program}: {count}");
         }
+    }
+    if analysis_result.stats.findings_suppressed > 0 {
+        info!("- Suppressed: {}", analysis_result.stats.findings_suppressed);
+    }
+    if analysis_result.stats.files_cached > 0 {
+        info!(
+            "- Files reused from the cache: {}/{}",
+            analysis_result.stats.files_cached, analysis_result.stats.files_analyzed
+        );
+    }
+    if !analysis_result.stats.exclusions.is_empty() {
+        info!("- Paths excluded: {} (see --stats)", analysis_result.stats.exclusions.len());
+    }
+    if analysis_result.stats.files_unparsed > 0 {
+        info!("- Files not analyzed (parse errors): {}", analysis_result.stats.files_unparsed);
+    }
+    if analysis_result.stats.test_files_skipped > 0 {
+        info!("- Test files skipped: {} (see --include-tests)", analysis_result.stats.test_files_skipped);
+    }
+    if analysis_result.stats.findings_merged > 0 {
+        info!("- Merged duplicates: {}", analysis_result.stats.findings_merged);
+    }
+    if analysis_result.stats.findings_expanded > 0 {
+        info!("- On expanded code: {}", analysis_result.stats.findings_expanded);
+    }
+    if !analysis_result.stats.findings_omitted.is_empty() {
+        let omitted: usize = analysis_result.stats.findings_omitted.values().sum();
+        info!("- Omitted by the finding caps: {omitted}");
+        for (rule_id, count) in &analysis_result.stats.findings_omitted {
+            info!("  - {rule_id}: {count}");
+        }
+    }
+    for (rule_id, targets) in &analysis_result.stats.timed_out_rules {
+        warn!("- Rule {rule_id} exceeded its time budget on {} file(s), its findings there are missing", targets.len());
+    }
+    if args.profile_rules {
+        log_rule_profiles(&analysis_result.stats);
+    }
+    if let Some(comparison) = &comparison {
+        let summary = comparison.summary();
+        info!("- Since the previous run: {} new, {} fixed, {} persisting", summary.new, summary.fixed, summary.persisting);
+    }
+    if let [.., _, _] = history.as_slice()
+        && let Some(last) = analyzer::history::trend(&history).pop()
+    {
+        info!("- Since the last recorded run: {} new, {} fixed (see history)", last.new.len(), last.fixed.len());
+    }
 
-        if let Some(ignore) = &args.ignore {
-            // Parse severities to ignore
-            for sev in ignore.split(',') {
-                match sev.trim().to_lowercase().as_str() {
-                    "high" => options.ignore_severities.push(analyzer::Severity::High),
-                    "medium" => options.ignore_severities.push(analyzer::Severity::Medium),
-                    "low" => options.ignore_severities.push(analyzer::Severity::Low),
-                    "informational" => options
-                        .ignore_severities
-                        .push(analyzer::Severity::Informational),
-                    _ => warn!("Unknown severity level: {sev}"),
+    // Every report is rendered from the same results; they are saved to their files, and the
+    // machine-readable formats go to stdout without an output file
+    let project_path = args.path.to_string_lossy();
+    // Paths of the CI formats relative to the working directory of the job, the root of the repository
+    let base = std::env::current_dir().unwrap_or_default();
+    let mut renderer = analyzer::output::Renderer::new(&analysis_result, &project_path, &base)
+        .with_audit(project.config.audit_info())
+        .with_sources(&sources);
+    if let Some(comparison) = &comparison {
+        renderer = renderer.with_comparison(comparison);
+    }
+    renderer = renderer.with_history(&history);
+    if let Some(template) = &report_template {
+        renderer = renderer.with_template(template);
+    }
+    // The NDJSON findings have been streamed during the analysis
+    for sink in sinks.iter().filter(|sink| !(args.stream && sink.format == analyzer::output::Format::Ndjson)) {
+        match (&sink.path, sink.format) {
+            (Some(output_path), format) => match std::fs::write(output_path, renderer.render(format)) {
+                Ok(()) => info!("📄 {} report saved to: {}", format.title(), output_path.display()),
+                Err(e) => error!("Failed to save report: {e}"),
+            },
+            // Show the findings in the console, apart from the logs
+            (None, analyzer::output::Format::Markdown) => {
+                let mut console = analyzer::console::ConsoleReport::new(&analysis_result)
+                    .with_color(color.for_stdout())
+                    .with_sources(&sources);
+                if let Some(comparison) = &comparison {
+                    console = console.with_comparison(comparison);
                 }
+                match message_format {
+                    analyzer::console::MessageFormat::Human => print!("{}", console.to_text()),
+                    analyzer::console::MessageFormat::Short => print!("{}", console.to_short()),
+                }
+            }
+            // Only the report goes to stdout, the logs go to stderr
+            (None, format) => print!("{}", renderer.render(format)),
+        }
+    }
+    if args.anchor {
+        let dir = outputs.first().cloned().unwrap_or_else(|| args.path.join(analyzer::anchor::REPORTS_DIR));
+        let programs = analyzer::anchor::programs(&args.path, &project.programs);
+        let audit = project.config.audit_info();
+        write_anchor_reports(&programs, &analysis_result, &formats, &dir, &audit, report_template.as_ref())?;
+    }
+    if args.stats || args.stats_output.is_some() {
+        let stats = analyzer::stats::StatsReport::new(&analysis_result, &project_path);
+        if let Some(stats_output) = &args.stats_output {
+            match std::fs::write(stats_output, stats.to_json()) {
+                Ok(()) => info!("📄 Statistics saved to: {}", stats_output.display()),
+                Err(e) => error!("Failed to save the statistics: {e}"),
             }
         }
+        // Apart from the report on stdout
+        if args.stats {
+            eprint!("{}", stats.to_text());
+        }
+    }
+    if let Some(inventory_output) = &args.inventory {
+        let inventory = analyzer::inventory::InventoryReport::new(&analysis_result.stats.inventory, &project_path);
+        match inventory.save(&inventory_output.to_string_lossy()) {
+            Ok(()) => info!("📄 Inventory saved to: {}", inventory_output.display()),
+            Err(e) => error!("Failed to save the inventory: {e}"),
+        }
+    }
+    if let Some(pdas_output) = &args.pdas {
+        let pdas = analyzer::pdas::PdaReport::new(&analysis_result.stats.pdas, &project_path);
+        match pdas.save(&pdas_output.to_string_lossy()) {
+            Ok(()) => info!("📄 PDA report saved to: {}", pdas_output.display()),
+            Err(e) => error!("Failed to save the PDA report: {e}"),
+        }
+    }
+    if let Some(matrix_output) = &args.constraint_matrix {
+        let matrix = analyzer::matrix::ConstraintMatrix::new(&analysis_result.stats.inventory, &project_path);
+        match matrix.save(&matrix_output.to_string_lossy()) {
+            Ok(()) => info!("📄 Constraint matrix saved to: {}", matrix_output.display()),
+            Err(e) => error!("Failed to save the constraint matrix: {e}"),
+        }
+    }
+    if let Some(cpi_output) = &args.cpi_graph {
+        let graph = analyzer::cpi::CpiGraphReport::new(&analysis_result.stats.cpis, &project_path);
+        match graph.save(&cpi_output.to_string_lossy()) {
+            Ok(()) => info!("📄 CPI graph saved to: {}", cpi_output.display()),
+            Err(e) => error!("Failed to save the CPI graph: {e}"),
+        }
+    }
 
-        if let Some(ignore_rules) = &args.ignore_rules {
-            // Parse rule IDs to ignore
-            for rule_id in ignore_rules.split(',') {
-                options.ignore_rules.push(rule_id.trim().to_string());
+    // The report is saved before failing, so CI pipelines can publish it
+    let mut exceeded_budgets = false;
+    for usage in budgets.iter().flat_map(|budgets| budgets.check(&analysis_result)) {
+        if usage.exceeded() {
+            error!("Budget exceeded: {usage}");
+            exceeded_budgets = true;
+        } else {
+            info!("Budget met: {usage}");
+        }
+    }
+    if let Some(count) = failed {
+        match (&fail_on, max_findings) {
+            (Some(fail_on), Some(max_findings)) => {
+                error!("{count} findings with severity {fail_on:?} or higher were found (at most {max_findings} allowed)");
             }
+            (Some(fail_on), None) => error!("Findings with severity {fail_on:?} or higher were found"),
+            (None, _) => error!("{count} findings were found (at most {} allowed)", max_findings.unwrap_or(0)),
         }
+        std::process::exit(1);
+    }
+    if exceeded_budgets {
+        std::process::exit(1);
+    }
 
-        // Create analyzer and run analysis
-        let analyzer = analyzer::create_analyzer_with_options(options);
-        match analyzer.analyze_files(&results) {
-            Ok(analysis_result) => {
-                info!(
-                    "Analysis completed: {} findings",
-                    analysis_result.findings.len()
-                );
+    info!("Analysis completed.");
+    Ok(())
+}
 
-                // Show summary of findings by severity
-                let mut severity_counts = HashMap::new();
-                for (severity, count) in &analysis_result.stats.findings_by_severity {
-                    severity_counts.insert(severity, *count);
-                }
+/// Initialize the logger on stderr, the findings and reports going to stdout: the info logs of
+/// the analyzer as plain messages, the debug or trace logs with -v or -vv, only the errors with
+/// --quiet, or the filters of RUST_LOG if it is set
+///
+/// Log lines clear the progress bar, which is drawn again with the next progress.
+fn init_logger(
+    args: &Cli,
+    color: analyzer::console::ColorChoice,
+    rust_log: bool,
+    progress: Option<std::sync::Arc<analyzer::progress::ProgressBar>>,
+) {
+    let mut builder = if rust_log { env_logger::Builder::from_default_env() } else { env_logger::Builder::new() };
+    builder.write_style(match color {
+        analyzer::console::ColorChoice::Auto => env_logger::WriteStyle::Auto,
+        analyzer::console::ColorChoice::Always => env_logger::WriteStyle::Always,
+        analyzer::console::ColorChoice::Never => env_logger::WriteStyle::Never,
+    });
+    if !rust_log {
+        let level = match (args.quiet, args.verbose) {
+            (true, _) => log::LevelFilter::Error,
+            (false, 0) => log::LevelFilter::Info,
+            (false, 1) => log::LevelFilter::Debug,
+            (false, _) => log::LevelFilter::Trace,
+        };
+        // The logs of the dependencies are left to RUST_LOG, their warnings aside
+        builder.filter_level(level.min(log::LevelFilter::Warn)).filter_module("rust_solana_analyzer", level);
+    }
+    // Timestamps and modules are only useful to debug the analyzer
+    if !rust_log && args.verbose == 0 {
+        builder.format(move |buf, record| {
+            use std::io::Write;
+            if let Some(bar) = progress.as_ref().filter(|bar| bar.is_visible()) {
+                write!(buf, "\r\x1b[2K")?;
+                bar.hidden();
+            }
+            let style = buf.default_level_style(record.level());
+            match record.level() {
+                log::Level::Error => writeln!(buf, "{style}error{style:#}: {}", record.args()),
+                log::Level::Warn => writeln!(buf, "{style}warning{style:#}: {}", record.args()),
+                _ => writeln!(buf, "{}", record.args()),
+            }
+        });
+    }
+    builder.init();
+}
 
-                // Display in order of severity (High to Informational)
-                for severity in &[
-                    analyzer::Severity::High,
-                    analyzer::Severity::Medium,
-                    analyzer::Severity::Low,
-                    analyzer::Severity::Informational,
-                ] {
-                    if let Some(count) = severity_counts.get(severity) {
-                        info!("- {severity:?}: {count}");
-                    }
-                }
+/// Options of the analysis of the project at --path: the project configuration, then the
+/// --config file, then the CLI flags
+fn project_options(args: &Cli, finding_sink: Option<analyzer::FindingSink>) -> project::ProjectOptions<'_> {
+    project::ProjectOptions {
+        config_file: args.config.clone(),
+        advisories_file: args.advisories.clone(),
+        changed_since: args.changed_since.clone(),
+        expanded: args.expanded,
+        exclude: args.exclude.clone(),
+        no_ignore: args.no_ignore,
+        ast_dir: match &args.ast_dir {
+            Some(dir) => Some(dir.clone()),
+            None => (args.ast || args.ast_combined).then(|| project::project_root(&args.path).join(project::AST_DIR_NAME)),
+        },
+        combined_ast: args.ast_combined,
+        finding_sink,
+        ..Default::default()
+    }
+    .with_overrides(|options| apply_flags(args, options))
+}
 
-                // Save results to file if specified
-                if let Some(output_path) = &args.output {
-                    let report_generator = analyzer::reporting::ReportGenerator::new(
-                        analysis_result.findings.clone(),
-                        args.path.to_string_lossy().to_string(),
-                    );
-
-                    let output_str = output_path.to_string_lossy();
-                    if output_str.ends_with(".md") || output_str.ends_with(".markdown") {
-                        // Generate Markdown report
-                        match report_generator.save_markdown_report(&output_str) {
-                            Ok(()) => {
-                                info!("📄 Markdown report saved to: {}", output_path.display());
-                            }
-                            Err(e) => error!("Failed to save report: {e}"),
-                        }
-                    } else {
-                        // Default to Markdown with .md extension
-                        let mut md_path = output_path.clone();
-                        md_path.set_extension("md");
-                        match report_generator.save_markdown_report(&md_path.to_string_lossy()) {
-                            Ok(()) => info!("📄 Markdown report saved to: {}", md_path.display()),
-                            Err(e) => error!("Failed to save report: {e}"),
-                        }
-                    }
-                } else {
-                    // Show findings in the console using logs
-                    if analysis_result.findings.is_empty() {
-                        info!("No vulnerabilities found");
-                    } else {
-                        info!("Found {} vulnerabilities:", analysis_result.findings.len());
-
-                        // Group findings by severity for better readability
-                        let mut findings_by_severity = HashMap::new();
-                        for finding in &analysis_result.findings {
-                            findings_by_severity
-                                .entry(&finding.severity)
-                                .or_insert_with(Vec::new)
-                                .push(finding);
-                        }
-
-                        // Display findings in order of severity
-                        let mut index = 1;
-                        for severity in &[
-                            analyzer::Severity::High,
-                            analyzer::Severity::Medium,
-                            analyzer::Severity::Low,
-                            analyzer::Severity::Informational,
-                        ] {
-                            if let Some(findings) = findings_by_severity.get(severity) {
-                                info!("----- {severity:?} Severity Findings -----");
-
-                                for finding in findings {
-                                    info!(
-                                        "{}.\t{} ({}:{})",
-                                        index,
-                                        finding.description,
-                                        finding.location.file,
-                                        finding.location.line
-                                    );
-
-                                    // Show code snippet if available
-                                    if let Some(snippet) = &finding.code_snippet {
-                                        debug!("    Code: {snippet}");
-                                    }
-
-                                    index += 1;
-                                }
-                            }
-                        }
-                    }
-                }
+/// Apply the CLI flags to the analysis options, over the configuration
+fn apply_flags(args: &Cli, options: &mut analyzer::AnalysisOptions) {
+    options.jobs = args.jobs;
+    if args.no_cache {
+        options.cache_dir = None;
+    } else if let Some(cache_dir) = &args.cache_dir {
+        options.cache_dir = Some(cache_dir.clone());
+    }
+    if let Some(rule_timeout) = args.rule_timeout {
+        options.rule_timeout = Some(std::time::Duration::from_millis(rule_timeout));
+    }
+    // The statistics include the timings of the rules
+    options.profile_rules = args.profile_rules || args.stats || args.stats_output.is_some();
+    // The constraint matrix is built from the inventory
+    options.inventory = args.inventory.is_some() || args.constraint_matrix.is_some();
+    options.pdas = args.pdas.is_some();
+    options.cpi_graph = args.cpi_graph.is_some();
+
+    if let Some(templates) = &args.templates {
+        options.custom_templates_path = Some(templates.to_string_lossy().to_string());
+    }
+
+    if let Some(plugins) = &args.plugins {
+        options.plugins_path = Some(plugins.to_string_lossy().to_string());
+    }
+
+    if let Some(ignore) = &args.ignore {
+        // Parse severities to ignore
+        options.ignore_severities.clear();
+        for sev in ignore.split(',') {
+            match sev.parse::<analyzer::Severity>() {
+                Ok(severity) => options.ignore_severities.push(severity),
+                Err(e) => warn!("{e}"),
             }
-            Err(e) => {
-                error!("Error during analysis: {e}");
+        }
+    }
+
+    if let Some(ignore_rules) = &args.ignore_rules {
+        // Parse rule IDs to ignore
+        options.ignore_rules = ignore_rules.split(',').map(|rule_id| rule_id.trim().to_string()).collect();
+    }
+
+    if let Some(rules) = &args.rules {
+        // Parse rule IDs to run
+        options.include_rules = rules
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+
+    if let Some(tags) = &args.tags {
+        // Parse tags of the rules to run
+        options.include_tags = tags
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+
+    if let Some(profile) = &args.profile {
+        options.profile = Some(profile.clone());
+    }
+
+    if let Some(min_confidence) = &args.min_confidence {
+        match min_confidence.parse::<analyzer::Confidence>() {
+            Ok(confidence) => options.min_confidence = Some(confidence),
+            Err(e) => warn!("{e}"),
+        }
+    }
+
+    for severity_override in &args.severity_overrides {
+        // Parse <rule-id>=<severity>
+        match severity_override.split_once('=') {
+            Some((rule_id, severity)) => match severity.trim().parse::<analyzer::Severity>() {
+                Ok(severity) => {
+                    options.severity_overrides.insert(rule_id.trim().to_string(), severity);
+                }
+                Err(e) => warn!("{e}"),
+            },
+            None => warn!("Invalid severity override (expected <rule-id>=<severity>): {severity_override}"),
+        }
+    }
+
+    for rule_param in &args.rule_params {
+        // Parse <rule-id>.<param>=<value>
+        match rule_param.split_once('=').and_then(|(key, value)| {
+            key.split_once('.').map(|(rule_id, param)| (rule_id, param, value))
+        }) {
+            Some((rule_id, param, value)) => {
+                options
+                    .rule_params
+                    .entry(rule_id.trim().to_string())
+                    .or_default()
+                    .insert(param.trim().to_string(), value.to_string());
             }
+            None => warn!("Invalid rule parameter (expected <rule-id>.<param>=<value>): {rule_param}"),
         }
     }
 
-    info!("Analysis completed.");
+    options.keep_duplicate_findings = args.keep_duplicates;
+    options.include_tests = args.include_tests;
+    if let Some(max_per_rule) = args.max_per_rule {
+        options.finding_caps.per_rule = Some(max_per_rule);
+    }
+    if let Some(max_per_file) = args.max_per_file {
+        options.finding_caps.per_file = Some(max_per_file);
+    }
+    if let Some(context) = args.context {
+        options.context_lines = context;
+    }
+}
+
+/// Record the run in the history of the project at `root`, returning the recorded runs, this
+/// one last
+fn record_run(root: &Path, result: &analyzer::AnalysisResult) -> Vec<analyzer::history::RunRecord> {
+    let history = analyzer::history::History::new(root);
+    let mut runs = history.load().unwrap_or_else(|e| {
+        warn!("{e}");
+        Vec::new()
+    });
+    let run = analyzer::history::RunRecord::now(result, root);
+    match history.record(&run) {
+        Ok(path) => debug!("Run recorded in {}", path.display()),
+        Err(e) => warn!("Cannot record the run: {e}"),
+    }
+    runs.push(run);
+    runs
+}
+
+/// Print the finding counts of the recorded runs of the project at `path` (`history`), or the
+/// runs where a finding appeared and disappeared
+fn history(path: &Path, finding: Option<&str>, limit: usize, format: &str) -> Result<()> {
+    let json = match format {
+        "text" => false,
+        "json" => true,
+        _ => anyhow::bail!("Invalid --format: {format} (text or json)"),
+    };
+    let runs = analyzer::history::History::new(&project::project_root(path)).load()?;
+    let Some(prefix) = finding else {
+        if json {
+            println!("{}", serde_json::to_string_pretty(&runs[runs.len().saturating_sub(limit)..])?);
+        } else {
+            print!("{}", analyzer::history::to_text(&runs, limit));
+        }
+        return Ok(());
+    };
+    let fingerprint = match analyzer::history::matching_fingerprints(&runs, prefix).as_slice() {
+        [fingerprint] => fingerprint.to_string(),
+        [] => anyhow::bail!("No recorded finding has the fingerprint {prefix}"),
+        fingerprints => anyhow::bail!("{} recorded findings have fingerprints starting with {prefix}", fingerprints.len()),
+    };
+    if json {
+        let events: Vec<_> = analyzer::history::finding_events(&runs, &fingerprint)
+            .into_iter()
+            .map(|event| {
+                serde_json::json!({
+                    "change": if event.change == analyzer::history::Change::Appeared { "appeared" } else { "disappeared" },
+                    "timestamp": event.run.timestamp,
+                    "commit": event.run.commit,
+                    "finding": event.finding,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&events)?);
+    } else {
+        print!("{}", analyzer::history::finding_text(&runs, &fingerprint));
+    }
+    Ok(())
+}
+
+/// Print the documentation of a rule, with the rules of the project's configuration and flags
+fn explain(args: &Cli, rule_id: &str, format: &str) -> Result<()> {
+    let markdown = match format {
+        "text" => false,
+        "md" | "markdown" => true,
+        _ => anyhow::bail!("Invalid --format: {format} (text or md)"),
+    };
+    let options = project::ProjectOptions { config_file: args.config.clone(), ..Default::default() }.with_overrides(|options| {
+        apply_flags(args, options);
+        options.cache_dir = None;
+    });
+    let (_, analysis_options) = project::analysis_options(&project::project_root(&args.path), options)?;
+    let rules = analyzer::Analyzer::with_options(analysis_options);
+    let Some(rule) = rules.rules().metadata_of(rule_id) else {
+        let ids: Vec<String> = rules.rules().metadata().map(|rule| rule.id).collect();
+        let similar = analyzer::explain::similar_ids(rule_id, ids.iter().map(String::as_str));
+        if similar.is_empty() {
+            anyhow::bail!("Unknown rule: {rule_id}");
+        }
+        anyhow::bail!("Unknown rule: {rule_id} (did you mean {}?)", similar.join(", "));
+    };
+    let doc = analyzer::explain::RuleDoc::new(&rule);
+    print!("{}", if markdown { doc.to_markdown() } else { doc.to_text() });
+    Ok(())
+}
+
+/// Write the configuration of the project at `root` (`init`), and its CI workflow
+fn init(root: &Path, ci: Option<&str>, force: bool) -> Result<()> {
+    if !root.is_dir() {
+        anyhow::bail!("Cannot initialize {}: not a directory", root.display());
+    }
+    let ci = match ci {
+        Some(ci) => Some(ci.parse::<analyzer::init::CiProvider>().map_err(|e| anyhow::anyhow!("Invalid --ci: {e}"))?),
+        None => None,
+    };
+    let (setup, files) = analyzer::init::init(root, ci, force)?;
+    println!("Detected {}", setup.describe());
+    for file in files {
+        println!("Wrote {}", file.display());
+    }
+    Ok(())
+}
+
+/// Install the pre-commit hook of the project at `root` (`hook install`)
+fn install_hook(root: &Path, force: bool) -> Result<()> {
+    if !root.is_dir() {
+        anyhow::bail!("Cannot install the hook of {}: not a directory", root.display());
+    }
+    let hook = analyzer::hook::install(root, force)?;
+    println!("Wrote {}", hook.display());
+    println!("The staged Rust files are analyzed before each commit (skip it with `git commit --no-verify`)");
+    Ok(())
+}
+
+/// Generate the skeleton of a built-in rule (`new-rule`) in the analyzer checkout at `root`
+fn new_rule(root: &Path, id: &str, severity: &str, kind: &str) -> Result<()> {
+    let severity = severity.parse::<analyzer::Severity>().map_err(|e| anyhow::anyhow!("Invalid --severity: {e}"))?;
+    let kind = kind.parse::<analyzer::scaffold::RuleKind>().map_err(|e| anyhow::anyhow!("Invalid --kind: {e}"))?;
+    let scaffold = analyzer::scaffold::RuleScaffold::new(id, severity, kind)?;
+    for file in scaffold.write(root)? {
+        println!("Wrote {}", file.display());
+    }
+    println!("Write the condition of the rule in is_vulnerable (filters.rs), then its description and examples (mod.rs)");
+    Ok(())
+}
+
+/// Analyze the files of the project with the fixes of `plan` applied, in memory
+/// (`--fix-dry-run`), to check that the fixes resolve their findings
+fn check_fixes(
+    args: &Cli,
+    plan: &analyzer::fixes::FixPlan,
+    findings: &[analyzer::Finding],
+    files: Vec<PathBuf>,
+    sources: &analyzer::results::Sources,
+) -> Result<()> {
+    info!("{} fixes for {} files (dry run)", plan.applied.len(), plan.files.len());
+    if plan.files.is_empty() {
+        return Ok(());
+    }
+    let fixed_sources = files
+        .into_iter()
+        .filter_map(|path| {
+            let key = path.to_string_lossy().to_string();
+            let source = match plan.files.iter().find(|file| file.path == key) {
+                Some(file) => file.fixed.clone(),
+                None => sources.get(&key).cloned().or_else(|| std::fs::read_to_string(&path).ok())?,
+            };
+            Some(rust_solana_analyzer::ast::parser::SourceFile::from_source(path, source))
+        })
+        .collect();
+    let fixed = project::analyze_sources(&args.path, fixed_sources, project_options(args, None))?;
+    log_unresolved_fixes(plan, findings, &fixed.result.findings);
+    Ok(())
+}
+
+/// Log the fixes whose findings are still reported once they are applied
+fn log_unresolved_fixes(plan: &analyzer::fixes::FixPlan, before: &[analyzer::Finding], after: &[analyzer::Finding]) {
+    let unresolved = plan.unresolved(before, after);
+    let count: usize = unresolved.values().sum();
+    info!("Findings resolved by the fixes: {}/{}", plan.applied.len() - count, plan.applied.len());
+    for ((rule_id, file), count) in unresolved {
+        warn!("- {count} fix(es) of {rule_id} in {file} did not resolve their findings");
+    }
+}
+
+/// Read the code of --stdin, reported at --stdin-filename in the project
+fn read_stdin(args: &Cli) -> Result<rust_solana_analyzer::ast::parser::SourceFile> {
+    let mut source = String::new();
+    std::io::Read::read_to_string(&mut std::io::stdin(), &mut source)
+        .map_err(|e| anyhow::anyhow!("Cannot read the code from stdin: {e}"))?;
+    let file_name = args.stdin_filename.clone().unwrap_or_else(|| PathBuf::from("stdin.rs"));
+    Ok(rust_solana_analyzer::ast::parser::SourceFile::from_source(args.path.join(file_name), source))
+}
+
+/// Write the reports of each program of the Anchor workspace (`--anchor`) to `dir`, named after
+/// their program IDs, in the formats (Markdown by default)
+fn write_anchor_reports(
+    programs: &[analyzer::anchor::AnchorProgram],
+    result: &analyzer::AnalysisResult,
+    formats: &[analyzer::output::Format],
+    dir: &Path,
+    audit: &analyzer::audit::AuditInfo,
+    template: Option<&analyzer::report_templates::ReportTemplate>,
+) -> Result<()> {
+    if programs.is_empty() {
+        warn!("No program in the Anchor workspace, no report written");
+        return Ok(());
+    }
+    std::fs::create_dir_all(dir).map_err(|e| anyhow::anyhow!("Cannot create {}: {e}", dir.display()))?;
+    let formats = if formats.is_empty() { &[analyzer::output::Format::Markdown][..] } else { formats };
+    let base = std::env::current_dir().unwrap_or_default();
+    for program in programs {
+        match (&program.idl, program.stale) {
+            (None, _) => warn!("No IDL built for {} (run anchor build), it is not checked against the code", program.name),
+            (Some(_), true) => warn!("The IDL or types of {} are older than its sources (run anchor build)", program.name),
+            (Some(_), false) => {}
+        }
+        let program_result = program.result(result);
+        info!("- {} ({}): {} findings", program.name, program.report_name(), program_result.findings.len());
+        let mut renderer = analyzer::output::Renderer::new(&program_result, &program.name, &base).with_audit(audit.clone());
+        if let Some(template) = template {
+            renderer = renderer.with_template(template);
+        }
+        for format in formats {
+            let path = program.report_path(dir, *format);
+            match std::fs::write(&path, renderer.render(*format)) {
+                Ok(()) => info!("📄 {} report of {} saved to: {}", format.title(), program.name, path.display()),
+                Err(e) => error!("Failed to save report: {e}"),
+            }
+        }
+    }
     Ok(())
 }
+
+/// Read the staged Rust files of the project at --path, exiting with code 2 if git cannot list them
+fn read_staged(args: &Cli, options: &project::ProjectOptions<'_>) -> Vec<rust_solana_analyzer::ast::parser::SourceFile> {
+    match project::staged_sources(&args.path, options) {
+        Ok(files) => {
+            info!("{} staged Rust files", files.len());
+            files
+        }
+        Err(e) => {
+            error!("Cannot read the staged files: {e}");
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Open the destination of the findings streamed with --stream: the output of --format ndjson, or stdout
+fn stream_sink(args: &Cli) -> Result<analyzer::FindingSink> {
+    let formats = analyzer::output::parse_formats(args.format.as_deref().unwrap_or_default())?;
+    if !formats.contains(&analyzer::output::Format::Ndjson) {
+        anyhow::bail!("--stream needs --format ndjson");
+    }
+    let sinks = analyzer::output::sinks(&formats, &args.output).map_err(|e| anyhow::anyhow!("Invalid --output: {e}"))?;
+    match sinks.into_iter().find(|sink| sink.format == analyzer::output::Format::Ndjson).and_then(|sink| sink.path) {
+        Some(path) => {
+            let file = std::fs::File::create(&path)
+                .map_err(|e| anyhow::anyhow!("Cannot create {}: {e}", path.display()))?;
+            info!("📄 Streaming the findings to: {}", path.display());
+            Ok(analyzer::ndjson::stream_to(file))
+        }
+        None => Ok(analyzer::ndjson::stream_to(std::io::stdout())),
+    }
+}
+
+/// Log the execution profile of each rule, slowest first
+fn log_rule_profiles(stats: &analyzer::AnalysisStats) {
+    let mut profiles: Vec<_> = stats.rule_profiles.iter().collect();
+    profiles.sort_by(|(a_id, a), (b_id, b)| b.time.cmp(&a.time).then_with(|| a_id.cmp(b_id)));
+
+    info!("Rule profile (time, runs, files matched, findings):");
+    for (rule_id, profile) in profiles {
+        let never_fired = if profile.findings == 0 { " (never fired)" } else { "" };
+        info!(
+            "- {rule_id}: {:.2}ms, {} runs, {} files, {} findings{never_fired}",
+            profile.time.as_secs_f64() * 1000.0,
+            profile.runs,
+            profile.files_matched,
+            profile.findings
+        );
+    }
+    if stats.files_cached > 0 {
+        info!("  Rules did not run on the {} files reused from the cache (see --no-cache)", stats.files_cached);
+    }
+}
@@ -0,0 +1,137 @@
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::config::{CONFIG_FILE_NAME, Config, ConfigError};
+use crate::analyzer::vfs::{MemoryFileSystem, SharedFileSystem};
+use crate::analyzer::{AnalysisOptions, Analyzer, Confidence, RuleType, packs};
+use crate::ast::parser::SourceFile;
+
+#[cfg(test)]
+mod test;
+
+/// Path of the analyzed code when the options do not name it
+pub const DEFAULT_FILE: &str = "src/lib.rs";
+
+/// Error returned when code cannot be analyzed by [`analyze_source`]
+#[derive(Debug)]
+pub enum PlaygroundError {
+    /// The options are not valid JSON or have an invalid field
+    Options(String),
+    /// The configuration is invalid
+    Config(ConfigError),
+    /// The analysis failed
+    Analysis(String),
+}
+
+impl fmt::Display for PlaygroundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlaygroundError::Options(error) => write!(f, "invalid options: {error}"),
+            PlaygroundError::Config(error) => write!(f, "{error}"),
+            PlaygroundError::Analysis(error) => write!(f, "analysis failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for PlaygroundError {}
+
+impl From<ConfigError> for PlaygroundError {
+    fn from(error: ConfigError) -> Self {
+        PlaygroundError::Config(error)
+    }
+}
+
+/// Options of [`analyze_source`], as JSON (every field is optional)
+///
+/// ```json
+/// {
+///   "file": "programs/vault/src/lib.rs",
+///   "manifest": "[package]\nname = \"vault\"\n\n[dependencies]\nanchor-lang = \"0.31\"",
+///   "config": "[rules]\nprofile = \"anchor\"",
+///   "profile": "anchor",
+///   "rules": ["missing-signer-check"],
+///   "ignore_rules": ["anchor-instructions"],
+///   "min_confidence": "medium",
+///   "include_tests": false
+/// }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlaygroundOptions {
+    /// Path of the analyzed code, relative ([`DEFAULT_FILE`] if not set)
+    pub file: Option<PathBuf>,
+    /// `Cargo.toml` of the crate of the code, which tells whether it uses Anchor (the code is
+    /// assumed to use Anchor without it)
+    pub manifest: Option<String>,
+    /// Content of a `solana-analyzer.toml`, applied before the other options
+    pub config: Option<String>,
+    /// Profile or packs to run (`--profile`)
+    pub profile: Option<String>,
+    /// Rules to run (`--rules`)
+    pub rules: Option<Vec<String>>,
+    /// Rules to skip (`--ignore-rules`)
+    pub ignore_rules: Option<Vec<String>>,
+    /// Minimum confidence of the findings (`--min-confidence`)
+    pub min_confidence: Option<String>,
+    /// Report the findings in test code (`--include-tests`)
+    pub include_tests: bool,
+}
+
+impl PlaygroundOptions {
+    /// Analysis options reading the manifest from memory, with the configuration and the options applied
+    fn analysis_options(&self) -> Result<AnalysisOptions, PlaygroundError> {
+        // At the root, the manifest of the crate of any relative path
+        let mut file_system = MemoryFileSystem::new();
+        if let Some(manifest) = &self.manifest {
+            file_system.insert("Cargo.toml", manifest.as_str());
+        }
+
+        let mut options = AnalysisOptions {
+            include_rule_types: RuleType::ALL.to_vec(),
+            file_system: SharedFileSystem::new(file_system),
+            ..Default::default()
+        };
+        if let Some(config) = &self.config {
+            Config::from_toml(config, CONFIG_FILE_NAME, Path::new(""))?.apply(&mut options)?;
+        }
+
+        if let Some(profile) = &self.profile {
+            packs::resolve_profile(profile).map_err(|e| PlaygroundError::Options(format!("profile: {e}")))?;
+            options.profile = Some(profile.clone());
+        }
+        if let Some(rules) = &self.rules {
+            options.include_rules = rules.clone();
+        }
+        if let Some(ignore_rules) = &self.ignore_rules {
+            options.ignore_rules = ignore_rules.clone();
+        }
+        if let Some(min_confidence) = &self.min_confidence {
+            let min_confidence = min_confidence
+                .parse::<Confidence>()
+                .map_err(|e| PlaygroundError::Options(format!("min_confidence: {e}")))?;
+            options.min_confidence = Some(min_confidence);
+        }
+        options.include_tests |= self.include_tests;
+        Ok(options)
+    }
+}
+
+/// Analyze Rust code held in memory and return its findings as JSON, with the fields of the
+/// findings of the JSON report
+///
+/// The entry point of the browser playground: it reads no file, spawns no process nor thread,
+/// and runs on `wasm32-unknown-unknown` (built without the `native` feature). `options_json`
+/// holds [`PlaygroundOptions`], an empty string runs every rule on `src/lib.rs`.
+pub fn analyze_source(code: &str, options_json: &str) -> Result<String, PlaygroundError> {
+    let options: PlaygroundOptions = match options_json.trim() {
+        "" => PlaygroundOptions::default(),
+        json => serde_json::from_str(json).map_err(|e| PlaygroundError::Options(e.to_string()))?,
+    };
+    let analyzer = Analyzer::with_options(options.analysis_options()?);
+    let file = options.file.unwrap_or_else(|| PathBuf::from(DEFAULT_FILE));
+
+    let source = SourceFile::from_source(file, code.to_string());
+    let result = analyzer.analyze_files([source]).map_err(|e| PlaygroundError::Analysis(e.to_string()))?;
+    serde_json::to_string(&result.findings).map_err(|e| PlaygroundError::Analysis(e.to_string()))
+}
@@ -0,0 +1,67 @@
+use crate::playground::{PlaygroundError, analyze_source};
+use serde_json::Value;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = r#"use anchor_lang::prelude::*;
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64, shares: u64) -> Result<()> {
+        let _ = amount / shares;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    pub authority: AccountInfo<'info>,
+}
+"#;
+
+    fn rule_ids(findings: &str) -> Vec<String> {
+        let findings: Vec<Value> = serde_json::from_str(findings).unwrap();
+        let mut ids: Vec<String> = findings.iter().map(|finding| finding["rules"][0]["id"].as_str().unwrap().to_string()).collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    #[test]
+    fn test_analyze_source() {
+        let findings: Vec<Value> = serde_json::from_str(&analyze_source(CODE, "").unwrap()).unwrap();
+        assert!(!findings.is_empty());
+        assert!(findings.iter().all(|finding| finding["location"]["file"] == "src/lib.rs"));
+        assert!(rule_ids(&analyze_source(CODE, "").unwrap()).contains(&"solana-division-by-zero".to_string()));
+
+        let options = r#"{"file": "programs/vault/src/lib.rs", "rules": ["solana-division-by-zero"]}"#;
+        let findings: Vec<Value> = serde_json::from_str(&analyze_source(CODE, options).unwrap()).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0]["location"]["file"], "programs/vault/src/lib.rs");
+        assert_eq!(findings[0]["location"]["line"], 7);
+    }
+
+    #[test]
+    fn test_analyze_source_options() {
+        // The Anchor rules only run on crates depending on anchor-lang
+        let anchor = rule_ids(&analyze_source(CODE, "").unwrap());
+        let manifest = serde_json::json!({ "manifest": "[package]\nname = \"vault\"\n\n[dependencies]\nborsh = \"1\"" });
+        let native = rule_ids(&analyze_source(CODE, &manifest.to_string()).unwrap());
+        assert!(native.len() < anchor.len());
+        assert!(native.contains(&"solana-division-by-zero".to_string()));
+
+        let config = serde_json::json!({ "config": "[rules]\nexclude = [\"solana-division-by-zero\"]" });
+        assert!(!rule_ids(&analyze_source(CODE, &config.to_string()).unwrap()).contains(&"solana-division-by-zero".to_string()));
+
+        // Parse errors are findings
+        assert_eq!(rule_ids(&analyze_source("pub fn broken(", "").unwrap()), ["parse-error"]);
+
+        assert!(matches!(analyze_source(CODE, "{\"min_confidence\": \"certain\"}"), Err(PlaygroundError::Options(_))));
+        assert!(matches!(analyze_source(CODE, "{\"rule\": []}"), Err(PlaygroundError::Options(_))));
+        assert!(matches!(analyze_source(CODE, "{\"config\": \"[rules\"}"), Err(PlaygroundError::Config(_))));
+    }
+}
@@ -0,0 +1,607 @@
+use log::{debug, info, warn};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::analyzer::advisories::{AdvisoryDb, AdvisoryError};
+use crate::analyzer::config::{Config, ConfigError, PathFilter, CONFIG_FILE_NAME};
+use crate::analyzer::diff::{self, ChangedLines, DiffError};
+use crate::analyzer::ignore::Excluder;
+use crate::analyzer::progress::{Progress, ProgressSink};
+use crate::analyzer::remote::{self, PackError, RemotePack};
+use crate::analyzer::workspace::{CrateManifest, Workspace};
+use crate::analyzer::{self, AnalysisOptions, AnalysisResult, FindingSink, RuleType};
+use crate::ast::json::{FileAst, ProgramAst};
+use crate::ast::parser::{self, ParseError, SourceFile, SourceText};
+
+/// Name of the default directory of the ASTs, in the project directory
+pub const AST_DIR_NAME: &str = ".analyzer-ast";
+
+/// Error returned when a project cannot be analyzed
+#[derive(Debug)]
+pub enum ProjectError {
+    /// The path does not exist
+    NotFound(PathBuf),
+    /// The path is not a directory, a Rust file or a glob of Rust files
+    NotADirectory(PathBuf),
+    /// The configuration cannot be loaded or is invalid
+    Config(ConfigError),
+    /// The extra advisory table cannot be loaded
+    Advisories(AdvisoryError),
+    /// The changed lines cannot be computed
+    Diff(DiffError),
+    /// The remote rule pack cannot be fetched or verified
+    RulePack(PackError),
+    /// The AST JSON of a file cannot be written
+    Ast { path: PathBuf, error: std::io::Error },
+    /// The analysis failed (a rule template or plugin cannot be loaded...)
+    Analysis(String),
+}
+
+impl fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectError::NotFound(path) => write!(f, "Path {} does not exist", path.display()),
+            ProjectError::NotADirectory(path) => {
+                write!(f, "Path {} is not a directory, a Rust file or a glob of Rust files", path.display())
+            }
+            ProjectError::Config(error) => write!(f, "{error}"),
+            ProjectError::Advisories(error) => write!(f, "{error}"),
+            ProjectError::Diff(error) => write!(f, "{error}"),
+            ProjectError::RulePack(error) => write!(f, "{error}"),
+            ProjectError::Ast { path, error } => write!(f, "cannot write the AST of {}: {error}", path.display()),
+            ProjectError::Analysis(error) => write!(f, "analysis failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+impl From<ConfigError> for ProjectError {
+    fn from(error: ConfigError) -> Self {
+        ProjectError::Config(error)
+    }
+}
+
+impl From<AdvisoryError> for ProjectError {
+    fn from(error: AdvisoryError) -> Self {
+        ProjectError::Advisories(error)
+    }
+}
+
+impl From<PackError> for ProjectError {
+    fn from(error: PackError) -> Self {
+        ProjectError::RulePack(error)
+    }
+}
+
+impl From<DiffError> for ProjectError {
+    fn from(error: DiffError) -> Self {
+        ProjectError::Diff(error)
+    }
+}
+
+/// Changes to the analysis options (the CLI flags for the binary)
+pub type Overrides<'a> = Box<dyn FnOnce(&mut AnalysisOptions) + 'a>;
+
+/// Options of `analyze_project`
+///
+/// The project's `solana-analyzer.toml` (then `config_file`) is applied to the default
+/// analysis options, and the overrides last, the way the CLI flags win over the configuration.
+#[derive(Default)]
+pub struct ProjectOptions<'a> {
+    /// Configuration file applied on top of the project's `solana-analyzer.toml`
+    pub config_file: Option<PathBuf>,
+    /// Advisory table checked along with the embedded one
+    pub advisories_file: Option<PathBuf>,
+    /// Only report findings on the lines changed since this git reference
+    pub changed_since: Option<String>,
+    /// Also analyze the macro-expanded code
+    pub expanded: bool,
+    /// Paths excluded along with the `paths.exclude` globs of the configuration (`--exclude`)
+    pub exclude: Vec<String>,
+    /// Do not exclude the paths of the `.gitignore` and `.ignore` files
+    pub no_ignore: bool,
+    /// Directory the AST of each parsed file is written to as JSON, at its path relative to the
+    /// project (see [`FileAst`])
+    pub ast_dir: Option<PathBuf>,
+    /// Also write the AST of each crate, its files in the order of their modules, to `ast_dir`
+    /// (see [`ProgramAst`])
+    pub combined_ast: bool,
+    /// Directory the remote rule packs are cached in (default: see [`remote::default_cache_dir`])
+    pub packs_cache_dir: Option<PathBuf>,
+    /// Changes to the analysis options, applied after the configuration
+    pub overrides: Option<Overrides<'a>>,
+    /// Receives the findings as they are produced (see [`Analyzer::with_finding_sink`])
+    ///
+    /// [`Analyzer::with_finding_sink`]: analyzer::Analyzer::with_finding_sink
+    pub finding_sink: Option<FindingSink>,
+    /// Receives the progress of the analysis (see [`Analyzer::with_progress_sink`])
+    ///
+    /// [`Analyzer::with_progress_sink`]: analyzer::Analyzer::with_progress_sink
+    pub progress_sink: Option<ProgressSink>,
+}
+
+impl<'a> ProjectOptions<'a> {
+    /// Change the analysis options after the configuration is applied
+    pub fn with_overrides(mut self, overrides: impl FnOnce(&mut AnalysisOptions) + 'a) -> Self {
+        self.overrides = Some(Box::new(overrides));
+        self
+    }
+}
+
+/// A source file of an analyzed project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalyzedFile {
+    /// Path of the file
+    pub path: PathBuf,
+    /// Why the file could not be parsed (it is not analyzed then)
+    pub parse_error: Option<ParseError>,
+    /// Workspace crate the file belongs to
+    pub program: Option<String>,
+}
+
+impl AnalyzedFile {
+    /// Record of a file, with the crate of the workspace it belongs to
+    fn of(path: &Path, parse_error: Option<ParseError>, workspace: Option<&Workspace>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            parse_error,
+            program: workspace
+                .and_then(|workspace| workspace.crate_of(path))
+                .map(|manifest_crate| manifest_crate.name.clone()),
+        }
+    }
+}
+
+/// Analysis of a project directory
+#[derive(Debug)]
+pub struct ProjectAnalysis {
+    /// Analyzed directory
+    pub root: PathBuf,
+    /// Configuration the analysis ran with (`solana-analyzer.toml` and the configuration file)
+    pub config: Config,
+    /// Crates of the workspace that are Solana programs
+    pub programs: Vec<CrateManifest>,
+    /// Source files found in the directory, minus the excluded paths
+    pub files: Vec<AnalyzedFile>,
+    /// Findings (with their metadata) and statistics of the analysis
+    pub result: AnalysisResult,
+}
+
+/// Rust files selected by a path: every file of a directory, a single file, or the files
+/// matching a glob
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    /// Project directory the configuration and the workspace are loaded from
+    pub root: PathBuf,
+    /// Selected files, at paths under `root`, or `None` for every file of `root`
+    pub files: Option<Vec<PathBuf>>,
+    /// Whether the files were matched by a glob (the excluded paths are left out of them)
+    pub matched: bool,
+}
+
+impl Selection {
+    /// Select the Rust files of a path: a directory, a `.rs` file, or a glob of files such as
+    /// `programs/*/src/instructions/*.rs` (`*` and `?` do not match `/`, `**` matches any number
+    /// of directories, a pattern matching a directory selects the files under it)
+    ///
+    /// The project of a file or a glob is the directory of its configuration or its workspace
+    /// (see [`project_root`]).
+    pub fn of(path: &Path) -> Result<Self, ProjectError> {
+        if path.is_dir() {
+            return Ok(Self { root: path.to_path_buf(), files: None, matched: false });
+        }
+        if path.is_file() {
+            if path.extension().is_none_or(|extension| extension != "rs") {
+                return Err(ProjectError::NotADirectory(path.to_path_buf()));
+            }
+            let (root, base) = project_dir(path.parent().unwrap_or(Path::new("")));
+            return Ok(Self { files: Some(vec![rebase(path, &root, base)]), root, matched: false });
+        }
+
+        let Some((base, pattern)) = split_glob(path) else {
+            return Err(ProjectError::NotFound(path.to_path_buf()));
+        };
+        let search_dir = if base.as_os_str().is_empty() { Path::new(".") } else { base.as_path() };
+        if !search_dir.is_dir() {
+            return Err(ProjectError::NotFound(path.to_path_buf()));
+        }
+        let matcher = PathFilter::new(&[pattern])?;
+        let (root, project) = project_dir(&base);
+        let files: Vec<PathBuf> = WalkDir::new(search_dir)
+            .follow_links(true)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(std::result::Result::ok)
+            .filter(|entry| entry.path().is_file() && entry.path().extension().is_some_and(|ext| ext == "rs"))
+            .filter(|entry| entry.path().strip_prefix(search_dir).is_ok_and(|file| matcher.is_excluded(file)))
+            .map(|entry| rebase(&base.join(entry.path().strip_prefix(search_dir).unwrap_or(entry.path())), &root, project))
+            .collect();
+        if files.is_empty() {
+            return Err(ProjectError::NotFound(path.to_path_buf()));
+        }
+        debug!("{} matches {} files", path.display(), files.len());
+        Ok(Self { root, files: Some(files), matched: true })
+    }
+}
+
+/// Project directory of a path (see [`Selection::of`]): the directory itself, or for a file or
+/// a glob the nearest directory above it with a `solana-analyzer.toml` or a workspace
+/// `Cargo.toml`, else the nearest one with a `Cargo.toml`, else its own directory
+pub fn project_root(path: &Path) -> PathBuf {
+    if path.is_dir() {
+        return path.to_path_buf();
+    }
+    let dir = match split_glob(path) {
+        Some((base, _)) => base,
+        None => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+    project_dir(&dir).0
+}
+
+/// Project directory of the files under `dir`, and the ancestor of `dir` it was found at (the
+/// project directory is `.` for the empty path)
+fn project_dir(dir: &Path) -> (PathBuf, &Path) {
+    let mut package = None;
+    let mut project = dir;
+    for ancestor in dir.ancestors() {
+        let manifest = ancestor_path(ancestor).join("Cargo.toml");
+        if ancestor_path(ancestor).join(CONFIG_FILE_NAME).is_file()
+            || fs::read_to_string(&manifest).is_ok_and(|manifest| manifest.contains("[workspace]"))
+        {
+            project = ancestor;
+            package = None;
+            break;
+        }
+        if package.is_none() && manifest.is_file() {
+            package = Some(ancestor);
+        }
+    }
+    let project = package.unwrap_or(project);
+    (ancestor_path(project).to_path_buf(), project)
+}
+
+/// Directory of an ancestor of a relative path, `.` for the empty path
+fn ancestor_path(ancestor: &Path) -> &Path {
+    if ancestor.as_os_str().is_empty() { Path::new(".") } else { ancestor }
+}
+
+/// Path of a file under the ancestor `base` of its path, under the project directory `root`
+/// instead, so the files of a project are at the paths of its directory walk (`./src/lib.rs`)
+fn rebase(file: &Path, root: &Path, base: &Path) -> PathBuf {
+    match file.strip_prefix(base) {
+        Ok(relative) => root.join(relative),
+        Err(_) => file.to_path_buf(),
+    }
+}
+
+/// Split a glob into the directory before its first wildcard and the pattern of the paths
+/// under it, `None` if there is no wildcard
+fn split_glob(path: &Path) -> Option<(PathBuf, String)> {
+    let components: Vec<_> = path.components().collect();
+    let first = components
+        .iter()
+        .position(|component| component.as_os_str().to_string_lossy().contains(['*', '?']))?;
+    let base: PathBuf = components[..first].iter().collect();
+    let pattern = components[first..]
+        .iter()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    Some((base, pattern))
+}
+
+/// Analyze the Rust sources of a project directory, a Rust file or a glob of Rust files (see
+/// [`Selection::of`])
+///
+/// This is what the `rust-solana-analyzer --analyze` binary runs: the configuration, the
+/// workspace crates, the advisories and the changed lines are loaded the same way.
+pub fn analyze_project(path: &Path, mut options: ProjectOptions<'_>) -> Result<ProjectAnalysis, ProjectError> {
+    let selection = Selection::of(path)?;
+    let path = selection.root.as_path();
+    let (config, mut analysis_options) = configure(path, &mut options)?;
+
+    let workspace = analysis_options.workspace.clone();
+    let programs = workspace.iter().flat_map(Workspace::programs).cloned().collect();
+
+    debug!("Starting analysis on directory: {}", path.display());
+    // Files are read as they are consumed and parsed crate by crate, so the analysis never holds
+    // every AST at once
+    let mut files = Vec::new();
+    let mut ast = AstWriter::new(path, options.ast_dir.as_deref(), options.combined_ast);
+    let excluder = excluder(path, &config, &options)?;
+    let sources = walk(&selection, &excluder).inspect(|file| {
+        files.push(AnalyzedFile::of(&file.path, None, workspace.as_ref()));
+        // Exporting the ASTs parses the files a second time
+        if let Some(ast) = ast.as_mut() {
+            ast.write(&file.clone().parse());
+        }
+    });
+
+    let create_analyzer = |analysis_options| {
+        let mut analyzer = analyzer::create_analyzer_with_options(analysis_options);
+        if let Some(sink) = options.finding_sink {
+            analyzer = analyzer.with_finding_sink(sink);
+        }
+        if let Some(sink) = options.progress_sink {
+            analyzer = analyzer.with_progress_sink(sink);
+        }
+        analyzer
+    };
+    // Expanding the macros needs every file at once
+    let analysis = if options.expanded {
+        let sources: Vec<SourceFile> = sources.map(SourceText::parse).collect();
+        let output_dir = std::env::temp_dir().join("solana-analyzer-expanded");
+        analysis_options.expanded_files = analyzer::expansion::expand_files(&sources, &output_dir);
+        info!("Analyzing {} expanded files along with the sources", analysis_options.expanded_files.len());
+        create_analyzer(analysis_options).analyze_files(sources)
+    } else {
+        create_analyzer(analysis_options).analyze_files(sources)
+    };
+    if let Some(ast) = ast {
+        ast.finish()?;
+    }
+    let mut result = analysis.map_err(|e| ProjectError::Analysis(e.to_string()))?;
+    result.stats.exclusions = excluder.exclusions();
+    for file in &mut files {
+        file.parse_error = result.stats.parse_errors.get(file.path.to_string_lossy().as_ref()).cloned();
+    }
+
+    Ok(ProjectAnalysis { root: path.to_path_buf(), config, programs, files, result })
+}
+
+/// Analyze sources held in memory (e.g. read from stdin) as files of the project directory at
+/// `path`, without reading its files
+///
+/// The configuration, the workspace crates (the paths of the sources place them in their
+/// crate) and the advisories of the project are loaded as [`analyze_project`] does. The
+/// macro expansion needs the files on disk, so `expanded` is not supported.
+pub fn analyze_sources(
+    path: &Path,
+    sources: Vec<SourceFile>,
+    mut options: ProjectOptions<'_>,
+) -> Result<ProjectAnalysis, ProjectError> {
+    let (config, analysis_options) = configure(path, &mut options)?;
+    if options.expanded {
+        warn!("The macro expansion needs the files on disk, the sources are analyzed without it");
+    }
+
+    let workspace = analysis_options.workspace.clone();
+    let programs = workspace.iter().flat_map(Workspace::programs).cloned().collect();
+    let files = sources
+        .iter()
+        .map(|file| AnalyzedFile::of(&file.path, file.parse_error.clone(), workspace.as_ref()))
+        .collect();
+    if let Some(mut ast) = AstWriter::new(path, options.ast_dir.as_deref(), options.combined_ast) {
+        sources.iter().for_each(|file| ast.write(file));
+        ast.finish()?;
+    }
+
+    info!("Analyzing {} sources in memory", sources.len());
+    let mut analyzer = analyzer::create_analyzer_with_options(analysis_options);
+    if let Some(sink) = options.finding_sink {
+        analyzer = analyzer.with_finding_sink(sink);
+    }
+    if let Some(sink) = options.progress_sink {
+        analyzer = analyzer.with_progress_sink(sink);
+    }
+    let result = analyzer.analyze_files(sources).map_err(|e| ProjectError::Analysis(e.to_string()))?;
+
+    Ok(ProjectAnalysis { root: path.to_path_buf(), config, programs, files, result })
+}
+
+/// Rust files of the project directory at `path` staged for the next commit, with their staged
+/// content, leaving out the excluded paths (see [`diff::staged_files`])
+///
+/// They are at their paths under `path`, to be analyzed with [`analyze_sources`]: the
+/// `--staged` mode of the binary, run by the pre-commit hook.
+pub fn staged_sources(path: &Path, options: &ProjectOptions<'_>) -> Result<Vec<SourceFile>, ProjectError> {
+    let config = load_config(path, options)?;
+    let excluder = excluder(path, &config, options)?;
+    let sources: Vec<SourceFile> = diff::staged_files(path)?
+        .into_iter()
+        .filter(|file| !excluder.is_excluded(&file.path))
+        .map(|file| SourceFile::from_source(path.join(file.path), file.source))
+        .collect();
+    debug!("{} staged Rust files to analyze in {}", sources.len(), path.display());
+    Ok(sources)
+}
+
+/// Configuration and analysis options of the project directory at `path`, the ones
+/// [`analyze_project`] runs with, without parsing its files
+///
+/// The language server analyzes the files of the project with them as they are edited.
+pub fn analysis_options(path: &Path, mut options: ProjectOptions<'_>) -> Result<(Config, AnalysisOptions), ProjectError> {
+    configure(path, &mut options)
+}
+
+/// Load the configuration of the project and the analysis options, taking the overrides of `options`
+fn configure(path: &Path, options: &mut ProjectOptions<'_>) -> Result<(Config, AnalysisOptions), ProjectError> {
+    let config = load_config(path, options)?;
+    config.output_format()?;
+    config.fail_on()?;
+
+    let mut analysis_options = AnalysisOptions {
+        generate_ast: options.ast_dir.is_some(),
+        project_root: Some(path.to_path_buf()),
+        cache_dir: Some(path.join(analyzer::incremental::CACHE_DIR_NAME)),
+        include_rule_types: RuleType::ALL.to_vec(),
+        ..Default::default()
+    };
+    config.apply(&mut analysis_options)?;
+
+    // Dependency advisories, the embedded table and the extra one
+    analysis_options.advisories = AdvisoryDb::embedded();
+    if let Some(advisories) = &options.advisories_file {
+        analysis_options.advisories.extend(AdvisoryDb::from_file(advisories)?);
+    }
+
+    if let Some(reference) = &options.changed_since {
+        analysis_options.changed_lines = Some(ChangedLines::from_git(path, reference)?);
+    }
+
+    // Crates of the workspace, each analyzed as its own program
+    match Workspace::load(path) {
+        Ok(Some(workspace)) => {
+            for manifest_crate in &workspace.crates {
+                debug!(
+                    "Crate {}{}{}{}",
+                    manifest_crate.name,
+                    if manifest_crate.is_program() { " (program)" } else { "" },
+                    manifest_crate.anchor_lang.as_ref().map(|version| format!(" [anchor-lang {version}]")).unwrap_or_default(),
+                    manifest_crate.program_id.as_ref().map(|id| format!(": {id}")).unwrap_or_default()
+                );
+            }
+            analysis_options.workspace = Some(workspace);
+        }
+        Ok(None) => debug!("No Cargo.toml in {}, analyzing the files as one program", path.display()),
+        Err(e) => warn!("Cannot read the workspace manifests: {e}"),
+    }
+
+    if let Some(overrides) = options.overrides.take() {
+        overrides(&mut analysis_options);
+    }
+
+    // A remote rule pack is fetched (or taken from the cache), then loaded as a templates directory
+    if let Some(templates) = &analysis_options.custom_templates_path
+        && remote::is_remote(templates)
+    {
+        let pack = RemotePack::parse(templates)?;
+        let cache_dir = options.packs_cache_dir.clone().unwrap_or_else(remote::default_cache_dir);
+        let dir = pack.fetch(&cache_dir)?;
+        debug!("Rule pack {} loaded from {}", pack.spec, dir.display());
+        analysis_options.custom_templates_path = Some(dir.to_string_lossy().to_string());
+    }
+
+    Ok((config, analysis_options))
+}
+
+/// Parse the Rust sources of a project directory (or a file, or a glob) without analyzing them
+///
+/// Only the configuration (its excluded paths) and the AST options are used.
+pub fn parse_project(path: &Path, options: &ProjectOptions<'_>) -> Result<Vec<AnalyzedFile>, ProjectError> {
+    let selection = Selection::of(path)?;
+    let path = selection.root.as_path();
+    let config = load_config(path, options)?;
+    let workspace = Workspace::load(path).ok().flatten();
+
+    let mut files = Vec::new();
+    let mut ast = AstWriter::new(path, options.ast_dir.as_deref(), options.combined_ast);
+    let excluder = excluder(path, &config, options)?;
+    for (index, file) in walk(&selection, &excluder).map(SourceText::parse).enumerate() {
+        if let Some(sink) = &options.progress_sink {
+            sink(Progress::Read { file: &file.path, count: index + 1 });
+        }
+        files.push(AnalyzedFile::of(&file.path, file.parse_error.clone(), workspace.as_ref()));
+        if let Some(ast) = ast.as_mut() {
+            ast.write(&file);
+        }
+    }
+    if let Some(ast) = ast {
+        ast.finish()?;
+    }
+    Ok(files)
+}
+
+/// Load the configuration of the project directory at `path`
+fn load_config(path: &Path, options: &ProjectOptions<'_>) -> Result<Config, ProjectError> {
+    if !path.exists() {
+        return Err(ProjectError::NotFound(path.to_path_buf()));
+    }
+    if !path.is_dir() {
+        return Err(ProjectError::NotADirectory(path.to_path_buf()));
+    }
+    Ok(Config::load(path, options.config_file.as_deref())?)
+}
+
+/// Excluder of the paths of the project at `path`: the `--exclude` and `paths.exclude` globs,
+/// and the ignore files unless `no_ignore` is set
+fn excluder(path: &Path, config: &Config, options: &ProjectOptions<'_>) -> Result<Excluder, ProjectError> {
+    let cli = PathFilter::new(&options.exclude)?;
+    Ok(Excluder::new(path, cli, config.path_filter()?, !options.no_ignore))
+}
+
+/// Read the selected files of the project lazily, leaving out the excluded paths
+fn walk<'a>(selection: &'a Selection, excluder: &'a Excluder) -> Box<dyn Iterator<Item = SourceText> + 'a> {
+    match &selection.files {
+        // A file given by its path is always analyzed
+        Some(paths) if selection.matched => Box::new(parser::load_files(
+            paths
+                .iter()
+                .filter(|file| !file.strip_prefix(&selection.root).is_ok_and(|file| excluder.is_excluded(file)))
+                .cloned()
+                .collect(),
+        )),
+        Some(paths) => Box::new(parser::load_files(paths.clone())),
+        None => Box::new(parser::walk_directory_excluding(&selection.root, |file| excluder.is_excluded(file))),
+    }
+}
+
+/// Writes the ASTs of the parsed files to the AST directory
+struct AstWriter<'a> {
+    /// Analyzed directory, the paths of the ASTs are relative to it
+    root: &'a Path,
+    /// Directory of the ASTs
+    dir: &'a Path,
+    /// ASTs of the files, kept for the combined ASTs of the crates
+    combined: Option<Vec<(PathBuf, syn::File)>>,
+    /// First error writing an AST
+    error: Option<ProjectError>,
+}
+
+impl<'a> AstWriter<'a> {
+    /// Creates the writer of the ASTs of the project at `root`, if there is an AST directory
+    fn new(root: &'a Path, dir: Option<&'a Path>, combined: bool) -> Option<Self> {
+        dir.map(|dir| Self { root, dir, combined: combined.then(Vec::new), error: None })
+    }
+
+    /// Write the AST of a parsed file, at its path relative to the project
+    fn write(&mut self, file: &SourceFile) {
+        if file.parse_error.is_some() || self.error.is_some() {
+            return;
+        }
+        let relative = self.relative(&file.path);
+        let json = FileAst::new(relative.to_string_lossy().replace('\\', "/"), &file.ast).to_json();
+        let output = self.dir.join(&relative).with_extension("json");
+        if let Err(error) = write_file(&output, &json) {
+            self.error = Some(ProjectError::Ast { path: file.path.clone(), error });
+            return;
+        }
+        debug!("AST JSON of {} written to {}", file.path.display(), output.display());
+        if let Some(combined) = &mut self.combined {
+            combined.push((file.path.clone(), file.ast.clone()));
+        }
+    }
+
+    /// Write the combined ASTs of the crates, or return the first error
+    fn finish(self) -> Result<(), ProjectError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        for program in ProgramAst::combine(self.combined.as_deref().unwrap_or_default(), self.root) {
+            let output = self.dir.join(&program.crate_root).with_extension("crate.json");
+            write_file(&output, &program.to_json())
+                .map_err(|error| ProjectError::Ast { path: self.root.join(&program.crate_root), error })?;
+            info!("Combined AST of {} written to {}", program.crate_root, output.display());
+        }
+        Ok(())
+    }
+
+    /// Path of a file relative to the project (its name if it is outside)
+    fn relative(&self, path: &Path) -> PathBuf {
+        match path.strip_prefix(self.root) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => path.file_name().map(PathBuf::from).unwrap_or_default(),
+        }
+    }
+}
+
+/// Helper function to write a file, creating its directory
+fn write_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)
+}
@@ -0,0 +1,55 @@
+//! `analyze_project` reports each file read, then each file analyzed out of the total, to the
+//! progress sink
+
+use rust_solana_analyzer::analyzer::progress::Progress;
+use rust_solana_analyzer::{ProjectOptions, analyze_project};
+use std::sync::{Arc, Mutex};
+
+const SOURCE: &str = "pub fn share(amount: u64, shares: u64) -> u64 {\n    amount / shares\n}\n";
+
+#[test]
+fn test_progress_of_the_files() {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-progress-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::create_dir_all(root.join("tests")).unwrap();
+    for name in ["src/lib.rs", "src/math.rs", "tests/vault.rs"] {
+        std::fs::write(root.join(name), SOURCE).unwrap();
+    }
+    std::fs::write(root.join("src/broken.rs"), "pub fn broken( {").unwrap();
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&events);
+    let options = ProjectOptions {
+        progress_sink: Some(Box::new(move |progress| {
+            recorded.lock().unwrap().push(match progress {
+                Progress::Read { count, .. } => ("read", count, None),
+                Progress::Analyzed { done, total, .. } => ("analyzed", done, Some(total)),
+            });
+        })),
+        ..Default::default()
+    }
+    .with_overrides(|options| {
+        options.cache_dir = None;
+        options.jobs = 2;
+    });
+    analyze_project(&root, options).unwrap();
+
+    // Files that cannot be parsed and test files are skipped but counted
+    let mut events = events.lock().unwrap().clone();
+    events.sort();
+    assert_eq!(
+        events,
+        [
+            ("analyzed", 1, Some(4)),
+            ("analyzed", 2, Some(4)),
+            ("analyzed", 3, Some(4)),
+            ("analyzed", 4, Some(4)),
+            ("read", 1, None),
+            ("read", 2, None),
+            ("read", 3, None),
+            ("read", 4, None),
+        ]
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
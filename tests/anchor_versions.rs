@@ -0,0 +1,78 @@
+//! Rules restricted to some anchor-lang versions only run on the crates built with them
+
+use rust_solana_analyzer::analyzer::dsl::{AstQuery, RuleBuilder};
+use rust_solana_analyzer::analyzer::workspace::Workspace;
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, Result, RuleEngine, RulePlugin, RuleType};
+use rust_solana_analyzer::ast::parser::SourceFile;
+
+const SOURCE: &str = r#"
+pub fn process(ctx: Context<Process>) -> Result<()> {
+    Ok(())
+}
+"#;
+
+/// Rule flagging every function, for the programs built with anchor-lang 0.28 or later
+struct InterfaceRules;
+
+impl RulePlugin for InterfaceRules {
+    fn name(&self) -> &str {
+        "interface"
+    }
+
+    fn register_rules(&self, engine: &mut RuleEngine) -> Result<()> {
+        RuleBuilder::new()
+            .id("interface-accounts")
+            .title("Interface Accounts")
+            .description("Token accounts should use InterfaceAccount")
+            .rule_type(RuleType::Solana)
+            .anchor_versions(">=0.28")
+            .dsl_query(|ast, _file_path, _span_extractor| AstQuery::new(ast).functions())
+            .register(engine)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_rules_only_run_on_matching_anchor_versions() {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-anchor-versions-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"programs/*\"]\n").unwrap();
+    let files: Vec<SourceFile> = [("legacy", "0.26.0"), ("vault", "0.30.1")]
+        .iter()
+        .map(|(program, version)| {
+            let dir = root.join("programs").join(program);
+            std::fs::create_dir_all(dir.join("src")).unwrap();
+            let manifest = format!("[package]\nname = \"{program}\"\n\n[dependencies]\nanchor-lang = \"{version}\"\n");
+            std::fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+            let path = dir.join("src/lib.rs");
+            std::fs::write(&path, SOURCE).unwrap();
+            SourceFile::parse(path, SOURCE.to_string()).unwrap()
+        })
+        .collect();
+
+    let options = AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        include_rules: vec!["interface-accounts".to_string()],
+        workspace: Workspace::load(&root).unwrap(),
+        ..Default::default()
+    };
+    let analyzer = Analyzer::with_rule_plugins(options.clone(), &[&InterfaceRules]);
+    let files_with_findings = |analyzer: Analyzer| -> Vec<String> {
+        let result = analyzer.analyze_files(&files).unwrap();
+        result
+            .findings
+            .iter()
+            .filter(|finding| finding.rule_ids().contains(&"interface-accounts"))
+            .map(|finding| finding.location.file.clone())
+            .collect()
+    };
+    let in_workspace = files_with_findings(analyzer);
+    assert_eq!(in_workspace.len(), 1, "{in_workspace:?}");
+    assert!(in_workspace[0].contains("vault"));
+
+    // Without a workspace the version is unknown, and the rule runs everywhere
+    let analyzer = Analyzer::with_rule_plugins(AnalysisOptions { workspace: None, ..options }, &[&InterfaceRules]);
+    assert_eq!(files_with_findings(analyzer).len(), 2);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,112 @@
+//! `--anchor` analyzes the workspace of `Anchor.toml` and splits the findings by program, named
+//! after the program IDs of `Anchor.toml`, the sources or the artifacts of `anchor build`
+
+use std::path::Path;
+
+use rust_solana_analyzer::analyzer::anchor;
+use rust_solana_analyzer::analyzer::output::Format;
+use rust_solana_analyzer::{ProjectOptions, analyze_project};
+
+const VAULT: &str = r#"
+use anchor_lang::prelude::*;
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        ctx.accounts.vault.balance = amount / ctx.accounts.vault.shares;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+}
+
+#[account]
+pub struct Vault {
+    pub balance: u64,
+    pub shares: u64,
+}
+"#;
+
+const STAKING: &str = r#"
+use anchor_lang::prelude::*;
+
+#[program]
+pub mod staking {
+    use super::*;
+
+    pub fn reward(ctx: Context<Reward>, points: u64, period: u64) -> Result<()> {
+        ctx.accounts.pool.rate = points / period;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Reward<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+}
+
+#[account]
+pub struct Pool {
+    pub rate: u64,
+}
+"#;
+
+const VAULT_ID: &str = "Vau1t11111111111111111111111111111111111111";
+const STAKING_ID: &str = "Stak1ng1111111111111111111111111111111111111";
+
+fn write(path: &Path, contents: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, contents).unwrap();
+}
+
+#[test]
+fn test_reports_by_program() {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-anchor-workflow-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    write(&root.join("Anchor.toml"), &format!("[programs.localnet]\nstaking = \"{STAKING_ID}\"\n"));
+    write(&root.join("Cargo.toml"), "[workspace]\nmembers = [\"programs/*\"]\n\n[profile.release]\noverflow-checks = true\n");
+    for (program, source) in [("vault", VAULT), ("staking", STAKING)] {
+        let manifest = format!("[package]\nname = \"{program}\"\n\n[dependencies]\nanchor-lang = \"0.30.1\"\n");
+        write(&root.join("programs").join(program).join("Cargo.toml"), &manifest);
+        write(&root.join("programs").join(program).join("src/lib.rs"), source);
+    }
+    // Built by `anchor build`: the vault has no program ID but the one of its IDL
+    let idl = format!(r#"{{"address": "{VAULT_ID}", "instructions": [{{"name": "withdraw", "accounts": [{{"name": "vault", "writable": true}}], "args": [{{"name": "amount", "type": "u64"}}]}}]}}"#);
+    write(&root.join("target/idl/vault.json"), &idl);
+    write(&root.join("target/types/vault.ts"), &format!("export type Vault = {{\n  \"address\": \"{VAULT_ID}\",\n}};\n"));
+
+    assert_eq!(anchor::find_root(&root.join("programs/vault/src/lib.rs")), Some(root.clone()));
+    assert_eq!(anchor::find_root(&std::env::temp_dir()), None);
+
+    let options = ProjectOptions::default().with_overrides(|options| {
+        options.cache_dir = None;
+        options.include_rules = vec!["solana-division-by-zero".to_string()];
+    });
+    let analysis = analyze_project(&root, options).unwrap();
+    assert_eq!(analysis.result.findings.len(), 2, "{:#?}", analysis.result.findings);
+
+    let programs = anchor::programs(&root, &analysis.programs);
+    let names: Vec<(&str, &str)> = programs.iter().map(|program| (program.name.as_str(), program.report_name())).collect();
+    assert_eq!(names, [("staking", STAKING_ID), ("vault", VAULT_ID)]);
+    let (staking, vault) = (&programs[0], &programs[1]);
+    assert!(staking.idl.is_none() && staking.types.is_none());
+    assert_eq!(vault.idl.as_deref(), Some(root.join("target/idl/vault.json").as_path()));
+    assert_eq!(vault.types.as_deref(), Some(root.join("target/types/vault.ts").as_path()));
+    assert_eq!(vault.report_path(&root.join(anchor::REPORTS_DIR), Format::Json), root.join(anchor::REPORTS_DIR).join(format!("{VAULT_ID}.json")));
+
+    for program in &programs {
+        let result = program.result(&analysis.result);
+        assert_eq!(result.findings.len(), 1, "{:#?}", result.findings);
+        assert!(Path::new(&result.findings[0].location.file).starts_with(&program.dir));
+        assert_eq!(result.stats.files_analyzed, 1);
+    }
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
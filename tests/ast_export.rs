@@ -0,0 +1,46 @@
+//! The ASTs are exported to a directory, with the spans of their nodes and combined by crate
+
+use rust_solana_analyzer::ProjectOptions;
+use rust_solana_analyzer::project::parse_project;
+use serde_json::Value;
+
+const LIB: &str = "mod state;\n\npub fn share(amount: u64, shares: u64) -> u64 {\n    amount.checked_div(shares).unwrap()\n}\n";
+const STATE: &str = "pub struct Vault {\n    pub amount: u64,\n}\n";
+
+#[test]
+fn test_asts_are_exported_with_spans() {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-ast-export-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("src/lib.rs"), LIB).unwrap();
+    std::fs::write(root.join("src/state.rs"), STATE).unwrap();
+    let ast_dir = root.join("out");
+    let options = ProjectOptions { ast_dir: Some(ast_dir.clone()), combined_ast: true, ..Default::default() };
+
+    parse_project(&root, &options).unwrap();
+
+    let read = |path: &str| -> Value { serde_json::from_str(&std::fs::read_to_string(ast_dir.join(path)).unwrap()).unwrap() };
+    let lib = read("src/lib.json");
+    assert_eq!(lib["file"], "src/lib.rs");
+    assert!(lib["ast"]["items"].is_array());
+
+    // The function spans lines 3 to 5, its method call line 4 from column 4
+    let function = &lib["nodes"][1];
+    assert_eq!(function["kind"], "ItemFn");
+    assert_eq!(function["name"], "share");
+    assert_eq!(function["span"], serde_json::json!({ "line": 3, "column": 0, "end_line": 5, "end_column": 1 }));
+    let body = function["children"].as_array().unwrap();
+    let unwrap = body.iter().find(|node| node["kind"] == "ExprMethodCall").unwrap();
+    assert_eq!(unwrap["name"], "unwrap");
+    assert_eq!(unwrap["span"]["line"], 4);
+    assert_eq!(unwrap["span"]["column"], 4);
+    assert_eq!(unwrap["children"][0]["name"], "checked_div");
+
+    // One AST for the crate, its files in module order
+    let program = read("src/lib.crate.json");
+    assert_eq!(program["crate_root"], "src/lib.rs");
+    assert_eq!(program["modules"][0]["module"], "crate");
+    assert_eq!(program["modules"][1]["module"], "crate::state");
+    assert_eq!(program["modules"][1]["nodes"][0]["children"][0]["name"], "amount");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,167 @@
+//! Machine-applicable fixes rewrite the files of a project (`--fix`), and the findings they fix
+//! are no longer reported once the project is analyzed again
+
+use rust_solana_analyzer::analyzer::Applicability;
+use rust_solana_analyzer::analyzer::fixes::FixPlan;
+use rust_solana_analyzer::{ProjectOptions, analyze_project};
+use std::path::{Path, PathBuf};
+
+const SOURCE: &str = r#"use anchor_lang::prelude::*;
+
+pub fn withdraw(ctx: Context<Withdraw>, amount: u64, shares: u64) -> Result<()> {
+    assert!(amount > 0);
+    let share = amount / shares;
+    ctx.accounts.vault.balance -= share;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = Vault::INIT_SPACE)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+"#;
+
+const RULES: &[&str] = &["solana-division-by-zero", "missing-discriminator-space", "assert-instead-of-require"];
+
+/// Helper function to write an Anchor program
+fn write_project() -> PathBuf {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-autofix-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"vault\"\n\n[dependencies]\nanchor-lang = \"0.30\"\n\n[profile.release]\noverflow-checks = true\n").unwrap();
+    std::fs::write(root.join("src/lib.rs"), SOURCE).unwrap();
+    root
+}
+
+/// Helper function to analyze the project with the rules that have machine-applicable fixes
+fn analyze(root: &Path) -> Vec<rust_solana_analyzer::analyzer::Finding> {
+    let options = ProjectOptions::default().with_overrides(|options| {
+        options.include_rules = RULES.iter().map(|rule| rule.to_string()).collect();
+        options.cache_dir = None;
+    });
+    analyze_project(root, options).unwrap().result.findings
+}
+
+#[test]
+fn test_fixes_resolve_their_findings() {
+    let root = write_project();
+    let before = analyze(&root);
+    let mut rules: Vec<_> = before.iter().flat_map(|finding| finding.rule_ids()).collect();
+    rules.sort();
+    assert_eq!(rules, ["assert-instead-of-require", "missing-discriminator-space", "solana-division-by-zero"]);
+
+    let plan = FixPlan::new(&before, None);
+    assert_eq!(plan.applied.len(), 3, "{:#?}", plan.skipped);
+    assert!(plan.skipped.is_empty());
+    let diff = plan.to_unified_diff();
+    assert!(diff.contains("+    let share = amount.checked_div(shares).ok_or(ProgramError::ArithmeticOverflow)?;\n"), "{diff}");
+
+    // A dry run leaves the files as they are
+    assert_eq!(std::fs::read_to_string(root.join("src/lib.rs")).unwrap(), SOURCE);
+
+    plan.write().unwrap();
+    let fixed = std::fs::read_to_string(root.join("src/lib.rs")).unwrap();
+    assert!(fixed.contains("    require!(amount > 0, anchor_lang::error::ErrorCode::RequireViolated);\n"), "{fixed}");
+    assert!(fixed.contains("space = 8 + Vault::INIT_SPACE)]\n"), "{fixed}");
+
+    let after = analyze(&root);
+    assert!(after.is_empty(), "{after:#?}");
+    assert!(plan.unresolved(&before, &after).is_empty());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+// Divisions the checked_div fix must not rewrite blindly
+const UNSAFE_REWRITES: &str = r#"use anchor_lang::prelude::*;
+
+pub const SCALE: u64 = 1_000;
+
+pub fn scaled(amount: u64) -> Result<u64> {
+    Ok(amount / SCALE)
+}
+
+pub fn shares(amounts: Vec<u64>, total: u64) -> Result<Vec<u64>> {
+    Ok(amounts.iter().map(|amount| amount / total).collect())
+}
+
+pub fn negated(amount: i64, total: i64) -> Result<i64> {
+    Ok(-amount / total)
+}
+
+pub fn ratio(amount: f64, total: f64) -> Result<f64> {
+    let value = amount;
+    Ok(value / total)
+}
+
+pub fn nested(amount: u64, total: u64) -> Result<u64> {
+    fn share(amount: u64, total: u64) -> u64 {
+        amount / total
+    }
+    Ok(share(amount, total))
+}
+"#;
+
+#[test]
+fn test_checked_div_fix_is_only_applied_when_safe() {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-autofix-unsafe-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("src/lib.rs"), UNSAFE_REWRITES).unwrap();
+    let options = ProjectOptions::default().with_overrides(|options| {
+        options.include_rules = vec!["solana-division-by-zero".to_string()];
+        options.cache_dir = None;
+    });
+    let findings = analyze_project(&root, options).unwrap().result.findings;
+
+    // Divisions by a nonzero constant are not reported, closures, nested functions and unary
+    // operands are not rewritten
+    let mut lines: Vec<usize> = findings.iter().map(|finding| finding.location.line).collect();
+    lines.sort();
+    assert_eq!(lines, [9, 13, 17, 22], "{findings:#?}");
+    let fixes: Vec<_> = findings.iter().flat_map(|finding| &finding.fixes).collect();
+    assert_eq!(fixes.len(), 1, "{fixes:#?}");
+    // The type of `value` is unknown, it could be a float
+    assert_eq!(fixes[0].applicability, Applicability::Advisory);
+    assert!(FixPlan::new(&findings, None).applied.is_empty());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+// A native program whose file does not import ProgramError
+const WITHOUT_PROGRAM_ERROR: &str = r#"use solana_program::entrypoint::ProgramResult;
+
+pub fn withdraw(amount: u64, shares: u64) -> ProgramResult {
+    let share = amount / shares;
+    msg!("{}", share);
+    Ok(())
+}
+"#;
+
+#[test]
+fn test_checked_div_fix_needs_program_error_in_scope() {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-autofix-import-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    let applicability = |source: &str| {
+        std::fs::write(root.join("src/lib.rs"), source).unwrap();
+        let options = ProjectOptions::default().with_overrides(|options| {
+            options.include_rules = vec!["solana-division-by-zero".to_string()];
+            options.cache_dir = None;
+        });
+        let findings = analyze_project(&root, options).unwrap().result.findings;
+        let fixes: Vec<_> = findings.iter().flat_map(|finding| &finding.fixes).collect();
+        assert_eq!(fixes.len(), 1, "{fixes:#?}");
+        fixes[0].applicability
+    };
+
+    // `ProgramError::ArithmeticOverflow` would not compile
+    assert_eq!(applicability(WITHOUT_PROGRAM_ERROR), Applicability::Advisory);
+    let imported = WITHOUT_PROGRAM_ERROR.replace(
+        "use solana_program::entrypoint::ProgramResult;",
+        "use solana_program::{entrypoint::ProgramResult, program_error::ProgramError};",
+    );
+    assert_eq!(applicability(&imported), Applicability::MachineApplicable);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
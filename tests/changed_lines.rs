@@ -0,0 +1,40 @@
+//! `--changed-since` reads the lines changed since a git reference, whatever the user's diff
+//! configuration
+
+use std::path::Path;
+use std::process::Command;
+
+use rust_solana_analyzer::analyzer::Location;
+use rust_solana_analyzer::analyzer::diff::ChangedLines;
+
+fn git(dir: &Path, args: &[&str]) -> bool {
+    Command::new("git").arg("-C").arg(dir).args(args).output().is_ok_and(|output| output.status.success())
+}
+
+#[test]
+fn test_changed_lines_ignore_the_diff_configuration() {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-changed-lines-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    if !git(&root, &["init", "-q"]) {
+        eprintln!("git is not available, skipping");
+        return;
+    }
+    for (key, value) in [("diff.noprefix", "true"), ("diff.mnemonicPrefix", "true"), ("core.quotePath", "true")] {
+        assert!(git(&root, &["config", key, value]));
+    }
+    std::fs::write(root.join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+    std::fs::write(root.join("src/état.rs"), "pub fn b() {}\n").unwrap();
+    assert!(git(&root, &["add", "."]));
+    assert!(git(&root, &["-c", "user.name=test", "-c", "user.email=test@example.com", "commit", "-q", "-m", "init"]));
+    std::fs::write(root.join("src/lib.rs"), "pub fn a() {}\n\npub fn c() {}\n").unwrap();
+    std::fs::write(root.join("src/état.rs"), "pub fn b() {\n}\n").unwrap();
+
+    let changed = ChangedLines::from_git(&root, "HEAD").unwrap();
+    let at = |file: &str, line| Location::new_precise(root.join(file).display().to_string(), line, None, None, None);
+    assert!(changed.intersects(&at("src/lib.rs", 3)));
+    assert!(!changed.intersects(&at("src/lib.rs", 1)));
+    assert!(changed.intersects(&at("src/état.rs", 1)));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,46 @@
+//! Findings over the `--fail-on` / `--max-findings` thresholds fail the analysis
+
+use rust_solana_analyzer::analyzer::{AnalysisOptions, AnalysisResult, Analyzer, RuleType, Severity};
+use rust_solana_analyzer::ast::parser::SourceFile;
+
+// A division (medium) and an unsafe block (high)
+const SOURCE: &str = r#"
+pub fn share(amount: u64, shares: u64) -> u64 {
+    amount / shares
+}
+
+pub fn raw(value: *const u64) -> u64 {
+    unsafe { *value }
+}
+"#;
+
+fn analyze() -> AnalysisResult {
+    let options = AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        include_rules: vec!["solana-division-by-zero".to_string(), "solana-unsafe-code".to_string()],
+        ..Default::default()
+    };
+    let file = SourceFile::parse("src/lib.rs", SOURCE.to_string()).unwrap();
+    Analyzer::with_options(options).analyze_files([file]).unwrap()
+}
+
+#[test]
+fn test_thresholds() {
+    let result = analyze();
+    let severities: Vec<&Severity> = result.findings.iter().map(|finding| &finding.severity).collect();
+    assert_eq!(result.findings.len(), 2, "{severities:?}");
+    assert_eq!(result.count_at_least(None), 2);
+    assert_eq!(result.count_at_least(Some(&Severity::High)), 1);
+
+    // No threshold never fails
+    assert!(!result.exceeds_thresholds(None, None));
+
+    assert!(result.exceeds_thresholds(Some(&Severity::High), None));
+    assert!(result.exceeds_thresholds(Some(&Severity::Medium), Some(1)));
+    assert!(!result.exceeds_thresholds(Some(&Severity::Medium), Some(2)));
+    assert!(!result.exceeds_thresholds(Some(&Severity::High), Some(1)));
+
+    // Without --fail-on, every finding counts
+    assert!(result.exceeds_thresholds(None, Some(1)));
+    assert!(!result.exceeds_thresholds(None, Some(2)));
+}
@@ -0,0 +1,90 @@
+//! Findings and reports are the same from run to run, whatever the order of the files and the number of jobs
+
+use rust_solana_analyzer::analyzer::reporting::ReportGenerator;
+use rust_solana_analyzer::analyzer::{AnalysisOptions, AnalysisResult, Analyzer, RuleType};
+use rust_solana_analyzer::ast::parser::{self, SourceText};
+use rust_solana_analyzer::{ProjectOptions, analyze_project};
+use std::path::{Path, PathBuf};
+
+/// Helper function to copy the bad fixtures of every rule into a project, one directory per rule
+fn write_project(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-deterministic-{name}-{}", std::process::id()));
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    for entry in std::fs::read_dir(&fixtures).unwrap() {
+        let rule_dir = entry.unwrap().path();
+        let dir = root.join(rule_dir.file_name().unwrap());
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in std::fs::read_dir(rule_dir.join("bad")).unwrap() {
+            let file = file.unwrap().path();
+            if file.extension().is_some_and(|extension| extension == "rs") {
+                std::fs::copy(&file, dir.join(file.file_name().unwrap())).unwrap();
+            }
+        }
+    }
+    root
+}
+
+fn analyze(files: &[SourceText], jobs: usize) -> AnalysisResult {
+    Analyzer::with_options(AnalysisOptions {
+        include_rule_types: RuleType::ALL.to_vec(),
+        jobs,
+        ..Default::default()
+    })
+    .analyze_files(files)
+    .unwrap()
+}
+
+fn report(root: &Path, result: &AnalysisResult) -> String {
+    ReportGenerator::new(result.findings.clone(), root.to_string_lossy().to_string()).generate_markdown_report()
+}
+
+#[test]
+fn test_files_are_walked_in_path_order() {
+    let root = write_project("walk");
+
+    let paths: Vec<PathBuf> = parser::walk_directory_excluding(&root, |_| false).map(|file| file.path).collect();
+
+    let mut sorted = paths.clone();
+    sorted.sort();
+    assert!(paths.len() > 10);
+    assert_eq!(paths, sorted);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_reports_do_not_depend_on_the_file_order() {
+    let root = write_project("order");
+    let files: Vec<SourceText> = parser::walk_directory_excluding(&root, |_| false).collect();
+    let reversed: Vec<SourceText> = files.iter().rev().cloned().collect();
+
+    let result = analyze(&files, 1);
+    assert!(!result.findings.is_empty());
+    assert!(result.findings.is_sorted_by(|a, b| {
+        (&a.location.file, a.location.line, a.location.column) <= (&b.location.file, b.location.line, b.location.column)
+    }));
+
+    let expected = report(&root, &result);
+    assert_eq!(report(&root, &analyze(&reversed, 1)), expected);
+    assert_eq!(report(&root, &analyze(&reversed, 4)), expected);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_project_reports_are_byte_identical() {
+    let root = write_project("project");
+    let run = || {
+        let options = ProjectOptions::default().with_overrides(|options| options.cache_dir = None);
+        let project = analyze_project(&root, options).unwrap();
+        (project.files, report(&root, &project.result))
+    };
+
+    let (files, first) = run();
+    let (_, second) = run();
+
+    assert!(files.is_sorted_by(|a, b| a.path <= b.path));
+    assert_eq!(first.as_bytes(), second.as_bytes());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
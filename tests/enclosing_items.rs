@@ -0,0 +1,61 @@
+//! Findings name the item and the module containing them, across the files of a crate
+
+use rust_solana_analyzer::analyzer::items::{ENCLOSING_ITEM_KEY, MODULE_PATH_KEY, qualified_name};
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, Finding, RuleType};
+use rust_solana_analyzer::ast::parser::SourceFile;
+
+const LIB: &str = "pub mod instructions;\n";
+
+const INSTRUCTIONS: &str = "pub mod withdraw;\n";
+
+const WITHDRAW: &str = r#"
+pub fn handler(amount: u64, shares: u64) -> u64 {
+    amount / shares
+}
+
+pub mod math {
+    pub fn ratio(left: u64, right: u64) -> u64 {
+        left / right
+    }
+}
+"#;
+
+fn analyze(files: &[(&str, &str)]) -> Vec<Finding> {
+    let options = AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        include_rules: vec!["solana-division-by-zero".to_string()],
+        ..Default::default()
+    };
+    let files: Vec<SourceFile> = files
+        .iter()
+        .map(|(path, source)| SourceFile::parse(*path, source.to_string()).unwrap())
+        .collect();
+    let result = Analyzer::with_options(options).analyze_files(files).unwrap();
+    assert_eq!(result.findings.len(), 2, "{:#?}", result.findings);
+    result.findings
+}
+
+#[test]
+fn test_enclosing_items() {
+    let findings = analyze(&[
+        ("src/lib.rs", LIB),
+        ("src/instructions/mod.rs", INSTRUCTIONS),
+        ("src/instructions/withdraw.rs", WITHDRAW),
+    ]);
+    let names: Vec<Option<String>> = findings.iter().map(qualified_name).collect();
+    assert_eq!(
+        names,
+        [Some("instructions::withdraw::handler".to_string()), Some("instructions::withdraw::math::ratio".to_string())]
+    );
+    assert_eq!(findings[0].metadata[ENCLOSING_ITEM_KEY].to_string(), "handler");
+    assert_eq!(findings[0].metadata[MODULE_PATH_KEY].to_string(), "instructions::withdraw");
+}
+
+#[test]
+fn test_enclosing_items_outside_crates() {
+    // A file no crate root reaches only has its inline modules
+    let findings = analyze(&[("programs/withdraw.rs", WITHDRAW)]);
+    assert_eq!(qualified_name(&findings[0]).as_deref(), Some("handler"));
+    assert!(!findings[0].metadata.contains_key(MODULE_PATH_KEY));
+    assert_eq!(qualified_name(&findings[1]).as_deref(), Some("math::ratio"));
+}
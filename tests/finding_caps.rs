@@ -0,0 +1,63 @@
+//! Findings over the `--max-per-rule` / `--max-per-file` caps are left out and counted
+
+use rust_solana_analyzer::analyzer::caps::FindingCaps;
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, RuleType, Severity};
+use rust_solana_analyzer::ast::parser::SourceFile;
+
+// Three divisions (medium) and an unsafe block (high)
+const SOURCE: &str = r#"
+pub fn share(amount: u64, shares: u64) -> u64 {
+    amount / shares
+}
+
+pub fn half(amount: u64, divisor: u64) -> u64 {
+    amount / divisor
+}
+
+pub fn ratio(left: u64, right: u64) -> u64 {
+    left / right
+}
+
+pub fn raw(value: *const u64) -> u64 {
+    unsafe { *value }
+}
+"#;
+
+fn analyze(finding_caps: FindingCaps) -> Vec<(Severity, usize)> {
+    let options = AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        include_rules: vec!["solana-division-by-zero".to_string(), "solana-unsafe-code".to_string()],
+        finding_caps,
+        ..Default::default()
+    };
+    let file = SourceFile::parse("src/lib.rs", SOURCE.to_string()).unwrap();
+    let result = Analyzer::with_options(options).analyze_files([file]).unwrap();
+    let omitted: usize = result.stats.findings_omitted.values().sum();
+    assert_eq!(result.findings.len() + omitted, 4, "{:#?}", result.stats.findings_omitted);
+    let mut findings: Vec<(Severity, usize)> = result
+        .findings
+        .iter()
+        .map(|finding| (finding.severity.clone(), finding.location.line))
+        .collect();
+    findings.sort_by_key(|(_, line)| *line);
+    findings
+}
+
+#[test]
+fn test_finding_caps() {
+    assert_eq!(analyze(FindingCaps::default()).len(), 4);
+
+    let per_rule = analyze(FindingCaps {
+        per_rule: Some(1),
+        per_file: None,
+    });
+    assert_eq!(per_rule, vec![(Severity::Medium, 2), (Severity::High, 14)]);
+
+    // The most severe findings of the file are kept
+    let per_file = analyze(FindingCaps {
+        per_rule: None,
+        per_file: Some(2),
+    });
+    assert_eq!(per_file.len(), 2);
+    assert!(per_file.contains(&(Severity::High, 14)), "{per_file:?}");
+}
@@ -0,0 +1,56 @@
+//! Findings carry the lines of source around them with `--context N`
+
+use rust_solana_analyzer::analyzer::dsl::query::MetadataValue;
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, CONTEXT_KEY, Finding, RuleType};
+use rust_solana_analyzer::ast::parser::SourceFile;
+
+const SOURCE: &str = "pub const FEE: u64 = 1;
+
+pub fn share(amount: u64, shares: u64) -> u64 {
+    amount / shares + FEE
+}
+
+pub const MAX: u64 = 100;
+";
+
+fn analyze(context_lines: usize) -> Vec<Finding> {
+    let options = AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        include_rules: vec!["solana-division-by-zero".to_string()],
+        context_lines,
+        ..Default::default()
+    };
+    let file = SourceFile::parse("src/lib.rs", SOURCE.to_string()).unwrap();
+    let result = Analyzer::with_options(options).analyze_files([file]).unwrap();
+    assert_eq!(result.findings.len(), 1, "{:#?}", result.findings);
+    result.findings
+}
+
+fn context(finding: &Finding) -> Option<&str> {
+    match finding.metadata.get(CONTEXT_KEY) {
+        Some(MetadataValue::Text(context)) => Some(context),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_finding_context() {
+    assert_eq!(context(&analyze(0)[0]), None);
+
+    let findings = analyze(1);
+    let lines: Vec<&str> = context(&findings[0]).unwrap().lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "    2 | ",
+            "→   3 | pub fn share(amount: u64, shares: u64) -> u64 {",
+            "→   4 |     amount / shares + FEE",
+            "→   5 | }",
+            "    6 | ",
+        ]
+    );
+
+    // Bounded by the file
+    let whole_file = analyze(100);
+    assert_eq!(context(&whole_file[0]).map(|context| context.lines().count()), Some(7));
+}
@@ -0,0 +1,74 @@
+//! Findings are sent to the sink of the analyzer as they are produced
+
+use std::sync::{Arc, Mutex};
+
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, Finding, RuleType, Severity};
+use rust_solana_analyzer::ast::parser::SourceFile;
+
+const LIB: &str = "pub fn share(amount: u64, shares: u64) -> u64 {
+    amount / shares
+}
+
+#[cfg(test)]
+mod tests {
+    pub fn half(amount: u64, parts: u64) -> u64 {
+        amount / parts
+    }
+}
+";
+
+const VAULT: &str = "pub fn fee(amount: u64, rate: u64) -> u64 {
+    amount / rate
+}
+";
+
+fn analyze(options: AnalysisOptions) -> (Vec<Finding>, Vec<Finding>) {
+    let streamed = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&streamed);
+    let options = AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        include_rules: vec!["solana-division-by-zero".to_string()],
+        jobs: 2,
+        ..options
+    };
+    let files = [
+        SourceFile::parse("src/lib.rs", LIB.to_string()).unwrap(),
+        SourceFile::parse("src/vault.rs", VAULT.to_string()).unwrap(),
+    ];
+    let result = Analyzer::with_options(options)
+        .with_finding_sink(Box::new(move |finding| sink.lock().unwrap().push(finding.clone())))
+        .analyze_files(files)
+        .unwrap();
+    let streamed = streamed.lock().unwrap().clone();
+    (streamed, result.findings)
+}
+
+fn locations(findings: &[Finding]) -> Vec<(String, usize)> {
+    let mut locations: Vec<_> = findings.iter().map(|finding| (finding.location.file.clone(), finding.location.line)).collect();
+    locations.sort();
+    locations
+}
+
+#[test]
+fn test_findings_are_streamed() {
+    let (streamed, findings) = analyze(AnalysisOptions::default());
+
+    // The findings of the test module are left out, like in the result
+    assert_eq!(locations(&findings), [("src/lib.rs".to_string(), 1), ("src/vault.rs".to_string(), 1)]);
+    assert_eq!(locations(&streamed), locations(&findings));
+
+    // Streamed before the fingerprints are computed
+    assert!(streamed.iter().all(|finding| finding.fingerprint.is_none()));
+    assert!(findings.iter().all(|finding| finding.fingerprint.is_some()));
+}
+
+#[test]
+fn test_streamed_findings_are_filtered() {
+    let severity = analyze(AnalysisOptions::default()).1[0].severity.clone();
+    let (streamed, findings) = analyze(AnalysisOptions { ignore_severities: vec![severity], ..Default::default() });
+    assert!(findings.is_empty());
+    assert!(streamed.is_empty());
+
+    let (streamed, _) = analyze(AnalysisOptions { ignore_severities: vec![Severity::Informational], ..Default::default() });
+    assert_eq!(streamed.len(), 2);
+}
@@ -0,0 +1,3 @@
+pub fn compute_fee(amount: u64) -> u64 {
+    amount.saturating_mul(3) / 1000
+}
@@ -0,0 +1,7 @@
+use anchor_lang::prelude::*;
+
+pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    assert!(amount <= ctx.accounts.vault.balance);
+    ctx.accounts.vault.balance -= amount;
+    Ok(())
+}
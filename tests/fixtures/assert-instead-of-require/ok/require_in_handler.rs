@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    require!(amount <= ctx.accounts.vault.balance, VaultError::InsufficientFunds);
+    ctx.accounts.vault.balance -= amount;
+    Ok(())
+}
+
+fn share(amount: u64, shares: u64) -> u64 {
+    assert!(shares > 0);
+    amount / shares
+}
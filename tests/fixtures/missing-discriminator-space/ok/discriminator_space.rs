@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = 8 + Vault::INIT_SPACE)]
+    pub vault: Account<'info, Vault>,
+    #[account(init, payer = payer, space = Config::LEN)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
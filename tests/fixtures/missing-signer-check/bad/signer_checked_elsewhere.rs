@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        check_signer(&ctx.accounts.payer)?;
+        Ok(())
+    }
+
+    pub fn sweep(ctx: Context<Withdraw>) -> Result<()> {
+        check_signer(&ctx.accounts.authority)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub authority: AccountInfo<'info>,
+    pub payer: Signer<'info>,
+}
+
+fn check_signer(account: &AccountInfo) -> Result<()> {
+    require!(account.is_signer, VaultError::Unauthorized);
+    Ok(())
+}
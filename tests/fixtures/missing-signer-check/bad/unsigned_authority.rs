@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Inspect<'info> {
+    pub vault: Account<'info, Vault>,
+    pub authority: AccountInfo<'info>,
+}
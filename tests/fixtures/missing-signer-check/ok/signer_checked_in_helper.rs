@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        ctx.accounts.validate()?;
+        Ok(())
+    }
+
+    #[access_control(check_signer(&ctx.accounts.authority))]
+    pub fn close(ctx: Context<Close>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub authority: AccountInfo<'info>,
+}
+
+impl<'info> Withdraw<'info> {
+    pub fn validate(&self) -> Result<()> {
+        check_signer(&self.authority.to_account_info())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Close<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: UncheckedAccount<'info>,
+}
+
+fn check_signer(account: &AccountInfo) -> Result<()> {
+    require!(account.is_signer, VaultError::Unauthorized);
+    Ok(())
+}
@@ -0,0 +1,19 @@
+entrypoint!(process_instruction);
+
+fn transfer(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let source = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    let instruction = Instruction {
+        program_id: *token_program.key,
+        accounts: vec![
+            AccountMeta::new(*source.key, false),
+            AccountMeta::new(*destination.key, false),
+            AccountMeta::new_readonly(*authority.key, true),
+        ],
+        data: amount.to_le_bytes().to_vec(),
+    };
+    invoke(&instruction, &[source.clone(), destination.clone(), authority.clone()])
+}
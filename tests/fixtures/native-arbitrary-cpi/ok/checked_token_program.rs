@@ -0,0 +1,35 @@
+entrypoint!(process_instruction);
+
+fn transfer(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let source = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    if token_program.key != &spl_token::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let instruction = Instruction::new_with_bytes(*token_program.key, &amount.to_le_bytes(), vec![]);
+    invoke(&instruction, &[source.clone(), destination.clone()])
+}
+
+fn transfer_with_steel(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [source, destination, token_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    token_program.is_program(&spl_token::ID)?;
+    let instruction = Instruction {
+        program_id: *token_program.key,
+        accounts: vec![],
+        data: data.to_vec(),
+    };
+    invoke(&instruction, &[source.clone(), destination.clone()])
+}
+
+fn transfer_to_known_program(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let instruction = Instruction {
+        program_id: spl_token::ID,
+        accounts: vec![],
+        data: data.to_vec(),
+    };
+    invoke(&instruction, accounts)
+}
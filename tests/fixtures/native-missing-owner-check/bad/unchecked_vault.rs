@@ -0,0 +1,7 @@
+entrypoint!(process_instruction);
+
+fn read_balance(accounts: &[AccountInfo]) -> Result<u64, ProgramError> {
+    let vault = next_account_info(&mut accounts.iter())?;
+    let state = Vault::try_from_slice(&vault.data.borrow())?;
+    Ok(state.balance)
+}
@@ -0,0 +1,14 @@
+entrypoint!(process_instruction);
+
+fn read_balance(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<u64, ProgramError> {
+    let vault = next_account_info(&mut accounts.iter())?;
+    if vault.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let state = Vault::try_from_slice(&vault.data.borrow())?;
+    Ok(state.balance)
+}
+
+fn parse_instruction(accounts: &[AccountInfo], data: &[u8]) -> Result<Instruction, ProgramError> {
+    Instruction::try_from_slice(data).map_err(|_| ProgramError::InvalidInstructionData)
+}
@@ -0,0 +1,10 @@
+entrypoint!(process_instruction);
+
+fn withdraw(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+    **vault.try_borrow_mut_lamports()? -= amount;
+    **destination.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
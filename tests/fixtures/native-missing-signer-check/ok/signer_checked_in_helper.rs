@@ -0,0 +1,17 @@
+entrypoint!(process_instruction);
+
+fn withdraw(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let authority = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    check_signer(authority)?;
+    **vault.try_borrow_mut_lamports()? -= amount;
+    Ok(())
+}
+
+fn check_signer(account: &AccountInfo) -> ProgramResult {
+    if !account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
@@ -0,0 +1,9 @@
+entrypoint!(process_instruction);
+
+fn close(accounts: &[AccountInfo]) -> ProgramResult {
+    let vault = &accounts[0];
+    let destination = &accounts[1];
+    **destination.try_borrow_mut_lamports()? += vault.lamports();
+    **vault.try_borrow_mut_lamports()? = 0;
+    Ok(())
+}
@@ -0,0 +1,19 @@
+entrypoint!(process_instruction);
+
+fn close(accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+    **destination.try_borrow_mut_lamports()? += vault.lamports();
+    **vault.try_borrow_mut_lamports()? = 0;
+    Ok(())
+}
+
+fn transfer(accounts: &[AccountInfo]) -> ProgramResult {
+    if accounts.len() < 2 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let source = &accounts[0];
+    let destination = &accounts[1];
+    Ok(())
+}
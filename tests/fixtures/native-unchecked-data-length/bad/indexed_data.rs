@@ -0,0 +1,9 @@
+entrypoint!(process_instruction);
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+    let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+    match instruction_data[0] {
+        0 => deposit(accounts, amount),
+        _ => withdraw(accounts, amount),
+    }
+}
@@ -0,0 +1,17 @@
+entrypoint!(process_instruction);
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+    if instruction_data.len() < 9 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+    match instruction_data[0] {
+        0 => deposit(accounts, amount),
+        _ => withdraw(accounts, amount),
+    }
+}
+
+fn parse_amount(data: &[u8]) -> Result<u64, ProgramError> {
+    let (tag, rest) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+    rest.get(..8).and_then(|bytes| bytes.try_into().ok()).map(u64::from_le_bytes).ok_or(ProgramError::InvalidInstructionData)
+}
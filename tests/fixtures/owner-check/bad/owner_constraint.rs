@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    #[account(mut, owner = crate::ID)]
+    pub config: AccountInfo<'info>,
+    pub authority: Signer<'info>,
+}
@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    #[account(mut)]
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+}
@@ -0,0 +1,15 @@
+use pinocchio::{account_info::AccountInfo, entrypoint, ProgramResult};
+use pinocchio_token::state::TokenAccount;
+
+entrypoint!(process_instruction);
+
+fn deposit(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let [vault, depositor_token, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let token_account = unsafe { TokenAccount::from_account_info_unchecked(depositor_token)? };
+    if token_account.amount() < amount {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    Ok(())
+}
@@ -0,0 +1,7 @@
+pub fn share(amount: u64, total: u64) -> u64 {
+    amount / total
+}
+
+pub fn half(amount: u64) -> u64 {
+    amount / 2
+}
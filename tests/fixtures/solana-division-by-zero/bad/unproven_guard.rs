@@ -0,0 +1,23 @@
+pub fn share(amount: u64, shares: u64, strict: bool) -> Result<u64> {
+    if strict {
+        require!(shares > 0, VaultError::NoShares);
+    }
+    Ok(amount / shares)
+}
+
+pub fn drain(amount: u64, mut shares: u64) -> Result<u64> {
+    require!(shares > 0, VaultError::NoShares);
+    shares -= 1;
+    Ok(amount / shares)
+}
+
+pub fn split(amount: u64, mut parts: u64) -> u64 {
+    let mut total = 0;
+    let mut step = 4;
+    while parts > 0 {
+        total += amount / step;
+        step -= 1;
+        parts -= 1;
+    }
+    total
+}
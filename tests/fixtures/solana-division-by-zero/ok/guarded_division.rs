@@ -0,0 +1,32 @@
+const FEE_DENOMINATOR: u64 = 10_000;
+const HALF: u64 = FEE_DENOMINATOR / 2;
+
+pub fn fee(amount: u64, fee_bps: u64) -> u64 {
+    amount * fee_bps / FEE_DENOMINATOR
+}
+
+pub fn half_fee(amount: u64) -> u64 {
+    amount / HALF
+}
+
+pub fn share(amount: u64, shares: u64) -> Result<u64> {
+    require!(shares > 0, VaultError::NoShares);
+    Ok(amount / shares)
+}
+
+pub fn price(ctx: Context<Swap>, amount: u64) -> Result<u64> {
+    let reserve = ctx.accounts.pool.reserve;
+    if reserve == 0 {
+        return err!(PoolError::Empty);
+    }
+    Ok(amount / reserve)
+}
+
+pub fn average(total: u64, count: u64) -> u64 {
+    if count != 0 { total / count } else { 0 }
+}
+
+pub fn ratio(a: u64, b: u64) -> u64 {
+    let divisor = b.max(1);
+    a / divisor
+}
@@ -0,0 +1,7 @@
+pub fn checked_fee(amount: u64) -> Result<u64> {
+    Ok(amount.saturating_mul(3) / 1000)
+}
+
+fn internal_fee(amount: u64) -> u64 {
+    amount.saturating_mul(3) / 1000
+}
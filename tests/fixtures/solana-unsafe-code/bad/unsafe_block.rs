@@ -0,0 +1,7 @@
+pub fn read_balance(data: &[u8]) -> u64 {
+    unsafe { *(data.as_ptr() as *const u64) }
+}
+
+pub fn safe_read(data: &[u8]) -> u64 {
+    u64::from_le_bytes(data[..8].try_into().unwrap())
+}
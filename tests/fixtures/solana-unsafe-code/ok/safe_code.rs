@@ -0,0 +1,10 @@
+pub fn read_balance(data: &[u8]) -> u64 {
+    u64::from_le_bytes(data[..8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    fn raw_read(data: &[u8]) -> u64 {
+        unsafe { *(data.as_ptr() as *const u64) }
+    }
+}
@@ -0,0 +1,13 @@
+use steel::*;
+
+pub fn process_withdraw(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    let args = Withdraw::try_from_bytes(data)?;
+    let [signer_info, vault_info, destination_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    let vault = vault_info.as_account_mut::<Vault>(&crate::ID)?;
+    vault.balance -= args.amount;
+    destination_info.collect(args.amount, vault_info)?;
+    Ok(())
+}
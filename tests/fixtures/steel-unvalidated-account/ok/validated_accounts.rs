@@ -0,0 +1,15 @@
+use steel::*;
+
+pub fn process_withdraw(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    let args = Withdraw::try_from_bytes(data)?;
+    let [signer_info, vault_info, destination_info, _system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    let vault = vault_info
+        .as_account_mut::<Vault>(&crate::ID)?
+        .assert_mut(|vault| vault.authority == *signer_info.key)?;
+    destination_info.is_writable()?.has_address(&vault.destination)?;
+    vault.balance -= args.amount;
+    Ok(())
+}
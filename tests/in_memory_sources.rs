@@ -0,0 +1,118 @@
+//! Sources are analyzed as they were parsed, without reading the files again (or any file, for
+//! the sources held in memory), and files that cannot be parsed are reported
+
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, PARSE_ERROR_ID, RuleType, Severity};
+use rust_solana_analyzer::analyzer::results::Sources;
+use rust_solana_analyzer::ast::parser::SourceFile;
+use rust_solana_analyzer::{Format, ProjectOptions, Renderer, analyze_sources};
+
+const SOURCE: &str = "pub fn raw(value: *const u64) -> u64 {\n    unsafe { *value }\n}\n";
+
+fn analyzer() -> Analyzer {
+    Analyzer::with_options(AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        include_rules: vec!["solana-unsafe-code".to_string()],
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_in_memory_sources_are_analyzed() {
+    // No file exists at this path
+    let file = SourceFile::parse("buffers/lib.rs", SOURCE.to_string()).unwrap();
+
+    let findings = analyzer().analyze_file(&file).unwrap();
+    assert_eq!(findings.len(), 1, "{findings:#?}");
+    assert_eq!(findings[0].location.file, "buffers/lib.rs");
+    assert_eq!(findings[0].location.line, 1);
+
+    let result = analyzer().analyze_files(&[file]).unwrap();
+    assert_eq!(result.findings.len(), 1, "{:#?}", result.findings);
+    assert_eq!(result.findings[0].location.line, 1);
+}
+
+#[test]
+fn test_files_changed_on_disk_keep_their_parsed_source() {
+    let dir = std::env::temp_dir().join(format!("solana-analyzer-in-memory-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("lib.rs");
+    std::fs::write(&path, SOURCE).unwrap();
+    let file = SourceFile::read(&path).unwrap();
+
+    // The file changes after it was parsed: locations still match the parsed source
+    std::fs::write(&path, format!("\n\n\n{SOURCE}")).unwrap();
+    let result = analyzer().analyze_files(&[file]).unwrap();
+    assert_eq!(result.findings.len(), 1, "{:#?}", result.findings);
+    assert_eq!(result.findings[0].location.line, 1);
+    assert!(result.findings[0].code_snippet.as_deref().is_some_and(|snippet| snippet.contains("unsafe")));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_unparseable_files_are_reported() {
+    let dir = std::env::temp_dir().join(format!("solana-analyzer-unparseable-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("lib.rs"), SOURCE).unwrap();
+    std::fs::write(dir.join("broken.rs"), "pub fn broken() {\n    let = 1;\n}\n").unwrap();
+
+    let files = rust_solana_analyzer::ast::parser::walk_directory_excluding(&dir, |_| false);
+    let result = analyzer().analyze_files(files).unwrap();
+    assert_eq!(result.stats.files_analyzed, 1);
+    assert_eq!(result.stats.files_unparsed, 1);
+    let parse_error = result
+        .findings
+        .iter()
+        .find(|finding| finding.rule_ids().contains(&PARSE_ERROR_ID))
+        .unwrap();
+    assert!(parse_error.location.file.ends_with("broken.rs"), "{parse_error:#?}");
+    assert_eq!(parse_error.location.line, 2);
+    assert_eq!(parse_error.severity, Severity::Informational);
+    assert!(parse_error.fingerprint.is_some());
+
+    // Parse errors are ignored like a rule
+    let options = AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        include_rules: vec!["solana-unsafe-code".to_string()],
+        ignore_rules: vec![PARSE_ERROR_ID.to_string()],
+        ..Default::default()
+    };
+    let file = SourceFile::load(&dir.join("broken.rs")).unwrap();
+    assert!(file.parse_error.is_some());
+    assert!(Analyzer::with_options(options).analyze_file(&file).unwrap().is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_sources_are_analyzed_in_their_project() {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-stdin-{}", std::process::id()));
+    let dir = root.join("programs/vault");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"programs/*\"]\n\n[profile.release]\noverflow-checks = true\n").unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"vault\"\n\n[dependencies]\nsolana-program = \"1.18\"\n")
+        .unwrap();
+
+    // The code is piped, no file exists at its path
+    let source = "pub fn share(amount: u64, shares: u64) -> u64 {\n    amount / shares\n}\n";
+    let file = SourceFile::from_source(dir.join("src/lib.rs"), source.to_string());
+    let options = ProjectOptions::default().with_overrides(|options| {
+        options.include_rules = vec!["solana-division-by-zero".to_string()];
+        options.cache_dir = None;
+    });
+    let project = analyze_sources(&root, vec![file], options).unwrap();
+    assert_eq!(project.files.len(), 1);
+    assert_eq!(project.files[0].program.as_deref(), Some("vault"));
+    assert_eq!(project.result.findings.len(), 1, "{:#?}", project.result.findings);
+
+    // The reports show the diffs of the fixes from the source in memory
+    let path = dir.join("src/lib.rs").to_string_lossy().to_string();
+    let sources: Sources = [(path, source.to_string())].into_iter().collect();
+    let markdown = Renderer::new(&project.result, "-", ".".as_ref()).render(Format::Markdown);
+    assert!(!markdown.contains("```diff"));
+    let markdown = Renderer::new(&project.result, "-", ".".as_ref()).with_sources(&sources).render(Format::Markdown);
+    assert!(markdown.contains("```diff"), "{markdown}");
+    assert!(markdown.contains("+    amount.checked_div(shares)"), "{markdown}");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
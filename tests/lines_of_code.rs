@@ -0,0 +1,55 @@
+//! Lines of code are counted while parsing and reported with the findings of each file and rule
+
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, RuleType, results};
+use rust_solana_analyzer::ast::parser::{SourceFile, count_lines_of_code};
+
+#[test]
+fn test_blank_and_comment_lines_are_not_code() {
+    let source = r#"//! Vault program
+
+/// Withdraw from the vault
+pub fn withdraw(amount: u64, shares: u64) -> u64 {
+    /* a block comment
+       /* nested */
+       still a comment */
+    let quote = '"'; // not a string
+    let text = "// not a comment
+    /* nor this */";
+    amount / shares // code with a comment
+}
+"#;
+
+    // The signature, the two lets (the second string spans two lines), the division and the brace
+    assert_eq!(count_lines_of_code(source), 6);
+    assert_eq!(SourceFile::parse("lib.rs", source.to_string()).unwrap().lines_of_code, 6);
+    assert_eq!(count_lines_of_code(""), 0);
+}
+
+#[test]
+fn test_stats_break_down_the_findings() {
+    let files = vec![
+        SourceFile::parse(
+            "programs/vault/src/lib.rs",
+            "pub fn withdraw(amount: u64, shares: u64) -> u64 {\n    amount / shares\n}\n".to_string(),
+        )
+        .unwrap(),
+        SourceFile::parse("programs/vault/src/state.rs", "// State\npub struct Vault {\n    pub amount: u64,\n}\n".to_string())
+            .unwrap(),
+    ];
+    let result = Analyzer::with_options(AnalysisOptions {
+        include_rule_types: RuleType::ALL.to_vec(),
+        include_rules: vec!["solana-division-by-zero".to_string()],
+        ..Default::default()
+    })
+    .analyze_files(&files)
+    .unwrap();
+
+    let stats = &result.stats;
+    assert_eq!(stats.lines_of_code.get("programs/vault/src/lib.rs"), Some(&3));
+    assert_eq!(stats.lines_of_code.get("programs/vault/src/state.rs"), Some(&3));
+    assert_eq!(stats.total_lines_of_code(), 6);
+    assert_eq!(stats.findings_by_file.get("programs/vault/src/lib.rs"), Some(&1));
+    assert_eq!(stats.findings_by_rule.get("solana-division-by-zero"), Some(&1));
+    assert_eq!(results::density(1, stats.total_lines_of_code()), Some(1000.0 / 6.0));
+    assert_eq!(results::density(1, 0), None);
+}
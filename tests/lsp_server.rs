@@ -0,0 +1,101 @@
+//! The language server publishes the findings of the opened and saved files as diagnostics
+
+use rust_solana_analyzer::ProjectOptions;
+use rust_solana_analyzer::lsp::{Server, path_to_uri, read_message, write_message};
+use serde_json::{Value, json};
+use std::path::PathBuf;
+
+const SOURCE: &str = r#"pub fn share(amount: u64, shares: u64) -> u64 {
+    amount / shares
+}
+"#;
+
+const FIXED: &str = r#"pub fn share(amount: u64, shares: u64) -> u64 {
+    amount.checked_div(shares).unwrap_or(0)
+}
+"#;
+
+/// Helper function to write a workspace of one program
+fn write_project(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-lsp-{name}-{}", std::process::id()));
+    let dir = root.join("programs/vault");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"programs/*\"]\n").unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"vault\"\n\n[dependencies]\nsolana-program = \"1.18\"\n")
+        .unwrap();
+    std::fs::write(dir.join("src/lib.rs"), SOURCE).unwrap();
+    root.canonicalize().unwrap()
+}
+
+/// Helper function to run a session of messages, returning the messages of the server
+fn session(root: &std::path::Path, messages: &[Value]) -> Vec<Value> {
+    let mut input = Vec::new();
+    for message in messages {
+        write_message(&mut input, message).unwrap();
+    }
+    let options = ProjectOptions::default().with_overrides(|options| {
+        options.include_rules = vec!["solana-division-by-zero".to_string()];
+        options.cache_dir = None;
+    });
+    let mut output = Vec::new();
+    Server::new(root, options).unwrap().serve(input.as_slice(), &mut output).unwrap();
+
+    let mut output = output.as_slice();
+    std::iter::from_fn(|| read_message(&mut output).unwrap()).collect()
+}
+
+#[test]
+fn test_diagnostics_on_open_and_save() {
+    let root = write_project("diagnostics");
+    let uri = path_to_uri(&root.join("programs/vault/src/lib.rs"));
+    let replies = session(
+        &root,
+        &[
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+            json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+                "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": SOURCE },
+            } }),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "textDocument/codeAction", "params": {
+                "textDocument": { "uri": uri },
+                "range": { "start": { "line": 1, "character": 4 }, "end": { "line": 1, "character": 4 } },
+                "context": { "diagnostics": [] },
+            } }),
+            // The edited buffer is analyzed when it is saved, before it is written to disk
+            json!({ "jsonrpc": "2.0", "method": "textDocument/didChange", "params": {
+                "textDocument": { "uri": uri, "version": 2 }, "contentChanges": [{ "text": FIXED }],
+            } }),
+            json!({ "jsonrpc": "2.0", "method": "textDocument/didSave", "params": { "textDocument": { "uri": uri } } }),
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "textDocument/hover", "params": {} }),
+            json!({ "jsonrpc": "2.0", "id": 4, "method": "shutdown" }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+        ],
+    );
+
+    assert_eq!(replies.len(), 6);
+    assert_eq!(replies[0]["id"], 1);
+    assert_eq!(replies[0]["result"]["capabilities"]["textDocumentSync"]["change"], 1);
+
+    assert_eq!(replies[1]["method"], "textDocument/publishDiagnostics");
+    assert_eq!(replies[1]["params"]["uri"], uri);
+    let diagnostic = &replies[1]["params"]["diagnostics"][0];
+    assert_eq!(diagnostic["code"], "solana-division-by-zero");
+    assert_eq!(diagnostic["severity"], 2);
+    assert_eq!(diagnostic["source"], "solana-analyzer");
+    assert_eq!(diagnostic["range"], json!({ "start": { "line": 0, "character": 0 }, "end": { "line": 2, "character": 1 } }));
+    // The division replaced by the fix
+    let related = &diagnostic["relatedInformation"][0];
+    assert_eq!(related["location"]["range"]["start"], json!({ "line": 1, "character": 4 }));
+    assert_eq!(related["message"], "Replace the division with checked_div");
+
+    let actions = replies[2]["result"].as_array().unwrap();
+    assert!(!actions.is_empty());
+    assert_eq!(actions[0]["kind"], "quickfix");
+    assert_eq!(actions[0]["diagnostics"][0]["code"], "solana-division-by-zero");
+    let edits = actions[0]["edit"]["changes"][uri.as_str()].as_array().unwrap();
+    assert_eq!(edits[0]["range"]["start"]["line"], 1);
+
+    assert_eq!(replies[3]["params"]["diagnostics"], json!([]));
+    assert_eq!(replies[4]["error"]["code"], -32601);
+    assert_eq!(replies[5], json!({ "jsonrpc": "2.0", "id": 4, "result": null }));
+}
@@ -0,0 +1,188 @@
+//! Native programs (written with `entrypoint!`) run the native rules instead of the Anchor rules,
+//! and the rules of the framework they are written with (Pinocchio, Steel)
+
+use rust_solana_analyzer::analyzer::workspace::Workspace;
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, RuleType};
+use rust_solana_analyzer::ast::parser::SourceFile;
+
+const NATIVE: &str = r#"
+use solana_program::entrypoint;
+
+entrypoint!(process_instruction);
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+    match instruction_data[0] {
+        0 => withdraw(program_id, accounts),
+        _ => deposit(program_id, accounts),
+    }
+}
+
+fn withdraw(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let vault = &accounts[0];
+    let destination = &accounts[1];
+    let state = Vault::try_from_slice(&vault.data.borrow())?;
+    **vault.try_borrow_mut_lamports()? -= state.amount;
+    **destination.try_borrow_mut_lamports()? += state.amount;
+    Ok(())
+}
+
+fn deposit(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let depositor = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    check_vault(program_id, depositor, vault)?;
+    let mut state = Vault::try_from_slice(&vault.data.borrow())?;
+    state.amount += 1;
+    state.serialize(&mut &mut vault.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+fn check_vault(program_id: &Pubkey, depositor: &AccountInfo, vault: &AccountInfo) -> ProgramResult {
+    if !depositor.is_signer || vault.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Legacy<'info> {
+    pub authority: AccountInfo<'info>,
+}
+"#;
+
+const ANCHOR: &str = r#"
+#[program]
+pub mod vault {
+    pub fn withdraw(ctx: Context<Withdraw>, data: &[u8]) -> Result<()> {
+        let vault = &ctx.remaining_accounts[0];
+        Ok(())
+    }
+}
+
+pub fn process(accounts: &[AccountInfo], data: &[u8]) -> Result<()> {
+    let amount = data[0];
+    invoke(&instruction, accounts)?;
+    Ok(())
+}
+"#;
+
+/// Program loading a token account without checking it, and leaving an account unvalidated
+const FRAMEWORK: &str = r#"
+entrypoint!(process_instruction);
+
+fn deposit(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let [depositor, depositor_token, vault] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    depositor.is_signer()?;
+    vault.is_writable()?;
+    let token_account = unsafe { TokenAccount::from_account_info_unchecked(depositor_token)? };
+    Ok(())
+}
+"#;
+
+fn options() -> AnalysisOptions {
+    AnalysisOptions {
+        include_rule_types: vec![
+            RuleType::Solana,
+            RuleType::Anchor,
+            RuleType::Native,
+            RuleType::Pinocchio,
+            RuleType::Steel,
+            RuleType::General,
+        ],
+        ..Default::default()
+    }
+}
+
+fn rules_found(path: &str, source: &str) -> Vec<(String, usize)> {
+    let analyzer = Analyzer::with_options(options());
+    let file = SourceFile::parse(path, source.to_string()).unwrap();
+    let result = analyzer.analyze_files(&[file]).unwrap();
+    let mut rules: Vec<(String, usize)> = result
+        .findings
+        .iter()
+        .flat_map(|finding| finding.rule_ids().into_iter().map(|id| (id.to_string(), finding.location.line)))
+        .collect();
+    rules.sort();
+    rules
+}
+
+#[test]
+fn test_native_rules_run_on_native_programs() {
+    let rules = rules_found("native/src/lib.rs", NATIVE);
+
+    // The dispatcher reads the instruction data without checking its length, `withdraw`
+    // moves lamports out of an account it reads without any signer or owner check and indexes
+    // the accounts; `deposit` checks both in a helper
+    for expected in [
+        ("native-missing-owner-check", 13),
+        ("native-missing-signer-check", 13),
+        ("native-unchecked-account-index", 13),
+        ("native-unchecked-data-length", 6),
+    ] {
+        assert!(
+            rules.contains(&(expected.0.to_string(), expected.1)),
+            "{expected:?} not found in {rules:?}"
+        );
+    }
+    assert!(!rules.iter().any(|(_, line)| *line == 22), "{rules:?}");
+    // Anchor rules do not run on native programs
+    assert!(!rules.iter().any(|(id, _)| id == "missing-signer-check"), "{rules:?}");
+}
+
+#[test]
+fn test_native_rules_do_not_run_on_anchor_programs() {
+    let rules = rules_found("vault/src/lib.rs", ANCHOR);
+
+    assert!(!rules.iter().any(|(id, _)| id.starts_with("native-")), "{rules:?}");
+}
+
+#[test]
+fn test_framework_rules_run_on_their_framework() {
+    // Imports tell the framework of files analyzed alone
+    let pinocchio = rules_found("pinocchio/src/lib.rs", &format!("use pinocchio::entrypoint;\n{FRAMEWORK}"));
+    assert!(pinocchio.iter().any(|(id, _)| id == "pinocchio-unchecked-account-load"), "{pinocchio:?}");
+    assert!(!pinocchio.iter().any(|(id, _)| id == "steel-unvalidated-account"), "{pinocchio:?}");
+
+    let native = rules_found("native/src/lib.rs", FRAMEWORK);
+    assert!(!native.iter().any(|(id, _)| id.starts_with("pinocchio-") || id.starts_with("steel-")), "{native:?}");
+
+    // In a workspace, the dependencies of each crate tell its framework
+    let root = std::env::temp_dir().join(format!("solana-analyzer-frameworks-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"programs/*\"]\n").unwrap();
+    let files: Vec<SourceFile> = ["pinocchio", "steel"]
+        .iter()
+        .map(|framework| {
+            let dir = root.join("programs").join(framework);
+            std::fs::create_dir_all(dir.join("src")).unwrap();
+            let manifest = format!("[package]\nname = \"{framework}-vault\"\n\n[dependencies]\n{framework} = \"*\"\n");
+            std::fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+            let path = dir.join("src/lib.rs");
+            std::fs::write(&path, FRAMEWORK).unwrap();
+            SourceFile::parse(path, FRAMEWORK.to_string()).unwrap()
+        })
+        .collect();
+    let analyzer = Analyzer::with_options(AnalysisOptions {
+        workspace: Workspace::load(&root).unwrap(),
+        ..options()
+    });
+    let result = analyzer.analyze_files(&files).unwrap();
+    let crates_found = |rule_id: &str| -> Vec<String> {
+        result
+            .findings
+            .iter()
+            .filter(|finding| finding.rule_ids().contains(&rule_id))
+            .map(|finding| finding.location.file.clone())
+            .collect()
+    };
+    let unchecked_loads = crates_found("pinocchio-unchecked-account-load");
+    assert_eq!(unchecked_loads.len(), 1, "{unchecked_loads:?}");
+    assert!(unchecked_loads[0].contains("pinocchio"));
+    let unvalidated = crates_found("steel-unvalidated-account");
+    assert_eq!(unvalidated.len(), 1, "{unvalidated:?}");
+    assert!(unvalidated[0].contains("steel"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
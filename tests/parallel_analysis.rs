@@ -0,0 +1,54 @@
+//! Files analyzed in parallel give the same results as a sequential analysis
+
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, RuleType};
+use rust_solana_analyzer::ast::parser::SourceFile;
+
+/// Helper function to write a project of `count` files, each with a division and an unsafe block
+fn write_project(count: usize) -> Vec<SourceFile> {
+    let dir = std::env::temp_dir().join(format!("solana-analyzer-parallel-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    (0..count)
+        .map(|index| {
+            let source = format!(
+                "pub fn share_{index}(amount: u64, shares: u64) -> u64 {{\n    amount / shares\n}}\n\n\
+                 pub fn raw_{index}(value: *const u64) -> u64 {{\n    unsafe {{ *value }}\n}}\n"
+            );
+            let path = dir.join(format!("file_{index:02}.rs"));
+            std::fs::write(&path, &source).unwrap();
+            SourceFile::parse(path, source).unwrap()
+        })
+        .collect()
+}
+
+fn analyzer(jobs: usize) -> Analyzer {
+    Analyzer::with_options(AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        jobs,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_parallel_analysis_is_deterministic() {
+    let files = write_project(12);
+
+    let sequential = analyzer(1).analyze_files(&files).unwrap();
+    let parallel = analyzer(4).analyze_files(&files).unwrap();
+
+    let summary = |findings: &[rust_solana_analyzer::analyzer::Finding]| -> Vec<(String, usize, String)> {
+        findings
+            .iter()
+            .map(|finding| (finding.location.file.clone(), finding.location.line, finding.description.clone()))
+            .collect()
+    };
+    assert!(sequential.findings.len() >= files.len() * 2, "{:#?}", sequential.findings);
+    assert_eq!(summary(&parallel.findings), summary(&sequential.findings));
+    assert_eq!(parallel.stats.findings_by_severity, sequential.stats.findings_by_severity);
+
+    // Findings follow the order of the files
+    let first_file = &parallel.findings[0].location.file;
+    assert!(first_file.ends_with("file_00.rs"), "{first_file}");
+
+    std::fs::remove_dir_all(files[0].path.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,81 @@
+//! `--exclude` globs and the `.gitignore`/`.ignore` files leave paths out of the analysis, and the stats list them
+
+use rust_solana_analyzer::analyzer::stats::StatsReport;
+use rust_solana_analyzer::{ProjectOptions, analyze_project};
+use std::path::{Path, PathBuf};
+
+const SOURCE: &str = r#"
+pub fn share(amount: u64, shares: u64) -> u64 {
+    amount / shares
+}
+"#;
+
+/// Helper function to write a workspace of one program, with build output and vendored code
+fn write_project(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-exclusions-{name}-{}", std::process::id()));
+    let dir = root.join("programs/vault");
+    for path in ["src", "target/debug/build", "vendor/spl/src"] {
+        std::fs::create_dir_all(dir.join(path)).unwrap();
+    }
+    std::fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"programs/*\"]\n\n[profile.release]\noverflow-checks = true\n").unwrap();
+    std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"vault\"\n\n[dependencies]\nsolana-program = \"1.18\"\n")
+        .unwrap();
+    std::fs::write(dir.join("src/lib.rs"), SOURCE).unwrap();
+    std::fs::write(dir.join("target/debug/build/out.rs"), SOURCE).unwrap();
+    std::fs::write(dir.join("vendor/spl/src/lib.rs"), SOURCE).unwrap();
+    root
+}
+
+fn options(exclude: &[&str], no_ignore: bool) -> ProjectOptions<'static> {
+    ProjectOptions {
+        exclude: exclude.iter().map(|glob| glob.to_string()).collect(),
+        no_ignore,
+        ..ProjectOptions::default().with_overrides(|options| {
+            options.include_rules = vec!["solana-division-by-zero".to_string()];
+            options.cache_dir = None;
+        })
+    }
+}
+
+fn analyzed_files(root: &Path, options: ProjectOptions) -> Vec<String> {
+    let project = analyze_project(root, options).unwrap();
+    let mut files: Vec<_> =
+        project.files.iter().map(|file| file.path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/")).collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn test_ignore_files_and_excludes() {
+    let root = write_project("analysis");
+
+    let project = analyze_project(&root, options(&["**/vendor"], false)).unwrap();
+    assert_eq!(project.files.len(), 1);
+    assert_eq!(project.result.by_rule()["solana-division-by-zero"].len(), 1);
+
+    let exclusions: Vec<_> = project.result.stats.exclusions.iter().map(|exclusion| (exclusion.path.as_str(), exclusion.rule.as_str())).collect();
+    assert_eq!(
+        exclusions,
+        [("programs/vault/target", ".gitignore: target/"), ("programs/vault/vendor", "--exclude **/vendor")]
+    );
+    let text = StatsReport::new(&project.result, "vault").to_text();
+    assert!(text.contains("1 analyzed, 0 unparsed, 0 from the cache, 0 test files skipped, 2 paths excluded"), "{text}");
+    assert!(text.contains("programs/vault/target (.gitignore: target/)"), "{text}");
+
+    // The ignore files are skipped on demand
+    assert_eq!(
+        analyzed_files(&root, options(&[], true)),
+        ["programs/vault/src/lib.rs", "programs/vault/target/debug/build/out.rs", "programs/vault/vendor/spl/src/lib.rs"]
+    );
+
+    // A `.ignore` file includes again what the `.gitignore` file excluded
+    std::fs::write(root.join("programs/vault/.ignore"), "!target/\ntarget/debug/\n").unwrap();
+    std::fs::write(root.join("programs/vault/target/kept.rs"), SOURCE).unwrap();
+    assert_eq!(
+        analyzed_files(&root, options(&[], false)),
+        ["programs/vault/src/lib.rs", "programs/vault/target/kept.rs", "programs/vault/vendor/spl/src/lib.rs"]
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,67 @@
+//! The PDA derivations of the programs, without the test code
+
+use rust_solana_analyzer::analyzer::pdas::PdaSource;
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, RuleType};
+use rust_solana_analyzer::ast::parser::SourceFile;
+
+const SOURCE: &str = r#"
+use anchor_lang::prelude::*;
+
+#[program]
+pub mod counter {
+    use super::*;
+
+    pub fn increment(ctx: Context<Increment>) -> Result<()> {
+        ctx.accounts.counter.count += 1;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Increment<'info> {
+    #[account(mut, seeds = [b"counter", authority.key().as_ref()], bump = counter.bump)]
+    pub counter: Account<'info, Counter>,
+    pub authority: Signer<'info>,
+}
+
+#[account]
+pub struct Counter {
+    pub count: u64,
+    pub bump: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address() {
+        let (address, _) = Pubkey::find_program_address(&[b"counter"], &crate::ID);
+    }
+}
+"#;
+
+fn analyzer(pdas: bool) -> Analyzer {
+    Analyzer::with_options(AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        pdas,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_pda_inventory() {
+    let file = SourceFile::parse("programs/counter/src/lib.rs", SOURCE.to_string()).unwrap();
+
+    let result = analyzer(true).analyze_files(std::slice::from_ref(&file)).unwrap();
+    assert_eq!(result.stats.pdas.len(), 1);
+    // The derivation of the test module is left out
+    let derivations = &result.stats.pdas[0].derivations;
+    assert_eq!(derivations.len(), 1);
+    assert_eq!(derivations[0].source, PdaSource::Constraint);
+    assert_eq!(derivations[0].seeds, vec!["b\"counter\"", "authority.key()"]);
+    assert_eq!(derivations[0].instructions, vec!["increment"]);
+
+    let result = analyzer(false).analyze_files(&[file]).unwrap();
+    assert!(result.stats.pdas.is_empty());
+}
@@ -0,0 +1,68 @@
+//! The inventory of the instructions, accounts and state of the programs, without the test code
+
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, RuleType};
+use rust_solana_analyzer::ast::parser::SourceFile;
+
+const SOURCE: &str = r#"
+use anchor_lang::prelude::*;
+
+#[program]
+pub mod counter {
+    use super::*;
+
+    pub fn increment(ctx: Context<Increment>) -> Result<()> {
+        ctx.accounts.counter.count += 1;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Increment<'info> {
+    #[account(mut, has_one = authority)]
+    pub counter: Account<'info, Counter>,
+    pub authority: Signer<'info>,
+}
+
+#[account]
+pub struct Counter {
+    pub authority: Pubkey,
+    pub count: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[account]
+    pub struct Fixture {
+        pub value: u8,
+    }
+}
+"#;
+
+fn analyzer(inventory: bool) -> Analyzer {
+    Analyzer::with_options(AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        inventory,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_program_inventory() {
+    let file = SourceFile::parse("programs/counter/src/lib.rs", SOURCE.to_string()).unwrap();
+
+    let result = analyzer(true).analyze_files(std::slice::from_ref(&file)).unwrap();
+    assert_eq!(result.stats.inventory.len(), 1);
+    let inventory = &result.stats.inventory[0];
+    assert_eq!(inventory.instructions[0].name, "increment");
+    assert_eq!(inventory.accounts[0].instructions, vec!["increment"]);
+    assert_eq!(inventory.accounts[0].fields[0].constraints, vec!["mut", "has_one=authority"]);
+    // The account of the test module is left out
+    assert_eq!(inventory.state.len(), 1);
+    assert_eq!(inventory.state[0].name, "Counter");
+    assert_eq!(inventory.state[0].space, Some(48));
+
+    let result = analyzer(false).analyze_files(&[file]).unwrap();
+    assert!(result.stats.inventory.is_empty());
+}
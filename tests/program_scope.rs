@@ -0,0 +1,102 @@
+//! Test code is skipped by default and Anchor rules only run on Anchor crates
+
+use rust_solana_analyzer::analyzer::{AnalysisOptions, AnalysisResult, Analyzer, RuleType};
+use rust_solana_analyzer::ast::parser::SourceFile;
+use std::path::{Path, PathBuf};
+
+const PROGRAM: &str = r#"
+use anchor_lang::prelude::*;
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub fn share(amount: u64, shares: u64) -> u64 {
+    amount / shares
+}
+
+#[cfg(test)]
+mod tests {
+    pub fn ratio(a: u64, b: u64) -> u64 {
+        a / b
+    }
+}
+"#;
+
+const INTEGRATION_TEST: &str = r#"
+pub fn average(total: u64, count: u64) -> u64 {
+    total / count
+}
+"#;
+
+/// Helper function to write a crate depending on `dependency`, with an integration test
+fn write_project(name: &str, dependency: &str) -> (PathBuf, Vec<SourceFile>) {
+    let dir = std::env::temp_dir().join(format!("solana-analyzer-scope-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::create_dir_all(dir.join("tests")).unwrap();
+    let manifest = format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\n\n[dependencies]\n{dependency} = \"*\"\n");
+    std::fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+
+    let files = [("src/lib.rs", PROGRAM), ("tests/withdraw.rs", INTEGRATION_TEST)]
+        .into_iter()
+        .map(|(file, source)| {
+            let path = dir.join(file);
+            std::fs::write(&path, source).unwrap();
+            SourceFile::parse(path, source.to_string()).unwrap()
+        })
+        .collect();
+    (dir, files)
+}
+
+fn analyze(root: &Path, files: &[SourceFile], include_tests: bool) -> AnalysisResult {
+    let analyzer = Analyzer::with_options(AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        project_root: Some(root.to_path_buf()),
+        include_tests,
+        ..Default::default()
+    });
+    analyzer.analyze_files(files).unwrap()
+}
+
+/// Helper function to list the (file name, line, rule) of the findings
+fn summary(result: &AnalysisResult) -> Vec<(String, usize, String)> {
+    result
+        .findings
+        .iter()
+        .flat_map(|finding| {
+            let file = Path::new(&finding.location.file).file_name().unwrap().to_string_lossy().to_string();
+            finding.rules.iter().map(move |rule| (file.clone(), finding.location.line, rule.id.clone()))
+        })
+        .collect()
+}
+
+#[test]
+fn test_analysis_is_scoped_to_program_code() {
+    let (root, files) = write_project("anchor", "anchor-lang");
+
+    let result = analyze(&root, &files, false);
+    let findings = summary(&result);
+    assert!(findings.contains(&("lib.rs".to_string(), 13, "solana-division-by-zero".to_string())), "{findings:#?}");
+    assert!(findings.iter().any(|(_, _, rule)| rule == "anchor-instructions"), "{findings:#?}");
+    // Neither the test module nor the integration test are reported
+    assert!(findings.iter().all(|(file, line, _)| file == "lib.rs" && *line < 17), "{findings:#?}");
+    assert_eq!(result.stats.test_files_skipped, 1);
+    assert_eq!(result.stats.files_analyzed, 1);
+
+    let result = analyze(&root, &files, true);
+    let findings = summary(&result);
+    assert!(findings.contains(&("lib.rs".to_string(), 19, "solana-division-by-zero".to_string())), "{findings:#?}");
+    assert!(findings.contains(&("withdraw.rs".to_string(), 2, "solana-division-by-zero".to_string())), "{findings:#?}");
+    assert_eq!(result.stats.test_files_skipped, 0);
+
+    // The same program without anchor-lang is only checked by the generic rules
+    let (root, files) = write_project("native", "solana-program");
+    let findings = summary(&analyze(&root, &files, false));
+    assert!(findings.contains(&("lib.rs".to_string(), 13, "solana-division-by-zero".to_string())), "{findings:#?}");
+    assert!(findings.iter().all(|(_, _, rule)| rule != "anchor-instructions"), "{findings:#?}");
+}
@@ -0,0 +1,162 @@
+//! `analyze_project` analyzes a project directory the way the binary does
+
+use rust_solana_analyzer::analyzer::Severity;
+use rust_solana_analyzer::project::{ProjectError, Selection, parse_project};
+use rust_solana_analyzer::{ProjectOptions, analyze_project};
+use std::path::PathBuf;
+
+const SOURCE: &str = r#"
+pub fn share(amount: u64, shares: u64) -> u64 {
+    amount / shares
+}
+"#;
+
+/// Helper function to write a workspace of one program, a generated file and a file that does not parse
+fn write_project(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-project-{name}-{}", std::process::id()));
+    let dir = root.join("programs/vault");
+    std::fs::create_dir_all(dir.join("src/generated")).unwrap();
+    std::fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"programs/*\"]\n\n[profile.release]\noverflow-checks = true\n").unwrap();
+    std::fs::write(root.join("solana-analyzer.toml"), "[paths]\nexclude = [\"**/generated/*.rs\"]\n").unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"vault\"\n\n[dependencies]\nsolana-program = \"1.18\"\n")
+        .unwrap();
+    std::fs::write(dir.join("src/lib.rs"), SOURCE).unwrap();
+    std::fs::write(dir.join("src/broken.rs"), "pub fn broken( {").unwrap();
+    std::fs::write(dir.join("src/generated/bindings.rs"), SOURCE).unwrap();
+    root
+}
+
+#[test]
+fn test_analyze_project_returns_programs_files_and_findings() {
+    let root = write_project("analysis");
+    let options = ProjectOptions::default().with_overrides(|options| {
+        options.include_rules = vec!["solana-division-by-zero".to_string()];
+        options.cache_dir = None;
+    });
+
+    let project = analyze_project(&root, options).unwrap();
+
+    assert_eq!(project.root, root);
+    assert_eq!(project.programs.iter().map(|program| program.name.as_str()).collect::<Vec<_>>(), ["vault"]);
+
+    let mut files: Vec<_> = project
+        .files
+        .iter()
+        .map(|file| (file.path.strip_prefix(&root).unwrap().to_path_buf(), file.parse_error.is_some(), file.program.clone()))
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        [
+            (PathBuf::from("programs/vault/src/broken.rs"), true, Some("vault".to_string())),
+            (PathBuf::from("programs/vault/src/lib.rs"), false, Some("vault".to_string())),
+        ]
+    );
+
+    let findings = project.result.filter(&Default::default());
+    let division = findings.iter().find(|finding| finding.rule_ids() == ["solana-division-by-zero"]).unwrap();
+    assert_eq!(division.severity, Severity::Medium);
+    assert!(division.location.file.ends_with("lib.rs"));
+    assert!(findings.iter().any(|finding| finding.rule_ids() == ["parse-error"]));
+    assert_eq!(project.result.stats.files_unparsed, 1);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_overrides_win_over_the_configuration() {
+    let root = write_project("overrides");
+    std::fs::write(root.join("solana-analyzer.toml"), "[rules]\nexclude = [\"solana-division-by-zero\"]\n").unwrap();
+
+    let project = analyze_project(&root, ProjectOptions::default()).unwrap();
+    assert!(!project.result.findings.iter().any(|finding| finding.rule_ids().contains(&"solana-division-by-zero")));
+
+    let options = ProjectOptions::default().with_overrides(|options| {
+        options.ignore_rules.clear();
+        options.include_rules = vec!["solana-division-by-zero".to_string()];
+    });
+    let project = analyze_project(&root, options).unwrap();
+    // The generated file is not excluded by this configuration
+    let by_rule = project.result.by_rule();
+    assert_eq!(by_rule.get("solana-division-by-zero").map(Vec::len), Some(2));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_parse_project_writes_the_asts() {
+    let root = write_project("parse");
+    let ast_dir = root.join("ast");
+    let options = ProjectOptions { ast_dir: Some(ast_dir.clone()), ..Default::default() };
+
+    let files = parse_project(&root, &options).unwrap();
+
+    assert_eq!(files.len(), 2);
+    assert!(ast_dir.join("programs/vault/src/lib.json").exists());
+    assert!(!ast_dir.join("programs/vault/src/broken.json").exists());
+    assert!(!root.join("programs/vault/src/lib.json").exists());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_invalid_projects_are_errors() {
+    let missing = std::env::temp_dir().join("solana-analyzer-project-missing");
+    assert!(matches!(analyze_project(&missing, ProjectOptions::default()), Err(ProjectError::NotFound(_))));
+
+    let root = write_project("invalid");
+    std::fs::write(root.join("solana-analyzer.toml"), "[thresholds]\nfail_on = \"critical\"\n").unwrap();
+    let error = analyze_project(&root, ProjectOptions::default()).unwrap_err();
+    assert!(matches!(error, ProjectError::Config(_)), "{error}");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_files_and_globs_are_analyzed_in_their_project() {
+    let root = write_project("selection");
+    let dir = root.join("programs/vault/src");
+    std::fs::write(dir.join("vault.rs"), SOURCE).unwrap();
+    let options = || {
+        ProjectOptions::default().with_overrides(|options| {
+            options.include_rules = vec!["solana-division-by-zero".to_string()];
+            options.cache_dir = None;
+        })
+    };
+
+    // A single file is analyzed in its workspace, with its configuration
+    let project = analyze_project(&dir.join("vault.rs"), options()).unwrap();
+    assert_eq!(project.root, root);
+    assert_eq!(project.files.len(), 1);
+    assert_eq!(project.files[0].program.as_deref(), Some("vault"));
+    let by_rule = project.result.by_rule();
+    let divisions = &by_rule["solana-division-by-zero"];
+    assert_eq!(divisions.len(), 1);
+    assert!(divisions[0].location.file.ends_with("vault.rs"));
+
+    // A glob leaves out the files that are not Rust files, and the analysis the excluded paths
+    std::fs::write(dir.join("generated/notes.txt"), "").unwrap();
+    let selection = Selection::of(&root.join("programs/*/src/**/*.rs")).unwrap();
+    assert_eq!(selection.root, root);
+    let files: Vec<_> = selection.files.unwrap().iter().map(|file| file.strip_prefix(&root).unwrap().to_path_buf()).collect();
+    assert_eq!(
+        files,
+        [
+            PathBuf::from("programs/vault/src/broken.rs"),
+            PathBuf::from("programs/vault/src/generated/bindings.rs"),
+            PathBuf::from("programs/vault/src/lib.rs"),
+            PathBuf::from("programs/vault/src/vault.rs"),
+        ]
+    );
+    let project = analyze_project(&root.join("programs/*/src/**/*.rs"), options()).unwrap();
+    assert_eq!(project.files.len(), 3);
+    assert_eq!(project.result.stats.exclusions.len(), 1);
+    let project = analyze_project(&root.join("programs/vault/src/?ault.rs"), options()).unwrap();
+    assert_eq!(project.files.len(), 1);
+    assert_eq!(project.result.by_rule()["solana-division-by-zero"].len(), 1);
+
+    assert!(matches!(Selection::of(&root.join("programs/*/src/*.sol")), Err(ProjectError::NotFound(_))));
+    assert!(matches!(Selection::of(&root.join("Cargo.toml")), Err(ProjectError::NotADirectory(_))));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,41 @@
+//! `init` writes a configuration matching the project, which the analysis of the project then uses
+
+use rust_solana_analyzer::analyzer::init::{CiProvider, InitError, init};
+use rust_solana_analyzer::{ProjectOptions, analyze_project};
+
+const SOURCE: &str = r#"
+pub fn share(amount: u64, shares: u64) -> u64 {
+    amount / shares
+}
+"#;
+
+#[test]
+fn test_init_anchor_workspace() {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-init-{}", std::process::id()));
+    let dir = root.join("programs/vault");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::create_dir_all(root.join("target/deploy")).unwrap();
+    std::fs::write(root.join("Anchor.toml"), "[provider]\ncluster = \"localnet\"\n").unwrap();
+    std::fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"programs/*\"]\n").unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"vault\"\n\n[dependencies]\nanchor-lang = \"0.30.1\"\n").unwrap();
+    std::fs::write(dir.join("src/lib.rs"), SOURCE).unwrap();
+    std::fs::write(root.join("target/deploy/generated.rs"), SOURCE).unwrap();
+
+    let (setup, files) = init(&root, Some(CiProvider::Github), false).unwrap();
+    assert_eq!(setup.describe(), "Anchor workspace with the program vault (anchor)");
+    assert_eq!(files, [root.join("solana-analyzer.toml"), root.join(".github/workflows/solana-analyzer.yml")]);
+
+    // Existing files are kept unless forced
+    assert!(matches!(init(&root, None, false), Err(InitError::Exists(path)) if path == root.join("solana-analyzer.toml")));
+    assert!(init(&root, None, true).is_ok());
+
+    // The analysis runs the Anchor profile and skips the build output
+    let options = ProjectOptions::default().with_overrides(|options| options.cache_dir = None);
+    let project = analyze_project(&root, options).unwrap();
+    assert_eq!(project.files.len(), 1);
+    assert_eq!(project.result.by_rule()["solana-division-by-zero"].len(), 1);
+    assert!(!project.result.by_rule().contains_key("native-missing-signer-check"));
+    assert_eq!(project.result.stats.exclusions[0].path, "target");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,37 @@
+//! The curated public API re-exported at the root of the crate
+
+use rust_solana_analyzer::ast::parser::SourceFile;
+use rust_solana_analyzer::dsl::ProgramContext;
+use rust_solana_analyzer::{AnalysisOptions, Analyzer, Finding, Format, Renderer, RuleType, Severity};
+
+const SOURCE: &str = r#"
+pub fn share(amount: u64, shares: u64) -> u64 {
+    amount / shares
+}
+"#;
+
+#[test]
+fn test_root_reexports_analyze_and_serialize() {
+    let file = SourceFile::parse("src/lib.rs", SOURCE.to_string()).unwrap();
+    let analyzer = Analyzer::with_options(AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::General],
+        include_rules: vec!["solana-division-by-zero".to_string()],
+        ..Default::default()
+    });
+
+    let result = analyzer.analyze_files(std::slice::from_ref(&file)).unwrap();
+    assert_eq!(result.findings.len(), 1);
+    assert_eq!(result.count_at_least(Some(&Severity::Medium)), 1);
+
+    // The whole result serializes, and findings read back as they were written
+    let json: serde_json::Value = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["stats"]["files_analyzed"], 1);
+    let finding: Finding = serde_json::from_value(json["findings"][0].clone()).unwrap();
+    assert_eq!(finding.location, result.findings[0].location);
+    assert_eq!(finding.severity, result.findings[0].severity);
+
+    let markdown = Renderer::new(&result, ".", ".".as_ref()).render(Format::Markdown);
+    assert!(markdown.contains("src/lib.rs"));
+
+    assert!(!ProgramContext::build([("src/lib.rs", &file.ast)]).is_empty());
+}
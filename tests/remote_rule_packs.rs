@@ -0,0 +1,84 @@
+//! `--templates <URL>` fetches a rule pack from a git repository or an archive, verifies it,
+//! caches it and loads its rules
+
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::process::Command;
+
+use rust_solana_analyzer::analyzer::remote::PackError;
+use rust_solana_analyzer::project::{ProjectError, analysis_options};
+use rust_solana_analyzer::{Analyzer, ProjectOptions};
+
+const TEMPLATE: &str = r#"
+id: unchecked-withdraw
+title: Unchecked Withdraw
+description: Withdraw handlers without an access control attribute
+severity: high
+match:
+  kind: function
+  name: "^withdraw"
+  attributes:
+    absent: [access_control]
+"#;
+
+fn run(dir: &Path, program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).current_dir(dir).args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Load the analysis options of the project with the rule pack as templates
+fn load(root: &Path, spec: &str) -> Result<Analyzer, ProjectError> {
+    let spec = spec.to_string();
+    let options = ProjectOptions { packs_cache_dir: Some(root.join("cache")), ..Default::default() }
+        .with_overrides(move |options| {
+            options.cache_dir = None;
+            options.custom_templates_path = Some(spec);
+        });
+    let (_, options) = analysis_options(&root.join("project"), options)?;
+    Ok(Analyzer::with_options(options))
+}
+
+#[test]
+fn test_remote_rule_packs() {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-remote-packs-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let pack = root.join("pack");
+    std::fs::create_dir_all(pack.join("rules")).unwrap();
+    std::fs::create_dir_all(root.join("project")).unwrap();
+    std::fs::write(pack.join("rules/unchecked-withdraw.yaml"), TEMPLATE).unwrap();
+
+    let git = |args: &[&str]| run(&pack, "git", &[&["-c", "user.name=Pack", "-c", "user.email=pack@example.com"], args].concat());
+    if git(&["init", "-q"]).is_none() || run(&root, "curl", &["--version"]).is_none() {
+        eprintln!("git or curl is not available, skipping");
+        return;
+    }
+    git(&["add", "."]).unwrap();
+    git(&["commit", "-q", "-m", "Rules"]).unwrap();
+    git(&["tag", "v1"]).unwrap();
+    let commit = git(&["rev-parse", "HEAD"]).unwrap();
+
+    // A git repository at a tag, pinned to its commit
+    let url = format!("git+file://{}", pack.display());
+    let analyzer = load(&root, &format!("{url}#v1&commit={}&path=rules", &commit[..12])).unwrap();
+    assert!(analyzer.rules().metadata_of("unchecked-withdraw").is_some());
+    let wrong_commit = load(&root, &format!("{url}#v1&commit=0000000")).err();
+    assert!(matches!(wrong_commit, Some(ProjectError::RulePack(PackError::Commit { .. }))), "{wrong_commit:?}");
+    // A tag without a signature is rejected
+    let unsigned = load(&root, &format!("{url}#v1&signed")).err();
+    assert!(matches!(unsigned, Some(ProjectError::RulePack(PackError::Signature { .. }))), "{unsigned:?}");
+
+    // An archive, checked against its checksum
+    run(&root, "tar", &["-czf", "pack.tar.gz", "-C", "pack", "rules"]).unwrap();
+    let archive = root.join("pack.tar.gz");
+    let sha256: String = Sha256::digest(std::fs::read(&archive).unwrap()).iter().map(|byte| format!("{byte:02x}")).collect();
+    let spec = format!("file://{}#sha256={sha256}&path=rules", archive.display());
+    assert!(load(&root, &spec).unwrap().rules().metadata_of("unchecked-withdraw").is_some());
+    let tampered = load(&root, &format!("file://{}#sha256={}", archive.display(), "0".repeat(64))).err();
+    assert!(matches!(tampered, Some(ProjectError::RulePack(PackError::Checksum { .. }))), "{tampered:?}");
+
+    // Fetched once, then loaded from the cache
+    std::fs::remove_file(&archive).unwrap();
+    assert!(load(&root, &spec).unwrap().rules().metadata_of("unchecked-withdraw").is_some());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,60 @@
+//! `explain <rule-id>` documents the built-in rules and the custom rules of the project
+
+use rust_solana_analyzer::analyzer::explain::RuleDoc;
+use rust_solana_analyzer::project::analysis_options;
+use rust_solana_analyzer::{Analyzer, ProjectOptions};
+
+const TEMPLATE: &str = r#"
+id: unchecked-withdraw
+title: Unchecked Withdraw
+description: Withdraw handlers without an access control attribute
+severity: high
+rationale: Anyone can call the handler and drain the vault
+examples:
+  vulnerable:
+    - |
+      pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+          transfer(ctx)
+      }
+  safe:
+    - |
+      #[access_control(is_admin(&ctx))]
+      pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+          transfer(ctx)
+      }
+match:
+  kind: function
+  name: "^withdraw"
+  attributes:
+    absent: [access_control]
+"#;
+
+#[test]
+fn test_custom_rules_are_documented() {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-rule-docs-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("rules")).unwrap();
+    std::fs::write(root.join("solana-analyzer.toml"), "[paths]\ntemplates = \"rules\"\n\n[rules]\nexclude = [\"unchecked-withdraw\"]\n")
+        .unwrap();
+    std::fs::write(root.join("rules/unchecked-withdraw.yaml"), TEMPLATE).unwrap();
+
+    let options = ProjectOptions::default().with_overrides(|options| options.cache_dir = None);
+    let (_, options) = analysis_options(&root, options).unwrap();
+    let analyzer = Analyzer::with_options(options);
+
+    // Rules filtered out by the configuration are documented too
+    let rule = analyzer.rules().metadata_of("unchecked-withdraw").unwrap();
+    let markdown = RuleDoc::new(&rule).to_markdown();
+    assert!(markdown.starts_with("# Unchecked Withdraw\n"), "{markdown}");
+    assert!(markdown.contains("| Enabled | no (filtered out by the configuration) |"), "{markdown}");
+    assert!(markdown.contains("## Rationale\n\nAnyone can call the handler and drain the vault\n"), "{markdown}");
+    assert!(
+        markdown.contains("Safe:\n\n```rust\n#[access_control(is_admin(&ctx))]\npub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {\n"),
+        "{markdown}"
+    );
+
+    // Along with the built-in rules
+    let rule = analyzer.rules().metadata_of("native-missing-owner-check").unwrap();
+    assert!(RuleDoc::new(&rule).to_text().contains("\nVulnerable:\n    fn read_balance("));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,69 @@
+//! Rules that panic or exceed their time budget do not stop the analysis
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_solana_analyzer::analyzer::dsl::{AstQuery, RuleBuilder};
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, Result, Rule, RuleEngine, RulePlugin, RuleType};
+use rust_solana_analyzer::ast::parser::SourceFile;
+
+const SOURCE: &str = r#"
+pub fn raw(value: *const u64) -> u64 {
+    unsafe { *value }
+}
+"#;
+
+fn create_rule(id: &str, check: fn()) -> Arc<dyn Rule> {
+    RuleBuilder::new()
+        .id(id)
+        .title("Faulty Rule")
+        .description("Rule failing on every file")
+        .rule_type(RuleType::Solana)
+        .dsl_query(move |ast, _file_path, _span_extractor| {
+            check();
+            AstQuery::new(ast).functions()
+        })
+        .build()
+        .unwrap()
+}
+
+struct FaultyRules;
+
+impl RulePlugin for FaultyRules {
+    fn name(&self) -> &str {
+        "faulty"
+    }
+
+    fn register_rules(&self, engine: &mut RuleEngine) -> Result<()> {
+        engine.add_rule(create_rule("panicking-rule", || panic!("index out of bounds")))?;
+        engine.add_rule(create_rule("slow-rule", || std::thread::sleep(Duration::from_millis(300))))?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_faulty_rules_degrade_to_warnings() {
+    let dir = std::env::temp_dir().join(format!("solana-analyzer-isolation-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("lib.rs");
+    std::fs::write(&path, SOURCE).unwrap();
+    let files = vec![SourceFile::parse(path.clone(), SOURCE.to_string()).unwrap()];
+
+    let options = AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        include_rules: vec!["panicking-rule".to_string(), "slow-rule".to_string(), "solana-unsafe-code".to_string()],
+        rule_timeout: Some(Duration::from_millis(100)),
+        ..Default::default()
+    };
+    let result = Analyzer::with_rule_plugins(options, &[&FaultyRules]).analyze_files(&files).unwrap();
+
+    // The other rules still report their findings
+    let rules: Vec<&str> = result.findings.iter().flat_map(|finding| finding.rule_ids()).collect();
+    assert_eq!(rules, ["solana-unsafe-code"], "{:#?}", result.findings);
+
+    let timed_out: Vec<&String> = result.stats.timed_out_rules.keys().collect();
+    assert_eq!(timed_out, ["slow-rule"]);
+    assert!(result.stats.timed_out_rules["slow-rule"].contains(&path.to_string_lossy().to_string()));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,89 @@
+//! Rule packs and profiles selecting the rules to run
+
+use rust_solana_analyzer::analyzer::dsl::{AstQuery, RuleBuildError, RuleBuilder};
+use rust_solana_analyzer::analyzer::packs::{RULE_PACKS, resolve_profile};
+use rust_solana_analyzer::analyzer::{RuleEngine, RuleEngineConfig};
+
+/// Helper function to load the built-in rules with the given profile
+fn engine_with_profile(profile: &str) -> RuleEngine {
+    let config = RuleEngineConfig {
+        packs: resolve_profile(profile).unwrap(),
+        ..Default::default()
+    };
+    let mut engine = RuleEngine::new(config);
+    engine.load_builtin_rules().unwrap();
+    engine
+}
+
+#[test]
+fn test_resolve_profile() {
+    assert_eq!(resolve_profile("all").unwrap(), None);
+    assert_eq!(resolve_profile("anchor,all").unwrap(), None);
+
+    let packs = resolve_profile("native, style").unwrap().unwrap();
+    let packs: Vec<_> = packs.iter().map(String::as_str).collect();
+    assert_eq!(packs, ["defi", "native", "spl-token", "style"]);
+
+    // Custom packs are resolved by name
+    assert!(resolve_profile("team-rules").unwrap().unwrap().contains("team-rules"));
+
+    assert!(resolve_profile("Anchor").is_err());
+    assert!(resolve_profile(" , ").is_err());
+}
+
+#[test]
+fn test_profile_selects_builtin_rules() {
+    let all = engine_with_profile("all");
+
+    let anchor = engine_with_profile("anchor");
+    assert!(anchor.has_rule("missing-signer-check"));
+    assert!(anchor.has_rule("solana-division-by-zero"));
+    assert!(!anchor.has_rule("solana-missing-error-handling"));
+
+    let native = engine_with_profile("native");
+    assert!(native.has_rule("solana-unsafe-code"));
+    assert!(!native.has_rule("owner-check"));
+    assert!(!native.has_rule("pinocchio-unchecked-account-load"));
+
+    let pinocchio = engine_with_profile("pinocchio");
+    assert!(pinocchio.has_rule("native-missing-signer-check"));
+    assert!(pinocchio.has_rule("pinocchio-unchecked-account-load"));
+    assert!(!pinocchio.has_rule("steel-unvalidated-account"));
+
+    // Every built-in rule belongs to a built-in pack
+    let packs = RULE_PACKS.iter().map(|pack| pack.name).collect::<Vec<_>>().join(",");
+    assert_eq!(engine_with_profile(&packs).rule_count(), all.rule_count());
+    assert!(anchor.rule_count() < all.rule_count());
+}
+
+#[test]
+fn test_rules_without_packs_only_run_with_every_rule() {
+    let build = || {
+        RuleBuilder::new()
+            .id("unpacked-rule")
+            .title("Unpacked Rule")
+            .description("Rule without a pack")
+            .dsl_query(|ast, _file_path, _span_extractor| AstQuery::new(ast).functions())
+            .build()
+            .unwrap()
+    };
+
+    let mut engine = RuleEngine::new(RuleEngineConfig::default());
+    engine.add_rule(build()).unwrap();
+    assert!(engine.has_rule("unpacked-rule"));
+
+    let mut engine = engine_with_profile("anchor");
+    engine.add_rule(build()).unwrap();
+    assert!(!engine.has_rule("unpacked-rule"));
+
+    let error = RuleBuilder::new()
+        .id("badly-packed-rule")
+        .title("Badly Packed Rule")
+        .description("Rule with an invalid pack name")
+        .pack("Anchor Core")
+        .dsl_query(|ast, _file_path, _span_extractor| AstQuery::new(ast).functions())
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(error, RuleBuildError::InvalidPack { .. }), "{error}");
+}
@@ -0,0 +1,95 @@
+//! Rules compiled in another crate and registered through `RulePlugin`
+
+use std::sync::Arc;
+
+use rust_solana_analyzer::analyzer::dsl::{AstQuery, RuleBuildError, RuleBuilder};
+use rust_solana_analyzer::analyzer::{
+    AnalysisOptions, Analyzer, Result, Rule, RuleEngine, RulePlugin, RuleType, Severity,
+};
+
+const SOURCE: &str = r#"
+pub fn emergency_withdraw(ctx: Context<Withdraw>) -> Result<()> {
+    Ok(())
+}
+"#;
+
+fn create_rule() -> std::result::Result<Arc<dyn Rule>, RuleBuildError> {
+    RuleBuilder::new()
+        .id("emergency-function")
+        .title("Emergency Function")
+        .description("Emergency functions bypass the usual checks")
+        .severity(Severity::Low)
+        .rule_type(RuleType::Solana)
+        .dsl_query(|ast, _file_path, _span_extractor| {
+            AstQuery::new(ast)
+                .functions()
+                .filter(|node| node.name().starts_with("emergency_"))
+        })
+        .build()
+}
+
+struct TeamRules;
+
+impl RulePlugin for TeamRules {
+    fn name(&self) -> &str {
+        "team"
+    }
+
+    fn register_rules(&self, engine: &mut RuleEngine) -> Result<()> {
+        engine.add_rule(create_rule()?)?;
+        Ok(())
+    }
+}
+
+/// Rule set reusing a built-in rule ID
+struct ConflictingRules;
+
+impl RulePlugin for ConflictingRules {
+    fn name(&self) -> &str {
+        "conflicting"
+    }
+
+    fn register_rules(&self, engine: &mut RuleEngine) -> Result<()> {
+        let rule = RuleBuilder::new()
+            .id("solana-unsafe-code")
+            .title("Duplicate")
+            .description("Reuses a built-in rule ID")
+            .dsl_query(|ast, _file_path, _span_extractor| AstQuery::new(ast).functions())
+            .build()?;
+        engine.add_rule(rule)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_plugin_rules_run_with_builtin_rules() {
+    let options = AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        ..Default::default()
+    };
+    let analyzer = Analyzer::with_rule_plugins(options, &[&TeamRules]);
+    let ast = syn::parse_file(SOURCE).unwrap();
+
+    let findings = analyzer.analyze_source("programs/vault/src/lib.rs", &ast, SOURCE).unwrap();
+    let emergency: Vec<_> = findings
+        .iter()
+        .filter(|finding| finding.rule_ids().contains(&"emergency-function"))
+        .collect();
+    assert_eq!(emergency.len(), 1, "{findings:#?}");
+    assert_eq!(emergency[0].location.line, 2);
+}
+
+#[test]
+fn test_register_plugin_with_builtin_rules() {
+    let mut engine = RuleEngine::default();
+    engine.load_builtin_rules().unwrap();
+    let builtin = engine.rule_count();
+
+    engine.register_plugin(&TeamRules).unwrap();
+    assert_eq!(engine.rule_count(), builtin + 1);
+    assert!(engine.has_rule("emergency-function"));
+
+    // Rule IDs must stay unique across rule sets
+    assert!(engine.register_plugin(&ConflictingRules).is_err());
+    assert_eq!(engine.rule_count(), builtin + 1);
+}
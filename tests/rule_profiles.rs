@@ -0,0 +1,44 @@
+//! Execution profiles of the rules (`--profile-rules`)
+
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, RuleType};
+use rust_solana_analyzer::ast::parser::SourceFile;
+
+const SOURCES: [&str; 2] = [
+    "pub fn raw(value: *const u64) -> u64 {\n    unsafe { *value }\n}\n",
+    "pub fn add(a: u64, b: u64) -> u64 {\n    a + b\n}\n",
+];
+
+#[test]
+fn test_rule_profiles() {
+    let dir = std::env::temp_dir().join(format!("solana-analyzer-profiles-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let files: Vec<_> = SOURCES
+        .iter()
+        .enumerate()
+        .map(|(index, source)| {
+            let path = dir.join(format!("file_{index}.rs"));
+            std::fs::write(&path, source).unwrap();
+            SourceFile::parse(path, source.to_string()).unwrap()
+        })
+        .collect();
+
+    let analyzer = |profile_rules| {
+        Analyzer::with_options(AnalysisOptions {
+            include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+            profile_rules,
+            ..Default::default()
+        })
+    };
+
+    let stats = analyzer(true).analyze_files(&files).unwrap().stats;
+    let unsafe_code = &stats.rule_profiles["solana-unsafe-code"];
+    assert_eq!((unsafe_code.runs, unsafe_code.files_matched, unsafe_code.findings), (2, 1, 1));
+
+    // Rules that never fire are listed too
+    let owner_check = &stats.rule_profiles["owner-check"];
+    assert_eq!((owner_check.runs, owner_check.findings), (2, 0));
+
+    assert!(analyzer(false).analyze_files(&files).unwrap().stats.rule_profiles.is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,98 @@
+//! Selection of the rules to run by ID and by tag, and severity overrides
+
+use rust_solana_analyzer::analyzer::{
+    AnalysisOptions, Analyzer, Category, RuleEngine, RuleEngineConfig, RuleType, Severity,
+};
+
+const SOURCE: &str = r#"
+pub fn divide(a: u64, b: u64) -> u64 {
+    a / b
+}
+"#;
+
+/// Helper function to load the built-in rules with the given configuration
+fn load_engine(config: RuleEngineConfig) -> RuleEngine {
+    let mut engine = RuleEngine::new(config);
+    engine.load_builtin_rules().unwrap();
+    engine
+}
+
+/// Helper function to load the built-in rules having any of the given tags
+fn engine_with_tags(tags: &[&str]) -> RuleEngine {
+    load_engine(RuleEngineConfig {
+        include_tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_include_rules_and_tags() {
+    let engine = load_engine(RuleEngineConfig {
+        include_rules: vec!["owner-check".to_string()],
+        ..Default::default()
+    });
+    assert_eq!(engine.rule_count(), 1);
+    assert!(engine.has_rule("owner-check"));
+
+    let engine = engine_with_tags(&["unsafe", "arithmetic"]);
+    assert_eq!(engine.rule_count(), 2);
+    assert!(engine.has_rule("solana-unsafe-code"));
+    assert!(engine.has_rule("solana-division-by-zero"));
+
+    // The selection is combined with the ignore lists
+    let engine = load_engine(RuleEngineConfig {
+        include_tags: vec!["unsafe".to_string(), "arithmetic".to_string()],
+        ignore_rules: vec!["solana-unsafe-code".to_string()],
+        ..Default::default()
+    });
+    assert_eq!(engine.rule_count(), 1);
+}
+
+#[test]
+fn test_analyze_with_a_single_rule() {
+    let options = AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        include_rules: vec!["solana-division-by-zero".to_string()],
+        ..Default::default()
+    };
+    let analyzer = Analyzer::with_options(options);
+    let ast = syn::parse_file(SOURCE).unwrap();
+
+    let findings = analyzer.analyze_source("src/lib.rs", &ast, SOURCE).unwrap();
+    assert_eq!(findings.len(), 1, "{findings:#?}");
+    assert!(findings[0].description.contains("Division Without Zero Check"), "{}", findings[0].description);
+
+    // Findings carry the metadata of their rule
+    let rule = findings[0].rule().unwrap();
+    assert_eq!(rule.id, "solana-division-by-zero");
+    assert_eq!(rule.title, "Division Without Zero Check");
+    assert_eq!(rule.tags, ["security", "arithmetic"]);
+    assert_eq!(rule.category, Some(Category::Arithmetic));
+    assert_eq!(rule.cwe, [369]);
+    assert!(!findings[0].recommendations.is_empty());
+}
+
+#[test]
+fn test_severity_overrides() {
+    let severity_overrides = [("solana-division-by-zero".to_string(), Severity::High)].into();
+    let options = AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        include_rules: vec!["solana-division-by-zero".to_string()],
+        severity_overrides,
+        ..Default::default()
+    };
+    let analyzer = Analyzer::with_options(options.clone());
+    let ast = syn::parse_file(SOURCE).unwrap();
+
+    let findings = analyzer.analyze_source("src/lib.rs", &ast, SOURCE).unwrap();
+    assert_eq!(findings.len(), 1, "{findings:#?}");
+    assert_eq!(findings[0].severity, Severity::High);
+
+    // Ignored severities apply to the overridden severity
+    let options = AnalysisOptions {
+        ignore_severities: vec![Severity::High],
+        ..options
+    };
+    let analyzer = Analyzer::with_options(options);
+    assert!(analyzer.analyze_source("src/lib.rs", &ast, SOURCE).unwrap().is_empty());
+}
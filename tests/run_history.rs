@@ -0,0 +1,48 @@
+//! Each run is recorded in `.analyzer/history`, whose runs the trend section of the reports shows
+
+use rust_solana_analyzer::analyzer::history::{History, RunRecord, trend};
+use rust_solana_analyzer::analyzer::reporting::ReportGenerator;
+use rust_solana_analyzer::{ProjectOptions, analyze_project};
+
+const SOURCE: &str = r#"
+pub fn share(amount: u64, shares: u64) -> u64 {
+    amount / shares
+}
+"#;
+
+const FIXED: &str = r#"
+pub fn share(amount: u64, shares: u64) -> u64 {
+    amount.checked_div(shares).unwrap_or(0)
+}
+"#;
+
+#[test]
+fn test_run_history() {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-run-history-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    let history = History::new(&root);
+
+    for (timestamp, source) in [("2026-10-16T09:00:00.000Z", SOURCE), ("2026-10-17T09:00:00.000Z", FIXED)] {
+        std::fs::write(root.join("src/lib.rs"), source).unwrap();
+        let options = ProjectOptions::default().with_overrides(|options| {
+            options.cache_dir = None;
+            options.include_rules = vec!["solana-division-by-zero".to_string()];
+        });
+        let analysis = analyze_project(&root, options).unwrap();
+        history.record(&RunRecord::of(&analysis.result, &root, timestamp.to_string(), None)).unwrap();
+    }
+
+    let runs = history.load().unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0].findings[0].file, "src/lib.rs");
+    let points = trend(&runs);
+    assert_eq!(points[1].fixed.len(), 1);
+    assert!(points[1].new.is_empty());
+
+    let report = ReportGenerator::new(Vec::new(), root.display().to_string()).with_history(runs).generate_markdown_report();
+    assert!(report.contains("## Findings Over Time"), "{report}");
+    assert!(report.contains("| 2026-10-17 09:00:00 | - | 0 | 0 | 0 | 0 | 0 | 0 | 1 |"), "{report}");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
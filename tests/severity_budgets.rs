@@ -0,0 +1,43 @@
+//! `check` compares the findings of each severity with the `[budgets]` of the configuration
+
+use rust_solana_analyzer::analyzer::Severity;
+use rust_solana_analyzer::analyzer::budgets::{Budget, Budgets};
+use rust_solana_analyzer::{ProjectOptions, analyze_project};
+
+// A division (medium) and an unsafe block (high)
+const SOURCE: &str = r#"
+pub fn share(amount: u64, shares: u64) -> u64 {
+    amount / shares
+}
+
+pub fn raw(value: *const u64) -> u64 {
+    unsafe { *value }
+}
+"#;
+
+#[test]
+fn test_budgets_of_the_project() {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-budgets-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("src/lib.rs"), SOURCE).unwrap();
+    std::fs::write(root.join("solana-analyzer.toml"), "[budgets]\nhigh = 0\nmedium = 1\nlow = \"unlimited\"\n").unwrap();
+
+    let options = ProjectOptions::default().with_overrides(|options| {
+        options.cache_dir = None;
+        options.include_rules = vec!["solana-division-by-zero".to_string(), "solana-unsafe-code".to_string()];
+    });
+    let analysis = analyze_project(&root, options).unwrap();
+    let mut budgets = analysis.config.budgets().unwrap();
+    let exceeded = |budgets: &Budgets| -> Vec<Severity> {
+        budgets.check(&analysis.result).into_iter().filter(|usage| usage.exceeded()).map(|usage| usage.severity).collect()
+    };
+    assert_eq!(budgets.check(&analysis.result).len(), 3);
+    assert_eq!(exceeded(&budgets), vec![Severity::High]);
+
+    // A `--budget` override relaxes the budget
+    budgets.set(Severity::High, Budget::Limited(1));
+    assert!(exceeded(&budgets).is_empty());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,47 @@
+//! `--staged` analyzes the Rust files staged for the next commit, as they are staged
+
+use std::path::Path;
+use std::process::Command;
+
+use rust_solana_analyzer::{ProjectOptions, analyze_sources, project};
+
+fn git(dir: &Path, args: &[&str]) -> bool {
+    Command::new("git").arg("-C").arg(dir).args(args).output().is_ok_and(|output| output.status.success())
+}
+
+#[test]
+fn test_staged_files_are_analyzed_as_staged() {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-staged-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    if !git(&root, &["init", "-q"]) {
+        eprintln!("git is not available, skipping");
+        return;
+    }
+    std::fs::write(root.join("solana-analyzer.toml"), "[paths]\nexclude = [\"src/generated.rs\"]\n").unwrap();
+    std::fs::write(root.join("src/lib.rs"), "pub fn share(amount: u64, shares: u64) -> u64 {\n    amount / shares\n}\n").unwrap();
+    std::fs::write(root.join("src/generated.rs"), "pub fn raw(value: *const u64) -> u64 {\n    unsafe { *value }\n}\n").unwrap();
+    std::fs::write(root.join("src/unstaged.rs"), "pub fn raw(value: *const u64) -> u64 {\n    unsafe { *value }\n}\n").unwrap();
+    assert!(git(&root, &["add", "src/lib.rs", "src/generated.rs"]));
+    // Changed after it was staged: the staged division is analyzed
+    std::fs::write(root.join("src/lib.rs"), "pub fn share() {}\n").unwrap();
+
+    let sources = project::staged_sources(&root, &ProjectOptions::default()).unwrap();
+    let paths: Vec<_> = sources.iter().map(|file| file.path.strip_prefix(&root).unwrap().to_path_buf()).collect();
+    assert_eq!(paths, vec![Path::new("src/lib.rs")]);
+    assert!(sources[0].source.contains("amount / shares"));
+
+    let options = ProjectOptions::default().with_overrides(|options| {
+        options.cache_dir = None;
+        options.include_rules = vec!["solana-division-by-zero".to_string(), "solana-unsafe-code".to_string()];
+    });
+    let analysis = analyze_sources(&root, sources, options).unwrap();
+    let rules: Vec<_> = analysis.result.findings.iter().flat_map(|finding| finding.rule_ids()).collect();
+    assert_eq!(rules, vec!["solana-division-by-zero"]);
+
+    // Nothing staged under a directory without staged files
+    std::fs::create_dir_all(root.join("programs")).unwrap();
+    assert!(project::staged_sources(&root.join("programs"), &ProjectOptions::default()).unwrap().is_empty());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
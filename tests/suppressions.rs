@@ -0,0 +1,68 @@
+//! Findings suppressed by `analyzer:ignore` comments
+
+use rust_solana_analyzer::analyzer::suppressions::{SuppressedBy, UNUSED_SUPPRESSION_ID};
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, RuleType, Severity};
+use rust_solana_analyzer::ast::parser::SourceFile;
+
+const SOURCE: &str = r#"
+pub fn share(amount: u64, shares: u64) -> u64 {
+    amount / shares // analyzer:ignore[solana-division-by-zero] reason="shares is never zero"
+}
+
+// analyzer:ignore[solana-division-by-zero]
+pub fn ratio(a: u64, b: u64) -> u64 {
+    a.checked_div(b).unwrap_or(0)
+}
+"#;
+
+fn analyzer(ignore_rules: Vec<String>) -> Analyzer {
+    Analyzer::with_options(AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        include_rules: vec!["solana-division-by-zero".to_string()],
+        ignore_rules,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_suppressed_findings_and_unused_suppressions() {
+    let ast = syn::parse_file(SOURCE).unwrap();
+
+    let findings = analyzer(Vec::new()).analyze_source("src/lib.rs", &ast, SOURCE).unwrap();
+    assert_eq!(findings.len(), 1, "{findings:#?}");
+    assert_eq!(findings[0].severity, Severity::Informational);
+    assert_eq!(findings[0].location.line, 6);
+
+    // Unused suppressions are not reported when ignored like a rule
+    let findings = analyzer(vec![UNUSED_SUPPRESSION_ID.to_string()])
+        .analyze_source("src/lib.rs", &ast, SOURCE)
+        .unwrap();
+    assert!(findings.is_empty(), "{findings:#?}");
+}
+
+#[test]
+fn test_suppressed_and_filtered_findings_are_recorded() {
+    let file = SourceFile::parse("src/lib.rs", SOURCE.to_string()).unwrap();
+    let analyzer = Analyzer::with_options(AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        include_rules: vec!["solana-division-by-zero".to_string()],
+        ignore_severities: vec![Severity::Informational],
+        ..Default::default()
+    });
+
+    let result = analyzer.analyze_files(&[file]).unwrap();
+    assert!(result.findings.is_empty(), "{:#?}", result.findings);
+    assert_eq!(result.stats.findings_suppressed, 2);
+
+    // In the order of the locations, fingerprinted like the reported findings
+    let suppressed = &result.stats.suppressed_findings;
+    assert_eq!(suppressed.len(), 2, "{suppressed:#?}");
+    assert_eq!(suppressed[0].rule_id, "solana-division-by-zero");
+    assert_eq!(suppressed[0].suppressed_by, SuppressedBy::Comment);
+    assert_eq!(suppressed[0].reason.as_deref(), Some("shares is never zero"));
+    assert_eq!(suppressed[0].finding.location.line, 2);
+    assert_eq!(suppressed[1].rule_id, UNUSED_SUPPRESSION_ID);
+    assert_eq!(suppressed[1].suppressed_by, SuppressedBy::Severity);
+    assert_eq!(suppressed[1].reason.as_deref(), Some("Severity informational is ignored"));
+    assert!(suppressed.iter().all(|suppressed| suppressed.finding.fingerprint.is_some()));
+}
@@ -0,0 +1,95 @@
+//! Each crate of a workspace is analyzed as its own program, with its findings grouped
+
+use rust_solana_analyzer::analyzer::dsl::query::MetadataValue;
+use rust_solana_analyzer::analyzer::workspace::{OVERFLOW_CHECKS_ID, PROGRAM_KEY, Workspace};
+use rust_solana_analyzer::analyzer::{AnalysisOptions, Analyzer, RuleType};
+use rust_solana_analyzer::ast::parser::{self, SourceFile};
+use std::path::PathBuf;
+
+const SOURCE: &str = r#"
+pub fn share(amount: u64, shares: u64) -> u64 {
+    amount / shares
+}
+"#;
+
+/// Helper function to write a workspace of two programs, each with a division
+fn write_workspace(name: &str) -> (PathBuf, Vec<SourceFile>) {
+    let root = std::env::temp_dir().join(format!("solana-analyzer-workspace-{name}-{}", std::process::id()));
+    let manifest = "[workspace]\nmembers = [\"programs/*\"]\n\n[profile.release.package.vault]\noverflow-checks = true\n";
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("Cargo.toml"), manifest).unwrap();
+
+    let files = ["vault", "escrow"]
+        .iter()
+        .map(|program| {
+            let dir = root.join("programs").join(program);
+            std::fs::create_dir_all(dir.join("src")).unwrap();
+            let manifest = format!("[package]\nname = \"{program}\"\n\n[dependencies]\nsolana-program = \"1.18\"\n");
+            std::fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+            let path = dir.join("src/lib.rs");
+            std::fs::write(&path, SOURCE).unwrap();
+            SourceFile::parse(path, SOURCE.to_string()).unwrap()
+        })
+        .collect();
+    (root, files)
+}
+
+fn analyzer(workspace: Workspace, ignore_rules: Vec<String>) -> Analyzer {
+    Analyzer::with_options(AnalysisOptions {
+        include_rule_types: vec![RuleType::Solana, RuleType::Anchor, RuleType::General],
+        include_rules: vec!["solana-division-by-zero".to_string()],
+        workspace: Some(workspace),
+        ignore_rules,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_findings_are_grouped_by_program() {
+    let (root, files) = write_workspace("analysis");
+    let workspace = Workspace::load(&root).unwrap().unwrap();
+
+    let result = analyzer(workspace.clone(), Vec::new()).analyze_files(&files).unwrap();
+    // One division in each program, and the release profile of escrow without overflow checks
+    assert_eq!(result.findings.len(), 3, "{:#?}", result.findings);
+    assert_eq!(result.stats.findings_by_program.get("vault"), Some(&1));
+    assert_eq!(result.stats.findings_by_program.get("escrow"), Some(&2));
+
+    let overflow = result.findings.iter().find(|finding| finding.rule_ids().contains(&OVERFLOW_CHECKS_ID)).unwrap();
+    assert!(overflow.location.file.ends_with("Cargo.toml"));
+    assert_eq!(overflow.metadata.get(PROGRAM_KEY), Some(&MetadataValue::Text("escrow".to_string())));
+    for finding in &result.findings {
+        let program = if finding.location.file.contains("vault") { "vault" } else { "escrow" };
+        assert_eq!(finding.metadata.get(PROGRAM_KEY), Some(&MetadataValue::Text(program.to_string())));
+    }
+
+    // The manifest check is ignored like a rule
+    let result = analyzer(workspace, vec![OVERFLOW_CHECKS_ID.to_string()]).analyze_files(&files).unwrap();
+    assert_eq!(result.findings.len(), 2, "{:#?}", result.findings);
+}
+
+#[test]
+fn test_streamed_files_give_the_same_results() {
+    let (root, files) = write_workspace("streaming");
+    let workspace = Workspace::load(&root).unwrap().unwrap();
+    // Directory walks do not follow the order the files were collected in
+    let summary = |findings: &[rust_solana_analyzer::analyzer::Finding]| -> Vec<(String, usize, Option<String>)> {
+        let mut summary: Vec<_> = findings
+            .iter()
+            .map(|finding| (finding.location.file.clone(), finding.location.line, finding.fingerprint.clone()))
+            .collect();
+        summary.sort();
+        summary
+    };
+
+    let collected = analyzer(workspace.clone(), Vec::new()).analyze_files(&files).unwrap();
+    // Files parsed while the directory is walked, each AST dropped once its crate is analyzed
+    let streamed = analyzer(workspace, Vec::new())
+        .analyze_files(parser::walk_directory_excluding(&root, |_| false))
+        .unwrap();
+    assert_eq!(streamed.stats.files_analyzed, 2);
+    assert_eq!(summary(&streamed.findings), summary(&collected.findings));
+    assert_eq!(streamed.stats.findings_by_program, collected.stats.findings_by_program);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}